@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_seedling::{
+    configuration::GraphConfiguration, prelude::*, utils::profiling::ProfilingBackend,
+};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+const VOICES: usize = 64;
+
+fn prepare_app(pools: usize) -> App {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        SeedlingPlugin::<ProfilingBackend> {
+            graph_config: GraphConfiguration::Empty,
+            ..SeedlingPlugin::<ProfilingBackend>::new()
+        },
+    ));
+
+    app.finish();
+    app.cleanup();
+
+    let world = app.world_mut();
+    for i in 0..pools {
+        world.spawn((SamplerPool(BenchPool(i)), PoolSize(VOICES..=VOICES)));
+    }
+
+    let sample = world.resource::<AssetServer>().load("caw.ogg");
+    world.insert_resource(BenchSample(sample));
+
+    // wait for the pools to populate and the sample to load
+    loop {
+        app.update();
+
+        let world = app.world();
+        let sample = &world.resource::<BenchSample>().0;
+        if world.resource::<Assets<AudioSample>>().contains(sample) {
+            break;
+        }
+    }
+
+    app
+}
+
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct BenchPool(usize);
+
+#[derive(Resource)]
+struct BenchSample(Handle<AudioSample>);
+
+/// Queue a full pool's worth of samples into every pool, forcing
+/// each pool to score and steal all of its samplers.
+fn churn(app: &mut App, pools: usize) {
+    let world = app.world_mut();
+    let sample = world.resource::<BenchSample>().0.clone();
+
+    for i in 0..pools {
+        for _ in 0..VOICES {
+            world.spawn((BenchPool(i), SamplePlayer::new(sample.clone())));
+        }
+    }
+
+    app.update();
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool assignment");
+
+    for pools in [1, 4, 8] {
+        let mut app = prepare_app(pools);
+
+        group.bench_with_input(BenchmarkId::from_parameter(pools), &pools, |b, &pools| {
+            b.iter(|| churn(&mut app, pools))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
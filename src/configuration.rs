@@ -13,19 +13,28 @@
 //! initialized. Following this initialization in [`PostStartup`], any
 //! further changes to [`AudioStreamConfig`] will cause the stream to
 //! stop and restart with the new configuration.
+//!
+//! ## Simulating failures
+//!
+//! Audio devices can disappear or change out from under a running game.
+//! To exercise your game's handling of these situations without touching
+//! any hardware, you can trigger [`SimulateDeviceLoss`],
+//! [`SimulateDeviceRemoval`], or [`SimulateSampleRateChange`]. These drive
+//! the same code paths as real failures.
 
 use crate::{
     context::AudioStreamConfig,
     edge::{AudioGraphInput, AudioGraphOutput, PendingConnections},
     node::FirewheelNode,
+    pool::label::PoolLabelContainer,
 };
 use bevy_app::prelude::*;
 use bevy_asset::prelude::*;
-use bevy_ecs::prelude::*;
+use bevy_ecs::{entity::Entities, prelude::*};
 use bevy_log::prelude::*;
 use bevy_seedling_macros::{NodeLabel, PoolLabel};
 use bevy_transform::prelude::Transform;
-use core::marker::PhantomData;
+use core::{marker::PhantomData, num::NonZeroU32};
 use firewheel::backend::AudioBackend;
 
 pub(crate) struct SeedlingStartup<B: AudioBackend> {
@@ -81,10 +90,19 @@ where
         )
         .add_systems(
             Last,
-            add_default_transforms.before(crate::SeedlingSystems::Acquire),
+            (add_default_transforms, restore_io).before(crate::SeedlingSystems::Acquire),
         )
+        .init_resource::<SimulatedDevices>()
+        .init_resource::<AutoTransformConfig>()
+        .init_resource::<RemovedGraphIo>()
+        .add_observer(observe_input_removal)
+        .add_observer(observe_output_removal)
         .add_observer(fetch_io::<B>)
-        .add_observer(restart_audio);
+        .add_observer(restart_audio)
+        .add_observer(simulate_device_loss)
+        .add_observer(simulate_device_removal)
+        .add_observer(simulate_device_reconnect)
+        .add_observer(simulate_sample_rate_change);
     }
 }
 
@@ -115,10 +133,12 @@ fn fetch_io<B: AudioBackend>(
     _: On<FetchAudioIoEvent>,
     existing_inputs: Query<(Entity, &InputDeviceInfo)>,
     existing_outputs: Query<(Entity, &OutputDeviceInfo)>,
+    simulated: Res<SimulatedDevices>,
     mut commands: Commands,
 ) {
     let new_inputs = B::available_input_devices()
         .into_iter()
+        .filter(|input| !simulated.is_removed(&input.name))
         .map(|input| InputDeviceInfo {
             name: input.name,
             num_channels: input.num_channels,
@@ -153,6 +173,7 @@ fn fetch_io<B: AudioBackend>(
 
     let new_outputs = B::available_output_devices()
         .into_iter()
+        .filter(|output| !simulated.is_removed(&output.name))
         .map(|output| OutputDeviceInfo {
             name: output.name,
             num_channels: output.num_channels,
@@ -240,6 +261,138 @@ fn restart_audio(
     config.set_changed();
 }
 
+/// Attempt to recover from an audio stream that stopped unexpectedly.
+pub(crate) fn recover_stream(commands: &mut Commands) {
+    // For now, we'll assume this is always due to a device becoming unavailable.
+    // First, we'll want to make sure the devices are up-to-date.
+    commands.trigger(FetchAudioIoEvent);
+    // Then, we'll attempt a restart.
+    commands.trigger(RestartAudioEvent);
+}
+
+/// When triggered globally, this simulates the audio stream
+/// stopping unexpectedly.
+///
+/// This is handled exactly like a real stream failure: the
+/// audio I/O devices are refreshed and the stream is restarted.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::configuration::SimulateDeviceLoss;
+/// fn pull_the_plug(mut commands: Commands) {
+///     commands.trigger(SimulateDeviceLoss);
+/// }
+/// ```
+#[derive(Event, Debug)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SimulateDeviceLoss;
+
+fn simulate_device_loss(_: On<SimulateDeviceLoss>, mut commands: Commands) {
+    warn!("Audio stream stopped: simulated device loss");
+
+    recover_stream(&mut commands);
+}
+
+/// When triggered globally, this simulates the removal
+/// of the named input or output device.
+///
+/// The device is hidden from [`FetchAudioIoEvent`], despawning
+/// its [`InputDeviceInfo`] or [`OutputDeviceInfo`] entity, and
+/// the stream is stopped as if the device had been unplugged.
+/// The device remains hidden until a [`SimulateDeviceReconnect`]
+/// with the same name is triggered.
+///
+/// Note that the underlying device is still used by the backend
+/// if it's selected as the system default.
+#[derive(Event, Debug)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SimulateDeviceRemoval {
+    /// The name of the device to remove.
+    pub name: String,
+}
+
+/// When triggered globally, this reverses a [`SimulateDeviceRemoval`].
+///
+/// The audio I/O devices are refreshed, respawning the device's entity.
+#[derive(Event, Debug)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SimulateDeviceReconnect {
+    /// The name of the device to reconnect.
+    pub name: String,
+}
+
+/// Devices hidden by [`SimulateDeviceRemoval`].
+#[derive(Resource, Debug, Default)]
+struct SimulatedDevices {
+    removed: Vec<String>,
+}
+
+impl SimulatedDevices {
+    fn is_removed(&self, name: &str) -> bool {
+        self.removed.iter().any(|r| r == name)
+    }
+}
+
+fn simulate_device_removal(
+    trigger: On<SimulateDeviceRemoval>,
+    mut simulated: ResMut<SimulatedDevices>,
+    mut commands: Commands,
+) {
+    let name = &trigger.event().name;
+
+    if !simulated.is_removed(name) {
+        simulated.removed.push(name.clone());
+    }
+
+    warn!("Audio stream stopped: simulated removal of \"{name}\"");
+
+    recover_stream(&mut commands);
+}
+
+fn simulate_device_reconnect(
+    trigger: On<SimulateDeviceReconnect>,
+    mut simulated: ResMut<SimulatedDevices>,
+    mut commands: Commands,
+) {
+    let name = &trigger.event().name;
+    simulated.removed.retain(|r| r != name);
+
+    commands.trigger(FetchAudioIoEvent);
+}
+
+/// When triggered globally, this simulates the output
+/// device changing its sample rate.
+///
+/// The stream is restarted with the new rate, triggering
+/// [`PreStreamRestartEvent`] and [`StreamRestartEvent`] just
+/// as a real change would.
+///
+/// This only works with the default `cpal` backend, and the
+/// backend may fall back to another rate if the device doesn't
+/// support the requested one.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::configuration::SimulateSampleRateChange;
+/// # use core::num::NonZeroU32;
+/// fn change_rate(mut commands: Commands) {
+///     commands.trigger(SimulateSampleRateChange(NonZeroU32::new(44100).unwrap()));
+/// }
+/// ```
+///
+/// [`PreStreamRestartEvent`]: crate::context::PreStreamRestartEvent
+/// [`StreamRestartEvent`]: crate::context::StreamRestartEvent
+#[derive(Event, Debug)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SimulateSampleRateChange(pub NonZeroU32);
+
+fn simulate_sample_rate_change(
+    trigger: On<SimulateSampleRateChange>,
+    mut config: ResMut<AudioStreamConfig>,
+) {
+    config.0.output.desired_sample_rate = Some(trigger.event().0.get());
+}
+
 /// Information about an audio input device.
 #[derive(Component, Debug, PartialEq, Clone)]
 #[component(immutable)]
@@ -275,21 +428,68 @@ pub struct OutputDeviceInfo {
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct SpatialPool;
 
-/// For convenience, we automatically insert `Transform` components
-/// on sample players with `SpatialPool`.
+/// Automatically insert a default [`Transform`] on sample players queued in this pool.
+///
+/// Spatial effects only take effect when their sample player has a transform.
+/// For pools that are always spatial, it's easy to forget one, so inserting
+/// [`AutoTransform`] alongside the pool fills in a [`Transform::default`]
+/// for any sample player that lacks one.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct FootstepPool;
+///
+/// fn spawn_pool(mut commands: Commands) {
+///     commands.spawn((
+///         SamplerPool(FootstepPool),
+///         AutoTransform,
+///         sample_effects![SpatialBasicNode::default()],
+///     ));
+/// }
+/// ```
+///
+/// In [`GraphConfiguration::Game`], the [`SpatialPool`] includes this marker.
+/// To disable automatic transforms entirely, set [`AutoTransformConfig::enabled`]
+/// to `false`.
+#[derive(Component, Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct AutoTransform;
+
+/// Global configuration for [`AutoTransform`].
+#[derive(Resource, Debug, Clone, Copy)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct AutoTransformConfig {
+    /// Whether [`AutoTransform`] pools insert transforms.
+    ///
+    /// Defaults to `true`.
+    pub enabled: bool,
+}
+
+impl Default for AutoTransformConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
 fn add_default_transforms(
-    q: Query<
-        Entity,
-        (
-            With<crate::prelude::SamplePlayer>,
-            With<SpatialPool>,
-            Without<Transform>,
-        ),
+    players: Query<
+        (Entity, &PoolLabelContainer),
+        (With<crate::prelude::SamplePlayer>, Without<Transform>),
     >,
+    pools: Query<&PoolLabelContainer, (With<AutoTransform>, Without<crate::prelude::SamplePlayer>)>,
+    config: Res<AutoTransformConfig>,
     mut commands: Commands,
 ) {
-    for entity in &q {
-        commands.entity(entity).insert(Transform::default());
+    if !config.enabled || pools.is_empty() {
+        return;
+    }
+
+    for (entity, label) in &players {
+        if pools.iter().any(|pool| pool.label == label.label) {
+            commands.entity(entity).insert(Transform::default());
+        }
     }
 }
 
@@ -311,6 +511,33 @@ pub struct MusicPool;
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct SfxBus;
 
+/// A label for the output limiter following the [`MainBus`].
+///
+/// In [`GraphConfiguration::Game`], the [`MainBus`] is routed through
+/// a [`LimiterNode`] with this label before reaching the output. This
+/// makes it easy to insert mastering effects ahead of the limiter.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn add_mastering(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+///     commands
+///         .entity(*main)
+///         .disconnect(MainLimiter)
+///         .chain_node(MultibandCompressorNode::mastering())
+///         .connect(MainLimiter);
+/// }
+/// ```
+///
+/// This label is unused in all other configurations,
+/// so you can freely reuse it.
+///
+/// [`MainBus`]: crate::prelude::MainBus
+/// [`LimiterNode`]: crate::prelude::LimiterNode
+#[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MainLimiter;
+
 /// Describes the initial audio graph configuration.
 ///
 /// If you're not familiar with routing audio or are unsure what you need,
@@ -339,9 +566,9 @@ pub enum GraphConfiguration {
     /// ┌▽─────────────────────────────────────▽┐
     /// │MainBus                                │
     /// └┬──────────────────────────────────────┘
-    /// ┌▽──────┐
-    /// │Limiter│
-    /// └───────┘
+    /// ┌▽──────────┐
+    /// │MainLimiter│
+    /// └───────────┘
     /// ```
     ///
     /// Additionally, each sampler pool includes a [`VolumeNode`] effect
@@ -357,7 +584,14 @@ pub enum GraphConfiguration {
     ///     // Buses
     ///     commands
     ///         .spawn((MainBus, VolumeNode::default()))
-    ///         .chain_node(LimiterNode::new(0.003, 0.15))
+    ///         .chain_node((
+    ///             MainLimiter,
+    ///             LimiterNode::new(0.003, 0.15),
+    ///             LimiterConfig {
+    ///                 true_peak: true,
+    ///                 ..Default::default()
+    ///             },
+    ///         ))
     ///         .connect(AudioGraphOutput);
     ///
     ///     commands.spawn((SfxBus, VolumeNode::default()));
@@ -379,6 +613,7 @@ pub enum GraphConfiguration {
     ///     commands
     ///         .spawn((
     ///             SamplerPool(SpatialPool),
+    ///             AutoTransform,
     ///             sample_effects![VolumeNode::default(), SpatialBasicNode::default()],
     ///         ))
     ///         .connect(SfxBus);
@@ -485,6 +720,103 @@ fn connect_io(
     })
 }
 
+/// A graph I/O marker that was removed after startup.
+struct RemovedIo {
+    entity: Entity,
+    pending: PendingConnections,
+}
+
+/// Graph I/O markers awaiting restoration.
+///
+/// The underlying graph nodes can't be removed, so losing the marker
+/// entities would otherwise silently break any routing that relies on them.
+#[derive(Resource, Default)]
+struct RemovedGraphIo {
+    input: Option<RemovedIo>,
+    output: Option<RemovedIo>,
+}
+
+fn take_pending(entity: Entity, pending: &mut Query<&mut PendingConnections>) -> RemovedIo {
+    RemovedIo {
+        entity,
+        pending: pending
+            .get_mut(entity)
+            .map(|mut p| core::mem::take(&mut *p))
+            .unwrap_or_default(),
+    }
+}
+
+fn observe_input_removal(
+    trigger: On<Remove, AudioGraphInput>,
+    mut pending: Query<&mut PendingConnections>,
+    mut removed: ResMut<RemovedGraphIo>,
+) {
+    removed.input = Some(take_pending(trigger.event_target(), &mut pending));
+}
+
+fn observe_output_removal(
+    trigger: On<Remove, AudioGraphOutput>,
+    mut pending: Query<&mut PendingConnections>,
+    mut removed: ResMut<RemovedGraphIo>,
+) {
+    removed.output = Some(take_pending(trigger.event_target(), &mut pending));
+}
+
+fn warn_io_removal(label: &str, removed: &RemovedIo, entities: &Entities) {
+    match entities
+        .entity_get_spawned_or_despawned_by(removed.entity)
+        .into_option()
+        .flatten()
+    {
+        Some(location) => warn!(
+            "`{label}` entity {} was despawned at {location}; respawning to preserve audio routing",
+            removed.entity
+        ),
+        None => warn!(
+            "`{label}` entity {} was removed; respawning to preserve audio routing",
+            removed.entity
+        ),
+    }
+}
+
+/// Respawn the graph I/O markers if they're removed.
+fn restore_io(
+    mut removed: ResMut<RemovedGraphIo>,
+    input: Query<(), With<AudioGraphInput>>,
+    output: Query<(), With<AudioGraphOutput>>,
+    entities: &Entities,
+    mut context: ResMut<crate::prelude::AudioContext>,
+    mut commands: Commands,
+) {
+    if removed.input.is_none() && removed.output.is_none() {
+        return;
+    }
+
+    let (graph_in, graph_out) =
+        context.with(|ctx| (ctx.graph_in_node_id(), ctx.graph_out_node_id()));
+
+    // If the marker has already been replaced, we leave it be.
+    if let Some(io) = removed.input.take().filter(|_| input.is_empty()) {
+        warn_io_removal("AudioGraphInput", &io, entities);
+        commands.spawn((
+            AudioGraphInput,
+            io.pending,
+            FirewheelNode(graph_in),
+            Name::new("Audio Input Node"),
+        ));
+    }
+
+    if let Some(io) = removed.output.take().filter(|_| output.is_empty()) {
+        warn_io_removal("AudioGraphOutput", &io, entities);
+        commands.spawn((
+            AudioGraphOutput,
+            io.pending,
+            FirewheelNode(graph_out),
+            Name::new("Audio Output Node"),
+        ));
+    }
+}
+
 /// Set up the graph according to the initial configuration.
 fn set_up_graph(mut commands: Commands, config: Res<ConfigResource>) {
     use crate::prelude::*;
@@ -494,7 +826,15 @@ fn set_up_graph(mut commands: Commands, config: Res<ConfigResource>) {
             // Buses
             commands
                 .spawn((MainBus, VolumeNode::default(), Name::new("Main Bus")))
-                .chain_node(LimiterNode::new(0.003, 0.15))
+                .chain_node((
+                    MainLimiter,
+                    LimiterNode::new(0.003, 0.15),
+                    LimiterConfig {
+                        true_peak: true,
+                        ..Default::default()
+                    },
+                    Name::new("Main Limiter"),
+                ))
                 .connect(AudioGraphOutput);
 
             commands.spawn((SfxBus, VolumeNode::default(), Name::new("SFX Bus")));
@@ -519,6 +859,7 @@ fn set_up_graph(mut commands: Commands, config: Res<ConfigResource>) {
             commands
                 .spawn((
                     SamplerPool(SpatialPool),
+                    AutoTransform,
                     Name::new("Spatial Sampler Pool"),
                     sample_effects![VolumeNode::default(), SpatialBasicNode::default()],
                 ))
@@ -549,3 +890,68 @@ fn set_up_graph(mut commands: Commands, config: Res<ConfigResource>) {
 
     commands.remove_resource::<ConfigResource>();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run},
+    };
+
+    #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct AutoPool;
+
+    #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct PlainPool;
+
+    #[test]
+    fn test_auto_transform() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(AutoPool), AutoTransform));
+            commands.spawn(SamplerPool(PlainPool));
+
+            commands.spawn((
+                AutoPool,
+                SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+            ));
+            commands.spawn((
+                PlainPool,
+                SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+            ));
+        });
+
+        app.update();
+
+        run(
+            &mut app,
+            |auto: Single<Has<Transform>, (With<AutoPool>, With<SamplePlayer>)>,
+             plain: Single<Has<Transform>, (With<PlainPool>, With<SamplePlayer>)>| {
+                assert!(*auto);
+                assert!(!*plain);
+            },
+        );
+
+        run(&mut app, |mut config: ResMut<AutoTransformConfig>| {
+            config.enabled = false;
+        });
+
+        run(
+            &mut app,
+            |server: Res<AssetServer>, mut commands: Commands| {
+                commands.spawn((
+                    AutoPool,
+                    SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+                ));
+            },
+        );
+        app.update();
+
+        run(
+            &mut app,
+            |players: Query<Has<Transform>, (With<AutoPool>, With<SamplePlayer>)>| {
+                assert_eq!(players.iter().filter(|has| !*has).count(), 1);
+            },
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Gating playback behind the browser's autoplay policy.
+//!
+//! Browsers won't start audio until the user interacts with the page.
+//! Until then, the audio stream is suspended and playback silently
+//! fails. The web audio gate detects the suspended state, holds
+//! new [`SamplePlayer`]s, and resumes the stream on the first user
+//! interaction, flushing any held samples.
+//!
+//! The current state is available in the [`AudioGateState`] resource,
+//! which can drive UI like a "click to enable sound" prompt.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, context::AudioGateState};
+//! #[derive(Component)]
+//! struct EnableSoundPrompt;
+//!
+//! fn show_prompt(
+//!     state: Res<AudioGateState>,
+//!     mut prompt: Single<&mut Visibility, With<EnableSoundPrompt>>,
+//! ) {
+//!     **prompt = match *state {
+//!         AudioGateState::Suspended => Visibility::Visible,
+//!         _ => Visibility::Hidden,
+//!     };
+//! }
+//! ```
+//!
+//! [`SamplePlayer`]: crate::prelude::SamplePlayer
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use firewheel::{backend::AudioBackend, clock::InstantSamples};
+use wasm_bindgen::{JsCast, closure::Closure};
+
+use super::{AudioContext, AudioStreamConfig};
+use crate::{
+    SeedlingSystems,
+    sample::{QueuedSample, SamplePlayer},
+};
+
+/// The number of frames the audio clock can stall
+/// before the stream is considered suspended.
+const STALL_FRAMES: u32 = 10;
+
+/// Set when the user first interacts with the page.
+///
+/// Browsers treat activation as sticky, so any interaction
+/// allows the stream to be resumed later.
+static INTERACTED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) struct WebAudioGatePlugin<B>(PhantomData<fn() -> B>);
+
+impl<B> Default for WebAudioGatePlugin<B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B> Plugin for WebAudioGatePlugin<B>
+where
+    B: AudioBackend + 'static,
+    B::Config: Clone + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioGateState>()
+            .add_systems(Startup, listen_for_interaction)
+            .add_systems(
+                Last,
+                (
+                    detect_suspension,
+                    resume_on_interaction::<B>,
+                    hold_samples,
+                    release_samples,
+                )
+                    .chain()
+                    .before(SeedlingSystems::Acquire),
+            );
+    }
+}
+
+/// The state of the browser's audio stream.
+///
+/// This is only available on `wasm32` targets.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AudioGateState {
+    /// The stream's state isn't yet known.
+    ///
+    /// This is the state at startup and immediately
+    /// following an attempt to resume the stream.
+    #[default]
+    Pending,
+    /// The browser has suspended the stream, likely waiting
+    /// for the user to interact with the page.
+    ///
+    /// New samples are held until the stream resumes.
+    Suspended,
+    /// The stream is running.
+    Running,
+}
+
+/// A sample held while the stream is suspended.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct HeldSample;
+
+fn listen_for_interaction() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let callback = Closure::<dyn FnMut()>::new(|| {
+        INTERACTED.store(true, Ordering::Relaxed);
+    });
+
+    for event in ["pointerdown", "keydown", "touchend"] {
+        if let Err(e) =
+            window.add_event_listener_with_callback(event, callback.as_ref().unchecked_ref())
+        {
+            warn!("failed to listen for `{event}` events: {e:?}");
+        }
+    }
+
+    // The listeners live as long as the page.
+    callback.forget();
+}
+
+fn detect_suspension(
+    mut state: ResMut<AudioGateState>,
+    mut context: ResMut<AudioContext>,
+    mut last_clock: Local<Option<InstantSamples>>,
+    mut stalled_frames: Local<u32>,
+) {
+    let clock = context.with(|c| c.audio_clock().samples);
+
+    if last_clock.is_some_and(|last| last != clock) {
+        *stalled_frames = 0;
+        state.set_if_neq(AudioGateState::Running);
+    } else {
+        *stalled_frames += 1;
+
+        if *stalled_frames >= STALL_FRAMES {
+            state.set_if_neq(AudioGateState::Suspended);
+        }
+    }
+
+    *last_clock = Some(clock);
+}
+
+fn resume_on_interaction<B>(
+    mut state: ResMut<AudioGateState>,
+    mut config: ResMut<AudioStreamConfig<B>>,
+) where
+    B: AudioBackend + 'static,
+    B::Config: Clone + Send + Sync + 'static,
+{
+    if *state != AudioGateState::Suspended || !INTERACTED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    debug!("resuming suspended audio stream");
+
+    // This restarts the stream, giving the browser a chance
+    // to start it now that the user has interacted.
+    config.set_changed();
+    *state = AudioGateState::Pending;
+}
+
+fn hold_samples(
+    state: Res<AudioGateState>,
+    samples: Query<Entity, (With<SamplePlayer>, With<QueuedSample>)>,
+    mut commands: Commands,
+) {
+    if *state != AudioGateState::Suspended {
+        return;
+    }
+
+    for sample in &samples {
+        commands
+            .entity(sample)
+            .remove::<QueuedSample>()
+            .insert(HeldSample);
+    }
+}
+
+fn release_samples(
+    state: Res<AudioGateState>,
+    samples: Query<Entity, With<HeldSample>>,
+    mut commands: Commands,
+) {
+    if *state != AudioGateState::Running {
+        return;
+    }
+
+    for sample in &samples {
+        commands
+            .entity(sample)
+            .remove::<HeldSample>()
+            .insert(QueuedSample);
+    }
+}
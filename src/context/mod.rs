@@ -16,6 +16,14 @@ mod os;
 #[cfg(not(target_arch = "wasm32"))]
 use os::InnerContext;
 
+#[cfg(target_arch = "wasm32")]
+mod gate;
+#[cfg(target_arch = "wasm32")]
+pub use gate::AudioGateState;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use gate::WebAudioGatePlugin;
+
+pub mod offline;
 mod seedling_context;
 
 pub use seedling_context::{SeedlingContext, SeedlingContextError, SeedlingContextWrapper};
@@ -148,7 +156,10 @@ where
 
     commands.insert_resource(context);
     commands.insert_resource(sample_rate.clone());
-    server.register_loader(crate::sample::SampleLoader { sample_rate });
+    server.register_loader(crate::sample::SampleLoader {
+        sample_rate,
+        server: server.clone(),
+    });
 
     commands.trigger(StreamStartEvent {
         sample_rate: raw_sample_rate,
@@ -0,0 +1,399 @@
+//! Offline, faster-than-realtime rendering.
+//!
+//! The [`OfflineBackend`] doesn't talk to any audio device. Instead,
+//! the audio graph is processed on demand, with the output collected
+//! into a buffer that can be inspected or written to a WAV file.
+//! This is useful for bouncing procedurally mixed music or for
+//! golden-file tests of effect chains.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, context::offline::*};
+//! let renderer = OfflineRenderer::default();
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     MinimalPlugins,
+//!     AssetPlugin::default(),
+//!     SeedlingPlugin::<OfflineBackend> {
+//!         stream_config: OfflineConfig::new(renderer.clone()),
+//!         ..SeedlingPlugin::<OfflineBackend>::new()
+//!     },
+//!     // Render a tenth of a second each update, regardless of wall time.
+//!     OfflinePlugin::new(DurationSeconds(0.1)),
+//! ));
+//!
+//! // Bounce ten seconds of audio.
+//! while renderer.rendered() < DurationSeconds(10.0) {
+//!     app.update();
+//! }
+//!
+//! renderer.write_wav("bounce.wav").unwrap();
+//! ```
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::TimeSystems;
+use firewheel::{
+    StreamInfo,
+    backend::{AudioBackend, BackendProcessInfo, DeviceInfo},
+    clock::DurationSeconds,
+    node::StreamStatus,
+    processor::FirewheelProcessor,
+};
+use std::{
+    io::Write,
+    num::{NonZeroU32, NonZeroUsize},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct RendererState {
+    processor: Option<FirewheelProcessor<OfflineBackend>>,
+    sample_rate: u32,
+    channels: usize,
+    block_frames: usize,
+    frames_rendered: u64,
+    output: Vec<f32>,
+}
+
+/// A handle for rendering the [`OfflineBackend`]'s audio.
+///
+/// Clones share the same underlying renderer.
+#[derive(Clone, Default)]
+pub struct OfflineRenderer(Arc<Mutex<RendererState>>);
+
+impl core::fmt::Debug for OfflineRenderer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OfflineRenderer").finish_non_exhaustive()
+    }
+}
+
+impl OfflineRenderer {
+    /// Render `duration` worth of audio, appending it to the output.
+    ///
+    /// The duration is rounded to the nearest frame. If the
+    /// stream hasn't started yet, this does nothing.
+    pub fn render(&self, duration: DurationSeconds) {
+        let mut state = self.0.lock().unwrap();
+        let frames = (duration.0.max(0.0) * state.sample_rate as f64).round() as usize;
+        state.render_frames(frames);
+    }
+
+    /// The total duration rendered so far.
+    pub fn rendered(&self) -> DurationSeconds {
+        let state = self.0.lock().unwrap();
+        if state.sample_rate == 0 {
+            return DurationSeconds(0.0);
+        }
+
+        DurationSeconds(state.frames_rendered as f64 / state.sample_rate as f64)
+    }
+
+    /// The stream's sample rate, or `None` if it hasn't started.
+    pub fn sample_rate(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.0.lock().unwrap().sample_rate)
+    }
+
+    /// The number of output channels.
+    pub fn channels(&self) -> usize {
+        self.0.lock().unwrap().channels
+    }
+
+    /// Take the rendered output, leaving the buffer empty.
+    ///
+    /// Samples are interleaved according to [`OfflineRenderer::channels`].
+    pub fn take_output(&self) -> Vec<f32> {
+        core::mem::take(&mut self.0.lock().unwrap().output)
+    }
+
+    /// Write the rendered output to a 32-bit float WAV file.
+    ///
+    /// This does not clear the output buffer.
+    pub fn write_wav(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        self.encode_wav(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Encode the rendered output as a 32-bit float WAV file.
+    ///
+    /// This does not clear the output buffer.
+    pub fn encode_wav(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let state = self.0.lock().unwrap();
+
+        crate::utils::wav::write_header(
+            writer,
+            state.sample_rate,
+            state.channels as u16,
+            state.output.len(),
+        )?;
+        crate::utils::wav::write_samples(writer, &state.output)
+    }
+}
+
+impl RendererState {
+    fn render_frames(&mut self, mut frames: usize) {
+        if self.processor.is_none() || self.block_frames == 0 {
+            return;
+        }
+
+        let channels = self.channels;
+        let block_frames = self.block_frames;
+        let start = Instant::now();
+
+        while frames > 0 {
+            let block = frames.min(block_frames);
+            let offset = self.output.len();
+            self.output.resize(offset + block * channels, 0.0);
+
+            let duration_since_stream_start =
+                Duration::from_secs_f64(self.frames_rendered as f64 / self.sample_rate as f64);
+
+            if let Some(processor) = &mut self.processor {
+                processor.process_interleaved(
+                    &[],
+                    &mut self.output[offset..],
+                    BackendProcessInfo {
+                        num_in_channels: 0,
+                        num_out_channels: channels,
+                        frames: block,
+                        process_timestamp: start,
+                        duration_since_stream_start,
+                        input_stream_status: StreamStatus::empty(),
+                        output_stream_status: StreamStatus::empty(),
+                        dropped_frames: 0,
+                    },
+                );
+            }
+
+            self.frames_rendered += block as u64;
+            frames -= block;
+        }
+    }
+}
+
+/// [`OfflineBackend`]'s configuration.
+#[derive(Debug, Clone)]
+pub struct OfflineConfig {
+    /// The renderer that drives the stream.
+    pub renderer: OfflineRenderer,
+    /// The sample rate.
+    ///
+    /// Defaults to 48kHz.
+    pub sample_rate: NonZeroU32,
+    /// The number of output channels.
+    ///
+    /// Defaults to stereo.
+    pub channels: NonZeroUsize,
+    /// The maximum number of frames processed at once.
+    ///
+    /// Defaults to 512.
+    pub block_frames: NonZeroU32,
+}
+
+impl OfflineConfig {
+    /// Create a new configuration driven by `renderer`.
+    pub fn new(renderer: OfflineRenderer) -> Self {
+        Self {
+            renderer,
+            sample_rate: NonZeroU32::new(48000).unwrap(),
+            channels: NonZeroUsize::new(2).unwrap(),
+            block_frames: NonZeroU32::new(512).unwrap(),
+        }
+    }
+}
+
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        Self::new(OfflineRenderer::default())
+    }
+}
+
+/// A backend that renders on demand rather than in realtime.
+///
+/// See the [module docs][self] for usage.
+pub struct OfflineBackend {
+    renderer: OfflineRenderer,
+}
+
+impl core::fmt::Debug for OfflineBackend {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OfflineBackend").finish_non_exhaustive()
+    }
+}
+
+impl Drop for OfflineBackend {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.renderer.0.lock() {
+            state.processor = None;
+        }
+    }
+}
+
+/// An error produced by the [`OfflineBackend`].
+///
+/// The offline backend can't actually fail.
+#[derive(Debug)]
+pub struct OfflineError;
+
+impl core::fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("offline backend error")
+    }
+}
+
+impl core::error::Error for OfflineError {}
+
+impl AudioBackend for OfflineBackend {
+    type Config = OfflineConfig;
+    type Instant = Instant;
+
+    type StartStreamError = OfflineError;
+    type StreamError = OfflineError;
+
+    fn available_input_devices() -> Vec<DeviceInfo> {
+        vec![]
+    }
+
+    fn available_output_devices() -> Vec<DeviceInfo> {
+        vec![DeviceInfo {
+            name: "offline output".into(),
+            num_channels: 2,
+            is_default: true,
+        }]
+    }
+
+    fn delay_from_last_process(&self, _: Self::Instant) -> Option<Duration> {
+        // Time only moves when rendering.
+        Some(Duration::ZERO)
+    }
+
+    fn start_stream(config: Self::Config) -> Result<(Self, StreamInfo), Self::StartStreamError> {
+        {
+            let mut state = config.renderer.0.lock().unwrap();
+            state.sample_rate = config.sample_rate.get();
+            state.channels = config.channels.get();
+            state.block_frames = config.block_frames.get() as usize;
+        }
+
+        Ok((
+            Self {
+                renderer: config.renderer,
+            },
+            StreamInfo {
+                prev_sample_rate: config.sample_rate,
+                sample_rate: config.sample_rate,
+                sample_rate_recip: 1.0 / config.sample_rate.get() as f64,
+                max_block_frames: config.block_frames,
+                num_stream_in_channels: 0,
+                num_stream_out_channels: config.channels.get() as u32,
+                declick_frames: NonZeroU32::new(16).unwrap(),
+                input_device_name: None,
+                output_device_name: Some("offline output".into()),
+                input_to_output_latency_seconds: 0.0,
+            },
+        ))
+    }
+
+    fn set_processor(&mut self, processor: FirewheelProcessor<Self>) {
+        self.renderer.0.lock().unwrap().processor = Some(processor);
+    }
+
+    fn poll_status(&mut self) -> Result<(), Self::StreamError> {
+        Ok(())
+    }
+}
+
+/// Render a fixed amount of audio every update.
+///
+/// The audio is rendered at the start of each frame, before
+/// [`Time<Audio>`][crate::time::Audio] is updated, so the audio clock advances
+/// by exactly `per_update` every frame regardless of wall time.
+#[derive(Debug, Clone)]
+pub struct OfflinePlugin {
+    per_update: DurationSeconds,
+}
+
+impl OfflinePlugin {
+    /// Render `per_update` worth of audio every update.
+    pub fn new(per_update: DurationSeconds) -> Self {
+        Self { per_update }
+    }
+}
+
+#[derive(Resource)]
+struct OfflineDriver {
+    renderer: Option<OfflineRenderer>,
+    per_update: DurationSeconds,
+}
+
+impl Plugin for OfflinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OfflineDriver {
+            renderer: None,
+            per_update: self.per_update,
+        })
+        .add_systems(First, drive_offline.before(TimeSystems));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let renderer = app
+            .world()
+            .get_resource::<super::AudioStreamConfig<OfflineBackend>>()
+            .map(|config| config.0.renderer.clone());
+
+        app.world_mut().resource_mut::<OfflineDriver>().renderer = renderer;
+    }
+}
+
+fn drive_offline(driver: Res<OfflineDriver>) {
+    if let Some(renderer) = &driver.renderer {
+        renderer.render(driver.per_update);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_offline_render() {
+        let renderer = OfflineRenderer::default();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            SeedlingPlugin::<OfflineBackend> {
+                stream_config: OfflineConfig::new(renderer.clone()),
+                graph_config: crate::configuration::GraphConfiguration::Empty,
+                ..SeedlingPlugin::<OfflineBackend>::new()
+            },
+            OfflinePlugin::new(DurationSeconds(0.01)),
+        ));
+        app.finish();
+        app.cleanup();
+
+        // let the stream start up
+        app.update();
+        app.update();
+
+        let before = renderer.rendered();
+        renderer.take_output();
+        app.update();
+
+        assert!((renderer.rendered().0 - before.0 - 0.01).abs() < 1e-9);
+        assert_eq!(renderer.take_output().len(), 480 * 2);
+
+        let mut wav = Vec::new();
+        renderer.encode_wav(&mut wav).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44);
+    }
+}
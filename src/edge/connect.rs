@@ -1,4 +1,4 @@
-use super::{DEFAULT_CONNECTION, EdgeTarget, NodeMap, PendingEdge};
+use super::{DEFAULT_CONNECTION, EdgeTarget, NodeMap, PendingEdge, ports::validate_ports};
 use crate::{context::AudioContext, node::FirewheelNode};
 use bevy_ecs::prelude::*;
 use bevy_log::prelude::*;
@@ -19,6 +19,16 @@ impl PendingConnections {
     pub fn push(&mut self, connection: PendingEdge) {
         self.0.push(connection)
     }
+
+    /// Returns `true` if there are no pending connections.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Take all pending connections.
+    pub(crate) fn drain(&mut self) -> Vec<PendingEdge> {
+        core::mem::take(&mut self.0)
+    }
 }
 
 /// An [`EntityCommands`] extension trait for connecting Firewheel nodes.
@@ -167,6 +177,19 @@ pub trait Connect<'a>: Sized {
 
     /// Queue a connection from this entity to the target with the provided port mappings.
     ///
+    /// The ports are a slice of `(output, input)` pairs. A [`PortMap`][super::PortMap]
+    /// dereferences to such a slice, so it can be passed by reference.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::{prelude::*, edge::PortMap};
+    /// # fn system(mut commands: Commands) {
+    /// commands
+    ///     .spawn(VolumeNode::default())
+    ///     .connect_with(MainBus, &PortMap::swap_lr());
+    /// # }
+    /// ```
+    ///
     /// The connection is deferred, finalizing in the
     /// [`SeedlingSystems::Connect`][crate::SeedlingSystems::Connect] set.
     #[cfg_attr(debug_assertions, track_caller)]
@@ -437,6 +460,32 @@ pub(crate) fn process_connections(
                     }
                 };
 
+                let channels = context
+                    .node_info(source_node.0)
+                    .zip(context.node_info(target.0))
+                    .map(|(source, target)| {
+                        (
+                            source.info.channel_config.num_outputs.get(),
+                            target.info.channel_config.num_inputs.get(),
+                        )
+                    });
+
+                let validation = channels
+                    .map(|(outputs, inputs)| validate_ports(ports, outputs, inputs))
+                    .unwrap_or(Ok(()));
+
+                if let Err(e) = validation {
+                    #[cfg(debug_assertions)]
+                    {
+                        let location = connection.origin;
+                        error_once!("invalid port mapping for connection to entity `{target_entity:?}` at {location}: {e}");
+                    }
+                    #[cfg(not(debug_assertions))]
+                    error_once!("invalid port mapping for connection to entity `{target_entity:?}`: {e}");
+
+                    return false;
+                }
+
                 if let Err(e) = context.connect(source_node.0, target.0, ports, false) {
                     error_once!("failed to connect audio node to target: {e}");
                 }
@@ -567,4 +616,53 @@ mod test {
             )
             .unwrap();
     }
+
+    #[test]
+    fn test_output_respawn() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+        });
+
+        app.world_mut()
+            .run_system_once(
+                |output: Single<Entity, With<AudioGraphOutput>>, mut commands: Commands| {
+                    commands.entity(*output).despawn();
+                },
+            )
+            .unwrap();
+        app.update();
+
+        app.world_mut()
+            .run_system_once(|mut commands: Commands| {
+                commands
+                    .spawn((VolumeNode::default(), One))
+                    .connect(AudioGraphOutput);
+            })
+            .unwrap();
+        app.update();
+
+        app.world_mut()
+            .run_system_once(
+                |mut context: ResMut<AudioContext>,
+                 output: Single<&FirewheelNode, With<AudioGraphOutput>>,
+                 main: Single<&FirewheelNode, With<MainBus>>,
+                 one: Single<&FirewheelNode, With<One>>| {
+                    context.with(|context| {
+                        assert_eq!(output.0, context.graph_out_node_id());
+
+                        let edges = context.edges();
+                        for source in [main.0, one.0] {
+                            assert!(
+                                edges
+                                    .iter()
+                                    .any(|e| e.src_node == source && e.dst_node == output.0)
+                            );
+                        }
+                    });
+                },
+            )
+            .unwrap();
+    }
 }
@@ -91,6 +91,9 @@ pub trait Disconnect: Sized {
 
     /// Queue a disconnection from this entity to the target with the provided port mappings.
     ///
+    /// The ports are a slice of `(output, input)` pairs. A
+    /// [`PortMap`][super::PortMap] can be passed by reference.
+    ///
     /// The disconnection is deferred, finalizing in the
     /// [`SeedlingSystems::Connect`][crate::SeedlingSystems::Connect] set.
     #[cfg_attr(debug_assertions, track_caller)]
@@ -13,9 +13,11 @@ use core::panic::Location;
 #[allow(clippy::module_inception)]
 mod connect;
 mod disconnect;
+mod ports;
 
 pub use connect::*;
 pub use disconnect::*;
+pub use ports::{PortMap, PortMapError};
 
 /// A node label for Firewheel's audio graph input.
 ///
@@ -140,7 +142,7 @@ impl From<Entity> for EdgeTarget {
     }
 }
 
-const DEFAULT_CONNECTION: &[(u32, u32)] = &[(0, 0), (1, 1)];
+pub(crate) const DEFAULT_CONNECTION: &[(u32, u32)] = &[(0, 0), (1, 1)];
 
 /// A map that associates [`NodeLabel`]s with audio
 /// graph nodes.
@@ -0,0 +1,238 @@
+use smallvec::SmallVec;
+
+/// A typed port mapping for node connections.
+///
+/// Each pair represents a single edge, where the first element is the
+/// source's output port and the second is the destination's input port.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, edge::PortMap};
+/// # fn system(mut commands: Commands) {
+/// let bus = commands.spawn(VolumeNode::default()).id();
+///
+/// // Route a mono source to both stereo channels.
+/// commands
+///     .spawn(VolumeNode::default())
+///     .connect_with(bus, &PortMap::mono_to_stereo());
+///
+/// // Raw port slices work just as well.
+/// commands
+///     .spawn(VolumeNode::default())
+///     .connect_with(bus, &[(0, 1), (1, 0)]);
+/// # }
+/// ```
+///
+/// When a connection is made, the mapping is validated against the
+/// channel counts of both nodes, reporting any out-of-range ports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortMap(SmallVec<[(u32, u32); 2]>);
+
+impl PortMap {
+    /// Create an empty port mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a connection from the source's `output` to the destination's `input`.
+    pub fn with(mut self, output: u32, input: u32) -> Self {
+        self.0.push((output, input));
+        self
+    }
+
+    /// A simple stereo connection.
+    ///
+    /// This is `[(0, 0), (1, 1)]`, the default mapping
+    /// for [`Connect::connect`][super::Connect::connect].
+    pub fn stereo() -> Self {
+        Self::channels(2)
+    }
+
+    /// A single mono connection.
+    ///
+    /// This is `[(0, 0)]`.
+    pub fn mono() -> Self {
+        Self::channels(1)
+    }
+
+    /// Connect a mono output to both stereo inputs.
+    ///
+    /// This is `[(0, 0), (0, 1)]`.
+    pub fn mono_to_stereo() -> Self {
+        Self::split(2)
+    }
+
+    /// Swap the left and right channels.
+    ///
+    /// This is `[(0, 1), (1, 0)]`.
+    pub fn swap_lr() -> Self {
+        Self::new().with(0, 1).with(1, 0)
+    }
+
+    /// Connect the first `n` channels one-to-one.
+    ///
+    /// `channels(3)` produces `[(0, 0), (1, 1), (2, 2)]`.
+    pub fn channels(n: u32) -> Self {
+        Self((0..n).map(|i| (i, i)).collect())
+    }
+
+    /// Split a single output across `n` inputs.
+    ///
+    /// `split(3)` produces `[(0, 0), (0, 1), (0, 2)]`.
+    pub fn split(n: u32) -> Self {
+        Self((0..n).map(|i| (0, i)).collect())
+    }
+
+    /// Return the mapping as a slice of `(output, input)` pairs.
+    pub fn as_slice(&self) -> &[(u32, u32)] {
+        &self.0
+    }
+
+    /// Validate this mapping against the source's output
+    /// count and the destination's input count.
+    pub fn validate(&self, outputs: u32, inputs: u32) -> Result<(), PortMapError> {
+        validate_ports(&self.0, outputs, inputs)
+    }
+}
+
+/// Validate a raw port mapping against the source's output
+/// count and the destination's input count.
+pub(crate) fn validate_ports(
+    ports: &[(u32, u32)],
+    outputs: u32,
+    inputs: u32,
+) -> Result<(), PortMapError> {
+    for (i, &(output, input)) in ports.iter().enumerate() {
+        if output >= outputs {
+            return Err(PortMapError::OutputOutOfRange {
+                port: output,
+                outputs,
+            });
+        }
+
+        if input >= inputs {
+            return Err(PortMapError::InputOutOfRange {
+                port: input,
+                inputs,
+            });
+        }
+
+        if ports[..i].contains(&(output, input)) {
+            return Err(PortMapError::Duplicate { output, input });
+        }
+    }
+
+    Ok(())
+}
+
+impl core::ops::Deref for PortMap {
+    type Target = [(u32, u32)];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl From<&[(u32, u32)]> for PortMap {
+    fn from(value: &[(u32, u32)]) -> Self {
+        Self(value.into())
+    }
+}
+
+impl<const N: usize> From<&[(u32, u32); N]> for PortMap {
+    fn from(value: &[(u32, u32); N]) -> Self {
+        Self(value.as_slice().into())
+    }
+}
+
+impl<const N: usize> From<[(u32, u32); N]> for PortMap {
+    fn from(value: [(u32, u32); N]) -> Self {
+        Self(value.as_slice().into())
+    }
+}
+
+impl From<Vec<(u32, u32)>> for PortMap {
+    fn from(value: Vec<(u32, u32)>) -> Self {
+        Self(value.into())
+    }
+}
+
+/// An invalid [`PortMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortMapError {
+    /// An output port exceeds the source's output count.
+    OutputOutOfRange {
+        /// The offending port.
+        port: u32,
+        /// The source's total outputs.
+        outputs: u32,
+    },
+    /// An input port exceeds the destination's input count.
+    InputOutOfRange {
+        /// The offending port.
+        port: u32,
+        /// The destination's total inputs.
+        inputs: u32,
+    },
+    /// The same pair of ports appears more than once.
+    Duplicate {
+        /// The output port.
+        output: u32,
+        /// The input port.
+        input: u32,
+    },
+}
+
+impl core::fmt::Display for PortMapError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutputOutOfRange { port, outputs } => {
+                write!(
+                    f,
+                    "output port {port} is out of range for a source with {outputs} output(s)"
+                )
+            }
+            Self::InputOutOfRange { port, inputs } => {
+                write!(
+                    f,
+                    "input port {port} is out of range for a destination with {inputs} input(s)"
+                )
+            }
+            Self::Duplicate { output, input } => {
+                write!(f, "port pair ({output}, {input}) appears more than once")
+            }
+        }
+    }
+}
+
+impl core::error::Error for PortMapError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        assert!(PortMap::stereo().validate(2, 2).is_ok());
+        assert!(PortMap::mono_to_stereo().validate(1, 2).is_ok());
+
+        assert_eq!(
+            PortMap::stereo().validate(1, 2),
+            Err(PortMapError::OutputOutOfRange {
+                port: 1,
+                outputs: 1
+            })
+        );
+        assert_eq!(
+            PortMap::split(3).validate(1, 2),
+            Err(PortMapError::InputOutOfRange { port: 2, inputs: 2 })
+        );
+        assert_eq!(
+            PortMap::mono().with(0, 0).validate(1, 1),
+            Err(PortMapError::Duplicate {
+                output: 0,
+                input: 0
+            })
+        );
+    }
+}
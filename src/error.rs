@@ -32,6 +32,27 @@ pub enum SeedlingError {
         /// an effect.
         empty_entity: Entity,
     },
+    /// An [`AutomationClip`][crate::node::automation::AutomationClip] was
+    /// scheduled onto a different type than it was recorded from.
+    AutomationMismatch {
+        /// The type the clip was recorded from.
+        expected: &'static str,
+        /// The type the clip was scheduled onto.
+        found: &'static str,
+    },
+    /// A [`SamplePlayer`][crate::prelude::SamplePlayer] was assigned
+    /// a sampler before its sample finished loading.
+    SampleNotLoaded {
+        /// The sample player entity.
+        sample: Entity,
+    },
+    /// An audio node failed a [dry run][crate::node::validate::dry_run].
+    InvalidNode {
+        /// The node's type name.
+        ty: &'static str,
+        /// What went wrong.
+        reason: String,
+    },
 }
 
 impl core::fmt::Display for SeedlingError {
@@ -46,6 +67,18 @@ impl core::fmt::Display for SeedlingError {
             Self::MissingEffect { .. } => {
                 write!(f, "Expected audio node in `SampleEffects` relationship")
             }
+            Self::AutomationMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Automation clip recorded from `{expected}` cannot be scheduled onto `{found}`"
+                )
+            }
+            Self::SampleNotLoaded { .. } => {
+                write!(f, "Cannot assign a sampler before the sample has loaded")
+            }
+            Self::InvalidNode { ty, reason } => {
+                write!(f, "Audio node `{ty}` failed validation: {reason}")
+            }
         }
     }
 }
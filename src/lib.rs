@@ -155,13 +155,26 @@
 //! | `mkv`           | Enable mkv format.                         | No      |
 //! | `adpcm`         | Enable adpcm encoding.                     | No      |
 //! | `flac`          | Enable FLAC format and encoding.           | No      |
+//! | `opus`          | Enable Ogg Opus decoding via libopus.      | No      |
+//! | `tracker`       | Enable MOD playback with `TrackerNode`.    | No      |
+//! | `soundfont`     | Enable MIDI playback with `SoundFontNode`. | No      |
+//! | `http`          | Enable [HTTP audio streaming].             | No      |
+//! | `sound_events`  | Enable [data-driven sound events].         | No      |
 //! | `web_audio`     | Enable the multi-threading web backend.    | No      |
 //! | `hrtf`          | Enable HRTF Spatialization.                | No      |
 //! | `hrtf_subjects` | Enable all HRTF embedded data.             | No      |
 //! | `loudness`      | Enable LUFS analyzer node.                 | Yes     |
 //! | `stream`        | Enable CPAL input and output stream nodes. | Yes     |
+//! | `midi_clock`    | Enable [MIDI clock output].                | No      |
+//! | `avian3d`       | Enable [Avian 3D velocity] integration.    | No      |
+//! | `sanitize`      | Enable [main bus sanitizing].              | No      |
 //!
 //! [`RandomPitch`]: crate::prelude::RandomPitch
+//! [MIDI clock output]: crate::time::midi_clock
+//! [Avian 3D velocity]: crate::spatial::VelocitySource
+//! [main bus sanitizing]: crate::nodes::sanitizer
+//! [HTTP audio streaming]: crate::nodes::http
+//! [data-driven sound events]: crate::prelude::SoundEvent
 //!
 //! ## Frequently asked questions
 //!
@@ -339,7 +352,10 @@
 //!
 //! In `bevy_seedling`, _sample_ primarily refers to a piece of recorded sound,
 //! like an audio file. Samples aren't limited to audio files, however; anything
-//! implementing [`SampleResource`] can work with [`AudioSample`].
+//! implementing [`SampleResource`] can work with [`AudioSample`]. Custom
+//! sources can be played without an asset loader through
+//! [`AudioSample::from_resource`], and buffers synthesized at runtime
+//! through [`AudioSample::from_frames`].
 //!
 //! Note that "sample" can also refer to the individual amplitude measurements
 //! that make up a sound. "Sample rate," often 44.1kHz or 48kHz, refers to these
@@ -347,6 +363,8 @@
 //!
 //! [`SampleResource`]: firewheel::core::sample_resource::SampleResource
 //! [`AudioSample`]: prelude::AudioSample
+//! [`AudioSample::from_resource`]: prelude::AudioSample::from_resource
+//! [`AudioSample::from_frames`]: prelude::AudioSample::from_frames
 
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![allow(clippy::type_complexity)]
@@ -370,6 +388,8 @@ pub mod configuration;
 pub mod context;
 pub mod edge;
 pub mod error;
+#[cfg(feature = "loudness")]
+pub mod mastering;
 pub mod node;
 pub mod nodes;
 pub mod pool;
@@ -382,38 +402,94 @@ pub mod prelude {
     //! All `bevy_seedlings`'s important types and traits.
 
     pub use crate::configuration::{
-        GraphConfiguration, InputDeviceInfo, MusicPool, OutputDeviceInfo, SeedlingStartupSystems,
-        SfxBus, SpatialPool,
+        AutoTransform, AutoTransformConfig, GraphConfiguration, InputDeviceInfo, MainLimiter,
+        MusicPool, OutputDeviceInfo, SeedlingStartupSystems, SfxBus, SpatialPool,
     };
     pub use crate::context::AudioContext;
     pub use crate::edge::{AudioGraphInput, AudioGraphOutput, Connect, Disconnect, EdgeTarget};
     pub use crate::node::{
-        FirewheelNode, RegisterNode,
+        DespawnWithTail, FirewheelNode, RegisterNode,
+        domain::{AudioDomain, AudioDomainPlugin},
         events::{AudioEvents, VolumeFade},
         label::{MainBus, NodeLabel},
+        library::{AddNodeLibrary, SeedlingNodeLibrary},
+        mute::{Mute, Solo},
     };
+    #[cfg(feature = "http")]
+    pub use crate::nodes::http::{HttpSource, HttpStreamConfig, HttpStreamNode, HttpStreamState};
     #[cfg(feature = "loudness")]
     pub use crate::nodes::loudness::{LoudnessConfig, LoudnessNode, LoudnessState};
+    #[cfg(feature = "soundfont")]
+    pub use crate::nodes::soundfont::{
+        MidiFile, MidiSource, SoundFontFile, SoundFontNode, SoundFontSource, SoundFontState,
+    };
+    #[cfg(feature = "tracker")]
+    pub use crate::nodes::tracker::{TrackerModule, TrackerNode, TrackerSource, TrackerState};
     pub use crate::nodes::{
+        auto_eq::{AutoEqDuckConfig, AutoEqDuckNode, BandRange},
         bpf::{BandPassConfig, BandPassNode},
+        bsf::{BandStopConfig, BandStopNode},
+        chorus::ChorusNode,
+        compressor::{CompressorConfig, CompressorNode, CompressorState},
+        convolution::{ConvolutionConfig, ConvolutionNode, ImpulseResponse},
+        delay::{DelayConfig, DelayNode, DelayTime},
+        envelope::{EnvelopeConfig, EnvelopeFollowerNode, EnvelopeState},
+        flanger::FlangerNode,
         freeverb::FreeverbNode,
+        gate::{GateConfig, GateNode, GateState},
+        hpf::{HighPassConfig, HighPassNode},
         itd::{ItdConfig, ItdNode},
         limiter::{LimiterConfig, LimiterNode},
         lpf::{LowPassConfig, LowPassNode},
+        meter::{MeterConfig, MeterNode, MeterState},
+        mid_side::{MidSideToStereoNode, StereoToMidSideNode},
+        multiband::{
+            CompressorBand, MultibandCompressorConfig, MultibandCompressorNode,
+            MultibandCompressorState,
+        },
+        noise::{NoiseColor, NoiseConfig, NoiseNode},
+        oscillator::{OscillatorConfig, OscillatorNode, Waveform},
+        phaser::PhaserNode,
+        recorder::{
+            MicrophoneRecorder, MicrophoneRecordingFinished, RecorderConfig, RecorderNode,
+            RecorderState, Recording,
+        },
+        sanitizer::{Sanitize, SanitizerConfig, SanitizerNode, SanitizerState},
+        saturation::{SaturationConfig, SaturationCurve, SaturationNode},
         send::{SendConfig, SendNode},
+        shelf::{HighShelfNode, LowShelfNode, ShelfConfig},
+        spectrum::{SpectrumConfig, SpectrumNode, SpectrumSnapshot, SpectrumState},
+        split::{MergerNode, SplitConfig, SplitterNode},
+        tremolo::{TremoloNode, TremoloRate},
+        width::StereoWidthNode,
     };
     pub use crate::pool::{
-        DefaultPoolSize, PlaybackCompletionEvent, PoolCommands, PoolDespawn, PoolSize, SamplerPool,
-        dynamic::DynamicBus,
+        DefaultPoolSize, NoSampleRetention, NoStealing, PlaybackCompletionEvent,
+        PlaybackTimeoutEvent, PoolCommands, PoolDespawn, PoolFullEvent, PoolPause, PoolPaused,
+        PoolSize, SampleReloadPolicy, SampleUnloadedEvent, SamplerPool, StealingPolicy,
+        dynamic::{
+            DynamicBus, DynamicPoolConfig, DynamicPoolCreated, DynamicPoolRetired, DynamicRouting,
+        },
+        growth::{DefaultPoolGrowth, DefaultPoolShrink, PoolGrowth, PoolShrink, WarmPool},
         label::{DefaultPool, PoolLabel},
+        overflow::OverflowTo,
+        presets::SamplerPresets,
+        resume::ResumeWindow,
         sample_effects::{EffectOf, EffectsQuery, SampleEffects},
+        tiers::{AudioQualityTier, SkipBelow},
     };
     pub use crate::sample::{
-        AudioSample, OnComplete, PlaybackSettings, SamplePlayer, SamplePriority,
+        AddSampleFrames, AudioForState, AudioPreloadSet, AudioSample, FirstAvailable, Intensity,
+        IntensityCurve, LoopCrossfade, LoopRegion, MaxInstances, MaxPlaybackDuration, OnComplete,
+        PlaybackRegion, PlaybackSettings, PreloadAudioState, PrewarmAudio, RegisterStateAudio,
+        SampleAssets, SampleCacheBudget, SampleCooldown, SampleMarker, SampleMarkerEvent,
+        SamplePlayer, SamplePriority, StealOldestInstance, ToneHighpass, ToneLowpass,
+        VariantChosenEvent,
     };
     pub use crate::sample_effects;
     pub use crate::spatial::{
-        DefaultSpatialScale, SpatialListener2D, SpatialListener3D, SpatialScale,
+        DefaultSpatialScale, Doppler, EmitterVelocity, ListenerLocal, SpatialListener2D,
+        SpatialListener3D, SpatialReverbSend, SpatialScale,
     };
     pub use crate::time::{Audio, AudioTime};
     pub use crate::utils::perceptual_volume::PerceptualVolume;
@@ -449,7 +525,10 @@ pub mod prelude {
     pub use firewheel_ircam_hrtf::{self as hrtf, HrtfConfig, HrtfNode};
 
     #[cfg(feature = "rand")]
-    pub use crate::sample::RandomPitch;
+    pub use crate::sample::{RandomInterval, RandomPitch, RandomSample};
+
+    #[cfg(feature = "sound_events")]
+    pub use crate::sample::{RegisterSoundPool, SoundEvent, SoundEventPlayer};
 }
 
 /// Sets for all `bevy_seedling` systems.
@@ -467,6 +546,25 @@ pub enum SeedlingSystems {
     Pool,
     /// Queue audio engine events.
     Queue,
+    /// All audio engine events have been queued, but not yet sent.
+    ///
+    /// Systems in this set can inspect or modify the final
+    /// [`AudioEvents`][prelude::AudioEvents] queues, for example
+    /// to filter events or collect metrics.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// fn count_events(events: Query<&AudioEvents>) {
+    ///     let total: usize = events.iter().map(|e| e.queued().len()).sum();
+    ///     debug!("sending {total} audio events");
+    /// }
+    ///
+    /// fn plugin(app: &mut App) {
+    ///     app.add_systems(Last, count_events.in_set(SeedlingSystems::PreFlush));
+    /// }
+    /// ```
+    PreFlush,
     /// The audio context is updated and flushed.
     Flush,
 }
@@ -586,6 +684,10 @@ where
             .init_resource::<node::AudioScheduleLookahead>()
             .init_resource::<node::PendingRemovals>()
             .init_resource::<pool::DefaultPoolSize>()
+            .init_resource::<sample::SampleFormats>()
+            .init_resource::<pool::growth::DefaultPoolGrowth>()
+            .init_resource::<pool::growth::DefaultPoolShrink>()
+            .init_resource::<pool::tiers::AudioQualityTier>()
             .init_asset::<sample::AudioSample>()
             .register_node::<VolumeNode>()
             .register_node::<VolumePanNode>()
@@ -598,7 +700,8 @@ where
                 SeedlingSystems::Connect.after(SeedlingSystems::Acquire),
                 SeedlingSystems::Pool.after(SeedlingSystems::Connect),
                 SeedlingSystems::Queue.after(SeedlingSystems::Pool),
-                SeedlingSystems::Flush.after(SeedlingSystems::Queue),
+                SeedlingSystems::PreFlush.after(SeedlingSystems::Queue),
+                SeedlingSystems::Flush.after(SeedlingSystems::PreFlush),
             ),
         )
         .add_systems(
@@ -621,19 +724,45 @@ where
         )
         .add_observer(node::label::NodeLabels::on_add_observer)
         .add_observer(node::label::NodeLabels::on_replace_observer)
-        .add_observer(sample::observe_player_insert);
+        .add_observer(sample::observe_player_insert)
+        .add_observer(node::disabled::mute_disabled_nodes)
+        .add_observer(node::disabled::restore_enabled_nodes);
 
         app.add_plugins((
             configuration::SeedlingStartup::<B>::new(self.config),
             pool::SamplePoolPlugin,
             nodes::SeedlingNodesPlugin,
             node::events::EventsPlugin,
+            node::automation::AutomationPlugin,
+            node::quality::QualityPlugin,
+            node::gain::GainStagePlugin,
+            node::mute::MutePlugin,
             spatial::SpatialPlugin,
             time::TimePlugin,
-            #[cfg(feature = "rand")]
-            sample::RandomPlugin,
+            utils::trace::TracePlugin,
+            (
+                sample::IntensityPlugin,
+                sample::FirstAvailablePlugin,
+                sample::TonePlugin,
+                sample::LoopCrossfadePlugin,
+                sample::LoopPointsPlugin,
+                sample::MarkerPlugin,
+                sample::PlaybackRegionPlugin,
+                sample::PcmPlugin,
+                sample::PreloadPlugin,
+                sample::SampleBudgetPlugin,
+                #[cfg(feature = "rand")]
+                sample::RandomPlugin,
+                #[cfg(feature = "sound_events")]
+                sample::SoundEventPlugin,
+            ),
+            #[cfg(feature = "loudness")]
+            mastering::MasteringPlugin,
         ));
 
+        #[cfg(target_arch = "wasm32")]
+        app.add_plugins(context::WebAudioGatePlugin::<B>::default());
+
         #[cfg(feature = "stream")]
         app.register_simple_node::<StreamReaderNode>()
             .register_simple_node::<StreamWriterNode>();
@@ -650,7 +779,12 @@ where
             .register_type::<HrtfConfig>();
 
         #[cfg(all(feature = "reflect", feature = "rand"))]
-        app.register_type::<RandomPitch>();
+        app.register_type::<RandomPitch>()
+            .register_type::<RandomInterval>()
+            .register_type::<RandomSample>();
+
+        #[cfg(all(feature = "reflect", feature = "loudness"))]
+        app.register_type::<mastering::LoudnessTarget>();
 
         #[cfg(feature = "reflect")]
         app.register_type::<FirewheelNode>()
@@ -658,44 +792,149 @@ where
             .register_type::<SamplePriority>()
             .register_type::<PlaybackSettings>()
             .register_type::<sample::SampleQueueLifetime>()
+            .register_type::<MaxPlaybackDuration>()
+            .register_type::<sample::MaxInstances>()
+            .register_type::<sample::StealOldestInstance>()
+            .register_type::<sample::SampleCooldown>()
             .register_type::<OnComplete>()
+            .register_type::<Intensity>()
+            .register_type::<FirstAvailable>()
+            .register_type::<IntensityCurve>()
+            .register_type::<LoopCrossfade>()
+            .register_type::<LoopRegion>()
+            .register_type::<PlaybackRegion>()
+            .register_type::<SampleCacheBudget>()
+            .register_type::<ToneLowpass>()
+            .register_type::<ToneHighpass>()
+            .register_type::<sample::IntensityVariant>()
             .register_type::<SpatialScale>()
             .register_type::<DefaultSpatialScale>()
             .register_type::<SpatialListener2D>()
             .register_type::<SpatialListener3D>()
+            .register_type::<spatial::ListenerLocal>()
+            .register_type::<spatial::SpatialReverbSend>()
+            .register_type::<EmitterVelocity>()
+            .register_type::<Doppler>()
             .register_type::<InputDeviceInfo>()
             .register_type::<OutputDeviceInfo>()
             .register_type::<firewheel::node::NodeID>()
             .register_type::<node::follower::FollowerOf>()
+            .register_type::<node::smooth::SmoothParams>()
             .register_type::<SendNode>()
             .register_type::<LowPassNode>()
             .register_type::<LowPassConfig>()
+            .register_type::<HighPassNode>()
+            .register_type::<HighPassConfig>()
             .register_type::<BandPassConfig>()
+            .register_type::<BandStopConfig>()
+            .register_type::<LowShelfNode>()
+            .register_type::<HighShelfNode>()
+            .register_type::<ShelfConfig>()
             .register_type::<LimiterNode>()
             .register_type::<LimiterConfig>()
             .register_type::<ItdNode>()
             .register_type::<ItdConfig>()
+            .register_type::<GateNode>()
+            .register_type::<GateConfig>()
+            .register_type::<CompressorNode>()
+            .register_type::<CompressorConfig>()
+            .register_type::<MeterNode>()
+            .register_type::<MeterConfig>()
+            .register_type::<EnvelopeFollowerNode>()
+            .register_type::<EnvelopeConfig>()
+            .register_type::<MultibandCompressorNode>()
+            .register_type::<MultibandCompressorConfig>()
+            .register_type::<CompressorBand>()
+            .register_type::<nodes::multiband::BandCount>()
+            .register_type::<RecorderNode>()
+            .register_type::<RecorderConfig>()
+            .register_type::<SanitizerNode>()
+            .register_type::<SanitizerConfig>()
+            .register_type::<Sanitize>()
+            .register_type::<SpectrumNode>()
+            .register_type::<SpectrumConfig>()
+            .register_type::<SpectrumSnapshot>()
+            .register_type::<AutoEqDuckNode>()
+            .register_type::<AutoEqDuckConfig>()
+            .register_type::<BandRange>()
             .register_type::<LimiterConfig>()
             .register_type::<FreeverbNode>()
+            .register_type::<ConvolutionNode>()
+            .register_type::<ConvolutionConfig>()
+            .register_type::<ChorusNode>()
+            .register_type::<FlangerNode>()
+            .register_type::<PhaserNode>()
+            .register_type::<SaturationNode>()
+            .register_type::<SaturationConfig>()
+            .register_type::<SaturationCurve>()
+            .register_type::<TremoloNode>()
+            .register_type::<TremoloRate>()
+            .register_type::<StereoWidthNode>()
+            .register_type::<SplitterNode>()
+            .register_type::<MergerNode>()
+            .register_type::<SplitConfig>()
+            .register_type::<StereoToMidSideNode>()
+            .register_type::<MidSideToStereoNode>()
+            .register_type::<OscillatorNode>()
+            .register_type::<OscillatorConfig>()
+            .register_type::<Waveform>()
+            .register_type::<NoiseNode>()
+            .register_type::<NoiseConfig>()
+            .register_type::<NoiseColor>()
+            .register_type::<DelayNode>()
+            .register_type::<DelayConfig>()
+            .register_type::<DelayTime>()
+            .register_type::<node::mute::Mute>()
+            .register_type::<node::mute::Solo>()
+            .register_type::<node::quality::DspQuality>()
+            .register_type::<node::quality::ScaleQuality>()
+            .register_type::<node::quality::DspLoad>()
+            .register_type::<node::quality::DspLoadGovernor>()
             .register_type::<Volume>()
             .register_type::<firewheel::dsp::pan_law::PanLaw>()
             .register_type::<MainBus>()
             .register_type::<PoolSize>()
+            .register_type::<pool::custom::ManualAssignment>()
             .register_type::<DefaultPoolSize>()
             .register_type::<PlaybackCompletionEvent>()
+            .register_type::<NoStealing>()
+            .register_type::<StealingPolicy>()
+            .register_type::<PoolPaused>()
+            .register_type::<pool::stats::PoolStats>()
+            .register_type::<PoolFullEvent>()
+            .register_type::<NoSampleRetention>()
+            .register_type::<SampleUnloadedEvent>()
+            .register_type::<SampleReloadPolicy>()
+            .register_type::<PlaybackTimeoutEvent>()
             .register_type::<DefaultPool>()
             .register_type::<SamplerPool<DefaultPool>>()
             .register_type::<DynamicBus>()
+            .register_type::<pool::dynamic::DynamicPoolCreated>()
+            .register_type::<pool::dynamic::DynamicPoolRetired>()
+            .register_type::<pool::growth::WarmPool>()
+            .register_type::<pool::growth::PoolShrink>()
+            .register_type::<pool::growth::DefaultPoolShrink>()
+            .register_type::<pool::resume::ResumeWindow>()
+            .register_type::<pool::tiers::AudioQualityTier>()
+            .register_type::<pool::tiers::SkipBelow>()
             .register_type::<configuration::FetchAudioIoEvent>()
             .register_type::<configuration::RestartAudioEvent>()
+            .register_type::<configuration::SimulateDeviceLoss>()
+            .register_type::<configuration::SimulateDeviceRemoval>()
+            .register_type::<configuration::SimulateDeviceReconnect>()
+            .register_type::<configuration::SimulateSampleRateChange>()
             .register_type::<configuration::SfxBus>()
+            .register_type::<configuration::MainLimiter>()
             .register_type::<configuration::GraphConfiguration>()
             .register_type::<configuration::MusicPool>()
             .register_type::<SamplerPool<configuration::MusicPool>>()
             .register_type::<configuration::SpatialPool>()
+            .register_type::<configuration::AutoTransform>()
+            .register_type::<configuration::AutoTransformConfig>()
             .register_type::<SamplerPool<configuration::SpatialPool>>()
             .register_type::<node::ScheduleDiffing>()
             .register_type::<node::AudioScheduleLookahead>()
+            .register_type::<node::DespawnWithTail>()
             .register_type::<NonZeroChannelCount>()
             .register_type::<SamplerConfig>()
             .register_type::<PlaybackState>()
@@ -714,6 +953,10 @@ where
             .register_type::<VolumeNodeConfig>()
             .register_type::<VolumePanNode>();
     }
+
+    fn finish(&self, app: &mut App) {
+        node::library::build_libraries(app);
+    }
 }
 
 #[cfg(test)]
@@ -731,7 +974,9 @@ mod test {
                 graph_config: crate::configuration::GraphConfiguration::Empty,
                 ..SeedlingPlugin::<crate::utils::profiling::ProfilingBackend>::new()
             },
+            crate::utils::profiling::VirtualClock::new(DurationSeconds(1.0 / 60.0)),
             TransformPlugin,
+            bevy::state::app::StatesPlugin,
         ))
         .add_systems(Startup, startup);
 
@@ -746,4 +991,32 @@ mod test {
         let world = app.world_mut();
         world.run_system_once(system).unwrap()
     }
+
+    /// The most updates [`run_until`] will wait for.
+    const MAX_UPDATES: usize = 1000;
+
+    /// Update the app until `condition` returns `true`.
+    ///
+    /// Since tests run on a virtual clock, waiting here only lets
+    /// background work like asset loading catch up in realtime.
+    ///
+    /// Panics if the condition never holds.
+    pub fn run_until<F, M>(app: &mut App, condition: F)
+    where
+        F: IntoSystem<(), bool, M> + 'static,
+    {
+        let condition = app.world_mut().register_system(condition);
+
+        for _ in 0..MAX_UPDATES {
+            if app.world_mut().run_system(condition).unwrap() {
+                app.world_mut().unregister_system(condition).unwrap();
+                return;
+            }
+
+            app.update();
+            std::thread::sleep(core::time::Duration::from_millis(1));
+        }
+
+        panic!("condition never held after {MAX_UPDATES} updates");
+    }
 }
@@ -0,0 +1,215 @@
+//! Automatic loudness normalization.
+//!
+//! Platforms often publish loudness requirements, like -16 LUFS for
+//! mobile or -23 LUFS for console. Rather than mastering your game's
+//! mix for each platform, you can insert a [`LoudnessTarget`] on a bus,
+//! and `bevy_seedling` will continuously trim the bus's volume until
+//! its measured loudness settles on the target.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, mastering::LoudnessTarget};
+//! fn normalize_output(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+//!     let target = if cfg!(any(target_os = "android", target_os = "ios")) {
+//!         LoudnessTarget::MOBILE
+//!     } else {
+//!         LoudnessTarget::CONSOLE
+//!     };
+//!
+//!     commands.entity(*main).insert(target);
+//! }
+//! ```
+//!
+//! The bus's loudness is measured with a [`LoudnessNode`], and the trim
+//! is applied by a dedicated gain stage after the bus rather than to the
+//! bus's own [`VolumeNode`], so you can still adjust the volume freely.
+//! Removing the [`LoudnessTarget`] removes the trim.
+//!
+//! [`VolumeNode`]: firewheel::nodes::volume::VolumeNode
+//!
+//! This requires the `loudness` feature.
+
+use crate::{
+    SeedlingSystems,
+    edge::Connect,
+    node::{AudioState, gain::GainStage},
+    nodes::loudness::{LoudnessConfig, LoudnessNode, LoudnessState},
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::{Real, Time};
+
+pub(crate) struct MasteringPlugin;
+
+impl Plugin for MasteringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            (attach_meters, trim_loudness)
+                .chain()
+                .before(SeedlingSystems::Acquire),
+        )
+        .add_observer(remove_trim);
+    }
+}
+
+/// Trim a bus towards a target loudness.
+///
+/// See the [module docs][self] for usage.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[require(GainStage)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct LoudnessTarget {
+    /// The target loudness in LUFS.
+    pub lufs: f64,
+    /// The largest boost that may be applied, in decibels.
+    ///
+    /// By default, this is 6 dB.
+    pub max_boost: f64,
+    /// The largest cut that may be applied, in decibels.
+    ///
+    /// By default, this is 12 dB.
+    pub max_cut: f64,
+    /// The time constant of the trim, in seconds.
+    ///
+    /// Slow responses ride the overall level of the mix without
+    /// flattening its dynamics. By default, this is 10 seconds.
+    pub response: f32,
+    /// The loudness below which measurements are ignored, in LUFS.
+    ///
+    /// This keeps quiet passages and silence from pumping the
+    /// trim up. By default, this is -50 LUFS.
+    pub gate: f64,
+}
+
+impl LoudnessTarget {
+    /// A common target for mobile platforms, -16 LUFS.
+    pub const MOBILE: Self = Self::new(-16.0);
+
+    /// A common target for consoles and television, -23 LUFS.
+    pub const CONSOLE: Self = Self::new(-23.0);
+
+    /// Create a new [`LoudnessTarget`] with the default limits and response.
+    pub const fn new(lufs: f64) -> Self {
+        Self {
+            lufs,
+            max_boost: 6.0,
+            max_cut: 12.0,
+            response: 10.0,
+            gate: -50.0,
+        }
+    }
+
+    /// Calculate the trim, in decibels, that brings a bus to the target.
+    ///
+    /// `measured` is the bus's current loudness, which already
+    /// includes the `applied` trim.
+    pub fn trim(&self, measured: f64, applied: f64) -> f64 {
+        let untrimmed = measured - applied;
+
+        (self.lufs - untrimmed).clamp(-self.max_cut.abs(), self.max_boost.abs())
+    }
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self::CONSOLE
+    }
+}
+
+/// The meter and trim state for a [`LoudnessTarget`].
+#[derive(Debug, Component)]
+struct LoudnessTrim {
+    meter: Entity,
+    /// The trim currently applied to the bus's gain stage, in decibels.
+    applied: f64,
+}
+
+fn attach_meters(
+    targets: Query<Entity, (With<LoudnessTarget>, Without<LoudnessTrim>)>,
+    mut commands: Commands,
+) {
+    for target in &targets {
+        let meter = commands
+            .spawn((
+                LoudnessNode::default(),
+                LoudnessConfig {
+                    ignore_silence: true,
+                    ..Default::default()
+                },
+            ))
+            .id();
+
+        commands.entity(target).insert(LoudnessTrim {
+            meter,
+            applied: 0.0,
+        });
+        commands.entity(target).connect(meter);
+    }
+}
+
+fn trim_loudness(
+    mut targets: Query<(&LoudnessTarget, &mut LoudnessTrim, &mut GainStage)>,
+    meters: Query<&AudioState<LoudnessState>>,
+    time: Res<Time<Real>>,
+) {
+    for (target, mut trim, mut stage) in targets.iter_mut() {
+        let Ok(state) = meters.get(trim.meter) else {
+            continue;
+        };
+
+        // The short-term loudness follows the mix closely enough
+        // to react within the response time.
+        let measured = state.0.short_term();
+        if !measured.is_finite() || measured < target.gate {
+            continue;
+        }
+
+        let desired = target.trim(measured, trim.applied);
+        let smoothing = 1.0 - (-time.delta_secs() / target.response.max(f32::EPSILON)).exp();
+        let applied = trim.applied + (desired - trim.applied) * smoothing as f64;
+
+        // Skipping inaudible changes avoids sending a patch every frame.
+        if (applied - trim.applied).abs() < 0.01 {
+            continue;
+        }
+
+        stage.set_trim(applied as f32);
+        trim.applied = applied;
+    }
+}
+
+fn remove_trim(
+    trigger: On<Remove, LoudnessTarget>,
+    mut targets: Query<(&LoudnessTrim, &mut GainStage)>,
+    mut commands: Commands,
+) {
+    let target = trigger.event_target();
+    let Ok((trim, mut stage)) = targets.get_mut(target) else {
+        return;
+    };
+
+    stage.set_trim(0.0);
+
+    commands.entity(trim.meter).despawn();
+    commands.entity(target).remove::<LoudnessTrim>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trim() {
+        let target = LoudnessTarget::new(-16.0);
+
+        assert_eq!(target.trim(-20.0, 0.0), 4.0);
+        // the measurement already includes the applied trim
+        assert_eq!(target.trim(-16.0, 4.0), 4.0);
+        assert_eq!(target.trim(-10.0, 0.0), -6.0);
+
+        // trims are limited
+        assert_eq!(target.trim(-40.0, 0.0), target.max_boost);
+        assert_eq!(target.trim(0.0, 0.0), -target.max_cut);
+    }
+}
@@ -0,0 +1,144 @@
+//! Mirroring the currently audible value of parameters.
+
+use bevy_ecs::prelude::*;
+use firewheel::{
+    diff::{Diff, EventQueue, Patch, PathBuilder},
+    event::NodeEventType,
+};
+
+use crate::time::{Audio, AudioTime};
+
+use super::events::AudioEvents;
+
+/// A mirror of an entity's `T` as it's currently heard.
+///
+/// When events are scheduled on a node, like a volume fade, its
+/// component may not reflect what's coming out of the speakers
+/// at any given moment. [`AudibleValue`] is updated every frame
+/// by evaluating the node's scheduled events at the current audio
+/// time, making it ideal for UI like animated volume sliders or
+/// filter sweep visualizations.
+///
+/// Mirroring is supported for all nodes registered with
+/// [`RegisterNode::register_node`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::audible::AudibleValue};
+/// fn spawn_filter(mut commands: Commands) {
+///     commands.spawn((
+///         LowPassNode::default(),
+///         AudibleValue::<LowPassNode>::new(),
+///     ));
+/// }
+///
+/// fn display_cutoff(filter: Single<&AudibleValue<LowPassNode>>) {
+///     if let Some(filter) = filter.get() {
+///         info!("cutoff: {}", filter.frequency);
+///     }
+/// }
+/// ```
+///
+/// [`RegisterNode::register_node`]: crate::prelude::RegisterNode::register_node
+#[derive(Debug, Component)]
+pub struct AudibleValue<T>(Option<T>);
+
+impl<T> Default for AudibleValue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AudibleValue<T> {
+    /// Begin mirroring the audible value.
+    ///
+    /// The value is first available after
+    /// the entity's events are processed.
+    pub fn new() -> Self {
+        Self(None)
+    }
+
+    /// Get the currently audible value.
+    ///
+    /// Returns `None` if the value hasn't yet been evaluated.
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+/// Tracks whether diffing produced any changes.
+struct ChangeQueue(bool);
+
+impl EventQueue for ChangeQueue {
+    fn push(&mut self, _: NodeEventType) {
+        self.0 = true;
+    }
+}
+
+pub(crate) fn update_audible_values<T: Diff + Patch + Component + Clone>(
+    mut values: Query<(&T, &AudioEvents, &mut AudibleValue<T>)>,
+    time: Res<bevy_time::Time<Audio>>,
+) {
+    let now = time.now();
+
+    for (value, events, mut audible) in values.iter_mut() {
+        let new_value = events.get_value_at(now, value);
+
+        let changed = match audible.0.as_ref() {
+            Some(current) => {
+                let mut queue = ChangeQueue(false);
+                new_value.diff(current, PathBuilder::default(), &mut queue);
+                queue.0
+            }
+            None => true,
+        };
+
+        // Avoid triggering change detection when nothing moved.
+        if changed {
+            audible.0 = Some(new_value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_audible_value() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((
+                LowPassNode { frequency: 1000.0 },
+                AudibleValue::<LowPassNode>::new(),
+            ));
+        });
+
+        app.update();
+
+        run(&mut app, |filter: Single<&AudibleValue<LowPassNode>>| {
+            assert_eq!(filter.get().unwrap().frequency, 1000.0);
+        });
+
+        // a change scheduled in the future isn't yet audible
+        run(
+            &mut app,
+            |filter: Single<(&LowPassNode, &mut AudioEvents)>, time: Res<Time<Audio>>| {
+                let (filter, mut events) = filter.into_inner();
+                events.schedule(time.delay(DurationSeconds(10.0)), filter, |filter| {
+                    filter.frequency = 500.0;
+                });
+            },
+        );
+
+        app.update();
+
+        run(&mut app, |filter: Single<&AudibleValue<LowPassNode>>| {
+            assert_eq!(filter.get().unwrap().frequency, 1000.0);
+        });
+    }
+}
@@ -0,0 +1,294 @@
+//! Recording and replaying parameter automation.
+
+use bevy_app::prelude::*;
+use bevy_asset::{Asset, AssetApp};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypePath;
+use core::any::TypeId;
+use firewheel::{
+    clock::{DurationSeconds, InstantSeconds},
+    diff::{Diff, EventQueue, ParamPath, PathBuilder},
+    event::{NodeEventType, ParamData},
+};
+
+use crate::{
+    SeedlingSystems,
+    error::SeedlingError,
+    prelude::PlaybackSettings,
+    time::{Audio, AudioTime},
+};
+
+use super::{DiffTimestamp, events::AudioEvents};
+
+pub(crate) struct AutomationPlugin;
+
+impl Plugin for AutomationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AutomationClip>().add_systems(
+            Last,
+            record_automation::<PlaybackSettings>
+                .after(SeedlingSystems::Pool)
+                .before(SeedlingSystems::Queue),
+        );
+    }
+}
+
+/// A single recorded parameter change.
+#[derive(Debug, Clone)]
+struct AutomationEvent {
+    offset: DurationSeconds,
+    data: ParamData,
+    path: ParamPath,
+}
+
+/// A recorded sequence of parameter changes.
+///
+/// Clips are produced by [`RecordAutomation`] and can be replayed
+/// through the scheduling system with [`AutomationClip::schedule`].
+/// Each change is stored relative to the beginning of the recording,
+/// so a clip can be replayed at any point on the audio clock.
+///
+/// Since the recorded changes are Firewheel patches, a clip can only be
+/// replayed onto the same type it was recorded from.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct AutomationClip {
+    ty: TypeId,
+    ty_name: &'static str,
+    events: Vec<AutomationEvent>,
+}
+
+impl AutomationClip {
+    fn new<T: 'static>() -> Self {
+        Self {
+            ty: TypeId::of::<T>(),
+            ty_name: core::any::type_name::<T>(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The total duration of the clip.
+    ///
+    /// This is the offset of the final recorded change.
+    pub fn duration(&self) -> DurationSeconds {
+        self.events
+            .last()
+            .map(|e| e.offset)
+            .unwrap_or(DurationSeconds(0.0))
+    }
+
+    /// Returns the number of recorded changes.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no changes were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Schedule the clip's changes, starting at `start`.
+    ///
+    /// `T` must be the type the clip was recorded from.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::{prelude::*, node::automation::AutomationClip};
+    /// fn replay(
+    ///     mut filter: Single<&mut AudioEvents, With<LowPassNode>>,
+    ///     clip: Res<MyClip>,
+    ///     clips: Res<Assets<AutomationClip>>,
+    ///     time: Res<Time<Audio>>,
+    /// ) -> Result {
+    ///     if let Some(clip) = clips.get(&clip.0) {
+    ///         clip.schedule::<LowPassNode>(time.delay(DurationSeconds(1.0)), &mut filter)?;
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// # #[derive(Resource)]
+    /// # struct MyClip(Handle<AutomationClip>);
+    /// ```
+    pub fn schedule<T: 'static>(
+        &self,
+        start: InstantSeconds,
+        events: &mut AudioEvents,
+    ) -> Result<(), SeedlingError> {
+        if self.ty != TypeId::of::<T>() {
+            return Err(SeedlingError::AutomationMismatch {
+                expected: self.ty_name,
+                found: core::any::type_name::<T>(),
+            });
+        }
+
+        events.schedule_params(
+            self.events
+                .iter()
+                .map(|e| (e.data.clone(), e.path.clone(), start + e.offset)),
+        );
+
+        Ok(())
+    }
+}
+
+/// Record changes to an entity's `T` into an [`AutomationClip`].
+///
+/// Every frame the value changes, the difference from the previous
+/// frame is recorded with an audio clock timestamp. This includes
+/// changes produced by scheduled events.
+///
+/// Recording is supported for all nodes registered with
+/// [`RegisterNode::register_node`] and for [`PlaybackSettings`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::automation::*};
+/// fn start_recording(filter: Single<Entity, With<LowPassNode>>, mut commands: Commands) {
+///     commands
+///         .entity(*filter)
+///         .insert(RecordAutomation::<LowPassNode>::new());
+/// }
+///
+/// fn stop_recording(
+///     mut filter: Single<&mut RecordAutomation<LowPassNode>>,
+///     mut clips: ResMut<Assets<AutomationClip>>,
+/// ) -> Handle<AutomationClip> {
+///     clips.add(filter.take_clip())
+/// }
+/// ```
+///
+/// [`RegisterNode::register_node`]: crate::prelude::RegisterNode::register_node
+#[derive(Debug, Component)]
+pub struct RecordAutomation<T> {
+    start: Option<InstantSeconds>,
+    previous: Option<T>,
+    clip: AutomationClip,
+}
+
+impl<T: 'static> Default for RecordAutomation<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> RecordAutomation<T> {
+    /// Begin a new recording.
+    ///
+    /// The recording starts on the first frame the recorder
+    /// observes the entity.
+    pub fn new() -> Self {
+        Self {
+            start: None,
+            previous: None,
+            clip: AutomationClip::new::<T>(),
+        }
+    }
+
+    /// Get the clip recorded so far.
+    pub fn clip(&self) -> &AutomationClip {
+        &self.clip
+    }
+
+    /// Take the clip recorded so far, restarting the recording.
+    pub fn take_clip(&mut self) -> AutomationClip {
+        self.start = None;
+        core::mem::replace(&mut self.clip, AutomationClip::new::<T>())
+    }
+}
+
+/// Collects diffed parameters for a single frame.
+struct RecordQueue<'a> {
+    events: &'a mut Vec<AutomationEvent>,
+    offset: DurationSeconds,
+}
+
+impl EventQueue for RecordQueue<'_> {
+    fn push(&mut self, data: NodeEventType) {
+        if let NodeEventType::Param { data, path } = data {
+            self.events.push(AutomationEvent {
+                offset: self.offset,
+                data,
+                path,
+            });
+        }
+    }
+}
+
+pub(crate) fn record_automation<T: Diff + Component + Clone>(
+    mut recorders: Query<(Ref<T>, &mut RecordAutomation<T>, Option<&DiffTimestamp>)>,
+    time: Res<bevy_time::Time<Audio>>,
+) {
+    for (value, mut recorder, timestamp) in recorders.iter_mut() {
+        let now = timestamp.map(|t| t.0).unwrap_or(time.now());
+        let recorder = recorder.as_mut();
+        let start = *recorder.start.get_or_insert(now);
+
+        let Some(previous) = recorder.previous.as_mut() else {
+            recorder.previous = Some(value.clone());
+            continue;
+        };
+
+        if !value.is_changed() {
+            continue;
+        }
+
+        let mut queue = RecordQueue {
+            events: &mut recorder.clip.events,
+            offset: DurationSeconds((now.0 - start.0).max(0.0)),
+        };
+        value.diff(previous, PathBuilder::default(), &mut queue);
+
+        *previous = value.clone();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((
+                LowPassNode { frequency: 1000.0 },
+                RecordAutomation::<LowPassNode>::new(),
+            ));
+        });
+
+        // the first frame only captures the initial value
+        app.update();
+
+        run(&mut app, |mut filter: Single<&mut LowPassNode>| {
+            filter.frequency = 500.0;
+        });
+        app.update();
+
+        let clip = run(
+            &mut app,
+            |mut recorder: Single<&mut RecordAutomation<LowPassNode>>| recorder.take_clip(),
+        );
+        assert_eq!(clip.len(), 1);
+
+        run(
+            &mut app,
+            move |mut events: Single<&mut AudioEvents, With<LowPassNode>>,
+                  time: Res<Time<Audio>>| {
+                assert!(
+                    clip.schedule::<VolumeNode>(time.now(), &mut events)
+                        .is_err()
+                );
+
+                let start = time.now();
+                clip.schedule::<LowPassNode>(start, &mut events).unwrap();
+
+                let value = events
+                    .get_value_at(start + clip.duration(), &LowPassNode { frequency: 1000.0 });
+                assert_eq!(value.frequency, 500.0);
+            },
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Support for Bevy's entity disabling.
+
+use super::FirewheelNode;
+use crate::prelude::AudioContext;
+use bevy_ecs::{entity_disabling::Disabled, prelude::*};
+use bevy_log::prelude::*;
+use firewheel::graph::Edge;
+
+/// The outgoing edges of a disabled node.
+///
+/// Firewheel has no notion of bypassing a node, so disabled
+/// nodes are muted by detaching their outputs. These are
+/// reconnected when the node is re-enabled.
+#[derive(Component)]
+pub(crate) struct DisabledEdges(Vec<Edge>);
+
+pub(crate) fn mute_disabled_nodes(
+    trigger: On<Add, Disabled>,
+    nodes: Query<&FirewheelNode, (With<Disabled>, Without<DisabledEdges>)>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    let Ok(node) = nodes.get(trigger.event_target()) else {
+        return;
+    };
+
+    let edges = context.with(|context| {
+        let edges: Vec<_> = context
+            .edges()
+            .into_iter()
+            .filter(|e| e.src_node == node.0)
+            .cloned()
+            .collect();
+
+        for edge in &edges {
+            context.disconnect_by_edge_id(edge.id);
+        }
+
+        edges
+    });
+
+    commands
+        .entity(trigger.event_target())
+        .insert(DisabledEdges(edges));
+}
+
+pub(crate) fn restore_enabled_nodes(
+    trigger: On<Remove, Disabled>,
+    nodes: Query<(&FirewheelNode, &DisabledEdges), With<Disabled>>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    let Ok((node, edges)) = nodes.get(trigger.event_target()) else {
+        return;
+    };
+
+    context.with(|context| {
+        for edge in &edges.0 {
+            // The destination may have been removed in the meantime.
+            if let Err(e) = context.connect(
+                node.0,
+                edge.dst_node,
+                &[(edge.src_port, edge.dst_port)],
+                false,
+            ) {
+                debug!("failed to restore edge for re-enabled node: {e:?}");
+            }
+        }
+    });
+
+    commands
+        .entity(trigger.event_target())
+        .remove::<DisabledEdges>();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        edge::AudioGraphOutput,
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::{ecs::entity_disabling::Disabled, prelude::*};
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    fn output_edges(app: &mut App) -> usize {
+        run(
+            app,
+            |q: Query<&FirewheelNode, (With<TestMarker>, Allow<Disabled>)>,
+             mut context: ResMut<AudioContext>| {
+                let node = q.single().unwrap();
+                context.with(|c| c.edges().iter().filter(|e| e.src_node == node.0).count())
+            },
+        )
+    }
+
+    #[test]
+    fn test_disabled_node_muted() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), TestMarker))
+                .connect(AudioGraphOutput);
+        });
+
+        assert_eq!(output_edges(&mut app), 2);
+
+        run(
+            &mut app,
+            |q: Single<Entity, With<TestMarker>>, mut commands: Commands| {
+                commands.entity(*q).insert(Disabled);
+            },
+        );
+        app.update();
+
+        assert_eq!(output_edges(&mut app), 0);
+
+        run(
+            &mut app,
+            |q: Single<Entity, (With<TestMarker>, With<Disabled>)>, mut commands: Commands| {
+                commands.entity(*q).remove::<Disabled>();
+            },
+        );
+        app.update();
+
+        assert_eq!(output_edges(&mut app), 2);
+    }
+}
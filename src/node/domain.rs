@@ -0,0 +1,245 @@
+//! State-driven audio domains.
+//!
+//! Games often have distinct sound "domains" that should only be heard
+//! in certain states: gameplay sounds go quiet when the pause menu opens,
+//! while menu sounds should only be heard in the menu. Rather than writing
+//! a volume system for each state, you can tag buses and pools with the
+//! states they belong to.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+//! enum GameState {
+//!     #[default]
+//!     Playing,
+//!     Paused,
+//!     PhotoMode,
+//! }
+//!
+//! #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+//! struct MenuPool;
+//!
+//! fn plugin(app: &mut App) {
+//!     app.add_plugins(AudioDomainPlugin::<GameState>::default());
+//! }
+//!
+//! fn spawn_pools(mut commands: Commands) {
+//!     // Gameplay sounds are heard while playing and in photo mode.
+//!     commands.spawn((
+//!         SamplerPool(DefaultPool),
+//!         AudioDomain::new([GameState::Playing, GameState::PhotoMode]),
+//!     ));
+//!
+//!     // Menu sounds are only heard while paused.
+//!     commands.spawn((SamplerPool(MenuPool), AudioDomain::new([GameState::Paused])));
+//! }
+//! ```
+//!
+//! [`AudioDomain`] works on any entity with a [`VolumeNode`], including
+//! sampler pools and buses. The domain's volume is applied by a dedicated
+//! gain stage after the node, so the node's own volume can be adjusted
+//! freely, and domains compose with [`Mute`] and loudness targets.
+//!
+//! [`Mute`]: super::mute::Mute
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_state::prelude::*;
+use core::marker::PhantomData;
+use firewheel::{Volume, clock::DurationSeconds};
+use smallvec::SmallVec;
+
+use crate::SeedlingSystems;
+
+use super::gain::{GainStage, update_gain_stages};
+
+#[cfg(doc)]
+use firewheel::nodes::volume::VolumeNode;
+
+/// Enables [`AudioDomain`]s driven by the state `S`.
+///
+/// Add one plugin for each state type you'd like to drive domains with.
+pub struct AudioDomainPlugin<S>(PhantomData<fn() -> S>);
+
+impl<S> core::fmt::Debug for AudioDomainPlugin<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("AudioDomainPlugin").finish()
+    }
+}
+
+impl<S> Default for AudioDomainPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: States> Plugin for AudioDomainPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            update_domains::<S>
+                .run_if(resource_exists::<State<S>>)
+                .after(SeedlingSystems::Pool)
+                .before(update_gain_stages),
+        );
+    }
+}
+
+/// Restrict an entity's [`VolumeNode`] to a set of states.
+///
+/// When the state `S` leaves the domain's states, the entity is
+/// faded to the domain's inactive volume. When it returns, the
+/// entity is faded back to unity gain.
+///
+/// Entities that begin outside their domain are set to the
+/// inactive volume immediately.
+///
+/// This requires the [`AudioDomainPlugin`] for `S`.
+#[derive(Debug, Component)]
+#[require(GainStage)]
+pub struct AudioDomain<S: States> {
+    states: SmallVec<[S; 2]>,
+    inactive_volume: Volume,
+    fade: DurationSeconds,
+    active: Option<bool>,
+}
+
+impl<S: States> AudioDomain<S> {
+    /// Create a new domain that's active in `states`.
+    ///
+    /// By default, the domain is silenced when inactive
+    /// and crossfades over a quarter of a second.
+    pub fn new(states: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            states: states.into_iter().collect(),
+            inactive_volume: Volume::SILENT,
+            fade: DurationSeconds(0.25),
+            active: None,
+        }
+    }
+
+    /// Set the volume applied while the domain is inactive.
+    ///
+    /// This is useful for ducking a domain rather than muting it.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// # #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
+    /// # enum GameState { Playing }
+    /// let domain = AudioDomain::new([GameState::Playing])
+    ///     .with_inactive_volume(Volume::Decibels(-18.0));
+    /// ```
+    pub fn with_inactive_volume(mut self, volume: Volume) -> Self {
+        self.inactive_volume = volume;
+        self
+    }
+
+    /// Set the duration of the crossfade when the domain
+    /// becomes active or inactive.
+    pub fn with_fade(mut self, fade: DurationSeconds) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// The states this domain is active in.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// Returns `true` if the domain is currently active.
+    ///
+    /// Before the domain has been evaluated, this returns `true`.
+    pub fn is_active(&self) -> bool {
+        self.active.unwrap_or(true)
+    }
+}
+
+fn update_domains<S: States>(
+    state: Res<State<S>>,
+    mut domains: Query<(&mut AudioDomain<S>, &mut GainStage)>,
+) {
+    for (mut domain, mut stage) in &mut domains {
+        let active = domain.states.contains(state.get());
+        if domain.active == Some(active) {
+            continue;
+        }
+
+        let first = domain.active.is_none();
+        domain.active = Some(active);
+
+        let volume = if active {
+            Volume::UNITY_GAIN
+        } else {
+            domain.inactive_volume
+        };
+        let fade = if first {
+            DurationSeconds(0.0)
+        } else {
+            domain.fade
+        };
+
+        stage.set_domain::<S>(volume, fade);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        node::gain::GainStageNode,
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::prelude::*;
+
+    #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+    enum GameState {
+        #[default]
+        Playing,
+        Paused,
+    }
+
+    #[test]
+    fn test_domains() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((VolumeNode::default(), AudioDomain::new([GameState::Paused])));
+        });
+
+        app.insert_resource(State::new(GameState::Playing))
+            .add_systems(Last, update_domains::<GameState>.before(update_gain_stages));
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the domain starts outside its states, so it's silenced immediately
+        run(
+            &mut app,
+            |domain: Single<&GainStageNode, With<AudioDomain<GameState>>>,
+             volumes: Query<&VolumeNode>| {
+                let stage = volumes.get(domain.node()).unwrap();
+                assert_eq!(stage.volume, Volume::SILENT);
+            },
+        );
+
+        app.insert_resource(State::new(GameState::Paused));
+        app.update();
+
+        run(
+            &mut app,
+            |domain: Single<(&VolumeNode, &GainStageNode, &AudioDomain<GameState>)>,
+             stages: Query<(&VolumeNode, &AudioEvents)>,
+             time: Res<Time<Audio>>| {
+                let (volume, stage, domain) = domain.into_inner();
+                assert!(domain.is_active());
+                // the entity's own volume is never touched
+                assert_eq!(volume.volume, Volume::UNITY_GAIN);
+
+                let (stage, events) = stages.get(stage.node()).unwrap();
+                let faded = events.get_value_at(time.now() + DurationSeconds(1.0), stage);
+                assert_eq!(faded.volume.amp(), 1.0);
+            },
+        );
+    }
+}
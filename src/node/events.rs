@@ -13,7 +13,7 @@ use firewheel::{
     nodes::volume::VolumeNode,
 };
 
-use crate::{error::SeedlingError, time::Audio};
+use crate::{error::SeedlingError, time::Audio, utils::fixed_vec::FixedVec};
 
 pub(crate) struct EventsPlugin;
 
@@ -101,9 +101,21 @@ pub struct AudioEvents {
     /// If we can instead render the events on-demand, we can fetch them whenever we need.
     /// It's also much easier to detect overlapping events.
     pub(super) timeline: Vec<EventTimeline>,
+    /// Parameter buffers reclaimed from elapsed timeline events.
+    ///
+    /// Heavy automation tends to schedule and expire many small
+    /// events every frame, so we hold onto a few cleared buffers
+    /// rather than returning them to the allocator.
+    spare: FixedVec<Vec<TimelineParam>>,
     now: InstantSeconds,
 }
 
+/// The number of cleared parameter buffers each [`AudioEvents`] retains.
+const SPARE_BUFFERS: usize = 4;
+
+/// Buffers that have grown larger than this are dropped rather than retained.
+const MAX_SPARE_CAPACITY: usize = 256;
+
 impl AudioEvents {
     /// Create a new instant of [`AudioEvents`], primed
     /// with the current audio context time.
@@ -111,10 +123,27 @@ impl AudioEvents {
         Self {
             queue: Default::default(),
             timeline: Default::default(),
+            spare: FixedVec::new(SPARE_BUFFERS),
             now: now.context().instant(),
         }
     }
 
+    /// Fetch a cleared parameter buffer, reusing a spare one if available.
+    fn take_buffer(&mut self) -> Vec<TimelineParam> {
+        self.spare.pop().unwrap_or_default()
+    }
+
+    /// Return an elapsed event's buffer to the spare list.
+    fn recycle(spare: &mut FixedVec<Vec<TimelineParam>>, mut buffer: Vec<TimelineParam>) {
+        if buffer.capacity() == 0 || buffer.capacity() > MAX_SPARE_CAPACITY {
+            return;
+        }
+
+        buffer.clear();
+        // If we're at capacity, the oldest spare is simply dropped.
+        spare.push(buffer);
+    }
+
     /// Essentially a duplicate of [`AudioTime::now`][crate::time::AudioTime::now].
     ///
     /// Given this duplicated information, this method is just an internal convenience
@@ -134,20 +163,42 @@ impl AudioEvents {
 
     /// Like `merge_timelines`, but clear all the events in `other` that
     /// have elapsed.
+    ///
+    /// Elapsed events are moved into `self` rather than cloned.
     pub(crate) fn merge_timelines_and_clear(&mut self, other: &mut Self, now: InstantSeconds) {
-        other.timeline.retain(|event| {
-            if !self.timeline.iter().any(|ev| ev.id() == event.id()) {
-                self.timeline.push(event.clone());
+        let spare = &mut other.spare;
+        other.timeline.retain_mut(|event| {
+            let present = self.timeline.iter().any(|ev| ev.id() == event.id());
+            let elapsed = event.completely_elapsed(now);
+
+            match (present, elapsed) {
+                (false, false) => self.timeline.push(event.clone()),
+                (false, true) => self.timeline.push(event.take()),
+                (true, true) => Self::recycle(spare, core::mem::take(&mut event.tween)),
+                (true, false) => {}
             }
 
-            !event.completely_elapsed(now)
+            !elapsed
         });
     }
 
     /// Clear the timeline of any elapsed events.
     pub(super) fn clear_elapsed_events(&mut self, now: InstantSeconds) {
-        self.timeline
-            .retain(|event| !event.completely_elapsed(now) || !event.render_progress.complete);
+        self.remove_events(|event| event.completely_elapsed(now) && event.render_progress.complete);
+    }
+
+    /// Remove timeline events matching `predicate`, preserving the order
+    /// of the remaining events and recycling the removed buffers.
+    fn remove_events(&mut self, mut predicate: impl FnMut(&EventTimeline) -> bool) {
+        let spare = &mut self.spare;
+        self.timeline.retain_mut(|event| {
+            if !predicate(event) {
+                return true;
+            }
+
+            Self::recycle(spare, core::mem::take(&mut event.tween));
+            false
+        });
     }
 
     /// Get the full timeline of events.
@@ -175,7 +226,7 @@ impl AudioEvents {
         let mut new_value = initial_value.clone();
         change(&mut new_value);
 
-        let mut events = Vec::new();
+        let mut events = self.take_buffer();
         let mut func = |ev, time| match ev {
             NodeEventType::Param { data, path } => {
                 events.push(TimelineParam { data, path, time });
@@ -203,7 +254,7 @@ impl AudioEvents {
         T: Diff + Patch + Send + Sync + Clone + 'static,
         F: Fn(&T, &T, f32) -> T,
     {
-        let mut events = Vec::new();
+        let mut events = self.take_buffer();
         let mut func = |ev, time| match ev {
             NodeEventType::Param { data, path } => {
                 events.push(TimelineParam { data, path, time });
@@ -227,6 +278,27 @@ impl AudioEvents {
         self.timeline.push(EventTimeline::new(events));
     }
 
+    /// Schedule pre-rendered parameters as a single timeline event.
+    ///
+    /// The parameters must be sorted by time.
+    pub(crate) fn schedule_params(
+        &mut self,
+        params: impl IntoIterator<Item = (ParamData, ParamPath, InstantSeconds)>,
+    ) {
+        let mut events = self.take_buffer();
+        events.extend(params.into_iter().map(|(data, path, time)| TimelineParam {
+            data,
+            path,
+            time,
+        }));
+
+        if events.is_empty() {
+            return;
+        }
+
+        self.timeline.push(EventTimeline::new(events));
+    }
+
     pub(crate) fn active_within(&self, start: InstantSeconds, end: InstantSeconds) -> bool {
         for event in &self.timeline {
             if event.active_within(start..=end) {
@@ -237,6 +309,22 @@ impl AudioEvents {
         false
     }
 
+    /// The immediate events queued for the next flush.
+    ///
+    /// This does not include scheduled events that have yet to be sent.
+    pub fn queued(&self) -> &[NodeEventType] {
+        &self.queue
+    }
+
+    /// Retain only the queued immediate events for which `f` returns `true`.
+    ///
+    /// This is most useful in [`SeedlingSystems::PreFlush`].
+    ///
+    /// [`SeedlingSystems::PreFlush`]: crate::prelude::SeedlingSystems::PreFlush
+    pub fn retain_queued(&mut self, f: impl FnMut(&NodeEventType) -> bool) {
+        self.queue.retain(f);
+    }
+
     /// Apply all scheduled events before `Instant` in this event queue to `value`.
     pub fn value_at<T>(
         &self,
@@ -395,6 +483,15 @@ impl EventTimeline {
         }
     }
 
+    /// Move this event out, leaving an empty parameter buffer behind.
+    fn take(&mut self) -> Self {
+        EventTimeline {
+            tween: core::mem::take(&mut self.tween),
+            render_progress: self.render_progress.clone(),
+            id: self.id,
+        }
+    }
+
     /// Report whether this event has completely elapsed by `now`.
     pub fn completely_elapsed(&self, now: InstantSeconds) -> bool {
         self.time_range().end < now
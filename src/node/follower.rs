@@ -6,7 +6,7 @@ use smallvec::SmallVec;
 
 use crate::time::{Audio, AudioTime};
 
-use super::{DiffTimestamp, events::AudioEvents};
+use super::{DiffTimestamp, events::AudioEvents, smooth::SmoothParams};
 
 /// A relationship that allows one entity's parameters to track another's.
 ///
@@ -59,7 +59,15 @@ pub struct Followers(SmallVec<[Entity; 2]>);
 /// on a sample player entity directly rather than drilling
 /// into the sample pool and node the sample is assigned to.
 pub(crate) fn param_follower<T: Diff + Patch + Component<Mutability = Mutable> + Clone>(
-    mut sources: Query<(&mut T, &mut AudioEvents, Option<&DiffTimestamp>), Without<FollowerOf>>,
+    mut sources: Query<
+        (
+            &mut T,
+            &mut AudioEvents,
+            Option<&DiffTimestamp>,
+            Option<&mut SmoothParams>,
+        ),
+        Without<FollowerOf>,
+    >,
     mut followers: Query<(Entity, &FollowerOf, &mut T, &mut AudioEvents)>,
     time: Res<bevy_time::Time<Audio>>,
     mut commands: Commands,
@@ -68,12 +76,22 @@ pub(crate) fn param_follower<T: Diff + Patch + Component<Mutability = Mutable> +
 
     let mut event_queue = Vec::new();
     for (entity, follower, mut params, mut events) in followers.iter_mut() {
-        let Ok((mut source, mut source_events, timestamp)) = sources.get_mut(follower.0) else {
+        let Ok((mut source, mut source_events, timestamp, smooth)) = sources.get_mut(follower.0)
+        else {
             continue;
         };
 
+        // TODO: this will hold changes for all but the first
+        // follower if there are multiple followers.
+        let should_diff = match smooth {
+            Some(mut smooth) => smooth.poll(source.is_changed(), time.now()),
+            None => true,
+        };
+
         // TODO: the ordering here might not be totally correct
-        source.diff(&params, PathBuilder::default(), &mut event_queue);
+        if should_diff {
+            source.diff(&params, PathBuilder::default(), &mut event_queue);
+        }
 
         if source_events.active_within(render_range.start, render_range.end) {
             source_events.value_at(render_range.start, render_range.end, source.as_mut())?;
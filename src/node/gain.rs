@@ -0,0 +1,385 @@
+//! A dedicated gain stage for automatic level adjustments.
+//!
+//! Features like muting, audio domains, and loudness normalization
+//! need to adjust a node's level without touching the user's own
+//! [`VolumeNode`]. Rather than saving and restoring that volume, they
+//! each write their own adjustment to a [`GainStage`], which combines
+//! them on a separate [`VolumeNode`] spliced into the node's outputs.
+
+use super::{
+    FirewheelNode,
+    events::{AudioEvents, VolumeFade},
+};
+use crate::{SeedlingSystems, edge::PendingConnections, prelude::AudioContext};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use core::any::TypeId;
+use firewheel::{
+    Volume,
+    channel_config::NonZeroChannelCount,
+    clock::DurationSeconds,
+    nodes::volume::{VolumeNode, VolumeNodeConfig},
+};
+use smallvec::SmallVec;
+
+pub(crate) struct GainStagePlugin;
+
+impl Plugin for GainStagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            (
+                update_gain_stages
+                    .after(SeedlingSystems::Pool)
+                    .before(SeedlingSystems::Queue),
+                reroute_outputs.in_set(SeedlingSystems::PreFlush),
+            ),
+        )
+        .add_observer(attach_gain_stage);
+    }
+}
+
+/// The automatic adjustments applied after a node's outputs.
+///
+/// Each adjustment is tracked separately, so they can be changed
+/// in any order without clobbering one another.
+#[derive(Debug, Component)]
+pub(crate) struct GainStage {
+    /// The loudness trim, in decibels.
+    trim: f32,
+    /// Whether the node is muted or soloed out.
+    silenced: bool,
+    /// The volume of each audio domain, keyed by its state type.
+    domains: SmallVec<[(TypeId, Volume); 1]>,
+    /// The longest fade requested since the last update.
+    fade: DurationSeconds,
+    /// The volume last written to the stage's node.
+    applied: Volume,
+}
+
+impl Default for GainStage {
+    fn default() -> Self {
+        Self {
+            trim: 0.0,
+            silenced: false,
+            domains: SmallVec::new(),
+            fade: DurationSeconds(0.0),
+            applied: Volume::UNITY_GAIN,
+        }
+    }
+}
+
+impl GainStage {
+    /// Set the loudness trim, in decibels.
+    #[cfg(feature = "loudness")]
+    pub(crate) fn set_trim(&mut self, trim: f32) {
+        self.trim = trim;
+    }
+
+    /// Returns `true` if the node is muted or soloed out.
+    pub(crate) fn silenced(&self) -> bool {
+        self.silenced
+    }
+
+    /// Silence or restore the node, fading over `fade`.
+    pub(crate) fn set_silenced(&mut self, silenced: bool, fade: DurationSeconds) {
+        self.silenced = silenced;
+        self.request_fade(fade);
+    }
+
+    /// Set the volume of the domain driven by the state `S`, fading over `fade`.
+    pub(crate) fn set_domain<S: 'static>(&mut self, volume: Volume, fade: DurationSeconds) {
+        let id = TypeId::of::<S>();
+        match self.domains.iter_mut().find(|(domain, _)| *domain == id) {
+            Some((_, current)) => *current = volume,
+            None => self.domains.push((id, volume)),
+        }
+        self.request_fade(fade);
+    }
+
+    fn request_fade(&mut self, fade: DurationSeconds) {
+        if fade.0 > self.fade.0 {
+            self.fade = fade;
+        }
+    }
+
+    /// The combined volume of every adjustment.
+    pub(crate) fn volume(&self) -> Volume {
+        let domains = self.domains.iter().map(|(_, volume)| *volume);
+        if self.silenced || domains.clone().any(|volume| volume.amp() == 0.0) {
+            return Volume::SILENT;
+        }
+
+        Volume::Decibels(self.trim + domains.map(|volume| volume.decibels()).sum::<f32>())
+    }
+}
+
+/// The [`VolumeNode`] applying an entity's [`GainStage`].
+#[derive(Debug, Component)]
+#[relationship(relationship_target = GainStageNode)]
+pub(crate) struct GainStageOf(pub Entity);
+
+/// The relationship target for [`GainStageOf`].
+#[derive(Debug, Component)]
+#[relationship_target(relationship = GainStageOf, linked_spawn)]
+pub(crate) struct GainStageNode(Entity);
+
+impl GainStageNode {
+    /// The stage's [`VolumeNode`] entity.
+    pub(crate) fn node(&self) -> Entity {
+        self.0
+    }
+}
+
+fn attach_gain_stage(
+    trigger: On<Add, GainStage>,
+    configs: Query<&VolumeNodeConfig>,
+    mut commands: Commands,
+) {
+    let target = trigger.event_target();
+    let channels = configs
+        .get(target)
+        .map(|config| config.channels)
+        .unwrap_or(NonZeroChannelCount::STEREO);
+
+    // The stage's outputs are taken over from its target in
+    // `reroute_outputs`, so it shouldn't be connected automatically.
+    commands.spawn((
+        VolumeNode::default(),
+        VolumeNodeConfig { channels },
+        PendingConnections::default(),
+        GainStageOf(target),
+    ));
+}
+
+/// Write each stage's combined volume to its node.
+///
+/// Systems that adjust a [`GainStage`] should run before this.
+pub(crate) fn update_gain_stages(
+    mut stages: Query<(&mut GainStage, &GainStageNode)>,
+    mut nodes: Query<(&mut VolumeNode, &mut AudioEvents), With<GainStageOf>>,
+) {
+    for (mut stage, node) in &mut stages {
+        let target = stage.volume();
+        if target == stage.applied {
+            if stage.fade.0 != 0.0 {
+                stage.fade = DurationSeconds(0.0);
+            }
+            continue;
+        }
+
+        let Ok((mut volume, mut events)) = nodes.get_mut(node.node()) else {
+            continue;
+        };
+
+        let stage = stage.as_mut();
+        if stage.fade.0 > 0.0 {
+            volume.fade_to(target, stage.fade, &mut events);
+        } else {
+            volume.volume = target;
+        }
+
+        stage.applied = target;
+        stage.fade = DurationSeconds(0.0);
+    }
+}
+
+/// Move any new outgoing connections of staged nodes onto their stages.
+///
+/// Only nodes whose connections may have changed this frame are
+/// checked, so idle frames leave the graph alone.
+fn reroute_outputs(
+    changed: Query<
+        Entity,
+        (
+            With<GainStageNode>,
+            Or<(
+                Changed<FirewheelNode>,
+                Changed<PendingConnections>,
+                Changed<GainStageNode>,
+            )>,
+        ),
+    >,
+    new_stages: Query<&GainStageOf, Changed<FirewheelNode>>,
+    nodes: Query<(&FirewheelNode, &GainStageNode)>,
+    stages: Query<&FirewheelNode, With<GainStageOf>>,
+    mut context: ResMut<AudioContext>,
+) {
+    let mut targets: Vec<_> = changed
+        .iter()
+        .chain(new_stages.iter().map(|stage| stage.0))
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+
+    let pairs: Vec<_> = nodes
+        .iter_many(targets)
+        .filter_map(|(node, stage)| Some((node.0, stages.get(stage.node()).ok()?.0)))
+        .collect();
+
+    if pairs.is_empty() {
+        return;
+    }
+
+    context.with(|context| {
+        for (node, stage) in pairs {
+            let edges: Vec<_> = context
+                .edges()
+                .into_iter()
+                .filter(|e| e.src_node == node)
+                .cloned()
+                .collect();
+
+            if !edges.iter().any(|e| e.dst_node == stage) {
+                let outputs = context
+                    .node_info(node)
+                    .map(|entry| entry.info.channel_config.num_outputs.get())
+                    .unwrap_or(0);
+                let ports: Vec<_> = (0..outputs).map(|i| (i, i)).collect();
+
+                if let Err(e) = context.connect(node, stage, &ports, false) {
+                    warn!("failed to connect gain stage: {e:?}");
+                    continue;
+                }
+            }
+
+            for edge in edges.into_iter().filter(|e| e.dst_node != stage) {
+                context.disconnect_by_edge_id(edge.id);
+                if let Err(e) = context.connect(
+                    stage,
+                    edge.dst_node,
+                    &[(edge.src_port, edge.dst_port)],
+                    false,
+                ) {
+                    warn!("failed to route connection through gain stage: {e:?}");
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run},
+    };
+
+    #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Bus;
+
+    #[test]
+    fn test_gain_stage() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+            commands
+                .spawn((
+                    VolumeNode {
+                        volume: Volume::Decibels(-6.0),
+                        ..Default::default()
+                    },
+                    Bus,
+                ))
+                .connect(MainBus);
+        });
+
+        let bus = run(&mut app, |bus: Single<Entity, With<Bus>>| *bus);
+        app.world_mut().entity_mut(bus).insert(GainStage {
+            trim: -3.0,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        run(
+            &mut app,
+            move |nodes: Query<(&VolumeNode, &FirewheelNode)>,
+                  stages: Query<&GainStageNode>,
+                  main: Single<&FirewheelNode, With<MainBus>>,
+                  mut context: ResMut<AudioContext>| {
+                let (volume, bus_node) = nodes.get(bus).unwrap();
+                let (stage_volume, stage_node) = nodes.get(stages.get(bus).unwrap().0).unwrap();
+
+                // the user's volume is left alone
+                assert_eq!(volume.volume, Volume::Decibels(-6.0));
+                assert_eq!(stage_volume.volume, Volume::Decibels(-3.0));
+
+                let (bus_node, stage_node, main) = (bus_node.0, stage_node.0, main.0);
+                context.with(|context| {
+                    let edges = context.edges();
+                    assert!(
+                        edges
+                            .iter()
+                            .all(|e| e.src_node != bus_node || e.dst_node == stage_node)
+                    );
+                    assert!(
+                        edges
+                            .iter()
+                            .any(|e| e.src_node == stage_node && e.dst_node == main)
+                    );
+                });
+            },
+        );
+    }
+
+    #[test]
+    fn test_late_connection() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+            commands
+                .spawn((VolumeNode::default(), Bus, GainStage::default()))
+                .connect(MainBus);
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // a connection made after the stage is in place
+        let (bus, other) = run(
+            &mut app,
+            |bus: Single<Entity, With<Bus>>, mut commands: Commands| {
+                let other = commands.spawn(VolumeNode::default()).id();
+                commands.entity(*bus).connect(other);
+                (*bus, other)
+            },
+        );
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        run(
+            &mut app,
+            move |nodes: Query<&FirewheelNode>,
+                  stages: Query<&GainStageNode>,
+                  mut context: ResMut<AudioContext>| {
+                let bus_node = nodes.get(bus).unwrap().0;
+                let stage_node = nodes.get(stages.get(bus).unwrap().node()).unwrap().0;
+                let other_node = nodes.get(other).unwrap().0;
+
+                context.with(|context| {
+                    let edges = context.edges();
+                    assert!(
+                        edges
+                            .iter()
+                            .all(|e| e.src_node != bus_node || e.dst_node == stage_node)
+                    );
+                    assert!(
+                        edges
+                            .iter()
+                            .any(|e| e.src_node == stage_node && e.dst_node == other_node)
+                    );
+                });
+            },
+        );
+    }
+}
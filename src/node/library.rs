@@ -0,0 +1,163 @@
+//! Registration for third-party node libraries.
+//!
+//! Crates that ship Firewheel nodes can bundle their registration
+//! into a [`SeedlingNodeLibrary`]. Libraries are built once
+//! `bevy_seedling` has finished its own setup, so they can be added
+//! in any order relative to the [`SeedlingPlugin`], and a library
+//! added by several dependents is only built once.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, node::library::*};
+//! # use bevy_seedling::prelude::LowPassNode as GranularNode;
+//! /// The nodes provided by a hypothetical crate.
+//! pub struct GranularNodes;
+//!
+//! impl SeedlingNodeLibrary for GranularNodes {
+//!     fn build(&self, app: &mut App) {
+//!         app.register_node::<GranularNode>();
+//!     }
+//! }
+//!
+//! // This may be called from any plugin, before or after
+//! // `SeedlingPlugin` is added.
+//! fn plugin(app: &mut App) {
+//!     app.add_node_library(GranularNodes);
+//! }
+//! ```
+//!
+//! Rust items can't be re-exported at runtime, so libraries should
+//! provide their own `prelude` module alongside `bevy_seedling`'s.
+//!
+//! [`SeedlingPlugin`]: crate::SeedlingPlugin
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashSet;
+use std::any::TypeId;
+
+/// A collection of audio nodes and their supporting types and systems.
+pub trait SeedlingNodeLibrary: Send + Sync + 'static {
+    /// Register the library's nodes.
+    ///
+    /// This is called exactly once, after all of `bevy_seedling`'s
+    /// built-in nodes have been registered. Nodes should be registered
+    /// with [`RegisterNode`], which guards against duplicate registration,
+    /// and any per-node systems should be placed relative to
+    /// [`SeedlingSystems`].
+    ///
+    /// Reflect types can be registered here as well, gated
+    /// on the library's own features.
+    ///
+    /// [`RegisterNode`]: crate::prelude::RegisterNode
+    /// [`SeedlingSystems`]: crate::SeedlingSystems
+    fn build(&self, app: &mut App);
+}
+
+/// Add [`SeedlingNodeLibrary`]s to an [`App`].
+pub trait AddNodeLibrary {
+    /// Add a node library.
+    ///
+    /// If the [`SeedlingPlugin`] hasn't finished yet, the library
+    /// is built when it does. Adding the same library type more than
+    /// once has no effect.
+    ///
+    /// [`SeedlingPlugin`]: crate::SeedlingPlugin
+    fn add_node_library<L: SeedlingNodeLibrary>(&mut self, library: L) -> &mut Self;
+}
+
+impl AddNodeLibrary for App {
+    fn add_node_library<L: SeedlingNodeLibrary>(&mut self, library: L) -> &mut Self {
+        let mut libraries = self.world_mut().get_resource_or_init::<NodeLibraries>();
+
+        if !libraries.added.insert(TypeId::of::<L>()) {
+            bevy_log::debug!(
+                "Node library `{}` was added more than once",
+                core::any::type_name::<L>(),
+            );
+
+            return self;
+        }
+
+        if !libraries.ready {
+            libraries.pending.push(Box::new(library));
+            return self;
+        }
+
+        library.build(self);
+        self
+    }
+}
+
+/// Tracks added libraries and defers them until seedling is ready.
+#[derive(Resource, Default)]
+struct NodeLibraries {
+    added: HashSet<TypeId>,
+    pending: Vec<Box<dyn SeedlingNodeLibrary>>,
+    ready: bool,
+}
+
+/// Build all pending libraries.
+///
+/// This is called when the [`SeedlingPlugin`] finishes,
+/// after every plugin has had a chance to add its libraries.
+///
+/// [`SeedlingPlugin`]: crate::SeedlingPlugin
+pub(crate) fn build_libraries(app: &mut App) {
+    let pending = {
+        let mut libraries = app.world_mut().get_resource_or_init::<NodeLibraries>();
+        libraries.ready = true;
+        core::mem::take(&mut libraries.pending)
+    };
+
+    // Libraries added while building these are built immediately.
+    for library in pending {
+        library.build(app);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{node::RegisteredNodes, prelude::*};
+    use bevy::prelude::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    struct TestLibrary;
+
+    impl SeedlingNodeLibrary for TestLibrary {
+        fn build(&self, app: &mut App) {
+            // Seedling's own nodes are always registered first.
+            assert!(
+                app.world()
+                    .resource::<RegisteredNodes>()
+                    .contains::<VolumeNode>()
+            );
+            BUILDS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_library_order() {
+        let mut app = App::new();
+
+        // Libraries may be added before seedling...
+        app.add_node_library(TestLibrary);
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            SeedlingPlugin::<crate::utils::profiling::ProfilingBackend> {
+                graph_config: crate::configuration::GraphConfiguration::Empty,
+                ..SeedlingPlugin::<crate::utils::profiling::ProfilingBackend>::new()
+            },
+        ));
+        // ...and added more than once.
+        app.add_node_library(TestLibrary);
+        assert_eq!(BUILDS.load(Ordering::Relaxed), 0);
+
+        app.finish();
+        assert_eq!(BUILDS.load(Ordering::Relaxed), 1);
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::edge::NodeMap;
 use crate::error::SeedlingError;
+use crate::nodes::meter::{MeterConfig, MeterNode, MeterState};
 use crate::pool::sample_effects::EffectOf;
 use crate::time::{Audio, AudioTime};
 use crate::{SeedlingSystems, prelude::AudioContext};
@@ -14,21 +15,35 @@ use bevy_ecs::{
     world::DeferredWorld,
 };
 use bevy_log::prelude::*;
-use bevy_platform::collections::HashSet;
+use bevy_platform::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
 use bevy_time::Time;
 use firewheel::clock::{DurationSeconds, EventInstant, InstantSeconds};
 use firewheel::error::UpdateError;
 use firewheel::{
+    Volume,
+    channel_config::NonZeroChannelCount,
     diff::{Diff, Patch},
     event::{NodeEvent, NodeEventType},
     node::{AudioNode, NodeID},
 };
 use std::any::TypeId;
-use std::ops::DerefMut;
 
+pub mod audible;
+pub mod automation;
+pub(crate) mod disabled;
+pub mod domain;
 pub mod events;
 pub mod follower;
+pub(crate) mod gain;
 pub mod label;
+pub mod library;
+pub mod mute;
+pub mod quality;
+pub mod smooth;
+pub mod validate;
 
 use events::AudioEvents;
 use label::NodeLabels;
@@ -120,37 +135,91 @@ fn apply_patch<T: Patch>(value: &mut T, event: &NodeEventType) -> Result {
 }
 
 fn generate_param_events<T: Diff + Patch + Component<Mutability = Mutable> + Clone>(
-    mut nodes: Query<(Mut<T>, &mut Baseline<T>, &mut AudioEvents, Has<EffectOf>)>,
+    mut nodes: Query<(
+        Entity,
+        Mut<T>,
+        &mut Baseline<T>,
+        &mut AudioEvents,
+        Has<EffectOf>,
+        Option<&mut smooth::SmoothParams>,
+    )>,
     time: Res<bevy_time::Time<Audio>>,
 ) -> Result {
     let render_range = time.render_range();
+    let now = time.now();
+
+    // Each node diffs into its own event queue, so we can freely
+    // split the work across threads without affecting the order
+    // in which a node's events are flushed.
+    let errors = Mutex::new(Vec::new());
+    nodes.par_iter_mut().for_each(
+        |(entity, params, mut baseline, mut events, effect, smooth)| {
+            // Effects are diffed by their followers.
+            let should_diff = !effect
+                && match smooth {
+                    Some(mut smooth) => smooth.poll(params.is_changed(), now),
+                    None => params.is_changed(),
+                };
+
+            if let Err(e) = diff_node(
+                params,
+                &mut baseline,
+                &mut events,
+                should_diff,
+                &render_range,
+            ) {
+                errors.lock().unwrap().push((entity, e));
+            }
+        },
+    );
+
+    // Errors arrive in whatever order the threads finish, so they're
+    // sorted to report the same one each time. The first is propagated,
+    // while the rest are logged so a single faulty node can't hide the others.
+    let mut errors = errors.into_inner().unwrap();
+    errors.sort_unstable_by_key(|(entity, _)| *entity);
+    let mut errors = errors.into_iter().map(|(_, e)| e);
+    let first = errors.next();
+    for e in errors {
+        error!("{e}");
+    }
 
-    for (mut params, mut baseline, mut events, effect) in nodes.iter_mut() {
-        if params.is_changed() && !effect {
-            // This ensures we only apply patches that were generated here.
-            // I'm not sure this is correct in all cases, though.
-            let starting_len = events.queue.len();
+    match first {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
 
-            params.diff(&baseline.0, Default::default(), events.deref_mut());
+fn diff_node<T: Diff + Patch + Component<Mutability = Mutable> + Clone>(
+    mut params: Mut<T>,
+    baseline: &mut Baseline<T>,
+    events: &mut AudioEvents,
+    should_diff: bool,
+    render_range: &core::ops::Range<InstantSeconds>,
+) -> Result {
+    if should_diff {
+        // This ensures we only apply patches that were generated here.
+        // I'm not sure this is correct in all cases, though.
+        let starting_len = events.queue.len();
 
-            // Patch the baseline.
-            for event in &events.queue[starting_len..] {
-                apply_patch(&mut baseline.0, event)?;
-            }
-        }
+        params.diff(&baseline.0, Default::default(), events);
 
-        // Finally, render any scheduled change, removing any
-        // expired events.
-        events.clear_elapsed_events(render_range.start);
-        // TODO: this change-detection guarding is still more coarse than it needs to be.
-        // Often, no events within an active range will occur on a given frame.
-        if events.active_within(render_range.start, render_range.end) {
-            // TODO: consider collecting these errors
-            events.value_at(render_range.start, render_range.end, params.as_mut())?;
-            events.value_at(render_range.start, render_range.end, &mut baseline.0)?;
+        // Patch the baseline.
+        for event in &events.queue[starting_len..] {
+            apply_patch(&mut baseline.0, event)?;
         }
     }
 
+    // Finally, render any scheduled change, removing any
+    // expired events.
+    events.clear_elapsed_events(render_range.start);
+    // TODO: this change-detection guarding is still more coarse than it needs to be.
+    // Often, no events within an active range will occur on a given frame.
+    if events.active_within(render_range.start, render_range.end) {
+        events.value_at(render_range.start, render_range.end, params.as_mut())?;
+        events.value_at(render_range.start, render_range.end, &mut baseline.0)?;
+    }
+
     Ok(())
 }
 
@@ -296,15 +365,143 @@ fn fetch_state<T, S>(
     });
 }
 
-#[derive(Resource, Default)]
-struct RegisteredNodes(HashSet<TypeId>);
+/// A type registered with `bevy_seedling` as part of an audio node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegisteredType {
+    /// The type's [`TypeId`].
+    ///
+    /// This can be used to look up the type in Bevy's type registry.
+    pub id: TypeId,
+    /// The type's name, as provided by [`core::any::type_name`].
+    pub name: &'static str,
+}
+
+impl RegisteredType {
+    fn of<T: core::any::Any>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: core::any::type_name::<T>(),
+        }
+    }
+}
+
+/// Information about an audio node registered with [`RegisterNode`].
+#[derive(Debug, Clone)]
+pub struct NodeRegistration {
+    /// The node type.
+    pub node: RegisteredType,
+    /// The node's [`AudioNode::Configuration`] type.
+    pub configuration: RegisteredType,
+    /// Whether this node was registered with automatic diffing.
+    ///
+    /// This is `true` for nodes registered with [`RegisterNode::register_node`]
+    /// and `false` for [`RegisterNode::register_simple_node`].
+    pub diffing: bool,
+}
+
+/// All audio node types registered with [`RegisterNode`].
+///
+/// This allows tools like editors, debug UIs, and validators
+/// to enumerate the available node types at runtime.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::RegisteredNodes};
+/// fn list_nodes(nodes: Res<RegisteredNodes>) {
+///     for node in nodes.iter() {
+///         info!(
+///             "{} (diffing: {}, states: {})",
+///             node.node.name,
+///             node.diffing,
+///             nodes.states_by_id(node.node.id).len(),
+///         );
+///     }
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct RegisteredNodes {
+    nodes: HashMap<TypeId, NodeRegistration>,
+    states: HashMap<TypeId, Vec<RegisteredType>>,
+}
 
 impl RegisteredNodes {
-    /// Insert the `TypeId` of `T`.
+    /// Insert the registration for `T`.
     ///
-    /// Returns `true` if the ID wasn't already present.
-    fn insert<T: core::any::Any>(&mut self) -> bool {
-        self.0.insert(TypeId::of::<T>())
+    /// Returns `true` if `T` wasn't already present.
+    fn insert<T: AudioNode<Configuration: 'static> + 'static>(&mut self, diffing: bool) -> bool {
+        let node = RegisteredType::of::<T>();
+        if self.nodes.contains_key(&node.id) {
+            return false;
+        }
+
+        self.nodes.insert(
+            node.id,
+            NodeRegistration {
+                node,
+                configuration: RegisteredType::of::<T::Configuration>(),
+                diffing,
+            },
+        );
+
+        true
+    }
+
+    /// Insert the state `S` for node `T`.
+    ///
+    /// Returns `true` if the pair wasn't already present.
+    fn insert_state<T: core::any::Any, S: core::any::Any>(&mut self) -> bool {
+        let states = self.states.entry(TypeId::of::<T>()).or_default();
+        let state = RegisteredType::of::<S>();
+
+        if states.contains(&state) {
+            return false;
+        }
+
+        states.push(state);
+        true
+    }
+
+    /// Iterate over all registered nodes.
+    ///
+    /// The iteration order is unspecified.
+    pub fn iter(&self) -> impl Iterator<Item = &NodeRegistration> {
+        self.nodes.values()
+    }
+
+    /// Get the registration for the node `T`, if present.
+    pub fn get<T: core::any::Any>(&self) -> Option<&NodeRegistration> {
+        self.get_by_id(TypeId::of::<T>())
+    }
+
+    /// Get the registration for a node by its [`TypeId`], if present.
+    pub fn get_by_id(&self, id: TypeId) -> Option<&NodeRegistration> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns `true` if the node `T` has been registered.
+    pub fn contains<T: core::any::Any>(&self) -> bool {
+        self.nodes.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Get the state types registered for the node `T`
+    /// with [`RegisterNode::register_node_state`].
+    pub fn states<T: core::any::Any>(&self) -> &[RegisteredType] {
+        self.states_by_id(TypeId::of::<T>())
+    }
+
+    /// Get the state types registered for a node by its [`TypeId`].
+    pub fn states_by_id(&self, id: TypeId) -> &[RegisteredType] {
+        self.states.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the number of registered nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if no nodes have been registered.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
     }
 }
 
@@ -320,18 +517,6 @@ impl RegisteredConfigs {
     }
 }
 
-#[derive(Resource, Default)]
-struct RegisteredState(HashSet<(TypeId, TypeId)>);
-
-impl RegisteredState {
-    /// Insert the `TypeId` of `T` and `U`.
-    ///
-    /// Returns `true` if the tuple wasn't already present.
-    fn insert<T: core::any::Any, U: core::any::Any>(&mut self) -> bool {
-        self.0.insert((TypeId::of::<T>(), TypeId::of::<U>()))
-    }
-}
-
 /// Register audio nodes in the ECS.
 ///
 /// ## Creating and registering nodes
@@ -460,7 +645,7 @@ impl RegisterNode for App {
         let world = self.world_mut();
         let mut nodes = world.get_resource_or_init::<RegisteredNodes>();
 
-        if nodes.insert::<T>() {
+        if nodes.insert::<T>(true) {
             world.add_observer(observe_node_insertion::<T>);
             world.register_required_components::<T, T::Configuration>();
         } else {
@@ -497,7 +682,12 @@ impl RegisterNode for App {
                 (acquire_id::<T>, handle_configuration_changes::<T>)
                     .chain()
                     .in_set(SeedlingSystems::Acquire),
-                (follower::param_follower::<T>, generate_param_events::<T>)
+                (
+                    follower::param_follower::<T>,
+                    generate_param_events::<T>,
+                    automation::record_automation::<T>,
+                    audible::update_audible_values::<T>,
+                )
                     .chain()
                     .in_set(SeedlingSystems::Queue),
             ),
@@ -512,7 +702,7 @@ impl RegisterNode for App {
         let world = self.world_mut();
         let mut nodes = world.get_resource_or_init::<RegisteredNodes>();
 
-        if nodes.insert::<T>() {
+        if nodes.insert::<T>(false) {
             world.add_observer(observe_simple_node_insertion::<T>);
             world.register_required_components::<T, T::Configuration>();
         } else {
@@ -556,9 +746,9 @@ impl RegisterNode for App {
         S: Clone + Send + Sync + 'static,
     {
         let world = self.world_mut();
-        let mut nodes = world.get_resource_or_init::<RegisteredState>();
+        let mut nodes = world.get_resource_or_init::<RegisteredNodes>();
 
-        if !nodes.insert::<T, S>() {
+        if !nodes.insert_state::<T, S>() {
             #[cfg(debug_assertions)]
             {
                 bevy_log::warn!(
@@ -634,6 +824,10 @@ fn observe_simple_node_insertion<T: Component>(
 ///
 /// When this component is removed, the underlying
 /// audio node is removed from the graph.
+///
+/// While a node entity is [`Disabled`][bevy_ecs::entity_disabling::Disabled],
+/// its outputs are disconnected, effectively muting it. The connections are
+/// restored when the entity is re-enabled.
 #[derive(Debug, Clone, Copy, Component)]
 #[component(on_replace = Self::on_replace_hook, immutable)]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
@@ -645,8 +839,68 @@ impl FirewheelNode {
             return;
         };
 
+        let tail = world.get::<DespawnWithTail>(context.entity).copied();
         let mut removals = world.resource_mut::<PendingRemovals>();
-        removals.push(node.0);
+        match tail {
+            Some(tail) => removals.push_with_tail(node.0, tail),
+            None => removals.push(node.0),
+        }
+    }
+}
+
+/// Keep an audio node in the graph for a short time after it's removed.
+///
+/// Despawning effects like [`FreeverbNode`] normally cuts off their
+/// output abruptly. When a node with this component is removed, its inputs
+/// are disconnected immediately, but the underlying audio node is kept
+/// alive for up to [`DespawnWithTail::max`], letting its tail ring out.
+///
+/// With a [`DespawnWithTail::threshold`], the node's output is metered
+/// while it rings out, and the node is removed as soon as the decaying
+/// peak of every output channel falls below the threshold.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn spawn_reverb(mut commands: Commands) {
+///     commands.spawn((
+///         FreeverbNode::default(),
+///         DespawnWithTail::new(DurationSeconds(3.0)).with_threshold(Volume::Decibels(-60.0)),
+///     ));
+/// }
+/// ```
+///
+/// Note that this applies whenever the entity's [`FirewheelNode`] is
+/// removed or replaced, including when a node is recreated following a
+/// configuration change.
+///
+/// [`FreeverbNode`]: crate::prelude::FreeverbNode
+#[derive(Debug, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DespawnWithTail {
+    /// The longest the node is kept alive after it's removed.
+    pub max: DurationSeconds,
+    /// The output level below which the node is removed early.
+    ///
+    /// When `None`, the node is always kept alive for [`DespawnWithTail::max`].
+    pub threshold: Option<Volume>,
+}
+
+impl DespawnWithTail {
+    /// Keep the node alive for `max` after it's removed.
+    pub fn new(max: DurationSeconds) -> Self {
+        Self {
+            max,
+            threshold: None,
+        }
+    }
+
+    /// Remove the node early once its output falls below `threshold`.
+    pub fn with_threshold(self, threshold: Volume) -> Self {
+        Self {
+            threshold: Some(threshold),
+            ..self
+        }
     }
 }
 
@@ -655,11 +909,40 @@ impl FirewheelNode {
 /// This resource allows us to defer audio node removals
 /// until the audio graph is ready.
 #[derive(Debug, Default, Resource)]
-pub(crate) struct PendingRemovals(Vec<NodeID>);
+pub(crate) struct PendingRemovals {
+    immediate: Vec<NodeID>,
+    /// Nodes that should have their inputs disconnected,
+    /// ringing out according to their tail.
+    tails: Vec<(NodeID, DespawnWithTail)>,
+    /// Disconnected nodes that are ringing out.
+    ringing: Vec<RingingNode>,
+}
 
 impl PendingRemovals {
     pub fn push(&mut self, node: NodeID) {
-        self.0.push(node);
+        self.immediate.push(node);
+    }
+
+    pub fn push_with_tail(&mut self, node: NodeID, tail: DespawnWithTail) {
+        self.tails.push((node, tail));
+    }
+}
+
+/// A disconnected node that will be removed at `deadline`.
+#[derive(Debug)]
+struct RingingNode {
+    node: NodeID,
+    deadline: InstantSeconds,
+    /// A meter on the node's outputs and the level, in decibels,
+    /// below which the node is removed early.
+    meter: Option<(NodeID, MeterState, f32)>,
+}
+
+impl RingingNode {
+    fn is_silent(&self) -> bool {
+        self.meter.as_ref().is_some_and(|(_, state, threshold)| {
+            (0..state.channels()).all(|channel| state.peak(channel) < *threshold)
+        })
     }
 }
 
@@ -677,17 +960,83 @@ pub(crate) fn flush_events(
     lookahead: Res<AudioScheduleLookahead>,
     mut commands: Commands,
 ) {
+    // We use the start-of-frame time here to ensure these events
+    // line up with the overall frame, even if it has already fallen
+    // behind the audio thread at this point in the frame.
+    let now = time.now();
+    let removals = &mut *removals;
+
     context.with(|context| {
-        for node in removals.0.drain(..) {
+        let graph_io = [context.graph_in_node_id(), context.graph_out_node_id()];
+        for node in removals.immediate.drain(..) {
+            // The graph's I/O nodes can't be removed. Their
+            // markers are restored in `configuration::restore_io`.
+            if graph_io.contains(&node) {
+                continue;
+            }
+
             if context.remove_node(node).is_err() {
                 error!("attempted to remove non-existent or invalid node from audio graph");
             }
         }
 
-        // We use the start-of-frame time here to ensure these events
-        // line up with the overall frame, even if it has already fallen
-        // behind the audio thread at this point in the frame.
-        let now = time.now();
+        for (node, tail) in removals.tails.drain(..) {
+            let inputs = context
+                .edges()
+                .iter()
+                .filter(|e| e.dst_node == node)
+                .map(|e| e.id)
+                .collect::<Vec<_>>();
+
+            for edge in inputs {
+                context.disconnect_by_edge_id(edge);
+            }
+
+            let outputs = context
+                .node_info(node)
+                .map(|entry| entry.info.channel_config.num_outputs.get())
+                .unwrap_or(0);
+
+            let meter = tail
+                .threshold
+                .zip(NonZeroChannelCount::new(outputs))
+                .and_then(|(threshold, channels)| {
+                    let meter =
+                        context.add_node(MeterNode::default(), Some(MeterConfig { channels }));
+                    let ports: Vec<_> = (0..outputs).map(|i| (i, i)).collect();
+                    if let Err(e) = context.connect(node, meter, &ports, false) {
+                        warn!("failed to meter ringing node: {e:?}");
+                        let _ = context.remove_node(meter);
+                        return None;
+                    }
+
+                    let state = context.node_state::<MeterState>(meter)?.clone();
+                    state.mark_unmeasured();
+                    Some((meter, state, threshold.decibels()))
+                });
+
+            removals.ringing.push(RingingNode {
+                node,
+                deadline: now + tail.max,
+                meter,
+            });
+        }
+
+        removals.ringing.retain(|ringing| {
+            if ringing.deadline > now && !ringing.is_silent() {
+                return true;
+            }
+
+            if context.remove_node(ringing.node).is_err() {
+                error!("attempted to remove non-existent or invalid node from audio graph");
+            }
+            if let Some((meter, ..)) = &ringing.meter {
+                let _ = context.remove_node(*meter);
+            }
+
+            false
+        });
+
         let range_to_render = InstantSeconds(0.0)..now + lookahead.0;
         for (node_entity, node, mut events, timestamp) in nodes.iter_mut() {
             for event in events.queue.drain(..) {
@@ -726,14 +1075,9 @@ pub(crate) fn flush_events(
 
         match result {
             Err(UpdateError::StreamStoppedUnexpectedly(e)) => {
-                // For now, we'll assume this is always due to a device becoming unavailable.
-                // As such, we'll attempt a reinitialization.
                 warn!("Audio stream stopped: {e:?}");
 
-                // First, we'll want to make sure the devices are up-to-date.
-                commands.trigger(crate::configuration::FetchAudioIoEvent);
-                // Then, we'll attempt a restart.
-                commands.trigger(crate::configuration::RestartAudioEvent);
+                crate::configuration::recover_stream(&mut commands);
             }
             Err(e) => {
                 error!("graph error: {e:?}");
@@ -748,7 +1092,7 @@ mod test {
     use super::*;
     use crate::{
         prelude::*,
-        test::{prepare_app, run},
+        test::{prepare_app, run, run_until},
     };
 
     #[derive(Component)]
@@ -825,4 +1169,135 @@ mod test {
             },
         );
     }
+
+    #[test]
+    fn test_pre_flush() {
+        #[derive(Resource, Default)]
+        struct Observed(usize);
+
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((VolumeNode::default(), TestMarker));
+        });
+
+        app.init_resource::<Observed>().add_systems(
+            Last,
+            (|events: Single<&mut AudioEvents, With<TestMarker>>,
+              mut observed: ResMut<Observed>| {
+                let mut events = events.into_inner();
+                observed.0 += events.queued().len();
+                events.retain_queued(|_| false);
+            })
+            .in_set(SeedlingSystems::PreFlush),
+        );
+
+        run(
+            &mut app,
+            |mut q: Query<&mut VolumeNode, With<TestMarker>>| {
+                q.single_mut().unwrap().volume = Volume::Decibels(-6.0);
+            },
+        );
+        app.update();
+
+        assert_eq!(app.world().resource::<Observed>().0, 1);
+        run(
+            &mut app,
+            |events: Single<&AudioEvents, With<TestMarker>>| {
+                assert!(events.queued().is_empty());
+            },
+        );
+    }
+
+    #[test]
+    fn test_registered_nodes() {
+        let mut app = prepare_app(|| {});
+
+        run(&mut app, |nodes: Res<RegisteredNodes>| {
+            let volume = nodes.get::<VolumeNode>().unwrap();
+            assert!(volume.diffing);
+            assert_eq!(volume.configuration.id, TypeId::of::<VolumeNodeConfig>());
+
+            let stereo_to_mono = nodes.get::<StereoToMonoNode>().unwrap();
+            assert!(!stereo_to_mono.diffing);
+
+            let sampler_states = nodes.states::<SamplerNode>();
+            assert_eq!(sampler_states.len(), 1);
+            assert_eq!(
+                sampler_states[0].id,
+                TypeId::of::<firewheel::nodes::sampler::SamplerState>()
+            );
+
+            assert!(nodes.states::<VolumeNode>().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_despawn_with_tail() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn(VolumeNode::default());
+            commands.spawn((
+                VolumeNode::default(),
+                DespawnWithTail::new(DurationSeconds(60.0)),
+            ));
+        });
+
+        let total_nodes = |app: &mut App| {
+            run(app, |mut context: ResMut<AudioContext>| {
+                context.with(|context| context.nodes().len())
+            })
+        };
+
+        // 2 + input and output
+        assert_eq!(total_nodes(&mut app), 4);
+
+        run(
+            &mut app,
+            |q: Query<Entity, With<VolumeNode>>, mut commands: Commands| {
+                for entity in &q {
+                    commands.entity(entity).despawn();
+                }
+            },
+        );
+
+        app.update();
+
+        // only the node with a tail should remain
+        assert_eq!(total_nodes(&mut app), 3);
+    }
+
+    #[test]
+    fn test_despawn_below_threshold() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            let tail =
+                DespawnWithTail::new(DurationSeconds(60.0)).with_threshold(Volume::Decibels(-60.0));
+
+            commands.spawn((VolumeNode::default(), tail));
+            commands.spawn((crate::prelude::OscillatorNode::default(), tail));
+        });
+
+        run(
+            &mut app,
+            |q: Query<Entity, With<DespawnWithTail>>, mut commands: Commands| {
+                for entity in &q {
+                    commands.entity(entity).despawn();
+                }
+            },
+        );
+
+        let total_nodes = |app: &mut App| {
+            run(app, |mut context: ResMut<AudioContext>| {
+                context.with(|context| context.nodes().len())
+            })
+        };
+
+        // the silent node is removed well before its maximum tail,
+        // while the oscillator and its meter keep ringing
+        run_until(&mut app, |mut context: ResMut<AudioContext>| {
+            context.with(|context| context.nodes().len() == 4)
+        });
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(total_nodes(&mut app), 4);
+    }
 }
@@ -0,0 +1,240 @@
+//! Muting and soloing buses and pools.
+//!
+//! [`Mute`] silences a [`VolumeNode`], while [`Solo`] silences every
+//! labelled bus and pool that isn't routed to or from a soloed node.
+//! Both follow the semantics of a typical mixing desk, making them
+//! a natural fit for debug tooling and mixer UIs.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! fn mute_music(music: Single<Entity, With<SamplerPool<MusicPool>>>, mut commands: Commands) {
+//!     // Silence music while debugging.
+//!     commands.entity(*music).insert(Mute);
+//! }
+//!
+//! fn solo_sfx(sfx: Single<Entity, With<SfxBus>>, mut commands: Commands) {
+//!     // Only the effects bus, the sources feeding it,
+//!     // and the buses it feeds will be heard.
+//!     commands.entity(*sfx).insert(Solo);
+//! }
+//! ```
+//!
+//! Changes are applied with a short fade to avoid clicks. Nodes are
+//! silenced by a dedicated gain stage after the node rather than through
+//! its own [`VolumeNode`], so its volume can be adjusted freely while
+//! silenced, and muting composes with [`AudioDomain`]s and loudness targets.
+//!
+//! [`AudioDomain`]: super::domain::AudioDomain
+
+use super::{
+    FirewheelNode,
+    gain::{GainStage, update_gain_stages},
+    label::NodeLabels,
+};
+use crate::{SeedlingSystems, pool::label::PoolLabelContainer, prelude::AudioContext};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashSet;
+use firewheel::{clock::DurationSeconds, node::NodeID, nodes::volume::VolumeNode};
+
+/// The duration of the fade applied when a node is silenced or restored.
+const MUTE_FADE: DurationSeconds = DurationSeconds(0.05);
+
+/// Silence an entity's [`VolumeNode`].
+///
+/// See the [module docs][self] for details.
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct Mute;
+
+/// Silence every labelled bus and pool that isn't routed to or from this node.
+///
+/// Multiple nodes can be soloed at once. [`Mute`] takes precedence,
+/// so a muted node stays silent even when soloed.
+///
+/// See the [module docs][self] for details.
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct Solo;
+
+pub(crate) struct MutePlugin;
+
+impl Plugin for MutePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            resolve_mute_solo
+                .after(SeedlingSystems::Pool)
+                .before(update_gain_stages),
+        );
+    }
+}
+
+/// Collect every node connected to `soloed` through the audio graph.
+///
+/// Routing in either direction counts, so a soloed bus keeps both
+/// its sources and its destinations audible.
+fn solo_paths(soloed: &[NodeID], context: &mut AudioContext) -> HashSet<NodeID> {
+    let edges: Vec<_> = context.with(|context| {
+        context
+            .edges()
+            .iter()
+            .map(|e| (e.src_node, e.dst_node))
+            .collect()
+    });
+
+    let mut audible: HashSet<_> = soloed.iter().copied().collect();
+
+    for forward in [true, false] {
+        let mut stack = soloed.to_vec();
+        while let Some(node) = stack.pop() {
+            for &(src, dst) in &edges {
+                let (from, to) = if forward { (src, dst) } else { (dst, src) };
+                if from == node && audible.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+    }
+
+    audible
+}
+
+fn resolve_mute_solo(
+    mut nodes: Query<
+        (Entity, &FirewheelNode, Has<Mute>, Option<&mut GainStage>),
+        (
+            With<VolumeNode>,
+            Or<(
+                With<NodeLabels>,
+                With<PoolLabelContainer>,
+                With<Mute>,
+                With<Solo>,
+                With<GainStage>,
+            )>,
+        ),
+    >,
+    soloed: Query<&FirewheelNode, With<Solo>>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    let soloed: Vec<_> = soloed.iter().map(|node| node.0).collect();
+    let audible = (!soloed.is_empty()).then(|| solo_paths(&soloed, &mut context));
+
+    for (entity, node, muted, stage) in &mut nodes {
+        let silent = muted || audible.as_ref().is_some_and(|a| !a.contains(&node.0));
+
+        match stage {
+            Some(mut stage) if stage.silenced() != silent => {
+                stage.set_silenced(silent, MUTE_FADE);
+            }
+            None if silent => {
+                let mut stage = GainStage::default();
+                stage.set_silenced(true, MUTE_FADE);
+                commands.entity(entity).insert(stage);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        node::gain::GainStageNode,
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::prelude::*;
+
+    #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct BusA;
+
+    #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct BusB;
+
+    /// The amplitude of `entity`'s gain stage after any fades complete.
+    fn faded_amp(app: &mut App, entity: Entity) -> f32 {
+        run(
+            app,
+            move |stages: Query<&GainStageNode>,
+                  nodes: Query<(&VolumeNode, &AudioEvents)>,
+                  time: Res<Time<Audio>>| {
+                let Ok(stage) = stages.get(entity) else {
+                    return 1.0;
+                };
+
+                let (volume, events) = nodes.get(stage.node()).unwrap();
+                events
+                    .get_value_at(time.now() + DurationSeconds(1.0), volume)
+                    .volume
+                    .amp()
+            },
+        )
+    }
+
+    #[test]
+    fn test_mute() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+            commands
+                .spawn((VolumeNode::default(), BusA, Mute))
+                .connect(MainBus);
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        let bus = run(&mut app, |bus: Single<Entity, With<BusA>>| *bus);
+        assert_eq!(faded_amp(&mut app, bus), 0.0);
+
+        // the bus's own volume is free to change while muted
+        run(&mut app, move |mut volumes: Query<&mut VolumeNode>| {
+            volumes.get_mut(bus).unwrap().volume = Volume::Decibels(-6.0);
+        });
+
+        app.world_mut().entity_mut(bus).remove::<Mute>();
+        app.update();
+
+        assert_eq!(faded_amp(&mut app, bus), 1.0);
+        run(&mut app, move |volumes: Query<&VolumeNode>| {
+            assert_eq!(volumes.get(bus).unwrap().volume, Volume::Decibels(-6.0));
+        });
+    }
+
+    #[test]
+    fn test_solo() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+            commands
+                .spawn((VolumeNode::default(), BusA, Solo))
+                .connect(MainBus);
+            commands
+                .spawn((VolumeNode::default(), BusB))
+                .connect(MainBus);
+        });
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let (a, b, main) = run(
+            &mut app,
+            |a: Single<Entity, With<BusA>>,
+             b: Single<Entity, With<BusB>>,
+             main: Single<Entity, With<MainBus>>| (*a, *b, *main),
+        );
+
+        // the main bus is fed by the soloed bus, so it stays audible
+        assert_eq!(faded_amp(&mut app, a), 1.0);
+        assert_eq!(faded_amp(&mut app, b), 0.0);
+        assert_eq!(faded_amp(&mut app, main), 1.0);
+    }
+}
@@ -0,0 +1,369 @@
+//! Reducing effect quality under DSP load.
+//!
+//! When the audio thread runs out of time, the result is dropouts,
+//! which are far more noticeable than slightly simpler effects.
+//! The [`DspLoadGovernor`] watches the [`DspLoad`] and, when it stays
+//! above a threshold, progressively lowers the [`DspQuality`] of every
+//! effect marked with [`ScaleQuality`]. Once the load falls comfortably
+//! below the threshold, full quality is restored one step at a time.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, node::quality::*};
+//! fn setup(mut commands: Commands) {
+//!     commands.insert_resource(DspLoadGovernor::default());
+//!
+//!     // This reverb may be disabled entirely under load...
+//!     commands.spawn((ConvolutionNode::default(), ScaleQuality::default()));
+//!
+//!     // ...while this chorus is never reduced past `Reduced`.
+//!     commands.spawn((
+//!         ChorusNode::default(),
+//!         ScaleQuality {
+//!             floor: DspQuality::Reduced,
+//!         },
+//!     ));
+//! }
+//!
+//! // `bevy_seedling` can't measure the processing time of every
+//! // backend, so the load is reported by the application.
+//! fn report_load(mut load: ResMut<DspLoad>, stats: Res<MyBackendStats>) {
+//!     load.0 = stats.process_time / stats.block_time;
+//! }
+//! # #[derive(Resource)]
+//! # struct MyBackendStats { process_time: f32, block_time: f32 }
+//! ```
+//!
+//! Custom nodes can participate by implementing [`QualityScalable`]
+//! and registering themselves with [`RegisterQualityScalable`].
+
+use crate::SeedlingSystems;
+use bevy_app::prelude::*;
+use bevy_ecs::{component::Mutable, prelude::*};
+use bevy_time::{Real, Time};
+use core::time::Duration;
+
+/// The quality level of scalable effects.
+///
+/// Levels are ordered from the most to the least expensive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum DspQuality {
+    /// Effects run as configured.
+    #[default]
+    Full,
+    /// Effects use cheaper settings that sound similar.
+    Reduced,
+    /// Effects use their cheapest settings, which may
+    /// disable them entirely.
+    Minimal,
+}
+
+impl DspQuality {
+    fn lower(self) -> Self {
+        match self {
+            Self::Full => Self::Reduced,
+            _ => Self::Minimal,
+        }
+    }
+
+    fn raise(self) -> Self {
+        match self {
+            Self::Minimal => Self::Reduced,
+            _ => Self::Full,
+        }
+    }
+}
+
+/// A node whose parameters can trade quality for processing time.
+pub trait QualityScalable: Component<Mutability = Mutable> + Clone {
+    /// Returns these parameters adjusted for `quality`.
+    ///
+    /// For [`DspQuality::Full`], this should return the parameters
+    /// unchanged. The full-quality parameters are always retained
+    /// and restored when the load drops.
+    fn scale_quality(&self, quality: DspQuality) -> Self;
+}
+
+/// Marks an effect as eligible for quality reduction.
+///
+/// While an effect is reduced, its full-quality parameters are
+/// held separately, and any changes made to the effect in the
+/// meantime are overwritten when quality is restored.
+#[derive(Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ScaleQuality {
+    /// The lowest quality this effect may be reduced to.
+    ///
+    /// By default, this is [`DspQuality::Minimal`].
+    pub floor: DspQuality,
+}
+
+impl Default for ScaleQuality {
+    fn default() -> Self {
+        Self {
+            floor: DspQuality::Minimal,
+        }
+    }
+}
+
+/// The fraction of each block's time budget spent processing audio.
+///
+/// A value of 1 means the audio thread is just barely keeping up.
+/// This is not measured by `bevy_seedling`, so it should be
+/// updated by the application from its backend or profiler.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Resource)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DspLoad(pub f32);
+
+/// Lowers the quality of designated effects when the [`DspLoad`] is high.
+///
+/// The governor is inactive until this resource is inserted.
+/// Each time the load stays above `threshold` for `hold`, the quality
+/// drops by one level. Each time it stays below `threshold - hysteresis`
+/// for `hold`, the quality rises by one level. A [`DspQualityChanged`]
+/// event is triggered for every change.
+#[derive(Debug, Clone, Resource)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DspLoadGovernor {
+    /// The load above which quality is reduced.
+    ///
+    /// By default, this is 0.75.
+    pub threshold: f32,
+    /// How far below `threshold` the load must fall
+    /// before quality is restored.
+    ///
+    /// By default, this is 0.2.
+    pub hysteresis: f32,
+    /// How long the load must stay past a boundary
+    /// before the quality changes.
+    ///
+    /// By default, this is half a second.
+    pub hold: Duration,
+    quality: DspQuality,
+    /// When the load last crossed a boundary.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    crossed: Option<Duration>,
+}
+
+impl Default for DspLoadGovernor {
+    fn default() -> Self {
+        Self {
+            threshold: 0.75,
+            hysteresis: 0.2,
+            hold: Duration::from_millis(500),
+            quality: DspQuality::Full,
+            crossed: None,
+        }
+    }
+}
+
+impl DspLoadGovernor {
+    /// The current quality level.
+    pub fn quality(&self) -> DspQuality {
+        self.quality
+    }
+
+    /// Returns the quality after observing `load` at `now`.
+    fn observe(&mut self, load: f32, now: Duration) -> DspQuality {
+        let target = if load > self.threshold {
+            self.quality.lower()
+        } else if load < self.threshold - self.hysteresis {
+            self.quality.raise()
+        } else {
+            self.quality
+        };
+
+        if target == self.quality {
+            self.crossed = None;
+            return self.quality;
+        }
+
+        let crossed = *self.crossed.get_or_insert(now);
+        if now.saturating_sub(crossed) >= self.hold {
+            self.quality = target;
+            // Each further step waits for another full hold.
+            self.crossed = None;
+        }
+
+        self.quality
+    }
+}
+
+/// Triggered globally when the [`DspLoadGovernor`] changes quality.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DspQualityChanged {
+    /// The previous quality.
+    pub previous: DspQuality,
+    /// The new quality.
+    pub current: DspQuality,
+}
+
+/// The full-quality parameters of a reduced effect.
+#[derive(Component)]
+struct QualityBaseline<T> {
+    params: T,
+    quality: DspQuality,
+}
+
+/// Register a [`QualityScalable`] node with the [`DspLoadGovernor`].
+pub trait RegisterQualityScalable {
+    /// Allow `T` to be scaled with [`ScaleQuality`].
+    fn register_quality_scalable<T: QualityScalable>(&mut self) -> &mut Self;
+}
+
+impl RegisterQualityScalable for App {
+    fn register_quality_scalable<T: QualityScalable>(&mut self) -> &mut Self {
+        self.add_systems(
+            Last,
+            scale_quality::<T>
+                .after(update_governor)
+                .before(SeedlingSystems::Acquire)
+                .run_if(resource_exists::<DspLoadGovernor>),
+        )
+        .add_observer(restore_unscaled::<T>)
+    }
+}
+
+fn update_governor(
+    governor: Option<ResMut<DspLoadGovernor>>,
+    load: Res<DspLoad>,
+    time: Res<Time<Real>>,
+    mut commands: Commands,
+) {
+    let Some(mut governor) = governor else {
+        return;
+    };
+
+    let previous = governor.quality;
+    let current = governor
+        .bypass_change_detection()
+        .observe(load.0, time.elapsed());
+
+    if current != previous {
+        governor.set_changed();
+        commands.trigger(DspQualityChanged { previous, current });
+    }
+}
+
+fn scale_quality<T: QualityScalable>(
+    governor: Res<DspLoadGovernor>,
+    mut nodes: Query<(
+        Entity,
+        &mut T,
+        &ScaleQuality,
+        Option<&mut QualityBaseline<T>>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut node, scale, baseline) in &mut nodes {
+        let quality = governor.quality.min(scale.floor);
+
+        match baseline {
+            Some(mut baseline) => {
+                if baseline.quality == quality {
+                    continue;
+                }
+
+                if quality == DspQuality::Full {
+                    *node = baseline.params.clone();
+                    commands.entity(entity).remove::<QualityBaseline<T>>();
+                } else {
+                    *node = baseline.params.scale_quality(quality);
+                    baseline.quality = quality;
+                }
+            }
+            None if quality != DspQuality::Full => {
+                let params = node.clone();
+                *node = params.scale_quality(quality);
+                commands
+                    .entity(entity)
+                    .insert(QualityBaseline { params, quality });
+            }
+            None => {}
+        }
+    }
+}
+
+/// Restore an effect's full quality when it's no longer scaled.
+fn restore_unscaled<T: QualityScalable>(
+    trigger: On<Remove, ScaleQuality>,
+    mut nodes: Query<(&mut T, &QualityBaseline<T>)>,
+    mut commands: Commands,
+) {
+    let Ok((mut node, baseline)) = nodes.get_mut(trigger.event_target()) else {
+        return;
+    };
+
+    *node = baseline.params.clone();
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<QualityBaseline<T>>();
+}
+
+pub(crate) struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        use crate::nodes::{chorus::ChorusNode, convolution::ConvolutionNode, phaser::PhaserNode};
+
+        app.init_resource::<DspLoad>()
+            .add_systems(Last, update_governor.before(SeedlingSystems::Acquire))
+            .register_quality_scalable::<ChorusNode>()
+            .register_quality_scalable::<PhaserNode>()
+            .register_quality_scalable::<ConvolutionNode>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        nodes::chorus::ChorusNode,
+        test::{prepare_app, run},
+    };
+
+    #[test]
+    fn test_hysteresis() {
+        let mut governor = DspLoadGovernor::default();
+        let ms = Duration::from_millis;
+
+        // brief spikes are ignored
+        assert_eq!(governor.observe(0.9, ms(0)), DspQuality::Full);
+        assert_eq!(governor.observe(0.5, ms(400)), DspQuality::Full);
+
+        // sustained load lowers quality one step at a time
+        assert_eq!(governor.observe(0.9, ms(1000)), DspQuality::Full);
+        assert_eq!(governor.observe(0.9, ms(1500)), DspQuality::Reduced);
+        assert_eq!(governor.observe(0.9, ms(1600)), DspQuality::Reduced);
+        assert_eq!(governor.observe(0.9, ms(2100)), DspQuality::Minimal);
+
+        // loads just under the threshold don't restore quality
+        assert_eq!(governor.observe(0.7, ms(5000)), DspQuality::Minimal);
+        assert_eq!(governor.observe(0.5, ms(6000)), DspQuality::Minimal);
+        assert_eq!(governor.observe(0.5, ms(6500)), DspQuality::Reduced);
+    }
+
+    #[test]
+    fn test_scale_and_restore() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.insert_resource(DspLoadGovernor::default());
+            commands.spawn((
+                ChorusNode {
+                    voices: 4,
+                    ..Default::default()
+                },
+                ScaleQuality::default(),
+            ));
+        });
+
+        let set_quality = |app: &mut App, quality| {
+            app.world_mut().resource_mut::<DspLoadGovernor>().quality = quality;
+            app.update();
+            run(app, |chorus: Single<&ChorusNode>| chorus.voices)
+        };
+
+        assert!(set_quality(&mut app, DspQuality::Minimal) < 4);
+        assert_eq!(set_quality(&mut app, DspQuality::Full), 4);
+    }
+}
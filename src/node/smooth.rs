@@ -0,0 +1,141 @@
+//! Rate-limited parameter diffing for continuously driven nodes.
+
+use bevy_ecs::prelude::*;
+use core::time::Duration;
+use firewheel::clock::InstantSeconds;
+
+/// Limit how often a node's parameters are sent to the audio graph.
+///
+/// Parameters driven every frame, like a filter cutoff that tracks
+/// distance or a pitch that tracks engine RPM, would otherwise send
+/// a patch every frame. With [`SmoothParams`], changes are collected
+/// and only the newest value is sent, at most once per `interval`.
+/// Firewheel's nodes smooth their parameters internally, so each
+/// update glides to the new target rather than stepping.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::smooth::SmoothParams};
+/// # use core::time::Duration;
+/// #[derive(Component)]
+/// struct EngineFilter;
+///
+/// fn spawn_engine(mut commands: Commands) {
+///     commands.spawn((
+///         LowPassNode::default(),
+///         EngineFilter,
+///         SmoothParams::new(Duration::from_millis(50)),
+///     ));
+/// }
+///
+/// fn track_rpm(mut filter: Single<&mut LowPassNode, With<EngineFilter>>, time: Res<Time>) {
+///     // Written every frame, but only sent every 50 milliseconds.
+///     filter.frequency = 800.0 + 400.0 * time.elapsed_secs().sin();
+/// }
+/// ```
+///
+/// Intervals close to a node's own smoothing time, typically
+/// a few tens of milliseconds, sound continuous. For sample effects,
+/// insert [`SmoothParams`] alongside the effect on the sample player.
+///
+/// Scheduled events and explicitly timestamped changes aren't limited.
+#[derive(Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SmoothParams {
+    /// The minimum time between updates.
+    pub interval: Duration,
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    last_sent: Option<InstantSeconds>,
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pending: bool,
+}
+
+impl SmoothParams {
+    /// Send parameter changes at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: None,
+            pending: false,
+        }
+    }
+
+    /// Returns whether the parameters should be diffed at `now`.
+    ///
+    /// Changes that arrive too early are held until the interval
+    /// elapses, even if the parameters stop changing in the meantime.
+    pub(crate) fn poll(&mut self, changed: bool, now: InstantSeconds) -> bool {
+        self.pending |= changed;
+        if !self.pending {
+            return false;
+        }
+
+        if let Some(last) = self.last_sent {
+            if now.0 - last.0 < self.interval.as_secs_f64() {
+                return false;
+            }
+        }
+
+        self.last_sent = Some(now);
+        self.pending = false;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        node::Baseline,
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy_app::App;
+
+    #[test]
+    fn test_poll() {
+        let mut smooth = SmoothParams::new(Duration::from_millis(100));
+
+        assert!(!smooth.poll(false, InstantSeconds(0.0)));
+        assert!(smooth.poll(true, InstantSeconds(0.0)));
+
+        // held until the interval elapses
+        assert!(!smooth.poll(true, InstantSeconds(0.05)));
+        assert!(!smooth.poll(false, InstantSeconds(0.08)));
+        assert!(smooth.poll(false, InstantSeconds(0.1)));
+
+        assert!(!smooth.poll(false, InstantSeconds(0.5)));
+    }
+
+    #[test]
+    fn test_throttled_events() {
+        #[derive(Component)]
+        struct Throttled;
+
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((
+                VolumeNode::default(),
+                Throttled,
+                SmoothParams::new(Duration::from_secs(3600)),
+            ));
+        });
+
+        let queued = |app: &mut App, volume: f32| {
+            run(
+                app,
+                move |mut q: Single<&mut VolumeNode, With<Throttled>>| {
+                    q.volume = Volume::Linear(volume);
+                },
+            );
+            app.update();
+            run(app, |q: Single<&Baseline<VolumeNode>, With<Throttled>>| {
+                q.0.volume
+            })
+        };
+
+        // spawning counts as the first change, so later changes are held
+        let initial = VolumeNode::default().volume;
+        assert_eq!(queued(&mut app, 0.5), initial);
+        assert_eq!(queued(&mut app, 0.25), initial);
+    }
+}
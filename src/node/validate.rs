@@ -0,0 +1,247 @@
+//! Dry-run validation for custom audio nodes.
+//!
+//! A misbehaving node usually isn't discovered until it's
+//! inserted into a running graph, where a panic takes down the
+//! audio stream and a NaN silently corrupts everything downstream.
+//! [`ValidateNode::validate_node`] catches these problems at startup
+//! instead by constructing the node's processor in a throwaway offline
+//! graph and running a few blocks of silence through it.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, node::validate::ValidateNode};
+//! # fn plugin(app: &mut App) -> Result {
+//! app.register_node::<VolumeNode>()
+//!     .validate_node::<VolumeNode>()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The checks are only as thorough as a block of silence with default
+//! parameters, so they complement rather than replace your own tests.
+
+use crate::{
+    context::offline::{OfflineBackend, OfflineConfig, OfflineRenderer},
+    error::SeedlingError,
+};
+use bevy_app::prelude::*;
+use firewheel::{
+    FirewheelConfig, FirewheelCtx, channel_config::MAX_CHANNELS, clock::DurationSeconds,
+    node::AudioNode,
+};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// The number of blocks processed during a dry run.
+///
+/// Graph changes are applied at the start of a block, so the
+/// first block may not include the node at all.
+const DRY_RUN_BLOCKS: u32 = 4;
+
+/// Validate audio nodes with a dry run.
+///
+/// See the [module docs][self] for usage.
+pub trait ValidateNode {
+    /// Construct `T`'s processor with its default parameters and
+    /// configuration, and process a few blocks of silence.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SeedlingError::InvalidNode`] if the node panics,
+    /// outputs non-finite samples, or declares an invalid channel configuration.
+    fn validate_node<T>(&mut self) -> Result<&mut Self, SeedlingError>
+    where
+        T: AudioNode + Default + Clone + 'static;
+}
+
+impl ValidateNode for App {
+    fn validate_node<T>(&mut self) -> Result<&mut Self, SeedlingError>
+    where
+        T: AudioNode + Default + Clone + 'static,
+    {
+        dry_run(&T::default(), T::Configuration::default())?;
+
+        Ok(self)
+    }
+}
+
+/// Construct `node`'s processor in an offline graph and process
+/// a few blocks of silence.
+///
+/// This is the check performed by [`ValidateNode::validate_node`],
+/// for nodes without defaults or with several configurations worth testing.
+pub fn dry_run<T: AudioNode + Clone + 'static>(
+    node: &T,
+    config: T::Configuration,
+) -> Result<(), SeedlingError> {
+    let invalid = |reason: String| SeedlingError::InvalidNode {
+        ty: core::any::type_name::<T>(),
+        reason,
+    };
+
+    let renderer = OfflineRenderer::default();
+    let stream_config = OfflineConfig::new(renderer.clone());
+    let block_frames = stream_config.block_frames.get();
+    let sample_rate = stream_config.sample_rate.get();
+
+    let mut context = FirewheelCtx::<OfflineBackend>::new(FirewheelConfig::default());
+    context
+        .start_stream(stream_config)
+        .map_err(|e| invalid(format!("failed to start offline stream: {e:?}")))?;
+
+    let node_id = catch_unwind(AssertUnwindSafe(|| {
+        context.add_node(node.clone(), Some(config))
+    }))
+    .map_err(|e| {
+        invalid(format!(
+            "panicked during construction: {}",
+            panic_message(&*e)
+        ))
+    })?;
+
+    let channels = context
+        .node_info(node_id)
+        .map(|entry| entry.info.channel_config)
+        .ok_or_else(|| invalid("node was not added to the graph".into()))?;
+
+    let inputs = channels.num_inputs.get();
+    let outputs = channels.num_outputs.get();
+
+    if inputs == 0 && outputs == 0 {
+        return Err(invalid("declares neither inputs nor outputs".into()));
+    }
+
+    if inputs as usize > MAX_CHANNELS || outputs as usize > MAX_CHANNELS {
+        return Err(invalid(format!(
+            "declares {inputs} inputs and {outputs} outputs, \
+            but at most {MAX_CHANNELS} channels are supported"
+        )));
+    }
+
+    // Fold every output into the stereo graph output so
+    // nothing the node produces goes unchecked.
+    if outputs > 0 {
+        let graph_out = context.graph_out_node_id();
+        let ports: Vec<_> = (0..outputs).map(|i| (i, i % 2)).collect();
+        context
+            .connect(node_id, graph_out, &ports, false)
+            .map_err(|e| invalid(format!("failed to connect outputs: {e:?}")))?;
+    }
+
+    catch_unwind(AssertUnwindSafe(|| -> Result<(), SeedlingError> {
+        context
+            .update()
+            .map_err(|e| invalid(format!("failed to compile graph: {e:?}")))?;
+
+        renderer.render(DurationSeconds(
+            (DRY_RUN_BLOCKS * block_frames) as f64 / sample_rate as f64,
+        ));
+
+        Ok(())
+    }))
+    .map_err(|e| invalid(format!("panicked while processing: {}", panic_message(&*e))))??;
+
+    let output = renderer.take_output();
+    if let Some(index) = output.iter().position(|sample| !sample.is_finite()) {
+        return Err(invalid(format!(
+            "produced a non-finite sample ({}) from silent input at frame {}",
+            output[index],
+            index / 2,
+        )));
+    }
+
+    Ok(())
+}
+
+fn panic_message(payload: &(dyn core::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use firewheel::{
+        channel_config::{ChannelConfig, ChannelCount},
+        event::ProcEvents,
+        node::{
+            AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig, ProcBuffers,
+            ProcExtra, ProcInfo, ProcessStatus,
+        },
+        nodes::volume::{VolumeNode, VolumeNodeConfig},
+    };
+
+    #[derive(Debug, Default, Clone)]
+    struct FaultyNode {
+        panic: bool,
+    }
+
+    impl AudioNode for FaultyNode {
+        type Configuration = EmptyConfig;
+
+        fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+            AudioNodeInfo::new()
+                .debug_name("faulty")
+                .channel_config(ChannelConfig {
+                    num_inputs: ChannelCount::STEREO,
+                    num_outputs: ChannelCount::STEREO,
+                })
+        }
+
+        fn construct_processor(
+            &self,
+            _: &Self::Configuration,
+            _: ConstructProcessorContext,
+        ) -> impl AudioNodeProcessor {
+            FaultyProcessor { panic: self.panic }
+        }
+    }
+
+    struct FaultyProcessor {
+        panic: bool,
+    }
+
+    impl AudioNodeProcessor for FaultyProcessor {
+        fn process(
+            &mut self,
+            proc_info: &ProcInfo,
+            ProcBuffers { outputs, .. }: ProcBuffers,
+            _: &mut ProcEvents,
+            _: &mut ProcExtra,
+        ) -> ProcessStatus {
+            if self.panic {
+                panic!("faulty processor");
+            }
+
+            for output in outputs.iter_mut() {
+                output[..proc_info.frames].fill(f32::NAN);
+            }
+
+            ProcessStatus::outputs_not_silent()
+        }
+    }
+
+    #[test]
+    fn test_dry_run() {
+        assert!(dry_run(&VolumeNode::default(), VolumeNodeConfig::default()).is_ok());
+
+        let error = dry_run(&FaultyNode { panic: false }, EmptyConfig).unwrap_err();
+        assert!(matches!(
+            error,
+            SeedlingError::InvalidNode { ty, .. } if ty.ends_with("FaultyNode")
+        ));
+
+        let error = dry_run(&FaultyNode { panic: true }, EmptyConfig).unwrap_err();
+        assert!(error.to_string().contains("faulty processor"));
+    }
+
+    #[test]
+    fn test_validate_node() {
+        let mut app = App::new();
+
+        assert!(app.validate_node::<VolumeNode>().is_ok());
+        assert!(app.validate_node::<FaultyNode>().is_err());
+    }
+}
@@ -0,0 +1,519 @@
+//! Automatic frequency slotting between two signals.
+
+use super::{
+    multiband::Biquad,
+    spectrum::{BAND_CENTERS, BAND_COUNT, OCTAVE_Q},
+};
+use crate::{
+    edge::{EdgeTarget, NodeMap, PendingConnections},
+    node::FirewheelNode,
+    prelude::AudioContext,
+};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount, MAX_CHANNELS, NonZeroChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, NodeID,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The range of octave bands an [`AutoEqDuckNode`] may cut.
+///
+/// Bands are included when their center frequency,
+/// one of [`BAND_CENTERS`], falls within the range.
+#[derive(Diff, Patch, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct BandRange {
+    /// The lowest band center, in hertz.
+    pub low: f32,
+    /// The highest band center, in hertz.
+    pub high: f32,
+}
+
+impl BandRange {
+    /// Create a new [`BandRange`].
+    pub fn new(low: f32, high: f32) -> Self {
+        Self { low, high }
+    }
+
+    fn contains(&self, frequency: f32) -> bool {
+        (self.low..=self.high).contains(&frequency)
+    }
+}
+
+impl Default for BandRange {
+    /// The 1 to 4 kHz range, where speech intelligibility lives.
+    fn default() -> Self {
+        Self::new(1000.0, 4000.0)
+    }
+}
+
+/// A dynamic EQ that carves space in one signal for another.
+///
+/// The observed signal is analyzed in octave bands. Wherever its
+/// energy rises above `threshold`, the adjusted signal is gently cut
+/// in the same bands, by up to `amount` decibels. Unlike broadband
+/// ducking with a [`CompressorNode`][crate::prelude::CompressorNode],
+/// the rest of the adjusted signal is left untouched, so music can
+/// stay full while dialogue remains intelligible.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(NodeLabel, PartialEq, Eq, Debug, Hash, Clone)]
+/// struct DialogueBus;
+///
+/// fn slot_dialogue(music: Single<Entity, With<SamplerPool<MusicPool>>>, mut commands: Commands) {
+///     let mut duck = AutoEqDuckNode::new(DialogueBus, *music);
+///     duck.amount = 4.0;
+///
+///     commands.spawn(duck);
+/// }
+/// ```
+///
+/// The adjusted node's outputs are routed through the
+/// [`AutoEqDuckNode`], and the observed node is connected to
+/// its sidechain inputs in addition to its existing connections.
+/// Despawning the [`AutoEqDuckNode`] restores the original routing.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(from_reflect = false))]
+#[require(PendingConnections)]
+pub struct AutoEqDuckNode {
+    /// The bands that may be cut.
+    ///
+    /// By default, this is 1 to 4 kHz.
+    pub bands: BandRange,
+    /// The maximum cut in each band, in decibels.
+    ///
+    /// By default, this is 6 dB.
+    pub amount: f32,
+    /// The level above which an observed band triggers a cut.
+    ///
+    /// Each decibel above the threshold cuts
+    /// the band by one decibel, up to `amount`.
+    ///
+    /// By default, this is -40 dB.
+    pub threshold: Volume,
+    /// How long it takes to apply a cut, in seconds.
+    ///
+    /// By default, this is 0.02s.
+    pub attack: f32,
+    /// How long it takes to release a cut, in seconds.
+    ///
+    /// By default, this is 0.3s.
+    pub release: f32,
+
+    #[diff(skip)]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub(crate) observe: EdgeTarget,
+    #[diff(skip)]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub(crate) adjust: EdgeTarget,
+}
+
+impl AutoEqDuckNode {
+    /// Cut `adjust` wherever `observe` is present.
+    pub fn new(observe: impl Into<EdgeTarget>, adjust: impl Into<EdgeTarget>) -> Self {
+        Self {
+            bands: BandRange::default(),
+            amount: 6.0,
+            threshold: Volume::Decibels(-40.0),
+            attack: 0.02,
+            release: 0.3,
+            observe: observe.into(),
+            adjust: adjust.into(),
+        }
+    }
+
+    /// The observed signal.
+    pub fn observe(&self) -> &EdgeTarget {
+        &self.observe
+    }
+
+    /// The adjusted signal.
+    pub fn adjust(&self) -> &EdgeTarget {
+        &self.adjust
+    }
+}
+
+/// [`AutoEqDuckNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct AutoEqDuckConfig {
+    /// The number of output channels.
+    ///
+    /// The node has `channels` main inputs followed
+    /// by `channels` sidechain inputs, so counts above
+    /// 32 are clamped.
+    pub channels: NonZeroChannelCount,
+}
+
+impl AutoEqDuckConfig {
+    /// The number of main inputs, leaving room for as many sidechain inputs.
+    fn clamped_channels(&self) -> ChannelCount {
+        ChannelCount::new(self.channels.get().get().min(MAX_CHANNELS as u32 / 2))
+            .unwrap_or(ChannelCount::STEREO)
+    }
+}
+
+impl Default for AutoEqDuckConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for AutoEqDuckNode {
+    type Configuration = AutoEqDuckConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("auto EQ duck")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::new(config.clamped_channels().get() * 2)
+                    .unwrap_or(ChannelCount::MAX),
+                num_outputs: config.clamped_channels(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let channels = config.clamped_channels().get() as usize;
+        let mut processor = AutoEqDuckProcessor {
+            params: self.clone(),
+            channels,
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            detectors: Default::default(),
+            energy: [0.0; BAND_COUNT],
+            cuts: [0.0; BAND_COUNT],
+            applied: [0.0; BAND_COUNT],
+            filters: vec![Default::default(); channels],
+        };
+        processor.update_detectors();
+
+        processor
+    }
+}
+
+struct AutoEqDuckProcessor {
+    params: AutoEqDuckNode,
+    channels: usize,
+    sample_rate: f32,
+    detectors: [Biquad; BAND_COUNT],
+    /// The sidechain energy of each band in the current block.
+    energy: [f32; BAND_COUNT],
+    /// The current cut of each band, in decibels.
+    cuts: [f32; BAND_COUNT],
+    /// The cuts the filters are currently configured for.
+    applied: [f32; BAND_COUNT],
+    filters: Vec<[Biquad; BAND_COUNT]>,
+}
+
+impl AutoEqDuckProcessor {
+    /// The center frequency of each band, kept below Nyquist.
+    fn centers(&self) -> [f32; BAND_COUNT] {
+        BAND_CENTERS.map(|center| center.min(self.sample_rate * 0.45))
+    }
+
+    fn update_detectors(&mut self) {
+        let centers = self.centers();
+        for (detector, center) in self.detectors.iter_mut().zip(centers) {
+            detector.set_band_pass(self.sample_rate, center, OCTAVE_Q);
+        }
+    }
+
+    /// Feed a single mono sidechain sample through the detectors.
+    #[inline]
+    fn detect(&mut self, sample: f32) {
+        let bands = self.detectors.iter_mut().zip(&mut self.energy);
+        for ((detector, energy), center) in bands.zip(BAND_CENTERS) {
+            if self.params.bands.contains(center) {
+                let level = detector.process(sample);
+                *energy += level * level;
+            }
+        }
+    }
+
+    /// Move each band's cut toward its target after `frames` detected frames.
+    fn update_cuts(&mut self, frames: usize) {
+        let threshold = 20.0 * self.params.threshold.amp().max(1e-9).log10();
+        let amount = self.params.amount.max(0.0);
+        let coefficient = |time: f32| {
+            if time <= 0.0 {
+                0.0
+            } else {
+                (-(frames as f32) / (time * self.sample_rate)).exp()
+            }
+        };
+        let (attack, release) = (
+            coefficient(self.params.attack),
+            coefficient(self.params.release),
+        );
+
+        let bands = self.detectors.iter_mut().zip(&mut self.energy);
+        for (((detector, energy), cut), center) in bands.zip(&mut self.cuts).zip(BAND_CENTERS) {
+            let target = if self.params.bands.contains(center) {
+                let level = 10.0 * (*energy / frames.max(1) as f32).max(1e-18).log10();
+                (level - threshold).clamp(0.0, amount)
+            } else {
+                detector.reset();
+                0.0
+            };
+            *energy = 0.0;
+
+            let coeff = if target > *cut { attack } else { release };
+            *cut = target + coeff * (*cut - target);
+
+            // Snap small cuts to zero so the filters can be bypassed.
+            if *cut < 1e-3 {
+                *cut = 0.0;
+            }
+        }
+    }
+
+    /// Reconfigure the filters whose cuts have changed.
+    fn update_filters(&mut self) {
+        let centers = self.centers();
+
+        for (band, (cut, applied)) in self.cuts.iter().zip(&mut self.applied).enumerate() {
+            if *cut == *applied {
+                continue;
+            }
+
+            for filters in &mut self.filters {
+                if *cut == 0.0 {
+                    filters[band].reset();
+                } else {
+                    filters[band].set_peaking(self.sample_rate, centers[band], OCTAVE_Q, -cut);
+                }
+            }
+            *applied = *cut;
+        }
+    }
+}
+
+impl AudioNodeProcessor for AutoEqDuckProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<AutoEqDuckNode>() {
+            self.params.apply(patch);
+        }
+
+        let (main, key) = inputs.split_at(self.channels);
+        let scale = 1.0 / self.channels as f32;
+        for frame in 0..proc_info.frames {
+            self.detect(key.iter().map(|k| k[frame]).sum::<f32>() * scale);
+        }
+        self.update_cuts(proc_info.frames);
+        self.update_filters();
+
+        if proc_info.in_silence_mask.all_channels_silent(self.channels) {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        for ((output, input), filters) in outputs.iter_mut().zip(main).zip(&mut self.filters) {
+            output[..proc_info.frames].copy_from_slice(&input[..proc_info.frames]);
+
+            for (filter, cut) in filters.iter_mut().zip(&self.cuts) {
+                if *cut == 0.0 {
+                    continue;
+                }
+
+                for sample in &mut output[..proc_info.frames] {
+                    *sample = filter.process(*sample);
+                }
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.update_detectors();
+        self.applied = [f32::NAN; BAND_COUNT];
+        self.update_filters();
+    }
+}
+
+fn resolve(
+    target: &EdgeTarget,
+    node_map: &NodeMap,
+    nodes: &Query<&FirewheelNode>,
+) -> Option<NodeID> {
+    match target {
+        EdgeTarget::Node(node) => Some(*node),
+        EdgeTarget::Entity(entity) => nodes.get(*entity).ok().map(|n| n.0),
+        EdgeTarget::Label(label) => nodes.get(*node_map.get(label)?).ok().map(|n| n.0),
+    }
+}
+
+/// Route the observed and adjusted nodes through their ducking EQs.
+///
+/// This runs every frame so that connections made to the
+/// adjusted node after the fact are also routed.
+pub(crate) fn route_auto_eq(
+    duckers: Query<(&AutoEqDuckNode, &AutoEqDuckConfig, &FirewheelNode)>,
+    nodes: Query<&FirewheelNode>,
+    node_map: Res<NodeMap>,
+    mut context: ResMut<AudioContext>,
+) {
+    let routes: Vec<_> = duckers
+        .iter()
+        .filter_map(|(ducker, config, node)| {
+            let observe = resolve(&ducker.observe, &node_map, &nodes)?;
+            let adjust = resolve(&ducker.adjust, &node_map, &nodes)?;
+
+            Some((observe, adjust, node.0, config.channels.get().get()))
+        })
+        .collect();
+
+    if routes.is_empty() {
+        return;
+    }
+
+    context.with(|context| {
+        for (observe, adjust, ducker, channels) in routes {
+            let edges: Vec<_> = context.edges().into_iter().cloned().collect();
+
+            if !edges
+                .iter()
+                .any(|e| e.src_node == observe && e.dst_node == ducker)
+            {
+                let ports: Vec<_> = (0..channels).map(|c| (c, c + channels)).collect();
+                if let Err(e) = context.connect(observe, ducker, &ports, false) {
+                    warn!("failed to connect observed node to auto EQ: {e:?}");
+                }
+            }
+
+            if !edges
+                .iter()
+                .any(|e| e.src_node == adjust && e.dst_node == ducker)
+            {
+                let ports: Vec<_> = (0..channels).map(|c| (c, c)).collect();
+                if let Err(e) = context.connect(adjust, ducker, &ports, false) {
+                    warn!("failed to connect adjusted node to auto EQ: {e:?}");
+                }
+            }
+
+            for edge in edges
+                .iter()
+                .filter(|e| e.src_node == adjust && e.dst_node != ducker)
+            {
+                context.disconnect_by_edge_id(edge.id);
+                if let Err(e) = context.connect(
+                    ducker,
+                    edge.dst_node,
+                    &[(edge.src_port, edge.dst_port)],
+                    false,
+                ) {
+                    warn!("failed to route connection through auto EQ: {e:?}");
+                }
+            }
+        }
+    });
+}
+
+pub(crate) fn remove_auto_eq(
+    trigger: On<Remove, AutoEqDuckNode>,
+    duckers: Query<(&AutoEqDuckNode, &FirewheelNode)>,
+    nodes: Query<&FirewheelNode>,
+    node_map: Res<NodeMap>,
+    mut context: ResMut<AudioContext>,
+) {
+    let Ok((ducker, node)) = duckers.get(trigger.event_target()) else {
+        return;
+    };
+    let Some(adjust) = resolve(&ducker.adjust, &node_map, &nodes) else {
+        return;
+    };
+    let ducker = node.0;
+
+    context.with(|context| {
+        let edges: Vec<_> = context
+            .edges()
+            .into_iter()
+            .filter(|e| e.src_node == ducker)
+            .cloned()
+            .collect();
+
+        for edge in edges {
+            // The destination may have been removed in the meantime.
+            if let Err(e) = context.connect(
+                adjust,
+                edge.dst_node,
+                &[(edge.src_port, edge.dst_port)],
+                false,
+            ) {
+                debug!("failed to restore edge for removed auto EQ: {e:?}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn processor(sample_rate: f32) -> AutoEqDuckProcessor {
+        let mut processor = AutoEqDuckProcessor {
+            params: AutoEqDuckNode::new(Entity::PLACEHOLDER, Entity::PLACEHOLDER),
+            channels: 1,
+            sample_rate,
+            detectors: Default::default(),
+            energy: [0.0; BAND_COUNT],
+            cuts: [0.0; BAND_COUNT],
+            applied: [0.0; BAND_COUNT],
+            filters: vec![Default::default()],
+        };
+        processor.update_detectors();
+
+        processor
+    }
+
+    #[test]
+    fn test_band_cuts() {
+        let sample_rate = 48000.0;
+        let mut processor = processor(sample_rate);
+
+        // A loud 2 kHz tone for half a second.
+        for block in 0..50 {
+            for frame in 0..480 {
+                let phase = (block * 480 + frame) as f32 * 2000.0 / sample_rate;
+                processor.detect((phase * core::f32::consts::TAU).sin() * 0.5);
+            }
+            processor.update_cuts(480);
+        }
+
+        let cuts = processor.cuts;
+        assert!((cuts[6] - 6.0).abs() < 0.1, "{cuts:?}");
+        assert!(cuts[5] > 0.0 && cuts[7] > 0.0, "{cuts:?}");
+
+        // Bands outside the range are never cut.
+        assert!(cuts[..5].iter().chain(&cuts[8..]).all(|c| *c == 0.0));
+
+        // Cuts are released in silence.
+        for _ in 0..400 {
+            for _ in 0..480 {
+                processor.detect(0.0);
+            }
+            processor.update_cuts(480);
+        }
+        assert_eq!(processor.cuts, [0.0; BAND_COUNT]);
+    }
+}
@@ -0,0 +1,203 @@
+//! A band-stop, or notch, filter.
+
+use crate::{nodes::multiband::Biquad, utils::timeline::Timeline};
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::ChannelConfig,
+    clock::DurationSeconds,
+    core::{channel_config::NonZeroChannelCount, node::ProcInfo},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcessStatus,
+    },
+};
+
+/// A band-stop filter.
+///
+/// This removes a band of frequencies around the center frequency
+/// while leaving the rest of the spectrum untouched. High *Q* values
+/// produce a narrow notch, which is useful for taming resonances.
+///
+/// ```
+/// # use bevy_seedling::prelude::*;
+/// # use bevy::prelude::*;
+/// fn remove_hum(mut commands: Commands, server: Res<AssetServer>) {
+///     commands
+///         .spawn(SamplePlayer::new(server.load("recording.wav")))
+///         .chain_node(BandStopNode::new(60.0, 10.0));
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+pub struct BandStopNode {
+    /// The center frequency in hertz.
+    pub frequency: Timeline<f32>,
+    /// The filter's *quality*.
+    ///
+    /// Higher values produce a narrower notch.
+    pub q: Timeline<f32>,
+}
+
+impl Default for BandStopNode {
+    fn default() -> Self {
+        Self {
+            frequency: Timeline::new(1000.0),
+            q: Timeline::new(1.0),
+        }
+    }
+}
+
+impl BandStopNode {
+    /// Create a new [`BandStopNode`] with an initial center frequency and quality.
+    ///
+    /// ```
+    /// # use bevy_seedling::prelude::*;
+    /// # use bevy::prelude::*;
+    /// # fn system(mut commands: Commands) {
+    /// commands.spawn(BandStopNode::new(1000.0, 1.0));
+    /// # }
+    /// ```
+    pub fn new(frequency: f32, q: f32) -> Self {
+        Self {
+            frequency: Timeline::new(frequency),
+            q: Timeline::new(q),
+        }
+    }
+}
+
+/// [`BandStopNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct BandStopConfig {
+    /// The number of channels to process.
+    ///
+    /// This node's input and output channel count will always match.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for BandStopConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for BandStopNode {
+    type Configuration = BandStopConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("band-stop filter")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let mut processor = BandStopProcessor {
+            params: self.clone(),
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            channels: vec![Biquad::default(); config.channels.get().get() as usize],
+        };
+        processor.set_coefficients(self.frequency.get(), self.q.get());
+
+        processor
+    }
+}
+
+struct BandStopProcessor {
+    params: BandStopNode,
+    sample_rate: f32,
+    channels: Vec<Biquad>,
+}
+
+impl BandStopProcessor {
+    fn set_coefficients(&mut self, frequency: f32, q: f32) {
+        let frequency = frequency.clamp(10.0, self.sample_rate * 0.45);
+        let q = q.max(0.01);
+
+        for channel in &mut self.channels {
+            channel.set_notch(self.sample_rate, frequency, q);
+        }
+    }
+}
+
+impl AudioNodeProcessor for BandStopProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<BandStopNode>() {
+            self.params.apply(patch);
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            // All inputs are silent.
+            for channel in &mut self.channels {
+                channel.reset();
+            }
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let time_range = proc_info.clock_seconds_range();
+
+        let seconds = time_range.start;
+        let frame_time = (time_range.end.0 - time_range.start.0) / proc_info.frames as f64;
+        for sample in 0..proc_info.frames {
+            if sample % 32 == 0 {
+                let seconds = seconds + DurationSeconds(sample as f64 * frame_time);
+                self.params.frequency.tick(seconds);
+                self.params.q.tick(seconds);
+                self.set_coefficients(self.params.frequency.get(), self.params.q.get());
+            }
+
+            for (i, channel) in self.channels.iter_mut().enumerate() {
+                outputs[i][sample] = channel.process(inputs[i][sample]);
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.set_coefficients(self.params.frequency.get(), self.params.q.get());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gain(filter: &mut Biquad, frequency: f32) -> f32 {
+        let samples = (0..48000)
+            .map(|i| (core::f32::consts::TAU * frequency * i as f32 / 48000.0).sin())
+            .map(|s| filter.process(s));
+
+        // Skip the filter's settling time.
+        samples.skip(24000).fold(0f32, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn test_notch() {
+        let mut filter = Biquad::default();
+        filter.set_notch(48000.0, 1000.0, 2.0);
+
+        assert!(gain(&mut filter, 1000.0) < 0.01);
+
+        for frequency in [100.0, 10000.0] {
+            filter.reset();
+            assert!((gain(&mut filter, frequency) - 1.0).abs() < 0.05);
+        }
+    }
+}
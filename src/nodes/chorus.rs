@@ -0,0 +1,241 @@
+//! A multi-voice stereo chorus.
+
+use super::dsp::{DelayLine, StereoLfo, Tail};
+use crate::node::quality::{DspQuality, QualityScalable};
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A stereo chorus.
+///
+/// The chorus mixes the input with several copies of itself,
+/// each delayed by a slowly modulated amount. This thickens
+/// and detunes the sound, which is great for underwater or
+/// dream-like effects.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn underwater(mut commands: Commands) {
+///     commands
+///         .spawn(ChorusNode {
+///             rate: 0.3,
+///             depth: 0.8,
+///             ..Default::default()
+///         })
+///         .connect(MainBus);
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ChorusNode {
+    /// The modulation rate in hertz.
+    ///
+    /// By default, this is 0.8 Hz.
+    pub rate: f32,
+    /// The modulation depth, expressed from 0 to 1.
+    ///
+    /// By default, this is 0.5.
+    pub depth: f32,
+    /// The number of delayed voices, from 1 to 4.
+    ///
+    /// By default, this is 3.
+    pub voices: u32,
+    /// The wet/dry mix, from 0 (fully dry) to 1 (fully wet).
+    ///
+    /// By default, this is 0.5.
+    pub mix: f32,
+}
+
+impl Default for ChorusNode {
+    fn default() -> Self {
+        Self {
+            rate: 0.8,
+            depth: 0.5,
+            voices: 3,
+            mix: 0.5,
+        }
+    }
+}
+
+impl QualityScalable for ChorusNode {
+    fn scale_quality(&self, quality: DspQuality) -> Self {
+        let voices = match quality {
+            DspQuality::Full => self.voices,
+            DspQuality::Reduced => self.voices.min(2),
+            DspQuality::Minimal => 1,
+        };
+
+        Self {
+            voices,
+            ..self.clone()
+        }
+    }
+}
+
+impl AudioNode for ChorusNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("chorus")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        ChorusProcessor {
+            params: self.clone(),
+            chorus: Chorus::new(cx.stream_info.sample_rate.get() as f32),
+            tail: Tail::default(),
+        }
+    }
+}
+
+const MAX_VOICES: u32 = 4;
+
+/// The delay around which voices are modulated, in seconds.
+const BASE_DELAY: f32 = 0.015;
+
+/// The modulation range at full depth, in seconds.
+const MAX_DEPTH: f32 = 0.01;
+
+struct Chorus {
+    lines: [DelayLine; 2],
+    lfo: StereoLfo,
+    sample_rate: f32,
+}
+
+impl Chorus {
+    fn new(sample_rate: f32) -> Self {
+        let line = DelayLine::new(BASE_DELAY + MAX_DEPTH, sample_rate);
+
+        Self {
+            lines: [line.clone(), line],
+            lfo: StereoLfo::default(),
+            sample_rate,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines[0].len()
+    }
+
+    /// Process a single stereo frame, returning the wet signal.
+    #[inline]
+    fn tick(&mut self, input: [f32; 2], params: &ChorusNode) -> [f32; 2] {
+        let voices = params.voices.clamp(1, MAX_VOICES);
+        let depth = params.depth.clamp(0.0, 1.0) * MAX_DEPTH * 0.5 * self.sample_rate;
+        let base = BASE_DELAY * self.sample_rate + depth;
+
+        let mut output = [0.0; 2];
+        for voice in 0..voices {
+            let offset = voice as f32 / voices as f32;
+
+            for (channel, (line, output)) in self.lines.iter().zip(&mut output).enumerate() {
+                let modulation = self.lfo.sine(channel, offset);
+                *output += line.read(base + depth * modulation);
+            }
+        }
+
+        for (line, input) in self.lines.iter_mut().zip(input) {
+            line.write(input);
+        }
+        self.lfo.advance(params.rate, self.sample_rate);
+
+        let scale = 1.0 / voices as f32;
+        output.map(|o| o * scale)
+    }
+}
+
+struct ChorusProcessor {
+    params: ChorusNode,
+    chorus: Chorus,
+    tail: Tail,
+}
+
+impl AudioNodeProcessor for ChorusProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<ChorusNode>() {
+            self.params.apply(patch);
+        }
+
+        let input_silent = proc_info.in_silence_mask.all_channels_silent(inputs.len());
+        if input_silent && self.tail.is_silent() {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let wet = self.params.mix.clamp(0.0, 1.0);
+        let dry = 1.0 - wet;
+
+        for frame in 0..proc_info.frames {
+            let input = [inputs[0][frame], inputs[1][frame]];
+            let [left, right] = self.chorus.tick(input, &self.params);
+
+            outputs[0][frame] = input[0] * dry + left * wet;
+            outputs[1][frame] = input[1] * dry + right * wet;
+        }
+
+        // Without feedback, the delayed voices fall silent
+        // once the input has passed through the lines.
+        self.tail
+            .update(!input_silent, self.chorus.len(), proc_info.frames);
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.chorus = Chorus::new(stream_info.sample_rate.get() as f32);
+        self.tail.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chorus_delay() {
+        let mut chorus = Chorus::new(1000.0);
+        let params = ChorusNode {
+            depth: 0.0,
+            voices: 2,
+            ..Default::default()
+        };
+
+        // With no modulation, every voice sits at the base delay.
+        let mut output = vec![chorus.tick([1.0, -1.0], &params)];
+        for _ in 0..20 {
+            output.push(chorus.tick([0.0, 0.0], &params));
+        }
+
+        let delay = (BASE_DELAY * 1000.0) as usize;
+        for (i, frame) in output.iter().enumerate() {
+            if i == delay {
+                assert!((frame[0] - 1.0).abs() < 1e-6);
+                assert!((frame[1] + 1.0).abs() < 1e-6);
+            } else {
+                assert!(frame.iter().all(|s| s.abs() < 1e-6));
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Single-band compressor with an optional sidechain input.
+
+use super::dsp::{amp_to_db, db_to_amp, time_coefficient};
+use crate::edge::PortMap;
+use bevy_ecs::component::Component;
+use core::sync::atomic::{AtomicU32, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A dynamic range compressor.
+///
+/// When the detected level exceeds `threshold`, the signal is attenuated
+/// according to `ratio`. By default, the level is detected from the
+/// compressor's own input. With [`CompressorConfig::sidechain`] enabled,
+/// the compressor gains an extra set of input ports that act as a key,
+/// so one signal can duck another.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct VoicePool;
+///
+/// fn duck_music_under_voice(
+///     music: Single<Entity, With<SamplerPool<MusicPool>>>,
+///     voice: Single<Entity, With<SamplerPool<VoicePool>>>,
+///     mut commands: Commands,
+/// ) {
+///     let config = CompressorConfig {
+///         sidechain: true,
+///         ..Default::default()
+///     };
+///     let sidechain = config.sidechain_ports();
+///
+///     let ducker = commands
+///         .spawn((
+///             CompressorNode {
+///                 threshold: Volume::Decibels(-30.0),
+///                 ratio: 6.0,
+///                 ..Default::default()
+///             },
+///             config,
+///         ))
+///         .connect(MainBus)
+///         .head();
+///
+///     // The music passes through the compressor...
+///     commands.entity(*music).disconnect(MainBus).connect(ducker);
+///
+///     // ...while the voice keys it, in addition to playing normally.
+///     commands.entity(*voice).connect_with(ducker, &sidechain);
+/// }
+/// ```
+///
+/// The current gain reduction is available through [`CompressorState`].
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct CompressorNode {
+    /// The level above which gain reduction is applied.
+    ///
+    /// By default, this is -18 dB.
+    pub threshold: Volume,
+    /// The compression ratio.
+    ///
+    /// A ratio of `4.0` means a signal 4 dB over the
+    /// threshold will be reduced to 1 dB over the threshold.
+    ///
+    /// By default, this is 4.
+    pub ratio: f32,
+    /// How long it takes to react to increases in level, in seconds.
+    ///
+    /// By default, this is 0.005s.
+    pub attack: f32,
+    /// How long it takes to react to decreases in level, in seconds.
+    ///
+    /// By default, this is 0.15s.
+    pub release: f32,
+    /// Gain applied after compression.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub makeup: Volume,
+}
+
+impl Default for CompressorNode {
+    fn default() -> Self {
+        Self {
+            threshold: Volume::Decibels(-18.0),
+            ratio: 4.0,
+            attack: 0.005,
+            release: 0.15,
+            makeup: Volume::UNITY_GAIN,
+        }
+    }
+}
+
+/// [`CompressorNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct CompressorConfig {
+    /// The number of input and output channels.
+    ///
+    /// All channels are compressed together.
+    pub channels: NonZeroChannelCount,
+    /// Whether to add sidechain inputs.
+    ///
+    /// When enabled, the compressor has `channels` additional inputs
+    /// following the main inputs. The level is detected from these
+    /// inputs rather than the main inputs.
+    pub sidechain: bool,
+}
+
+impl Default for CompressorConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+            sidechain: false,
+        }
+    }
+}
+
+impl CompressorConfig {
+    /// The port mapping that connects a node with
+    /// `channels` outputs to the sidechain inputs.
+    pub fn sidechain_ports(&self) -> PortMap {
+        let channels = self.channels.get().get();
+        (0..channels)
+            .map(|c| (c, c + channels))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+/// The shared atomics used by [`CompressorNode`] to communicate its current state.
+///
+/// Because audio is processed in chunks, this will typically
+/// update at a rate of 40-80 hertz. As a result, you may not
+/// observe changes on every frame.
+#[derive(Debug, Clone)]
+pub struct CompressorState(ArcGc<AtomicU32>);
+
+impl CompressorState {
+    /// The largest gain reduction applied during the last
+    /// processed block, in decibels.
+    ///
+    /// This is always positive or zero.
+    pub fn gain_reduction(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+impl AudioNode for CompressorNode {
+    type Configuration = CompressorConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        let channels = config.channels.get().get();
+        let inputs = if config.sidechain {
+            channels * 2
+        } else {
+            channels
+        };
+
+        AudioNodeInfo::new()
+            .debug_name("compressor")
+            .channel_config(ChannelConfig {
+                // TODO: remove panic
+                num_inputs: ChannelCount::new(inputs)
+                    .expect("sidechain channel count must not exceed 32"),
+                num_outputs: config.channels.get(),
+            })
+            .custom_state(CompressorState(ArcGc::new(AtomicU32::new(0))))
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let mut processor = CompressorProcessor {
+            params: self.clone(),
+            channels: config.channels.get().get() as usize,
+            sidechain: config.sidechain,
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            envelope: Envelope::default(),
+            state: cx.custom_state().cloned().unwrap(),
+        };
+        processor.update_params();
+
+        processor
+    }
+}
+
+/// Tracks the gain reduction for a detected level.
+#[derive(Debug, Default, Clone)]
+struct Envelope {
+    threshold: f32,
+    slope: f32,
+    attack: f32,
+    release: f32,
+    /// The current gain reduction in decibels.
+    reduction: f32,
+}
+
+impl Envelope {
+    /// Advance by one frame, returning the gain reduction in decibels.
+    #[inline]
+    fn next(&mut self, level: f32) -> f32 {
+        let over = amp_to_db(level) - self.threshold;
+        let target = over.max(0.0) * self.slope;
+
+        let coeff = if target > self.reduction {
+            self.attack
+        } else {
+            self.release
+        };
+        self.reduction = target + coeff * (self.reduction - target);
+
+        // Snap small reductions to zero so the node can settle into silence.
+        if self.reduction < 1e-4 {
+            self.reduction = 0.0;
+        }
+
+        self.reduction
+    }
+}
+
+struct CompressorProcessor {
+    params: CompressorNode,
+    channels: usize,
+    sidechain: bool,
+    sample_rate: f32,
+    envelope: Envelope,
+    state: CompressorState,
+}
+
+impl CompressorProcessor {
+    fn update_params(&mut self) {
+        self.envelope.threshold = amp_to_db(self.params.threshold.amp());
+        self.envelope.slope = 1.0 - 1.0 / self.params.ratio.max(1.0);
+        self.envelope.attack = time_coefficient(self.sample_rate, self.params.attack);
+        self.envelope.release = time_coefficient(self.sample_rate, self.params.release);
+    }
+}
+
+impl AudioNodeProcessor for CompressorProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        let mut changed = false;
+        for patch in events.drain_patches::<CompressorNode>() {
+            self.params.apply(patch);
+            changed = true;
+        }
+
+        if changed {
+            self.update_params();
+        }
+
+        let (main, key) = inputs.split_at(self.channels);
+        let key = if self.sidechain { key } else { main };
+
+        if proc_info.in_silence_mask.all_channels_silent(self.channels) {
+            // Keep the envelope moving so a stale reduction doesn't linger.
+            if self.envelope.reduction != 0.0 {
+                for frame in 0..proc_info.frames {
+                    self.envelope
+                        .next(key.iter().map(|k| k[frame].abs()).fold(0f32, f32::max));
+                }
+            }
+
+            self.state
+                .0
+                .store(self.envelope.reduction.to_bits(), Ordering::Relaxed);
+
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let makeup = self.params.makeup.amp();
+        let mut block_reduction = 0f32;
+
+        for frame in 0..proc_info.frames {
+            let level = key.iter().map(|k| k[frame].abs()).fold(0f32, f32::max);
+            let reduction = self.envelope.next(level);
+            block_reduction = block_reduction.max(reduction);
+
+            let gain = db_to_amp(-reduction) * makeup;
+            for (output, input) in outputs.iter_mut().zip(main) {
+                output[frame] = input[frame] * gain;
+            }
+        }
+
+        self.state
+            .0
+            .store(block_reduction.to_bits(), Ordering::Relaxed);
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.update_params();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let mut envelope = Envelope {
+            threshold: -20.0,
+            slope: 1.0 - 1.0 / 4.0,
+            attack: time_coefficient(48000.0, 0.005),
+            release: time_coefficient(48000.0, 0.05),
+            reduction: 0.0,
+        };
+
+        // A key 20 dB over the threshold settles at 15 dB of reduction.
+        let mut reduction = 0.0;
+        for _ in 0..4800 {
+            reduction = envelope.next(1.0);
+        }
+        assert!((reduction - 15.0).abs() < 1e-2, "{reduction}");
+
+        // Once the key goes quiet, the reduction is released.
+        for _ in 0..48000 {
+            reduction = envelope.next(0.0);
+        }
+        assert_eq!(reduction, 0.0);
+    }
+
+    #[test]
+    fn test_sidechain_ports() {
+        let config = CompressorConfig {
+            sidechain: true,
+            ..Default::default()
+        };
+
+        assert_eq!(config.sidechain_ports().as_slice(), &[(0, 2), (1, 3)]);
+    }
+}
@@ -0,0 +1,557 @@
+//! Convolution reverb.
+
+use crate::{
+    node::quality::{DspQuality, QualityScalable},
+    sample::AudioSample,
+};
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+    sample_resource::SampleResource,
+};
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex, num_complex::Complex};
+use std::sync::Arc;
+
+/// The number of frames in each partition of the impulse response.
+///
+/// This is also the wet signal's latency.
+const PARTITION_FRAMES: usize = 256;
+
+const FFT_FRAMES: usize = PARTITION_FRAMES * 2;
+
+/// A stereo convolution reverb.
+///
+/// Convolution reproduces the acoustics of a real space from a recording
+/// of its impulse response, making it ideal for realistic rooms, caves, and
+/// halls. It's considerably more expensive than the [`FreeverbNode`], with a
+/// cost proportional to the length of the impulse response.
+///
+/// The impulse response is provided by an [`ImpulseResponse`] on the same entity.
+/// Mono impulse responses are applied to both channels, while stereo
+/// impulse responses are applied per channel.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn cave_bus(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         ConvolutionNode::default(),
+///         ImpulseResponse(server.load("cave_ir.wav")),
+///     ));
+/// }
+/// ```
+///
+/// The wet signal is delayed by 256 frames, which is
+/// generally imperceptible as part of a reverb's pre-delay.
+///
+/// [`FreeverbNode`]: crate::prelude::FreeverbNode
+#[derive(Diff, Patch, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ConvolutionNode {
+    /// The gain of the convolved signal.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub wet: Volume,
+    /// The gain of the unprocessed signal.
+    ///
+    /// By default, this is [`Volume::SILENT`], which suits reverb buses.
+    pub dry: Volume,
+    /// The prepared impulse response.
+    ///
+    /// This is set automatically from the entity's [`ImpulseResponse`]
+    /// once its sample has loaded.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub kernel: Option<ArcGc<ConvolutionKernel>>,
+}
+
+impl Default for ConvolutionNode {
+    fn default() -> Self {
+        Self {
+            wet: Volume::UNITY_GAIN,
+            dry: Volume::SILENT,
+            kernel: None,
+        }
+    }
+}
+
+impl QualityScalable for ConvolutionNode {
+    /// At [`DspQuality::Minimal`], the kernel is removed,
+    /// skipping the convolution entirely.
+    fn scale_quality(&self, quality: DspQuality) -> Self {
+        match quality {
+            DspQuality::Minimal => Self {
+                kernel: None,
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+impl core::fmt::Debug for ConvolutionNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ConvolutionNode")
+            .field("wet", &self.wet)
+            .field("dry", &self.dry)
+            .field("kernel", &self.kernel.as_deref())
+            .finish()
+    }
+}
+
+/// [`ConvolutionNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ConvolutionConfig {
+    /// The longest impulse response the node can apply, in seconds.
+    ///
+    /// The processor's memory is allocated up front, so longer impulse
+    /// responses are truncated.
+    ///
+    /// By default, this is 4 seconds.
+    pub max_seconds: f32,
+}
+
+impl Default for ConvolutionConfig {
+    fn default() -> Self {
+        Self { max_seconds: 4.0 }
+    }
+}
+
+/// The impulse response applied by a [`ConvolutionNode`].
+///
+/// Identical impulse responses are only prepared once,
+/// so they can be freely shared between nodes.
+#[derive(Debug, Clone, Component)]
+pub struct ImpulseResponse(pub Handle<AudioSample>);
+
+/// An impulse response prepared for convolution.
+///
+/// The impulse response is split into partitions and transformed
+/// into the frequency domain ahead of time, so that changing
+/// a [`ConvolutionNode`]'s impulse response is cheap.
+pub struct ConvolutionKernel {
+    /// The partition spectra for each channel.
+    channels: Vec<Vec<Box<[Complex<f32>]>>>,
+    frames: usize,
+}
+
+impl ConvolutionKernel {
+    /// Prepare an impulse response.
+    ///
+    /// The impulse response should match the audio stream's sample rate.
+    pub fn new(impulse: &dyn SampleResource) -> Self {
+        let frames = impulse.len_frames() as usize;
+        let mut buffers = vec![vec![0.0; frames]; impulse.num_channels().get()];
+
+        {
+            let mut slices: Vec<_> = buffers.iter_mut().map(Vec::as_mut_slice).collect();
+            impulse.fill_buffers(&mut slices, 0..frames, 0);
+        }
+
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FFT_FRAMES);
+        let mut input = fft.make_input_vec();
+
+        // Folding the inverse transform's scaling into the
+        // kernel saves a pass over every output block.
+        let scale = 1.0 / FFT_FRAMES as f32;
+
+        let channels = buffers
+            .iter()
+            .map(|channel| {
+                channel
+                    .chunks(PARTITION_FRAMES)
+                    .map(|chunk| {
+                        input.fill(0.0);
+                        for (input, sample) in input.iter_mut().zip(chunk) {
+                            *input = sample * scale;
+                        }
+
+                        let mut spectrum = fft.make_output_vec();
+                        fft.process(&mut input, &mut spectrum)
+                            .expect("buffers should match the FFT length");
+
+                        spectrum.into_boxed_slice()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { channels, frames }
+    }
+
+    /// The length of the impulse response in frames.
+    pub fn len_frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The number of channels in the impulse response.
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn partitions(&self, channel: usize) -> &[Box<[Complex<f32>]>] {
+        match self.channels.len() {
+            0 => &[],
+            len => &self.channels[channel % len],
+        }
+    }
+}
+
+impl core::fmt::Debug for ConvolutionKernel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ConvolutionKernel")
+            .field("channels", &self.channels.len())
+            .field("frames", &self.frames)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The impulse response an entity's kernel was prepared from.
+#[derive(Debug, Component)]
+pub(crate) struct PreparedKernel(AssetId<AudioSample>);
+
+pub(crate) fn prepare_kernels(
+    mut nodes: Query<(
+        Entity,
+        &ImpulseResponse,
+        &mut ConvolutionNode,
+        Option<&PreparedKernel>,
+    )>,
+    assets: Res<Assets<AudioSample>>,
+    mut cache: Local<HashMap<AssetId<AudioSample>, ArcGc<ConvolutionKernel>>>,
+    mut commands: Commands,
+) {
+    cache.retain(|id, _| assets.contains(*id));
+
+    for (entity, impulse, mut node, prepared) in nodes.iter_mut() {
+        let id = impulse.0.id();
+        if prepared.is_some_and(|p| p.0 == id) {
+            continue;
+        }
+
+        let Some(sample) = assets.get(id) else {
+            continue;
+        };
+
+        let kernel = cache
+            .entry(id)
+            .or_insert_with(|| ArcGc::new(ConvolutionKernel::new(&*sample.get())))
+            .clone();
+
+        node.kernel = Some(kernel);
+        commands.entity(entity).insert(PreparedKernel(id));
+    }
+}
+
+pub(crate) fn remove_kernel(
+    trigger: On<Remove, ImpulseResponse>,
+    mut nodes: Query<&mut ConvolutionNode>,
+    mut commands: Commands,
+) {
+    if let Ok(mut node) = nodes.get_mut(trigger.event_target()) {
+        node.kernel = None;
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<PreparedKernel>();
+}
+
+impl AudioNode for ConvolutionNode {
+    type Configuration = ConvolutionConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("convolution")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let sample_rate = cx.stream_info.sample_rate.get() as f32;
+        let max_frames = (config.max_seconds.max(0.0) * sample_rate) as usize;
+        let max_partitions = max_frames.div_ceil(PARTITION_FRAMES).max(1);
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(FFT_FRAMES);
+        let ifft = planner.plan_fft_inverse(FFT_FRAMES);
+
+        ConvolutionProcessor {
+            params: self.clone(),
+            channels: [0, 1].map(|_| Convolver::new(fft.as_ref(), max_partitions)),
+            fft_input: fft.make_input_vec(),
+            fft_output: ifft.make_output_vec(),
+            forward_scratch: fft.make_scratch_vec(),
+            inverse_scratch: ifft.make_scratch_vec(),
+            fft,
+            ifft,
+            head: 0,
+            position: 0,
+            tail: 0,
+        }
+    }
+}
+
+/// The convolution state for a single channel.
+struct Convolver {
+    /// The previous partition of input.
+    previous: Box<[f32]>,
+    /// The partition of input currently being collected.
+    input: Box<[f32]>,
+    /// The most recently convolved partition of output.
+    output: Box<[f32]>,
+    /// A ring buffer of past input spectra.
+    history: Box<[Box<[Complex<f32>]>]>,
+    accumulator: Box<[Complex<f32>]>,
+}
+
+impl Convolver {
+    fn new(fft: &dyn RealToComplex<f32>, max_partitions: usize) -> Self {
+        Self {
+            previous: vec![0.0; PARTITION_FRAMES].into(),
+            input: vec![0.0; PARTITION_FRAMES].into(),
+            output: vec![0.0; PARTITION_FRAMES].into(),
+            history: (0..max_partitions)
+                .map(|_| fft.make_output_vec().into_boxed_slice())
+                .collect(),
+            accumulator: fft.make_output_vec().into_boxed_slice(),
+        }
+    }
+}
+
+struct ConvolutionProcessor {
+    params: ConvolutionNode,
+    channels: [Convolver; 2],
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    fft_input: Vec<f32>,
+    fft_output: Vec<f32>,
+    forward_scratch: Vec<Complex<f32>>,
+    inverse_scratch: Vec<Complex<f32>>,
+    /// The index of the most recent spectrum in each channel's history.
+    head: usize,
+    /// The current frame within the partition.
+    position: usize,
+    /// The number of frames remaining before the reverb tail is silent.
+    tail: usize,
+}
+
+impl ConvolutionProcessor {
+    /// Convolve the most recent partition of input using uniformly
+    /// partitioned overlap-save convolution.
+    fn convolve_partition(&mut self) {
+        let max_partitions = self.channels[0].history.len();
+        self.head = (self.head + 1) % max_partitions;
+
+        for (index, channel) in self.channels.iter_mut().enumerate() {
+            self.fft_input[..PARTITION_FRAMES].copy_from_slice(&channel.previous);
+            self.fft_input[PARTITION_FRAMES..].copy_from_slice(&channel.input);
+            channel.previous.copy_from_slice(&channel.input);
+
+            // The input spectrum is needed by later partitions
+            // even when there's no kernel to apply.
+            self.fft
+                .process_with_scratch(
+                    &mut self.fft_input,
+                    &mut channel.history[self.head],
+                    &mut self.forward_scratch,
+                )
+                .expect("buffers should match the FFT length");
+
+            let Some(kernel) = self.params.kernel.as_deref() else {
+                channel.output.fill(0.0);
+                continue;
+            };
+
+            channel.accumulator.fill(Complex::default());
+            for (age, partition) in kernel
+                .partitions(index)
+                .iter()
+                .take(max_partitions)
+                .enumerate()
+            {
+                let spectrum =
+                    &channel.history[(self.head + max_partitions - age) % max_partitions];
+                for ((accumulator, input), partition) in
+                    channel.accumulator.iter_mut().zip(spectrum).zip(partition)
+                {
+                    *accumulator += input * partition;
+                }
+            }
+
+            // The spectrum of a real signal has purely real DC and Nyquist bins.
+            let last = channel.accumulator.len() - 1;
+            channel.accumulator[0].im = 0.0;
+            channel.accumulator[last].im = 0.0;
+
+            self.ifft
+                .process_with_scratch(
+                    &mut channel.accumulator,
+                    &mut self.fft_output,
+                    &mut self.inverse_scratch,
+                )
+                .expect("buffers should match the FFT length");
+
+            channel
+                .output
+                .copy_from_slice(&self.fft_output[PARTITION_FRAMES..]);
+        }
+    }
+}
+
+impl AudioNodeProcessor for ConvolutionProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<ConvolutionNode>() {
+            self.params.apply(patch);
+        }
+
+        let input_silent = proc_info.in_silence_mask.all_channels_silent(inputs.len());
+        if input_silent && self.tail == 0 {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let wet = self.params.wet.amp();
+        let dry = self.params.dry.amp();
+
+        for frame in 0..proc_info.frames {
+            for (channel, (input, output)) in self
+                .channels
+                .iter_mut()
+                .zip(inputs.iter().zip(outputs.iter_mut()))
+            {
+                let sample = input[frame];
+                output[frame] = sample * dry + channel.output[self.position] * wet;
+                channel.input[self.position] = sample;
+            }
+
+            self.position += 1;
+            if self.position == PARTITION_FRAMES {
+                self.convolve_partition();
+                self.position = 0;
+            }
+        }
+
+        if input_silent {
+            self.tail = self.tail.saturating_sub(proc_info.frames);
+        } else {
+            let max_frames = self.channels[0].history.len() * PARTITION_FRAMES;
+            let kernel_frames = self
+                .params
+                .kernel
+                .as_deref()
+                .map(|k| k.len_frames().min(max_frames))
+                .unwrap_or_default();
+
+            // The final partition of input takes two partitions to leave the output.
+            self.tail = kernel_frames + PARTITION_FRAMES * 2;
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::num::NonZeroUsize;
+    use core::ops::Range;
+
+    struct Impulse(Vec<f32>);
+
+    impl SampleResource for Impulse {
+        fn num_channels(&self) -> NonZeroUsize {
+            NonZeroUsize::MIN
+        }
+
+        fn len_frames(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn fill_buffers(
+            &self,
+            buffers: &mut [&mut [f32]],
+            buffer_range: Range<usize>,
+            start_frame: u64,
+        ) {
+            let start = start_frame as usize;
+            let frames = buffer_range.len().min(self.0.len().saturating_sub(start));
+            buffers[0][buffer_range.start..buffer_range.start + frames]
+                .copy_from_slice(&self.0[start..start + frames]);
+        }
+    }
+
+    #[test]
+    fn test_partitioned_convolution() {
+        // An impulse response spanning several partitions.
+        let mut impulse = vec![0.0; PARTITION_FRAMES * 3];
+        impulse[0] = 1.0;
+        impulse[PARTITION_FRAMES + 10] = 0.5;
+        impulse[PARTITION_FRAMES * 2 + 20] = -0.25;
+        let kernel = ConvolutionKernel::new(&Impulse(impulse.clone()));
+        assert_eq!(kernel.len_frames(), impulse.len());
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(FFT_FRAMES);
+        let ifft = planner.plan_fft_inverse(FFT_FRAMES);
+        let mut processor = ConvolutionProcessor {
+            params: ConvolutionNode {
+                kernel: Some(ArcGc::new(kernel)),
+                ..Default::default()
+            },
+            channels: [0, 1].map(|_| Convolver::new(fft.as_ref(), 4)),
+            fft_input: fft.make_input_vec(),
+            fft_output: ifft.make_output_vec(),
+            forward_scratch: fft.make_scratch_vec(),
+            inverse_scratch: ifft.make_scratch_vec(),
+            fft,
+            ifft,
+            head: 0,
+            position: 0,
+            tail: 0,
+        };
+
+        // Feed a single impulse, collecting the output one partition late.
+        let mut output = Vec::new();
+        for partition in 0..5 {
+            for frame in 0..PARTITION_FRAMES {
+                let sample = if partition == 0 && frame == 0 {
+                    1.0
+                } else {
+                    0.0
+                };
+
+                output.push(processor.channels[0].output[frame]);
+                processor.channels[0].input[frame] = sample;
+            }
+
+            processor.convolve_partition();
+        }
+
+        let output = &output[PARTITION_FRAMES..];
+        for (output, expected) in output.iter().zip(&impulse) {
+            assert!((output - expected).abs() < 1e-5);
+        }
+    }
+}
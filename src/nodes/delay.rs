@@ -0,0 +1,236 @@
+//! Feedback delay with optional tempo sync.
+
+use super::dsp::{DelayLine, Tail, time_coefficient};
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    clock::{DurationMusical, DurationSeconds},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The length of a [`DelayNode`]'s echoes.
+#[derive(Diff, Patch, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum DelayTime {
+    /// A fixed duration.
+    Seconds(DurationSeconds),
+    /// A musical duration, in beats.
+    ///
+    /// This follows the musical transport's tempo, so echoes
+    /// stay on the beat as the tempo changes. If no transport
+    /// is active, this is interpreted at 120 BPM.
+    Musical(DurationMusical),
+}
+
+impl Default for DelayTime {
+    fn default() -> Self {
+        Self::Seconds(DurationSeconds(0.25))
+    }
+}
+
+/// A feedback delay.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn dotted_eighth_echo(mut commands: Commands) {
+///     commands
+///         .spawn(DelayNode {
+///             time: DelayTime::Musical(DurationMusical(0.75)),
+///             feedback: 0.4,
+///             ..Default::default()
+///         })
+///         .connect(MainBus);
+/// }
+/// ```
+///
+/// The delay time is limited by [`DelayConfig::max_delay`].
+/// Changes to the delay time glide smoothly, producing the
+/// characteristic pitch bend of a tape delay.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DelayNode {
+    /// The delay time.
+    ///
+    /// By default, this is 0.25s.
+    pub time: DelayTime,
+    /// The amount of each echo fed back into the delay, from 0 to 1.
+    ///
+    /// By default, this is 0.35.
+    pub feedback: f32,
+    /// The wet/dry mix, from 0 (fully dry) to 1 (fully wet).
+    ///
+    /// By default, this is 0.3.
+    pub mix: f32,
+}
+
+impl Default for DelayNode {
+    fn default() -> Self {
+        Self {
+            time: DelayTime::default(),
+            feedback: 0.35,
+            mix: 0.3,
+        }
+    }
+}
+
+/// [`DelayNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DelayConfig {
+    /// The longest possible delay time.
+    ///
+    /// By default, this is 2 seconds.
+    pub max_delay: DurationSeconds,
+    /// The number of input and output channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for DelayConfig {
+    fn default() -> Self {
+        Self {
+            max_delay: DurationSeconds(2.0),
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for DelayNode {
+    type Configuration = DelayConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("delay")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let sample_rate = cx.stream_info.sample_rate.get() as f32;
+        let max_delay = config.max_delay.0.max(0.0) as f32;
+
+        DelayProcessor {
+            params: self.clone(),
+            lines: vec![
+                DelayLine::new(max_delay, sample_rate);
+                config.channels.get().get() as usize
+            ],
+            max_delay,
+            sample_rate,
+            current_delay: None,
+            tail: Tail::default(),
+        }
+    }
+}
+
+/// How quickly the delay glides to a new time, in seconds.
+const GLIDE_SECONDS: f32 = 0.05;
+
+const DEFAULT_BEATS_PER_MINUTE: f64 = 120.0;
+
+struct DelayProcessor {
+    params: DelayNode,
+    lines: Vec<DelayLine>,
+    max_delay: f32,
+    sample_rate: f32,
+    current_delay: Option<f32>,
+    tail: Tail,
+}
+
+impl DelayProcessor {
+    fn target_delay(&self, proc_info: &ProcInfo) -> f32 {
+        let seconds = match self.params.time {
+            DelayTime::Seconds(seconds) => seconds.0,
+            DelayTime::Musical(beats) => {
+                let beats_per_minute = proc_info
+                    .transport_info
+                    .as_ref()
+                    .map(|t| t.beats_per_minute)
+                    .filter(|bpm| *bpm > 0.0)
+                    .unwrap_or(DEFAULT_BEATS_PER_MINUTE);
+
+                beats.0 * 60.0 / beats_per_minute
+            }
+        };
+
+        let max_frames = self.lines.first().map(|l| l.max_frames()).unwrap_or(0.0);
+        (seconds as f32 * self.sample_rate).clamp(1.0, max_frames.max(1.0))
+    }
+}
+
+impl AudioNodeProcessor for DelayProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<DelayNode>() {
+            self.params.apply(patch);
+        }
+
+        let input_silent = proc_info.in_silence_mask.all_channels_silent(inputs.len());
+        if input_silent && self.tail.is_silent() {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let target = self.target_delay(proc_info);
+        let mut delay = self.current_delay.unwrap_or(target);
+        let glide = 1.0 - time_coefficient(self.sample_rate, GLIDE_SECONDS);
+
+        let feedback = self.params.feedback.clamp(0.0, 0.99);
+        let wet = self.params.mix.clamp(0.0, 1.0);
+        let dry = 1.0 - wet;
+
+        let mut peak = 0f32;
+        for frame in 0..proc_info.frames {
+            delay += (target - delay) * glide;
+
+            for ((line, input), output) in self.lines.iter_mut().zip(inputs).zip(outputs.iter_mut())
+            {
+                let input = input[frame];
+                let delayed = line.process(input, delay, feedback);
+                peak = peak.max(delayed.abs());
+
+                output[frame] = input * dry + delayed * wet;
+            }
+        }
+
+        self.current_delay = Some(delay);
+
+        let length = (self.max_delay * self.sample_rate) as usize;
+        if self
+            .tail
+            .update(!input_silent || peak > 1e-6, length, proc_info.frames)
+        {
+            for line in &mut self.lines {
+                line.clear();
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        if stream_info.sample_rate.get() as f32 != self.sample_rate {
+            self.sample_rate = stream_info.sample_rate.get() as f32;
+            for line in &mut self.lines {
+                *line = DelayLine::new(self.max_delay, self.sample_rate);
+            }
+            self.current_delay = None;
+            self.tail.reset();
+        }
+    }
+}
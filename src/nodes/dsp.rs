@@ -0,0 +1,169 @@
+//! Small DSP helpers shared by the built-in nodes.
+
+/// The one-pole smoothing coefficient for a time constant in seconds.
+///
+/// Non-positive times yield `0.0`, which follows the input instantly.
+pub(crate) fn time_coefficient(sample_rate: f32, seconds: f32) -> f32 {
+    if seconds <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (seconds * sample_rate)).exp()
+    }
+}
+
+/// Convert a linear amplitude to decibels, flooring silence at -180 dB.
+pub(crate) fn amp_to_db(amp: f32) -> f32 {
+    20.0 * amp.max(1e-9).log10()
+}
+
+/// Convert decibels to a linear amplitude.
+pub(crate) fn db_to_amp(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// A ring buffer read at fractional delays with linear interpolation.
+#[derive(Debug, Clone)]
+pub(crate) struct DelayLine {
+    buffer: Vec<f32>,
+    write: usize,
+}
+
+impl DelayLine {
+    /// Create a line holding up to `max_delay` seconds.
+    pub(crate) fn new(max_delay: f32, sample_rate: f32) -> Self {
+        // Two extra frames leave room for interpolation.
+        let len = (max_delay * sample_rate).ceil() as usize + 2;
+
+        Self {
+            buffer: vec![0.0; len],
+            write: 0,
+        }
+    }
+
+    /// The length of the buffer in frames.
+    pub(crate) fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The longest delay the line can hold, in frames.
+    pub(crate) fn max_frames(&self) -> f32 {
+        (self.buffer.len() - 2) as f32
+    }
+
+    /// Read the signal from `delay` frames ago.
+    ///
+    /// `delay` must be at least one.
+    #[inline]
+    pub(crate) fn read(&self, delay: f32) -> f32 {
+        let len = self.buffer.len();
+        let read = self.write as f32 + len as f32 - delay;
+        let index = read.floor();
+        let fraction = read - index;
+
+        let a = self.buffer[index as usize % len];
+        let b = self.buffer[(index as usize + 1) % len];
+        a + (b - a) * fraction
+    }
+
+    /// Write the current frame and advance the line.
+    #[inline]
+    pub(crate) fn write(&mut self, sample: f32) {
+        self.buffer[self.write] = sample;
+        self.write = (self.write + 1) % self.buffer.len();
+    }
+
+    /// Process a single frame, returning the delayed signal.
+    ///
+    /// The delayed signal is fed back into the line, scaled by `feedback`.
+    #[inline]
+    pub(crate) fn process(&mut self, input: f32, delay: f32, feedback: f32) -> f32 {
+        let delayed = self.read(delay);
+        self.write(input + delayed * feedback);
+        delayed
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+/// A stereo low-frequency oscillator for modulation effects.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct StereoLfo {
+    /// The phase in cycles.
+    phase: f32,
+}
+
+impl StereoLfo {
+    /// The phase of `channel`, in cycles.
+    #[inline]
+    fn phase(&self, channel: usize) -> f32 {
+        // Offsetting the right channel by a quarter cycle widens the image.
+        self.phase + channel as f32 * 0.25
+    }
+
+    /// A sine in `-1.0..=1.0`, shifted by `offset` cycles.
+    #[inline]
+    pub(crate) fn sine(&self, channel: usize, offset: f32) -> f32 {
+        ((self.phase(channel) + offset) * core::f32::consts::TAU).sin()
+    }
+
+    /// A raised cosine sweeping `0.0..=1.0`, starting at zero.
+    #[inline]
+    pub(crate) fn sweep(&self, channel: usize) -> f32 {
+        0.5 - 0.5 * (self.phase(channel) * core::f32::consts::TAU).cos()
+    }
+
+    /// Advance the oscillator by one frame at `rate` hertz.
+    #[inline]
+    pub(crate) fn advance(&mut self, rate: f32, sample_rate: f32) {
+        self.phase = (self.phase + rate.max(0.0) / sample_rate).fract();
+    }
+}
+
+/// The number of frames remaining before an effect's tail is silent.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Tail(usize);
+
+impl Tail {
+    /// Returns `true` once the tail has rung out.
+    pub(crate) fn is_silent(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Update the tail after processing a block of `frames`.
+    ///
+    /// While `ringing`, the tail is held `length` frames past the block.
+    /// Otherwise, it counts down, returning `true` once it's silent.
+    pub(crate) fn update(&mut self, ringing: bool, length: usize, frames: usize) -> bool {
+        if ringing {
+            self.0 = length + frames;
+            false
+        } else {
+            self.0 = self.0.saturating_sub(frames);
+            self.0 == 0
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delay_line() {
+        let mut line = DelayLine::new(0.01, 1000.0);
+
+        let mut output = Vec::new();
+        output.push(line.process(1.0, 4.0, 0.5));
+        for _ in 0..9 {
+            output.push(line.process(0.0, 4.0, 0.5));
+        }
+
+        assert_eq!(output, [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.5, 0.0]);
+    }
+}
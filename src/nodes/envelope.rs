@@ -0,0 +1,239 @@
+//! Amplitude envelope following.
+
+use super::dsp::time_coefficient;
+use bevy_ecs::component::Component;
+use core::sync::atomic::{AtomicU32, Ordering};
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A node that tracks the smoothed amplitude of its input.
+///
+/// The amplitude is published through [`EnvelopeState`], allowing
+/// gameplay systems to react to audio, like shaking the camera
+/// on bass hits or animating a speaker cone.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState};
+/// #[derive(Component)]
+/// struct Speaker;
+///
+/// fn follow_main(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+///     let follower = commands
+///         .spawn(EnvelopeFollowerNode {
+///             attack: 0.005,
+///             release: 0.1,
+///         })
+///         .id();
+///     commands.entity(*main).connect(follower);
+/// }
+///
+/// fn pulse(
+///     envelope: Single<&AudioState<EnvelopeState>>,
+///     mut speakers: Query<&mut Transform, With<Speaker>>,
+/// ) {
+///     let scale = 1.0 + envelope.0.amplitude() * 0.2;
+///     for mut transform in &mut speakers {
+///         transform.scale = Vec3::splat(scale);
+///     }
+/// }
+/// ```
+///
+/// The envelope follows the loudest input channel. Since this node
+/// has no outputs, it's typically connected alongside a bus's usual
+/// destination rather than in series.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct EnvelopeFollowerNode {
+    /// How quickly the envelope rises to meet the input, in seconds.
+    ///
+    /// By default, this is 0.01s.
+    pub attack: f32,
+    /// How quickly the envelope falls once the input drops, in seconds.
+    ///
+    /// By default, this is 0.15s.
+    pub release: f32,
+}
+
+impl Default for EnvelopeFollowerNode {
+    fn default() -> Self {
+        Self {
+            attack: 0.01,
+            release: 0.15,
+        }
+    }
+}
+
+/// [`EnvelopeFollowerNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct EnvelopeConfig {
+    /// The number of input channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+/// The shared atomic used by [`EnvelopeFollowerNode`] to publish its envelope.
+///
+/// Because audio is processed in chunks, this will typically
+/// update at a rate of 40-80 hertz.
+#[derive(Debug, Clone)]
+pub struct EnvelopeState(ArcGc<AtomicU32>);
+
+impl EnvelopeState {
+    /// The envelope's linear amplitude.
+    ///
+    /// For typical signals, this falls within `0.0..=1.0`.
+    pub fn amplitude(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// The envelope's level in dBFS.
+    pub fn decibels(&self) -> f32 {
+        20.0 * self.amplitude().max(1e-9).log10()
+    }
+}
+
+impl AudioNode for EnvelopeFollowerNode {
+    type Configuration = EnvelopeConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("envelope follower")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: ChannelCount::ZERO,
+            })
+            .custom_state(EnvelopeState(ArcGc::new(AtomicU32::new(0))))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        EnvelopeProcessor {
+            params: self.clone(),
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            envelope: 0.0,
+            state: cx.custom_state().cloned().unwrap(),
+        }
+    }
+}
+
+struct EnvelopeProcessor {
+    params: EnvelopeFollowerNode,
+    sample_rate: f32,
+    envelope: f32,
+    state: EnvelopeState,
+}
+
+impl EnvelopeProcessor {
+    /// Advance the envelope over a block of `frames` samples.
+    ///
+    /// Silent blocks pass an empty slice of inputs.
+    fn follow(&mut self, frames: usize, inputs: &[&[f32]]) {
+        let attack = time_coefficient(self.sample_rate, self.params.attack);
+        let release = time_coefficient(self.sample_rate, self.params.release);
+
+        if inputs.is_empty() {
+            self.envelope *= release.powi(frames as i32);
+        } else {
+            for frame in 0..frames {
+                let amplitude = inputs
+                    .iter()
+                    .map(|input| input[frame].abs())
+                    .filter(|x| x.is_finite())
+                    .fold(0f32, f32::max);
+
+                let coeff = if amplitude > self.envelope {
+                    attack
+                } else {
+                    release
+                };
+                self.envelope = amplitude + coeff * (self.envelope - amplitude);
+            }
+        }
+
+        // Snap tiny levels to zero so a silent envelope settles.
+        if self.envelope < 1e-9 {
+            self.envelope = 0.0;
+        }
+
+        self.state
+            .0
+            .store(self.envelope.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl AudioNodeProcessor for EnvelopeProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<EnvelopeFollowerNode>() {
+            self.params.apply(patch);
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            self.follow(proc_info.frames, &[]);
+        } else {
+            self.follow(proc_info.frames, inputs);
+        }
+
+        ProcessStatus::ClearAllOutputs
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let mut follower = EnvelopeProcessor {
+            params: EnvelopeFollowerNode::default(),
+            sample_rate: 48000.0,
+            envelope: 0.0,
+            state: EnvelopeState(ArcGc::new(AtomicU32::new(0))),
+        };
+
+        let left = vec![0.8; 480];
+        let right = vec![-0.2; 480];
+
+        // 100ms is many attack time constants.
+        for _ in 0..10 {
+            follower.follow(480, &[&left, &right]);
+        }
+        assert!((follower.state.amplitude() - 0.8).abs() < 0.01);
+
+        // One release time constant of silence falls to about 37%.
+        for _ in 0..15 {
+            follower.follow(480, &[]);
+        }
+        let expected = 0.8 * (-1f32).exp();
+        assert!((follower.state.amplitude() - expected).abs() < 0.01);
+    }
+}
@@ -0,0 +1,230 @@
+//! A stereo flanger.
+
+use super::dsp::{DelayLine, StereoLfo, Tail};
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A stereo flanger.
+///
+/// The flanger mixes the input with a copy delayed by a few
+/// milliseconds, sweeping the delay with a low-frequency oscillator.
+/// Feeding the delayed signal back into itself produces the
+/// characteristic "jet plane" whoosh.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn jet_engine(mut commands: Commands) {
+///     commands
+///         .spawn(FlangerNode {
+///             rate: 0.1,
+///             feedback: 0.8,
+///             ..Default::default()
+///         })
+///         .connect(MainBus);
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct FlangerNode {
+    /// The sweep rate in hertz.
+    ///
+    /// By default, this is 0.25 Hz.
+    pub rate: f32,
+    /// The sweep depth, expressed from 0 to 1.
+    ///
+    /// By default, this is 0.7.
+    pub depth: f32,
+    /// The amount of the delayed signal fed back into the delay,
+    /// expressed from -1 to 1.
+    ///
+    /// Negative values invert the feedback, producing a hollower tone.
+    ///
+    /// By default, this is 0.5.
+    pub feedback: f32,
+    /// The wet/dry mix, from 0 (fully dry) to 1 (fully wet).
+    ///
+    /// By default, this is 0.5.
+    pub mix: f32,
+}
+
+impl Default for FlangerNode {
+    fn default() -> Self {
+        Self {
+            rate: 0.25,
+            depth: 0.7,
+            feedback: 0.5,
+            mix: 0.5,
+        }
+    }
+}
+
+impl AudioNode for FlangerNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("flanger")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        FlangerProcessor {
+            params: self.clone(),
+            flanger: Flanger::new(cx.stream_info.sample_rate.get() as f32),
+            tail: Tail::default(),
+        }
+    }
+}
+
+/// The shortest delay in the sweep, in seconds.
+const MIN_DELAY: f32 = 0.0005;
+
+/// The sweep range at full depth, in seconds.
+const MAX_DEPTH: f32 = 0.005;
+
+struct Flanger {
+    lines: [DelayLine; 2],
+    lfo: StereoLfo,
+    sample_rate: f32,
+}
+
+impl Flanger {
+    fn new(sample_rate: f32) -> Self {
+        let line = DelayLine::new(MIN_DELAY + MAX_DEPTH, sample_rate);
+
+        Self {
+            lines: [line.clone(), line],
+            lfo: StereoLfo::default(),
+            sample_rate,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines[0].len()
+    }
+
+    /// Process a single stereo frame, returning the wet signal.
+    #[inline]
+    fn tick(&mut self, input: [f32; 2], params: &FlangerNode) -> [f32; 2] {
+        let depth = params.depth.clamp(0.0, 1.0) * MAX_DEPTH * self.sample_rate;
+        let min = (MIN_DELAY * self.sample_rate).max(1.0);
+        let feedback = params.feedback.clamp(-0.95, 0.95);
+
+        let mut output = [0.0; 2];
+        for (channel, (line, (input, output))) in self
+            .lines
+            .iter_mut()
+            .zip(input.into_iter().zip(&mut output))
+            .enumerate()
+        {
+            let delay = min + depth * self.lfo.sweep(channel);
+            *output = line.process(input, delay, feedback);
+        }
+
+        self.lfo.advance(params.rate, self.sample_rate);
+
+        output
+    }
+
+    fn clear(&mut self) {
+        for line in &mut self.lines {
+            line.clear();
+        }
+    }
+}
+
+struct FlangerProcessor {
+    params: FlangerNode,
+    flanger: Flanger,
+    tail: Tail,
+}
+
+impl AudioNodeProcessor for FlangerProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<FlangerNode>() {
+            self.params.apply(patch);
+        }
+
+        let input_silent = proc_info.in_silence_mask.all_channels_silent(inputs.len());
+        if input_silent && self.tail.is_silent() {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let wet = self.params.mix.clamp(0.0, 1.0);
+        let dry = 1.0 - wet;
+
+        let mut peak = 0f32;
+        for frame in 0..proc_info.frames {
+            let input = [inputs[0][frame], inputs[1][frame]];
+            let [left, right] = self.flanger.tick(input, &self.params);
+            peak = peak.max(left.abs()).max(right.abs());
+
+            outputs[0][frame] = input[0] * dry + left * wet;
+            outputs[1][frame] = input[1] * dry + right * wet;
+        }
+
+        let ringing = !input_silent || peak > 1e-6;
+        if self
+            .tail
+            .update(ringing, self.flanger.len(), proc_info.frames)
+        {
+            self.flanger.clear();
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.flanger = Flanger::new(stream_info.sample_rate.get() as f32);
+        self.tail.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flanger_feedback() {
+        let mut flanger = Flanger::new(10_000.0);
+        let params = FlangerNode {
+            rate: 0.0,
+            depth: 0.0,
+            feedback: 0.5,
+            ..Default::default()
+        };
+
+        // With no sweep, echoes arrive at the minimum delay, halving each time.
+        let mut output = vec![flanger.tick([1.0, 1.0], &params)];
+        for _ in 0..20 {
+            output.push(flanger.tick([0.0, 0.0], &params));
+        }
+
+        let delay = (MIN_DELAY * 10_000.0).round() as usize;
+        assert!((output[delay][0] - 1.0).abs() < 1e-6);
+        assert!((output[delay * 2][0] - 0.5).abs() < 1e-6);
+        assert!((output[delay * 3][1] - 0.25).abs() < 1e-6);
+    }
+}
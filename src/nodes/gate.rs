@@ -0,0 +1,279 @@
+//! Noise gate with hold and adjustable range.
+
+use super::dsp::time_coefficient;
+use bevy_ecs::component::Component;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A noise gate.
+///
+/// When the input falls below `threshold` for longer than `hold`,
+/// the gate closes, attenuating the signal by `range`. A `range` of
+/// [`Volume::SILENT`] fully mutes the signal, while more moderate ranges
+/// make the gate behave like a downward expander.
+///
+/// The gate works equally well on an input stream or as an insert effect.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn gate_microphone(input: Single<Entity, With<AudioGraphInput>>, mut commands: Commands) {
+///     let gate = commands
+///         .spawn(GateNode {
+///             threshold: Volume::Decibels(-45.0),
+///             ..Default::default()
+///         })
+///         .connect(MainBus)
+///         .head();
+///
+///     commands.entity(*input).connect(gate);
+/// }
+/// ```
+///
+/// Whether the gate is currently open is available through [`GateState`].
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct GateNode {
+    /// The level above which the gate opens.
+    ///
+    /// By default, this is -40 dB.
+    pub threshold: Volume,
+    /// How long it takes the gate to open, in seconds.
+    ///
+    /// By default, this is 0.001s.
+    pub attack: f32,
+    /// How long the gate stays open after the input
+    /// falls below the threshold, in seconds.
+    ///
+    /// By default, this is 0.05s.
+    pub hold: f32,
+    /// How long it takes the gate to close, in seconds.
+    ///
+    /// By default, this is 0.1s.
+    pub release: f32,
+    /// The attenuation applied while the gate is closed.
+    ///
+    /// By default, this is [`Volume::SILENT`].
+    pub range: Volume,
+}
+
+impl Default for GateNode {
+    fn default() -> Self {
+        Self {
+            threshold: Volume::Decibels(-40.0),
+            attack: 0.001,
+            hold: 0.05,
+            release: 0.1,
+            range: Volume::SILENT,
+        }
+    }
+}
+
+/// [`GateNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct GateConfig {
+    /// The number of input and output channels.
+    ///
+    /// All channels are gated together.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for GateConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InnerState {
+    open: AtomicBool,
+    gain: AtomicU32,
+}
+
+/// The shared atomics used by [`GateNode`] to communicate its current state.
+///
+/// Because audio is processed in chunks, this will typically
+/// update at a rate of 40-80 hertz. As a result, you may not
+/// observe changes on every frame.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState};
+/// fn indicator(
+///     gate: Single<&AudioState<GateState>>,
+///     mut indicator: Single<&mut Visibility, With<MicIndicator>>,
+/// ) {
+///     **indicator = if gate.0.is_open() {
+///         Visibility::Visible
+///     } else {
+///         Visibility::Hidden
+///     };
+/// }
+/// # #[derive(Component)]
+/// # struct MicIndicator;
+/// ```
+#[derive(Debug, Clone)]
+pub struct GateState(ArcGc<InnerState>);
+
+impl GateState {
+    /// Returns `true` if the gate was open at the end of the last processed block.
+    ///
+    /// The gate is considered open from the moment the input crosses
+    /// the threshold until the hold time has elapsed.
+    pub fn is_open(&self) -> bool {
+        self.0.open.load(Ordering::Relaxed)
+    }
+
+    /// The gain applied by the gate at the end of the last
+    /// processed block, as a linear amplitude.
+    ///
+    /// This ranges from the gate's `range` when closed to `1.0` when open.
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.0.gain.load(Ordering::Relaxed))
+    }
+}
+
+impl AudioNode for GateNode {
+    type Configuration = GateConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("gate")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+            .custom_state(GateState(ArcGc::new(InnerState {
+                open: AtomicBool::new(false),
+                gain: AtomicU32::new(self.range.amp().to_bits()),
+            })))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let mut processor = GateProcessor {
+            params: self.clone(),
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            threshold: 0.0,
+            attack: 0.0,
+            release: 0.0,
+            hold_frames: 0,
+            hold_remaining: 0,
+            open: false,
+            gain: self.range.amp(),
+            state: cx.custom_state().cloned().unwrap(),
+        };
+        processor.update_params();
+
+        processor
+    }
+}
+
+struct GateProcessor {
+    params: GateNode,
+    sample_rate: f32,
+    threshold: f32,
+    attack: f32,
+    release: f32,
+    hold_frames: usize,
+    hold_remaining: usize,
+    open: bool,
+    /// The current gain as a linear amplitude.
+    gain: f32,
+    state: GateState,
+}
+
+impl GateProcessor {
+    fn update_params(&mut self) {
+        self.threshold = self.params.threshold.amp();
+        self.attack = time_coefficient(self.sample_rate, self.params.attack);
+        self.release = time_coefficient(self.sample_rate, self.params.release);
+        self.hold_frames = (self.params.hold.max(0.0) * self.sample_rate) as usize;
+    }
+}
+
+impl AudioNodeProcessor for GateProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        let mut changed = false;
+        for patch in events.drain_patches::<GateNode>() {
+            self.params.apply(patch);
+            changed = true;
+        }
+
+        if changed {
+            self.update_params();
+        }
+
+        let floor = self.params.range.amp().min(1.0);
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            self.hold_remaining = 0;
+            self.open = false;
+            self.gain = floor;
+            self.state.0.open.store(false, Ordering::Relaxed);
+            self.state.0.gain.store(floor.to_bits(), Ordering::Relaxed);
+
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        for frame in 0..proc_info.frames {
+            let peak = inputs
+                .iter()
+                .map(|input| input[frame].abs())
+                .fold(0f32, f32::max);
+
+            if peak >= self.threshold {
+                self.hold_remaining = self.hold_frames;
+            } else {
+                self.hold_remaining = self.hold_remaining.saturating_sub(1);
+            }
+
+            self.open = peak >= self.threshold || self.hold_remaining > 0;
+            let (target, coeff) = if self.open {
+                (1.0, self.attack)
+            } else {
+                (floor, self.release)
+            };
+            self.gain = target + coeff * (self.gain - target);
+
+            for (output, input) in outputs.iter_mut().zip(inputs) {
+                output[frame] = input[frame] * self.gain;
+            }
+        }
+
+        self.state.0.open.store(self.open, Ordering::Relaxed);
+        self.state
+            .0
+            .gain
+            .store(self.gain.to_bits(), Ordering::Relaxed);
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.update_params();
+    }
+}
@@ -0,0 +1,839 @@
+//! Streaming audio over HTTP.
+//!
+//! Internet radio and other remote streams can be played through
+//! the audio graph like any other source. Audio is fetched in the
+//! background, decoded as it arrives on a worker thread, and handed
+//! to the [`HttpStreamNode`] through a fixed-size buffer.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, node::AudioState};
+//! fn play_radio(mut commands: Commands) {
+//!     commands.spawn((
+//!         HttpStreamNode::default(),
+//!         HttpSource::new("https://radio.example.com/lofi.mp3"),
+//!     ));
+//! }
+//!
+//! fn buffering_indicator(radio: Single<&AudioState<HttpStreamState>>) {
+//!     if radio.0.is_buffering() {
+//!         info!("buffering: {:.1}s", radio.0.buffered_seconds());
+//!     }
+//! }
+//! ```
+//!
+//! The stream's format must be enabled through the crate's
+//! format features, like `mp3` or `ogg`. Streams of unknown length
+//! play until the server closes the connection. Since decoding
+//! needs a thread of its own, streams aren't available on `wasm32`.
+
+use crate::node::AudioState;
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+use std::{
+    collections::VecDeque,
+    io::{Read, Seek, SeekFrom},
+    num::NonZeroU32,
+    ops::ControlFlow,
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
+    time::Duration,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{Decoder, DecoderOptions},
+    formats::{FormatOptions, FormatReader},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+/// The most bytes buffered ahead of the decoder.
+///
+/// Once full, the fetch waits for the decoder to catch up.
+const MAX_RECEIVED_BYTES: usize = 256 * 1024;
+
+/// How long the decoder waits for room in the node's buffer.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A node that plays audio streamed over HTTP.
+///
+/// The stream is provided by an [`HttpSource`] on the same entity.
+/// See the [module docs][self] for an example.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct HttpStreamNode {
+    /// The stream's output volume.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub volume: Volume,
+    /// Whether playback is paused.
+    ///
+    /// The stream keeps buffering while paused, up to
+    /// [`HttpStreamConfig::buffer_frames`].
+    pub paused: bool,
+    /// How much audio must be buffered before playback starts,
+    /// or resumes after the buffer runs dry, in seconds.
+    ///
+    /// Larger values ride out slower connections at the
+    /// cost of a longer wait. By default, this is `1.0`.
+    pub prebuffer: f32,
+}
+
+impl Default for HttpStreamNode {
+    fn default() -> Self {
+        Self {
+            volume: Volume::UNITY_GAIN,
+            paused: false,
+            prebuffer: 1.0,
+        }
+    }
+}
+
+/// [`HttpStreamNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct HttpStreamConfig {
+    /// The number of decoded frames that can be buffered
+    /// between the ECS and the audio thread.
+    ///
+    /// This should comfortably exceed [`HttpStreamNode::prebuffer`].
+    /// By default, this is 262,144 frames, or about five
+    /// and a half seconds at 48kHz.
+    pub buffer_frames: NonZeroU32,
+}
+
+impl Default for HttpStreamConfig {
+    fn default() -> Self {
+        Self {
+            buffer_frames: NonZeroU32::new(262_144).unwrap(),
+        }
+    }
+}
+
+/// The URL streamed by an [`HttpStreamNode`].
+///
+/// Changing the URL starts a new connection,
+/// discarding any audio buffered from the previous one.
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct HttpSource(pub String);
+
+impl HttpSource {
+    /// Stream from `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(url.into())
+    }
+}
+
+/// A single-producer, single-consumer ring of stereo frames.
+#[derive(Debug)]
+struct InnerState {
+    buffer: Box<[AtomicU32]>,
+    write: AtomicUsize,
+    read: AtomicUsize,
+    source_rate: AtomicU32,
+    buffering: AtomicBool,
+    finished: AtomicBool,
+    reset: AtomicBool,
+    underruns: AtomicUsize,
+}
+
+impl InnerState {
+    fn new(frames: usize) -> Self {
+        Self {
+            buffer: (0..frames * 2).map(|_| AtomicU32::new(0)).collect(),
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            source_rate: AtomicU32::new(0),
+            buffering: AtomicBool::new(true),
+            finished: AtomicBool::new(false),
+            reset: AtomicBool::new(false),
+            underruns: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of buffered frames.
+    fn available(&self) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        let read = self.read.load(Ordering::Acquire);
+        write.wrapping_sub(read) / 2
+    }
+
+    /// The number of frames that can be pushed.
+    fn free(&self) -> usize {
+        self.buffer.len() / 2 - self.available()
+    }
+
+    /// Push a frame into the ring. Called from the ECS.
+    fn push(&self, frame: [f32; 2]) -> bool {
+        if self.free() == 0 {
+            return false;
+        }
+
+        let len = self.buffer.len();
+        let write = self.write.load(Ordering::Relaxed);
+        self.buffer[write % len].store(frame[0].to_bits(), Ordering::Relaxed);
+        self.buffer[write.wrapping_add(1) % len].store(frame[1].to_bits(), Ordering::Relaxed);
+        self.write.store(write.wrapping_add(2), Ordering::Release);
+
+        true
+    }
+
+    /// Pop a frame from the ring. Called from the audio thread.
+    fn pop(&self) -> Option<[f32; 2]> {
+        let len = self.buffer.len();
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.write.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let frame = [
+            f32::from_bits(self.buffer[read % len].load(Ordering::Relaxed)),
+            f32::from_bits(self.buffer[read.wrapping_add(1) % len].load(Ordering::Relaxed)),
+        ];
+        self.read.store(read.wrapping_add(2), Ordering::Release);
+
+        Some(frame)
+    }
+
+    /// Ask the processor to discard buffered audio. Called from the ECS.
+    fn request_reset(&self) {
+        self.source_rate.store(0, Ordering::Relaxed);
+        self.finished.store(false, Ordering::Relaxed);
+        self.reset.store(true, Ordering::Release);
+    }
+}
+
+/// The shared state of an [`HttpStreamNode`].
+#[derive(Debug, Clone)]
+pub struct HttpStreamState(ArcGc<InnerState>);
+
+impl HttpStreamState {
+    /// Returns `true` while playback is waiting for the buffer to fill.
+    pub fn is_buffering(&self) -> bool {
+        self.0.buffering.load(Ordering::Relaxed) && !self.is_finished()
+    }
+
+    /// Returns `true` once the stream has ended or failed
+    /// and all of its audio has been played.
+    pub fn is_finished(&self) -> bool {
+        self.0.finished.load(Ordering::Relaxed) && self.0.available() == 0
+    }
+
+    /// The amount of decoded audio waiting to be played, in seconds.
+    pub fn buffered_seconds(&self) -> f32 {
+        match self.0.source_rate.load(Ordering::Relaxed) {
+            0 => 0.0,
+            rate => self.0.available() as f32 / rate as f32,
+        }
+    }
+
+    /// The number of times the buffer ran dry during playback.
+    ///
+    /// If this is increasing, consider raising [`HttpStreamNode::prebuffer`].
+    pub fn underruns(&self) -> usize {
+        self.0.underruns.load(Ordering::Relaxed)
+    }
+
+    fn request_reset(&self) {
+        self.0.request_reset();
+    }
+}
+
+impl AudioNode for HttpStreamNode {
+    type Configuration = HttpStreamConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("http stream")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::ZERO,
+                num_outputs: ChannelCount::STEREO,
+            })
+            .custom_state(HttpStreamState(ArcGc::new(InnerState::new(
+                config.buffer_frames.get() as usize,
+            ))))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        HttpStreamProcessor {
+            params: self.clone(),
+            state: cx.custom_state().cloned().unwrap(),
+            sample_rate: cx.stream_info.sample_rate.get() as f64,
+            current: [0.0; 2],
+            next: [0.0; 2],
+            fraction: 0.0,
+        }
+    }
+}
+
+struct HttpStreamProcessor {
+    params: HttpStreamNode,
+    state: HttpStreamState,
+    sample_rate: f64,
+    /// The source frames being interpolated between.
+    current: [f32; 2],
+    next: [f32; 2],
+    fraction: f64,
+}
+
+impl AudioNodeProcessor for HttpStreamProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { outputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<HttpStreamNode>() {
+            self.params.apply(patch);
+        }
+
+        let state = &self.state.0;
+        if state.reset.swap(false, Ordering::Acquire) {
+            state
+                .read
+                .store(state.write.load(Ordering::Acquire), Ordering::Release);
+            state.buffering.store(true, Ordering::Relaxed);
+            self.current = [0.0; 2];
+            self.next = [0.0; 2];
+            self.fraction = 0.0;
+        }
+
+        let source_rate = state.source_rate.load(Ordering::Relaxed);
+        if self.params.paused || source_rate == 0 {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let available = state.available();
+        let finished = state.finished.load(Ordering::Relaxed);
+        if state.buffering.load(Ordering::Relaxed) {
+            let prebuffer = (self.params.prebuffer.max(0.0) * source_rate as f32) as usize;
+            let ready = available > 0 && (available >= prebuffer || finished);
+
+            if !ready {
+                return ProcessStatus::ClearAllOutputs;
+            }
+            state.buffering.store(false, Ordering::Relaxed);
+        }
+
+        let [left, right] = outputs else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        let frames = proc_info.frames;
+        let ratio = source_rate as f64 / self.sample_rate;
+        let gain = self.params.volume.amp();
+
+        for frame in 0..frames {
+            let fraction = self.fraction as f32;
+            left[frame] = (self.current[0] + (self.next[0] - self.current[0]) * fraction) * gain;
+            right[frame] = (self.current[1] + (self.next[1] - self.current[1]) * fraction) * gain;
+
+            self.fraction += ratio;
+            while self.fraction >= 1.0 {
+                self.fraction -= 1.0;
+                self.current = self.next;
+
+                match state.pop() {
+                    Some(next) => self.next = next,
+                    None => {
+                        if !finished {
+                            state.underruns.fetch_add(1, Ordering::Relaxed);
+                        }
+                        state.buffering.store(true, Ordering::Relaxed);
+
+                        left[frame + 1..frames].fill(0.0);
+                        right[frame + 1..frames].fill(0.0);
+                        return ProcessStatus::outputs_not_silent();
+                    }
+                }
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f64;
+    }
+}
+
+/// Bytes received from the server.
+#[derive(Default)]
+struct Received {
+    bytes: VecDeque<u8>,
+    content_type: Option<String>,
+    /// Set once the response headers arrive.
+    responded: bool,
+    ended: bool,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct Incoming {
+    received: Mutex<Received>,
+    /// Notified whenever bytes are received or read,
+    /// or the connection is closed.
+    changed: Condvar,
+    /// Set when the connection is dropped.
+    closed: AtomicBool,
+}
+
+impl Incoming {
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Close the connection, waking the fetch and the decoder.
+    fn close(&self) {
+        let _received = self.received.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.changed.notify_all();
+    }
+
+    /// Handle part of the server's response. Called from the fetch.
+    ///
+    /// Once [`MAX_RECEIVED_BYTES`] are waiting, this blocks
+    /// until the decoder catches up.
+    fn receive(&self, part: Result<ehttp::streaming::Part, String>) -> ControlFlow<()> {
+        let mut received = self.received.lock().unwrap();
+        let flow = match part {
+            Ok(ehttp::streaming::Part::Response(response)) => {
+                received.responded = true;
+
+                if response.ok {
+                    received.content_type = response.headers.get("content-type").map(Into::into);
+                    ControlFlow::Continue(())
+                } else {
+                    received.error = Some(format!("{} {}", response.status, response.status_text));
+                    ControlFlow::Break(())
+                }
+            }
+            // An empty chunk marks the end of the response.
+            Ok(ehttp::streaming::Part::Chunk(chunk)) if chunk.is_empty() => {
+                received.ended = true;
+                ControlFlow::Break(())
+            }
+            Ok(ehttp::streaming::Part::Chunk(chunk)) => {
+                while !self.is_closed()
+                    && !received.bytes.is_empty()
+                    && received.bytes.len() + chunk.len() > MAX_RECEIVED_BYTES
+                {
+                    received = self.changed.wait(received).unwrap();
+                }
+
+                if !self.is_closed() {
+                    received.bytes.extend(chunk);
+                }
+                ControlFlow::Continue(())
+            }
+            Err(e) => {
+                received.error = Some(e);
+                ControlFlow::Break(())
+            }
+        };
+
+        self.changed.notify_all();
+
+        if self.is_closed() {
+            return ControlFlow::Break(());
+        }
+
+        flow
+    }
+}
+
+/// A blocking reader over the received bytes.
+///
+/// Reads wait for more data until the server closes the connection.
+struct IncomingReader(Arc<Incoming>);
+
+impl Read for IncomingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut received = self.0.received.lock().unwrap();
+        while received.bytes.is_empty() {
+            if let Some(e) = &received.error {
+                return Err(std::io::Error::other(e.clone()));
+            }
+
+            if received.ended || self.0.is_closed() {
+                return Ok(0);
+            }
+
+            received = self.0.changed.wait(received).unwrap();
+        }
+
+        let len = buf.len().min(received.bytes.len());
+        for (byte, received) in buf.iter_mut().zip(received.bytes.drain(..len)) {
+            *byte = received;
+        }
+        self.0.changed.notify_all();
+
+        Ok(len)
+    }
+}
+
+impl Seek for IncomingReader {
+    fn seek(&mut self, _: SeekFrom) -> std::io::Result<u64> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl MediaSource for IncomingReader {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+struct HttpDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+    /// Decoded frames waiting for room in the buffer.
+    pending: VecDeque<[f32; 2]>,
+}
+
+impl HttpDecoder {
+    fn new(incoming: &Arc<Incoming>, hint: &Hint) -> Option<Self> {
+        let stream = MediaSourceStream::new(
+            Box::new(IncomingReader(incoming.clone())),
+            Default::default(),
+        );
+        let probed = symphonia::default::get_probe()
+            .format(
+                hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .ok()?;
+
+        let format = probed.format;
+        let track = format.default_track()?;
+        let track_id = track.id;
+        let sample_rate = track.codec_params.sample_rate?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .ok()?;
+
+        Some(Self {
+            format,
+            decoder,
+            track_id,
+            sample_rate,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Decode the next packet into the pending frames.
+    ///
+    /// Returns `false` once the stream has ended.
+    fn decode(&mut self) -> bool {
+        let Ok(packet) = self.format.next_packet() else {
+            return false;
+        };
+
+        if packet.track_id() != self.track_id {
+            return true;
+        }
+
+        // Corrupt packets are skipped rather than ending the stream.
+        let Ok(decoded) = self.decoder.decode(&packet) else {
+            return true;
+        };
+
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        let channels = spec.channels.count().max(1);
+        for frame in buffer.samples().chunks_exact(channels) {
+            let left = frame[0];
+            let right = frame.get(1).copied().unwrap_or(left);
+            self.pending.push_back([left, right]);
+        }
+
+        true
+    }
+}
+
+/// Guess a stream's format from its content type and URL.
+fn hint(url: &str, content_type: Option<&str>) -> Hint {
+    let mut hint = Hint::new();
+
+    if let Some(content_type) = content_type {
+        hint.mime_type(content_type);
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    if let Some((_, extension)) = path.rsplit_once('.') {
+        if !extension.contains('/') {
+            hint.with_extension(extension);
+        }
+    }
+
+    hint
+}
+
+/// Decode a stream into the node's buffer. Runs on the connection's worker.
+fn decode_stream(url: &str, incoming: &Arc<Incoming>, state: &InnerState) {
+    let content_type = {
+        let mut received = incoming.received.lock().unwrap();
+        while !received.responded && received.error.is_none() && !incoming.is_closed() {
+            received = incoming.changed.wait(received).unwrap();
+        }
+
+        received.content_type.clone()
+    };
+
+    let decoder = HttpDecoder::new(incoming, &hint(url, content_type.as_deref()));
+    if let Some(mut decoder) = decoder {
+        state
+            .source_rate
+            .store(decoder.sample_rate, Ordering::Relaxed);
+
+        while decoder.decode() {
+            while let Some(frame) = decoder.pending.front() {
+                if incoming.is_closed() {
+                    return;
+                }
+
+                if state.push(*frame) {
+                    decoder.pending.pop_front();
+                } else {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    }
+
+    // Closed connections have been replaced, so their state isn't ours to touch.
+    if incoming.is_closed() {
+        return;
+    }
+
+    match incoming.received.lock().unwrap().error.take() {
+        Some(e) => error!("failed to stream audio from {url}: {e}"),
+        None if state.source_rate.load(Ordering::Relaxed) == 0 => {
+            error!("unsupported audio stream format from {url}");
+        }
+        None => {}
+    }
+
+    state.finished.store(true, Ordering::Relaxed);
+    incoming.close();
+}
+
+/// An open connection feeding an [`HttpStreamNode`].
+#[derive(Component)]
+pub(crate) struct HttpConnection {
+    url: String,
+    incoming: Arc<Incoming>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HttpConnection {
+    fn open(url: String, state: &HttpStreamState) -> Self {
+        let incoming = Arc::new(Incoming::default());
+
+        let worker_url = url.clone();
+        let worker_incoming = incoming.clone();
+        let worker_state = state.0.clone();
+        let worker = std::thread::Builder::new()
+            .name("seedling http".into())
+            .spawn(move || decode_stream(&worker_url, &worker_incoming, &worker_state));
+
+        let worker = match worker {
+            Ok(worker) => worker,
+            Err(e) => {
+                error!("failed to stream audio from {url}: {e}");
+                state.0.finished.store(true, Ordering::Relaxed);
+                incoming.close();
+
+                return Self {
+                    url,
+                    incoming,
+                    worker: None,
+                };
+            }
+        };
+
+        let callback_incoming = incoming.clone();
+        ehttp::streaming::fetch(ehttp::Request::get(&url), move |part| {
+            callback_incoming.receive(part)
+        });
+
+        Self {
+            url,
+            incoming,
+            worker: Some(worker),
+        }
+    }
+
+    /// Close the connection and wait for its worker to stop.
+    ///
+    /// Afterwards, nothing else is pushed into the node's buffer.
+    fn close(&mut self) {
+        self.incoming.close();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for HttpConnection {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+pub(crate) fn connect_streams(
+    mut nodes: Query<
+        (
+            Entity,
+            &HttpSource,
+            &AudioState<HttpStreamState>,
+            Option<&mut HttpConnection>,
+        ),
+        With<HttpStreamNode>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, source, state, connection) in &mut nodes {
+        if let Some(mut connection) = connection {
+            if connection.url == source.0 {
+                continue;
+            }
+
+            connection.close();
+        }
+
+        state.0.request_reset();
+        commands
+            .entity(entity)
+            .insert(HttpConnection::open(source.0.clone(), &state.0));
+    }
+}
+
+pub(crate) fn remove_source(
+    trigger: On<Remove, HttpSource>,
+    mut nodes: Query<(&AudioState<HttpStreamState>, Option<&mut HttpConnection>)>,
+    mut commands: Commands,
+) {
+    if let Ok((state, connection)) = nodes.get_mut(trigger.event_target()) {
+        if let Some(mut connection) = connection {
+            connection.close();
+        }
+
+        state.0.request_reset();
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<HttpConnection>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_incoming_reader() {
+        let incoming = Arc::new(Incoming::default());
+        let mut reader = IncomingReader(incoming.clone());
+        let mut buf = [0; 4];
+
+        // reads wait for data to arrive
+        let sender = incoming.clone();
+        let handle = std::thread::spawn(move || {
+            sender.receive(Ok(ehttp::streaming::Part::Chunk(vec![1, 2, 3, 4, 5, 6])))
+        });
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(handle.join().unwrap(), ControlFlow::Continue(()));
+
+        // the end of the response reads as the end of the stream
+        let flow = incoming.receive(Ok(ehttp::streaming::Part::Chunk(Vec::new())));
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_received_backpressure() {
+        let incoming = Arc::new(Incoming::default());
+        let mut reader = IncomingReader(incoming.clone());
+
+        let chunk = || {
+            Ok(ehttp::streaming::Part::Chunk(vec![
+                0;
+                MAX_RECEIVED_BYTES / 2
+            ]))
+        };
+        assert_eq!(incoming.receive(chunk()), ControlFlow::Continue(()));
+        assert_eq!(incoming.receive(chunk()), ControlFlow::Continue(()));
+
+        // the third chunk waits until the decoder makes room
+        let sender = incoming.clone();
+        let handle = std::thread::spawn(move || sender.receive(chunk()));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!handle.is_finished());
+        assert_eq!(
+            incoming.received.lock().unwrap().bytes.len(),
+            MAX_RECEIVED_BYTES
+        );
+
+        let mut buf = vec![0; MAX_RECEIVED_BYTES / 2];
+        assert_eq!(reader.read(&mut buf).unwrap(), MAX_RECEIVED_BYTES / 2);
+        assert_eq!(handle.join().unwrap(), ControlFlow::Continue(()));
+        assert_eq!(
+            incoming.received.lock().unwrap().bytes.len(),
+            MAX_RECEIVED_BYTES
+        );
+
+        // closing the connection releases a waiting fetch
+        let sender = incoming.clone();
+        let handle = std::thread::spawn(move || sender.receive(chunk()));
+        incoming.close();
+        assert_eq!(handle.join().unwrap(), ControlFlow::Break(()));
+    }
+
+    #[test]
+    fn test_ring() {
+        let state = InnerState::new(2);
+
+        assert!(state.push([0.5, -0.5]));
+        assert!(state.push([1.0, -1.0]));
+        assert!(!state.push([0.0, 0.0]));
+        assert_eq!(state.available(), 2);
+
+        assert_eq!(state.pop(), Some([0.5, -0.5]));
+        assert!(state.push([0.25, -0.25]));
+        assert_eq!(state.pop(), Some([1.0, -1.0]));
+        assert_eq!(state.pop(), Some([0.25, -0.25]));
+        assert_eq!(state.pop(), None);
+    }
+}
@@ -1,4 +1,4 @@
-//! Limiter with configurable lookahead, attack and release.
+//! Limiter with configurable lookahead, attack, release, and true peak detection.
 
 use core::f32;
 use std::num::NonZeroU32;
@@ -163,6 +163,70 @@ impl IncrementalMax {
     }
 }
 
+/// The number of input samples used to interpolate each true peak.
+const TRUE_PEAK_TAPS: usize = 8;
+/// The oversampling factor for true peak detection.
+const TRUE_PEAK_FACTOR: usize = 4;
+
+/// Estimates inter-sample peaks by 4x oversampling.
+///
+/// Each intermediate point is interpolated with a Hann-windowed sinc
+/// filter. The estimate lags the input by a few samples, which is
+/// covered by the limiter's lookahead.
+#[derive(Debug, Clone)]
+struct TruePeak {
+    coefficients: [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_FACTOR - 1],
+    /// The most recent samples for each channel, oldest first.
+    history: Box<[f32]>,
+}
+
+impl TruePeak {
+    fn new(num_channels: usize) -> Self {
+        let center = (TRUE_PEAK_TAPS / 2 - 1) as f32;
+        let half_width = (TRUE_PEAK_TAPS / 2) as f32;
+
+        let coefficients = core::array::from_fn(|phase| {
+            let offset = (phase + 1) as f32 / TRUE_PEAK_FACTOR as f32;
+            let mut taps: [f32; TRUE_PEAK_TAPS] = core::array::from_fn(|k| {
+                let t = k as f32 - center - offset;
+                let sinc = (f32::consts::PI * t).sin() / (f32::consts::PI * t);
+                let window = 0.5 + 0.5 * (f32::consts::PI * t / half_width).cos();
+                sinc * window
+            });
+
+            // Normalize for unity gain at DC.
+            let sum: f32 = taps.iter().sum();
+            for tap in &mut taps {
+                *tap /= sum;
+            }
+            taps
+        });
+
+        Self {
+            coefficients,
+            history: vec![0.; num_channels * TRUE_PEAK_TAPS].into(),
+        }
+    }
+
+    /// Push a sample for `channel` and return the peak amplitude
+    /// of the sample and the points interpolated before it.
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let history = &mut self.history[channel * TRUE_PEAK_TAPS..][..TRUE_PEAK_TAPS];
+        history.copy_within(1.., 0);
+        history[TRUE_PEAK_TAPS - 1] = sample;
+
+        self.coefficients.iter().fold(sample.abs(), |peak, taps| {
+            let value: f32 = taps.iter().zip(&*history).map(|(c, x)| c * x).sum();
+            peak.max(value.abs())
+        })
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(0.);
+    }
+}
+
 /// Configuration for a [`LimiterNode`].
 #[derive(Debug, Clone, Component, PartialEq)]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
@@ -180,6 +244,15 @@ pub struct LimiterConfig {
     ///
     /// By default, no headroom is added.
     pub headroom: Volume,
+    /// Whether to detect peaks between samples.
+    ///
+    /// A signal that never exceeds unity at its sample points can still
+    /// overshoot once it's reconstructed by a DAC or resampled. With this
+    /// enabled, the limiter estimates these inter-sample peaks by
+    /// oversampling its input, at a small cost in CPU time.
+    ///
+    /// By default, only sample peaks are detected.
+    pub true_peak: bool,
     /// How many channels to take as input/return as output.
     ///
     /// By default, this is stereo.
@@ -191,6 +264,7 @@ impl Default for LimiterConfig {
         Self {
             lookahead: None,
             headroom: Volume::Decibels(0.),
+            true_peak: false,
             channels: NonZeroChannelCount::STEREO,
         }
     }
@@ -232,6 +306,7 @@ struct Limiter {
     headroom: Volume,
     sample_rate: NonZeroU32,
     reducer: IncrementalMax,
+    true_peak: Option<TruePeak>,
     follower: AsymmetricalSmoothedParam,
     buffer: Box<[f32]>,
     num_channels: u32,
@@ -257,12 +332,9 @@ impl AudioNode for LimiterNode {
         cx: ConstructProcessorContext,
     ) -> impl AudioNodeProcessor {
         Limiter::new(
+            self,
+            config,
             cx.stream_info.sample_rate,
-            config.lookahead.unwrap_or(self.attack),
-            self.attack,
-            self.release,
-            config.headroom,
-            config.channels.get().get(),
             cx.stream_info.max_block_frames,
         )
     }
@@ -278,19 +350,18 @@ impl Limiter {
     }
 
     fn new(
+        node: &LimiterNode,
+        config: &LimiterConfig,
         sample_rate: NonZeroU32,
-        lookahead: f32,
-        attack: f32,
-        release: f32,
-        headroom: Volume,
-        num_channels: u32,
         max_buffer_length: NonZeroU32,
     ) -> Self {
+        let lookahead = config.lookahead.unwrap_or(node.attack);
+        let num_channels = config.channels.get().get();
         let follower = AsymmetricalSmoothedParam::new(
             1.,
             AsymmetricalSmootherConfig {
-                smooth_secs_up: attack,
-                smooth_secs_down: release,
+                smooth_secs_up: node.attack,
+                smooth_secs_down: node.release,
             },
             sample_rate,
         );
@@ -304,11 +375,14 @@ impl Limiter {
             num_channels,
             max_buffer_length,
             reducer,
+            true_peak: config
+                .true_peak
+                .then(|| TruePeak::new(num_channels as usize)),
             index: 0,
 
             // Static
             lookahead,
-            headroom,
+            headroom: config.headroom,
             follower,
         }
     }
@@ -344,12 +418,23 @@ impl AudioNodeProcessor for Limiter {
         let frame_size = proc_info.frames;
 
         for i in 0..frame_size {
-            let amplitude = buffers
-                .inputs
-                .iter()
-                .map(|input| input[i])
-                .filter(|x| x.is_finite())
-                .fold(0f32, |amp, x| amp.max(x.abs()));
+            let amplitude = match &mut self.true_peak {
+                Some(true_peak) => buffers
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(channel, input)| (channel, input[i]))
+                    .filter(|(_, x)| x.is_finite())
+                    .fold(0f32, |amp, (channel, x)| {
+                        amp.max(true_peak.process(channel, x))
+                    }),
+                None => buffers
+                    .inputs
+                    .iter()
+                    .map(|input| input[i])
+                    .filter(|x| x.is_finite())
+                    .fold(0f32, |amp, x| amp.max(x.abs())),
+            };
 
             self.reducer.set(self.index, amplitude);
             let max = self.reducer.max();
@@ -387,6 +472,10 @@ impl AudioNodeProcessor for Limiter {
 
         self.follower.update_sample_rate(stream_info.sample_rate);
 
+        if let Some(true_peak) = &mut self.true_peak {
+            true_peak.reset();
+        }
+
         let new_buffer_size = self.reducer.len() * self.num_channels as usize;
 
         if self.buffer.len() == new_buffer_size {
@@ -396,3 +485,26 @@ impl AudioNodeProcessor for Limiter {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_true_peak() {
+        // A quarter-rate sine offset by 45 degrees only
+        // reaches about -3 dB at its sample points.
+        let samples: Vec<f32> = (0..64)
+            .map(|n| (f32::consts::FRAC_PI_2 * n as f32 + f32::consts::FRAC_PI_4).sin())
+            .collect();
+
+        let sample_peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        assert!(sample_peak < 0.71);
+
+        let mut detector = TruePeak::new(1);
+        let true_peak = samples
+            .iter()
+            .fold(0f32, |peak, s| peak.max(detector.process(0, *s)));
+        assert!((true_peak - 1.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,277 @@
+//! Peak and RMS level metering.
+
+use super::dsp::amp_to_db;
+use bevy_ecs::component::Component;
+use core::sync::atomic::{AtomicU32, Ordering};
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A node that measures the peak and RMS level of each input channel.
+///
+/// The levels are published through [`MeterState`], so UI meters
+/// can read them every frame without waiting on the audio thread.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState};
+/// fn meter_main_bus(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+///     let meter = commands.spawn(MeterNode::default()).id();
+///     commands.entity(*main).connect(meter);
+/// }
+///
+/// fn draw_meter(meter: Single<&AudioState<MeterState>>) {
+///     let left = meter.0.peak(0);
+///     let right = meter.0.peak(1);
+///     info!("peak: {left:.1} dB / {right:.1} dB");
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MeterNode {
+    /// How quickly the peak level falls, in decibels per second.
+    ///
+    /// By default, this is 20 dB/s.
+    pub peak_decay: f32,
+    /// The time over which the RMS level is averaged, in seconds.
+    ///
+    /// By default, this is 0.3s.
+    pub rms_window: f32,
+}
+
+impl Default for MeterNode {
+    fn default() -> Self {
+        Self {
+            peak_decay: 20.0,
+            rms_window: 0.3,
+        }
+    }
+}
+
+/// [`MeterNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MeterConfig {
+    /// The number of input channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for MeterConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InnerState {
+    peak: Vec<AtomicU32>,
+    rms: Vec<AtomicU32>,
+}
+
+/// The shared atomics used by [`MeterNode`] to communicate its levels.
+///
+/// Because audio is processed in chunks, this will typically
+/// update at a rate of 40-80 hertz.
+#[derive(Debug, Clone)]
+pub struct MeterState(ArcGc<InnerState>);
+
+impl MeterState {
+    /// The number of metered channels.
+    pub fn channels(&self) -> usize {
+        self.0.peak.len()
+    }
+
+    /// The decaying peak level of a channel, in dBFS.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel index is out of bounds.
+    pub fn peak(&self, channel: usize) -> f32 {
+        amp_to_db(f32::from_bits(self.0.peak[channel].load(Ordering::Relaxed)))
+    }
+
+    /// The RMS level of a channel, in dBFS.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel index is out of bounds.
+    pub fn rms(&self, channel: usize) -> f32 {
+        amp_to_db(f32::from_bits(self.0.rms[channel].load(Ordering::Relaxed)))
+    }
+
+    /// Report infinite levels until the meter first processes audio.
+    pub(crate) fn mark_unmeasured(&self) {
+        for level in self.0.peak.iter().chain(&self.0.rms) {
+            level.store(f32::INFINITY.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl AudioNode for MeterNode {
+    type Configuration = MeterConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        let channels = config.channels.get().get() as usize;
+
+        AudioNodeInfo::new()
+            .debug_name("meter")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: ChannelCount::ZERO,
+            })
+            .custom_state(MeterState(ArcGc::new(InnerState {
+                peak: (0..channels).map(|_| Default::default()).collect(),
+                rms: (0..channels).map(|_| Default::default()).collect(),
+            })))
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        MeterProcessor {
+            params: self.clone(),
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            channels: vec![ChannelLevel::default(); config.channels.get().get() as usize],
+            state: cx.custom_state().cloned().unwrap(),
+        }
+    }
+}
+
+/// The running levels of a single channel.
+#[derive(Debug, Default, Clone, Copy)]
+struct ChannelLevel {
+    peak: f32,
+    /// The running mean square.
+    square: f32,
+}
+
+struct MeterProcessor {
+    params: MeterNode,
+    sample_rate: f32,
+    channels: Vec<ChannelLevel>,
+    state: MeterState,
+}
+
+impl MeterProcessor {
+    /// Advance a channel's levels over a block of `frames` samples.
+    ///
+    /// Silent blocks pass `None` so their buffers are never read.
+    fn measure(&mut self, channel: usize, frames: usize, samples: Option<&[f32]>) {
+        let decay =
+            10f32.powf(-self.params.peak_decay.max(0.0) * frames as f32 / self.sample_rate / 20.0);
+        let coeff = if self.params.rms_window <= 0.0 {
+            0.0
+        } else {
+            (-1.0 / (self.params.rms_window * self.sample_rate)).exp()
+        };
+
+        let level = &mut self.channels[channel];
+        match samples {
+            Some(samples) => {
+                let block_peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+                level.peak = block_peak.max(level.peak * decay);
+
+                for sample in samples {
+                    let square = sample * sample;
+                    level.square = square + coeff * (level.square - square);
+                }
+            }
+            None => {
+                level.peak *= decay;
+                level.square *= coeff.powi(frames as i32);
+            }
+        }
+
+        // Snap tiny levels to zero so silent meters settle.
+        if level.peak < 1e-9 {
+            level.peak = 0.0;
+        }
+        if level.square < 1e-18 {
+            level.square = 0.0;
+        }
+
+        self.state.0.peak[channel].store(level.peak.to_bits(), Ordering::Relaxed);
+        self.state.0.rms[channel].store(level.square.sqrt().to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl AudioNodeProcessor for MeterProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<MeterNode>() {
+            self.params.apply(patch);
+        }
+
+        for (channel, input) in inputs.iter().enumerate() {
+            let samples = (!proc_info.in_silence_mask.is_channel_silent(channel))
+                .then(|| &input[..proc_info.frames]);
+            self.measure(channel, proc_info.frames, samples);
+        }
+
+        ProcessStatus::ClearAllOutputs
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn processor(channels: usize) -> MeterProcessor {
+        MeterProcessor {
+            params: MeterNode::default(),
+            sample_rate: 48000.0,
+            channels: vec![ChannelLevel::default(); channels],
+            state: MeterState(ArcGc::new(InnerState {
+                peak: (0..channels).map(|_| Default::default()).collect(),
+                rms: (0..channels).map(|_| Default::default()).collect(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_peak_and_rms() {
+        let mut meter = processor(2);
+        let square: Vec<f32> = (0..512)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+
+        for _ in 0..200 {
+            meter.measure(0, square.len(), Some(&square));
+            meter.measure(1, square.len(), None);
+        }
+
+        // A square wave's RMS matches its peak.
+        assert!((meter.state.peak(0) - -6.02).abs() < 0.1);
+        assert!((meter.state.rms(0) - -6.02).abs() < 0.1);
+        assert!(meter.state.peak(1) < -100.0);
+        assert!(meter.state.rms(1) < -100.0);
+
+        // One second of silence drops the peak by the decay rate,
+        // while the mean square falls by e^(-1 / rms_window).
+        for _ in 0..(48000 / 480) {
+            meter.measure(0, 480, None);
+        }
+        assert!((meter.state.peak(0) - -26.02).abs() < 0.1);
+        assert!((meter.state.rms(0) - -20.5).abs() < 0.1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Mid/side encoding and decoding.
+//!
+//! A stereo signal can be represented as its mid (the average of both
+//! channels) and side (half their difference) components. Placing effects
+//! between a [`StereoToMidSideNode`] and a [`MidSideToStereoNode`] lets
+//! each component be processed independently.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, edge::PortMap};
+//! fn mono_bass(mut commands: Commands) {
+//!     let decoder = commands.spawn(MidSideToStereoNode).id();
+//!
+//!     // Remove low frequencies from the side component,
+//!     // keeping the bass centered.
+//!     let side_filter = commands
+//!         .spawn((
+//!             HighPassNode {
+//!                 frequency: 150.0,
+//!                 ..Default::default()
+//!             },
+//!             HighPassConfig {
+//!                 channels: NonZeroChannelCount::MONO,
+//!                 ..Default::default()
+//!             },
+//!         ))
+//!         .connect_with(decoder, &PortMap::new().with(0, 1))
+//!         .head();
+//!
+//!     commands
+//!         .spawn(StereoToMidSideNode)
+//!         // The mid component passes through unchanged.
+//!         .connect_with(decoder, &PortMap::mono())
+//!         .connect_with(side_filter, &PortMap::new().with(1, 0));
+//! }
+//! ```
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// Encodes a stereo signal into mid (channel 0) and side (channel 1).
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct StereoToMidSideNode;
+
+/// Decodes mid (channel 0) and side (channel 1) into a stereo signal.
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MidSideToStereoNode;
+
+#[inline]
+fn encode([left, right]: [f32; 2]) -> [f32; 2] {
+    [(left + right) * 0.5, (left - right) * 0.5]
+}
+
+#[inline]
+fn decode([mid, side]: [f32; 2]) -> [f32; 2] {
+    [mid + side, mid - side]
+}
+
+impl AudioNode for StereoToMidSideNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("mid/side encoder")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        MidSideProcessor(encode)
+    }
+}
+
+impl AudioNode for MidSideToStereoNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("mid/side decoder")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        MidSideProcessor(decode)
+    }
+}
+
+struct MidSideProcessor(fn([f32; 2]) -> [f32; 2]);
+
+impl AudioNodeProcessor for MidSideProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        _: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        for frame in 0..proc_info.frames {
+            let [a, b] = (self.0)([inputs[0][frame], inputs[1][frame]]);
+
+            outputs[0][frame] = a;
+            outputs[1][frame] = b;
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mid_side() {
+        let frame = [1.0, 0.25];
+
+        assert_eq!(encode(frame), [0.625, 0.375]);
+        assert_eq!(decode(encode(frame)), frame);
+
+        // a centered signal has no side component
+        assert_eq!(encode([0.5, 0.5]), [0.5, 0.0]);
+    }
+}
@@ -4,31 +4,142 @@ use crate::{SeedlingSystems, prelude::RegisterNode};
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 
+pub mod auto_eq;
 pub mod bpf;
+pub mod bsf;
+pub mod chorus;
+pub mod compressor;
+pub mod convolution;
+pub mod delay;
+pub(crate) mod dsp;
+pub mod envelope;
+pub mod flanger;
 pub mod freeverb;
+pub mod gate;
+pub mod hpf;
 pub mod itd;
 pub mod limiter;
 pub mod lpf;
+pub mod meter;
+pub mod mid_side;
+pub mod multiband;
+pub mod noise;
+pub mod oscillator;
+pub mod phaser;
+pub mod recorder;
+pub mod sanitizer;
+pub mod saturation;
 pub mod send;
+pub mod shelf;
+pub mod spectrum;
+pub mod split;
+pub mod tremolo;
+pub mod width;
 
 #[cfg(feature = "loudness")]
 pub mod loudness;
 
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "tracker")]
+pub mod tracker;
+
+#[cfg(feature = "soundfont")]
+pub mod soundfont;
+
 /// Registration and logic for `bevy_seedling`'s audio nodes.
 pub(crate) struct SeedlingNodesPlugin;
 
 impl Plugin for SeedlingNodesPlugin {
     fn build(&self, app: &mut App) {
         app.register_node::<bpf::BandPassNode>()
+            .register_node::<bsf::BandStopNode>()
             .register_node::<lpf::LowPassNode>()
+            .register_node::<hpf::HighPassNode>()
+            .register_node::<shelf::LowShelfNode>()
+            .register_node::<shelf::HighShelfNode>()
             .register_node::<send::SendNode>()
+            .register_node::<oscillator::OscillatorNode>()
+            .register_node::<noise::NoiseNode>()
             .register_node::<freeverb::FreeverbNode>()
+            .register_node::<convolution::ConvolutionNode>()
+            .register_node::<chorus::ChorusNode>()
+            .register_node::<flanger::FlangerNode>()
+            .register_node::<phaser::PhaserNode>()
+            .register_node::<saturation::SaturationNode>()
+            .register_node::<tremolo::TremoloNode>()
+            .register_node::<width::StereoWidthNode>()
+            .register_simple_node::<split::SplitterNode>()
+            .register_simple_node::<split::MergerNode>()
+            .register_simple_node::<mid_side::StereoToMidSideNode>()
+            .register_simple_node::<mid_side::MidSideToStereoNode>()
+            .register_node::<delay::DelayNode>()
             .register_node::<limiter::LimiterNode>()
             .register_node::<itd::ItdNode>()
+            .register_node::<gate::GateNode>()
+            .register_node_state::<gate::GateNode, gate::GateState>()
+            .register_node::<compressor::CompressorNode>()
+            .register_node_state::<compressor::CompressorNode, compressor::CompressorState>()
+            .register_node::<envelope::EnvelopeFollowerNode>()
+            .register_node_state::<envelope::EnvelopeFollowerNode, envelope::EnvelopeState>()
+            .register_node::<meter::MeterNode>()
+            .register_node_state::<meter::MeterNode, meter::MeterState>()
+            .register_node::<multiband::MultibandCompressorNode>()
+            .register_node_state::<
+                multiband::MultibandCompressorNode,
+                multiband::MultibandCompressorState,
+            >()
+            .register_node::<recorder::RecorderNode>()
+            .register_node_state::<recorder::RecorderNode, recorder::RecorderState>()
+            .register_node::<sanitizer::SanitizerNode>()
+            .register_node_state::<sanitizer::SanitizerNode, sanitizer::SanitizerState>()
+            .register_node::<spectrum::SpectrumNode>()
+            .register_node_state::<spectrum::SpectrumNode, spectrum::SpectrumState>()
+            .register_node::<auto_eq::AutoEqDuckNode>()
             .add_systems(
                 Last,
                 (send::connect_sends, send::update_remote_sends).before(SeedlingSystems::Acquire),
-            );
+            )
+            .add_systems(
+                Last,
+                (
+                    recorder::write_recordings,
+                    recorder::connect_microphones.before(SeedlingSystems::Acquire),
+                    recorder::capture_microphones,
+                ),
+            )
+            .add_systems(
+                Last,
+                convolution::prepare_kernels.before(SeedlingSystems::Acquire),
+            )
+            .add_systems(
+                Last,
+                (
+                    sanitizer::attach_sanitizers.before(SeedlingSystems::Acquire),
+                    sanitizer::reroute_inputs.in_set(SeedlingSystems::PreFlush),
+                    sanitizer::report_sanitized,
+                ),
+            )
+            .add_systems(
+                Last,
+                (
+                    spectrum::attach_analyzers.before(SeedlingSystems::Acquire),
+                    spectrum::update_snapshots,
+                ),
+            )
+            .add_systems(
+                Last,
+                auto_eq::route_auto_eq.in_set(SeedlingSystems::PreFlush),
+            )
+            .add_observer(recorder::stop_recording)
+            .add_observer(convolution::remove_kernel)
+            .add_observer(sanitizer::remove_sanitizer)
+            .add_observer(spectrum::remove_analyzer)
+            .add_observer(auto_eq::remove_auto_eq);
+
+        #[cfg(feature = "sanitize")]
+        app.add_observer(sanitizer::sanitize_main_bus);
 
         #[cfg(feature = "loudness")]
         app.register_node::<loudness::LoudnessNode>()
@@ -36,5 +147,52 @@ impl Plugin for SeedlingNodesPlugin {
 
         #[cfg(all(feature = "reflect", feature = "loudness"))]
         app.register_type::<loudness::LoudnessNode>();
+
+        #[cfg(feature = "tracker")]
+        {
+            use bevy_asset::AssetApp;
+
+            app.init_asset::<tracker::TrackerModule>()
+                .register_asset_loader(tracker::TrackerLoader)
+                .register_node::<tracker::TrackerNode>()
+                .register_node_state::<tracker::TrackerNode, tracker::TrackerState>()
+                .add_systems(Last, tracker::load_songs.before(SeedlingSystems::Acquire))
+                .add_observer(tracker::remove_song);
+        }
+
+        #[cfg(all(feature = "reflect", feature = "tracker"))]
+        app.register_type::<tracker::TrackerNode>();
+
+        #[cfg(feature = "soundfont")]
+        {
+            use bevy_asset::AssetApp;
+
+            app.init_asset::<soundfont::MidiFile>()
+                .init_asset::<soundfont::SoundFontFile>()
+                .register_asset_loader(soundfont::MidiLoader)
+                .register_asset_loader(soundfont::SoundFontLoader)
+                .register_node::<soundfont::SoundFontNode>()
+                .register_node_state::<soundfont::SoundFontNode, soundfont::SoundFontState>()
+                .add_systems(
+                    Last,
+                    (soundfont::load_songs, soundfont::load_soundfonts)
+                        .before(SeedlingSystems::Acquire),
+                )
+                .add_observer(soundfont::remove_song)
+                .add_observer(soundfont::remove_soundfont);
+        }
+
+        #[cfg(all(feature = "reflect", feature = "soundfont"))]
+        app.register_type::<soundfont::SoundFontNode>();
+
+        #[cfg(feature = "http")]
+        app.register_node::<http::HttpStreamNode>()
+            .register_node_state::<http::HttpStreamNode, http::HttpStreamState>()
+            .add_systems(Last, http::connect_streams.before(SeedlingSystems::Acquire))
+            .add_observer(http::remove_source);
+
+        #[cfg(all(feature = "reflect", feature = "http"))]
+        app.register_type::<http::HttpStreamNode>()
+            .register_type::<http::HttpStreamConfig>();
     }
 }
@@ -0,0 +1,620 @@
+//! Multi-band compressor for bus processing.
+
+use super::dsp::{amp_to_db, db_to_amp, time_coefficient};
+use bevy_ecs::component::Component;
+use core::sync::atomic::{AtomicU32, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// Compression parameters for a single band of a [`MultibandCompressorNode`].
+#[derive(Diff, Patch, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct CompressorBand {
+    /// The level above which gain reduction is applied.
+    pub threshold: Volume,
+    /// The compression ratio.
+    ///
+    /// A ratio of `4.0` means a signal 4 dB over the
+    /// threshold will be reduced to 1 dB over the threshold.
+    pub ratio: f32,
+    /// Gain applied to the band after compression.
+    pub makeup: Volume,
+}
+
+impl CompressorBand {
+    /// Create a new [`CompressorBand`] with no makeup gain.
+    pub fn new(threshold: Volume, ratio: f32) -> Self {
+        Self {
+            threshold,
+            ratio,
+            makeup: Volume::UNITY_GAIN,
+        }
+    }
+}
+
+impl Default for CompressorBand {
+    fn default() -> Self {
+        Self::new(Volume::Decibels(-12.0), 2.0)
+    }
+}
+
+/// A multi-band compressor.
+///
+/// The input is split into bands with Linkwitz-Riley crossovers, each
+/// band is compressed independently, and the bands are summed back together.
+/// This is useful on the main bus for taming low-end rumble without
+/// pulling down the rest of the mix.
+///
+/// With [`BandCount::Four`], the bands are split at `low_crossover`,
+/// `mid_crossover`, and `high_crossover`. With [`BandCount::Three`],
+/// `mid_crossover` and `high_mid` are ignored, and `low_mid` covers
+/// everything between `low_crossover` and `high_crossover`.
+///
+/// Crossover frequencies should be in ascending order.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn master_chain(mut commands: Commands) {
+///     commands
+///         .spawn(MultibandCompressorNode {
+///             low: CompressorBand::new(Volume::Decibels(-18.0), 4.0),
+///             ..Default::default()
+///         })
+///         .connect(MainBus);
+/// }
+/// ```
+///
+/// The current gain reduction of each band is available through
+/// [`MultibandCompressorState`].
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MultibandCompressorNode {
+    /// The crossover between the low and low-mid bands, in hertz.
+    ///
+    /// By default, this is 120 Hz.
+    pub low_crossover: f32,
+    /// The crossover between the low-mid and high-mid bands, in hertz.
+    ///
+    /// By default, this is 1 kHz.
+    pub mid_crossover: f32,
+    /// The crossover between the high-mid and high bands, in hertz.
+    ///
+    /// By default, this is 6 kHz.
+    pub high_crossover: f32,
+    /// The low band.
+    pub low: CompressorBand,
+    /// The low-mid band.
+    pub low_mid: CompressorBand,
+    /// The high-mid band.
+    pub high_mid: CompressorBand,
+    /// The high band.
+    pub high: CompressorBand,
+    /// How long it takes to react to increases in volume, in seconds.
+    ///
+    /// By default, this is 0.01s.
+    pub attack: f32,
+    /// How long it takes to react to decreases in volume, in seconds.
+    ///
+    /// By default, this is 0.15s.
+    pub release: f32,
+}
+
+impl Default for MultibandCompressorNode {
+    fn default() -> Self {
+        Self {
+            low_crossover: 120.0,
+            mid_crossover: 1000.0,
+            high_crossover: 6000.0,
+            low: CompressorBand::default(),
+            low_mid: CompressorBand::default(),
+            high_mid: CompressorBand::default(),
+            high: CompressorBand::default(),
+            attack: 0.01,
+            release: 0.15,
+        }
+    }
+}
+
+impl MultibandCompressorNode {
+    /// Gentle settings for the end of a mastering chain.
+    ///
+    /// The bands are lightly compressed to glue the mix together,
+    /// with the low band held a little tighter to keep rumble from
+    /// driving the output limiter. This is intended for placement
+    /// ahead of the [`MainLimiter`][crate::prelude::MainLimiter].
+    pub fn mastering() -> Self {
+        Self {
+            low: CompressorBand::new(Volume::Decibels(-20.0), 3.0),
+            low_mid: CompressorBand::new(Volume::Decibels(-16.0), 1.5),
+            high_mid: CompressorBand::new(Volume::Decibels(-16.0), 1.5),
+            high: CompressorBand::new(Volume::Decibels(-18.0), 2.0),
+            attack: 0.02,
+            release: 0.25,
+            ..Default::default()
+        }
+    }
+
+    fn bands(&self) -> [CompressorBand; 4] {
+        [self.low, self.low_mid, self.high_mid, self.high]
+    }
+}
+
+/// The number of bands in a [`MultibandCompressorNode`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum BandCount {
+    /// Low, mid, and high bands.
+    Three,
+    /// Low, low-mid, high-mid, and high bands.
+    #[default]
+    Four,
+}
+
+/// [`MultibandCompressorNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MultibandCompressorConfig {
+    /// The number of bands.
+    pub bands: BandCount,
+    /// The number of input and output channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for MultibandCompressorConfig {
+    fn default() -> Self {
+        Self {
+            bands: BandCount::Four,
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+/// The shared atomics used by [`MultibandCompressorNode`] to
+/// communicate each band's gain reduction.
+///
+/// Because audio is processed in chunks, this will typically
+/// update at a rate of 40-80 hertz. As a result, you may not
+/// observe changes on every frame.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState, nodes::multiband::Band};
+/// fn meter(compressor: Single<&AudioState<MultibandCompressorState>>) {
+///     info!("low band reduction: {:.1} dB", compressor.0.gain_reduction(Band::Low));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultibandCompressorState(ArcGc<[AtomicU32; 4]>);
+
+/// A band of a [`MultibandCompressorNode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Band {
+    /// The low band.
+    Low,
+    /// The low-mid band.
+    ///
+    /// With [`BandCount::Three`], this is the mid band.
+    LowMid,
+    /// The high-mid band.
+    ///
+    /// With [`BandCount::Three`], this band is inactive.
+    HighMid,
+    /// The high band.
+    High,
+}
+
+impl MultibandCompressorState {
+    /// The peak gain reduction applied to `band` over the last
+    /// processed block, in decibels.
+    ///
+    /// This is always zero or positive.
+    pub fn gain_reduction(&self, band: Band) -> f32 {
+        f32::from_bits(self.0[band as usize].load(Ordering::Relaxed))
+    }
+
+    fn store(&self, band: usize, reduction: f32) {
+        self.0[band].store(reduction.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl AudioNode for MultibandCompressorNode {
+    type Configuration = MultibandCompressorConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("multi-band compressor")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+            .custom_state(MultibandCompressorState(ArcGc::new(Default::default())))
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let sample_rate = cx.stream_info.sample_rate.get() as f32;
+        let channels = config.channels.get().get() as usize;
+
+        let mut processor = MultibandProcessor {
+            params: self.clone(),
+            bands: config.bands,
+            sample_rate,
+            splitters: vec![Splitter::default(); channels],
+            scratch: vec![[0.0; 4]; channels],
+            reduction: [0.0; 4],
+            attack: 0.0,
+            release: 0.0,
+            state: cx.custom_state().cloned().unwrap(),
+        };
+        processor.update_crossovers();
+        processor.update_timing();
+
+        processor
+    }
+}
+
+/// A second-order section in transposed direct form II.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    z: [f32; 2],
+}
+
+impl Biquad {
+    /// Configure a band-pass filter with a constant 0 dB peak.
+    pub(crate) fn set_band_pass(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let w0 = core::f32::consts::TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+
+        self.b = [alpha / a0, 0.0, -alpha / a0];
+        self.a = [-2.0 * cos / a0, (1.0 - alpha) / a0];
+    }
+
+    /// Configure a notch filter that removes `frequency`.
+    pub(crate) fn set_notch(&mut self, sample_rate: f32, frequency: f32, q: f32) {
+        let w0 = core::f32::consts::TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+
+        self.b = [1.0 / a0, -2.0 * cos / a0, 1.0 / a0];
+        self.a = [-2.0 * cos / a0, (1.0 - alpha) / a0];
+    }
+
+    /// Configure a peaking filter with `gain` decibels at `frequency`.
+    pub(crate) fn set_peaking(&mut self, sample_rate: f32, frequency: f32, q: f32, gain: f32) {
+        let w0 = core::f32::consts::TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let amp = 10f32.powf(gain / 40.0);
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha / amp;
+
+        self.b = [
+            (1.0 + alpha * amp) / a0,
+            -2.0 * cos / a0,
+            (1.0 - alpha * amp) / a0,
+        ];
+        self.a = [-2.0 * cos / a0, (1.0 - alpha / amp) / a0];
+    }
+
+    /// Configure a shelving filter with `gain` decibels beyond `frequency`.
+    ///
+    /// A `slope` of 1 is the steepest shelf without overshoot.
+    pub(crate) fn set_shelf(
+        &mut self,
+        sample_rate: f32,
+        frequency: f32,
+        slope: f32,
+        gain: f32,
+        high: bool,
+    ) {
+        let w0 = core::f32::consts::TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let amp = 10f32.powf(gain / 40.0);
+        let alpha = sin / 2.0 * ((amp + 1.0 / amp) * (1.0 / slope - 1.0) + 2.0).sqrt();
+        let beta = 2.0 * amp.sqrt() * alpha;
+
+        // The high shelf mirrors the low shelf's cosine terms.
+        let cos = if high { -cos } else { cos };
+        let sign = if high { -1.0 } else { 1.0 };
+
+        let a0 = (amp + 1.0) + (amp - 1.0) * cos + beta;
+        self.b = [
+            amp * ((amp + 1.0) - (amp - 1.0) * cos + beta) / a0,
+            sign * 2.0 * amp * ((amp - 1.0) - (amp + 1.0) * cos) / a0,
+            amp * ((amp + 1.0) - (amp - 1.0) * cos - beta) / a0,
+        ];
+        self.a = [
+            sign * -2.0 * ((amp - 1.0) + (amp + 1.0) * cos) / a0,
+            ((amp + 1.0) + (amp - 1.0) * cos - beta) / a0,
+        ];
+    }
+
+    /// Clear the filter's history.
+    pub(crate) fn reset(&mut self) {
+        self.z = [0.0; 2];
+    }
+
+    fn set_butterworth(&mut self, sample_rate: f32, frequency: f32, high_pass: bool) {
+        let w0 = core::f32::consts::TAU * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin * core::f32::consts::FRAC_1_SQRT_2;
+        let a0 = 1.0 + alpha;
+
+        let b = if high_pass {
+            let b = (1.0 + cos) / 2.0;
+            [b, -2.0 * b, b]
+        } else {
+            let b = (1.0 - cos) / 2.0;
+            [b, 2.0 * b, b]
+        };
+
+        self.b = b.map(|b| b / a0);
+        self.a = [-2.0 * cos / a0, (1.0 - alpha) / a0];
+    }
+
+    #[inline]
+    pub(crate) fn process(&mut self, input: f32) -> f32 {
+        let output = self.b[0] * input + self.z[0];
+        self.z[0] = self.b[1] * input - self.a[0] * output + self.z[1];
+        self.z[1] = self.b[2] * input - self.a[1] * output;
+        output
+    }
+}
+
+/// A fourth-order Linkwitz-Riley crossover.
+///
+/// The low and high outputs sum to an all-pass response.
+#[derive(Debug, Default, Clone)]
+struct Crossover {
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+}
+
+impl Crossover {
+    fn set_frequency(&mut self, sample_rate: f32, frequency: f32) {
+        let frequency = frequency.clamp(10.0, sample_rate * 0.45);
+
+        for section in &mut self.low {
+            section.set_butterworth(sample_rate, frequency, false);
+        }
+        for section in &mut self.high {
+            section.set_butterworth(sample_rate, frequency, true);
+        }
+    }
+
+    #[inline]
+    fn split(&mut self, input: f32) -> (f32, f32) {
+        let low = self.low[0].process(input);
+        let high = self.high[0].process(input);
+        (self.low[1].process(low), self.high[1].process(high))
+    }
+
+    /// Apply the crossover's phase response without splitting.
+    #[inline]
+    fn all_pass(&mut self, input: f32) -> f32 {
+        let (low, high) = self.split(input);
+        low + high
+    }
+}
+
+/// Splits a single channel into bands.
+///
+/// Each branch is passed through the all-pass response of the
+/// crossovers it doesn't go through so the bands stay phase-aligned.
+#[derive(Debug, Default, Clone)]
+struct Splitter {
+    low: Crossover,
+    mid: Crossover,
+    high: Crossover,
+    low_comp: Crossover,
+    high_comp: Crossover,
+}
+
+impl Splitter {
+    fn set_frequencies(&mut self, sample_rate: f32, [low, mid, high]: [f32; 3]) {
+        self.low.set_frequency(sample_rate, low);
+        self.mid.set_frequency(sample_rate, mid);
+        self.high.set_frequency(sample_rate, high);
+        self.low_comp.set_frequency(sample_rate, low);
+        self.high_comp.set_frequency(sample_rate, high);
+    }
+
+    #[inline]
+    fn split(&mut self, bands: BandCount, input: f32) -> [f32; 4] {
+        match bands {
+            BandCount::Three => {
+                let (low, rest) = self.low.split(input);
+                let (mid, high) = self.high.split(rest);
+                let low = self.high_comp.all_pass(low);
+
+                [low, mid, 0.0, high]
+            }
+            BandCount::Four => {
+                let (lower, upper) = self.mid.split(input);
+                let lower = self.high_comp.all_pass(lower);
+                let upper = self.low_comp.all_pass(upper);
+
+                let (low, low_mid) = self.low.split(lower);
+                let (high_mid, high) = self.high.split(upper);
+
+                [low, low_mid, high_mid, high]
+            }
+        }
+    }
+}
+
+struct MultibandProcessor {
+    params: MultibandCompressorNode,
+    bands: BandCount,
+    sample_rate: f32,
+    splitters: Vec<Splitter>,
+    /// Each channel's bands for the current frame.
+    scratch: Vec<[f32; 4]>,
+    /// The current gain reduction for each band in decibels.
+    reduction: [f32; 4],
+    attack: f32,
+    release: f32,
+    state: MultibandCompressorState,
+}
+
+impl MultibandProcessor {
+    fn update_crossovers(&mut self) {
+        let frequencies = [
+            self.params.low_crossover,
+            self.params.mid_crossover,
+            self.params.high_crossover,
+        ];
+
+        for splitter in &mut self.splitters {
+            splitter.set_frequencies(self.sample_rate, frequencies);
+        }
+    }
+
+    fn update_timing(&mut self) {
+        self.attack = time_coefficient(self.sample_rate, self.params.attack);
+        self.release = time_coefficient(self.sample_rate, self.params.release);
+    }
+}
+
+impl AudioNodeProcessor for MultibandProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        let mut crossovers_changed = false;
+        let mut timing_changed = false;
+        for patch in events.drain_patches::<MultibandCompressorNode>() {
+            match &patch {
+                MultibandCompressorNodePatch::LowCrossover(_)
+                | MultibandCompressorNodePatch::MidCrossover(_)
+                | MultibandCompressorNodePatch::HighCrossover(_) => crossovers_changed = true,
+                MultibandCompressorNodePatch::Attack(_)
+                | MultibandCompressorNodePatch::Release(_) => timing_changed = true,
+                _ => {}
+            }
+
+            self.params.apply(patch);
+        }
+
+        if crossovers_changed {
+            self.update_crossovers();
+        }
+        if timing_changed {
+            self.update_timing();
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len())
+            && self.reduction.iter().all(|r| *r == 0.0)
+        {
+            for band in 0..4 {
+                self.state.store(band, 0.0);
+            }
+
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let params = self.params.bands().map(|band| {
+            let threshold = amp_to_db(band.threshold.amp());
+            let slope = 1.0 - 1.0 / band.ratio.max(1.0);
+            (threshold, slope, band.makeup.amp())
+        });
+
+        let mut block_reduction = [0f32; 4];
+
+        for frame in 0..proc_info.frames {
+            let mut peaks = [0f32; 4];
+
+            for ((splitter, split), input) in
+                self.splitters.iter_mut().zip(&mut self.scratch).zip(inputs)
+            {
+                *split = splitter.split(self.bands, input[frame]);
+
+                for (peak, band) in peaks.iter_mut().zip(*split) {
+                    *peak = peak.max(band.abs());
+                }
+            }
+
+            let mut gains = [0f32; 4];
+            for (band, current) in self.reduction.iter_mut().enumerate() {
+                let (threshold, slope, makeup) = params[band];
+                let over = amp_to_db(peaks[band]) - threshold;
+                let target = over.max(0.0) * slope;
+
+                let coeff = if target > *current {
+                    self.attack
+                } else {
+                    self.release
+                };
+                *current = target + coeff * (*current - target);
+
+                // Snap small reductions to zero so the node can settle into silence.
+                if *current < 1e-4 {
+                    *current = 0.0;
+                }
+
+                block_reduction[band] = block_reduction[band].max(*current);
+                gains[band] = db_to_amp(-*current) * makeup;
+            }
+
+            for (output, split) in outputs.iter_mut().zip(&self.scratch) {
+                output[frame] = split
+                    .iter()
+                    .zip(gains)
+                    .map(|(sample, gain)| sample * gain)
+                    .sum();
+            }
+        }
+
+        for (band, reduction) in block_reduction.into_iter().enumerate() {
+            self.state.store(band, reduction);
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.update_crossovers();
+        self.update_timing();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bands_sum_to_input() {
+        for bands in [BandCount::Three, BandCount::Four] {
+            let mut splitter = Splitter::default();
+            splitter.set_frequencies(48000.0, [120.0, 1000.0, 6000.0]);
+
+            let mut sum = 0.0;
+            for _ in 0..48000 {
+                sum = splitter.split(bands, 0.5).into_iter().sum();
+            }
+
+            assert!((sum - 0.5f32).abs() < 1e-3, "{bands:?}: {sum}");
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! White, pink, and brown noise generation.
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The spectral color of a [`NoiseNode`].
+#[derive(Diff, Patch, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum NoiseColor {
+    /// Equal energy per hertz, like static or a hiss.
+    #[default]
+    White,
+    /// Equal energy per octave, like rain or a waterfall.
+    Pink,
+    /// Energy falling 6 dB per octave, like wind or distant surf.
+    Brown,
+}
+
+/// A noise generator.
+///
+/// The generator has no inputs, and each output channel receives
+/// independent noise. This is useful as a basis for ambiences
+/// like wind or static, and as a broadband test signal.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn wind(mut commands: Commands) {
+///     commands
+///         .spawn(NoiseNode {
+///             color: NoiseColor::Brown,
+///             amplitude: 0.1,
+///         })
+///         .chain_node(LowPassNode { frequency: 600.0 });
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct NoiseNode {
+    /// The noise color.
+    ///
+    /// By default, this is [`NoiseColor::White`].
+    pub color: NoiseColor,
+    /// The approximate peak amplitude, from 0 to 1.
+    ///
+    /// By default, this is 0.25.
+    pub amplitude: f32,
+}
+
+impl Default for NoiseNode {
+    fn default() -> Self {
+        Self {
+            color: NoiseColor::default(),
+            amplitude: 0.25,
+        }
+    }
+}
+
+/// [`NoiseNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct NoiseConfig {
+    /// The number of output channels.
+    pub channels: NonZeroChannelCount,
+    /// The seed for the noise sequence.
+    ///
+    /// Generators with the same seed produce identical output,
+    /// which is useful for reproducible tests.
+    pub seed: u64,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+impl AudioNode for NoiseNode {
+    type Configuration = NoiseConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("noise")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::ZERO,
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        NoiseProcessor {
+            params: self.clone(),
+            amplitude: self.amplitude,
+            generators: (0..config.channels.get().get() as u64)
+                .map(|channel| Generator::new(config.seed.wrapping_add(channel)))
+                .collect(),
+        }
+    }
+}
+
+/// A single channel's noise source.
+#[derive(Debug, Clone)]
+struct Generator {
+    rng: u64,
+    /// Pink noise filter state.
+    pink: [f32; 7],
+    /// Brown noise integrator state.
+    brown: f32,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        // Scramble nearby seeds so channels are decorrelated.
+        let mut rng = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        rng ^= rng >> 31;
+
+        Self {
+            // xorshift can't escape zero
+            rng: rng.max(1),
+            pink: [0.0; 7],
+            brown: 0.0,
+        }
+    }
+
+    /// Generate a white sample in `[-1, 1)`.
+    #[inline]
+    fn white(&mut self) -> f32 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng = x;
+
+        (x >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    /// Generate a sample of the given color, scaled to roughly `[-1, 1]`.
+    #[inline]
+    fn next(&mut self, color: NoiseColor) -> f32 {
+        let white = self.white();
+
+        match color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => {
+                // Paul Kellet's refined pink noise filter.
+                let p = &mut self.pink;
+                p[0] = 0.99886 * p[0] + white * 0.0555179;
+                p[1] = 0.99332 * p[1] + white * 0.0750759;
+                p[2] = 0.96900 * p[2] + white * 0.153852;
+                p[3] = 0.86650 * p[3] + white * 0.3104856;
+                p[4] = 0.55000 * p[4] + white * 0.5329522;
+                p[5] = -0.7616 * p[5] - white * 0.0168980;
+                let pink = p[0] + p[1] + p[2] + p[3] + p[4] + p[5] + p[6] + white * 0.5362;
+                p[6] = white * 0.115926;
+
+                pink * 0.11
+            }
+            NoiseColor::Brown => {
+                // A leaky integrator keeps the walk from drifting away.
+                self.brown = (self.brown + 0.02 * white) / 1.02;
+                self.brown * 3.5
+            }
+        }
+    }
+}
+
+struct NoiseProcessor {
+    params: NoiseNode,
+    /// The amplitude at the end of the previous block.
+    amplitude: f32,
+    generators: Vec<Generator>,
+}
+
+impl AudioNodeProcessor for NoiseProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { outputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<NoiseNode>() {
+            self.params.apply(patch);
+        }
+
+        let target = self.params.amplitude.clamp(0.0, 1.0);
+        if target == 0.0 && self.amplitude == 0.0 {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        // Ramping the amplitude across the block avoids clicks.
+        let step = (target - self.amplitude) / proc_info.frames as f32;
+        for (output, generator) in outputs.iter_mut().zip(&mut self.generators) {
+            let mut amplitude = self.amplitude;
+            for sample in &mut output[..proc_info.frames] {
+                amplitude += step;
+                *sample = generator.next(self.params.color) * amplitude;
+            }
+        }
+        self.amplitude = target;
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_noise_colors() {
+        for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+            let mut generator = Generator::new(7);
+            let samples: Vec<_> = (0..48000).map(|_| generator.next(color)).collect();
+
+            let peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+            assert!(peak <= 1.5 && peak > 0.1, "{color:?} has a peak of {peak}");
+
+            let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+            assert!(mean.abs() < 0.05, "{color:?} has an offset of {mean}");
+        }
+
+        // The same seed always produces the same sequence.
+        let mut a = Generator::new(3);
+        let mut b = Generator::new(3);
+        assert!((0..64).all(|_| a.white() == b.white()));
+
+        // Neighboring channels are decorrelated.
+        let mut left = Generator::new(3);
+        let mut right = Generator::new(4);
+        let correlation: f32 = (0..4800).map(|_| left.white() * right.white()).sum();
+        assert!((correlation / 4800.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,233 @@
+//! A basic waveform generator.
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The shape of an [`OscillatorNode`]'s waveform.
+#[derive(Diff, Patch, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum Waveform {
+    /// A pure tone with no harmonics.
+    #[default]
+    Sine,
+    /// A hollow tone with only odd harmonics.
+    Square,
+    /// A bright, buzzy tone with every harmonic.
+    Saw,
+    /// A soft tone with quickly decaying odd harmonics.
+    Triangle,
+}
+
+impl Waveform {
+    /// Evaluate the waveform at `phase`, from 0 to 1.
+    ///
+    /// `increment` is the phase advanced per sample. Discontinuities
+    /// are smoothed over one increment to reduce aliasing.
+    #[inline]
+    pub fn sample(&self, phase: f32, increment: f32) -> f32 {
+        match self {
+            Self::Sine => (phase * core::f32::consts::TAU).sin(),
+            Self::Square => {
+                let naive = if phase < 0.5 { 1.0 } else { -1.0 };
+                naive + poly_blep(phase, increment) - poly_blep((phase + 0.5).fract(), increment)
+            }
+            Self::Saw => 2.0 * phase - 1.0 - poly_blep(phase, increment),
+            Self::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
+/// A polynomial band-limited step, correcting a naive
+/// waveform's discontinuity at phase 0.
+#[inline]
+fn poly_blep(phase: f32, increment: f32) -> f32 {
+    if increment <= 0.0 {
+        0.0
+    } else if phase < increment {
+        let t = phase / increment;
+        2.0 * t - t * t - 1.0
+    } else if phase > 1.0 - increment {
+        let t = (phase - 1.0) / increment;
+        t * t + 2.0 * t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// A basic oscillator.
+///
+/// The oscillator has no inputs. Like any other node, it's
+/// connected to the [`MainBus`][crate::prelude::MainBus] by default.
+/// This is useful for simple procedural sounds and alarms, and
+/// for tests that need a deterministic signal.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn alarm(mut commands: Commands) {
+///     commands.spawn(OscillatorNode {
+///         waveform: Waveform::Square,
+///         frequency: 880.0,
+///         amplitude: 0.1,
+///     });
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct OscillatorNode {
+    /// The waveform.
+    ///
+    /// By default, this is [`Waveform::Sine`].
+    pub waveform: Waveform,
+    /// The frequency in hertz.
+    ///
+    /// By default, this is 440 Hz.
+    pub frequency: f32,
+    /// The peak amplitude, from 0 to 1.
+    ///
+    /// By default, this is 0.25.
+    pub amplitude: f32,
+}
+
+impl Default for OscillatorNode {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::default(),
+            frequency: 440.0,
+            amplitude: 0.25,
+        }
+    }
+}
+
+/// [`OscillatorNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct OscillatorConfig {
+    /// The number of output channels.
+    ///
+    /// Every channel receives the same signal.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for OscillatorConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for OscillatorNode {
+    type Configuration = OscillatorConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("oscillator")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::ZERO,
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        OscillatorProcessor {
+            params: self.clone(),
+            phase: 0.0,
+            amplitude: self.amplitude,
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+        }
+    }
+}
+
+struct OscillatorProcessor {
+    params: OscillatorNode,
+    phase: f32,
+    /// The amplitude at the end of the previous block.
+    amplitude: f32,
+    sample_rate: f32,
+}
+
+impl AudioNodeProcessor for OscillatorProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { outputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<OscillatorNode>() {
+            self.params.apply(patch);
+        }
+
+        let target = self.params.amplitude.clamp(0.0, 1.0);
+        let increment = (self.params.frequency / self.sample_rate).clamp(0.0, 0.5);
+
+        if target == 0.0 && self.amplitude == 0.0 {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let Some((first, rest)) = outputs.split_first_mut() else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        // Ramping the amplitude across the block avoids clicks.
+        let step = (target - self.amplitude) / proc_info.frames as f32;
+        for sample in &mut first[..proc_info.frames] {
+            self.amplitude += step;
+            *sample = self.params.waveform.sample(self.phase, increment) * self.amplitude;
+            self.phase = (self.phase + increment).fract();
+        }
+        self.amplitude = target;
+
+        for output in rest {
+            output[..proc_info.frames].copy_from_slice(&first[..proc_info.frames]);
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_waveforms() {
+        for waveform in [
+            Waveform::Sine,
+            Waveform::Square,
+            Waveform::Saw,
+            Waveform::Triangle,
+        ] {
+            // Every waveform is bounded and has no DC offset.
+            let increment = 1.0 / 480.0;
+            let samples: Vec<_> = (0..480)
+                .map(|i| waveform.sample(i as f32 * increment, increment))
+                .collect();
+
+            assert!(samples.iter().all(|s| s.abs() <= 1.0 + 1e-3));
+            let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+            assert!(mean.abs() < 1e-2, "{waveform:?} has an offset of {mean}");
+        }
+
+        assert_eq!(Waveform::Triangle.sample(0.5, 0.0), 1.0);
+        assert_eq!(Waveform::Square.sample(0.25, 0.01), 1.0);
+        assert!((Waveform::Sine.sample(0.25, 0.0) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,617 @@
+//! Record any point in the audio graph to a WAV file
+//! or an [`AudioSample`].
+
+use crate::{
+    edge::{AudioGraphInput, Connect},
+    node::AudioState,
+    sample::AudioSample,
+};
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    num::{NonZeroU32, NonZeroUsize},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// A node that records its input to disk.
+///
+/// Connect any node to the recorder to tap its output. Recording
+/// begins when a [`Recording`] is inserted on the recorder's entity,
+/// and the file is finalized when the [`Recording`] is removed
+/// or the entity is despawned.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(Component)]
+/// struct ReplayRecorder;
+///
+/// fn spawn_recorder(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+///     let recorder = commands.spawn((ReplayRecorder, RecorderNode::default())).id();
+///     commands.entity(*main).connect(recorder);
+/// }
+///
+/// fn start(recorder: Single<Entity, With<ReplayRecorder>>, mut commands: Commands) {
+///     commands.entity(*recorder).insert(Recording::new("replay.wav"));
+/// }
+///
+/// fn pause(mut recorder: Single<&mut RecorderNode, With<ReplayRecorder>>) {
+///     recorder.paused = true;
+/// }
+///
+/// fn stop(recorder: Single<Entity, With<ReplayRecorder>>, mut commands: Commands) {
+///     commands.entity(*recorder).remove::<Recording>();
+/// }
+/// ```
+///
+/// Audio is handed off to the ECS through a fixed-size buffer and
+/// written to disk in [`Last`][bevy_app::prelude::Last]. If the app stalls
+/// for longer than the buffer can hold, audio will be dropped; see
+/// [`RecorderState::dropped_frames`].
+#[derive(Diff, Patch, Debug, Default, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct RecorderNode {
+    /// Whether recording is paused.
+    ///
+    /// While paused, incoming audio is discarded rather than written.
+    pub paused: bool,
+}
+
+/// [`RecorderNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct RecorderConfig {
+    /// The number of input channels.
+    pub channels: NonZeroChannelCount,
+    /// The number of frames that can be buffered between
+    /// the audio thread and the ECS.
+    ///
+    /// By default, this is 96,000 frames, or two seconds at 48kHz.
+    pub buffer_frames: NonZeroU32,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+            buffer_frames: NonZeroU32::new(96_000).unwrap(),
+        }
+    }
+}
+
+/// A single-producer, single-consumer ring of interleaved samples.
+#[derive(Debug)]
+struct InnerState {
+    buffer: Box<[AtomicU32]>,
+    channels: usize,
+    write: AtomicUsize,
+    read: AtomicUsize,
+    active: AtomicBool,
+    sample_rate: AtomicU32,
+    dropped_frames: AtomicUsize,
+}
+
+impl InnerState {
+    fn new(channels: usize, frames: usize) -> Self {
+        Self {
+            buffer: (0..channels * frames).map(|_| AtomicU32::new(0)).collect(),
+            channels,
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            active: AtomicBool::new(false),
+            sample_rate: AtomicU32::new(0),
+            dropped_frames: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push planar frames into the ring. Called from the audio thread.
+    fn push(&self, inputs: &[&[f32]], frames: usize) {
+        let len = self.buffer.len();
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+
+        let free = len - write.wrapping_sub(read);
+        if free < frames * self.channels {
+            self.dropped_frames.fetch_add(frames, Ordering::Relaxed);
+            return;
+        }
+
+        let mut index = write;
+        for frame in 0..frames {
+            for input in inputs {
+                self.buffer[index % len].store(input[frame].to_bits(), Ordering::Relaxed);
+                index = index.wrapping_add(1);
+            }
+        }
+
+        self.write.store(index, Ordering::Release);
+    }
+
+    /// Pop all available samples. Called from the ECS.
+    fn drain(&self, mut f: impl FnMut(f32)) {
+        let len = self.buffer.len();
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+
+        let mut index = read;
+        while index != write {
+            f(f32::from_bits(
+                self.buffer[index % len].load(Ordering::Relaxed),
+            ));
+            index = index.wrapping_add(1);
+        }
+
+        self.read.store(write, Ordering::Release);
+    }
+
+    /// Discard any buffered samples. Called from the ECS.
+    fn clear(&self) {
+        let write = self.write.load(Ordering::Acquire);
+        self.read.store(write, Ordering::Release);
+    }
+}
+
+/// The shared state used by [`RecorderNode`] to hand audio off to the ECS.
+#[derive(Debug, Clone)]
+pub struct RecorderState(ArcGc<InnerState>);
+
+impl RecorderState {
+    /// Returns `true` if the recorder is writing to a file.
+    pub fn is_recording(&self) -> bool {
+        self.0.active.load(Ordering::Relaxed)
+    }
+
+    /// The total number of frames dropped because the
+    /// buffer was full.
+    ///
+    /// If this is increasing, consider raising
+    /// [`RecorderConfig::buffer_frames`].
+    pub fn dropped_frames(&self) -> usize {
+        self.0.dropped_frames.load(Ordering::Relaxed)
+    }
+}
+
+impl AudioNode for RecorderNode {
+    type Configuration = RecorderConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("recorder")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: ChannelCount::ZERO,
+            })
+            .custom_state(RecorderState(ArcGc::new(InnerState::new(
+                config.channels.get().get() as usize,
+                config.buffer_frames.get() as usize,
+            ))))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let state: RecorderState = cx.custom_state().cloned().unwrap();
+        state
+            .0
+            .sample_rate
+            .store(cx.stream_info.sample_rate.get(), Ordering::Relaxed);
+
+        RecorderProcessor {
+            params: self.clone(),
+            state,
+        }
+    }
+}
+
+struct RecorderProcessor {
+    params: RecorderNode,
+    state: RecorderState,
+}
+
+impl AudioNodeProcessor for RecorderProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<RecorderNode>() {
+            self.params.apply(patch);
+        }
+
+        if !self.params.paused && self.state.0.active.load(Ordering::Relaxed) {
+            self.state.0.push(inputs, proc_info.frames);
+        }
+
+        ProcessStatus::Bypass
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.state
+            .0
+            .sample_rate
+            .store(stream_info.sample_rate.get(), Ordering::Relaxed);
+    }
+}
+
+struct WavFile {
+    writer: BufWriter<File>,
+    sample_rate: u32,
+    channels: u16,
+    samples: usize,
+}
+
+impl WavFile {
+    fn create(path: &Path, sample_rate: u32, channels: u16) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        crate::utils::wav::write_header(&mut writer, sample_rate, channels, 0)?;
+
+        Ok(Self {
+            writer,
+            sample_rate,
+            channels,
+            samples: 0,
+        })
+    }
+
+    fn write(&mut self, sample: f32) -> std::io::Result<()> {
+        self.samples += 1;
+        self.writer.write_all(&sample.to_le_bytes())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        crate::utils::wav::write_header(
+            &mut self.writer,
+            self.sample_rate,
+            self.channels,
+            self.samples,
+        )?;
+        self.writer
+            .seek(SeekFrom::Start(crate::utils::wav::HEADER_LEN))?;
+        self.writer.flush()
+    }
+}
+
+impl Drop for WavFile {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            bevy_log::error!("failed to finalize recording: {e}");
+        }
+    }
+}
+
+/// Record a [`RecorderNode`]'s input to a WAV file.
+///
+/// The file is created once the recorder is ready, and finalized
+/// when this component is removed or dropped. Samples are
+/// written as 32-bit floats.
+#[derive(Component)]
+pub struct Recording {
+    path: PathBuf,
+    file: Option<WavFile>,
+    failed: bool,
+}
+
+impl core::fmt::Debug for Recording {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Recording")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Recording {
+    /// Record to the file at `path`, overwriting it if it exists.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: None,
+            failed: false,
+        }
+    }
+
+    /// The path being recorded to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn drain(&mut self, state: &InnerState) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+
+        let mut result = Ok(());
+        state.drain(|sample| {
+            if result.is_ok() {
+                result = file.write(sample);
+            }
+        });
+
+        if let Err(e) = result {
+            bevy_log::error!("failed to write recording to {:?}: {e}", self.path);
+            state.active.store(false, Ordering::Relaxed);
+            self.file = None;
+            self.failed = true;
+        }
+    }
+}
+
+pub(crate) fn write_recordings(mut recorders: Query<(&AudioState<RecorderState>, &mut Recording)>) {
+    for (state, mut recording) in &mut recorders {
+        let state = &state.0.0;
+        if recording.failed {
+            continue;
+        }
+
+        if recording.file.is_none() {
+            // The processor hasn't been constructed yet.
+            let sample_rate = state.sample_rate.load(Ordering::Relaxed);
+            if sample_rate == 0 {
+                continue;
+            }
+
+            match WavFile::create(&recording.path, sample_rate, state.channels as u16) {
+                Ok(file) => {
+                    state.clear();
+                    state.active.store(true, Ordering::Relaxed);
+                    recording.file = Some(file);
+                }
+                Err(e) => {
+                    bevy_log::error!("failed to create recording at {:?}: {e}", recording.path);
+                    recording.failed = true;
+                }
+            }
+
+            continue;
+        }
+
+        recording.drain(state);
+    }
+}
+
+pub(crate) fn stop_recording(
+    trigger: On<Remove, Recording>,
+    mut recorders: Query<(&AudioState<RecorderState>, &mut Recording)>,
+) {
+    let Ok((state, mut recording)) = recorders.get_mut(trigger.event_target()) else {
+        return;
+    };
+
+    let state = &state.0.0;
+    state.active.store(false, Ordering::Relaxed);
+    recording.drain(state);
+
+    // Dropping the file finalizes it.
+    recording.file = None;
+}
+
+/// Records the audio graph's input, like a microphone, into an [`AudioSample`].
+///
+/// Recording begins once the recorder is connected to the [`AudioGraphInput`],
+/// which happens automatically. When [stopped][MicrophoneRecorder::stop]
+/// or once it reaches its maximum duration, the captured audio is added
+/// to [`Assets<AudioSample>`] and a [`MicrophoneRecordingFinished`]
+/// event is triggered on the recorder's entity.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use core::time::Duration;
+/// fn record_call_out(mut commands: Commands) {
+///     commands
+///         .spawn(MicrophoneRecorder::new().with_max_duration(Duration::from_secs(3)))
+///         .observe(
+///             |finished: On<MicrophoneRecordingFinished>, mut commands: Commands| {
+///                 commands.spawn(SamplePlayer::new(finished.sample.clone()));
+///                 commands.entity(finished.entity).despawn();
+///             },
+///         );
+/// }
+///
+/// fn release_button(mut recorder: Single<&mut MicrophoneRecorder>) {
+///     recorder.stop();
+/// }
+/// ```
+///
+/// The graph has no input by default; make sure your backend is
+/// configured with an input device. The recorder captures the input's
+/// channels up to its [`RecorderConfig::channels`].
+#[derive(Debug, Default, Component)]
+#[require(RecorderNode)]
+pub struct MicrophoneRecorder {
+    max_duration: Option<Duration>,
+    frames: Vec<f32>,
+    started: bool,
+    stopping: bool,
+    finished: bool,
+}
+
+impl MicrophoneRecorder {
+    /// Create a new recorder without a maximum duration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop recording automatically after `duration`.
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Stop recording, producing an [`AudioSample`] with the audio captured so far.
+    ///
+    /// If the recorder hasn't started yet, it finishes as soon
+    /// as it does with an empty sample.
+    pub fn stop(&mut self) {
+        self.stopping = true;
+    }
+
+    /// Returns `true` once the recording has been turned into a sample.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Drain the captured audio, returning the sample once finished.
+    fn capture(&mut self, state: &InnerState) -> Option<AudioSample> {
+        if self.finished {
+            return None;
+        }
+
+        // The processor hasn't been constructed yet.
+        let sample_rate = NonZeroU32::new(state.sample_rate.load(Ordering::Relaxed))?;
+        let channels = NonZeroUsize::new(state.channels)?;
+
+        if !self.started {
+            state.clear();
+            state.active.store(true, Ordering::Relaxed);
+            self.started = true;
+        }
+
+        let frames = &mut self.frames;
+        state.drain(|sample| frames.push(sample));
+
+        if let Some(max) = self.max_duration {
+            let limit = (max.as_secs_f64() * sample_rate.get() as f64) as usize * channels.get();
+            if self.frames.len() >= limit {
+                self.frames.truncate(limit);
+                self.stopping = true;
+            }
+        }
+
+        if !self.stopping {
+            return None;
+        }
+
+        state.active.store(false, Ordering::Relaxed);
+        self.finished = true;
+
+        Some(AudioSample::from_frames(
+            core::mem::take(&mut self.frames),
+            channels,
+            sample_rate,
+        ))
+    }
+}
+
+/// An event triggered on a [`MicrophoneRecorder`]'s entity
+/// once its recording has been added as an [`AudioSample`].
+#[derive(Debug, Clone, EntityEvent)]
+pub struct MicrophoneRecordingFinished {
+    /// The recorder's entity.
+    pub entity: Entity,
+    /// The recorded sample.
+    pub sample: Handle<AudioSample>,
+}
+
+/// Marks a [`MicrophoneRecorder`] connected to the graph's input.
+#[derive(Debug, Component)]
+pub(crate) struct MicrophoneConnected;
+
+pub(crate) fn connect_microphones(
+    recorders: Query<Entity, (With<MicrophoneRecorder>, Without<MicrophoneConnected>)>,
+    input: Query<Entity, With<AudioGraphInput>>,
+    mut commands: Commands,
+) {
+    let Ok(input) = input.single() else {
+        return;
+    };
+
+    for recorder in &recorders {
+        commands.entity(input).connect(recorder);
+        commands.entity(recorder).insert(MicrophoneConnected);
+    }
+}
+
+pub(crate) fn capture_microphones(
+    mut recorders: Query<(Entity, &AudioState<RecorderState>, &mut MicrophoneRecorder)>,
+    mut samples: ResMut<Assets<AudioSample>>,
+    mut commands: Commands,
+) {
+    for (entity, state, mut recorder) in &mut recorders {
+        if let Some(sample) = recorder.capture(&state.0.0) {
+            commands.trigger(MicrophoneRecordingFinished {
+                entity,
+                sample: samples.add(sample),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_to_wav() {
+        let path = std::env::temp_dir().join("bevy_seedling_recorder_test.wav");
+        let state = InnerState::new(2, 64);
+        state.sample_rate.store(48000, Ordering::Relaxed);
+        state.active.store(true, Ordering::Relaxed);
+
+        let mut recording = Recording::new(&path);
+        recording.file = Some(WavFile::create(&path, 48000, 2).unwrap());
+
+        let left = [0.5f32; 48];
+        let right = [-0.5f32; 48];
+        state.push(&[&left, &right], 48);
+        // this exceeds the buffer's capacity
+        state.push(&[&left, &right], 48);
+        recording.drain(&state);
+        state.push(&[&left, &right], 16);
+        recording.drain(&state);
+
+        assert_eq!(state.dropped_frames.load(Ordering::Relaxed), 48);
+        drop(recording);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(bytes.len(), 44 + 64 * 2 * 4);
+        assert_eq!(&bytes[44..48], &0.5f32.to_le_bytes());
+        assert_eq!(&bytes[48..52], &(-0.5f32).to_le_bytes());
+    }
+
+    #[test]
+    fn test_microphone_capture() {
+        let state = InnerState::new(2, 64);
+        let mut recorder = MicrophoneRecorder::new().with_max_duration(Duration::from_millis(1));
+
+        // nothing happens until the processor starts
+        assert!(recorder.capture(&state).is_none());
+        state.sample_rate.store(32000, Ordering::Relaxed);
+        assert!(recorder.capture(&state).is_none());
+        assert!(state.active.load(Ordering::Relaxed));
+
+        let left = [0.5f32; 24];
+        let right = [-0.5f32; 24];
+        state.push(&[&left, &right], 24);
+        assert!(recorder.capture(&state).is_none());
+        state.push(&[&left, &right], 24);
+
+        // one millisecond at 32kHz is 32 frames
+        let sample = recorder.capture(&state).unwrap();
+        assert!(recorder.is_finished());
+        assert!(!state.active.load(Ordering::Relaxed));
+        assert_eq!(sample.get().num_channels().get(), 2);
+        assert_eq!(sample.get().len_frames(), 32);
+    }
+}
@@ -0,0 +1,461 @@
+//! Detection and scrubbing of invalid samples.
+//!
+//! A single NaN or infinite sample from a buggy node propagates through
+//! every node downstream, and stateful effects like filters and reverbs
+//! can hold onto it indefinitely. The [`SanitizerNode`] replaces these
+//! samples with silence and reports them, so a misbehaving branch of
+//! the graph can't blow out the entire output.
+//!
+//! With the `sanitize` feature enabled, the [`MainBus`][crate::prelude::MainBus] automatically
+//! routes its inputs through a sanitizer. To narrow down which branch
+//! produced invalid samples, insert [`Sanitize`] on other buses as well.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, nodes::sanitizer::*};
+//! fn sanitize_sfx(sfx: Single<Entity, With<SfxBus>>, mut commands: Commands) {
+//!     commands.entity(*sfx).insert(Sanitize);
+//! }
+//!
+//! fn report(event: On<SanitizerEvent>) {
+//!     error!("sanitizer {} scrubbed {} NaNs", event.entity, event.nan);
+//! }
+//! ```
+
+use crate::{
+    edge::Connect,
+    node::{AudioState, FirewheelNode},
+    prelude::AudioContext,
+};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashMap;
+use core::sync::atomic::{AtomicU64, Ordering};
+use firewheel::{
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A node that replaces NaN, infinite, and denormal samples with silence.
+///
+/// Each invalid sample is counted in the node's [`SanitizerState`],
+/// and new invalid samples trigger a [`SanitizerEvent`].
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SanitizerNode {
+    /// Whether denormal samples are flushed to zero.
+    ///
+    /// Denormals are valid, but they're far too quiet to hear and
+    /// can be dramatically slower to process on some hardware.
+    ///
+    /// By default, this is `true`.
+    pub flush_denormals: bool,
+}
+
+impl Default for SanitizerNode {
+    fn default() -> Self {
+        Self {
+            flush_denormals: true,
+        }
+    }
+}
+
+/// [`SanitizerNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SanitizerConfig {
+    /// The number of input and output channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for SanitizerConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct InnerState {
+    nan: AtomicU64,
+    infinite: AtomicU64,
+    denormal: AtomicU64,
+}
+
+/// The shared atomics used by [`SanitizerNode`] to
+/// count the invalid samples it has scrubbed.
+#[derive(Debug, Clone)]
+pub struct SanitizerState(ArcGc<InnerState>);
+
+impl SanitizerState {
+    /// The total number of NaN samples scrubbed.
+    pub fn nan(&self) -> u64 {
+        self.0.nan.load(Ordering::Relaxed)
+    }
+
+    /// The total number of infinite samples scrubbed.
+    pub fn infinite(&self) -> u64 {
+        self.0.infinite.load(Ordering::Relaxed)
+    }
+
+    /// The total number of denormal samples flushed.
+    pub fn denormal(&self) -> u64 {
+        self.0.denormal.load(Ordering::Relaxed)
+    }
+}
+
+impl AudioNode for SanitizerNode {
+    type Configuration = SanitizerConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("sanitizer")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+            .custom_state(SanitizerState(ArcGc::new(InnerState::default())))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        SanitizerProcessor {
+            params: self.clone(),
+            state: cx.custom_state().cloned().unwrap(),
+        }
+    }
+}
+
+struct SanitizerProcessor {
+    params: SanitizerNode,
+    state: SanitizerState,
+}
+
+impl AudioNodeProcessor for SanitizerProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<SanitizerNode>() {
+            self.params.apply(patch);
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let mut counts = [0u64; 3];
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            for (input, output) in input[..proc_info.frames]
+                .iter()
+                .zip(&mut output[..proc_info.frames])
+            {
+                *output = if input.is_nan() {
+                    counts[0] += 1;
+                    0.0
+                } else if input.is_infinite() {
+                    counts[1] += 1;
+                    0.0
+                } else if self.params.flush_denormals && input.is_subnormal() {
+                    counts[2] += 1;
+                    0.0
+                } else {
+                    *input
+                };
+            }
+        }
+
+        let state = &self.state.0;
+        for (count, atomic) in
+            counts
+                .into_iter()
+                .zip([&state.nan, &state.infinite, &state.denormal])
+        {
+            if count > 0 {
+                atomic.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+/// Route a bus's inputs through a [`SanitizerNode`].
+///
+/// Every connection into the bus is redirected into the sanitizer,
+/// including connections made after this component is inserted.
+/// Removing [`Sanitize`] restores the original connections.
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct Sanitize;
+
+/// The [`SanitizerNode`] in front of a bus with [`Sanitize`].
+#[derive(Debug, Component)]
+pub(crate) struct SanitizedBy(Entity);
+
+/// The bus a [`SanitizerNode`] was spawned for.
+#[derive(Debug, Component)]
+pub(crate) struct SanitizerOf(Entity);
+
+/// An event triggered on [`SanitizerNode`] entities when
+/// they've scrubbed invalid samples since the last report.
+#[derive(Debug, EntityEvent)]
+pub struct SanitizerEvent {
+    /// The [`SanitizerNode`] entity.
+    pub entity: Entity,
+    /// The bus whose inputs were sanitized, if the
+    /// node was spawned for a bus with [`Sanitize`].
+    pub bus: Option<Entity>,
+    /// The number of NaN samples scrubbed.
+    pub nan: u64,
+    /// The number of infinite samples scrubbed.
+    pub infinite: u64,
+    /// The number of denormal samples flushed.
+    pub denormal: u64,
+}
+
+#[cfg(feature = "sanitize")]
+pub(crate) fn sanitize_main_bus(trigger: On<Add, crate::prelude::MainBus>, mut commands: Commands) {
+    commands.entity(trigger.event_target()).insert(Sanitize);
+}
+
+pub(crate) fn attach_sanitizers(
+    buses: Query<(Entity, &FirewheelNode), (With<Sanitize>, Without<SanitizedBy>)>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    if buses.is_empty() {
+        return;
+    }
+
+    let ids: Vec<_> = buses.iter().map(|(_, node)| node.0).collect();
+    let channels = context.with(|context| {
+        ids.into_iter()
+            .map(|id| {
+                context
+                    .node_info(id)
+                    .and_then(|entry| {
+                        NonZeroChannelCount::new(entry.info.channel_config.num_inputs.get())
+                    })
+                    .unwrap_or(NonZeroChannelCount::STEREO)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for ((bus, _), channels) in buses.iter().zip(channels) {
+        let sanitizer = commands
+            .spawn((
+                SanitizerNode::default(),
+                SanitizerConfig { channels },
+                SanitizerOf(bus),
+            ))
+            .connect(bus)
+            .head();
+
+        commands.entity(bus).insert(SanitizedBy(sanitizer));
+    }
+}
+
+/// Redirect any new connections into sanitized buses.
+pub(crate) fn reroute_inputs(
+    buses: Query<(&FirewheelNode, &SanitizedBy), With<Sanitize>>,
+    sanitizers: Query<&FirewheelNode, With<SanitizerNode>>,
+    mut context: ResMut<AudioContext>,
+) {
+    let pairs: Vec<_> = buses
+        .iter()
+        .filter_map(|(bus, by)| Some((bus.0, sanitizers.get(by.0).ok()?.0)))
+        .collect();
+
+    if pairs.is_empty() {
+        return;
+    }
+
+    context.with(|context| {
+        for (bus, sanitizer) in pairs {
+            let edges: Vec<_> = context
+                .edges()
+                .into_iter()
+                .filter(|e| e.dst_node == bus && e.src_node != sanitizer)
+                .cloned()
+                .collect();
+
+            for edge in edges {
+                context.disconnect_by_edge_id(edge.id);
+                if let Err(e) = context.connect(
+                    edge.src_node,
+                    sanitizer,
+                    &[(edge.src_port, edge.dst_port)],
+                    false,
+                ) {
+                    warn!("failed to route connection through sanitizer: {e:?}");
+                }
+            }
+        }
+    });
+}
+
+pub(crate) fn remove_sanitizer(
+    trigger: On<Remove, Sanitize>,
+    buses: Query<(&FirewheelNode, &SanitizedBy)>,
+    sanitizers: Query<&FirewheelNode, With<SanitizerNode>>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    let bus = trigger.event_target();
+    let Ok((bus_node, by)) = buses.get(bus) else {
+        return;
+    };
+
+    if let Ok(sanitizer) = sanitizers.get(by.0) {
+        let (bus_node, sanitizer) = (bus_node.0, sanitizer.0);
+
+        context.with(|context| {
+            let edges: Vec<_> = context
+                .edges()
+                .into_iter()
+                .filter(|e| e.dst_node == sanitizer)
+                .cloned()
+                .collect();
+
+            for edge in edges {
+                // The source may have been removed in the meantime.
+                if let Err(e) = context.connect(
+                    edge.src_node,
+                    bus_node,
+                    &[(edge.src_port, edge.dst_port)],
+                    false,
+                ) {
+                    debug!("failed to restore edge for unsanitized bus: {e:?}");
+                }
+            }
+        });
+    }
+
+    commands.entity(by.0).despawn();
+    commands.entity(bus).remove::<SanitizedBy>();
+}
+
+pub(crate) fn report_sanitized(
+    sanitizers: Query<(Entity, &AudioState<SanitizerState>, Option<&SanitizerOf>)>,
+    names: Query<&Name>,
+    mut reported: Local<HashMap<Entity, [u64; 3]>>,
+    mut commands: Commands,
+) {
+    reported.retain(|entity, _| sanitizers.contains(*entity));
+
+    for (entity, state, bus) in &sanitizers {
+        let totals = [state.0.nan(), state.0.infinite(), state.0.denormal()];
+        let previous = reported.insert(entity, totals).unwrap_or_default();
+        if totals == previous {
+            continue;
+        }
+
+        let [nan, infinite, denormal] = [0, 1, 2].map(|i| totals[i] - previous[i]);
+        let bus = bus.map(|b| b.0);
+
+        if nan > 0 || infinite > 0 {
+            let source = match bus.map(|b| (b, names.get(b))) {
+                Some((_, Ok(name))) => format!("the inputs of `{name}`"),
+                Some((bus, Err(_))) => format!("the inputs of {bus}"),
+                None => format!("the inputs of sanitizer {entity}"),
+            };
+
+            warn!("Scrubbed {nan} NaN and {infinite} infinite samples from {source}");
+        }
+
+        commands.trigger(SanitizerEvent {
+            entity,
+            bus,
+            nan,
+            infinite,
+            denormal,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy_app::App;
+
+    #[test]
+    fn test_reroute_inputs() {
+        #[derive(Component)]
+        struct Source;
+
+        #[derive(Component)]
+        struct Bus;
+
+        let mut app = prepare_app(|mut commands: Commands| {
+            let bus = commands
+                .spawn((VolumeNode::default(), Bus, Sanitize))
+                .connect(AudioGraphOutput)
+                .head();
+
+            commands.spawn((VolumeNode::default(), Source)).connect(bus);
+        });
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let sources = |app: &mut App| {
+            run(
+                app,
+                |source: Single<&FirewheelNode, With<Source>>,
+                 bus: Single<(&FirewheelNode, &SanitizedBy), With<Bus>>,
+                 nodes: Query<&FirewheelNode>,
+                 mut context: ResMut<AudioContext>| {
+                    let (bus, by) = *bus;
+                    let (source, bus, sanitizer) = (source.0, bus.0, nodes.get(by.0).unwrap().0);
+                    context.with(|c| {
+                        let edges = c.edges();
+                        let into = |dst| {
+                            edges
+                                .iter()
+                                .filter(|e| e.src_node == source && e.dst_node == dst)
+                                .count()
+                        };
+
+                        (into(bus), into(sanitizer))
+                    })
+                },
+            )
+        };
+
+        assert_eq!(sources(&mut app), (0, 2));
+
+        let sanitizer = run(
+            &mut app,
+            |bus: Single<(Entity, &SanitizedBy), With<Bus>>, mut commands: Commands| {
+                commands.entity(bus.0).remove::<Sanitize>();
+                bus.1.0
+            },
+        );
+        app.update();
+
+        run(&mut app, move |q: Query<(), With<SanitizerNode>>| {
+            assert!(!q.contains(sanitizer));
+        });
+    }
+}
@@ -0,0 +1,203 @@
+//! Waveshaping saturation.
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The transfer curve of a [`SaturationNode`].
+#[derive(Diff, Patch, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum SaturationCurve {
+    /// A cubic curve that's linear near zero and bends
+    /// smoothly into clipping at full scale.
+    ///
+    /// This is the gentlest curve, well suited to bus glue.
+    #[default]
+    SoftClip,
+    /// A hyperbolic tangent curve.
+    ///
+    /// This compresses earlier than [`SaturationCurve::SoftClip`],
+    /// producing warmer, tape-like harmonics.
+    Tanh,
+    /// A hard clipper.
+    ///
+    /// This is linear up to full scale and flat beyond it,
+    /// producing harsh, buzzy harmonics when overdriven.
+    HardClip,
+}
+
+impl SaturationCurve {
+    /// Apply the curve to a single sample.
+    #[inline]
+    pub fn apply(&self, sample: f32) -> f32 {
+        match self {
+            Self::SoftClip => {
+                let x = sample.clamp(-1.0, 1.0);
+                1.5 * x - 0.5 * x * x * x
+            }
+            Self::Tanh => sample.tanh(),
+            Self::HardClip => sample.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+/// A waveshaping saturator.
+///
+/// The input is amplified by `drive`, shaped by `curve`,
+/// and scaled by `output`. Louder signals are bent further
+/// into the curve, adding harmonics and gently taming peaks.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn glue_main_bus(main: Single<Entity, With<MainBus>>, mut commands: Commands) {
+///     let saturation = commands
+///         .spawn(SaturationNode {
+///             curve: SaturationCurve::Tanh,
+///             drive: Volume::Decibels(3.0),
+///             output: Volume::Decibels(-3.0),
+///         })
+///         .connect(AudioGraphOutput)
+///         .head();
+///
+///     commands
+///         .entity(*main)
+///         .disconnect(AudioGraphOutput)
+///         .connect(saturation);
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SaturationNode {
+    /// The transfer curve.
+    ///
+    /// By default, this is [`SaturationCurve::SoftClip`].
+    pub curve: SaturationCurve,
+    /// Gain applied before the curve.
+    ///
+    /// By default, this is 6 dB.
+    pub drive: Volume,
+    /// Gain applied after the curve.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub output: Volume,
+}
+
+impl Default for SaturationNode {
+    fn default() -> Self {
+        Self {
+            curve: SaturationCurve::default(),
+            drive: Volume::Decibels(6.0),
+            output: Volume::UNITY_GAIN,
+        }
+    }
+}
+
+/// [`SaturationNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SaturationConfig {
+    /// The number of input and output channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for SaturationConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for SaturationNode {
+    type Configuration = SaturationConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("saturation")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        SaturationProcessor {
+            params: self.clone(),
+        }
+    }
+}
+
+struct SaturationProcessor {
+    params: SaturationNode,
+}
+
+impl AudioNodeProcessor for SaturationProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<SaturationNode>() {
+            self.params.apply(patch);
+        }
+
+        // Every curve maps silence to silence.
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let curve = self.params.curve;
+        let drive = self.params.drive.amp();
+        let output_gain = self.params.output.amp();
+
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            for (input, output) in input[..proc_info.frames]
+                .iter()
+                .zip(&mut output[..proc_info.frames])
+            {
+                *output = curve.apply(input * drive) * output_gain;
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_saturation_curves() {
+        for curve in [
+            SaturationCurve::SoftClip,
+            SaturationCurve::Tanh,
+            SaturationCurve::HardClip,
+        ] {
+            assert_eq!(curve.apply(0.0), 0.0);
+            // Every curve is odd-symmetric and bounded at full scale.
+            assert_eq!(curve.apply(-0.5), -curve.apply(0.5));
+            assert!(curve.apply(10.0) <= 1.0);
+        }
+
+        assert_eq!(SaturationCurve::HardClip.apply(0.5), 0.5);
+        assert_eq!(SaturationCurve::SoftClip.apply(1.0), 1.0);
+        assert_eq!(SaturationCurve::SoftClip.apply(2.0), 1.0);
+        assert!(SaturationCurve::Tanh.apply(0.5) < 0.5);
+    }
+}
@@ -0,0 +1,329 @@
+//! Low and high shelving filters.
+
+use crate::nodes::multiband::Biquad;
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+    param::smoother::{SmoothedParam, SmootherConfig},
+};
+
+/// A low-shelf filter.
+///
+/// This boosts or cuts all frequencies below the corner frequency
+/// by a fixed amount, which is useful for adding weight to or
+/// removing rumble from a bus.
+///
+/// ```
+/// # use bevy_seedling::prelude::*;
+/// # use bevy::prelude::*;
+/// fn warm_music(mut commands: Commands, server: Res<AssetServer>) {
+///     commands
+///         .spawn(SamplePlayer::new(server.load("music.ogg")))
+///         .chain_node(LowShelfNode {
+///             gain: 3.0,
+///             ..Default::default()
+///         });
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct LowShelfNode {
+    /// The corner frequency in hertz.
+    ///
+    /// By default, this is 200 Hz.
+    pub frequency: f32,
+    /// The gain applied below the corner, in decibels.
+    ///
+    /// By default, this is 0 dB.
+    pub gain: f32,
+    /// The steepness of the transition, from 0 to 1.
+    ///
+    /// A slope of 1 is the steepest transition without overshoot.
+    ///
+    /// By default, this is 1.
+    pub slope: f32,
+}
+
+impl Default for LowShelfNode {
+    fn default() -> Self {
+        Self {
+            frequency: 200.0,
+            gain: 0.0,
+            slope: 1.0,
+        }
+    }
+}
+
+/// A high-shelf filter.
+///
+/// This boosts or cuts all frequencies above the corner frequency
+/// by a fixed amount, which is useful for adding air to or
+/// darkening a bus.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct HighShelfNode {
+    /// The corner frequency in hertz.
+    ///
+    /// By default, this is 4 kHz.
+    pub frequency: f32,
+    /// The gain applied above the corner, in decibels.
+    ///
+    /// By default, this is 0 dB.
+    pub gain: f32,
+    /// The steepness of the transition, from 0 to 1.
+    ///
+    /// A slope of 1 is the steepest transition without overshoot.
+    ///
+    /// By default, this is 1.
+    pub slope: f32,
+}
+
+impl Default for HighShelfNode {
+    fn default() -> Self {
+        Self {
+            frequency: 4000.0,
+            gain: 0.0,
+            slope: 1.0,
+        }
+    }
+}
+
+/// The configuration shared by [`LowShelfNode`] and [`HighShelfNode`].
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ShelfConfig {
+    /// The parameter smoothing config used for all parameters.
+    pub smoother_config: SmootherConfig,
+    /// The number of input and output channels.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for ShelfConfig {
+    fn default() -> Self {
+        Self {
+            smoother_config: Default::default(),
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+/// Common access to both shelf nodes' parameters.
+trait Shelf: Diff + Patch + Clone + Send + 'static {
+    const HIGH: bool;
+
+    /// The frequency, gain, and slope.
+    fn params(&self) -> [f32; 3];
+}
+
+impl Shelf for LowShelfNode {
+    const HIGH: bool = false;
+
+    fn params(&self) -> [f32; 3] {
+        [self.frequency, self.gain, self.slope]
+    }
+}
+
+impl Shelf for HighShelfNode {
+    const HIGH: bool = true;
+
+    fn params(&self) -> [f32; 3] {
+        [self.frequency, self.gain, self.slope]
+    }
+}
+
+impl AudioNode for LowShelfNode {
+    type Configuration = ShelfConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("low-shelf filter")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        ShelfProcessor::new(self.clone(), config, cx)
+    }
+}
+
+impl AudioNode for HighShelfNode {
+    type Configuration = ShelfConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("high-shelf filter")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        config: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        ShelfProcessor::new(self.clone(), config, cx)
+    }
+}
+
+/// How often the coefficients are recalculated while smoothing.
+const UPDATE_INTERVAL: usize = 32;
+
+struct ShelfProcessor<T> {
+    params: T,
+    sample_rate: f32,
+    /// The smoothed frequency, gain, and slope.
+    smoothed: [SmoothedParam; 3],
+    channels: Vec<Biquad>,
+}
+
+impl<T: Shelf> ShelfProcessor<T> {
+    fn new(params: T, config: &ShelfConfig, cx: ConstructProcessorContext) -> Self {
+        let sample_rate = cx.stream_info.sample_rate;
+        let smoothed = params
+            .params()
+            .map(|value| SmoothedParam::new(value, config.smoother_config, sample_rate));
+
+        let mut processor = Self {
+            params,
+            sample_rate: sample_rate.get() as f32,
+            smoothed,
+            channels: vec![Biquad::default(); config.channels.get().get() as usize],
+        };
+        processor.set_coefficients(processor.params.params());
+
+        processor
+    }
+
+    fn set_coefficients(&mut self, [frequency, gain, slope]: [f32; 3]) {
+        let frequency = frequency.clamp(10.0, self.sample_rate * 0.45);
+        let gain = gain.clamp(-48.0, 48.0);
+        let slope = slope.clamp(0.01, 1.0);
+
+        for channel in &mut self.channels {
+            channel.set_shelf(self.sample_rate, frequency, slope, gain, T::HIGH);
+        }
+    }
+}
+
+impl<T: Shelf> AudioNodeProcessor for ShelfProcessor<T> {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        let mut changed = false;
+        for patch in events.drain_patches::<T>() {
+            self.params.apply(patch);
+            changed = true;
+        }
+
+        if changed {
+            for (param, value) in self.smoothed.iter_mut().zip(self.params.params()) {
+                param.set_value(value);
+            }
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            for param in &mut self.smoothed {
+                param.reset();
+            }
+            for channel in &mut self.channels {
+                channel.reset();
+            }
+            if changed {
+                self.set_coefficients(self.params.params());
+            }
+
+            // All inputs are silent.
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        if self.smoothed.iter().any(|p| p.is_smoothing()) {
+            for sample in 0..proc_info.frames {
+                let values = self.smoothed.each_mut().map(|p| p.next_smoothed());
+                if sample % UPDATE_INTERVAL == 0 {
+                    self.set_coefficients(values);
+                }
+
+                for (i, channel) in self.channels.iter_mut().enumerate() {
+                    outputs[i][sample] = channel.process(inputs[i][sample]);
+                }
+            }
+
+            for param in &mut self.smoothed {
+                param.settle();
+            }
+        } else {
+            if changed {
+                self.set_coefficients(self.smoothed.each_ref().map(|p| p.target_value()));
+            }
+
+            for (channel, (input, output)) in self
+                .channels
+                .iter_mut()
+                .zip(inputs.iter().zip(outputs.iter_mut()))
+            {
+                for (input, output) in input[..proc_info.frames]
+                    .iter()
+                    .zip(&mut output[..proc_info.frames])
+                {
+                    *output = channel.process(*input);
+                }
+            }
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        for param in &mut self.smoothed {
+            param.update_sample_rate(stream_info.sample_rate);
+        }
+        self.set_coefficients(self.smoothed.each_ref().map(|p| p.target_value()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gain(filter: &mut Biquad, frequency: f32) -> f32 {
+        filter.reset();
+        (0..48000)
+            .map(|i| (core::f32::consts::TAU * frequency * i as f32 / 48000.0).sin())
+            .map(|s| filter.process(s))
+            // Skip the filter's settling time.
+            .skip(24000)
+            .fold(0f32, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn test_shelves() {
+        let mut low = Biquad::default();
+        low.set_shelf(48000.0, 1000.0, 1.0, 6.0, false);
+        assert!((gain(&mut low, 30.0) - 2.0).abs() < 0.05);
+        assert!((gain(&mut low, 15000.0) - 1.0).abs() < 0.05);
+
+        let mut high = Biquad::default();
+        high.set_shelf(48000.0, 1000.0, 1.0, -6.0, true);
+        assert!((gain(&mut high, 30.0) - 1.0).abs() < 0.05);
+        assert!((gain(&mut high, 15000.0) - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,606 @@
+//! SoundFont 2 parsing.
+
+use bevy_asset::{Asset, AssetLoader};
+use bevy_reflect::TypePath;
+use firewheel::collector::ArcGc;
+use std::ops::RangeInclusive;
+
+/// A loaded SoundFont 2 file.
+///
+/// SoundFonts are played by a [`SoundFontNode`][super::SoundFontNode]
+/// through a [`SoundFontSource`][super::SoundFontSource].
+#[derive(Asset, TypePath, Clone)]
+pub struct SoundFontFile(ArcGc<SoundFont>);
+
+impl SoundFontFile {
+    /// Parse a SoundFont from its bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SoundFontLoaderError> {
+        Ok(Self(ArcGc::new(SoundFont::parse(bytes)?)))
+    }
+
+    /// Share the inner value.
+    pub fn get(&self) -> ArcGc<SoundFont> {
+        self.0.clone()
+    }
+}
+
+impl core::fmt::Debug for SoundFontFile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SoundFontFile").field(&*self.0).finish()
+    }
+}
+
+/// A parsed SoundFont's presets and sample data.
+pub struct SoundFont {
+    /// Every sample, concatenated.
+    pub(super) samples: Vec<f32>,
+    pub(super) presets: Vec<Preset>,
+}
+
+impl SoundFont {
+    /// The number of presets, or instruments, in the SoundFont.
+    pub fn num_presets(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Find the preset for a bank and program.
+    ///
+    /// Missing presets fall back to the same program in the
+    /// first bank of their kind, and then to any preset at all.
+    pub(super) fn preset(&self, bank: u16, program: u8) -> Option<&Preset> {
+        let fallback_bank = if bank >= 128 { 128 } else { 0 };
+
+        self.presets
+            .iter()
+            .find(|p| p.bank == bank && p.program == program)
+            .or_else(|| {
+                self.presets
+                    .iter()
+                    .find(|p| p.bank == fallback_bank && p.program == program)
+            })
+            .or_else(|| self.presets.iter().find(|p| p.bank == fallback_bank))
+            .or_else(|| self.presets.first())
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, SoundFontLoaderError> {
+        use SoundFontLoaderError::Format;
+
+        if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"sfbk") {
+            return Err(Format("not a SoundFont 2 file"));
+        }
+
+        let mut samples = None;
+        let mut hydra = Hydra::default();
+
+        for (id, data) in chunks(&bytes[12..]) {
+            if id != b"LIST" || data.len() < 4 {
+                continue;
+            }
+
+            for (id, data) in chunks(&data[4..]) {
+                match id {
+                    b"smpl" => {
+                        samples = Some(
+                            data.chunks_exact(2)
+                                .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+                                .collect::<Vec<_>>(),
+                        )
+                    }
+                    b"phdr" => hydra.phdr = data,
+                    b"pbag" => hydra.pbag = data,
+                    b"pgen" => hydra.pgen = data,
+                    b"inst" => hydra.inst = data,
+                    b"ibag" => hydra.ibag = data,
+                    b"igen" => hydra.igen = data,
+                    b"shdr" => hydra.shdr = data,
+                    _ => {}
+                }
+            }
+        }
+
+        let samples = samples.ok_or(Format("missing sample data"))?;
+        let presets = hydra
+            .presets(samples.len())
+            .ok_or(Format("malformed preset data"))?;
+
+        Ok(Self { samples, presets })
+    }
+}
+
+impl core::fmt::Debug for SoundFont {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SoundFont")
+            .field("presets", &self.presets.len())
+            .field("samples", &self.samples.len())
+            .finish()
+    }
+}
+
+/// Iterate over a sequence of RIFF chunks.
+fn chunks(mut bytes: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    core::iter::from_fn(move || {
+        let id = bytes.get(0..4)?;
+        let len = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?) as usize;
+        let data = bytes.get(8..8 + len)?;
+
+        // Chunks are padded to an even length.
+        bytes = bytes.get(8 + len + (len & 1)..).unwrap_or_default();
+        Some((id, data))
+    })
+}
+
+/// A playable preset.
+pub(super) struct Preset {
+    pub(super) bank: u16,
+    pub(super) program: u8,
+    pub(super) regions: Vec<Region>,
+}
+
+/// A sample mapped to a range of keys and velocities.
+#[derive(Debug, Clone)]
+pub(super) struct Region {
+    pub(super) keys: RangeInclusive<u8>,
+    pub(super) velocities: RangeInclusive<u8>,
+    pub(super) start: usize,
+    pub(super) end: usize,
+    /// The loop region, if the sample loops.
+    pub(super) looping: Option<(usize, usize)>,
+    pub(super) sample_rate: f64,
+    pub(super) root_key: u8,
+    /// Fine tuning in semitones.
+    pub(super) tune: f64,
+    /// Linear gain from the region's attenuation.
+    pub(super) gain: f32,
+    /// Stereo position, from -1 to 1.
+    pub(super) pan: f32,
+    pub(super) envelope: Envelope,
+}
+
+/// A region's volume envelope, in seconds.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Envelope {
+    pub(super) delay: f32,
+    pub(super) attack: f32,
+    pub(super) hold: f32,
+    pub(super) decay: f32,
+    /// The sustain level as linear gain.
+    pub(super) sustain: f32,
+    pub(super) release: f32,
+}
+
+// Generator operators.
+const START_OFFSET: usize = 0;
+const END_OFFSET: usize = 1;
+const LOOP_START_OFFSET: usize = 2;
+const LOOP_END_OFFSET: usize = 3;
+const START_COARSE_OFFSET: usize = 4;
+const END_COARSE_OFFSET: usize = 12;
+const PAN: usize = 17;
+const DELAY_VOL_ENV: usize = 33;
+const ATTACK_VOL_ENV: usize = 34;
+const HOLD_VOL_ENV: usize = 35;
+const DECAY_VOL_ENV: usize = 36;
+const SUSTAIN_VOL_ENV: usize = 37;
+const RELEASE_VOL_ENV: usize = 38;
+const INSTRUMENT: usize = 41;
+const KEY_RANGE: usize = 43;
+const VEL_RANGE: usize = 44;
+const LOOP_START_COARSE_OFFSET: usize = 45;
+const INITIAL_ATTENUATION: usize = 48;
+const LOOP_END_COARSE_OFFSET: usize = 50;
+const COARSE_TUNE: usize = 51;
+const FINE_TUNE: usize = 52;
+const SAMPLE_ID: usize = 53;
+const SAMPLE_MODES: usize = 54;
+const OVERRIDING_ROOT_KEY: usize = 58;
+const GENERATORS: usize = 61;
+
+/// A zone's generator values.
+#[derive(Clone, Copy)]
+struct Zone([Option<i16>; GENERATORS]);
+
+impl Default for Zone {
+    fn default() -> Self {
+        Self([None; GENERATORS])
+    }
+}
+
+impl Zone {
+    fn get(&self, generator: usize) -> Option<i16> {
+        self.0[generator]
+    }
+
+    /// A generator's value, or the SoundFont default.
+    fn value(&self, generator: usize) -> i16 {
+        self.0[generator].unwrap_or(match generator {
+            DELAY_VOL_ENV | ATTACK_VOL_ENV | HOLD_VOL_ENV | DECAY_VOL_ENV | RELEASE_VOL_ENV => {
+                -12000
+            }
+            OVERRIDING_ROOT_KEY => -1,
+            _ => 0,
+        })
+    }
+
+    fn range(&self, generator: usize) -> RangeInclusive<u8> {
+        match self.0[generator] {
+            Some(range) => {
+                let [low, high] = range.to_le_bytes();
+                low..=high
+            }
+            None => 0..=127,
+        }
+    }
+
+    /// Override this zone's generators with another's.
+    fn merge(&self, local: &Zone) -> Zone {
+        let mut zone = *self;
+        for (value, local) in zone.0.iter_mut().zip(local.0) {
+            if local.is_some() {
+                *value = local;
+            }
+        }
+        zone
+    }
+}
+
+fn intersect(a: RangeInclusive<u8>, b: RangeInclusive<u8>) -> RangeInclusive<u8> {
+    *a.start().max(b.start())..=*a.end().min(b.end())
+}
+
+fn timecents(value: i16) -> f32 {
+    2f32.powf(value as f32 / 1200.0)
+}
+
+fn centibels(value: i16) -> f32 {
+    10f32.powf(-(value.max(0) as f32) / 200.0)
+}
+
+/// The raw preset, instrument, and sample tables.
+#[derive(Default)]
+struct Hydra<'a> {
+    phdr: &'a [u8],
+    pbag: &'a [u8],
+    pgen: &'a [u8],
+    inst: &'a [u8],
+    ibag: &'a [u8],
+    igen: &'a [u8],
+    shdr: &'a [u8],
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+impl Hydra<'_> {
+    /// Collect the zones of each record in a header table.
+    ///
+    /// Each record's zones span from its bag index to the next record's.
+    /// A first zone without a terminal generator is the record's global zone.
+    fn zones(
+        headers: &[u8],
+        size: usize,
+        bag_offset: usize,
+        bags: &[u8],
+        generators: &[u8],
+        terminal: usize,
+    ) -> Option<Vec<(Zone, Vec<Zone>)>> {
+        let count = (headers.len() / size).checked_sub(1)?;
+        let mut records = Vec::with_capacity(count);
+
+        for record in 0..count {
+            let first = u16_at(headers, record * size + bag_offset)? as usize;
+            let last = u16_at(headers, (record + 1) * size + bag_offset)? as usize;
+
+            let mut global = Zone::default();
+            let mut zones = Vec::new();
+            for bag in first..last {
+                let gen_first = u16_at(bags, bag * 4)? as usize;
+                let gen_last = u16_at(bags, (bag + 1) * 4)? as usize;
+
+                let mut zone = Zone::default();
+                let mut has_terminal = false;
+                for generator in gen_first..gen_last {
+                    let operator = u16_at(generators, generator * 4)? as usize;
+                    let amount = u16_at(generators, generator * 4 + 2)? as i16;
+                    if operator < GENERATORS {
+                        zone.0[operator] = Some(amount);
+                        has_terminal |= operator == terminal;
+                    }
+                }
+
+                if has_terminal {
+                    zones.push(zone);
+                } else if bag == first {
+                    global = zone;
+                }
+            }
+
+            records.push((global, zones));
+        }
+
+        Some(records)
+    }
+
+    fn presets(&self, num_samples: usize) -> Option<Vec<Preset>> {
+        let instruments = Self::zones(self.inst, 22, 20, self.ibag, self.igen, SAMPLE_ID)?;
+        let presets = Self::zones(self.phdr, 38, 24, self.pbag, self.pgen, INSTRUMENT)?;
+
+        presets
+            .iter()
+            .enumerate()
+            .map(|(index, (global, zones))| {
+                let header = index * 38;
+                let mut regions = Vec::new();
+
+                for preset_zone in zones {
+                    let preset_zone = global.merge(preset_zone);
+                    let Some((inst_global, inst_zones)) = preset_zone
+                        .get(INSTRUMENT)
+                        .and_then(|i| instruments.get(i as u16 as usize))
+                    else {
+                        continue;
+                    };
+
+                    for inst_zone in inst_zones {
+                        let zone = inst_global.merge(inst_zone);
+                        if let Some(region) = self.region(&preset_zone, &zone, num_samples) {
+                            regions.push(region);
+                        }
+                    }
+                }
+
+                Some(Preset {
+                    program: u16_at(self.phdr, header + 20)? as u8,
+                    bank: u16_at(self.phdr, header + 22)?,
+                    regions,
+                })
+            })
+            .collect()
+    }
+
+    /// Combine a preset zone and an instrument zone into a playable region.
+    fn region(&self, preset: &Zone, zone: &Zone, num_samples: usize) -> Option<Region> {
+        let sample = zone.get(SAMPLE_ID)? as u16 as usize * 46;
+        let header = self.shdr.get(sample..sample + 46)?;
+
+        let offset = |fine: usize, coarse: usize| {
+            zone.value(fine) as isize + zone.value(coarse) as isize * 32768
+        };
+        let address = |base: u32, offset: isize| {
+            (base as isize + offset).clamp(0, num_samples as isize) as usize
+        };
+
+        let start = address(
+            u32_at(header, 20)?,
+            offset(START_OFFSET, START_COARSE_OFFSET),
+        );
+        let end = address(u32_at(header, 24)?, offset(END_OFFSET, END_COARSE_OFFSET));
+        let loop_start = address(
+            u32_at(header, 28)?,
+            offset(LOOP_START_OFFSET, LOOP_START_COARSE_OFFSET),
+        );
+        let loop_end = address(
+            u32_at(header, 32)?,
+            offset(LOOP_END_OFFSET, LOOP_END_COARSE_OFFSET),
+        );
+        let sample_rate = u32_at(header, 36)?;
+        let original_key = header[40];
+        let correction = header[41] as i8;
+
+        if start >= end || sample_rate == 0 {
+            return None;
+        }
+
+        // Modes 1 and 3 loop, with 3 playing out the rest of the sample on release.
+        let looping = matches!(zone.value(SAMPLE_MODES) & 3, 1 | 3)
+            && loop_start < loop_end
+            && loop_end <= end;
+
+        let root_key = match zone.value(OVERRIDING_ROOT_KEY) {
+            key @ 0..=127 => key as u8,
+            _ => original_key.min(127),
+        };
+
+        // Preset generators are offsets to the instrument's values.
+        let sum = |generator: usize| zone.value(generator) + preset.get(generator).unwrap_or(0);
+
+        let tune = sum(COARSE_TUNE) as f64 + (sum(FINE_TUNE) as f64 + correction as f64) / 100.0;
+
+        Some(Region {
+            keys: intersect(preset.range(KEY_RANGE), zone.range(KEY_RANGE)),
+            velocities: intersect(preset.range(VEL_RANGE), zone.range(VEL_RANGE)),
+            start,
+            end,
+            looping: looping.then_some((loop_start, loop_end)),
+            sample_rate: sample_rate as f64,
+            root_key,
+            tune,
+            gain: centibels(sum(INITIAL_ATTENUATION)),
+            pan: (sum(PAN) as f32 / 500.0).clamp(-1.0, 1.0),
+            envelope: Envelope {
+                delay: timecents(sum(DELAY_VOL_ENV)),
+                attack: timecents(sum(ATTACK_VOL_ENV)),
+                hold: timecents(sum(HOLD_VOL_ENV)),
+                decay: timecents(sum(DECAY_VOL_ENV)),
+                sustain: centibels(sum(SUSTAIN_VOL_ENV)),
+                release: timecents(sum(RELEASE_VOL_ENV)),
+            },
+        })
+    }
+}
+
+/// A loader for SoundFont 2 files.
+#[derive(Debug, Default)]
+pub struct SoundFontLoader;
+
+/// Errors produced while loading SoundFonts.
+#[derive(Debug)]
+pub enum SoundFontLoaderError {
+    /// An I/O error, such as missing files.
+    StdIo(std::io::Error),
+    /// The SoundFont is malformed.
+    Format(&'static str),
+}
+
+impl From<std::io::Error> for SoundFontLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        Self::StdIo(value)
+    }
+}
+
+impl std::error::Error for SoundFontLoaderError {}
+
+impl std::fmt::Display for SoundFontLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StdIo(stdio) => stdio.fmt(f),
+            Self::Format(format) => f.write_str(format),
+        }
+    }
+}
+
+impl AssetLoader for SoundFontLoader {
+    type Asset = SoundFontFile;
+    type Settings = ();
+    type Error = SoundFontLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy_asset::io::Reader,
+        _: &Self::Settings,
+        _: &mut bevy_asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        SoundFontFile::from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sf2"]
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        if data.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn list(kind: &[u8], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = kind.to_vec();
+        for c in chunks {
+            data.extend(c);
+        }
+        chunk(b"LIST", &data)
+    }
+
+    fn record(name_len: usize, fields: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0; name_len];
+        for field in fields {
+            bytes.extend(*field);
+        }
+        bytes
+    }
+
+    /// A minimal SoundFont with one preset playing a looping square
+    /// wave, rooted at middle C, across the whole keyboard.
+    pub(in super::super) fn square_font() -> Vec<u8> {
+        let wave: Vec<u8> = (0..100)
+            .flat_map(|i| if i % 50 < 25 { 16000i16 } else { -16000 }.to_le_bytes())
+            .collect();
+
+        let u16 = |v: u16| v.to_le_bytes();
+        let u32 = |v: u32| v.to_le_bytes();
+
+        let phdr = [
+            record(20, &[&u16(0), &u16(0), &u16(0), &u32(0), &u32(0), &u32(0)]),
+            record(20, &[&u16(0), &u16(0), &u16(1), &u32(0), &u32(0), &u32(0)]),
+        ]
+        .concat();
+        let pbag = [u16(0), u16(0), u16(1), u16(0)].concat();
+        let pgen = [u16(INSTRUMENT as u16), u16(0), u16(0), u16(0)].concat();
+        let inst = [record(20, &[&u16(0)]), record(20, &[&u16(1)])].concat();
+        let ibag = [u16(0), u16(0), u16(2), u16(0)].concat();
+        let igen = [
+            u16(SAMPLE_MODES as u16),
+            u16(1),
+            u16(SAMPLE_ID as u16),
+            u16(0),
+            u16(0),
+            u16(0),
+        ]
+        .concat();
+        let shdr = [
+            record(
+                20,
+                &[
+                    &u32(0),
+                    &u32(100),
+                    &u32(0),
+                    &u32(100),
+                    &u32(44100),
+                    &[60, 0],
+                    &u16(0),
+                    &u16(1),
+                ],
+            ),
+            record(20, &[&[0; 26]]),
+        ]
+        .concat();
+
+        let body = [
+            b"sfbk".to_vec(),
+            list(b"sdta", &[chunk(b"smpl", &wave)]),
+            list(
+                b"pdta",
+                &[
+                    chunk(b"phdr", &phdr),
+                    chunk(b"pbag", &pbag),
+                    chunk(b"pgen", &pgen),
+                    chunk(b"inst", &inst),
+                    chunk(b"ibag", &ibag),
+                    chunk(b"igen", &igen),
+                    chunk(b"shdr", &shdr),
+                ],
+            ),
+        ]
+        .concat();
+
+        chunk(b"RIFF", &body)
+    }
+
+    #[test]
+    fn test_parse_soundfont() {
+        let font = SoundFont::parse(&square_font()).unwrap();
+        assert_eq!(font.num_presets(), 1);
+        assert_eq!(font.samples.len(), 100);
+
+        let preset = font.preset(0, 40).unwrap();
+        assert_eq!(preset.regions.len(), 1);
+
+        let region = &preset.regions[0];
+        assert_eq!(region.keys, 0..=127);
+        assert_eq!(region.root_key, 60);
+        assert_eq!(region.looping, Some((0, 100)));
+        assert_eq!((region.start, region.end), (0, 100));
+
+        assert!(SoundFont::parse(b"RIFF\0\0\0\0WAVE").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Standard MIDI file parsing.
+
+use bevy_asset::{Asset, AssetLoader};
+use bevy_reflect::TypePath;
+use firewheel::collector::ArcGc;
+use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+
+/// A loaded standard MIDI file.
+///
+/// MIDI files are played by a [`SoundFontNode`][super::SoundFontNode]
+/// through a [`MidiSource`][super::MidiSource].
+#[derive(Asset, TypePath, Clone)]
+pub struct MidiFile(ArcGc<MidiSong>);
+
+impl MidiFile {
+    /// Parse a MIDI file from its bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MidiLoaderError> {
+        Ok(Self(ArcGc::new(MidiSong::parse(bytes)?)))
+    }
+
+    /// Share the inner value.
+    pub fn get(&self) -> ArcGc<MidiSong> {
+        self.0.clone()
+    }
+}
+
+impl core::fmt::Debug for MidiFile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MidiFile").field(&*self.0).finish()
+    }
+}
+
+/// The default tempo of 120 beats per minute, in seconds per beat.
+pub(super) const DEFAULT_TEMPO: f64 = 0.5;
+
+/// A parsed MIDI song, with every track merged into a single timeline.
+pub struct MidiSong {
+    /// Events sorted by their position in beats.
+    pub(super) events: Vec<MidiEvent>,
+}
+
+impl MidiSong {
+    /// The song's length in beats.
+    pub fn len_beats(&self) -> f64 {
+        self.events.last().map(|e| e.beat).unwrap_or_default()
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, MidiLoaderError> {
+        let smf = Smf::parse(bytes)?;
+
+        // Timecode files are timed in seconds, so we
+        // lay them out at the default tempo instead.
+        let (ticks_per_beat, tempo_changes) = match smf.header.timing {
+            Timing::Metrical(ticks) => (ticks.as_int().max(1) as f64, true),
+            Timing::Timecode(fps, subframes) => (
+                fps.as_f32() as f64 * subframes as f64 * DEFAULT_TEMPO,
+                false,
+            ),
+        };
+
+        let mut events = Vec::new();
+        for track in &smf.tracks {
+            let mut tick = 0u64;
+            for event in track {
+                tick += event.delta.as_int() as u64;
+                let beat = tick as f64 / ticks_per_beat;
+
+                let kind = match event.kind {
+                    TrackEventKind::Meta(MetaMessage::Tempo(tempo)) if tempo_changes => {
+                        EventKind::Tempo(tempo.as_int() as f64 / 1_000_000.0)
+                    }
+                    TrackEventKind::Midi { channel, message } => {
+                        let message = match message {
+                            MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                                Message::NoteOn {
+                                    key: key.as_int(),
+                                    velocity: vel.as_int(),
+                                }
+                            }
+                            MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                                Message::NoteOff { key: key.as_int() }
+                            }
+                            MidiMessage::Controller { controller, value } => Message::Controller {
+                                controller: controller.as_int(),
+                                value: value.as_int(),
+                            },
+                            MidiMessage::ProgramChange { program } => {
+                                Message::Program(program.as_int())
+                            }
+                            MidiMessage::PitchBend { bend } => Message::PitchBend(bend.as_f32()),
+                            _ => continue,
+                        };
+
+                        EventKind::Midi {
+                            channel: channel.as_int(),
+                            message,
+                        }
+                    }
+                    _ => continue,
+                };
+
+                events.push(MidiEvent { beat, kind });
+            }
+        }
+
+        // The sort is stable, so simultaneous events keep their track order.
+        events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+
+        Ok(Self { events })
+    }
+}
+
+impl core::fmt::Debug for MidiSong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MidiSong")
+            .field("events", &self.events.len())
+            .field("len_beats", &self.len_beats())
+            .finish()
+    }
+}
+
+/// A timed event in a song.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct MidiEvent {
+    pub(super) beat: f64,
+    pub(super) kind: EventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum EventKind {
+    /// A new tempo in seconds per beat.
+    Tempo(f64),
+    Midi {
+        channel: u8,
+        message: Message,
+    },
+}
+
+/// The channel messages the synth responds to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Message {
+    NoteOn {
+        key: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        key: u8,
+    },
+    Controller {
+        controller: u8,
+        value: u8,
+    },
+    Program(u8),
+    /// Pitch bend from -1 to 1.
+    PitchBend(f32),
+}
+
+/// A loader for standard MIDI files.
+#[derive(Debug, Default)]
+pub struct MidiLoader;
+
+/// Errors produced while loading MIDI files.
+#[derive(Debug)]
+pub enum MidiLoaderError {
+    /// An I/O error, such as missing files.
+    StdIo(std::io::Error),
+    /// The file is malformed.
+    Midi(midly::Error),
+}
+
+impl From<std::io::Error> for MidiLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        Self::StdIo(value)
+    }
+}
+
+impl From<midly::Error> for MidiLoaderError {
+    fn from(value: midly::Error) -> Self {
+        Self::Midi(value)
+    }
+}
+
+impl std::error::Error for MidiLoaderError {}
+
+impl std::fmt::Display for MidiLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StdIo(stdio) => stdio.fmt(f),
+            Self::Midi(midi) => midi.fmt(f),
+        }
+    }
+}
+
+impl AssetLoader for MidiLoader {
+    type Asset = MidiFile;
+    type Settings = ();
+    type Error = MidiLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy_asset::io::Reader,
+        _: &Self::Settings,
+        _: &mut bevy_asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        MidiFile::from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mid", "midi"]
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+
+    /// A single-track file at 96 ticks per beat and 240 beats per minute
+    /// that holds middle C for one beat.
+    pub(in super::super) fn middle_c() -> Vec<u8> {
+        let track = [
+            // Tempo: 250,000 microseconds per beat.
+            0x00, 0xff, 0x51, 0x03, 0x03, 0xd0, 0x90, //
+            // Note on, then off a beat later.
+            0x00, 0x90, 0x3c, 0x64, //
+            0x60, 0x80, 0x3c, 0x00, //
+            // End of track.
+            0x00, 0xff, 0x2f, 0x00,
+        ];
+
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend(6u32.to_be_bytes());
+        bytes.extend([0, 0, 0, 1, 0, 96]);
+        bytes.extend(b"MTrk");
+        bytes.extend((track.len() as u32).to_be_bytes());
+        bytes.extend(track);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_midi() {
+        let song = MidiSong::parse(&middle_c()).unwrap();
+
+        assert_eq!(
+            song.events,
+            [
+                MidiEvent {
+                    beat: 0.0,
+                    kind: EventKind::Tempo(0.25)
+                },
+                MidiEvent {
+                    beat: 0.0,
+                    kind: EventKind::Midi {
+                        channel: 0,
+                        message: Message::NoteOn {
+                            key: 60,
+                            velocity: 100
+                        }
+                    }
+                },
+                MidiEvent {
+                    beat: 1.0,
+                    kind: EventKind::Midi {
+                        channel: 0,
+                        message: Message::NoteOff { key: 60 }
+                    }
+                },
+            ]
+        );
+        assert_eq!(song.len_beats(), 1.0);
+
+        assert!(MidiSong::parse(b"MThd").is_err());
+    }
+}
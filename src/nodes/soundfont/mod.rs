@@ -0,0 +1,348 @@
+//! MIDI playback through a SoundFont synthesizer.
+//!
+//! Unlike pre-rendered music, MIDI songs are synthesized live,
+//! so their tempo and key can follow the game's state.
+//! The notes are voiced by the presets of a SoundFont 2 file.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+//!     commands.spawn((
+//!         SoundFontNode::default(),
+//!         MidiSource(server.load("music/overworld.mid")),
+//!         SoundFontSource(server.load("music/general_user.sf2")),
+//!     ));
+//! }
+//!
+//! fn enter_chase(mut synth: Single<&mut SoundFontNode>) {
+//!     // Pick up the pace and raise the key by a whole step.
+//!     synth.tempo = 1.25;
+//!     synth.transpose = 2.0;
+//! }
+//! ```
+//!
+//! Channel ten follows the General MIDI convention of playing
+//! percussion from bank 128, and is never transposed.
+
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+mod font;
+mod midi;
+mod synth;
+
+pub use font::{SoundFont, SoundFontFile, SoundFontLoader, SoundFontLoaderError};
+pub use midi::{MidiFile, MidiLoader, MidiLoaderError, MidiSong};
+
+/// A node that plays a MIDI song with a SoundFont.
+///
+/// The song and SoundFont are provided by a [`MidiSource`]
+/// and [`SoundFontSource`] on the same entity.
+/// See the [module docs][self] for an example.
+#[derive(Diff, Patch, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SoundFontNode {
+    /// The song's output volume.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub volume: Volume,
+    /// Whether playback is paused.
+    pub paused: bool,
+    /// Whether the song starts over once it ends.
+    ///
+    /// Otherwise, the node falls silent after the last note.
+    /// By default, this is `true`.
+    pub looping: bool,
+    /// A multiplier on the song's tempo.
+    ///
+    /// By default, this is `1.0`.
+    pub tempo: f32,
+    /// Shift every pitched note by this many semitones.
+    ///
+    /// Sounding notes follow changes immediately.
+    pub transpose: f32,
+    /// The song being played.
+    ///
+    /// This is set automatically from the entity's [`MidiSource`]
+    /// once its file has loaded.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub song: Option<ArcGc<MidiSong>>,
+    /// The SoundFont voicing the song.
+    ///
+    /// This is set automatically from the entity's [`SoundFontSource`]
+    /// once its file has loaded.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub soundfont: Option<ArcGc<SoundFont>>,
+}
+
+impl Default for SoundFontNode {
+    fn default() -> Self {
+        Self {
+            volume: Volume::UNITY_GAIN,
+            paused: false,
+            looping: true,
+            tempo: 1.0,
+            transpose: 0.0,
+            song: None,
+            soundfont: None,
+        }
+    }
+}
+
+impl core::fmt::Debug for SoundFontNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SoundFontNode")
+            .field("volume", &self.volume)
+            .field("paused", &self.paused)
+            .field("looping", &self.looping)
+            .field("tempo", &self.tempo)
+            .field("transpose", &self.transpose)
+            .field("song", &self.song.as_deref())
+            .field("soundfont", &self.soundfont.as_deref())
+            .finish()
+    }
+}
+
+/// The song played by a [`SoundFontNode`].
+#[derive(Debug, Clone, Component)]
+pub struct MidiSource(pub Handle<MidiFile>);
+
+/// The SoundFont used by a [`SoundFontNode`].
+#[derive(Debug, Clone, Component)]
+pub struct SoundFontSource(pub Handle<SoundFontFile>);
+
+/// The file an entity's song was taken from.
+#[derive(Debug, Component)]
+pub(crate) struct LoadedMidi(AssetId<MidiFile>);
+
+/// The file an entity's SoundFont was taken from.
+#[derive(Debug, Component)]
+pub(crate) struct LoadedSoundFont(AssetId<SoundFontFile>);
+
+pub(crate) fn load_songs(
+    mut nodes: Query<(Entity, &MidiSource, &mut SoundFontNode, Option<&LoadedMidi>)>,
+    assets: Res<Assets<MidiFile>>,
+    mut commands: Commands,
+) {
+    for (entity, source, mut node, loaded) in nodes.iter_mut() {
+        let id = source.0.id();
+        if loaded.is_some_and(|l| l.0 == id) {
+            continue;
+        }
+
+        let Some(file) = assets.get(id) else {
+            continue;
+        };
+
+        node.song = Some(file.get());
+        commands.entity(entity).insert(LoadedMidi(id));
+    }
+}
+
+pub(crate) fn load_soundfonts(
+    mut nodes: Query<(
+        Entity,
+        &SoundFontSource,
+        &mut SoundFontNode,
+        Option<&LoadedSoundFont>,
+    )>,
+    assets: Res<Assets<SoundFontFile>>,
+    mut commands: Commands,
+) {
+    for (entity, source, mut node, loaded) in nodes.iter_mut() {
+        let id = source.0.id();
+        if loaded.is_some_and(|l| l.0 == id) {
+            continue;
+        }
+
+        let Some(file) = assets.get(id) else {
+            continue;
+        };
+
+        node.soundfont = Some(file.get());
+        commands.entity(entity).insert(LoadedSoundFont(id));
+    }
+}
+
+pub(crate) fn remove_song(
+    trigger: On<Remove, MidiSource>,
+    mut nodes: Query<&mut SoundFontNode>,
+    mut commands: Commands,
+) {
+    if let Ok(mut node) = nodes.get_mut(trigger.event_target()) {
+        node.song = None;
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<LoadedMidi>();
+}
+
+pub(crate) fn remove_soundfont(
+    trigger: On<Remove, SoundFontSource>,
+    mut nodes: Query<&mut SoundFontNode>,
+    mut commands: Commands,
+) {
+    if let Ok(mut node) = nodes.get_mut(trigger.event_target()) {
+        node.soundfont = None;
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<LoadedSoundFont>();
+}
+
+/// The shared playback position of a [`SoundFontNode`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState};
+/// fn sync_to_music(synth: Single<&AudioState<SoundFontState>>) {
+///     let bar = synth.0.beat() as u32 / 4;
+///     info!("playing bar {bar}");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoundFontState(ArcGc<Position>);
+
+#[derive(Debug, Default)]
+struct Position {
+    /// The beat as `f64` bits.
+    beat: AtomicU64,
+    finished: AtomicBool,
+}
+
+impl SoundFontState {
+    /// The playback position in beats.
+    pub fn beat(&self) -> f64 {
+        f64::from_bits(self.0.beat.load(Ordering::Relaxed))
+    }
+
+    /// Returns `true` if a song without looping has ended.
+    pub fn finished(&self) -> bool {
+        self.0.finished.load(Ordering::Relaxed)
+    }
+}
+
+impl AudioNode for SoundFontNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("soundfont")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::ZERO,
+                num_outputs: ChannelCount::STEREO,
+            })
+            .custom_state(SoundFontState(ArcGc::new(Position::default())))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let sample_rate = cx.stream_info.sample_rate.get() as f64;
+
+        SoundFontProcessor {
+            player: new_player(self, sample_rate),
+            params: self.clone(),
+            sample_rate,
+            state: cx.custom_state().cloned().unwrap(),
+        }
+    }
+}
+
+fn new_player(params: &SoundFontNode, sample_rate: f64) -> Option<synth::Player> {
+    let (Some(song), Some(font)) = (&params.song, &params.soundfont) else {
+        return None;
+    };
+
+    Some(synth::Player::new(font.clone(), song.clone(), sample_rate))
+}
+
+struct SoundFontProcessor {
+    params: SoundFontNode,
+    player: Option<synth::Player>,
+    sample_rate: f64,
+    state: SoundFontState,
+}
+
+impl AudioNodeProcessor for SoundFontProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { outputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<SoundFontNode>() {
+            let sources_changed = matches!(
+                patch,
+                SoundFontNodePatch::Song(_) | SoundFontNodePatch::Soundfont(_)
+            );
+            self.params.apply(patch);
+
+            if sources_changed {
+                let unchanged = match (&self.params.song, &self.params.soundfont, &self.player) {
+                    (Some(song), Some(font), Some(player)) => {
+                        core::ptr::eq(&**song, &**player.song())
+                            && core::ptr::eq(&**font, &**player.font())
+                    }
+                    _ => false,
+                };
+
+                if !unchanged {
+                    self.player = new_player(&self.params, self.sample_rate);
+                }
+            }
+        }
+
+        let Some(player) = self.player.as_mut().filter(|_| !self.params.paused) else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        if player.finished() && !self.params.looping {
+            self.state.0.finished.store(true, Ordering::Relaxed);
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let frames = proc_info.frames;
+        let [left, right] = outputs else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        player.render(
+            [&mut left[..frames], &mut right[..frames]],
+            self.params.tempo as f64,
+            self.params.transpose as f64,
+            self.params.looping,
+        );
+
+        let gain = self.params.volume.amp();
+        for sample in left[..frames].iter_mut().chain(right[..frames].iter_mut()) {
+            *sample *= gain;
+        }
+
+        self.state
+            .0
+            .beat
+            .store(player.beat().to_bits(), Ordering::Relaxed);
+        self.state.0.finished.store(false, Ordering::Relaxed);
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
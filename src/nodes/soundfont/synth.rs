@@ -0,0 +1,469 @@
+//! Sample-based synthesis of MIDI songs.
+
+use super::{
+    font::{Region, SoundFont},
+    midi::{DEFAULT_TEMPO, EventKind, Message, MidiSong},
+};
+use firewheel::collector::ArcGc;
+
+/// The most notes that can sound at once.
+const MAX_VOICES: usize = 64;
+
+/// General MIDI reserves the tenth channel for percussion.
+const DRUM_CHANNEL: u8 = 9;
+
+/// The bank holding percussion presets.
+const DRUM_BANK: u16 = 128;
+
+/// The pitch bend range in semitones.
+const BEND_RANGE: f64 = 2.0;
+
+/// The number of frames rendered between sequencer steps.
+const BLOCK: usize = 64;
+
+/// A MIDI channel's controller state.
+#[derive(Clone, Copy)]
+struct Channel {
+    program: u8,
+    bank: u16,
+    /// Linear gain from the channel volume controller.
+    volume: f32,
+    /// Linear gain from the expression controller.
+    expression: f32,
+    pan: f32,
+    /// Pitch bend in semitones.
+    bend: f64,
+    sustain: bool,
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self {
+            program: 0,
+            bank: 0,
+            volume: controller_gain(100),
+            expression: 1.0,
+            pan: 0.0,
+            bend: 0.0,
+            sustain: false,
+        }
+    }
+}
+
+/// Controllers and velocities follow a roughly squared volume curve.
+fn controller_gain(value: u8) -> f32 {
+    let value = value as f32 / 127.0;
+    value * value
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    Delay,
+    Attack,
+    Hold,
+    Decay,
+    Sustain,
+    Release,
+    Finished,
+}
+
+/// A single sounding note.
+struct Voice {
+    channel: u8,
+    key: u8,
+    region: Region,
+    /// The note's pitch relative to its sample's root, in semitones.
+    pitch: f64,
+    /// Whether the node's transposition applies.
+    transposed: bool,
+    position: f64,
+    gain: f32,
+    stage: Stage,
+    /// Seconds spent in the current stage.
+    elapsed: f32,
+    level: f32,
+    /// The envelope level when the note was released.
+    release_level: f32,
+    /// Whether a note off is waiting on the sustain pedal.
+    held: bool,
+    age: u64,
+}
+
+impl Voice {
+    fn release(&mut self) {
+        if self.stage != Stage::Release && self.stage != Stage::Finished {
+            self.stage = Stage::Release;
+            self.elapsed = 0.0;
+            self.release_level = self.level;
+        }
+    }
+
+    /// Step the volume envelope forward, returning the new level.
+    fn envelope(&mut self, dt: f32) -> f32 {
+        let envelope = self.region.envelope;
+        self.elapsed += dt;
+
+        let (level, duration, next) = match self.stage {
+            Stage::Delay => (0.0, envelope.delay, Stage::Attack),
+            Stage::Attack => (
+                (self.elapsed / envelope.attack).min(1.0),
+                envelope.attack,
+                Stage::Hold,
+            ),
+            Stage::Hold => (1.0, envelope.hold, Stage::Decay),
+            Stage::Decay => (
+                1.0 - (1.0 - envelope.sustain) * (self.elapsed / envelope.decay).min(1.0),
+                envelope.decay,
+                Stage::Sustain,
+            ),
+            Stage::Sustain => (envelope.sustain, f32::INFINITY, Stage::Sustain),
+            Stage::Release => (
+                self.release_level * (1.0 - self.elapsed / envelope.release).max(0.0),
+                envelope.release,
+                Stage::Finished,
+            ),
+            Stage::Finished => (0.0, f32::INFINITY, Stage::Finished),
+        };
+
+        if self.elapsed >= duration {
+            self.stage = next;
+            self.elapsed = 0.0;
+        }
+
+        // A fully decayed note with no sustain will never be heard again.
+        if self.stage == Stage::Sustain && envelope.sustain <= 0.0 {
+            self.stage = Stage::Finished;
+        }
+
+        self.level = level;
+        level
+    }
+}
+
+/// A voice allocator driven by MIDI messages.
+pub(super) struct Synth {
+    font: ArcGc<SoundFont>,
+    channels: [Channel; 16],
+    voices: Vec<Voice>,
+    sample_rate: f64,
+    age: u64,
+}
+
+impl Synth {
+    pub(super) fn new(font: ArcGc<SoundFont>, sample_rate: f64) -> Self {
+        Self {
+            font,
+            channels: [Channel::default(); 16],
+            voices: Vec::with_capacity(MAX_VOICES),
+            sample_rate,
+            age: 0,
+        }
+    }
+
+    /// Reset every channel's controllers and program.
+    fn reset_channels(&mut self) {
+        self.channels = [Channel::default(); 16];
+    }
+
+    fn message(&mut self, channel: u8, message: Message, transpose: f64) {
+        let index = channel as usize & 15;
+
+        match message {
+            Message::NoteOn { key, velocity } => self.note_on(channel, key, velocity, transpose),
+            Message::NoteOff { key } => {
+                let sustain = self.channels[index].sustain;
+                for voice in self
+                    .voices
+                    .iter_mut()
+                    .filter(|v| v.channel == channel && v.key == key)
+                {
+                    if sustain {
+                        voice.held = true;
+                    } else {
+                        voice.release();
+                    }
+                }
+            }
+            Message::Program(program) => self.channels[index].program = program,
+            Message::PitchBend(bend) => self.channels[index].bend = bend as f64 * BEND_RANGE,
+            Message::Controller { controller, value } => {
+                let state = &mut self.channels[index];
+                match controller {
+                    0 => state.bank = value as u16,
+                    7 => state.volume = controller_gain(value),
+                    10 => state.pan = (value as f32 - 64.0) / 64.0,
+                    11 => state.expression = controller_gain(value),
+                    64 => {
+                        state.sustain = value >= 64;
+                        if !state.sustain {
+                            for voice in self
+                                .voices
+                                .iter_mut()
+                                .filter(|v| v.channel == channel && v.held)
+                            {
+                                voice.release();
+                            }
+                        }
+                    }
+                    120 => self.voices.retain(|v| v.channel != channel),
+                    121 => {
+                        *state = Channel {
+                            program: state.program,
+                            bank: state.bank,
+                            ..Default::default()
+                        }
+                    }
+                    123 => {
+                        for voice in self.voices.iter_mut().filter(|v| v.channel == channel) {
+                            voice.release();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn note_on(&mut self, channel: u8, key: u8, velocity: u8, transpose: f64) {
+        let state = self.channels[channel as usize & 15];
+        let drums = channel == DRUM_CHANNEL;
+        let bank = if drums { DRUM_BANK } else { state.bank };
+
+        let Some(preset) = self.font.preset(bank, state.program) else {
+            return;
+        };
+
+        // Percussion maps keys to instruments, so it's never transposed.
+        let played = if drums {
+            key
+        } else {
+            (key as f64 + transpose).round().clamp(0.0, 127.0) as u8
+        };
+
+        let regions = preset
+            .regions
+            .iter()
+            .filter(|r| r.keys.contains(&played) && r.velocities.contains(&velocity));
+
+        for region in regions {
+            if self.voices.len() >= MAX_VOICES {
+                // Steal the oldest voice, preferring released ones.
+                if let Some(index) = self
+                    .voices
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, v)| (v.stage != Stage::Release, v.age))
+                    .map(|(i, _)| i)
+                {
+                    self.voices.swap_remove(index);
+                }
+            }
+
+            self.age += 1;
+            self.voices.push(Voice {
+                channel,
+                key,
+                region: region.clone(),
+                pitch: key as f64 - region.root_key as f64 + region.tune,
+                transposed: !drums,
+                position: region.start as f64,
+                gain: region.gain * controller_gain(velocity),
+                stage: Stage::Delay,
+                elapsed: 0.0,
+                level: 0.0,
+                release_level: 0.0,
+                held: false,
+                age: self.age,
+            });
+        }
+    }
+
+    /// Mix every voice into the buffers.
+    fn render(&mut self, [left, right]: [&mut [f32]; 2], transpose: f64) {
+        let samples = &self.font.samples;
+        let dt = (1.0 / self.sample_rate) as f32;
+
+        for voice in &mut self.voices {
+            let channel = &self.channels[voice.channel as usize & 15];
+
+            let mut pitch = voice.pitch + channel.bend;
+            if voice.transposed {
+                pitch += transpose;
+            }
+            let step = 2f64.powf(pitch / 12.0) * voice.region.sample_rate / self.sample_rate;
+
+            let pan = (voice.region.pan + channel.pan).clamp(-1.0, 1.0);
+            let angle = (pan + 1.0) * core::f32::consts::FRAC_PI_4;
+            let gain = voice.gain * channel.volume * channel.expression;
+            let (left_gain, right_gain) = (angle.cos() * gain, angle.sin() * gain);
+
+            for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+                if let Some((loop_start, loop_end)) = voice.region.looping {
+                    if voice.position >= loop_end as f64 {
+                        voice.position -= (loop_end - loop_start) as f64;
+                    }
+                }
+
+                let index = voice.position as usize;
+                if index >= voice.region.end {
+                    voice.stage = Stage::Finished;
+                    break;
+                }
+
+                let fraction = (voice.position - index as f64) as f32;
+                let a = samples[index];
+                let next = match voice.region.looping {
+                    Some((loop_start, loop_end)) if index + 1 >= loop_end => loop_start,
+                    _ => index + 1,
+                };
+                let b = if next < voice.region.end {
+                    samples[next]
+                } else {
+                    0.0
+                };
+
+                let sample = (a + (b - a) * fraction) * voice.envelope(dt);
+                *l += sample * left_gain;
+                *r += sample * right_gain;
+
+                voice.position += step;
+                if voice.stage == Stage::Finished {
+                    break;
+                }
+            }
+        }
+
+        self.voices.retain(|v| v.stage != Stage::Finished);
+    }
+}
+
+/// Plays a MIDI song through a [`Synth`].
+pub(super) struct Player {
+    synth: Synth,
+    song: ArcGc<MidiSong>,
+    /// The next event to dispatch.
+    index: usize,
+    /// The playback position in beats.
+    beat: f64,
+    /// The current tempo in seconds per beat.
+    tempo: f64,
+}
+
+impl Player {
+    pub(super) fn new(font: ArcGc<SoundFont>, song: ArcGc<MidiSong>, sample_rate: f64) -> Self {
+        Self {
+            synth: Synth::new(font, sample_rate),
+            song,
+            index: 0,
+            beat: 0.0,
+            tempo: DEFAULT_TEMPO,
+        }
+    }
+
+    pub(super) fn font(&self) -> &ArcGc<SoundFont> {
+        &self.synth.font
+    }
+
+    pub(super) fn song(&self) -> &ArcGc<MidiSong> {
+        &self.song
+    }
+
+    /// The playback position in beats.
+    pub(super) fn beat(&self) -> f64 {
+        self.beat
+    }
+
+    /// Returns `true` once the song has ended and every note has faded out.
+    pub(super) fn finished(&self) -> bool {
+        self.index >= self.song.events.len() && self.synth.voices.is_empty()
+    }
+
+    fn restart(&mut self) {
+        self.index = 0;
+        self.beat = 0.0;
+        self.tempo = DEFAULT_TEMPO;
+        self.synth.reset_channels();
+    }
+
+    /// Render the song into the buffers.
+    ///
+    /// `speed` scales the song's tempo, while `transpose`
+    /// shifts every pitched note by a number of semitones.
+    pub(super) fn render(
+        &mut self,
+        [left, right]: [&mut [f32]; 2],
+        speed: f64,
+        transpose: f64,
+        looping: bool,
+    ) {
+        left.fill(0.0);
+        right.fill(0.0);
+
+        let sample_rate = self.synth.sample_rate;
+        for (left, right) in left.chunks_mut(BLOCK).zip(right.chunks_mut(BLOCK)) {
+            while let Some(event) = self.song.events.get(self.index) {
+                if event.beat > self.beat {
+                    break;
+                }
+
+                match event.kind {
+                    EventKind::Tempo(tempo) => self.tempo = tempo,
+                    EventKind::Midi { channel, message } => {
+                        self.synth.message(channel, message, transpose)
+                    }
+                }
+                self.index += 1;
+            }
+
+            // Empty songs would otherwise restart on every block.
+            if self.index >= self.song.events.len() && looping && self.song.len_beats() > 0.0 {
+                self.restart();
+            }
+
+            let frames = left.len();
+            self.synth.render([left, right], transpose);
+
+            self.beat += frames as f64 / sample_rate / self.tempo * speed.max(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nodes::soundfont::{font::SoundFontFile, midi::MidiFile};
+
+    #[test]
+    fn test_playback() {
+        let font = SoundFontFile::from_bytes(&super::super::font::test::square_font())
+            .unwrap()
+            .get();
+        let song = MidiFile::from_bytes(&super::super::midi::test::middle_c())
+            .unwrap()
+            .get();
+
+        let mut player = Player::new(font, song, 44100.0);
+        let mut left = vec![0.0; 4410];
+        let mut right = vec![0.0; 4410];
+
+        // The note sounds in both channels from the start.
+        player.render([&mut left, &mut right], 1.0, 0.0, false);
+        assert!(left.iter().any(|s| s.abs() > 0.1));
+        assert!(right.iter().any(|s| s.abs() > 0.1));
+        assert!(!player.finished());
+
+        // At 240 BPM, the note is released after a quarter second.
+        for _ in 0..3 {
+            player.render([&mut left, &mut right], 1.0, 0.0, false);
+        }
+        assert!(player.beat() > 1.0);
+        assert!(player.finished());
+        assert!(left.iter().all(|s| *s == 0.0));
+
+        // Doubling the speed halves the time to the note off.
+        player.restart();
+        player.render([&mut left, &mut right], 2.0, 12.0, false);
+        player.render([&mut left, &mut right], 2.0, 12.0, false);
+        assert!(player.finished());
+    }
+}
@@ -0,0 +1,427 @@
+//! Octave-band spectrum analysis.
+//!
+//! Adaptive mixing often depends on what's happening in a particular
+//! part of the spectrum. For example, you might carve space for dialogue
+//! when the music is busy between 1 and 4 kHz. Inserting a [`SpectrumSnapshot`]
+//! on a bus or sampler pool periodically measures its energy in ten
+//! octave bands, from 31.25 Hz to 16 kHz.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! #[derive(NodeLabel, PartialEq, Eq, Debug, Hash, Clone)]
+//! struct MusicBus;
+//!
+//! fn spawn_music_bus(mut commands: Commands) {
+//!     commands.spawn((MusicBus, VolumeNode::default(), SpectrumSnapshot::default()));
+//! }
+//!
+//! fn check_presence(music: Single<&SpectrumSnapshot, With<MusicBus>>) {
+//!     if music.range(1000.0..=4000.0) > -24.0 {
+//!         info!("the music is busy in the presence range");
+//!     }
+//! }
+//! ```
+//!
+//! Removing the [`SpectrumSnapshot`] removes the analyzer.
+
+use super::multiband::Biquad;
+use crate::{
+    edge::Connect,
+    node::{AudioState, FirewheelNode},
+    prelude::AudioContext,
+};
+use bevy_ecs::prelude::*;
+use core::{
+    ops::RangeInclusive,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount, NonZeroChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The number of octave bands.
+pub const BAND_COUNT: usize = 10;
+
+/// The center frequency of each octave band, in hertz.
+pub const BAND_CENTERS: [f32; BAND_COUNT] = [
+    31.25, 62.5, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// The quality factor of a one-octave band-pass filter.
+pub(crate) const OCTAVE_Q: f32 = core::f32::consts::SQRT_2;
+
+/// The level reported for silent bands, in decibels.
+const SILENCE: f32 = -120.0;
+
+/// A node that measures its input's energy in octave bands.
+///
+/// Generally, you'll want to use a [`SpectrumSnapshot`] rather
+/// than spawning this node directly.
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SpectrumNode {
+    /// The duration over which each measurement is averaged, in seconds.
+    ///
+    /// By default, this is 0.1 seconds.
+    pub window: f32,
+}
+
+impl Default for SpectrumNode {
+    fn default() -> Self {
+        Self { window: 0.1 }
+    }
+}
+
+/// [`SpectrumNode`]'s configuration.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SpectrumConfig {
+    /// The number of input channels.
+    ///
+    /// All channels are summed before analysis.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+/// The shared atomics used by [`SpectrumNode`] to
+/// communicate its latest measurement.
+#[derive(Debug, Clone)]
+pub struct SpectrumState(ArcGc<[AtomicU32; BAND_COUNT]>);
+
+impl SpectrumState {
+    /// The RMS level of each band over the last window, in decibels.
+    pub fn bands(&self) -> [f32; BAND_COUNT] {
+        core::array::from_fn(|i| f32::from_bits(self.0[i].load(Ordering::Relaxed)))
+    }
+}
+
+impl AudioNode for SpectrumNode {
+    type Configuration = SpectrumConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("spectrum analyzer")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: ChannelCount::ZERO,
+            })
+            .custom_state(SpectrumState(ArcGc::new(core::array::from_fn(|_| {
+                AtomicU32::new(SILENCE.to_bits())
+            }))))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let mut processor = SpectrumProcessor {
+            params: self.clone(),
+            filters: Default::default(),
+            energy: [0.0; BAND_COUNT],
+            frames: 0,
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+            state: cx.custom_state().cloned().unwrap(),
+        };
+        processor.update_filters();
+
+        processor
+    }
+}
+
+struct SpectrumProcessor {
+    params: SpectrumNode,
+    filters: [Biquad; BAND_COUNT],
+    /// The accumulated energy of each band in the current window.
+    energy: [f32; BAND_COUNT],
+    /// The number of frames accumulated in the current window.
+    frames: usize,
+    sample_rate: f32,
+    state: SpectrumState,
+}
+
+impl SpectrumProcessor {
+    fn update_filters(&mut self) {
+        for (filter, center) in self.filters.iter_mut().zip(BAND_CENTERS) {
+            let center = center.min(self.sample_rate * 0.45);
+            filter.set_band_pass(self.sample_rate, center, OCTAVE_Q);
+        }
+    }
+
+    /// Feed a single mono sample through the band-pass bank.
+    #[inline]
+    fn tick(&mut self, sample: f32) {
+        for (filter, energy) in self.filters.iter_mut().zip(&mut self.energy) {
+            let band = filter.process(sample);
+            *energy += band * band;
+        }
+        self.frames += 1;
+    }
+
+    /// Store the mean level of each band and begin a new window.
+    fn publish(&mut self) {
+        let frames = self.frames.max(1) as f32;
+        for (energy, atomic) in self.energy.iter_mut().zip(self.state.0.iter()) {
+            let level = (10.0 * (*energy / frames).log10()).max(SILENCE);
+            atomic.store(level.to_bits(), Ordering::Relaxed);
+            *energy = 0.0;
+        }
+        self.frames = 0;
+    }
+}
+
+impl AudioNodeProcessor for SpectrumProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<SpectrumNode>() {
+            self.params.apply(patch);
+        }
+
+        let window = (self.params.window.max(0.0) * self.sample_rate) as usize;
+        let silent = proc_info.in_silence_mask.all_channels_silent(inputs.len());
+        let scale = 1.0 / inputs.len().max(1) as f32;
+
+        for frame in 0..proc_info.frames {
+            let sample = if silent {
+                0.0
+            } else {
+                inputs.iter().map(|input| input[frame]).sum::<f32>() * scale
+            };
+
+            self.tick(sample);
+            if self.frames >= window.max(1) {
+                self.publish();
+            }
+        }
+
+        ProcessStatus::ClearAllOutputs
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+        self.update_filters();
+    }
+}
+
+/// A periodic octave-band snapshot of a bus or sampler pool.
+///
+/// Inserting this component attaches a [`SpectrumNode`] to the
+/// entity's output. The snapshot is refreshed once per `window`,
+/// and only marked as changed when the levels actually change.
+#[derive(Debug, Clone, PartialEq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SpectrumSnapshot {
+    /// The duration over which each snapshot is averaged, in seconds.
+    ///
+    /// By default, this is 0.1 seconds.
+    pub window: f32,
+    bands: [f32; BAND_COUNT],
+}
+
+impl Default for SpectrumSnapshot {
+    fn default() -> Self {
+        Self {
+            window: 0.1,
+            bands: [SILENCE; BAND_COUNT],
+        }
+    }
+}
+
+impl SpectrumSnapshot {
+    /// The RMS level of each band in [`BAND_CENTERS`], in decibels.
+    pub fn bands(&self) -> &[f32; BAND_COUNT] {
+        &self.bands
+    }
+
+    /// The level of the band nearest to `frequency`, in decibels.
+    pub fn level(&self, frequency: f32) -> f32 {
+        self.bands[nearest_band(frequency)]
+    }
+
+    /// The combined level of the bands centered within `frequencies`, in decibels.
+    ///
+    /// If no band is centered within the range, the band
+    /// nearest to its geometric center is used.
+    pub fn range(&self, frequencies: RangeInclusive<f32>) -> f32 {
+        let (power, count) = BAND_CENTERS
+            .iter()
+            .zip(&self.bands)
+            .filter(|(center, _)| frequencies.contains(center))
+            .fold((0.0, 0), |(power, count), (_, level)| {
+                (power + 10f32.powf(level / 10.0), count + 1)
+            });
+
+        if count == 0 {
+            let center = (frequencies.start() * frequencies.end()).sqrt();
+            return self.level(center);
+        }
+
+        (10.0 * (power / count as f32).log10()).max(SILENCE)
+    }
+}
+
+/// Returns the index of the band nearest to `frequency` on a log scale.
+fn nearest_band(frequency: f32) -> usize {
+    let octaves = (frequency.max(1.0) / BAND_CENTERS[0]).log2().round();
+    (octaves.max(0.0) as usize).min(BAND_COUNT - 1)
+}
+
+/// Links a snapshot to its analyzer.
+#[derive(Debug, Component)]
+pub(crate) struct AnalyzedBy(Entity);
+
+pub(crate) fn attach_analyzers(
+    targets: Query<(Entity, &FirewheelNode, &SpectrumSnapshot), Without<AnalyzedBy>>,
+    mut context: ResMut<AudioContext>,
+    mut commands: Commands,
+) {
+    if targets.is_empty() {
+        return;
+    }
+
+    let ids: Vec<_> = targets.iter().map(|(_, node, _)| node.0).collect();
+    let channels = context.with(|context| {
+        ids.into_iter()
+            .map(|id| {
+                context
+                    .node_info(id)
+                    .and_then(|entry| {
+                        NonZeroChannelCount::new(entry.info.channel_config.num_outputs.get())
+                    })
+                    .unwrap_or(NonZeroChannelCount::STEREO)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for ((target, _, snapshot), channels) in targets.iter().zip(channels) {
+        let analyzer = commands
+            .spawn((
+                SpectrumNode {
+                    window: snapshot.window,
+                },
+                SpectrumConfig { channels },
+            ))
+            .id();
+
+        let mut target = commands.entity(target);
+        target.insert(AnalyzedBy(analyzer));
+        target.reborrow().connect(analyzer);
+    }
+}
+
+pub(crate) fn update_snapshots(
+    mut targets: Query<(&mut SpectrumSnapshot, &AnalyzedBy)>,
+    mut analyzers: Query<(&mut SpectrumNode, &AudioState<SpectrumState>)>,
+) {
+    for (mut snapshot, by) in &mut targets {
+        let Ok((mut node, state)) = analyzers.get_mut(by.0) else {
+            continue;
+        };
+
+        if node.window != snapshot.window {
+            node.window = snapshot.window;
+        }
+
+        let bands = state.0.bands();
+        if snapshot.bands != bands {
+            snapshot.bands = bands;
+        }
+    }
+}
+
+pub(crate) fn remove_analyzer(
+    trigger: On<Remove, SpectrumSnapshot>,
+    targets: Query<&AnalyzedBy>,
+    mut commands: Commands,
+) {
+    let target = trigger.event_target();
+    let Ok(by) = targets.get(target) else {
+        return;
+    };
+
+    commands.entity(by.0).despawn();
+    commands.entity(target).try_remove::<AnalyzedBy>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn processor(sample_rate: f32) -> SpectrumProcessor {
+        let mut processor = SpectrumProcessor {
+            params: SpectrumNode::default(),
+            filters: Default::default(),
+            energy: [0.0; BAND_COUNT],
+            frames: 0,
+            sample_rate,
+            state: SpectrumState(ArcGc::new(core::array::from_fn(|_| {
+                AtomicU32::new(SILENCE.to_bits())
+            }))),
+        };
+        processor.update_filters();
+
+        processor
+    }
+
+    #[test]
+    fn test_octave_bands() {
+        let sample_rate = 48000.0;
+        let mut processor = processor(sample_rate);
+
+        for i in 0..4800 {
+            let phase = i as f32 * 1000.0 / sample_rate;
+            processor.tick((phase * core::f32::consts::TAU).sin());
+        }
+        processor.publish();
+
+        let bands = processor.state.bands();
+        let loudest = (0..BAND_COUNT)
+            .max_by(|a, b| bands[*a].total_cmp(&bands[*b]))
+            .unwrap();
+        assert_eq!(BAND_CENTERS[loudest], 1000.0);
+
+        // A full-scale sine has an RMS level of about -3 dB.
+        assert!((bands[loudest] + 3.0).abs() < 1.0, "{bands:?}");
+        for (i, band) in bands.iter().enumerate().filter(|(i, _)| *i != loudest) {
+            assert!(*band < bands[loudest] - 6.0, "band {i} leaked: {bands:?}");
+        }
+    }
+
+    #[test]
+    fn test_snapshot_range() {
+        let mut snapshot = SpectrumSnapshot::default();
+        snapshot.bands[5] = 0.0;
+        snapshot.bands[6] = 0.0;
+
+        assert_eq!(snapshot.level(1100.0), 0.0);
+        assert_eq!(snapshot.level(20.0), SILENCE);
+        assert_eq!(snapshot.level(20000.0), SILENCE);
+
+        // 1 kHz, 2 kHz, and 4 kHz average to two thirds of full power.
+        let presence = snapshot.range(1000.0..=4000.0);
+        assert!((presence - 10.0 * (2.0f32 / 3.0).log10()).abs() < 1e-4);
+
+        // no band is centered here, so the nearest is used
+        assert_eq!(snapshot.range(1300.0..=1500.0), 0.0);
+    }
+}
@@ -0,0 +1,211 @@
+//! Channel splitter and merger nodes.
+//!
+//! Both nodes pass their input through unchanged. They exist to give
+//! multichannel routing a clear, named point where channels are pulled
+//! apart or brought together, along with helpers that build the
+//! port mappings for individual channels.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! fn split_music(mut commands: Commands) {
+//!     let left = commands.spawn(LowPassNode::default()).id();
+//!     let right = commands.spawn(HighPassNode::default()).id();
+//!
+//!     // Process each channel of a stereo stream independently...
+//!     commands
+//!         .spawn(SplitterNode)
+//!         .connect_with(left, &SplitterNode::output(0))
+//!         .connect_with(right, &SplitterNode::output(1));
+//!
+//!     // ...and recombine them.
+//!     let merger = commands.spawn(MergerNode).id();
+//!     commands
+//!         .entity(left)
+//!         .connect_with(merger, &MergerNode::input(0));
+//!     commands
+//!         .entity(right)
+//!         .connect_with(merger, &MergerNode::input(1));
+//! }
+//! ```
+//!
+//! These helpers map a single channel to or from the first port,
+//! so the other side of the connection is typically a mono node.
+
+use crate::edge::PortMap;
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, NonZeroChannelCount},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, ProcBuffers,
+        ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A node that exposes each of its input channels as a separate output.
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SplitterNode;
+
+impl SplitterNode {
+    /// Connect the splitter's `channel` to the destination's first input.
+    ///
+    /// This is `[(channel, 0)]`.
+    pub fn output(channel: u32) -> PortMap {
+        PortMap::new().with(channel, 0)
+    }
+}
+
+/// A node that combines several sources into a single multichannel output.
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MergerNode;
+
+impl MergerNode {
+    /// Connect the source's first output to the merger's `channel`.
+    ///
+    /// This is `[(0, channel)]`.
+    pub fn input(channel: u32) -> PortMap {
+        PortMap::new().with(0, channel)
+    }
+}
+
+/// The configuration shared by [`SplitterNode`] and [`MergerNode`].
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SplitConfig {
+    /// The number of channels to split or merge.
+    ///
+    /// By default, this is stereo.
+    pub channels: NonZeroChannelCount,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            channels: NonZeroChannelCount::STEREO,
+        }
+    }
+}
+
+impl AudioNode for SplitterNode {
+    type Configuration = SplitConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("splitter")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        PassThroughProcessor
+    }
+}
+
+impl AudioNode for MergerNode {
+    type Configuration = SplitConfig;
+
+    fn info(&self, config: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("merger")
+            .channel_config(ChannelConfig {
+                num_inputs: config.channels.get(),
+                num_outputs: config.channels.get(),
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        PassThroughProcessor
+    }
+}
+
+struct PassThroughProcessor;
+
+impl AudioNodeProcessor for PassThroughProcessor {
+    fn process(
+        &mut self,
+        _: &ProcInfo,
+        _: ProcBuffers,
+        _: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        ProcessStatus::Bypass
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        edge::AudioGraphOutput,
+        prelude::*,
+        test::{prepare_app, run},
+    };
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_channel_routing() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            let left = commands.spawn(VolumeNode::default()).id();
+            let right = commands.spawn(VolumeNode::default()).id();
+
+            commands
+                .spawn(SplitterNode)
+                .connect_with(left, &SplitterNode::output(0))
+                .connect_with(right, &SplitterNode::output(1));
+
+            let merger = commands.spawn(MergerNode).connect(AudioGraphOutput).head();
+            commands
+                .entity(left)
+                .connect_with(merger, &MergerNode::input(0));
+            commands
+                .entity(right)
+                .connect_with(merger, &MergerNode::input(1));
+        });
+
+        run(
+            &mut app,
+            |splitter: Single<&FirewheelNode, With<SplitterNode>>,
+             merger: Single<&FirewheelNode, With<MergerNode>>,
+             mut context: ResMut<AudioContext>| {
+                let (splitter, merger) = (splitter.0, merger.0);
+
+                context.with(|context| {
+                    let mut outgoing: Vec<_> = context
+                        .edges()
+                        .into_iter()
+                        .filter(|e| e.src_node == splitter)
+                        .map(|e| (e.src_port, e.dst_port))
+                        .collect();
+                    outgoing.sort();
+                    assert_eq!(outgoing, [(0, 0), (1, 0)]);
+
+                    let mut incoming: Vec<_> = context
+                        .edges()
+                        .into_iter()
+                        .filter(|e| e.dst_node == merger)
+                        .map(|e| (e.src_port, e.dst_port))
+                        .collect();
+                    incoming.sort();
+                    assert_eq!(incoming, [(0, 0), (0, 1)]);
+                });
+            },
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Tracker module playback.
+//!
+//! Tracker modules pack a song's instruments and note patterns into
+//! a single small file, making them a natural fit for retro-style
+//! games. Since they're rendered live, individual patterns can be
+//! jumped to and channels muted as the game's state changes.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+//!     commands.spawn((
+//!         TrackerNode::default(),
+//!         TrackerSource(server.load("music/title.mod")),
+//!     ));
+//! }
+//!
+//! fn enter_boss_fight(mut tracker: Single<&mut TrackerNode>) {
+//!     // Jump to the boss theme's first pattern in the order list.
+//!     *tracker.order = 12;
+//!     // Drop the melody on the fourth channel.
+//!     tracker.muted = 1 << 3;
+//! }
+//! ```
+//!
+//! Only ProTracker-compatible MOD files are currently supported,
+//! including their common 6- and 8-channel variants.
+
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use core::sync::atomic::{AtomicU32, Ordering};
+use firewheel::{
+    Volume,
+    channel_config::{ChannelConfig, ChannelCount},
+    collector::ArcGc,
+    diff::{Diff, Notify, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+mod module;
+mod player;
+
+pub use module::{TrackerLoader, TrackerLoaderError, TrackerModule, TrackerSong};
+
+/// A node that renders a tracker module.
+///
+/// The song is provided by a [`TrackerSource`] on the same entity.
+/// See the [module docs][self] for an example.
+#[derive(Diff, Patch, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct TrackerNode {
+    /// The song's output volume.
+    ///
+    /// By default, this is [`Volume::UNITY_GAIN`].
+    pub volume: Volume,
+    /// Whether playback is paused.
+    pub paused: bool,
+    /// Whether the song returns to its restart position after the last order.
+    ///
+    /// Otherwise, the node falls silent once the song ends.
+    /// By default, this is `true`.
+    pub looping: bool,
+    /// A bitmask of muted channels, where bit `n` mutes channel `n`.
+    ///
+    /// Muted channels keep playing silently, so they
+    /// can be brought back in without losing their place.
+    pub muted: u32,
+    /// Jump to the start of this position in the song's order list.
+    ///
+    /// Touching the field restarts the position, even if it's unchanged.
+    pub order: Notify<u8>,
+    /// The song being played.
+    ///
+    /// This is set automatically from the entity's [`TrackerSource`]
+    /// once its module has loaded.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    pub song: Option<ArcGc<TrackerSong>>,
+}
+
+impl Default for TrackerNode {
+    fn default() -> Self {
+        Self {
+            volume: Volume::UNITY_GAIN,
+            paused: false,
+            looping: true,
+            muted: 0,
+            order: Notify::new(0),
+            song: None,
+        }
+    }
+}
+
+impl core::fmt::Debug for TrackerNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TrackerNode")
+            .field("volume", &self.volume)
+            .field("paused", &self.paused)
+            .field("looping", &self.looping)
+            .field("muted", &self.muted)
+            .field("order", &*self.order)
+            .field("song", &self.song.as_deref())
+            .finish()
+    }
+}
+
+/// The module played by a [`TrackerNode`].
+#[derive(Debug, Clone, Component)]
+pub struct TrackerSource(pub Handle<TrackerModule>);
+
+/// The module an entity's song was taken from.
+#[derive(Debug, Component)]
+pub(crate) struct LoadedSong(AssetId<TrackerModule>);
+
+pub(crate) fn load_songs(
+    mut nodes: Query<(
+        Entity,
+        &TrackerSource,
+        &mut TrackerNode,
+        Option<&LoadedSong>,
+    )>,
+    assets: Res<Assets<TrackerModule>>,
+    mut commands: Commands,
+) {
+    for (entity, source, mut node, loaded) in nodes.iter_mut() {
+        let id = source.0.id();
+        if loaded.is_some_and(|l| l.0 == id) {
+            continue;
+        }
+
+        let Some(module) = assets.get(id) else {
+            continue;
+        };
+
+        node.song = Some(module.get());
+        commands.entity(entity).insert(LoadedSong(id));
+    }
+}
+
+pub(crate) fn remove_song(
+    trigger: On<Remove, TrackerSource>,
+    mut nodes: Query<&mut TrackerNode>,
+    mut commands: Commands,
+) {
+    if let Ok(mut node) = nodes.get_mut(trigger.event_target()) {
+        node.song = None;
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .try_remove::<LoadedSong>();
+}
+
+/// The shared playback position of a [`TrackerNode`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, node::AudioState};
+/// fn sync_to_music(tracker: Single<&AudioState<TrackerState>>) {
+///     if tracker.0.row() % 16 == 0 {
+///         info!("downbeat in order {}", tracker.0.order());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrackerState(ArcGc<AtomicU32>);
+
+const FINISHED: u32 = 1 << 31;
+
+impl TrackerState {
+    /// The current position in the song's order list.
+    pub fn order(&self) -> u8 {
+        (self.0.load(Ordering::Relaxed) >> 8) as u8
+    }
+
+    /// The current row within the pattern.
+    pub fn row(&self) -> u8 {
+        self.0.load(Ordering::Relaxed) as u8
+    }
+
+    /// Returns `true` if a song without looping has ended.
+    pub fn finished(&self) -> bool {
+        self.0.load(Ordering::Relaxed) & FINISHED != 0
+    }
+}
+
+impl AudioNode for TrackerNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("tracker")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::ZERO,
+                num_outputs: ChannelCount::STEREO,
+            })
+            .custom_state(TrackerState(ArcGc::new(AtomicU32::new(0))))
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        let sample_rate = cx.stream_info.sample_rate.get() as f64;
+
+        TrackerProcessor {
+            player: self
+                .song
+                .clone()
+                .map(|song| player::Player::new(song, sample_rate)),
+            params: self.clone(),
+            sample_rate,
+            state: cx.custom_state().cloned().unwrap(),
+        }
+    }
+}
+
+struct TrackerProcessor {
+    params: TrackerNode,
+    player: Option<player::Player>,
+    sample_rate: f64,
+    state: TrackerState,
+}
+
+impl AudioNodeProcessor for TrackerProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { outputs, .. }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<TrackerNode>() {
+            let jump = matches!(patch, TrackerNodePatch::Order(_));
+            self.params.apply(patch);
+
+            let song_changed = match (&self.params.song, &self.player) {
+                (Some(song), Some(player)) => !core::ptr::eq(&**song, &**player.song()),
+                (song, player) => song.is_some() != player.is_some(),
+            };
+
+            if song_changed {
+                self.player = self
+                    .params
+                    .song
+                    .clone()
+                    .map(|song| player::Player::new(song, self.sample_rate));
+            } else if jump {
+                if let Some(player) = &mut self.player {
+                    player.jump_to(*self.params.order as usize);
+                }
+            }
+        }
+
+        let Some(player) = self.player.as_mut().filter(|_| !self.params.paused) else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        if player.finished() {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        let frames = proc_info.frames;
+        let [left, right] = outputs else {
+            return ProcessStatus::ClearAllOutputs;
+        };
+
+        player.render(
+            [&mut left[..frames], &mut right[..frames]],
+            self.params.looping,
+            self.params.muted,
+        );
+
+        let gain = self.params.volume.amp();
+        for sample in left[..frames].iter_mut().chain(right[..frames].iter_mut()) {
+            *sample *= gain;
+        }
+
+        let mut position = ((player.order() as u32 & 0xff) << 8) | (player.row() as u32 & 0xff);
+        if player.finished() {
+            position |= FINISHED;
+        }
+        self.state.0.store(position, Ordering::Relaxed);
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
@@ -0,0 +1,314 @@
+//! ProTracker module parsing.
+
+use bevy_asset::{Asset, AssetLoader};
+use bevy_reflect::TypePath;
+use firewheel::collector::ArcGc;
+
+/// The number of rows in each pattern.
+pub(super) const ROWS: usize = 64;
+
+/// A loaded tracker module.
+///
+/// Modules are played by a [`TrackerNode`][super::TrackerNode]
+/// through a [`TrackerSource`][super::TrackerSource].
+#[derive(Asset, TypePath, Clone)]
+pub struct TrackerModule(ArcGc<TrackerSong>);
+
+impl TrackerModule {
+    /// Parse a module from its bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TrackerLoaderError> {
+        Ok(Self(ArcGc::new(TrackerSong::parse(bytes)?)))
+    }
+
+    /// Share the inner value.
+    pub fn get(&self) -> ArcGc<TrackerSong> {
+        self.0.clone()
+    }
+}
+
+impl core::fmt::Debug for TrackerModule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("TrackerModule").field(&*self.0).finish()
+    }
+}
+
+/// A parsed tracker song.
+pub struct TrackerSong {
+    title: String,
+    pub(super) channels: usize,
+    pub(super) instruments: Vec<Instrument>,
+    pub(super) orders: Vec<u8>,
+    pub(super) restart: usize,
+    /// Each pattern's cells, row by row.
+    pub(super) patterns: Vec<Vec<Cell>>,
+}
+
+impl TrackerSong {
+    /// The song's title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The number of channels, or tracks, in each pattern.
+    pub fn num_channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of entries in the song's order list.
+    pub fn num_orders(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// The cell at `row` and `channel` of the pattern at `order`.
+    pub(super) fn cell(&self, order: usize, row: usize, channel: usize) -> Cell {
+        self.orders
+            .get(order)
+            .and_then(|pattern| self.patterns.get(*pattern as usize))
+            .and_then(|pattern| pattern.get(row * self.channels + channel))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, TrackerLoaderError> {
+        use TrackerLoaderError::Format;
+
+        let signature = bytes.get(1080..1084).ok_or(Format("file is too short"))?;
+        let channels = match signature {
+            b"M.K." | b"M!K!" | b"FLT4" | b"4CHN" => 4,
+            b"FLT8" | b"OCTA" | b"CD81" => 8,
+            [n, b'C', b'H', b'N'] if n.is_ascii_digit() => (n - b'0') as usize,
+            [a, b, b'C', b'H'] if a.is_ascii_digit() && b.is_ascii_digit() => {
+                ((a - b'0') * 10 + (b - b'0')) as usize
+            }
+            _ => return Err(Format("unsupported module format")),
+        };
+        if channels == 0 {
+            return Err(Format("module has no channels"));
+        }
+
+        let title = text(&bytes[..20]);
+
+        let headers: Vec<_> = bytes[20..950].chunks(30).collect();
+        let song_length = (bytes[950] as usize).clamp(1, 128);
+        let restart = bytes[951] as usize;
+        let orders = bytes[952..952 + song_length].to_vec();
+
+        // Every pattern in the order table is stored, including unused entries.
+        let num_patterns = bytes[952..1080].iter().max().copied().unwrap_or(0) as usize + 1;
+        let pattern_bytes = ROWS * channels * 4;
+
+        let mut offset = 1084;
+        let mut patterns = Vec::with_capacity(num_patterns);
+        for _ in 0..num_patterns {
+            let data = bytes
+                .get(offset..offset + pattern_bytes)
+                .ok_or(Format("pattern data is truncated"))?;
+            patterns.push(data.chunks(4).map(Cell::parse).collect());
+            offset += pattern_bytes;
+        }
+
+        let instruments = headers
+            .iter()
+            .map(|header| {
+                let word = |index: usize| u16::from_be_bytes([header[index], header[index + 1]]);
+                let len = word(22) as usize * 2;
+
+                // Sample data is frequently truncated, so we take what's there.
+                let available = bytes.len().saturating_sub(offset).min(len);
+                let data = bytes[offset..offset + available]
+                    .iter()
+                    .map(|sample| *sample as i8 as f32 / 128.0)
+                    .collect::<Vec<_>>();
+                offset += len;
+
+                // Finetune is a signed nibble.
+                let finetune = ((header[24] & 0x0f) << 4) as i8 >> 4;
+                let loop_start = word(26) as usize * 2;
+                let loop_len = word(28) as usize * 2;
+                let looped = loop_len > 2 && loop_start < data.len();
+
+                Instrument {
+                    finetune,
+                    volume: header[25].min(64),
+                    loop_start,
+                    loop_end: if looped {
+                        (loop_start + loop_len).min(data.len())
+                    } else {
+                        0
+                    },
+                    data,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            title,
+            channels,
+            instruments,
+            orders,
+            restart,
+            patterns,
+        })
+    }
+}
+
+impl core::fmt::Debug for TrackerSong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TrackerSong")
+            .field("title", &self.title)
+            .field("channels", &self.channels)
+            .field("orders", &self.orders.len())
+            .field("patterns", &self.patterns.len())
+            .finish_non_exhaustive()
+    }
+}
+
+fn text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end])
+        .trim_end()
+        .to_string()
+}
+
+/// A sampled instrument.
+pub(super) struct Instrument {
+    pub(super) data: Vec<f32>,
+    pub(super) finetune: i8,
+    pub(super) volume: u8,
+    pub(super) loop_start: usize,
+    /// The end of the loop, or zero if the instrument doesn't loop.
+    pub(super) loop_end: usize,
+}
+
+/// A single note event in a pattern.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(super) struct Cell {
+    /// The one-based instrument, or zero for none.
+    pub(super) instrument: u8,
+    /// The note's Amiga period, or zero for none.
+    pub(super) period: u16,
+    pub(super) effect: u8,
+    pub(super) param: u8,
+}
+
+impl Cell {
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            instrument: (bytes[0] & 0xf0) | (bytes[2] >> 4),
+            period: (((bytes[0] & 0x0f) as u16) << 8) | bytes[1] as u16,
+            effect: bytes[2] & 0x0f,
+            param: bytes[3],
+        }
+    }
+}
+
+/// A loader for tracker modules.
+///
+/// Only ProTracker-compatible MOD files are currently supported,
+/// including their common 6- and 8-channel variants.
+#[derive(Debug, Default)]
+pub struct TrackerLoader;
+
+/// Errors produced while loading tracker modules.
+#[derive(Debug)]
+pub enum TrackerLoaderError {
+    /// An I/O error, such as missing files.
+    StdIo(std::io::Error),
+    /// The module is malformed or unsupported.
+    Format(&'static str),
+}
+
+impl From<std::io::Error> for TrackerLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        Self::StdIo(value)
+    }
+}
+
+impl std::error::Error for TrackerLoaderError {}
+
+impl std::fmt::Display for TrackerLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StdIo(stdio) => stdio.fmt(f),
+            Self::Format(format) => f.write_str(format),
+        }
+    }
+}
+
+impl AssetLoader for TrackerLoader {
+    type Asset = TrackerModule;
+    type Settings = ();
+    type Error = TrackerLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy_asset::io::Reader,
+        _: &Self::Settings,
+        _: &mut bevy_asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        TrackerModule::from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mod"]
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+
+    /// Build a four-channel module with one pattern and a single
+    /// looping square wave instrument.
+    pub(in super::super) fn square_module(cells: &[(usize, usize, [u8; 4])]) -> Vec<u8> {
+        let mut bytes = vec![0; 1084];
+        bytes[..4].copy_from_slice(b"test");
+
+        // The first instrument: 32 frames, full volume, looping throughout.
+        bytes[20 + 22..20 + 24].copy_from_slice(&16u16.to_be_bytes());
+        bytes[20 + 25] = 64;
+        bytes[20 + 28..20 + 30].copy_from_slice(&16u16.to_be_bytes());
+
+        bytes[950] = 1;
+        bytes[1080..1084].copy_from_slice(b"M.K.");
+
+        let mut pattern = vec![0; ROWS * 4 * 4];
+        for (row, channel, cell) in cells {
+            let index = (row * 4 + channel) * 4;
+            pattern[index..index + 4].copy_from_slice(cell);
+        }
+        bytes.extend(pattern);
+
+        bytes.extend((0..32).map(|i| if i < 16 { 100u8 } else { 156 }));
+        bytes
+    }
+
+    #[test]
+    fn test_parse_module() {
+        // Instrument 1 playing period 428 (C-2) on the second channel.
+        let bytes = square_module(&[(0, 1, [0x01, 0xac, 0x10, 0x00])]);
+        let song = TrackerSong::parse(&bytes).unwrap();
+
+        assert_eq!(song.title(), "test");
+        assert_eq!(song.num_channels(), 4);
+        assert_eq!(song.num_orders(), 1);
+        assert_eq!(
+            song.cell(0, 0, 1),
+            Cell {
+                instrument: 1,
+                period: 428,
+                effect: 0,
+                param: 0
+            }
+        );
+
+        let instrument = &song.instruments[0];
+        assert_eq!(instrument.data.len(), 32);
+        assert_eq!(instrument.loop_end, 32);
+
+        assert!(TrackerSong::parse(&bytes[..1000]).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//! ProTracker playback.
+
+use super::module::{Cell, ROWS, TrackerSong};
+use firewheel::collector::ArcGc;
+
+/// The PAL Amiga's clock rate, from which note frequencies are derived.
+const PAL_CLOCK: f64 = 7_093_789.2;
+
+/// The valid range of Amiga periods.
+const MIN_PERIOD: i32 = 113;
+const MAX_PERIOD: i32 = 856;
+
+/// The first half of ProTracker's vibrato and tremolo sine table.
+const SINE: [u8; 32] = [
+    0, 24, 49, 74, 97, 120, 141, 161, 180, 197, 212, 224, 235, 244, 250, 253, 255, 253, 250, 244,
+    235, 224, 212, 197, 180, 161, 141, 120, 97, 74, 49, 24,
+];
+
+/// The mixing state of a single channel.
+#[derive(Default, Clone)]
+struct Channel {
+    instrument: Option<usize>,
+    position: f64,
+    playing: bool,
+    period: i32,
+    /// The period after vibrato and arpeggio.
+    output_period: i32,
+    volume: i32,
+    /// The volume after tremolo.
+    output_volume: i32,
+    finetune: i8,
+    effect: u8,
+    param: u8,
+    target_period: i32,
+    porta_speed: i32,
+    vibrato: Oscillator,
+    tremolo: Oscillator,
+    loop_row: usize,
+    loop_count: u8,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Oscillator {
+    position: u8,
+    speed: u8,
+    depth: u8,
+}
+
+impl Oscillator {
+    /// Update the speed and depth from an effect parameter's nibbles.
+    fn set(&mut self, param: u8) {
+        if param >> 4 > 0 {
+            self.speed = param >> 4;
+        }
+        if param & 0x0f > 0 {
+            self.depth = param & 0x0f;
+        }
+    }
+
+    fn value(&self) -> i32 {
+        let magnitude = SINE[(self.position & 31) as usize] as i32;
+        let value = if self.position < 32 {
+            magnitude
+        } else {
+            -magnitude
+        };
+        value * self.depth as i32
+    }
+
+    fn advance(&mut self) {
+        self.position = (self.position + self.speed) & 63;
+    }
+}
+
+/// Apply an instrument's finetune to a period.
+///
+/// Each step of finetune is an eighth of a semitone.
+fn finetuned(period: u16, finetune: i8) -> i32 {
+    (period as f64 * 2f64.powf(-(finetune as f64) / 96.0)).round() as i32
+}
+
+/// A playing tracker song.
+pub(super) struct Player {
+    song: ArcGc<TrackerSong>,
+    sample_rate: f64,
+    channels: Vec<Channel>,
+    order: usize,
+    row: usize,
+    tick: usize,
+    speed: usize,
+    tempo: usize,
+    /// The frames remaining in the current tick.
+    tick_frames: usize,
+    /// The position to move to after the current row.
+    jump: Option<(usize, usize)>,
+    finished: bool,
+}
+
+impl Player {
+    pub(super) fn new(song: ArcGc<TrackerSong>, sample_rate: f64) -> Self {
+        let mut player = Self {
+            channels: vec![Channel::default(); song.channels],
+            song,
+            sample_rate,
+            order: 0,
+            row: 0,
+            tick: 0,
+            speed: 6,
+            tempo: 125,
+            tick_frames: 0,
+            jump: None,
+            finished: false,
+        };
+        player.jump_to(0);
+        player
+    }
+
+    pub(super) fn song(&self) -> &ArcGc<TrackerSong> {
+        &self.song
+    }
+
+    pub(super) fn order(&self) -> usize {
+        self.order
+    }
+
+    pub(super) fn row(&self) -> usize {
+        self.row
+    }
+
+    pub(super) fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Restart playback at the beginning of `order`.
+    pub(super) fn jump_to(&mut self, order: usize) {
+        self.order = order.min(self.song.orders.len().saturating_sub(1));
+        self.row = 0;
+        self.jump = None;
+        self.finished = false;
+        self.start_row();
+    }
+
+    /// Mix the song into a stereo pair of buffers, overwriting their contents.
+    ///
+    /// Channels whose bit is set in `muted` are silenced.
+    pub(super) fn render(&mut self, [left, right]: [&mut [f32]; 2], looping: bool, muted: u32) {
+        let mut frame = 0;
+        while frame < left.len() {
+            if self.finished {
+                left[frame..].fill(0.0);
+                right[frame..].fill(0.0);
+                return;
+            }
+
+            if self.tick_frames == 0 {
+                self.next_tick(looping);
+                continue;
+            }
+
+            let frames = self.tick_frames.min(left.len() - frame);
+            let range = frame..frame + frames;
+            left[range.clone()].fill(0.0);
+            right[range.clone()].fill(0.0);
+
+            for (index, channel) in self.channels.iter_mut().enumerate() {
+                let gain = match muted & (1 << (index % 32)) {
+                    0 => channel.output_volume as f32 / 64.0 * 0.5,
+                    _ => 0.0,
+                };
+
+                // Amiga channels are hard-panned left, right, right, left.
+                // We soften this a little for headphones.
+                let pan = if matches!(index % 4, 0 | 3) {
+                    0.25
+                } else {
+                    0.75
+                };
+
+                mix_channel(
+                    &self.song,
+                    channel,
+                    self.sample_rate,
+                    &mut left[range.clone()],
+                    &mut right[range.clone()],
+                    [gain * (1.0 - pan), gain * pan],
+                );
+            }
+
+            self.tick_frames -= frames;
+            frame += frames;
+        }
+    }
+
+    fn next_tick(&mut self, looping: bool) {
+        self.tick += 1;
+        if self.tick >= self.speed {
+            self.tick = 0;
+
+            match self.jump.take() {
+                Some((order, row)) => {
+                    self.order = order;
+                    self.row = row;
+                }
+                None => {
+                    self.row += 1;
+                    if self.row >= ROWS {
+                        self.row = 0;
+                        self.order += 1;
+                    }
+                }
+            }
+
+            if self.order >= self.song.orders.len() {
+                if !looping {
+                    self.finished = true;
+                    return;
+                }
+
+                self.order = match self.song.restart {
+                    restart if restart < self.song.orders.len() => restart,
+                    _ => 0,
+                };
+            }
+
+            self.start_row();
+        } else {
+            self.update_effects();
+            self.tick_frames = (self.sample_rate * 2.5 / self.tempo as f64) as usize;
+        }
+    }
+
+    /// Process the notes and effects of the current row.
+    fn start_row(&mut self) {
+        self.tick = 0;
+
+        for index in 0..self.channels.len() {
+            let cell = self.song.cell(self.order, self.row, index);
+            self.start_cell(index, cell);
+        }
+
+        self.tick_frames = (self.sample_rate * 2.5 / self.tempo as f64) as usize;
+    }
+
+    fn start_cell(&mut self, index: usize, cell: Cell) {
+        let song = &self.song;
+        let channel = &mut self.channels[index];
+        channel.effect = cell.effect;
+        channel.param = cell.param;
+
+        let instrument = (cell.instrument as usize).checked_sub(1);
+        if let Some((slot, instrument)) =
+            instrument.and_then(|i| Some((i, song.instruments.get(i)?)))
+        {
+            channel.instrument = Some(slot);
+            channel.volume = instrument.volume as i32;
+            channel.finetune = instrument.finetune;
+        }
+
+        let (x, y) = (cell.param >> 4, cell.param & 0x0f);
+
+        if cell.period > 0 {
+            let period = finetuned(cell.period, channel.finetune);
+
+            if matches!(cell.effect, 0x3 | 0x5) {
+                channel.target_period = period;
+            } else {
+                channel.period = period;
+                channel.position = if cell.effect == 0x9 {
+                    cell.param as f64 * 256.0
+                } else {
+                    0.0
+                };
+                channel.playing = channel.instrument.is_some();
+                channel.vibrato.position = 0;
+                channel.tremolo.position = 0;
+            }
+        }
+
+        match cell.effect {
+            0x3 if cell.param > 0 => channel.porta_speed = cell.param as i32,
+            0x4 => channel.vibrato.set(cell.param),
+            0x7 => channel.tremolo.set(cell.param),
+            0xb => self.jump = Some((cell.param as usize, 0)),
+            0xc => channel.volume = (cell.param as i32).min(64),
+            0xd => {
+                let row = (x as usize * 10 + y as usize) % ROWS;
+                let order = self.jump.map(|j| j.0).unwrap_or(self.order + 1);
+                self.jump = Some((order, row));
+            }
+            0xe => match x {
+                0x1 => channel.period = (channel.period - y as i32).max(MIN_PERIOD),
+                0x2 => channel.period = (channel.period + y as i32).min(MAX_PERIOD),
+                0x6 if y == 0 => channel.loop_row = self.row,
+                0x6 => {
+                    if channel.loop_count == 0 {
+                        channel.loop_count = y;
+                    } else {
+                        channel.loop_count -= 1;
+                    }
+
+                    if channel.loop_count > 0 {
+                        self.jump = Some((self.order, channel.loop_row));
+                    }
+                }
+                0xa => channel.volume = (channel.volume + y as i32).min(64),
+                0xb => channel.volume = (channel.volume - y as i32).max(0),
+                0xc if y == 0 => channel.volume = 0,
+                _ => {}
+            },
+            0xf if cell.param > 0 => {
+                if cell.param < 32 {
+                    self.speed = cell.param as usize;
+                } else {
+                    self.tempo = cell.param as usize;
+                }
+            }
+            _ => {}
+        }
+
+        channel.output_period = channel.period;
+        channel.output_volume = channel.volume;
+    }
+
+    /// Apply continuous effects between rows.
+    fn update_effects(&mut self) {
+        let tick = self.tick;
+
+        for channel in &mut self.channels {
+            let (x, y) = (channel.param >> 4, channel.param & 0x0f);
+            channel.output_period = channel.period;
+            channel.output_volume = channel.volume;
+
+            match channel.effect {
+                0x0 if channel.param > 0 => {
+                    let semitones = [0, x, y][tick % 3];
+                    channel.output_period =
+                        (channel.period as f64 / 2f64.powf(semitones as f64 / 12.0)) as i32;
+                }
+                0x1 => channel.period = (channel.period - channel.param as i32).max(MIN_PERIOD),
+                0x2 => channel.period = (channel.period + channel.param as i32).min(MAX_PERIOD),
+                0x3 | 0x5 => {
+                    let delta = channel.target_period - channel.period;
+                    let step = delta.clamp(-channel.porta_speed, channel.porta_speed);
+                    channel.period += step;
+                }
+                0x4 | 0x6 => {
+                    channel.output_period = channel.period + channel.vibrato.value() / 128;
+                    channel.vibrato.advance();
+                }
+                0x7 => {
+                    channel.output_volume =
+                        (channel.volume + channel.tremolo.value() / 64).clamp(0, 64);
+                    channel.tremolo.advance();
+                }
+                0xe if x == 0x9 && y > 0 && tick % y as usize == 0 => channel.position = 0.0,
+                0xe if x == 0xc && tick == y as usize => channel.volume = 0,
+                _ => {}
+            }
+
+            if matches!(channel.effect, 0x5 | 0x6 | 0xa) {
+                channel.volume = match x {
+                    0 => channel.volume - y as i32,
+                    _ => channel.volume + x as i32,
+                }
+                .clamp(0, 64);
+            }
+
+            if !matches!(channel.effect, 0x0 | 0x4 | 0x6) {
+                channel.output_period = channel.period;
+            }
+            if channel.effect != 0x7 {
+                channel.output_volume = channel.volume;
+            }
+        }
+    }
+}
+
+fn mix_channel(
+    song: &TrackerSong,
+    channel: &mut Channel,
+    sample_rate: f64,
+    left: &mut [f32],
+    right: &mut [f32],
+    [left_gain, right_gain]: [f32; 2],
+) {
+    let Some(instrument) = channel.instrument.and_then(|i| song.instruments.get(i)) else {
+        return;
+    };
+    if !channel.playing || channel.output_period <= 0 {
+        return;
+    }
+
+    let data = &instrument.data;
+    let step = PAL_CLOCK / (channel.output_period as f64 * 2.0) / sample_rate;
+    let looped = instrument.loop_end > 0;
+
+    for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+        if looped {
+            let loop_len = (instrument.loop_end - instrument.loop_start) as f64;
+            while channel.position >= instrument.loop_end as f64 {
+                channel.position -= loop_len;
+            }
+        } else if channel.position >= data.len() as f64 {
+            channel.playing = false;
+            return;
+        }
+
+        let index = channel.position as usize;
+        let fraction = (channel.position - index as f64) as f32;
+        let next = match index + 1 {
+            next if looped && next >= instrument.loop_end => instrument.loop_start,
+            next => next,
+        };
+
+        let a = data.get(index).copied().unwrap_or_default();
+        let b = data.get(next).copied().unwrap_or_default();
+        let sample = a + (b - a) * fraction;
+
+        *left += sample * left_gain;
+        *right += sample * right_gain;
+
+        channel.position += step;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nodes::tracker::{TrackerModule, module::test::square_module};
+
+    fn render(player: &mut Player, frames: usize, looping: bool) -> Vec<f32> {
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        player.render([left.as_mut_slice(), right.as_mut_slice()], looping, 0);
+        left.iter().zip(&right).map(|(l, r)| l + r).collect()
+    }
+
+    #[test]
+    fn test_playback() {
+        let bytes = square_module(&[
+            (0, 0, [0x01, 0xac, 0x10, 0x00]),
+            // Jump back to the start of the order list on row 2.
+            (2, 1, [0x00, 0x00, 0x0b, 0x00]),
+        ]);
+        let song = TrackerModule::from_bytes(&bytes).unwrap().get();
+
+        let mut player = Player::new(song.clone(), 48000.0);
+        let output = render(&mut player, 4800, true);
+        assert!(output.iter().any(|s| s.abs() > 0.1));
+
+        // At the default speed and tempo, each row lasts 120ms.
+        let mut player = Player::new(song.clone(), 48000.0);
+        render(&mut player, 5760 * 3 + 10, true);
+        assert_eq!((player.order(), player.row()), (0, 0));
+
+        // Without the jump, the pattern ends and playback stops.
+        let bytes = square_module(&[(0, 0, [0x01, 0xac, 0x10, 0x00])]);
+        let song = TrackerModule::from_bytes(&bytes).unwrap().get();
+        let mut player = Player::new(song, 48000.0);
+        render(&mut player, 5760 * ROWS + 10, false);
+        assert!(player.finished());
+    }
+}
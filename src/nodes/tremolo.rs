@@ -0,0 +1,220 @@
+//! Tremolo and auto-pan.
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount},
+    clock::DurationMusical,
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// The rate of a [`TremoloNode`]'s oscillator.
+#[derive(Diff, Patch, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum TremoloRate {
+    /// A fixed rate in hertz.
+    Hertz(f32),
+    /// The length of one cycle, in beats.
+    ///
+    /// This follows the musical transport's tempo. If no
+    /// transport is active, this is interpreted at 120 BPM.
+    Musical(DurationMusical),
+}
+
+impl Default for TremoloRate {
+    fn default() -> Self {
+        Self::Hertz(4.0)
+    }
+}
+
+/// A stereo tremolo and auto-panner.
+///
+/// A low-frequency oscillator modulates the signal's amplitude,
+/// its stereo position, or both. Slow, deep panning adds gentle
+/// movement to ambience, while fast amplitude modulation
+/// produces the classic tremolo pulse.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn drifting_wind(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("wind.ogg")).looping(),
+///         sample_effects![TremoloNode {
+///             // One sweep every two bars of 4/4.
+///             rate: TremoloRate::Musical(DurationMusical(8.0)),
+///             depth: 0.0,
+///             pan: 0.8,
+///         }],
+///     ));
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct TremoloNode {
+    /// The oscillator's rate.
+    ///
+    /// By default, this is 4 Hz.
+    pub rate: TremoloRate,
+    /// The amplitude modulation depth, from 0 to 1.
+    ///
+    /// At 1, the signal is fully silenced at the bottom of each cycle.
+    ///
+    /// By default, this is 0.5.
+    pub depth: f32,
+    /// The pan modulation depth, from 0 to 1.
+    ///
+    /// At 1, the signal sweeps fully from left to right.
+    ///
+    /// By default, this is 0.
+    pub pan: f32,
+}
+
+impl Default for TremoloNode {
+    fn default() -> Self {
+        Self {
+            rate: TremoloRate::default(),
+            depth: 0.5,
+            pan: 0.0,
+        }
+    }
+}
+
+impl AudioNode for TremoloNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("tremolo")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        cx: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        TremoloProcessor {
+            params: self.clone(),
+            phase: 0.0,
+            sample_rate: cx.stream_info.sample_rate.get() as f32,
+        }
+    }
+}
+
+const DEFAULT_BEATS_PER_MINUTE: f64 = 120.0;
+
+/// Calculate the left and right gains at `phase`.
+#[inline]
+fn gains(phase: f32, depth: f32, pan: f32) -> [f32; 2] {
+    let lfo = (phase * core::f32::consts::TAU).sin();
+
+    let depth = depth.clamp(0.0, 1.0);
+    let amplitude = 1.0 - depth * (0.5 - 0.5 * lfo);
+
+    // An equal-power pan law, normalized to unity at the center.
+    let position = pan.clamp(0.0, 1.0) * lfo;
+    let angle = (position + 1.0) * core::f32::consts::FRAC_PI_4;
+    let [left, right] = [angle.cos(), angle.sin()].map(|g| g * core::f32::consts::SQRT_2);
+
+    [amplitude * left, amplitude * right]
+}
+
+struct TremoloProcessor {
+    params: TremoloNode,
+    phase: f32,
+    sample_rate: f32,
+}
+
+impl TremoloProcessor {
+    fn frequency(&self, proc_info: &ProcInfo) -> f32 {
+        match self.params.rate {
+            TremoloRate::Hertz(hertz) => hertz,
+            TremoloRate::Musical(beats) => {
+                let beats_per_minute = proc_info
+                    .transport_info
+                    .as_ref()
+                    .map(|t| t.beats_per_minute)
+                    .filter(|bpm| *bpm > 0.0)
+                    .unwrap_or(DEFAULT_BEATS_PER_MINUTE);
+
+                if beats.0 <= 0.0 {
+                    0.0
+                } else {
+                    (beats_per_minute / 60.0 / beats.0) as f32
+                }
+            }
+        }
+    }
+}
+
+impl AudioNodeProcessor for TremoloProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<TremoloNode>() {
+            self.params.apply(patch);
+        }
+
+        let increment = self.frequency(proc_info).max(0.0) / self.sample_rate;
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            // Keep the oscillator running so it stays in time.
+            self.phase = (self.phase + increment * proc_info.frames as f32).fract();
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        for frame in 0..proc_info.frames {
+            let [left, right] = gains(self.phase, self.params.depth, self.params.pan);
+
+            outputs[0][frame] = inputs[0][frame] * left;
+            outputs[1][frame] = inputs[1][frame] * right;
+
+            self.phase = (self.phase + increment).fract();
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+
+    fn new_stream(&mut self, stream_info: &firewheel::StreamInfo) {
+        self.sample_rate = stream_info.sample_rate.get() as f32;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tremolo_gains() {
+        let close = |a: [f32; 2], b: [f32; 2]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5);
+
+        // without modulation, the signal passes unchanged
+        assert!(close(gains(0.3, 0.0, 0.0), [1.0, 1.0]));
+
+        // full depth silences the bottom of the cycle
+        assert!(close(gains(0.75, 1.0, 0.0), [0.0, 0.0]));
+        assert!(close(gains(0.25, 1.0, 0.0), [1.0, 1.0]));
+
+        // full panning sweeps hard right, then hard left
+        assert!(close(
+            gains(0.25, 0.0, 1.0),
+            [0.0, core::f32::consts::SQRT_2]
+        ));
+        assert!(close(
+            gains(0.75, 0.0, 1.0),
+            [core::f32::consts::SQRT_2, 0.0]
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Stereo width and mid/side balance.
+
+use bevy_ecs::component::Component;
+use firewheel::{
+    channel_config::{ChannelConfig, ChannelCount},
+    diff::{Diff, Patch},
+    event::ProcEvents,
+    node::{
+        AudioNode, AudioNodeInfo, AudioNodeProcessor, ConstructProcessorContext, EmptyConfig,
+        ProcBuffers, ProcExtra, ProcInfo, ProcessStatus,
+    },
+};
+
+/// A stereo imager.
+///
+/// The signal is split into its mid (the sum of both channels)
+/// and side (their difference) components, which are rebalanced
+/// and recombined. Narrowing is useful for sounds that should feel
+/// distant, while widening opens up ambience and music buses.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn distant_thunder(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("thunder.ogg")),
+///         sample_effects![StereoWidthNode {
+///             width: 0.3,
+///             ..Default::default()
+///         }],
+///     ));
+/// }
+/// ```
+#[derive(Diff, Patch, Debug, Clone, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct StereoWidthNode {
+    /// The stereo width.
+    ///
+    /// At 0, the output is mono. At 1, the width is unchanged,
+    /// and values above 1 widen the image. Values much larger
+    /// than 2 tend to sound hollow.
+    ///
+    /// By default, this is 1.
+    pub width: f32,
+    /// The balance between the mid and side components,
+    /// from -1 (only mid) to 1 (only side).
+    ///
+    /// By default, this is 0, leaving both unchanged.
+    pub balance: f32,
+}
+
+impl Default for StereoWidthNode {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            balance: 0.0,
+        }
+    }
+}
+
+impl StereoWidthNode {
+    /// The gains applied to the mid and side components.
+    #[inline]
+    fn gains(&self) -> [f32; 2] {
+        let balance = self.balance.clamp(-1.0, 1.0);
+        let mid = (1.0 - balance).min(1.0);
+        let side = (1.0 + balance).min(1.0) * self.width.max(0.0);
+
+        [mid, side]
+    }
+
+    /// Process a single stereo frame.
+    #[inline]
+    fn tick(&self, [left, right]: [f32; 2]) -> [f32; 2] {
+        let [mid_gain, side_gain] = self.gains();
+        let mid = (left + right) * 0.5 * mid_gain;
+        let side = (left - right) * 0.5 * side_gain;
+
+        [mid + side, mid - side]
+    }
+}
+
+impl AudioNode for StereoWidthNode {
+    type Configuration = EmptyConfig;
+
+    fn info(&self, _: &Self::Configuration) -> AudioNodeInfo {
+        AudioNodeInfo::new()
+            .debug_name("stereo width")
+            .channel_config(ChannelConfig {
+                num_inputs: ChannelCount::STEREO,
+                num_outputs: ChannelCount::STEREO,
+            })
+    }
+
+    fn construct_processor(
+        &self,
+        _: &Self::Configuration,
+        _: ConstructProcessorContext,
+    ) -> impl AudioNodeProcessor {
+        StereoWidthProcessor {
+            params: self.clone(),
+        }
+    }
+}
+
+struct StereoWidthProcessor {
+    params: StereoWidthNode,
+}
+
+impl AudioNodeProcessor for StereoWidthProcessor {
+    fn process(
+        &mut self,
+        proc_info: &ProcInfo,
+        ProcBuffers { inputs, outputs }: ProcBuffers,
+        events: &mut ProcEvents,
+        _: &mut ProcExtra,
+    ) -> ProcessStatus {
+        for patch in events.drain_patches::<StereoWidthNode>() {
+            self.params.apply(patch);
+        }
+
+        if proc_info.in_silence_mask.all_channels_silent(inputs.len()) {
+            return ProcessStatus::ClearAllOutputs;
+        }
+
+        for frame in 0..proc_info.frames {
+            let [left, right] = self.params.tick([inputs[0][frame], inputs[1][frame]]);
+
+            outputs[0][frame] = left;
+            outputs[1][frame] = right;
+        }
+
+        ProcessStatus::outputs_not_silent()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stereo_width() {
+        let frame = [1.0, 0.25];
+
+        // the default leaves the signal unchanged
+        assert_eq!(StereoWidthNode::default().tick(frame), frame);
+
+        let mono = StereoWidthNode {
+            width: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(mono.tick(frame), [0.625, 0.625]);
+
+        let wide = StereoWidthNode {
+            width: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(wide.tick(frame), [1.375, -0.125]);
+
+        // only the side component remains
+        let side = StereoWidthNode {
+            balance: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(side.tick(frame), [0.375, -0.375]);
+    }
+}
@@ -0,0 +1,299 @@
+//! Building blocks for custom pool strategies.
+//!
+//! [`SamplerPool`]s handle sampler allocation, growth, and assignment
+//! automatically. When the default strategy doesn't fit, you can
+//! take over parts of it without giving up the rest of the machinery.
+//!
+//! - [`PoolScoring`] adjusts which occupied samplers are reassigned
+//!   first when a pool is congested.
+//! - [`ManualAssignment`] opts a pool out of automatic assignment
+//!   entirely. Its samplers are still spawned and grown as usual, and
+//!   queued samples still expire after their
+//!   [`SampleQueueLifetime`][crate::sample::SampleQueueLifetime], but
+//!   pairing samples with samplers is left to you.
+//! - [`AssignSample`] assigns a sample to a specific sampler, normalizing
+//!   the sample's effects to match the pool's. Once assigned, the
+//!   sampler's effects chain automatically follows the sample's effects.
+//! - [`spawn_sampler_chain`] spawns a sampler and a copy of a pool's
+//!   effects chain, for pools that manage their own size.
+//!
+//! For example, a simple round-robin strategy might look like:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{
+//! #     pool::{PoolSamplers, custom::ManualAssignment},
+//! #     prelude::*,
+//! #     sample::QueuedSample,
+//! # };
+//! #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+//! struct RoundRobinPool;
+//!
+//! fn spawn_pool(mut commands: Commands) {
+//!     commands.spawn((
+//!         SamplerPool(RoundRobinPool),
+//!         PoolSize(4..=4),
+//!         ManualAssignment,
+//!     ));
+//! }
+//!
+//! fn round_robin(
+//!     queued: Query<Entity, (With<RoundRobinPool>, With<QueuedSample>)>,
+//!     pool: Single<&PoolSamplers, With<SamplerPool<RoundRobinPool>>>,
+//!     mut next: Local<usize>,
+//!     mut commands: Commands,
+//! ) {
+//!     let samplers = pool.samplers();
+//!     if samplers.is_empty() {
+//!         return;
+//!     }
+//!
+//!     for sample in &queued {
+//!         commands.assign_sample(sample, samplers[*next % samplers.len()]);
+//!         *next += 1;
+//!     }
+//! }
+//! ```
+//!
+//! Custom assignment systems should run in [`SeedlingSystems::Pool`][crate::SeedlingSystems::Pool].
+//! Note that [`AssignSample`] will fail if the sample's asset hasn't loaded yet.
+
+use super::{
+    PoolSamplerOf, PoolShape, SamplerOf,
+    queue::assign_sampler,
+    sample_effects::{EffectOf, SampleEffects},
+};
+use crate::{
+    error::SeedlingError,
+    node::{AudioState, EffectId},
+    sample::{AudioSample, SamplePlayer, SamplePriority},
+};
+use bevy_asset::prelude::*;
+use bevy_ecs::{prelude::*, system::SystemState};
+use firewheel::nodes::sampler::{SamplerConfig, SamplerNode, SamplerState};
+use std::sync::Arc;
+
+/// Opt a [`SamplerPool`][super::SamplerPool] out of automatic assignment.
+///
+/// Samples queued in this pool are never assigned a sampler
+/// unless you do so with [`AssignSample`].
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ManualAssignment;
+
+/// A sampler considered for reassignment in a congested pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplerCandidate {
+    /// The [`SamplerNode`] entity.
+    pub sampler: Entity,
+    /// The [`SamplePlayer`] currently assigned to this sampler, if any.
+    pub assignment: Option<Entity>,
+    /// The priority of the current assignment.
+    pub priority: SamplePriority,
+    /// Whether the current assignment is looping.
+    pub is_looping: bool,
+    /// Firewheel's score for the sampler.
+    ///
+    /// Unlike [`SamplerScorePolicy::score`], _higher_ scores mark better
+    /// candidates, like stopped or paused samplers. When no [`PoolScoring`]
+    /// is provided, pools rank samplers by `u64::MAX - worker_score`, so
+    /// invert it the same way to build on the default ranking.
+    pub worker_score: u64,
+}
+
+/// Scores the samplers in a congested pool.
+///
+/// When a pool has more queued samples than inactive samplers,
+/// occupied samplers are ranked for reassignment. Samplers
+/// with lower scores are reassigned first. Note that this is the
+/// opposite of [`SamplerCandidate::worker_score`].
+///
+/// The score only breaks ties after a sampler's priority and
+/// looping status, so a high-priority or looping sample is never
+/// interrupted by a custom policy alone.
+pub trait SamplerScorePolicy: Send + Sync + 'static {
+    /// Returns the sampler's score.
+    fn score(&self, candidate: &SamplerCandidate) -> u64;
+}
+
+/// Set a [`SamplerPool`][super::SamplerPool]'s scoring policy.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, pool::custom::*};
+/// /// Always reassign samplers in the order they were spawned.
+/// struct SpawnOrder;
+///
+/// impl SamplerScorePolicy for SpawnOrder {
+///     fn score(&self, candidate: &SamplerCandidate) -> u64 {
+///         candidate.sampler.index() as u64
+///     }
+/// }
+///
+/// # fn spawn_pool(mut commands: Commands) {
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct VoicePool;
+///
+/// commands.spawn((SamplerPool(VoicePool), PoolScoring::new(SpawnOrder)));
+/// # }
+/// ```
+#[derive(Clone, Component)]
+pub struct PoolScoring(pub Arc<dyn SamplerScorePolicy>);
+
+impl PoolScoring {
+    /// Create a new [`PoolScoring`].
+    pub fn new(policy: impl SamplerScorePolicy) -> Self {
+        Self(Arc::new(policy))
+    }
+}
+
+impl core::fmt::Debug for PoolScoring {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PoolScoring").finish_non_exhaustive()
+    }
+}
+
+/// Spawn a sampler and a copy of an effects chain in a pool.
+///
+/// The chain is connected to the pool's terminal node, `pool`, and
+/// registered in its [`PoolSamplers`][super::PoolSamplers]. `effects`
+/// should generally be the pool's own [`SampleEffects`], less any skipped
+/// for the current [`AudioQualityTier`][super::tiers::AudioQualityTier].
+///
+/// Returns the new [`SamplerNode`] entity.
+pub fn spawn_sampler_chain(
+    pool: Entity,
+    config: Option<SamplerConfig>,
+    effects: &[Entity],
+    commands: &mut Commands,
+) -> Entity {
+    super::spawn_chain(pool, config, effects, commands)
+}
+
+/// Assign a [`SamplePlayer`] to a specific sampler.
+///
+/// If the sampler is already playing a sample, that sample
+/// is stopped and triggers a [`PlaybackCompletionEvent`][super::PlaybackCompletionEvent].
+///
+/// This can also be queued with [`PoolCommands::assign_sample`][super::PoolCommands::assign_sample].
+#[derive(Debug, Clone, Copy)]
+pub struct AssignSample {
+    /// The [`SamplePlayer`] entity.
+    pub sample: Entity,
+    /// The [`SamplerNode`] entity.
+    pub sampler: Entity,
+}
+
+impl Command<Result> for AssignSample {
+    fn apply(self, world: &mut World) -> Result {
+        let mut state = SystemState::<(
+            Query<(&SamplePlayer, Option<&SampleEffects>, &SamplePriority)>,
+            Query<&PoolShape>,
+            Query<
+                (
+                    Entity,
+                    &mut SamplerNode,
+                    &AudioState<SamplerState>,
+                    Option<&SamplerOf>,
+                ),
+                With<PoolSamplerOf>,
+            >,
+            Query<&PoolSamplerOf>,
+            Query<&EffectId, With<EffectOf>>,
+            Res<Assets<AudioSample>>,
+            Commands,
+        )>::new(world);
+
+        {
+            let (samples, pools, mut nodes, pool_of, mut effects, assets, mut commands) =
+                state.get_mut(world);
+
+            let (player, sample_effects, priority) = samples.get(self.sample)?;
+            let asset = assets
+                .get(&player.sample)
+                .ok_or(SeedlingError::SampleNotLoaded {
+                    sample: self.sample,
+                })?;
+
+            let pool_shape = pools.get(pool_of.get(self.sampler)?.0)?;
+            let current_assignment = nodes
+                .get(self.sampler)?
+                .3
+                .map(|a| a.0)
+                .filter(|a| *a != self.sample);
+
+            assign_sampler(
+                (self.sample, player, asset, sample_effects, priority),
+                self.sampler,
+                pool_shape,
+                &mut nodes,
+                &mut effects,
+                current_assignment,
+                &mut commands,
+            )?;
+        }
+
+        state.apply(world);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        pool::{PoolSamplers, Sampler},
+        prelude::*,
+        sample::SampleQueueLifetime,
+        test::{prepare_app, run, run_until},
+    };
+
+    #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct TestPool;
+
+    #[test]
+    fn test_manual_assignment() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(2..=2), ManualAssignment));
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                SampleQueueLifetime(core::time::Duration::from_secs(60)),
+            ));
+        });
+
+        run_until(
+            &mut app,
+            |q: Single<&PoolSamplers, With<SamplerPool<TestPool>>>| q.samplers().len() == 2,
+        );
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        // manual pools never assign on their own
+        run(&mut app, |q: Query<(), With<Sampler>>| {
+            assert_eq!(q.iter().len(), 0);
+        });
+
+        let sampler = run(
+            &mut app,
+            |sample: Single<Entity, With<SamplePlayer>>,
+             pool: Single<&PoolSamplers, With<SamplerPool<TestPool>>>,
+             mut commands: Commands| {
+                let sampler = pool.samplers()[1];
+                commands.assign_sample(*sample, sampler);
+                sampler
+            },
+        );
+        app.update();
+
+        run(&mut app, move |q: Single<&Sampler>| {
+            assert_eq!(q.sampler(), sampler);
+        });
+    }
+}
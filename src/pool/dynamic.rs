@@ -33,13 +33,23 @@
 //! Dynamic pools are a convenient abstraction, but they may not be appropriate for all use-cases.
 //! They have three main drawbacks:
 //!
-//! 1. Dynamic pools cannot have individual routing. They are all connected to the [`DynamicBus`].
+//! 1. Dynamic pools cannot have individual routing. By default, they are all connected to
+//!    the [`DynamicBus`], though [`DynamicRouting`] can send them to other buses.
 //! 2. The number of pools corresponds to the total permutations of effects your project uses,
 //!    which could grow fairly large. Silent sampler nodes shouldn't take much CPU time,
 //!    but many unused nodes could grow your memory usage by a few megabytes.
 //! 3. Dynamic pools are spawned on-the-fly, so you may see a small amount of additional
 //!    playback latency as the pool propagates to the audio graph.
 //!
+//! ## Retiring dynamic pools
+//!
+//! By default, dynamic pools live for the rest of the program once spawned.
+//! If your project uses many short-lived effect combinations, you can
+//! configure [`DynamicPoolConfig::idle_timeout`] to despawn pools that have
+//! been idle for some time. [`DynamicPoolCreated`] and [`DynamicPoolRetired`]
+//! are triggered as pools come and go, and [`DynamicPools`] lists the
+//! pools that currently exist.
+//!
 //! Dynamic pool are best-suited for sounds that do not need complicated routing or
 //! bus configurations and when the kinds of effects you apply are simple and regular.
 //! Keep in mind that you can freely mix dynamic and static pools, so you're not restricted
@@ -48,44 +58,242 @@
 //! Note that when no effects are applied, your samples will be queued in the
 //! [`DefaultPool`][crate::prelude::DefaultPool], not a dynamic pool.
 
-use super::{DefaultPoolSize, PoolSize, SamplerPool, sample_effects::EffectOf};
+use super::{
+    DefaultPoolSize, PoolSamplers, PoolSize, SamplerOf, SamplerPool, label::PoolLabel,
+    sample_effects::EffectOf,
+};
 use crate::{
-    edge::Connect,
-    node::EffectId,
+    edge::{Connect, EdgeTarget},
+    node::{
+        EffectId,
+        label::{InternedNodeLabel, NodeLabel},
+    },
     pool::{label::PoolLabelContainer, sample_effects::SampleEffects},
     sample::{QueuedSample, SamplePlayer},
 };
 use bevy_app::prelude::*;
-use bevy_ecs::{component::ComponentId, entity::EntityCloner, prelude::*};
+use bevy_ecs::{
+    component::{ComponentId, Components},
+    entity::EntityCloner,
+    prelude::*,
+};
 use bevy_log::prelude::*;
 use bevy_platform::collections::HashMap;
-use bevy_seedling_macros::{NodeLabel, PoolLabel};
+use bevy_time::Time;
+use core::{any::TypeId, time::Duration};
 
 pub(super) struct DynamicPlugin;
 
 impl Plugin for DynamicPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Registries>()
-            .add_systems(PostUpdate, update_dynamic_pools);
+        app.init_resource::<DynamicPools>()
+            .init_resource::<DynamicRouting>()
+            .init_resource::<DynamicPoolConfig>()
+            .add_systems(PostUpdate, update_dynamic_pools)
+            .add_systems(
+                Last,
+                retire_dynamic_pools.after(crate::SeedlingSystems::Pool),
+            );
     }
 }
 
-/// The destination for all dynamic pools.
+/// The default destination for dynamic pools.
 #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct DynamicBus;
 
+/// Routes dynamic pools to buses other than the [`DynamicBus`].
+///
+/// Routes are checked in the order they're added, and the first match
+/// determines where a newly spawned dynamic pool connects. Pools that
+/// match no route connect to the [`DynamicBus`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct MusicBus;
+///
+/// #[derive(Component)]
+/// struct Music;
+///
+/// fn plugin(app: &mut App) {
+///     app.insert_resource(
+///         DynamicRouting::default()
+///             // Samples tagged as music get their own dynamic pools.
+///             .route_marker::<Music>(MusicBus)
+///             // Spatial dynamic pools join the rest of the sound effects.
+///             .route_effect::<SpatialBasicNode>(SfxBus),
+///     );
+/// }
+///
+/// fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         Music,
+///         SamplePlayer::new(server.load("my_sample.wav")),
+///         sample_effects![LowPassNode::default()],
+///     ));
+/// }
+/// ```
+///
+/// Since a pool's routing can't change once it's spawned, samples
+/// matching different marker routes are placed in separate dynamic
+/// pools even if their effects are identical. Changes to this
+/// resource only apply to dynamic pools spawned afterwards.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct DynamicRouting {
+    routes: Vec<DynamicRoute>,
+}
+
+#[derive(Debug, Clone)]
+struct DynamicRoute {
+    filter: RouteFilter,
+    bus: InternedNodeLabel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RouteFilter {
+    Effect(TypeId),
+    Marker(TypeId),
+}
+
+impl DynamicRouting {
+    /// Route dynamic pools that include the effect `T` to `bus`.
+    pub fn route_effect<T: Component>(mut self, bus: impl NodeLabel) -> Self {
+        self.routes.push(DynamicRoute {
+            filter: RouteFilter::Effect(TypeId::of::<T>()),
+            bus: bus.intern(),
+        });
+        self
+    }
+
+    /// Route samples with the component `T` to dynamic pools connected to `bus`.
+    pub fn route_marker<T: Component>(mut self, bus: impl NodeLabel) -> Self {
+        self.routes.push(DynamicRoute {
+            filter: RouteFilter::Marker(TypeId::of::<T>()),
+            bus: bus.intern(),
+        });
+        self
+    }
+
+    /// Find the first route matching a sample.
+    fn find(
+        &self,
+        sample: EntityRef,
+        effects: &[ComponentId],
+        components: &Components,
+    ) -> Option<usize> {
+        self.routes.iter().position(|route| match route.filter {
+            RouteFilter::Effect(ty) => components
+                .get_id(ty)
+                .is_some_and(|id| effects.contains(&id)),
+            RouteFilter::Marker(ty) => sample.contains_type_id(ty),
+        })
+    }
+}
+
+/// Configures the lifetime of dynamic pools.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct DynamicPoolConfig {
+    /// How long a dynamic pool may sit idle before it's despawned.
+    ///
+    /// A pool is idle when none of its samplers are assigned
+    /// and no samples are queued for it. If `None`, dynamic
+    /// pools are never retired.
+    ///
+    /// Defaults to `None`.
+    pub idle_timeout: Option<Duration>,
+}
+
+/// An event triggered on a dynamic pool's entity when it's spawned.
+#[derive(Debug, EntityEvent)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DynamicPoolCreated(pub Entity);
+
+/// An event triggered on a dynamic pool's entity just before it's
+/// despawned for exceeding [`DynamicPoolConfig::idle_timeout`].
+#[derive(Debug, EntityEvent)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DynamicPoolRetired(pub Entity);
+
 /// A label reserved for dynamic pools.
 #[derive(PoolLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct DynamicPoolLabel(usize);
 
+#[derive(Debug)]
 struct RegistryEntry {
     label: DynamicPoolLabel,
+    pool: Entity,
+    bus: Option<InternedNodeLabel>,
+    idle: Duration,
 }
 
-#[derive(Resource, Default)]
-struct Registries(HashMap<Vec<ComponentId>, RegistryEntry>);
+/// The dynamic pools that currently exist.
+///
+/// This can help track down an unexpectedly large number of dynamic pools.
+///
+/// ```
+/// # use bevy::{prelude::*, ecs::component::Components};
+/// # use bevy_seedling::{prelude::*, pool::dynamic::DynamicPools};
+/// fn log_dynamic_pools(pools: Res<DynamicPools>, components: &Components) {
+///     for pool in pools.iter() {
+///         let effects: Vec<_> = pool
+///             .effects
+///             .iter()
+///             .filter_map(|id| components.get_name(*id))
+///             .collect();
+///
+///         info!("{:?}: {effects:?}, idle for {:?}", pool.pool, pool.idle);
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug, Default)]
+pub struct DynamicPools {
+    /// Pools keyed by their effects and route.
+    pools: HashMap<(Vec<ComponentId>, Option<usize>), RegistryEntry>,
+    next_label: usize,
+}
+
+/// Information about a single dynamic pool.
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicPoolInfo<'a> {
+    /// The pool's entity.
+    pub pool: Entity,
+    /// The pool's effects, in order.
+    pub effects: &'a [ComponentId],
+    /// The bus selected by [`DynamicRouting`], if any.
+    ///
+    /// If `None`, the pool is connected to the [`DynamicBus`].
+    pub bus: Option<InternedNodeLabel>,
+    /// How long the pool has been idle.
+    pub idle: Duration,
+}
 
+impl DynamicPools {
+    /// Iterate over all dynamic pools.
+    pub fn iter(&self) -> impl Iterator<Item = DynamicPoolInfo<'_>> {
+        self.pools
+            .iter()
+            .map(|((effects, _), entry)| DynamicPoolInfo {
+                pool: entry.pool,
+                effects,
+                bus: entry.bus,
+                idle: entry.idle,
+            })
+    }
+
+    /// The number of dynamic pools.
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Returns `true` if there are no dynamic pools.
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update_dynamic_pools(
     queued_samples: Query<
         (Entity, &SampleEffects),
@@ -98,7 +306,10 @@ fn update_dynamic_pools(
     // TODO: make sure to migrate this to `If<Single<_>>` for 0.17
     dynamic_bus: Single<Entity, With<DynamicBus>>,
     mut effects: Query<&EffectId>,
-    mut registries: ResMut<Registries>,
+    entities: Query<EntityRef>,
+    components: &Components,
+    routing: Res<DynamicRouting>,
+    mut registries: ResMut<DynamicPools>,
     mut commands: Commands,
     dynamic_range: Res<DefaultPoolSize>,
 ) -> Result {
@@ -113,16 +324,26 @@ fn update_dynamic_pools(
                 }
             };
 
-        match registries.0.get_mut(&component_ids) {
+        let route = routing.find(entities.get(sample)?, &component_ids, components);
+        let key = (component_ids, route);
+
+        match registries.pools.get_mut(&key) {
             Some(entry) => {
                 commands.entity(sample).insert(entry.label);
             }
             None => {
-                let label = DynamicPoolLabel(registries.0.len());
+                let label = DynamicPoolLabel(registries.next_label);
+                registries.next_label += 1;
+
+                let bus_label = route.map(|route| routing.routes[route].bus);
+                let target = match bus_label {
+                    Some(label) => EdgeTarget::Label(label),
+                    None => EdgeTarget::Entity(*dynamic_bus),
+                };
 
                 let bus = commands
                     .spawn((SamplerPool(label), PoolSize(dynamic_range.0.clone())))
-                    .connect(*dynamic_bus)
+                    .connect(target)
                     .head();
 
                 let effects: Vec<_> = sample_effects.iter().collect();
@@ -140,7 +361,16 @@ fn update_dynamic_pools(
                     world.entity_mut(bus).add_related::<EffectOf>(&cloned);
                 });
 
-                registries.0.insert(component_ids, RegistryEntry { label });
+                registries.pools.insert(
+                    key,
+                    RegistryEntry {
+                        label,
+                        pool: bus,
+                        bus: bus_label,
+                        idle: Duration::ZERO,
+                    },
+                );
+                commands.trigger(DynamicPoolCreated(bus));
 
                 commands.entity(sample).insert(label);
             }
@@ -149,3 +379,41 @@ fn update_dynamic_pools(
 
     Ok(())
 }
+
+fn retire_dynamic_pools(
+    mut registries: ResMut<DynamicPools>,
+    config: Res<DynamicPoolConfig>,
+    pools: Query<&PoolSamplers>,
+    samplers: Query<(), With<SamplerOf>>,
+    queued: Query<&PoolLabelContainer, With<QueuedSample>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let Some(timeout) = config.idle_timeout else {
+        return;
+    };
+
+    let delta = time.delta();
+    registries.pools.retain(|_, entry| {
+        let label = entry.label.intern();
+        let assigned = pools
+            .get(entry.pool)
+            .is_ok_and(|pool| pool.iter().any(|s| samplers.contains(s)));
+        let waiting = queued.iter().any(|q| q.label == label);
+
+        if assigned || waiting {
+            entry.idle = Duration::ZERO;
+            return true;
+        }
+
+        entry.idle += delta;
+        if entry.idle < timeout {
+            return true;
+        }
+
+        commands.trigger(DynamicPoolRetired(entry.pool));
+        commands.entity(entry.pool).despawn();
+
+        false
+    });
+}
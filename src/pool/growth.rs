@@ -0,0 +1,294 @@
+//! Pool growth policies.
+
+use bevy_ecs::prelude::*;
+use core::time::Duration;
+use std::sync::Arc;
+
+/// The state of a pool that may need to grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolGrowthRequest {
+    /// The current number of samplers.
+    pub size: usize,
+    /// The pool's maximum number of samplers.
+    pub max_size: usize,
+    /// The number of queued samples that can't be
+    /// assigned an inactive sampler.
+    pub deficit: usize,
+    /// The number of consecutive frames, including this one,
+    /// that the pool has had a deficit.
+    pub frames_in_deficit: u32,
+}
+
+/// Decides how [`SamplerPool`]s grow to meet demand.
+///
+/// Growth is only considered when a pool has fewer inactive samplers
+/// than queued samples and hasn't yet reached its maximum [`PoolSize`].
+/// The returned growth is clamped to the pool's maximum size.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, pool::growth::*};
+/// /// Grow exactly as much as needed.
+/// struct Tight;
+///
+/// impl PoolGrowthPolicy for Tight {
+///     fn growth(&self, request: &PoolGrowthRequest) -> usize {
+///         request.deficit
+///     }
+/// }
+///
+/// fn configure(mut commands: Commands) {
+///     commands.insert_resource(DefaultPoolGrowth::new(Tight));
+/// }
+/// ```
+///
+/// [`SamplerPool`]: super::SamplerPool
+/// [`PoolSize`]: super::PoolSize
+pub trait PoolGrowthPolicy: Send + Sync + 'static {
+    /// Returns the number of samplers to add to the pool.
+    ///
+    /// Returning zero defers growth.
+    fn growth(&self, request: &PoolGrowthRequest) -> usize;
+
+    /// Whether growth should be logged.
+    ///
+    /// Defaults to `true` in debug builds.
+    fn log_growth(&self) -> bool {
+        cfg!(debug_assertions)
+    }
+}
+
+/// `bevy_seedling`'s default growth policy.
+///
+/// Pools grow by the larger of their deficit and their current
+/// size, capped at [`max_step`][Self::max_step]. As a result,
+/// small pools grow quadratically, so the cost of queuing samples
+/// is roughly amortized constant.
+#[derive(Debug, Clone)]
+pub struct QuadraticGrowth {
+    /// The maximum number of samplers added in one step,
+    /// unless the deficit is larger.
+    ///
+    /// Defaults to 16.
+    pub max_step: usize,
+    /// The number of consecutive frames a pool must be in
+    /// deficit before growing.
+    ///
+    /// While growth is deferred, queued samples may steal
+    /// samplers from lower-priority samples. Defaults to 0,
+    /// growing immediately.
+    pub hysteresis: u32,
+    /// Whether to log growth.
+    ///
+    /// Defaults to `true` in debug builds.
+    pub log: bool,
+}
+
+impl Default for QuadraticGrowth {
+    fn default() -> Self {
+        Self {
+            max_step: 16,
+            hysteresis: 0,
+            log: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl PoolGrowthPolicy for QuadraticGrowth {
+    fn growth(&self, request: &PoolGrowthRequest) -> usize {
+        if request.frames_in_deficit <= self.hysteresis {
+            return 0;
+        }
+
+        request.deficit.max(request.size.min(self.max_step))
+    }
+
+    fn log_growth(&self) -> bool {
+        self.log
+    }
+}
+
+/// Set a [`SamplerPool`]'s growth policy.
+///
+/// If not provided, the [`DefaultPoolGrowth`] is used.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, pool::growth::*};
+/// # fn spawn_pool(mut commands: Commands) {
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct AmbiencePool;
+///
+/// commands.spawn((
+///     SamplerPool(AmbiencePool),
+///     PoolSize(4..=64),
+///     // Ignore brief spikes in demand.
+///     PoolGrowth::new(QuadraticGrowth {
+///         hysteresis: 30,
+///         ..Default::default()
+///     }),
+/// ));
+/// # }
+/// ```
+///
+/// [`SamplerPool`]: super::SamplerPool
+#[derive(Clone, Component)]
+pub struct PoolGrowth(pub Arc<dyn PoolGrowthPolicy>);
+
+impl PoolGrowth {
+    /// Create a new [`PoolGrowth`].
+    pub fn new(policy: impl PoolGrowthPolicy) -> Self {
+        Self(Arc::new(policy))
+    }
+}
+
+impl core::fmt::Debug for PoolGrowth {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PoolGrowth").finish_non_exhaustive()
+    }
+}
+
+/// The default [`PoolGrowth`] applied to [`SamplerPool`]s.
+///
+/// The default is [`QuadraticGrowth`].
+///
+/// [`SamplerPool`]: super::SamplerPool
+#[derive(Clone, Resource)]
+pub struct DefaultPoolGrowth(pub Arc<dyn PoolGrowthPolicy>);
+
+impl DefaultPoolGrowth {
+    /// Create a new [`DefaultPoolGrowth`].
+    pub fn new(policy: impl PoolGrowthPolicy) -> Self {
+        Self(Arc::new(policy))
+    }
+}
+
+impl Default for DefaultPoolGrowth {
+    fn default() -> Self {
+        Self::new(QuadraticGrowth::default())
+    }
+}
+
+impl core::fmt::Debug for DefaultPoolGrowth {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("DefaultPoolGrowth").finish_non_exhaustive()
+    }
+}
+
+/// Grow a [`SamplerPool`] to at least this many samplers ahead of demand.
+///
+/// Growing a pool spawns new nodes, which takes a frame or two to reach the
+/// audio graph. When you know a burst of sounds is coming, warming the pool
+/// beforehand avoids any delay. The target is clamped to the pool's maximum
+/// [`PoolSize`], and the component is removed once applied.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, pool::growth::WarmPool};
+/// fn prepare_explosions(pool: Single<Entity, With<SamplerPool<DefaultPool>>>, mut commands: Commands) {
+///     commands.entity(*pool).insert(WarmPool(32));
+/// }
+/// ```
+///
+/// [`SamplerPool`]: super::SamplerPool
+/// [`PoolSize`]: super::PoolSize
+#[derive(Debug, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct WarmPool(pub usize);
+
+/// Tracks consecutive frames a pool has been in deficit.
+#[derive(Debug, Default, Component)]
+pub(super) struct GrowthPressure(pub u32);
+
+/// Shrink a [`SamplerPool`] back toward the start of its [`PoolSize`]
+/// after it's been idle for this long.
+///
+/// A pool is idle while no samples are queued for it. Once the
+/// timeout elapses, its unassigned samplers beyond the minimum
+/// size are despawned along with their effects chains, freeing
+/// audio graph nodes after a spike in demand.
+///
+/// If not provided, the [`DefaultPoolShrink`] is used.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, pool::growth::PoolShrink};
+/// # use core::time::Duration;
+/// # fn spawn_pool(mut commands: Commands) {
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct ExplosionPool;
+///
+/// commands.spawn((
+///     SamplerPool(ExplosionPool),
+///     PoolSize(4..=64),
+///     PoolShrink(Duration::from_secs(10)),
+/// ));
+/// # }
+/// ```
+///
+/// [`SamplerPool`]: super::SamplerPool
+/// [`PoolSize`]: super::PoolSize
+#[derive(Debug, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PoolShrink(pub Duration);
+
+/// The default [`PoolShrink`] timeout applied to [`SamplerPool`]s.
+///
+/// If `None`, pools without a [`PoolShrink`] never shrink.
+///
+/// Defaults to `None`.
+///
+/// [`SamplerPool`]: super::SamplerPool
+#[derive(Debug, Default, Clone, Copy, Resource)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct DefaultPoolShrink(pub Option<Duration>);
+
+/// Tracks how long a pool has been idle.
+#[derive(Debug, Default, Component)]
+pub(super) struct IdleTime(pub Duration);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_quadratic_growth() {
+        let policy = QuadraticGrowth::default();
+        let request = PoolGrowthRequest {
+            size: 4,
+            max_size: 32,
+            deficit: 1,
+            frames_in_deficit: 1,
+        };
+
+        assert_eq!(policy.growth(&request), 4);
+        assert_eq!(
+            policy.growth(&PoolGrowthRequest {
+                size: 24,
+                ..request
+            }),
+            16
+        );
+        assert_eq!(
+            policy.growth(&PoolGrowthRequest {
+                deficit: 20,
+                ..request
+            }),
+            20
+        );
+
+        let policy = QuadraticGrowth {
+            hysteresis: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(policy.growth(&request), 0);
+        assert_eq!(
+            policy.growth(&PoolGrowthRequest {
+                frames_in_deficit: 3,
+                ..request
+            }),
+            4
+        );
+    }
+}
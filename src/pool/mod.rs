@@ -6,20 +6,30 @@ use crate::{
     edge::{PendingConnections, PendingEdge},
     error::SeedlingError,
     node::{AudioState, DiffTimestamp, EffectId, FirewheelNode, RegisterNode},
-    pool::label::PoolLabelContainer,
+    pool::label::{InternedPoolLabel, PoolLabelContainer},
     prelude::{AudioEvents, PoolLabel},
-    sample::{OnComplete, PlaybackSettings, QueuedSample, SamplePlayer},
+    sample::{
+        AudioSample, MaxInstances, MaxPlaybackDuration, OnComplete, PlaybackSettings, QueuedSample,
+        SampleCooldown, SamplePlayer, StealOldestInstance,
+    },
+    spatial::DopplerShift,
     time::{Audio, AudioTime},
 };
 use bevy_app::prelude::*;
 use bevy_asset::prelude::*;
 use bevy_ecs::{
-    component::ComponentId, entity::EntityCloner, lifecycle::HookContext, prelude::*,
-    system::QueryLens, world::DeferredWorld,
+    component::ComponentId, entity::EntityCloner, entity_disabling::Disabled,
+    lifecycle::HookContext, prelude::*, system::QueryLens, world::DeferredWorld,
+};
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashMap;
+use bevy_time::{Stopwatch, Time};
+use core::{
+    ops::{Deref, RangeInclusive},
+    time::Duration,
 };
-use core::ops::{Deref, RangeInclusive};
 use firewheel::{
-    clock::{DurationSamples, DurationSeconds},
+    clock::{DurationSamples, DurationSeconds, InstantSeconds},
     nodes::{
         sampler::{PlaybackState, Playhead, SamplerConfig, SamplerNode, SamplerState},
         volume::VolumeNode,
@@ -28,30 +38,60 @@ use firewheel::{
 use queue::SkipTimer;
 use sample_effects::{EffectOf, SampleEffects};
 
+pub mod custom;
 pub mod dynamic;
+pub mod growth;
 pub mod label;
+pub mod overflow;
+pub mod presets;
 mod queue;
+pub mod resume;
+mod routes;
 pub mod sample_effects;
+pub mod stats;
+pub mod tiers;
 
 pub(crate) struct SamplePoolPlugin;
 
 impl Plugin for SamplePoolPlugin {
     fn build(&self, app: &mut App) {
-        app.register_node::<SamplerNode>()
+        app.init_resource::<resume::HeldSamplers>()
+            .init_resource::<SampleCooldowns>()
+            .init_resource::<SampleReloadPolicy>()
+            .register_node::<SamplerNode>()
             .register_node_state::<SamplerNode, SamplerState>()
             .add_systems(
                 Last,
                 (
-                    (populate_pool, queue::assign_default, queue::grow_pools)
+                    (
+                        populate_pool,
+                        tiers::rebuild_tiered_pools
+                            .run_if(resource_changed::<tiers::AudioQualityTier>),
+                        queue::assign_default,
+                        queue::warm_pools,
+                        queue::grow_pools,
+                        queue::shrink_pools,
+                    )
                         .chain()
                         .before(SeedlingSystems::Acquire),
-                    poll_finished
+                    (
+                        poll_finished,
+                        stop_unloaded_samples,
+                        reload_modified_samples,
+                        time_out_samples,
+                        (cool_down_samples, limit_instances).chain(),
+                    )
                         .before(SeedlingSystems::Pool)
                         .after(SeedlingSystems::Connect),
-                    watch_sample_players
+                    (watch_sample_players, routes::route_sample_players)
                         .before(SeedlingSystems::Queue)
                         .after(SeedlingSystems::Pool),
-                    (queue::assign_work, queue::update_followers)
+                    (
+                        resume::resume_held,
+                        queue::assign_work,
+                        queue::update_followers,
+                        stats::update_pool_stats,
+                    )
                         .chain()
                         .in_set(SeedlingSystems::Pool),
                     (queue::tick_skipped, queue::mark_skipped)
@@ -60,8 +100,15 @@ impl Plugin for SamplePoolPlugin {
                 ),
             )
             .add_observer(remove_finished)
+            .add_observer(mark_playback_start)
             .add_observer(generate_snapshots)
             .add_observer(apply_snapshots)
+            .add_observer(pause_disabled_players)
+            .add_observer(resume_enabled_players)
+            .add_observer(pause_pool_players)
+            .add_observer(resume_pool_players)
+            .add_observer(routes::unroute_removed_players)
+            .add_observer(resume::hold_sampler)
             .add_plugins(dynamic::DynamicPlugin);
     }
 }
@@ -275,13 +322,28 @@ impl<T: PoolLabel + Component + Clone> SamplerPool<T> {
 #[derive(Component, Default)]
 struct PoolMarker;
 
+/// A relationship linking a [`SamplerNode`] to its pool.
+///
+/// This resides in the [`SamplerNode`] entity, pointing
+/// to the pool's terminal node.
 #[derive(Debug, Component)]
 #[relationship(relationship_target = PoolSamplers)]
-struct PoolSamplerOf(pub Entity);
+pub struct PoolSamplerOf(pub Entity);
 
+/// The set of [`SamplerNode`] entities belonging to a pool.
+///
+/// This resides in the pool's terminal node. Samplers are
+/// despawned along with the pool.
 #[derive(Debug, Component)]
 #[relationship_target(relationship = PoolSamplerOf, linked_spawn)]
-struct PoolSamplers(Vec<Entity>);
+pub struct PoolSamplers(Vec<Entity>);
+
+impl PoolSamplers {
+    /// The pool's samplers, in the order they were spawned.
+    pub fn samplers(&self) -> &[Entity] {
+        &self.0
+    }
+}
 
 /// A sampler assignment relationships.
 ///
@@ -466,8 +528,87 @@ fn apply_snapshots(
     }
 }
 
+/// The playback state of a [`SamplePlayer`] before it was disabled.
+#[derive(Component)]
+struct DisabledPlayback(PlaybackState);
+
+/// Pause the sampler assigned to a disabled [`SamplePlayer`].
+///
+/// While the player is disabled, [`watch_sample_players`] can't
+/// see it, so the sampler simply holds onto the paused state.
+fn pause_disabled_players(
+    trigger: On<Add, Disabled>,
+    players: Query<(&PlaybackSettings, &Sampler), (With<SamplePlayer>, With<Disabled>)>,
+    mut samplers: Query<&mut SamplerNode>,
+    mut commands: Commands,
+) {
+    let Ok((settings, sampler)) = players.get(trigger.event_target()) else {
+        return;
+    };
+
+    if let Ok(mut node) = samplers.get_mut(sampler.sampler) {
+        node.pause();
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .insert(DisabledPlayback(*settings.playback));
+}
+
+/// Restore the playback state of a re-enabled [`SamplePlayer`].
+fn resume_enabled_players(
+    trigger: On<Remove, Disabled>,
+    mut players: Query<(&mut PlaybackSettings, &DisabledPlayback), With<Disabled>>,
+    mut commands: Commands,
+) {
+    let Ok((mut settings, previous)) = players.get_mut(trigger.event_target()) else {
+        return;
+    };
+
+    match &previous.0 {
+        // Restoring the original `Play` state could seek,
+        // so we simply resume where we left off.
+        PlaybackState::Play { .. } => settings.play(),
+        other => *settings.playback = *other,
+    }
+
+    commands
+        .entity(trigger.event_target())
+        .remove::<DisabledPlayback>();
+}
+
+/// The effects a pool's sampler chains are built from.
+///
+/// Effects left out for the current [`AudioQualityTier`][tiers::AudioQualityTier]
+/// are tracked separately, so samples carrying them can be normalized quietly.
 #[derive(Component)]
-struct PoolShape(Vec<ComponentId>);
+struct PoolShape {
+    ids: Vec<ComponentId>,
+    effects: Vec<Entity>,
+    skipped: Vec<ComponentId>,
+}
+
+impl PoolShape {
+    fn new(
+        pool_effects: &[Entity],
+        tier: tiers::AudioQualityTier,
+        skip: &Query<&tiers::SkipBelow>,
+        lens: &mut QueryLens<&EffectId>,
+    ) -> core::result::Result<Self, SeedlingError> {
+        let (effects, skipped) = tiers::partition_effects(pool_effects, tier, skip);
+
+        Ok(Self {
+            ids: fetch_effect_ids(&effects, lens)?,
+            skipped: fetch_effect_ids(&skipped, lens)?,
+            effects,
+        })
+    }
+
+    /// Returns whether the pool has any effects, including skipped ones.
+    fn has_effects(&self) -> bool {
+        !self.effects.is_empty() || !self.skipped.is_empty()
+    }
+}
 
 fn fetch_effect_ids(
     effects: &[Entity],
@@ -499,6 +640,7 @@ fn watch_sample_players(
             &mut PlaybackSettings,
             &mut AudioEvents,
             Option<&DiffTimestamp>,
+            Option<&DopplerShift>,
         ),
         Without<SamplerOf>,
     >,
@@ -508,7 +650,8 @@ fn watch_sample_players(
     let render_range = time.render_range();
 
     for (sampler_entity, mut sampler_node, mut events, sample) in q.iter_mut() {
-        let Ok((mut settings, mut source_events, timestamp)) = samples.get_mut(sample.0) else {
+        let Ok((mut settings, mut source_events, timestamp, doppler)) = samples.get_mut(sample.0)
+        else {
             continue;
         };
 
@@ -517,7 +660,7 @@ fn watch_sample_players(
         // sampler itself would call `value_at` afterwards, meaning we'd
         // produce incorrectly duplicated, potentially unscheduled events.
         sampler_node.playback = settings.playback;
-        sampler_node.speed = settings.speed;
+        sampler_node.speed = settings.speed * doppler.map(|d| d.0).unwrap_or(1.0);
 
         // TODO: consider collecting these errors
         if source_events.active_within(render_range.start, render_range.end) {
@@ -534,6 +677,12 @@ fn watch_sample_players(
     Ok(())
 }
 
+/// The final node in a sampler's effects chain.
+///
+/// This is the sampler itself when there are no effects.
+#[derive(Debug, Component)]
+struct SamplerChainTail(Entity);
+
 fn spawn_chain(
     bus: Entity,
     config: Option<SamplerConfig>,
@@ -564,6 +713,12 @@ fn spawn_chain(
         for effect in effects {
             chain.push(cloner.spawn_clone(world, effect));
         }
+        let tail = chain.last().copied().unwrap_or(sampler);
+        world.entity_mut(sampler).insert(SamplerChainTail(tail));
+
+        // Only the effects belong to the sampler. Parenting the bus
+        // too would despawn the pool along with any single sampler.
+        world.get_entity_mut(sampler)?.add_children(&chain);
         chain.push(bus);
 
         // Until we come up with a good way to implement the
@@ -571,7 +726,6 @@ fn spawn_chain(
         // a bit of boilerplate.
         world
             .get_entity_mut(sampler)?
-            .add_children(&chain)
             .entry::<PendingConnections>()
             .or_default()
             .into_mut()
@@ -610,12 +764,98 @@ fn spawn_chain(
 /// resources as necessary. If a size isn't explicitly provided,
 /// it'll be initialized according to the [`DefaultPoolSize`] resource.
 ///
-/// Pools are grown quadratically, so the cost of queuing samples
-/// is roughly amortized constant.
+/// By default, pools are grown quadratically, so the cost of queuing
+/// samples is roughly amortized constant. This can be customized
+/// with a [`PoolGrowth`][growth::PoolGrowth] policy. Pools never
+/// shrink unless given a [`PoolShrink`][growth::PoolShrink] timeout.
 #[derive(Debug, Clone, Component)]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct PoolSize(pub RangeInclusive<usize>);
 
+/// Guarantee that samples in a [`SamplerPool`] are never interrupted.
+///
+/// By default, a full pool will steal samplers from lower-priority
+/// samples to make room for new ones. [`SamplePriority`] can prevent
+/// most interruptions, but it can't express "never." With [`NoStealing`],
+/// once a sample has been assigned a sampler, it will play until completion.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # fn spawn_pool(mut commands: Commands) {
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct VoicePool;
+///
+/// commands.spawn((SamplerPool(VoicePool), PoolSize(4..=8), NoStealing));
+/// # }
+/// ```
+///
+/// Rather than silently waiting, samples queued in a full pool that can no
+/// longer grow fail immediately, triggering a [`PoolFullEvent`] followed
+/// by a [`PlaybackCompletionEvent`].
+///
+/// [`SamplePriority`]: crate::prelude::SamplePriority
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct NoStealing;
+
+/// An event triggered on [`SamplePlayer`] entities that couldn't
+/// be assigned a sampler in a full [`NoStealing`] pool.
+///
+/// This is always followed by a [`PlaybackCompletionEvent`].
+#[derive(Debug, EntityEvent)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PoolFullEvent(pub Entity);
+
+/// Choose which samples a full [`SamplerPool`] interrupts to make room for new ones.
+///
+/// When a pool is congested, occupied samplers are ranked for reassignment.
+/// [`SamplePriority`] and looping status always come first, so this policy
+/// only decides between samples that are otherwise equal.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # fn spawn_pools(mut commands: Commands) {
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct ImpactPool;
+///
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct DialoguePool;
+///
+/// // Distant impacts matter least.
+/// commands.spawn((SamplerPool(ImpactPool), StealingPolicy::Furthest));
+///
+/// // Lines of dialogue should always finish.
+/// commands.spawn((SamplerPool(DialoguePool), StealingPolicy::Never));
+/// # }
+/// ```
+///
+/// Without a policy, pools use Firewheel's sampler scoring, which
+/// reassigns stopped and paused samplers first, followed by the
+/// samplers that have been playing the longest.
+/// A [`PoolScoring`][custom::PoolScoring] takes precedence over this policy.
+///
+/// [`SamplePriority`]: crate::prelude::SamplePriority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum StealingPolicy {
+    /// Interrupt the sample that's been playing the longest,
+    /// as measured by its playhead.
+    Oldest,
+    /// Interrupt the sample with the lowest volume.
+    Quietest,
+    /// Interrupt the sample furthest from its nearest listener.
+    ///
+    /// Samples without a transform are considered to be
+    /// at the listener, and are interrupted last.
+    Furthest,
+    /// Never interrupt samples.
+    ///
+    /// This is equivalent to [`NoStealing`].
+    Never,
+}
+
 /// The default [`PoolSize`] applied to [`SamplerPool`]s.
 ///
 /// The default is `4..=32`.
@@ -624,9 +864,12 @@ pub struct PoolSize(pub RangeInclusive<usize>);
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct DefaultPoolSize(pub RangeInclusive<usize>);
 
+/// The default range of [`DefaultPoolSize`].
+pub const DEFAULT_POOL_SIZE: RangeInclusive<usize> = 4..=32;
+
 impl Default for DefaultPoolSize {
     fn default() -> Self {
-        Self(4..=32)
+        Self(DEFAULT_POOL_SIZE)
     }
 }
 
@@ -646,7 +889,9 @@ fn populate_pool(
         ),
     >,
     mut effects: Query<&EffectId>,
+    skip: Query<&tiers::SkipBelow>,
     default_pool_size: Res<DefaultPoolSize>,
+    tier: Res<tiers::AudioQualityTier>,
     mut commands: Commands,
 ) -> Result {
     for (pool, config, size, pool_effects, effect_id) in &q {
@@ -654,8 +899,10 @@ fn populate_pool(
             commands.entity(pool).insert(VolumeNode::default());
         }
 
-        let component_ids = fetch_effect_ids(
+        let shape = PoolShape::new(
             pool_effects.map(|e| e.deref()).unwrap_or(&[]),
+            *tier,
+            &skip,
             &mut effects.as_query_lens(),
         )?;
 
@@ -663,20 +910,19 @@ fn populate_pool(
             .map(|p| p.0.clone())
             .unwrap_or(default_pool_size.0.clone());
 
-        commands
-            .entity(pool)
-            .insert((PoolShape(component_ids), PoolSize(size.clone())));
-
-        let size = (*size.start()).max(1);
+        let size_start = (*size.start()).max(1);
         let config = config.clone();
-        for _ in 0..size {
-            spawn_chain(
-                pool,
-                Some(config.clone()),
-                pool_effects.map(|e| e.deref()).unwrap_or(&[]),
-                &mut commands,
-            );
+        for _ in 0..size_start {
+            spawn_chain(pool, Some(config.clone()), &shape.effects, &mut commands);
         }
+
+        commands.entity(pool).insert((
+            shape,
+            PoolSize(size.clone()),
+            growth::GrowthPressure::default(),
+            growth::IdleTime::default(),
+            stats::PoolStats::default(),
+        ));
     }
 
     Ok(())
@@ -688,10 +934,85 @@ fn populate_pool(
 /// Note that this may be triggered even when the sample isn't
 /// played, including when its playback is set to
 /// [`PlaybackState::Stop`][crate::prelude::PlaybackState] or
-/// when it can't find space in a sampler pool.
+/// when it can't find space in a sampler pool. In these cases,
+/// [`sampler`][Self::sampler] and [`playhead`][Self::playhead] are `None`.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn track_listening(completion: On<PlaybackCompletionEvent>) {
+///     if let Some(playhead) = completion.playhead {
+///         info!(
+///             "{} stopped at {playhead:?} after playing for {:?}",
+///             completion.entity, completion.played,
+///         );
+///     }
+/// }
+/// ```
 #[derive(Debug, EntityEvent)]
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
-pub struct PlaybackCompletionEvent(pub Entity);
+pub struct PlaybackCompletionEvent {
+    /// The [`SamplePlayer`] entity.
+    pub entity: Entity,
+    /// The sampler the player was assigned to, if any.
+    pub sampler: Option<Entity>,
+    /// How long the player held its sampler, measured by the audio clock.
+    ///
+    /// This includes time spent paused or waiting for a scheduled start.
+    /// Unlike the playhead, it keeps counting across loops.
+    pub played: core::time::Duration,
+    /// The sampler's playhead at completion.
+    ///
+    /// This may lag the true playhead by a processing block.
+    pub playhead: Option<core::time::Duration>,
+}
+
+impl PlaybackCompletionEvent {
+    /// Collect a player's final playback details.
+    ///
+    /// This must be called before the player loses its [`Sampler`].
+    pub(crate) fn gather(world: &World, entity: Entity) -> Self {
+        let sampler = world.get::<Sampler>(entity);
+        let played = world
+            .get::<PlaybackStart>(entity)
+            .zip(world.get_resource::<Time<Audio>>())
+            .map(|(start, time)| {
+                core::time::Duration::from_secs_f64((time.now().0 - start.0.0).max(0.0))
+            })
+            .unwrap_or_default();
+
+        Self {
+            entity,
+            sampler: sampler.map(Sampler::sampler),
+            played,
+            playhead: sampler
+                .and_then(Sampler::try_playhead_seconds)
+                .map(|playhead| core::time::Duration::from_secs_f64(playhead.0.max(0.0))),
+        }
+    }
+}
+
+/// Trigger a [`PlaybackCompletionEvent`] for `player`.
+pub(crate) fn complete_playback(player: Entity, commands: &mut Commands) {
+    commands.queue(move |world: &mut World| {
+        let completion = PlaybackCompletionEvent::gather(world, player);
+        world.trigger(completion);
+    });
+}
+
+/// The audio time at which a player was assigned its sampler.
+#[derive(Component)]
+struct PlaybackStart(InstantSeconds);
+
+fn mark_playback_start(
+    trigger: On<Insert, Sampler>,
+    time: Res<Time<Audio>>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.event_target())
+        .insert(PlaybackStart(time.now()));
+}
 
 /// Clean up sample resources according to their playback settings.
 fn remove_finished(
@@ -704,9 +1025,13 @@ fn remove_finished(
 
     match settings.on_complete {
         OnComplete::Preserve => {
-            commands
-                .entity(sample_entity)
-                .remove::<(Sampler, QueuedSample, SkipTimer)>();
+            commands.entity(sample_entity).remove::<(
+                Sampler,
+                QueuedSample,
+                SkipTimer,
+                PlaybackTimer,
+                PlaybackStart,
+            )>();
         }
         OnComplete::Remove => {
             commands
@@ -719,6 +1044,8 @@ fn remove_finished(
                     Sampler,
                     QueuedSample,
                     SkipTimer,
+                    PlaybackTimer,
+                    PlaybackStart,
                     AudioEvents,
                 )>();
         }
@@ -740,7 +1067,355 @@ fn poll_finished(
         let finished = state.0.finished() == node.playback.id();
 
         if finished {
-            commands.trigger(PlaybackCompletionEvent(active.0));
+            complete_playback(active.0, &mut commands);
+        }
+    }
+}
+
+/// Opt out of sample retention for a [`SamplePlayer`].
+///
+/// A [`SamplePlayer`] holds a strong handle to its [`AudioSample`] for as
+/// long as it plays, and its sampler keeps playing the sample's data
+/// even if the asset is removed from [`Assets<AudioSample>`] mid-playback.
+///
+/// In memory-critical scenarios, you may prefer to free samples as
+/// soon as you're done with them. With [`NoSampleRetention`], removing
+/// the asset stops the sample and releases the sampler's reference to
+/// its data, triggering a [`SampleUnloadedEvent`] followed by a
+/// [`PlaybackCompletionEvent`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("my_long_track.ogg")),
+///         NoSampleRetention,
+///     ));
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct NoSampleRetention;
+
+/// An event triggered on [`SamplePlayer`] entities whose playback was
+/// stopped because their [`AudioSample`] was unloaded.
+///
+/// This is only triggered for players with [`NoSampleRetention`],
+/// and it's always followed by a [`PlaybackCompletionEvent`].
+#[derive(Debug, EntityEvent)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SampleUnloadedEvent(pub Entity);
+
+/// Stop unretained samples whose assets have been unloaded.
+fn stop_unloaded_samples(
+    mut asset_events: MessageReader<AssetEvent<AudioSample>>,
+    mut nodes: Query<(&mut SamplerNode, &SamplerOf)>,
+    players: Query<&SamplePlayer, With<NoSampleRetention>>,
+    mut commands: Commands,
+) {
+    let unloaded: Vec<_> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } | AssetEvent::Unused { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    if unloaded.is_empty() {
+        return;
+    }
+
+    for (mut node, active) in nodes.iter_mut() {
+        let Ok(player) = players.get(active.0) else {
+            continue;
+        };
+
+        if unloaded.contains(&player.sample.id()) {
+            // Dropping the sampler's reference allows the
+            // sample's memory to be freed.
+            node.sample = None;
+
+            commands.trigger(SampleUnloadedEvent(active.0));
+            complete_playback(active.0, &mut commands);
+        }
+    }
+}
+
+/// How playing samples respond when their [`AudioSample`] is modified.
+///
+/// Samplers hold onto the data they started with, so by default,
+/// playing samples are unaffected when their asset is hot reloaded.
+/// Restarting them instead makes sound design iteration much faster,
+/// since changes can be heard without replaying anything in-game.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn plugin(app: &mut App) {
+///     #[cfg(debug_assertions)]
+///     app.insert_resource(SampleReloadPolicy::Resume);
+/// }
+/// ```
+///
+/// Samples that are still queued always play the latest data.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum SampleReloadPolicy {
+    /// Keep playing the previous data.
+    #[default]
+    Ignore,
+    /// Restart playback from the beginning with the new data.
+    Restart,
+    /// Continue from the current playhead with the new data.
+    Resume,
+}
+
+/// Restart players whose samples were modified, according to the [`SampleReloadPolicy`].
+fn reload_modified_samples(
+    policy: Res<SampleReloadPolicy>,
+    mut asset_events: MessageReader<AssetEvent<AudioSample>>,
+    mut players: Query<(Entity, &SamplePlayer, &Sampler, &mut PlaybackSettings)>,
+    mut commands: Commands,
+) {
+    let modified: Vec<_> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    if modified.is_empty() || *policy == SampleReloadPolicy::Ignore {
+        return;
+    }
+
+    for (entity, player, sampler, mut settings) in &mut players {
+        if !modified.contains(&player.sample.id()) {
+            continue;
+        }
+
+        let playhead = match *policy {
+            SampleReloadPolicy::Resume => sampler
+                .try_playhead_seconds()
+                .map(|playhead| playhead.0)
+                .unwrap_or_default(),
+            _ => 0.0,
+        };
+
+        *settings.playback = PlaybackState::Play {
+            playhead: Some(Playhead::Seconds(playhead)),
+        };
+
+        // Requeuing rather than reinserting the player keeps it from
+        // looking like a new trigger to cooldowns and instance limits.
+        commands
+            .entity(entity)
+            .remove::<Sampler>()
+            .insert(QueuedSample);
+    }
+}
+
+/// An event triggered on [`SamplePlayer`] entities whose playback was
+/// stopped because it exceeded their [`MaxPlaybackDuration`].
+///
+/// This indicates a sample played far longer than expected, which
+/// usually points to a looping sample that was never stopped. It's
+/// always followed by a [`PlaybackCompletionEvent`].
+#[derive(Debug, EntityEvent)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PlaybackTimeoutEvent(pub Entity);
+
+/// Tracks how long a sample with a [`MaxPlaybackDuration`] has been playing.
+#[derive(Component, Default)]
+struct PlaybackTimer(Stopwatch);
+
+/// Stop samples that have exceeded their [`MaxPlaybackDuration`].
+fn time_out_samples(
+    mut samples: Query<(
+        Entity,
+        &MaxPlaybackDuration,
+        &Sampler,
+        Option<&mut PlaybackTimer>,
+    )>,
+    mut nodes: Query<&mut SamplerNode>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (sample, max_duration, sampler, timer) in samples.iter_mut() {
+        let Some(mut timer) = timer else {
+            commands.entity(sample).insert(PlaybackTimer::default());
+            continue;
+        };
+
+        timer.0.tick(time.delta());
+        if timer.0.elapsed() < max_duration.0 {
+            continue;
+        }
+
+        if let Ok(mut node) = nodes.get_mut(sampler.sampler()) {
+            node.stop();
+        }
+
+        commands.entity(sample).remove::<PlaybackTimer>();
+        commands.trigger(PlaybackTimeoutEvent(sample));
+        complete_playback(sample, &mut commands);
+    }
+}
+
+/// The last time each sample with a [`SampleCooldown`] started in
+/// each pool, along with its cooldown.
+#[derive(Resource, Default)]
+struct SampleCooldowns(
+    HashMap<(AssetId<AudioSample>, Option<InternedPoolLabel>), (Duration, Duration)>,
+);
+
+/// Drop new samples whose asset is still cooling down in their pool.
+fn cool_down_samples(
+    players: Query<(
+        Entity,
+        Ref<SamplePlayer>,
+        Option<&SampleCooldown>,
+        Option<&PoolLabelContainer>,
+    )>,
+    pools: Query<(&PoolLabelContainer, &SampleCooldown), With<PoolSamplers>>,
+    mut cooldowns: ResMut<SampleCooldowns>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed();
+    cooldowns
+        .0
+        .retain(|_, (start, cooldown)| now.saturating_sub(*start) < *cooldown);
+
+    let mut new_samples: Vec<_> = players.iter().filter(|p| p.1.is_added()).collect();
+    new_samples.sort_unstable_by_key(|p| p.0);
+
+    for (entity, player, cooldown, label) in new_samples {
+        let label = label.map(|label| label.label);
+        let Some(cooldown) = cooldown.map(|c| c.0).or_else(|| {
+            pools
+                .iter()
+                .find(|(pool, _)| Some(pool.label) == label)
+                .map(|(_, cooldown)| cooldown.0)
+        }) else {
+            continue;
+        };
+
+        let key = (player.sample.id(), label);
+        if cooldowns.0.contains_key(&key) {
+            debug!("dropping sample {entity:?} during its cooldown");
+            complete_playback(entity, &mut commands);
+            continue;
+        }
+
+        cooldowns.0.insert(key, (now, cooldown));
+    }
+}
+
+/// Drop or steal from new samples that exceed their [`MaxInstances`].
+fn limit_instances(
+    players: Query<(
+        Entity,
+        Ref<SamplePlayer>,
+        Option<&MaxInstances>,
+        Has<StealOldestInstance>,
+        Option<&PoolLabelContainer>,
+        Option<&PlaybackStart>,
+        Option<&Sampler>,
+        Has<QueuedSample>,
+    )>,
+    pools: Query<
+        (&PoolLabelContainer, &MaxInstances, Has<StealOldestInstance>),
+        With<PoolSamplers>,
+    >,
+    mut nodes: Query<&mut SamplerNode>,
+    mut commands: Commands,
+) {
+    let limit_of =
+        |limit: Option<&MaxInstances>, steal: bool, label: Option<&PoolLabelContainer>| {
+            if let Some(limit) = limit {
+                return Some((limit.0 as usize, steal));
+            }
+
+            let label = label?;
+            pools
+                .iter()
+                .find(|(pool, ..)| pool.label == label.label)
+                .map(|(_, limit, steal)| (limit.0 as usize, steal))
+        };
+
+    let mut new_samples = Vec::new();
+    for (entity, player, limit, steal, label, start, sampler, queued) in &players {
+        if !player.is_added() {
+            continue;
+        }
+
+        if let Some(limit) = limit_of(limit, steal, label) {
+            let instance = (entity, start.map(|s| s.0), sampler.map(|s| s.sampler()));
+            new_samples.push((
+                instance,
+                player.sample.id(),
+                limit,
+                sampler.is_some() || queued,
+            ));
+        }
+    }
+
+    if new_samples.is_empty() {
+        return;
+    }
+    new_samples.sort_unstable_by_key(|sample| sample.0.0);
+
+    // Completed samples may be preserved, and new samples
+    // only count once they've been admitted.
+    let mut instances: HashMap<AssetId<AudioSample>, Vec<_>> = HashMap::default();
+    for (entity, player, .., start, sampler, queued) in &players {
+        if player.is_added() || !(sampler.is_some() || queued) {
+            continue;
+        }
+
+        let asset = player.sample.id();
+        if new_samples.iter().any(|sample| sample.1 == asset) {
+            instances.entry(asset).or_default().push((
+                entity,
+                start.map(|s| s.0),
+                sampler.map(|s| s.sampler()),
+            ));
+        }
+    }
+
+    for (instance, asset, (limit, steal), admitted) in new_samples {
+        let entity = instance.0;
+        let instances = instances.entry(asset).or_default();
+
+        if instances.len() >= limit {
+            // Nothing can be stolen to make room under a limit of zero.
+            if !steal || limit == 0 {
+                debug!("dropping sample {entity:?} beyond its instance limit");
+                complete_playback(entity, &mut commands);
+                continue;
+            }
+
+            // Samples that haven't started yet are the newest.
+            instances.sort_by(|a, b| match (a.1, b.1) {
+                (Some(a), Some(b)) => a.0.total_cmp(&b.0),
+                (Some(_), None) => core::cmp::Ordering::Less,
+                (None, Some(_)) => core::cmp::Ordering::Greater,
+                (None, None) => a.0.cmp(&b.0),
+            });
+
+            for (oldest, _, sampler) in instances.drain(..instances.len() + 1 - limit) {
+                if let Some(mut node) = sampler.and_then(|s| nodes.get_mut(s).ok()) {
+                    node.stop();
+                }
+
+                complete_playback(oldest, &mut commands);
+            }
+        }
+
+        if admitted {
+            instances.push(instance);
         }
     }
 }
@@ -777,51 +1452,206 @@ impl<T: PoolLabel + Component + Clone> PoolDespawn<T> {
 
 impl<T: PoolLabel + Component + Clone> Command for PoolDespawn<T> {
     fn apply(self, world: &mut World) {
-        let mut roots = world.query_filtered::<(Entity, &PoolLabelContainer), (
-            With<SamplerPool<T>>,
-            With<PoolSamplers>,
-            With<FirewheelNode>,
-        )>();
-
-        let roots: Vec<_> = roots
-            .iter(world)
-            .map(|(root, label)| (root, label.clone()))
-            .collect();
+        let roots = pool_roots(world, &self.0);
 
         let mut commands = world.commands();
-
-        let interned = self.0.intern();
-        for (root, label) in roots {
-            if label.label == interned {
-                commands.entity(root).despawn();
-            }
+        for root in roots {
+            commands.entity(root).despawn();
         }
     }
 }
 
-/// Provides methods on [`Commands`] to manage sample pools.
-pub trait PoolCommands {
-    /// Despawn a sample pool, cleaning up its resources
-    /// in the ECS and audio graph.
-    ///
-    /// Despawning the terminal volume node recursively
-    /// will produce the same effect.
-    fn despawn_pool<T: PoolLabel + Component + Clone>(&mut self, label: T);
+/// Find the root entities of the pools labeled `label`.
+fn pool_roots<T: PoolLabel + Component + Clone>(world: &mut World, label: &T) -> Vec<Entity> {
+    let mut roots = world.query_filtered::<(Entity, &PoolLabelContainer), (
+        With<SamplerPool<T>>,
+        With<PoolSamplers>,
+        With<FirewheelNode>,
+    )>();
+
+    let interned = label.intern();
+    roots
+        .iter(world)
+        .filter(|(_, container)| container.label == interned)
+        .map(|(root, _)| root)
+        .collect()
 }
 
-impl PoolCommands for Commands<'_, '_> {
-    fn despawn_pool<T: PoolLabel + Component + Clone>(&mut self, label: T) {
-        self.queue(PoolDespawn::new(label));
-    }
-}
+/// A marker for paused [`SamplerPool`]s.
+///
+/// While a pool is paused, its playing samples are paused and
+/// its queued samples wait without being assigned or timing out.
+/// This is inserted and removed by [`PoolCommands::pause_pool`]
+/// and [`PoolCommands::resume_pool`].
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PoolPaused;
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::{
-        prelude::*,
-        sample_effects,
-        test::{prepare_app, run},
+/// A pool pausing command.
+///
+/// This can be used directly or via the [`PoolCommands`] trait.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn pause_menu(mut commands: Commands) {
+///     commands.queue(PoolPause::new(DefaultPool));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PoolPause<T> {
+    label: T,
+    paused: bool,
+}
+
+impl<T: PoolLabel + Component + Clone> PoolPause<T> {
+    /// Pause the pools with the provided label.
+    pub fn new(label: T) -> Self {
+        Self {
+            label,
+            paused: true,
+        }
+    }
+
+    /// Resume the pools with the provided label.
+    pub fn resume(label: T) -> Self {
+        Self {
+            label,
+            paused: false,
+        }
+    }
+}
+
+impl<T: PoolLabel + Component + Clone> Command for PoolPause<T> {
+    fn apply(self, world: &mut World) {
+        let roots = pool_roots(world, &self.label);
+
+        let mut commands = world.commands();
+        for root in roots {
+            if self.paused {
+                commands.entity(root).insert(PoolPaused);
+            } else {
+                commands.entity(root).remove::<PoolPaused>();
+            }
+        }
+    }
+}
+
+/// Marks samples that were playing when their pool was paused.
+#[derive(Component)]
+struct PausedByPool;
+
+fn pause_pool_players(
+    trigger: On<Add, PoolPaused>,
+    pools: Query<&PoolSamplers>,
+    samplers: Query<&SamplerOf>,
+    mut players: Query<&mut PlaybackSettings, With<SamplePlayer>>,
+    mut commands: Commands,
+) {
+    let Ok(pool) = pools.get(trigger.event_target()) else {
+        return;
+    };
+
+    for assignment in samplers.iter_many(pool.iter()) {
+        let Ok(mut settings) = players.get_mut(assignment.0) else {
+            continue;
+        };
+
+        if matches!(*settings.playback, PlaybackState::Play { .. }) {
+            settings.pause();
+            commands.entity(assignment.0).insert(PausedByPool);
+        }
+    }
+}
+
+fn resume_pool_players(
+    trigger: On<Remove, PoolPaused>,
+    pools: Query<&PoolSamplers>,
+    samplers: Query<&SamplerOf>,
+    mut players: Query<&mut PlaybackSettings, (With<SamplePlayer>, With<PausedByPool>)>,
+    mut commands: Commands,
+) {
+    let Ok(pool) = pools.get(trigger.event_target()) else {
+        return;
+    };
+
+    for assignment in samplers.iter_many(pool.iter()) {
+        let Ok(mut settings) = players.get_mut(assignment.0) else {
+            continue;
+        };
+
+        // Samples paused or stopped in the meantime stay that way.
+        if matches!(*settings.playback, PlaybackState::Pause) {
+            settings.play();
+        }
+        commands.entity(assignment.0).remove::<PausedByPool>();
+    }
+}
+
+/// Provides methods on [`Commands`] to manage sample pools.
+pub trait PoolCommands {
+    /// Despawn a sample pool, cleaning up its resources
+    /// in the ECS and audio graph.
+    ///
+    /// Despawning the terminal volume node recursively
+    /// will produce the same effect.
+    fn despawn_pool<T: PoolLabel + Component + Clone>(&mut self, label: T);
+
+    /// Assign a [`SamplePlayer`] to a specific sampler.
+    ///
+    /// See [`AssignSample`][custom::AssignSample] for details.
+    fn assign_sample(&mut self, sample: Entity, sampler: Entity);
+
+    /// Pause a sample pool.
+    ///
+    /// Every playing sample in the pool is paused, and queued samples
+    /// wait without being assigned or timing out. This is handy for
+    /// pause menus that should silence effects but keep music running.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// fn open_menu(mut commands: Commands) {
+    ///     commands.pause_pool(DefaultPool);
+    /// }
+    ///
+    /// fn close_menu(mut commands: Commands) {
+    ///     commands.resume_pool(DefaultPool);
+    /// }
+    /// ```
+    fn pause_pool<T: PoolLabel + Component + Clone>(&mut self, label: T);
+
+    /// Resume a sample pool paused with [`pause_pool`][Self::pause_pool].
+    ///
+    /// Only the samples paused along with the pool are resumed.
+    fn resume_pool<T: PoolLabel + Component + Clone>(&mut self, label: T);
+}
+
+impl PoolCommands for Commands<'_, '_> {
+    fn despawn_pool<T: PoolLabel + Component + Clone>(&mut self, label: T) {
+        self.queue(PoolDespawn::new(label));
+    }
+
+    fn assign_sample(&mut self, sample: Entity, sampler: Entity) {
+        self.queue(custom::AssignSample { sample, sampler });
+    }
+
+    fn pause_pool<T: PoolLabel + Component + Clone>(&mut self, label: T) {
+        self.queue(PoolPause::new(label));
+    }
+
+    fn resume_pool<T: PoolLabel + Component + Clone>(&mut self, label: T) {
+        self.queue(PoolPause::resume(label));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        sample_effects,
+        test::{prepare_app, run, run_until},
     };
     use bevy_seedling_macros::PoolLabel;
 
@@ -886,23 +1716,59 @@ mod test {
             ));
         });
 
-        loop {
-            let players = run(
-                &mut app,
-                |q: Query<Entity, (With<SamplePlayer>, With<Sampler>)>| q.iter().len(),
-            );
-
-            if players == 1 {
-                break;
-            }
-
-            app.update();
-        }
+        run_until(
+            &mut app,
+            |q: Query<Entity, (With<SamplePlayer>, With<Sampler>)>| q.iter().len() == 1,
+        );
     }
 
     #[derive(Component)]
     struct EmptyComponent;
 
+    #[test]
+    fn test_disabled_player_pauses() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(1..=1)));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                EmptyComponent,
+            ));
+        });
+
+        run_until(
+            &mut app,
+            |q: Query<Entity, (With<EmptyComponent>, With<Sampler>)>| q.iter().len() == 1,
+        );
+
+        run(
+            &mut app,
+            |q: Single<Entity, With<EmptyComponent>>, mut commands: Commands| {
+                commands.entity(*q).insert(Disabled);
+            },
+        );
+        app.update();
+
+        run(&mut app, |q: Single<&SamplerNode, With<SamplerOf>>| {
+            assert!(matches!(*q.playback, PlaybackState::Pause));
+        });
+
+        run(
+            &mut app,
+            |q: Single<Entity, (With<EmptyComponent>, With<Disabled>)>, mut commands: Commands| {
+                commands.entity(*q).remove::<Disabled>();
+            },
+        );
+        app.update();
+
+        run(&mut app, |q: Single<&SamplerNode, With<SamplerOf>>| {
+            assert!(matches!(
+                *q.playback,
+                PlaybackState::Play { playhead: None }
+            ));
+        });
+    }
+
     #[test]
     fn test_remove_in_dynamic() {
         let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
@@ -919,18 +1785,10 @@ mod test {
         });
 
         // Then wait until the sample player is removed.
-        loop {
-            let players = run(
-                &mut app,
-                |q: Query<Entity, (With<SamplePlayer>, With<EmptyComponent>)>| q.iter().len(),
-            );
-
-            if players == 0 {
-                break;
-            }
-
-            app.update();
-        }
+        run_until(
+            &mut app,
+            |q: Query<Entity, (With<SamplePlayer>, With<EmptyComponent>)>| q.iter().len() == 0,
+        );
 
         // Once removed, we'll verify that _all_ audio-related components are removed.
         let world = app.world_mut();
@@ -943,6 +1801,150 @@ mod test {
         assert!(entity.contains::<EmptyComponent>());
     }
 
+    #[test]
+    fn test_player_connections() {
+        #[derive(Component)]
+        struct Tap;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((VolumeNode::default(), MainBus));
+            commands.spawn((
+                SamplerPool(TestPool),
+                sample_effects![LowPassNode::default()],
+            ));
+            let tap = commands.spawn((VolumeNode::default(), Tap)).id();
+
+            commands
+                .spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+                ))
+                .connect(tap);
+        });
+
+        fn tap_edges(
+            tap: Single<&FirewheelNode, With<Tap>>,
+            filters: Query<&FirewheelNode, With<LowPassNode>>,
+            mut context: ResMut<AudioContext>,
+        ) -> usize {
+            let tap = tap.0;
+            let filters: Vec<_> = filters.iter().map(|f| f.0).collect();
+            context.with(move |context| {
+                context
+                    .edges()
+                    .iter()
+                    .filter(|e| e.dst_node == tap && filters.contains(&e.src_node))
+                    .count()
+            })
+        }
+
+        // The player's connection is made from its chain's tail.
+        run_until(&mut app, tap_edges.map(|edges| edges > 0));
+
+        run(
+            &mut app,
+            |player: Single<Entity, With<SamplePlayer>>, mut commands: Commands| {
+                commands.entity(*player).despawn();
+            },
+        );
+        app.update();
+        app.update();
+
+        assert_eq!(run(&mut app, tap_edges), 0);
+    }
+
+    #[test]
+    fn test_dynamic_routing() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((VolumeNode::default(), dynamic::DynamicBus));
+            commands.spawn((VolumeNode::default(), SfxBus));
+        });
+
+        app.insert_resource(
+            dynamic::DynamicRouting::default().route_marker::<EmptyComponent>(SfxBus),
+        );
+
+        run(
+            &mut app,
+            |mut commands: Commands, server: Res<AssetServer>| {
+                commands.spawn((
+                    SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+                    EmptyComponent,
+                    sample_effects![LowPassNode::default()],
+                ));
+                commands.spawn((
+                    SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+                    sample_effects![LowPassNode::default()],
+                ));
+            },
+        );
+
+        // Identical effects with different routes produce separate pools.
+        run_until(
+            &mut app,
+            |dynamic: Single<&FirewheelNode, With<dynamic::DynamicBus>>,
+             sfx: Single<&FirewheelNode, With<SfxBus>>,
+             mut context: ResMut<AudioContext>| {
+                let (dynamic, sfx) = (dynamic.0, sfx.0);
+                context.with(move |context| {
+                    let edges = context.edges();
+                    edges.iter().any(|e| e.dst_node == dynamic)
+                        && edges.iter().any(|e| e.dst_node == sfx)
+                })
+            },
+        );
+
+        let pools = run(
+            &mut app,
+            |q: Query<(), (With<PoolSamplers>, Without<DefaultPool>)>| q.iter().len(),
+        );
+        assert_eq!(pools, 2);
+    }
+
+    #[test]
+    fn test_dynamic_retirement() {
+        #[derive(Resource, Default)]
+        struct Lifecycle {
+            created: usize,
+            retired: usize,
+        }
+
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((VolumeNode::default(), dynamic::DynamicBus));
+        });
+
+        app.init_resource::<Lifecycle>()
+            .insert_resource(dynamic::DynamicPoolConfig {
+                idle_timeout: Some(core::time::Duration::ZERO),
+            })
+            .add_observer(
+                |_: On<dynamic::DynamicPoolCreated>, mut lifecycle: ResMut<Lifecycle>| {
+                    lifecycle.created += 1;
+                },
+            )
+            .add_observer(
+                |_: On<dynamic::DynamicPoolRetired>, mut lifecycle: ResMut<Lifecycle>| {
+                    lifecycle.retired += 1;
+                },
+            );
+
+        run(
+            &mut app,
+            |mut commands: Commands, server: Res<AssetServer>| {
+                commands.spawn((
+                    SamplePlayer::new(server.load("sine_440hz_1ms.wav")),
+                    sample_effects![LowPassNode::default()],
+                ));
+            },
+        );
+
+        run_until(&mut app, |lifecycle: Res<Lifecycle>| lifecycle.retired == 1);
+
+        let lifecycle = app.world().resource::<Lifecycle>();
+        assert_eq!(lifecycle.created, 1);
+        assert!(app.world().resource::<dynamic::DynamicPools>().is_empty());
+    }
+
     #[test]
     fn test_remove_in_pool() {
         let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
@@ -963,18 +1965,10 @@ mod test {
         });
 
         // Then wait until the sample player is removed.
-        loop {
-            let players = run(
-                &mut app,
-                |q: Query<Entity, (With<SamplePlayer>, With<EmptyComponent>)>| q.iter().len(),
-            );
-
-            if players == 0 {
-                break;
-            }
-
-            app.update();
-        }
+        run_until(
+            &mut app,
+            |q: Query<Entity, (With<SamplePlayer>, With<EmptyComponent>)>| q.iter().len() == 0,
+        );
 
         // Once removed, we'll verify that _all_ audio-related components are removed.
         let world = app.world_mut();
@@ -1001,14 +1995,7 @@ mod test {
         });
 
         // wait for at least one to load
-        loop {
-            let world = app.world_mut();
-            let mut q = world.query_filtered::<Entity, With<Sampler>>();
-            if q.iter(world).len() != 0 {
-                break;
-            }
-            app.update();
-        }
+        run_until(&mut app, |q: Query<(), With<Sampler>>| !q.is_empty());
 
         // allow them to jostle
         for _ in 0..2 {
@@ -1021,4 +2008,659 @@ mod test {
         let mut q = world.query_filtered::<Entity, With<SamplePlayer>>();
         assert_eq!(q.iter(world).len(), 4);
     }
+
+    #[test]
+    fn test_no_stealing() {
+        #[derive(Resource, Default)]
+        struct FullCount(usize);
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(4..=4), NoStealing));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..8 {
+                commands.spawn((TestPool, SamplePlayer::new(server.load("caw.ogg"))));
+            }
+        });
+
+        app.init_resource::<FullCount>().add_observer(
+            |_: On<PoolFullEvent>, mut count: ResMut<FullCount>| {
+                count.0 += 1;
+            },
+        );
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| !q.is_empty());
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the overflowing players fail immediately rather than stealing
+        assert_eq!(app.world().resource::<FullCount>().0, 4);
+
+        let world = app.world_mut();
+        let mut q = world.query_filtered::<Entity, With<SamplePlayer>>();
+        assert_eq!(q.iter(world).len(), 4);
+    }
+
+    #[test]
+    fn test_stealing_policy() {
+        #[derive(Component)]
+        struct Quiet;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(2..=2),
+                StealingPolicy::Quietest,
+            ));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg"))
+                    .looping()
+                    .with_volume(Volume::Linear(0.1)),
+                Quiet,
+            ));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 2);
+
+        let server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn((
+            TestPool,
+            SamplePlayer::new(server.load("caw.ogg")).looping(),
+        ));
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the quiet sample makes room, even though it started first
+        let world = app.world_mut();
+        let mut quiet = world.query_filtered::<Entity, With<Quiet>>();
+        assert_eq!(quiet.iter(world).len(), 0);
+
+        let mut q = world.query_filtered::<Entity, With<Sampler>>();
+        assert_eq!(q.iter(world).len(), 2);
+    }
+
+    #[test]
+    fn test_default_stealing() {
+        #[derive(Component)]
+        struct Paused;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(2..=2)));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                PlaybackSettings::default().with_playback(PlaybackState::Pause),
+                Paused,
+            ));
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 2);
+
+        let server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn((
+            TestPool,
+            SamplePlayer::new(server.load("caw.ogg")).looping(),
+        ));
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the paused sample makes room, rather than the playing one
+        let world = app.world_mut();
+        let mut paused = world.query_filtered::<Entity, With<Paused>>();
+        assert_eq!(paused.iter(world).len(), 0);
+
+        let mut q = world.query_filtered::<Entity, With<Sampler>>();
+        assert_eq!(q.iter(world).len(), 2);
+    }
+
+    #[test]
+    fn test_pool_stats() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(2..=2), NoStealing));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..3 {
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ));
+            }
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 2);
+        app.update();
+
+        run(
+            &mut app,
+            |stats: Single<&stats::PoolStats, With<SamplerPool<TestPool>>>| {
+                assert_eq!(stats.active, 2);
+                assert_eq!(stats.peak, 2);
+                assert_eq!(stats.queued, 0);
+                assert_eq!(stats.steals, 0);
+                // the third sample is rejected by the full pool
+                assert_eq!(stats.skips, 1);
+            },
+        );
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(1..=8),
+                growth::WarmPool(4),
+                growth::PoolShrink(core::time::Duration::from_secs(3600)),
+            ));
+        });
+
+        let pool_size = |app: &mut App| {
+            run(
+                app,
+                |pool: Single<&PoolSamplers, With<SamplerPool<TestPool>>>| pool.len(),
+            )
+        };
+
+        app.update();
+        assert_eq!(pool_size(&mut app), 4);
+
+        run(
+            &mut app,
+            |pool: Single<Entity, With<SamplerPool<TestPool>>>, mut commands: Commands| {
+                commands
+                    .entity(*pool)
+                    .insert(growth::PoolShrink(core::time::Duration::ZERO));
+            },
+        );
+        app.update();
+
+        assert_eq!(pool_size(&mut app), 1);
+    }
+
+    #[test]
+    fn test_pause_pool() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(1..=1), NoStealing));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 1);
+
+        run(
+            &mut app,
+            |mut commands: Commands, server: Res<AssetServer>| {
+                commands.pause_pool(TestPool);
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                    crate::sample::SampleQueueLifetime(core::time::Duration::ZERO),
+                ));
+            },
+        );
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        // the playing sample is paused, and the new one
+        // waits despite its queue lifetime
+        run(
+            &mut app,
+            |players: Query<(&PlaybackSettings, Has<Sampler>), With<SamplePlayer>>| {
+                assert_eq!(players.iter().len(), 2);
+                for (settings, assigned) in &players {
+                    if assigned {
+                        assert!(matches!(*settings.playback, PlaybackState::Pause));
+                    }
+                }
+                assert_eq!(players.iter().filter(|p| p.1).count(), 1);
+            },
+        );
+
+        run(&mut app, |mut commands: Commands| {
+            commands.resume_pool(TestPool);
+        });
+        app.update();
+
+        // the queued sample is rejected by the full pool
+        run(
+            &mut app,
+            |players: Query<&PlaybackSettings, With<SamplePlayer>>| {
+                assert_eq!(players.iter().len(), 1);
+                assert!(
+                    players
+                        .iter()
+                        .all(|settings| matches!(*settings.playback, PlaybackState::Play { .. }))
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_max_instances() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(4..=4)));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..3 {
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                    MaxInstances(2),
+                ));
+            }
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the third instance is dropped
+        run(&mut app, |players: Query<(), With<SamplePlayer>>| {
+            assert_eq!(players.iter().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(4..=4),
+                SampleCooldown(Duration::from_secs(3600)),
+            ));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..3 {
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ));
+            }
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        let server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn((
+            TestPool,
+            SamplePlayer::new(server.load("caw.ogg")).looping(),
+        ));
+        app.update();
+
+        // only the first play makes it through
+        run(&mut app, |players: Query<(), With<SamplePlayer>>| {
+            assert_eq!(players.iter().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_cooldown_per_pool() {
+        #[derive(PoolLabel, Clone, Debug, PartialEq, Eq, Hash)]
+        struct Unrestricted;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(4..=4),
+                SampleCooldown(Duration::from_secs(3600)),
+            ));
+            commands.spawn((SamplerPool(Unrestricted), PoolSize(4..=4)));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..2 {
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ));
+                commands.spawn((
+                    Unrestricted,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ));
+            }
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the cooldown only applies within its own pool
+        run(
+            &mut app,
+            |cooled: Query<(), (With<SamplePlayer>, With<TestPool>)>,
+             unrestricted: Query<(), (With<SamplePlayer>, With<Unrestricted>)>| {
+                assert_eq!(cooled.iter().len(), 1);
+                assert_eq!(unrestricted.iter().len(), 2);
+            },
+        );
+    }
+
+    #[test]
+    fn test_steal_oldest_instance() {
+        #[derive(Component)]
+        struct First;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(4..=4),
+                MaxInstances(1),
+                StealOldestInstance,
+            ));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                First,
+            ));
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 1);
+
+        let server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn((
+            TestPool,
+            SamplePlayer::new(server.load("caw.ogg")).looping(),
+        ));
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the first instance makes room for the second
+        run(
+            &mut app,
+            |players: Query<Has<First>, With<SamplePlayer>>| {
+                assert_eq!(players.iter().collect::<Vec<_>>(), [false]);
+            },
+        );
+    }
+
+    #[test]
+    fn test_steal_with_zero_limit() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(4..=4),
+                MaxInstances(0),
+                StealOldestInstance,
+            ));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+        });
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // there's nothing to steal, so the sample never plays
+        run(&mut app, |players: Query<(), With<SamplePlayer>>| {
+            assert_eq!(players.iter().len(), 0);
+        });
+    }
+
+    #[test]
+    fn test_overflow() {
+        #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+        struct FallbackPool;
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                PoolSize(4..=4),
+                NoStealing,
+                overflow::OverflowTo::new(FallbackPool),
+            ));
+            commands.spawn((SamplerPool(FallbackPool), PoolSize(4..=4)));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(crate::edge::AudioGraphOutput);
+
+            for _ in 0..8 {
+                commands.spawn((
+                    TestPool,
+                    SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ));
+            }
+        });
+
+        app.add_observer(|_: On<PoolFullEvent>| {
+            panic!("overflowing samples should not be rejected");
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 8);
+
+        let world = app.world_mut();
+        let mut q = world.query_filtered::<Entity, (With<SamplePlayer>, With<FallbackPool>)>();
+        assert_eq!(q.iter(world).len(), 4);
+        let mut q = world.query_filtered::<Entity, (With<SamplePlayer>, With<TestPool>)>();
+        assert_eq!(q.iter(world).len(), 4);
+    }
+
+    #[test]
+    fn test_sample_retention() {
+        #[derive(Resource, Default)]
+        struct Unloaded(usize);
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(2..=2)));
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                EmptyComponent,
+            ));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+                NoSampleRetention,
+            ));
+        });
+
+        app.init_resource::<Unloaded>().add_observer(
+            |_: On<SampleUnloadedEvent>, mut unloaded: ResMut<Unloaded>| {
+                unloaded.0 += 1;
+            },
+        );
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 2);
+
+        run(
+            &mut app,
+            |players: Query<&SamplePlayer>, mut assets: ResMut<Assets<AudioSample>>| {
+                for player in &players {
+                    assets.remove(&player.sample);
+                }
+            },
+        );
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        // the unretained sample is stopped and completes
+        assert_eq!(app.world().resource::<Unloaded>().0, 1);
+        run(&mut app, |q: Query<(), With<NoSampleRetention>>| {
+            assert_eq!(q.iter().len(), 0);
+        });
+
+        // while the retained sample continues playing
+        run(
+            &mut app,
+            |q: Query<(), (With<Sampler>, With<EmptyComponent>)>| {
+                assert_eq!(q.iter().len(), 1);
+            },
+        );
+    }
+
+    #[test]
+    fn test_reload_policy() {
+        #[derive(Resource, Default)]
+        struct Requeued(usize);
+
+        #[derive(Resource, Default)]
+        struct Reinserted(usize);
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(1..=1)));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+        });
+
+        run_until(&mut app, |q: Query<(), With<Sampler>>| q.iter().len() == 1);
+
+        app.insert_resource(SampleReloadPolicy::Restart)
+            .init_resource::<Requeued>()
+            .init_resource::<Reinserted>()
+            .add_observer(
+                |_: On<Insert, QueuedSample>, mut requeued: ResMut<Requeued>| {
+                    requeued.0 += 1;
+                },
+            )
+            .add_observer(
+                |_: On<Insert, SamplePlayer>, mut reinserted: ResMut<Reinserted>| {
+                    reinserted.0 += 1;
+                },
+            );
+
+        run(
+            &mut app,
+            |players: Query<&SamplePlayer>, mut assets: ResMut<Assets<AudioSample>>| {
+                for player in &players {
+                    assets.get_mut(&player.sample);
+                }
+            },
+        );
+        app.update();
+        app.update();
+
+        // the playing sample is queued again with the new data
+        // without being mistaken for a new trigger
+        assert_eq!(app.world().resource::<Requeued>().0, 1);
+        assert_eq!(app.world().resource::<Reinserted>().0, 0);
+    }
+
+    #[test]
+    fn test_playback_timeout() {
+        #[derive(Resource, Default)]
+        struct TimedOut(usize);
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(2..=2)));
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                MaxPlaybackDuration(core::time::Duration::ZERO),
+            ));
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+            ));
+        });
+
+        app.init_resource::<TimedOut>().add_observer(
+            |_: On<PlaybackTimeoutEvent>, mut timed_out: ResMut<TimedOut>| {
+                timed_out.0 += 1;
+            },
+        );
+
+        run_until(&mut app, |timed_out: Res<TimedOut>| timed_out.0 > 0);
+        app.update();
+
+        // only the limited sample is stopped and despawned
+        assert_eq!(app.world().resource::<TimedOut>().0, 1);
+        run(
+            &mut app,
+            |q: Query<Option<&MaxPlaybackDuration>, With<SamplePlayer>>| {
+                assert_eq!(q.iter().len(), 1);
+                assert!(q.iter().all(|max| max.is_none()));
+            },
+        );
+    }
+
+    #[test]
+    fn test_completion_details() {
+        #[derive(Resource, Default)]
+        struct Completed(Vec<(Entity, Option<Entity>)>);
+
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(1..=1), NoStealing));
+
+            // This player times out while holding the only sampler...
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                MaxPlaybackDuration(core::time::Duration::ZERO),
+                SamplePriority(1),
+            ));
+            // ...while this one is never assigned.
+            commands.spawn((TestPool, SamplePlayer::new(server.load("caw.ogg"))));
+        });
+
+        app.init_resource::<Completed>().add_observer(
+            |completion: On<PlaybackCompletionEvent>, mut completed: ResMut<Completed>| {
+                completed.0.push((completion.entity, completion.sampler));
+            },
+        );
+
+        run_until(&mut app, |completed: Res<Completed>| completed.0.len() >= 2);
+
+        let completed = &app.world().resource::<Completed>().0;
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed.iter().filter(|(_, s)| s.is_some()).count(), 1);
+    }
 }
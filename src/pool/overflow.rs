@@ -0,0 +1,124 @@
+//! Overflowing full pools into fallback pools.
+
+use super::label::PoolLabel;
+use crate::sample::SamplePriority;
+use bevy_ecs::{lifecycle::HookContext, prelude::*, system::EntityCommands, world::DeferredWorld};
+use std::sync::Arc;
+
+/// Overflow samples from a full pool into a fallback pool.
+///
+/// When a pool has reached its maximum [`PoolSize`] and a queued sample
+/// can't steal a sampler, the sample would normally wait until its
+/// [`SampleQueueLifetime`] expires. With [`OverflowTo`], samples with at
+/// least [`OverflowTo::min_priority`] are instead moved into the fallback
+/// pool, where they're queued as usual.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct FootstepPool;
+///
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct OverflowPool;
+///
+/// fn spawn_pools(mut commands: Commands) {
+///     commands
+///         .spawn((
+///             SamplerPool(FootstepPool),
+///             PoolSize(4..=4),
+///             OverflowTo::new(OverflowPool).with_min_priority(SamplePriority(1)),
+///         ))
+///         .connect(SfxBus);
+///
+///     // The fallback pool should generally share the first pool's routing.
+///     commands
+///         .spawn((SamplerPool(OverflowPool), PoolSize(4..=4)))
+///         .connect(SfxBus);
+/// }
+/// ```
+///
+/// Pools with [`NoStealing`] overflow qualifying samples rather than
+/// rejecting them. Samples that don't meet the priority threshold
+/// behave as if the pool had no fallback.
+///
+/// Overflowing replaces the sample's pool label, so the sample will
+/// never return to the original pool. Take care not to form cycles,
+/// or samples may bounce between full pools until they expire.
+///
+/// [`PoolSize`]: super::PoolSize
+/// [`NoStealing`]: super::NoStealing
+/// [`SampleQueueLifetime`]: crate::sample::SampleQueueLifetime
+#[derive(Debug, Component, Clone)]
+#[component(immutable, on_insert = Self::on_insert_hook, on_remove = Self::on_remove_hook)]
+pub struct OverflowTo<T: PoolLabel + Component + Clone> {
+    /// The fallback pool's label.
+    pub pool: T,
+    /// The minimum priority a sample must have to overflow.
+    ///
+    /// Defaults to [`SamplePriority(0)`][SamplePriority].
+    pub min_priority: SamplePriority,
+}
+
+impl<T: PoolLabel + Component + Clone> OverflowTo<T> {
+    /// Overflow into `pool`.
+    pub fn new(pool: T) -> Self {
+        Self {
+            pool,
+            min_priority: SamplePriority(0),
+        }
+    }
+
+    /// Set the minimum priority a sample must have to overflow.
+    pub fn with_min_priority(self, min_priority: SamplePriority) -> Self {
+        Self {
+            min_priority,
+            ..self
+        }
+    }
+
+    fn on_insert_hook(mut world: DeferredWorld, context: HookContext) {
+        let Some(value) = world.get::<Self>(context.entity) else {
+            return;
+        };
+
+        let pool = value.pool.clone();
+        let overflow = PoolOverflow {
+            min_priority: value.min_priority,
+            relabel: Arc::new(move |commands: &mut EntityCommands| {
+                commands.insert(pool.clone());
+            }),
+        };
+
+        world.commands().entity(context.entity).insert(overflow);
+    }
+
+    fn on_remove_hook(mut world: DeferredWorld, context: HookContext) {
+        world
+            .commands()
+            .entity(context.entity)
+            .try_remove::<PoolOverflow>();
+    }
+}
+
+/// The type-erased counterpart to [`OverflowTo`].
+#[derive(Component, Clone)]
+pub(super) struct PoolOverflow {
+    pub(super) min_priority: SamplePriority,
+    relabel: Arc<dyn Fn(&mut EntityCommands) + Send + Sync>,
+}
+
+impl core::fmt::Debug for PoolOverflow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PoolOverflow")
+            .field("min_priority", &self.min_priority)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PoolOverflow {
+    /// Move a sample into the fallback pool.
+    pub(super) fn relabel(&self, commands: &mut EntityCommands) {
+        (self.relabel)(commands);
+    }
+}
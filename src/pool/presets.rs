@@ -0,0 +1,84 @@
+//! Named [`SamplerConfig`] presets.
+//!
+//! Most of [`SamplerConfig`]'s defaults are reasonable, but the number
+//! of declickers makes a noticeable difference depending on what a pool is used for.
+//!
+//! - `num_declickers` is the number of fades the sampler can run at once
+//!   when a sample is stopped, restarted, or replaced. Each declicker
+//!   briefly keeps the old playhead alive, so samples retriggered in rapid
+//!   succession need more of them to avoid clicks. More declickers allocate
+//!   more memory and cost more to process. The default is two.
+//! - `speed_quality` selects the resampling algorithm used for playback
+//!   speed changes. [`PlaybackSpeedQuality::LinearFast`] is currently the
+//!   only option, so every preset uses it.
+//!
+//! The presets keep the default stereo `channels`.
+//!
+//! Presets are selected per pool by inserting them alongside the [`SamplerPool`].
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+//! struct FootstepPool;
+//!
+//! #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+//! struct MusicPool;
+//!
+//! fn spawn_pools(mut commands: Commands) {
+//!     commands.spawn((SamplerPool(FootstepPool), SamplerConfig::low_latency()));
+//!     commands.spawn((SamplerPool(MusicPool), SamplerConfig::high_quality()));
+//! }
+//! ```
+//!
+//! [`SamplerPool`]: super::SamplerPool
+//! [`PlaybackSpeedQuality::LinearFast`]: firewheel::nodes::sampler::PlaybackSpeedQuality::LinearFast
+
+use firewheel::nodes::sampler::SamplerConfig;
+
+/// Named presets for [`SamplerConfig`].
+///
+/// See the [module docs][self] for the knobs each preset adjusts.
+pub trait SamplerPresets: Sized {
+    /// A preset for short, interactive sound effects.
+    ///
+    /// Each sampler has a single declicker, keeping voices as cheap
+    /// as possible for large pools of one-shot sounds.
+    fn low_latency() -> Self;
+
+    /// A preset for music and long ambiences.
+    ///
+    /// Each sampler has four declickers, so restarting or replacing
+    /// a long sample fades out cleanly even if it happens a few times in a row.
+    fn high_quality() -> Self;
+
+    /// A preset for pools that frequently restart or seek
+    /// their samples, like rapidly retriggered loops.
+    ///
+    /// Each sampler has eight declickers, so even very rapid
+    /// restarts are faded rather than cut off.
+    fn crossfade_heavy() -> Self;
+}
+
+impl SamplerPresets for SamplerConfig {
+    fn low_latency() -> Self {
+        Self {
+            num_declickers: 1,
+            ..Default::default()
+        }
+    }
+
+    fn high_quality() -> Self {
+        Self {
+            num_declickers: 4,
+            ..Default::default()
+        }
+    }
+
+    fn crossfade_heavy() -> Self {
+        Self {
+            num_declickers: 8,
+            ..Default::default()
+        }
+    }
+}
@@ -1,20 +1,33 @@
 use super::{
-    PlaybackCompletionEvent, PoolSamplerOf, PoolSamplers, PoolShape, PoolSize, SamplerOf,
+    NoStealing, PlaybackCompletionEvent, PoolFullEvent, PoolPaused, PoolSamplerOf, PoolSamplers,
+    PoolShape, PoolSize, SamplerOf, StealingPolicy,
+    custom::{ManualAssignment, PoolScoring, SamplerCandidate},
+    growth::{
+        DefaultPoolGrowth, DefaultPoolShrink, GrowthPressure, IdleTime, PoolGrowth,
+        PoolGrowthRequest, PoolShrink, WarmPool,
+    },
+    overflow::PoolOverflow,
+    resume::HeldSamplers,
     sample_effects::{EffectOf, SampleEffects},
+    stats::PoolStats,
 };
 use crate::{
     node::{AudioState, EffectId, follower::FollowerOf},
     pool::label::PoolLabelContainer,
     prelude::DefaultPool,
     sample::{AudioSample, QueuedSample, SamplePlayer, SamplePriority, SampleQueueLifetime},
+    spatial::{SpatialListener2D, SpatialListener3D},
 };
 use bevy_asset::prelude::*;
 use bevy_ecs::{entity::EntityCloner, prelude::*, relationship::Relationship};
 use bevy_log::prelude::*;
+use bevy_math::Vec3;
 use bevy_platform::collections::HashMap;
 use bevy_time::{Stopwatch, Time};
+use bevy_transform::prelude::*;
+use core::time::Duration;
 use firewheel::nodes::sampler::{RepeatMode, SamplerConfig, SamplerNode, SamplerState};
-use std::ops::Deref;
+use std::sync::Mutex;
 
 #[derive(PartialEq, Debug, Eq, PartialOrd, Ord, Copy, Clone)]
 struct SamplerScore {
@@ -38,16 +51,23 @@ impl Default for SamplerScore {
 /// Eagerly grow pools to handle over-allocation when possible.
 pub(super) fn grow_pools(
     queued_samples: Query<(&SamplePlayer, &PoolLabelContainer), With<QueuedSample>>,
-    pools: Query<(
-        Entity,
-        &PoolLabelContainer,
-        &PoolSamplers,
-        &PoolSize,
-        Option<&SampleEffects>,
-        &SamplerConfig,
-    )>,
+    mut pools: Query<
+        (
+            Entity,
+            &PoolLabelContainer,
+            &PoolSamplers,
+            &PoolSize,
+            &PoolShape,
+            &SamplerConfig,
+            Option<&PoolGrowth>,
+            &mut GrowthPressure,
+        ),
+        Without<PoolPaused>,
+    >,
     nodes: Query<Option<&SamplerOf>, With<PoolSamplerOf>>,
+    held: Res<HeldSamplers>,
     assets: Res<Assets<AudioSample>>,
+    default_growth: Res<DefaultPoolGrowth>,
     mut commands: Commands,
 ) -> Result {
     let queued_samples: HashMap<_, usize> = queued_samples
@@ -58,56 +78,71 @@ pub(super) fn grow_pools(
             acc
         });
 
-    if queued_samples.is_empty() {
-        return Ok(());
-    }
-
-    for (pool_entity, label, samplers, size, pool_effects, pool_config) in pools {
-        let Some(queued_samples) = queued_samples.get(&label.label).copied() else {
-            continue;
-        };
+    for (pool_entity, label, samplers, size, shape, pool_config, growth, mut pressure) in
+        pools.iter_mut()
+    {
+        let queued_samples = queued_samples.get(&label.label).copied().unwrap_or(0);
 
         let inactive_samplers = nodes
-            .iter_many(samplers.iter())
+            .iter_many(samplers.iter().filter(|s| !held.holds(*s)))
             .filter(|n| n.is_none())
             .count();
 
         if inactive_samplers >= queued_samples {
+            if pressure.0 != 0 {
+                pressure.0 = 0;
+            }
             continue;
         }
 
         let difference = queued_samples - inactive_samplers;
+        pressure.0 = pressure.0.saturating_add(1);
 
         // attempt to grow pool if possible
         if samplers.len() < *size.0.end() {
-            let growth_size = difference.max(samplers.len().min(16));
+            let policy = growth.map(|g| &g.0).unwrap_or(&default_growth.0);
+
+            let growth_size = policy.growth(&PoolGrowthRequest {
+                size: samplers.len(),
+                max_size: *size.0.end(),
+                deficit: difference,
+                frames_in_deficit: pressure.0,
+            });
+
+            if growth_size == 0 {
+                continue;
+            }
+
             let new_size = (samplers.len() + growth_size).min(*size.0.end());
 
-            #[cfg(debug_assertions)]
-            commands.queue({
-                let id = label.label_id;
-                let num_samplers = samplers.len();
-                move |world: &mut World| {
-                    let component = world.components().get_descriptor(id);
-
-                    if let Some(component) = component {
-                        let s = if new_size != 1 { "s" } else { "" };
-                        debug!(
-                            "growing {} from {} to {} sampler{s} ({} over-allocated)",
-                            component.name(),
-                            num_samplers,
-                            new_size,
-                            difference,
-                        );
+            if policy.log_growth() {
+                commands.queue({
+                    let id = label.label_id;
+                    let num_samplers = samplers.len();
+                    move |world: &mut World| {
+                        let component = world.components().get_descriptor(id);
+
+                        if let Some(component) = component {
+                            let s = if new_size != 1 { "s" } else { "" };
+                            debug!(
+                                "growing {} from {} to {} sampler{s} ({} over-allocated)",
+                                component.name(),
+                                num_samplers,
+                                new_size,
+                                difference,
+                            );
+                        }
                     }
-                }
-            });
+                });
+            }
+
+            pressure.0 = 0;
 
             for _ in samplers.len()..new_size {
                 super::spawn_chain(
                     pool_entity,
                     Some(pool_config.clone()),
-                    pool_effects.map(|e| e.deref()).unwrap_or(&[]),
+                    &shape.effects,
                     &mut commands,
                 );
             }
@@ -117,10 +152,161 @@ pub(super) fn grow_pools(
     Ok(())
 }
 
+/// Shrink idle pools toward their minimum size.
+pub(super) fn shrink_pools(
+    queued_samples: Query<&PoolLabelContainer, (With<SamplePlayer>, With<QueuedSample>)>,
+    mut pools: Query<(
+        &PoolLabelContainer,
+        &PoolSamplers,
+        &PoolSize,
+        Option<&PoolShrink>,
+        &mut IdleTime,
+    )>,
+    nodes: Query<Option<&SamplerOf>, With<PoolSamplerOf>>,
+    held: Res<HeldSamplers>,
+    default_shrink: Res<DefaultPoolShrink>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (label, samplers, size, shrink, mut idle) in pools.iter_mut() {
+        let Some(timeout) = shrink.map(|s| s.0).or(default_shrink.0) else {
+            continue;
+        };
+
+        let minimum = (*size.0.start()).max(1);
+        if samplers.len() <= minimum || queued_samples.iter().any(|q| q.label == label.label) {
+            if idle.0 != Duration::ZERO {
+                idle.0 = Duration::ZERO;
+            }
+            continue;
+        }
+
+        idle.0 += time.delta();
+        if idle.0 < timeout {
+            continue;
+        }
+
+        // The most recently spawned samplers are removed first.
+        let excess = samplers.len() - minimum;
+        let removed: Vec<_> = samplers
+            .iter()
+            .rev()
+            .filter(|s| !held.holds(*s) && nodes.get(*s).is_ok_and(|n| n.is_none()))
+            .take(excess)
+            .collect();
+
+        if removed.is_empty() {
+            continue;
+        }
+
+        debug!(
+            "shrinking pool from {} to {} samplers after {:?} idle",
+            samplers.len(),
+            samplers.len() - removed.len(),
+            idle.0,
+        );
+
+        for sampler in removed {
+            commands.entity(sampler).despawn();
+        }
+
+        idle.0 = Duration::ZERO;
+    }
+}
+
+/// Grow pools with [`WarmPool`] to their requested size.
+pub(super) fn warm_pools(
+    pools: Query<(
+        Entity,
+        &WarmPool,
+        &PoolSamplers,
+        &PoolSize,
+        &PoolShape,
+        &SamplerConfig,
+    )>,
+    mut commands: Commands,
+) {
+    for (pool_entity, warm, samplers, size, shape, pool_config) in &pools {
+        let target = warm.0.min(*size.0.end());
+
+        for _ in samplers.len()..target {
+            super::spawn_chain(
+                pool_entity,
+                Some(pool_config.clone()),
+                &shape.effects,
+                &mut commands,
+            );
+        }
+
+        commands.entity(pool_entity).remove::<WarmPool>();
+    }
+}
+
+type QueuedItem<'a> = (
+    Entity,
+    &'a SamplePlayer,
+    &'a AudioSample,
+    Option<&'a SampleEffects>,
+    &'a SamplePriority,
+);
+
+type PoolItem<'a> = (
+    Entity,
+    &'a PoolLabelContainer,
+    &'a PoolSamplers,
+    &'a PoolSize,
+    &'a PoolShape,
+    bool,
+    Option<&'a PoolOverflow>,
+    Option<&'a PoolScoring>,
+    Option<&'a StealingPolicy>,
+);
+
+type NodeItem<'a> = (
+    Entity,
+    &'a SamplerNode,
+    &'a AudioState<SamplerState>,
+    Option<&'a SamplerOf>,
+);
+
+/// A single pool's sampler assignments.
+///
+/// Pools don't share samplers or samples, so plans
+/// can be computed independently.
+struct PoolPlan<'a> {
+    pool: Entity,
+    label: &'a PoolLabelContainer,
+    pool_shape: &'a PoolShape,
+    /// Each queued sample, its new sampler, and the
+    /// sample it's replacing, if any.
+    assignments: Vec<(QueuedItem<'a>, Entity, Option<Entity>)>,
+    /// Samples that can't be assigned in a full [`NoStealing`] pool.
+    rejected: Vec<Entity>,
+    /// Samples that can't be assigned in a full pool and
+    /// should move to the pool's [`PoolOverflow`].
+    overflow: Vec<Entity>,
+    overflow_to: Option<&'a PoolOverflow>,
+    #[cfg(debug_assertions)]
+    summary: PlanSummary,
+}
+
+#[cfg(debug_assertions)]
+struct PlanSummary {
+    label_id: bevy_ecs::component::ComponentId,
+    queued: usize,
+    inactive: usize,
+    total: usize,
+    size: core::ops::RangeInclusive<usize>,
+}
+
 /// Scan through the set of pending sample players
 /// and assign work to the most appropriate sampler node.
+///
+/// Scoring and sorting are performed for each pool in parallel,
+/// while the resulting assignments are applied serially.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn assign_work(
-    mut queued_samples: Query<
+    queued_samples: Query<
         (
             Entity,
             &SamplePlayer,
@@ -130,29 +316,48 @@ pub(super) fn assign_work(
         ),
         With<QueuedSample>,
     >,
-    pools: Query<(
-        &PoolLabelContainer,
-        &PoolSamplers,
-        &PoolSize,
-        &PoolShape,
-        Option<&SampleEffects>,
-    )>,
-    mut nodes: Query<
+    pools: Query<
         (
             Entity,
-            &mut SamplerNode,
-            &AudioState<SamplerState>,
-            Option<&SamplerOf>,
+            &PoolLabelContainer,
+            &PoolSamplers,
+            &PoolSize,
+            &PoolShape,
+            Has<NoStealing>,
+            Option<&PoolOverflow>,
+            Option<&PoolScoring>,
+            Option<&StealingPolicy>,
         ),
-        With<PoolSamplerOf>,
+        (Without<ManualAssignment>, Without<PoolPaused>),
     >,
-    active_samples: Query<(&SamplePlayer, &SamplePriority)>,
+    mut nodes: ParamSet<(
+        Query<NodeItem, With<PoolSamplerOf>>,
+        Query<
+            (
+                Entity,
+                &mut SamplerNode,
+                &AudioState<SamplerState>,
+                Option<&SamplerOf>,
+            ),
+            With<PoolSamplerOf>,
+        >,
+    )>,
+    active_samples: Query<(&SamplePlayer, &SamplePriority, Option<&GlobalTransform>)>,
+    listeners: Query<&GlobalTransform, Or<(With<SpatialListener2D>, With<SpatialListener3D>)>>,
     mut effects: Query<&EffectId, With<EffectOf>>,
+    mut stats: Query<&mut PoolStats>,
+    held: Res<HeldSamplers>,
     assets: Res<Assets<AudioSample>>,
     mut commands: Commands,
 ) -> Result {
-    let mut queued_samples: HashMap<_, Vec<_>> = queued_samples
-        .iter_mut()
+    for mut stats in &mut stats {
+        if stats.steals != 0 {
+            stats.steals = 0;
+        }
+    }
+
+    let queued_samples: HashMap<_, Vec<_>> = queued_samples
+        .iter()
         .filter_map(|(entity, player, label, effects, priority)| {
             let asset = assets.get(&player.sample)?;
 
@@ -167,195 +372,160 @@ pub(super) fn assign_work(
         return Ok(());
     }
 
-    for (label, samplers, size, pool_shape, pool_effects) in pools {
-        // To suppress warnings when debug assertions are disabled, as `size` is only used in the debug-only `commands.queue` call below.
-        #[cfg(not(debug_assertions))]
-        let _size = size;
+    let listeners: Vec<_> = listeners.iter().map(|t| t.translation()).collect();
 
-        let Some(mut queued_samples) = queued_samples.remove(&label.label) else {
-            continue;
-        };
+    let plans = Mutex::new(Vec::new());
+    {
+        let readonly_nodes = nodes.p0();
+        pools.par_iter().for_each(|pool| {
+            let Some(queued) = queued_samples.get(&pool.1.label) else {
+                return;
+            };
 
-        // if there is enough sampler availability in the pool,
-        // don't bother sorting samples by priority
+            let plan = plan_pool(
+                pool,
+                queued.clone(),
+                &readonly_nodes,
+                &active_samples,
+                &listeners,
+                &held,
+            );
+            plans.lock().unwrap().push(plan);
+        });
+    }
 
-        let inactive_samplers: Vec<_> = samplers
-            .iter()
-            .filter(|s| nodes.get(*s).is_ok_and(|n| n.3.is_none()))
-            .collect();
+    // Applying the plans in a consistent order keeps
+    // command application deterministic.
+    let mut plans = plans.into_inner().unwrap();
+    plans.sort_unstable_by_key(|plan| plan.pool);
 
+    let mut nodes = nodes.p1();
+    for plan in plans {
         #[cfg(debug_assertions)]
         commands.queue({
-            let inactive = inactive_samplers.len();
-            let queued_len = queued_samples.len();
-            let total_samplers = samplers.len();
-            let size = size.clone();
-            let id = label.label_id;
+            let PlanSummary {
+                label_id,
+                queued,
+                inactive,
+                total,
+                size,
+            } = plan.summary;
+
             move |world: &mut World| {
-                let component = world.components().get_descriptor(id);
+                let component = world.components().get_descriptor(label_id);
 
                 if let Some(component) = component {
-                    let s = if queued_len != 1 { "s" } else { "" };
+                    let s = if queued != 1 { "s" } else { "" };
                     debug!(
-                        "queued {queued_len} sample{s} in {} ({} total, {inactive} inactive, {:?})",
+                        "queued {queued} sample{s} in {} ({total} total, {inactive} inactive, {size:?})",
                         component.name(),
-                        total_samplers,
-                        size.0
                     );
                 }
             }
         });
 
-        if inactive_samplers.len() >= queued_samples.len() {
-            let mut inactive = inactive_samplers.iter();
-
-            for (sample_entity, player, asset, sample_effects, _priority) in queued_samples {
-                let (sampler_entity, mut params, state, _) =
-                    nodes.get_mut(*inactive.next().unwrap())?;
-
-                params.sample = Some(asset.get());
-                params.volume = player.volume;
-                params.repeat_mode = player.repeat_mode;
-                state.0.clear_finished();
-
-                // normalize sample effects
-                if sample_effects.is_some() && pool_effects.is_none() {
-                    match player.sample.path() {
-                        Some(path) => warn!(
-                            "Queued sample \"{}\" with effects in an effect-less pool.",
-                            path
-                        ),
-                        None => warn!("Queued sample with effects in an effect-less pool."),
-                    }
-                }
+        if let Some(overflow_to) = plan.overflow_to {
+            for sample_entity in plan.overflow {
+                debug!("overflowing sample {sample_entity:?} from a full pool");
 
-                if let Some(pool_effects) = pool_effects {
-                    match sample_effects {
-                        Some(sample_effects) => {
-                            let component_ids = match super::fetch_effect_ids(
-                                sample_effects,
-                                &mut effects.as_query_lens(),
-                            ) {
-                                Ok(ids) => ids,
-                                Err(e) => {
-                                    error!("{e}");
-
-                                    continue;
-                                }
-                            };
-
-                            if component_ids != pool_shape.0 {
-                                // N will never be large enough for this to be a concern
-                                if component_ids.iter().any(|id| !pool_shape.0.contains(id)) {
-                                    match player.sample.path() {
-                                        Some(path) => warn!(
-                                            "Queued sample \"{}\" contains one or more effects that the pool does not.",
-                                            path
-                                        ),
-                                        None => warn!(
-                                            "Queued sample contains one or more effects that the pool does not."
-                                        ),
-                                    }
-                                }
-
-                                let mut new_effects = Vec::new();
-                                new_effects.reserve_exact(pool_shape.0.len());
-                                let mut clone_into = Vec::new();
-
-                                for (effect, id) in pool_effects.iter().zip(&pool_shape.0) {
-                                    match component_ids.iter().position(|c| c == id) {
-                                        Some(index) => {
-                                            new_effects.push(sample_effects[index]);
-                                        }
-                                        None => {
-                                            let empty = commands.spawn_empty().id();
-
-                                            clone_into.push((empty, effect));
-                                            new_effects.push(empty);
-                                        }
-                                    }
-                                }
-
-                                commands
-                                    .entity(sample_entity)
-                                    .remove_related::<EffectOf>(sample_effects)
-                                    .add_related::<EffectOf>(&new_effects);
-
-                                commands.queue(move |world: &mut World| {
-                                    let mut cloner = EntityCloner::build_opt_out(world);
-                                    cloner.deny::<EffectOf>();
-                                    let mut cloner = cloner.finish();
-
-                                    for (dest, src) in clone_into {
-                                        cloner.clone_entity(world, src, dest);
-                                    }
-                                });
-                            }
-                        }
-                        None => {
-                            let pool_effects: Vec<_> = pool_effects.iter().collect();
-                            commands.queue(move |world: &mut World| {
-                                let mut cloner = EntityCloner::build_opt_out(world);
-                                cloner.deny::<EffectOf>();
-                                let mut cloner = cloner.finish();
-
-                                let mut sample_effects = Vec::new();
-                                sample_effects.reserve_exact(pool_effects.len());
-                                for effect in pool_effects {
-                                    let sample_effect = cloner.spawn_clone(world, effect);
-                                    sample_effects.push(sample_effect);
-                                }
-
-                                world
-                                    .entity_mut(sample_entity)
-                                    .add_related::<EffectOf>(&sample_effects);
-                            });
-                        }
-                    }
-                }
-
-                commands
-                    .entity(sample_entity)
-                    .remove::<QueuedSample>()
-                    .add_one_related::<SamplerOf>(sampler_entity);
+                let mut sample = commands.entity(sample_entity);
+                sample.remove_by_id(plan.label.label_id);
+                overflow_to.relabel(&mut sample);
             }
+        }
 
-            continue;
+        if let Ok(mut stats) = stats.get_mut(plan.pool) {
+            let steals = plan
+                .assignments
+                .iter()
+                .filter(|assignment| assignment.2.is_some())
+                .count();
+
+            if steals != 0 {
+                stats.steals = steals;
+            }
+            if !plan.rejected.is_empty() {
+                stats.skips += plan.rejected.len();
+            }
         }
 
-        // otherwise, sort the available samplers
-        let mut sampler_scores = Vec::new();
-        for (sampler_entity, params, state, assignment) in nodes.iter_many(samplers.iter()) {
-            let raw_score = state.0.worker_score(params);
-            let has_assignment = assignment.is_some();
-
-            let active_data = assignment.and_then(|a| {
-                active_samples
-                    .get(a.0)
-                    .map(|s| (s.0.repeat_mode, *s.1))
-                    .ok()
-            });
+        for sample_entity in plan.rejected {
+            warn!("sample {sample_entity:?} could not be assigned in a full pool");
 
-            let (is_looping, priority) = match active_data {
-                Some((repeat, priority)) => (repeat != RepeatMode::PlayOnce, priority),
-                None => (false, SamplePriority(0)),
-            };
+            commands.trigger(PoolFullEvent(sample_entity));
+            super::complete_playback(sample_entity, &mut commands);
+        }
 
-            sampler_scores.push((
+        for (queued, sampler_entity, current_assignment) in plan.assignments {
+            assign_sampler(
+                queued,
                 sampler_entity,
-                assignment.map(|s| s.0),
-                SamplerScore {
-                    priority,
-                    raw_score,
-                    has_assignment,
-                    is_looping,
-                },
-            ));
+                plan.pool_shape,
+                &mut nodes,
+                &mut effects,
+                current_assignment,
+                &mut commands,
+            )?;
         }
+    }
+
+    Ok(())
+}
+
+/// Score a pool's samplers and pair them with its queued samples.
+fn plan_pool<'a>(
+    pool_item: PoolItem<'a>,
+    mut queued_samples: Vec<QueuedItem<'a>>,
+    nodes: &Query<NodeItem, With<PoolSamplerOf>>,
+    active_samples: &Query<(&SamplePlayer, &SamplePriority, Option<&GlobalTransform>)>,
+    listeners: &[Vec3],
+    held: &HeldSamplers,
+) -> PoolPlan<'a> {
+    let (pool, label, samplers, size, pool_shape, no_stealing, overflow_to, scoring, stealing) =
+        pool_item;
+    let no_stealing = no_stealing || stealing == Some(&StealingPolicy::Never);
+
+    let mut plan = PoolPlan {
+        pool,
+        label,
+        pool_shape,
+        assignments: Vec::new(),
+        rejected: Vec::new(),
+        overflow: Vec::new(),
+        overflow_to,
+        #[cfg(debug_assertions)]
+        summary: PlanSummary {
+            label_id: label.label_id,
+            queued: queued_samples.len(),
+            inactive: 0,
+            total: samplers.len(),
+            size: size.0.clone(),
+        },
+    };
+
+    // if there is enough sampler availability in the pool,
+    // don't bother sorting samples by priority
+
+    // Held samplers are reserved for resuming loops.
+    let available = samplers.iter().filter(|s| !held.holds(*s));
+
+    let inactive_samplers: Vec<_> = available
+        .clone()
+        .filter(|s| nodes.get(*s).is_ok_and(|n| n.3.is_none()))
+        .collect();
+
+    #[cfg(debug_assertions)]
+    {
+        plan.summary.inactive = inactive_samplers.len();
+    }
 
-        sampler_scores.sort_by_key(|pair| pair.2);
+    let is_full = samplers.len() >= *size.0.end();
+    let can_overflow = |priority: &SamplePriority| {
+        is_full && overflow_to.is_some_and(|o| *priority >= o.min_priority)
+    };
 
-        // then sort the queued samples
+    // Pools that forbid stealing only ever assign inactive samplers.
+    if no_stealing && inactive_samplers.len() < queued_samples.len() {
         queued_samples.sort_by_key(|s| {
             (
                 core::cmp::Reverse(s.4),
@@ -363,137 +533,288 @@ pub(super) fn assign_work(
             )
         });
 
-        for ((sampler_entity, current_assignment, sampler_score), queued) in
-            sampler_scores.into_iter().zip(queued_samples.into_iter())
-        {
-            let (sample_entity, player, asset, sample_effects, priority) = queued;
+        let overflow = queued_samples.split_off(inactive_samplers.len());
 
-            // Due to the sorting, if any queued sample has a lower priority then a currently playing sample,
-            // then every subsequent sample must also have a lower priority than its corresponding player.
-            if &sampler_score.priority > priority {
-                break;
+        // If the pool can still grow, the remaining samples
+        // can simply wait for the new samplers.
+        if is_full {
+            for sample in overflow {
+                if can_overflow(sample.4) {
+                    plan.overflow.push(sample.0);
+                } else {
+                    plan.rejected.push(sample.0);
+                }
             }
+        }
+    }
 
-            // We'll also skip over samples that won't loop
-            // when the occupied sampler is currently looping.
-            if sampler_score.is_looping && player.repeat_mode == RepeatMode::PlayOnce {
-                continue;
+    if inactive_samplers.len() >= queued_samples.len() {
+        plan.assignments = queued_samples
+            .into_iter()
+            .zip(inactive_samplers)
+            .map(|(queued, sampler)| (queued, sampler, None))
+            .collect();
+
+        return plan;
+    }
+
+    // otherwise, sort the available samplers
+    let mut sampler_scores = Vec::new();
+    for (sampler_entity, params, state, assignment) in nodes.iter_many(available) {
+        let worker_score = state.0.worker_score(params);
+        let has_assignment = assignment.is_some();
+
+        let active_data = assignment.and_then(|a| {
+            active_samples
+                .get(a.0)
+                .map(|s| (s.0.repeat_mode, *s.1, s.2))
+                .ok()
+        });
+
+        let (is_looping, priority, transform) = match active_data {
+            Some((repeat, priority, transform)) => {
+                (repeat != RepeatMode::PlayOnce, priority, transform)
             }
+            None => (false, SamplePriority(0), None),
+        };
 
-            let (sampler_entity, mut params, state, _) = nodes.get_mut(sampler_entity)?;
-
-            params.sample = Some(asset.get());
-            params.volume = player.volume;
-            params.repeat_mode = player.repeat_mode;
-            state.0.clear_finished();
-
-            // normalize sample effects
-            if sample_effects.is_some() && pool_effects.is_none() {
-                match player.sample.path() {
-                    Some(path) => warn!(
-                        "Queued sample \"{}\" with effects in an effect-less pool.",
-                        path
-                    ),
-                    None => warn!("Queued sample with effects in an effect-less pool."),
-                }
+        let raw_score = match (scoring, stealing) {
+            (Some(scoring), _) => scoring.0.score(&SamplerCandidate {
+                sampler: sampler_entity,
+                assignment: assignment.map(|a| a.0),
+                priority,
+                is_looping,
+                worker_score,
+            }),
+            (None, Some(StealingPolicy::Oldest)) if has_assignment => {
+                u64::MAX - state.0.playhead_frames().0.max(0) as u64
             }
+            (None, Some(StealingPolicy::Quietest)) if has_assignment => {
+                params.volume.linear().max(0.0).to_bits() as u64
+            }
+            (None, Some(StealingPolicy::Furthest)) if has_assignment => {
+                let distance = transform
+                    .and_then(|transform| {
+                        listeners
+                            .iter()
+                            .map(|listener| listener.distance(transform.translation()))
+                            .min_by(f32::total_cmp)
+                    })
+                    .unwrap_or(0.0);
+
+                u64::MAX - distance.max(0.0).to_bits() as u64
+            }
+            // Firewheel's score is higher for better candidates,
+            // whereas lower scores are reassigned first here.
+            _ => u64::MAX - worker_score,
+        };
 
-            if let Some(pool_effects) = pool_effects {
-                match sample_effects {
-                    Some(sample_effects) => {
-                        let component_ids = match super::fetch_effect_ids(
-                            sample_effects,
-                            &mut effects.as_query_lens(),
-                        ) {
-                            Ok(ids) => ids,
-                            Err(e) => {
-                                error!("{e}");
-
-                                continue;
-                            }
-                        };
-
-                        if component_ids != pool_shape.0 {
-                            // N will never be large enough for this to be a concern
-                            if component_ids.iter().any(|id| !pool_shape.0.contains(id)) {
-                                match player.sample.path() {
-                                    Some(path) => warn!(
-                                        "Queued sample \"{}\" contains one or more effects that the pool does not.",
-                                        path
-                                    ),
-                                    None => warn!(
-                                        "Queued sample contains one or more effects that the pool does not."
-                                    ),
-                                }
-                            }
+        sampler_scores.push((
+            sampler_entity,
+            assignment.map(|s| s.0),
+            SamplerScore {
+                priority,
+                raw_score,
+                has_assignment,
+                is_looping,
+            },
+        ));
+    }
 
-                            let mut new_effects = Vec::new();
-                            new_effects.reserve_exact(pool_shape.0.len());
-                            let mut clone_into = Vec::new();
-
-                            for (effect, id) in pool_effects.iter().zip(&pool_shape.0) {
-                                match component_ids.iter().position(|c| c == id) {
-                                    Some(index) => {
-                                        new_effects.push(sample_effects[index]);
-                                    }
-                                    None => {
-                                        let empty = commands.spawn_empty().id();
-
-                                        clone_into.push((empty, effect));
-                                        new_effects.push(empty);
-                                    }
-                                }
-                            }
+    sampler_scores.sort_by_key(|pair| pair.2);
+
+    // then sort the queued samples
+    queued_samples.sort_by_key(|s| {
+        (
+            core::cmp::Reverse(s.4),
+            s.1.repeat_mode == RepeatMode::PlayOnce,
+        )
+    });
+
+    for ((sampler_entity, current_assignment, sampler_score), &queued) in
+        sampler_scores.into_iter().zip(&queued_samples)
+    {
+        let (_, player, _, _, priority) = queued;
+
+        // Due to the sorting, if any queued sample has a lower priority then a currently playing sample,
+        // then every subsequent sample must also have a lower priority than its corresponding player.
+        if &sampler_score.priority > priority {
+            break;
+        }
 
-                            commands
-                                .entity(sample_entity)
-                                .remove_related::<EffectOf>(sample_effects)
-                                .add_related::<EffectOf>(&new_effects);
+        // We'll also skip over samples that won't loop
+        // when the occupied sampler is currently looping.
+        if sampler_score.is_looping && player.repeat_mode == RepeatMode::PlayOnce {
+            continue;
+        }
+
+        plan.assignments
+            .push((queued, sampler_entity, current_assignment));
+    }
 
-                            commands.queue(move |world: &mut World| {
-                                let mut cloner = EntityCloner::build_opt_out(world);
-                                cloner.deny::<EffectOf>();
-                                let mut cloner = cloner.finish();
+    // Anything left over would otherwise wait out its queue lifetime.
+    if overflow_to.is_some() {
+        plan.overflow = queued_samples
+            .iter()
+            .filter(|queued| {
+                can_overflow(queued.4) && !plan.assignments.iter().any(|a| a.0.0 == queued.0)
+            })
+            .map(|queued| queued.0)
+            .collect();
+    }
 
-                                for (dest, src) in clone_into {
-                                    cloner.clone_entity(world, src, dest);
-                                }
-                            });
+    plan
+}
+
+/// Assign a queued sample to a sampler, normalizing its effects
+/// to match the pool's.
+pub(super) fn assign_sampler(
+    (sample_entity, player, asset, sample_effects, _priority): QueuedItem,
+    sampler_entity: Entity,
+    pool_shape: &PoolShape,
+    nodes: &mut Query<
+        (
+            Entity,
+            &mut SamplerNode,
+            &AudioState<SamplerState>,
+            Option<&SamplerOf>,
+        ),
+        With<PoolSamplerOf>,
+    >,
+    effects: &mut Query<&EffectId, With<EffectOf>>,
+    current_assignment: Option<Entity>,
+    commands: &mut Commands,
+) -> Result {
+    let (sampler_entity, mut params, state, _) = nodes.get_mut(sampler_entity)?;
+
+    params.sample = Some(asset.get());
+    params.volume = player.volume;
+    params.repeat_mode = player.repeat_mode;
+    state.0.clear_finished();
+
+    // normalize sample effects
+    if sample_effects.is_some() && !pool_shape.has_effects() {
+        match player.sample.path() {
+            Some(path) => warn!(
+                "Queued sample \"{}\" with effects in an effect-less pool.",
+                path
+            ),
+            None => warn!("Queued sample with effects in an effect-less pool."),
+        }
+    }
+
+    if pool_shape.has_effects() {
+        match sample_effects {
+            Some(sample_effects) => {
+                let component_ids =
+                    match super::fetch_effect_ids(sample_effects, &mut effects.as_query_lens()) {
+                        Ok(ids) => ids,
+                        Err(e) => {
+                            error!("{e}");
+
+                            return Ok(());
+                        }
+                    };
+
+                if component_ids != pool_shape.ids {
+                    // N will never be large enough for this to be a concern
+                    if component_ids
+                        .iter()
+                        .any(|id| !pool_shape.ids.contains(id) && !pool_shape.skipped.contains(id))
+                    {
+                        match player.sample.path() {
+                            Some(path) => warn!(
+                                "Queued sample \"{}\" contains one or more effects that the pool does not.",
+                                path
+                            ),
+                            None => warn!(
+                                "Queued sample contains one or more effects that the pool does not."
+                            ),
                         }
                     }
-                    None => {
-                        let pool_effects: Vec<_> = pool_effects.iter().collect();
-                        commands.queue(move |world: &mut World| {
-                            let mut cloner = EntityCloner::build_opt_out(world);
-                            cloner.deny::<EffectOf>();
-                            let mut cloner = cloner.finish();
-
-                            let mut sample_effects = Vec::new();
-                            sample_effects.reserve_exact(pool_effects.len());
-                            for effect in pool_effects {
-                                let sample_effect = cloner.spawn_clone(world, effect);
-                                sample_effects.push(sample_effect);
+
+                    let mut new_effects = Vec::new();
+                    new_effects.reserve_exact(pool_shape.ids.len());
+                    let mut clone_into = Vec::new();
+
+                    for (&effect, id) in pool_shape.effects.iter().zip(&pool_shape.ids) {
+                        match component_ids.iter().position(|c| c == id) {
+                            Some(index) => {
+                                new_effects.push(sample_effects[index]);
                             }
+                            None => {
+                                let empty = commands.spawn_empty().id();
 
-                            world
-                                .entity_mut(sample_entity)
-                                .add_related::<EffectOf>(&sample_effects);
-                        });
+                                clone_into.push((empty, effect));
+                                new_effects.push(empty);
+                            }
+                        }
                     }
+
+                    commands
+                        .entity(sample_entity)
+                        .remove_related::<EffectOf>(sample_effects)
+                        .add_related::<EffectOf>(&new_effects);
+
+                    // Effects skipped for the current quality tier
+                    // would otherwise linger as standalone nodes.
+                    for (effect, id) in sample_effects.iter().zip(&component_ids) {
+                        if pool_shape.skipped.contains(id) && !pool_shape.ids.contains(id) {
+                            commands.entity(effect).despawn();
+                        }
+                    }
+
+                    commands.queue(move |world: &mut World| {
+                        let mut cloner = EntityCloner::build_opt_out(world);
+                        cloner.deny::<EffectOf>();
+                        let mut cloner = cloner.finish();
+
+                        for (dest, src) in clone_into {
+                            cloner.clone_entity(world, src, dest);
+                        }
+                    });
                 }
             }
+            None => {
+                let pool_effects = pool_shape.effects.clone();
+                commands.queue(move |world: &mut World| {
+                    let mut cloner = EntityCloner::build_opt_out(world);
+                    cloner.deny::<EffectOf>();
+                    let mut cloner = cloner.finish();
+
+                    let mut sample_effects = Vec::new();
+                    sample_effects.reserve_exact(pool_effects.len());
+                    for effect in pool_effects {
+                        let sample_effect = cloner.spawn_clone(world, effect);
+                        sample_effects.push(sample_effect);
+                    }
 
-            commands
-                .entity(sample_entity)
-                .remove::<QueuedSample>()
-                .add_one_related::<SamplerOf>(sampler_entity);
-
-            if let Some(assignment) = current_assignment {
-                commands.trigger(PlaybackCompletionEvent(assignment));
+                    world
+                        .entity_mut(sample_entity)
+                        .add_related::<EffectOf>(&sample_effects);
+                });
             }
         }
     }
 
+    commands.queue(move |world: &mut World| -> Result {
+        // The previous player's details must be
+        // gathered before it loses its sampler.
+        let completion =
+            current_assignment.map(|player| PlaybackCompletionEvent::gather(world, player));
+
+        world
+            .get_entity_mut(sample_entity)?
+            .remove::<QueuedSample>()
+            .add_one_related::<SamplerOf>(sampler_entity);
+
+        if let Some(completion) = completion {
+            world.trigger(completion);
+        }
+
+        Ok(())
+    });
+
     Ok(())
 }
 
@@ -530,19 +851,37 @@ pub(super) fn mark_skipped(
 
 pub(super) fn tick_skipped(
     mut samples: Query<
-        (Entity, &mut SkipTimer, &SampleQueueLifetime),
+        (
+            Entity,
+            &mut SkipTimer,
+            &SampleQueueLifetime,
+            Option<&PoolLabelContainer>,
+        ),
         (With<SamplePlayer>, With<QueuedSample>),
     >,
+    mut pools: Query<(&PoolLabelContainer, &mut PoolStats)>,
+    paused: Query<&PoolLabelContainer, With<PoolPaused>>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
     let delta = time.delta();
 
-    for (sample_entity, mut timer, lifetime) in &mut samples {
+    for (sample_entity, mut timer, lifetime, label) in &mut samples {
+        // Samples queued in paused pools wait indefinitely.
+        if label.is_some_and(|label| paused.iter().any(|p| p.label == label.label)) {
+            continue;
+        }
+
         if timer.0.tick(delta).elapsed() >= lifetime.0 {
             debug!("skipping sample {:?} after {:?}", sample_entity, lifetime.0,);
 
-            commands.trigger(PlaybackCompletionEvent(sample_entity));
+            if let Some(label) = label {
+                for (_, mut stats) in pools.iter_mut().filter(|(l, _)| l.label == label.label) {
+                    stats.skips += 1;
+                }
+            }
+
+            super::complete_playback(sample_entity, &mut commands);
         }
     }
 }
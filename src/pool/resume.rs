@@ -0,0 +1,237 @@
+//! Resuming briefly interrupted loops.
+
+use super::{PoolCommands, PoolSamplerOf, Sampler, SamplerOf, label::InternedPoolLabel};
+use crate::{
+    pool::label::PoolLabelContainer,
+    sample::{AudioSample, PlaybackSettings, QueuedSample, SamplePlayer},
+};
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use core::time::Duration;
+use firewheel::nodes::sampler::{PlaybackState, Playhead, RepeatMode};
+
+/// Hold a looping sample's sampler for a short time after it stops.
+///
+/// When a looping ambience is stopped and immediately respawned, like
+/// when the player flickers across a zone boundary, it would normally
+/// lose its sampler and restart from the beginning. With [`ResumeWindow`],
+/// the sampler and playhead are held in reserve after the sample stops.
+/// A matching sample queued within the window is assigned the same sampler
+/// and resumes where the previous one left off.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use core::time::Duration;
+/// #[derive(Component)]
+/// struct Forest;
+///
+/// fn enter_forest(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         Forest,
+///         SamplePlayer::new(server.load("forest.ogg")).looping(),
+///         ResumeWindow(Duration::from_millis(500)),
+///     ));
+/// }
+///
+/// fn leave_forest(forest: Query<Entity, With<Forest>>, mut commands: Commands) {
+///     for entity in &forest {
+///         commands.entity(entity).despawn();
+///     }
+/// }
+/// ```
+///
+/// A sample matches if it plays the same sample in the same pool and also
+/// has a [`ResumeWindow`]. Samples that play once are never held.
+///
+/// While held, the sampler won't be assigned or stolen by other samples.
+#[derive(Debug, Component, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ResumeWindow(pub Duration);
+
+#[derive(Debug)]
+struct HeldSampler {
+    sample: AssetId<AudioSample>,
+    pool: InternedPoolLabel,
+    sampler: Entity,
+    playhead: f64,
+    expires: Duration,
+}
+
+/// Samplers held by recently stopped [`ResumeWindow`] samples.
+#[derive(Debug, Default, Resource)]
+pub(super) struct HeldSamplers(Vec<HeldSampler>);
+
+impl HeldSamplers {
+    /// Returns whether `sampler` is reserved.
+    pub(super) fn holds(&self, sampler: Entity) -> bool {
+        self.0.iter().any(|h| h.sampler == sampler)
+    }
+}
+
+/// Reserve a stopped loop's sampler.
+pub(super) fn hold_sampler(
+    trigger: On<Remove, Sampler>,
+    players: Query<(&SamplePlayer, &PoolLabelContainer, &ResumeWindow, &Sampler)>,
+    samplers: Query<&SamplerOf>,
+    time: Res<Time>,
+    mut held: ResMut<HeldSamplers>,
+) {
+    let player_entity = trigger.event_target();
+    let Ok((player, label, window, sampler)) = players.get(player_entity) else {
+        return;
+    };
+
+    if player.repeat_mode == RepeatMode::PlayOnce {
+        return;
+    }
+
+    // A stolen sampler already belongs to another sample.
+    let sampler_entity = sampler.sampler();
+    if samplers
+        .get(sampler_entity)
+        .is_ok_and(|s| s.0 != player_entity)
+    {
+        return;
+    }
+
+    let playhead = sampler
+        .try_playhead_seconds()
+        .map(|p| p.0)
+        .unwrap_or_default();
+
+    held.0.retain(|h| h.sampler != sampler_entity);
+    held.0.push(HeldSampler {
+        sample: player.sample.id(),
+        pool: label.label,
+        sampler: sampler_entity,
+        playhead,
+        expires: time.elapsed() + window.0,
+    });
+}
+
+/// Release expired samplers and resume matching samples.
+pub(super) fn resume_held(
+    mut queued: Query<
+        (
+            Entity,
+            &SamplePlayer,
+            &PoolLabelContainer,
+            &mut PlaybackSettings,
+        ),
+        (With<QueuedSample>, With<ResumeWindow>),
+    >,
+    samplers: Query<Has<SamplerOf>, With<PoolSamplerOf>>,
+    assets: Res<Assets<AudioSample>>,
+    time: Res<Time>,
+    mut held: ResMut<HeldSamplers>,
+    mut commands: Commands,
+) {
+    if held.0.is_empty() {
+        return;
+    }
+
+    // Samplers that have been despawned or manually
+    // assigned are no longer available to resume.
+    let now = time.elapsed();
+    held.0
+        .retain(|h| h.expires > now && samplers.get(h.sampler).is_ok_and(|busy| !busy));
+
+    for (entity, player, label, mut settings) in &mut queued {
+        if player.repeat_mode == RepeatMode::PlayOnce || !assets.contains(player.sample.id()) {
+            continue;
+        }
+
+        let Some(index) = held
+            .0
+            .iter()
+            .position(|h| h.sample == player.sample.id() && h.pool == label.label)
+        else {
+            continue;
+        };
+
+        let HeldSampler {
+            sampler, playhead, ..
+        } = held.0.swap_remove(index);
+
+        *settings.playback = PlaybackState::Play {
+            playhead: Some(Playhead::Seconds(playhead)),
+        };
+        commands.assign_sample(entity, sampler);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        edge::AudioGraphOutput,
+        prelude::*,
+        test::{prepare_app, run, run_until},
+    };
+    use bevy::prelude::*;
+
+    #[derive(Component)]
+    struct Ambience;
+
+    #[derive(Component)]
+    struct Other;
+
+    fn looping(server: &AssetServer) -> SamplePlayer {
+        SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping()
+    }
+
+    fn wait_for_sampler<T: Component>(app: &mut App) -> Entity {
+        run_until(app, |player: Query<(), (With<Sampler>, With<T>)>| {
+            player.single().is_ok()
+        });
+
+        run(app, |player: Single<&Sampler, With<T>>| player.sampler())
+    }
+
+    #[test]
+    fn test_resume_window() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands
+                .spawn(SamplerPool(DefaultPool))
+                .connect(AudioGraphOutput);
+
+            commands.spawn((
+                Ambience,
+                looping(&server),
+                ResumeWindow(Duration::from_secs(10)),
+            ));
+        });
+
+        let held = wait_for_sampler::<Ambience>(&mut app);
+
+        run(
+            &mut app,
+            |ambience: Single<Entity, With<Ambience>>,
+             server: Res<AssetServer>,
+             mut commands: Commands| {
+                commands.entity(*ambience).despawn();
+                commands.spawn((Other, looping(&server)));
+            },
+        );
+
+        // The held sampler isn't available to other samples.
+        let other = wait_for_sampler::<Other>(&mut app);
+        assert_ne!(other, held);
+
+        run(
+            &mut app,
+            |server: Res<AssetServer>, mut commands: Commands| {
+                commands.spawn((
+                    Ambience,
+                    looping(&server),
+                    ResumeWindow(Duration::from_secs(10)),
+                ));
+            },
+        );
+
+        let resumed = wait_for_sampler::<Ambience>(&mut app);
+        assert_eq!(resumed, held);
+    }
+}
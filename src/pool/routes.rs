@@ -0,0 +1,120 @@
+//! Direct connections from sample players.
+
+use super::{Sampler, SamplerChainTail};
+use crate::{
+    edge::{Connect, DEFAULT_CONNECTION, Disconnect, PendingConnections, PendingEdge},
+    node::FirewheelNode,
+    prelude::SamplePlayer,
+};
+use bevy_ecs::prelude::*;
+
+/// Connections made from a [`SamplePlayer`] entity.
+///
+/// Sample players aren't audio nodes themselves, so their
+/// connections are held here and applied to the tail of whichever
+/// sampler chain they're assigned to. When the assignment changes
+/// or ends, the connections follow.
+#[derive(Debug, Default, Component)]
+pub(super) struct PlayerRoutes {
+    edges: Vec<PendingEdge>,
+    /// The chain tail the edges are currently applied to.
+    tail: Option<Entity>,
+}
+
+fn connect_edges(tail: Entity, edges: &[PendingEdge], commands: &mut Commands) {
+    let Ok(mut tail) = commands.get_entity(tail) else {
+        return;
+    };
+
+    for edge in edges {
+        tail.reborrow().connect_with(
+            edge.target.clone(),
+            edge.ports.as_deref().unwrap_or(DEFAULT_CONNECTION),
+        );
+    }
+}
+
+fn disconnect_edges(tail: Entity, edges: &[PendingEdge], commands: &mut Commands) {
+    let Ok(mut tail) = commands.get_entity(tail) else {
+        return;
+    };
+
+    for edge in edges {
+        tail.reborrow().disconnect_with(
+            edge.target.clone(),
+            edge.ports.as_deref().unwrap_or(DEFAULT_CONNECTION),
+        );
+    }
+}
+
+/// Move sample player connections onto their assigned sampler chains.
+pub(super) fn route_sample_players(
+    mut players: Query<
+        (
+            Entity,
+            &mut PendingConnections,
+            Option<&Sampler>,
+            Option<&mut PlayerRoutes>,
+        ),
+        (With<SamplePlayer>, Without<FirewheelNode>),
+    >,
+    tails: Query<&SamplerChainTail>,
+    mut commands: Commands,
+) {
+    for (player, mut pending, sampler, routes) in &mut players {
+        let new_edges = if pending.is_empty() {
+            Vec::new()
+        } else {
+            pending.drain()
+        };
+        let current = sampler.and_then(|s| tails.get(s.sampler()).ok().map(|t| t.0));
+
+        let Some(mut routes) = routes else {
+            if new_edges.is_empty() {
+                continue;
+            }
+
+            if let Some(tail) = current {
+                connect_edges(tail, &new_edges, &mut commands);
+            }
+
+            commands.entity(player).insert(PlayerRoutes {
+                edges: new_edges,
+                tail: current,
+            });
+            continue;
+        };
+
+        if routes.tail != current {
+            if let Some(previous) = routes.tail {
+                disconnect_edges(previous, &routes.edges, &mut commands);
+            }
+            if let Some(tail) = current {
+                connect_edges(tail, &routes.edges, &mut commands);
+            }
+            routes.tail = current;
+        }
+
+        if !new_edges.is_empty() {
+            if let Some(tail) = current {
+                connect_edges(tail, &new_edges, &mut commands);
+            }
+            routes.edges.extend(new_edges);
+        }
+    }
+}
+
+/// Remove a player's connections when it's despawned.
+pub(super) fn unroute_removed_players(
+    trigger: On<Remove, PlayerRoutes>,
+    routes: Query<&PlayerRoutes>,
+    mut commands: Commands,
+) {
+    let Ok(routes) = routes.get(trigger.event_target()) else {
+        return;
+    };
+
+    if let Some(tail) = routes.tail {
+        disconnect_edges(tail, &routes.edges, &mut commands);
+    }
+}
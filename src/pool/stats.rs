@@ -0,0 +1,74 @@
+//! Pool usage statistics.
+
+use super::{PoolSamplerOf, PoolSamplers, SamplerOf, label::PoolLabelContainer};
+use crate::sample::{QueuedSample, SamplePlayer};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+
+/// Usage statistics for a [`SamplerPool`][super::SamplerPool].
+///
+/// This is inserted on every pool and updated in
+/// [`SeedlingSystems::Pool`][crate::SeedlingSystems::Pool], making
+/// it easy to build debug overlays or tune a pool's [`PoolSize`][super::PoolSize]
+/// with real data.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{
+/// #     pool::{PoolSamplers, stats::PoolStats},
+/// #     prelude::*,
+/// # };
+/// fn report(pools: Query<(&PoolStats, &PoolSamplers), With<SamplerPool<DefaultPool>>>) {
+///     for (stats, samplers) in &pools {
+///         info!(
+///             "{}/{} voices ({} peak), {} queued, {} stolen",
+///             stats.active,
+///             samplers.samplers().len(),
+///             stats.peak,
+///             stats.queued,
+///             stats.steals,
+///         );
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PoolStats {
+    /// The number of samplers currently playing a sample.
+    pub active: usize,
+    /// The number of samples waiting for a sampler.
+    pub queued: usize,
+    /// The number of playing samples interrupted this frame
+    /// to make room for new ones.
+    pub steals: usize,
+    /// The total number of samples that were never played, either
+    /// because they expired in the queue or were rejected by a full pool.
+    pub skips: usize,
+    /// The highest number of samplers that have played at once.
+    pub peak: usize,
+}
+
+/// Count the active and queued samples in each pool.
+pub(super) fn update_pool_stats(
+    mut pools: Query<(&PoolLabelContainer, &PoolSamplers, &mut PoolStats)>,
+    samplers: Query<(), (With<PoolSamplerOf>, With<SamplerOf>)>,
+    queued: Query<&PoolLabelContainer, (With<SamplePlayer>, With<QueuedSample>)>,
+) {
+    let queued: HashMap<_, usize> = queued.iter().fold(HashMap::new(), |mut acc, label| {
+        *acc.entry(label.label).or_default() += 1;
+        acc
+    });
+
+    for (label, pool_samplers, mut stats) in &mut pools {
+        let active = samplers.iter_many(pool_samplers.iter()).count();
+        let queued = queued.get(&label.label).copied().unwrap_or(0);
+
+        let peak = stats.peak.max(active);
+        stats.set_if_neq(PoolStats {
+            active,
+            queued,
+            peak,
+            ..stats.clone()
+        });
+    }
+}
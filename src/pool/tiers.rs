@@ -0,0 +1,207 @@
+//! Scaling pool effects across devices.
+//!
+//! Effects like HRTF spatialization or reverb sends can be too expensive
+//! for low-end devices. Rather than defining separate pools for each
+//! device class, a pool's effects can be marked with [`SkipBelow`],
+//! leaving them out of the pool's sampler chains whenever the
+//! [`AudioQualityTier`] resource is lower.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::prelude::*;
+//! #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+//! struct WorldPool;
+//!
+//! fn spawn_pool(mut commands: Commands) {
+//!     commands.spawn((
+//!         SamplerPool(WorldPool),
+//!         sample_effects![
+//!             // Spatialization is skipped only on the lowest tier...
+//!             (SpatialBasicNode::default(), SkipBelow(AudioQualityTier::Medium)),
+//!             // ...while per-sample reverb is reserved for capable devices.
+//!             (FreeverbNode::default(), SkipBelow(AudioQualityTier::High)),
+//!         ],
+//!     ));
+//! }
+//!
+//! fn detect_device(mut commands: Commands) {
+//!     # let is_low_end = true;
+//!     if is_low_end {
+//!         commands.insert_resource(AudioQualityTier::Low);
+//!     }
+//! }
+//! ```
+//!
+//! The tier is read when a pool is populated. Changing it afterwards
+//! rebuilds the chains of every affected pool. Samples that were playing
+//! are queued again and resume from their current playhead.
+//!
+//! Samples in the pool may still be spawned with skipped effects.
+//! These are despawned when the sample is assigned a sampler, so
+//! any changes made to them are lost.
+
+use super::{
+    PoolSamplers, PoolShape, Sampler, SamplerOf, custom::ManualAssignment, queue::SkipTimer,
+    sample_effects::SampleEffects,
+};
+use crate::{
+    node::EffectId,
+    sample::{PlaybackSettings, QueuedSample},
+};
+use bevy_ecs::prelude::*;
+use firewheel::nodes::sampler::{PlaybackState, Playhead, SamplerConfig};
+
+/// The quality tier of sampler pool effects.
+///
+/// Tiers are ordered from the cheapest to the most expensive.
+/// See the [module docs][self] for details.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Resource)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub enum AudioQualityTier {
+    /// Only essential effects are included.
+    Low,
+    /// Expensive effects are left out.
+    Medium,
+    /// Every effect is included.
+    #[default]
+    High,
+}
+
+/// Leave a pool effect out of its sampler chains when
+/// the [`AudioQualityTier`] is lower than this tier.
+///
+/// See the [module docs][self] for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SkipBelow(pub AudioQualityTier);
+
+/// Split a pool's effects into those included at `tier` and those skipped.
+pub(super) fn partition_effects(
+    effects: &[Entity],
+    tier: AudioQualityTier,
+    skip: &Query<&SkipBelow>,
+) -> (Vec<Entity>, Vec<Entity>) {
+    effects
+        .iter()
+        .copied()
+        .partition(|effect| skip.get(*effect).ok().is_none_or(|s| tier >= s.0))
+}
+
+/// Rebuild sampler chains whose effects change with the tier.
+pub(super) fn rebuild_tiered_pools(
+    tier: Res<AudioQualityTier>,
+    mut pools: Query<
+        (
+            Entity,
+            &SampleEffects,
+            &mut PoolShape,
+            &PoolSamplers,
+            &SamplerConfig,
+        ),
+        Without<ManualAssignment>,
+    >,
+    samplers: Query<&SamplerOf>,
+    mut players: Query<(&Sampler, &mut PlaybackSettings)>,
+    skip: Query<&SkipBelow>,
+    mut effect_ids: Query<&EffectId>,
+    mut commands: Commands,
+) -> Result {
+    for (pool, effects, mut shape, pool_samplers, config) in &mut pools {
+        let new_shape = PoolShape::new(effects, *tier, &skip, &mut effect_ids.as_query_lens())?;
+
+        if new_shape.effects == shape.effects {
+            continue;
+        }
+
+        for sampler in pool_samplers.iter() {
+            let assignment = samplers.get(sampler).ok().map(|s| s.0);
+
+            if let Some((player, (assigned, mut settings))) =
+                assignment.and_then(|p| Some((p, players.get_mut(p).ok()?)))
+            {
+                if matches!(*settings.playback, PlaybackState::Play { .. }) {
+                    let playhead = assigned
+                        .try_playhead_seconds()
+                        .map(|p| p.0)
+                        .unwrap_or_default();
+
+                    *settings.playback = PlaybackState::Play {
+                        playhead: Some(Playhead::Seconds(playhead)),
+                    };
+                }
+
+                commands
+                    .entity(player)
+                    .remove::<SkipTimer>()
+                    .insert(QueuedSample);
+            }
+
+            commands.entity(sampler).despawn();
+        }
+
+        for _ in 0..pool_samplers.len() {
+            super::spawn_chain(
+                pool,
+                Some(config.clone()),
+                &new_shape.effects,
+                &mut commands,
+            );
+        }
+
+        *shape = new_shape;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        edge::AudioGraphOutput,
+        prelude::*,
+        sample_effects,
+        test::{prepare_app, run},
+    };
+
+    #[test]
+    fn test_tier_switch() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.insert_resource(AudioQualityTier::Low);
+
+            commands
+                .spawn((
+                    SamplerPool(DefaultPool),
+                    PoolSize(2..=2),
+                    sample_effects![
+                        VolumeNode::default(),
+                        (LowPassNode::default(), SkipBelow(AudioQualityTier::High)),
+                    ],
+                ))
+                .connect(AudioGraphOutput);
+        });
+
+        run(
+            &mut app,
+            |samplers: Query<(), With<SamplerNode>>,
+             low_pass: Query<(), (With<LowPassNode>, Without<EffectOf>)>| {
+                assert_eq!(samplers.iter().len(), 2);
+                assert_eq!(low_pass.iter().len(), 0);
+            },
+        );
+
+        run(&mut app, |mut commands: Commands| {
+            commands.insert_resource(AudioQualityTier::High);
+        });
+        app.update();
+
+        run(
+            &mut app,
+            |samplers: Query<(), With<SamplerNode>>,
+             low_pass: Query<(), (With<LowPassNode>, Without<EffectOf>)>| {
+                assert_eq!(samplers.iter().len(), 2);
+                assert_eq!(low_pass.iter().len(), 2);
+            },
+        );
+    }
+}
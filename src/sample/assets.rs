@@ -1,7 +1,18 @@
+use super::{
+    loop_points::{LoopPoints, SampleTags, read_loop_points},
+    markers::{SampleMarker, read_markers},
+    pcm::PcmSample,
+    stream::StreamedSample,
+};
 use bevy_asset::{Asset, AssetLoader};
+use bevy_log::prelude::*;
 use bevy_reflect::TypePath;
 use firewheel::{collector::ArcGc, sample_resource::SampleResource};
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    sync::Arc,
+};
 
 /// A type-erased audio sample.
 ///
@@ -11,18 +22,225 @@ use std::sync::Arc;
 ///
 /// The available containers and formats can be configured with
 /// this crate's feature flags.
+///
+/// Loop points and markers stored in the file's metadata are available
+/// through [`AudioSample::loop_points`] and [`AudioSample::markers`].
 #[derive(Asset, TypePath, Clone)]
-pub struct AudioSample(ArcGc<dyn SampleResource>);
+pub struct AudioSample {
+    resource: ArcGc<dyn SampleResource>,
+    loop_points: Option<LoopPoints>,
+    markers: Vec<SampleMarker>,
+    /// The loop that playheads past its end wrap into,
+    /// for mapping them back onto the markers' frames.
+    marker_loop: Option<(LoopPoints, Option<u64>)>,
+    /// The rate of frames that still need to be brought to the engine's.
+    sample_rate: Option<NonZeroU32>,
+    /// Whether the sample is decoded as it plays.
+    streamed: bool,
+}
 
 impl AudioSample {
     /// Create a new [`AudioSample`] from a [`SampleResource`] loaded into memory.
     pub fn new<S: SampleResource>(sample: S) -> Self {
-        Self(ArcGc::new_unsized(|| Arc::new(sample) as _))
+        Self {
+            resource: ArcGc::new_unsized(|| Arc::new(sample) as _),
+            loop_points: None,
+            markers: Vec::new(),
+            marker_loop: None,
+            sample_rate: None,
+            streamed: false,
+        }
+    }
+
+    /// Create a new [`AudioSample`] from a shared [`SampleResource`].
+    ///
+    /// This allows custom sources, like procedurally generated buffers,
+    /// to be played without an asset loader. Once added to the asset
+    /// storage, they're played like any other sample, participating
+    /// in pools and effects.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::{prelude::*, firewheel::sample_resource::SampleResource};
+    /// # use std::sync::Arc;
+    /// fn play_procedural(
+    ///     mut commands: Commands,
+    ///     server: Res<AssetServer>,
+    ///     # resource: Res<MyResource>,
+    /// ) {
+    ///     # let resource = resource.0.clone();
+    ///     let sample = AudioSample::from_resource(resource);
+    ///
+    ///     commands.spawn((
+    ///         SamplePlayer::new(server.add(sample)),
+    ///         sample_effects![LowPassNode::default()],
+    ///     ));
+    /// }
+    /// # #[derive(Resource)]
+    /// # struct MyResource(Arc<dyn SampleResource>);
+    /// ```
+    pub fn from_resource(resource: Arc<dyn SampleResource>) -> Self {
+        Self {
+            resource: ArcGc::new_unsized(|| resource),
+            loop_points: None,
+            markers: Vec::new(),
+            marker_loop: None,
+            sample_rate: None,
+            streamed: false,
+        }
+    }
+
+    /// Create a new [`AudioSample`] from interleaved PCM frames.
+    ///
+    /// This lets games synthesize buffers at runtime, like procedural
+    /// footsteps or decoded network voice, and play them through
+    /// [`SamplePlayer`][crate::prelude::SamplePlayer] like any other sample.
+    /// Frames recorded at a different rate than the audio engine's are
+    /// resampled once the sample is added to [`Assets<AudioSample>`].
+    ///
+    /// A trailing partial frame is dropped.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// # use std::num::{NonZeroU32, NonZeroUsize};
+    /// fn play_noise(mut commands: Commands, server: Res<AssetServer>) {
+    ///     // Half a second of stereo noise at 44.1 kHz.
+    ///     let frames = (0..44100).map(|i| ((i * 7919) % 201) as f32 / 100.0 - 1.0).collect();
+    ///     let channels = NonZeroUsize::new(2).unwrap();
+    ///     let rate = NonZeroU32::new(44100).unwrap();
+    ///     let sample = AudioSample::from_frames(frames, channels, rate);
+    ///
+    ///     commands.spawn(SamplePlayer::new(server.add(sample)));
+    /// }
+    /// ```
+    pub fn from_frames(frames: Vec<f32>, channels: NonZeroUsize, sample_rate: NonZeroU32) -> Self {
+        Self {
+            sample_rate: Some(sample_rate),
+            ..Self::new(PcmSample::from_interleaved(&frames, channels))
+        }
+    }
+
+    /// Resample frames built at another rate to `target`.
+    ///
+    /// Returns `None` if the sample needs no conversion.
+    pub(super) fn resampled(&self, target: NonZeroU32) -> Option<Self> {
+        let rate = self.sample_rate.filter(|rate| *rate != target)?;
+        let ratio = rate.get() as f64 / target.get() as f64;
+
+        // Loop points and markers follow the frames they refer to.
+        let scale = |frame: u64| (frame as f64 / ratio).round() as u64;
+
+        Some(Self {
+            resource: ArcGc::new_unsized(|| {
+                Arc::new(PcmSample::resampled(&*self.resource, ratio)) as _
+            }),
+            loop_points: self.loop_points.map(|points| LoopPoints {
+                start: scale(points.start),
+                end: scale(points.end),
+            }),
+            markers: self
+                .markers
+                .iter()
+                .map(|marker| SampleMarker {
+                    frame: scale(marker.frame),
+                    ..marker.clone()
+                })
+                .collect(),
+            marker_loop: None,
+            sample_rate: Some(target),
+            streamed: false,
+        })
+    }
+
+    /// An estimate of the memory held by the decoded sample, in bytes.
+    ///
+    /// Streamed samples only hold their encoded data, so they report zero.
+    pub fn memory_size(&self) -> usize {
+        if self.streamed {
+            return 0;
+        }
+
+        self.resource.len_frames() as usize * self.resource.num_channels().get() * size_of::<f32>()
+    }
+
+    /// Average every channel into one.
+    fn into_mono(self) -> Self {
+        if self.resource.num_channels().get() == 1 {
+            return self;
+        }
+
+        Self {
+            resource: ArcGc::new_unsized(|| Arc::new(PcmSample::downmixed(&*self.resource)) as _),
+            ..self
+        }
     }
 
     /// Share the inner value.
     pub fn get(&self) -> ArcGc<dyn SampleResource> {
-        self.0.clone()
+        self.resource.clone()
+    }
+
+    /// The sample's loop region, if any.
+    ///
+    /// See [`LoopPoints`] for details.
+    pub fn loop_points(&self) -> Option<LoopPoints> {
+        self.loop_points
+    }
+
+    /// Set the sample's loop region.
+    pub fn with_loop_points(mut self, loop_points: LoopPoints) -> Self {
+        self.loop_points = Some(loop_points);
+        self
+    }
+
+    /// The sample's markers, ordered by frame.
+    ///
+    /// See [`SampleMarker`] for details.
+    pub fn markers(&self) -> &[SampleMarker] {
+        &self.markers
+    }
+
+    /// Set the sample's markers.
+    pub fn with_markers(mut self, markers: impl IntoIterator<Item = SampleMarker>) -> Self {
+        self.markers = markers.into_iter().collect();
+        self.markers.sort_by_key(|marker| marker.frame);
+        self
+    }
+
+    /// Map playheads past `points.end` back into the loop
+    /// when checking for markers.
+    pub(super) fn with_marker_loop(mut self, points: LoopPoints, repeats: Option<u64>) -> Self {
+        self.marker_loop = Some((points, repeats));
+        self
+    }
+
+    /// The loop region playheads wrap into, if any.
+    pub(super) fn marker_loop(&self) -> Option<LoopPoints> {
+        self.marker_loop.map(|(points, _)| points)
+    }
+
+    /// Map a playhead to the frame its markers refer to.
+    pub(super) fn marker_frame(&self, playhead: u64) -> u64 {
+        let Some((points, repeats)) = self.marker_loop else {
+            return playhead;
+        };
+        if playhead < points.end {
+            return playhead;
+        }
+
+        let body = points.end - points.start;
+        let past = playhead - points.end;
+        match repeats {
+            Some(repeats) if past / body >= repeats => points.end + (past - repeats * body),
+            _ => points.start + past % body,
+        }
+    }
+}
+
+impl From<Arc<dyn SampleResource>> for AudioSample {
+    fn from(value: Arc<dyn SampleResource>) -> Self {
+        Self::from_resource(value)
     }
 }
 
@@ -33,10 +251,121 @@ impl core::fmt::Debug for AudioSample {
 }
 
 /// A simple loader for audio samples.
+///
+/// The loader only depends on the bytes provided by the asset's
+/// [`Reader`][bevy_asset::io::Reader], so samples can be loaded
+/// from any registered asset source, including archives like zip
+/// or pak files.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_packed(mut commands: Commands, server: Res<AssetServer>) {
+///     // Assuming a `pak` asset source has been registered.
+///     commands.spawn(SamplePlayer::new(server.load("pak://music/theme.ogg")));
+/// }
+/// ```
+///
+/// The format is detected from the entry's extension when available,
+/// and from its contents otherwise.
+///
+/// Long samples like music can be streamed rather than decoded
+/// in full by enabling [`SampleLoaderSettings::stream`].
 #[derive(Debug)]
 pub struct SampleLoader {
     /// The sampling rate of the audio engine.
     pub(crate) sample_rate: crate::context::SampleRate,
+    /// The asset server, from which streamed samples are read as they play.
+    pub(crate) server: bevy_asset::AssetServer,
+}
+
+/// Settings for the [`SampleLoader`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, sample::SampleLoaderSettings};
+/// fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+///     let track = server.load_with_settings(
+///         "music/ten_minute_track.ogg",
+///         |settings: &mut SampleLoaderSettings| settings.stream = true,
+///     );
+///
+///     commands.spawn(SamplePlayer::new(track).looping());
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SampleLoaderSettings {
+    /// Decode the sample in chunks as it plays rather than all at once.
+    ///
+    /// Streamed samples read their encoded data from the asset source as
+    /// they play, decoding only a few seconds around the playhead on a
+    /// background thread.
+    /// This greatly reduces the memory used by long music and ambience
+    /// tracks, at the cost of a small amount of background work.
+    /// When the engine's sample rate differs from the file's, streamed
+    /// samples are resampled with simple linear interpolation.
+    ///
+    /// Samples whose length can't be determined up front are decoded
+    /// in full, as are all samples on platforms without threads.
+    ///
+    /// Defaults to `false`.
+    pub stream: bool,
+
+    /// Downmix the sample to a single channel as it loads.
+    ///
+    /// Spatialized sound effects are heard from a single point anyway,
+    /// so storing them in stereo only wastes memory. Streamed samples
+    /// keep their original channels.
+    ///
+    /// Defaults to `false`.
+    pub mono: bool,
+
+    /// The quality of the resampler used when the file's sample
+    /// rate differs from the audio engine's.
+    ///
+    /// This applies to fully decoded samples; streamed and Opus
+    /// samples are always resampled with linear interpolation.
+    ///
+    /// Defaults to [`ResampleQuality::High`].
+    pub resample_quality: ResampleQuality,
+}
+
+/// The quality of the resampler used by the [`SampleLoader`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, sample::{ResampleQuality, SampleLoaderSettings}};
+/// fn load_footsteps(mut commands: Commands, server: Res<AssetServer>) {
+///     let step = server.load_with_settings(
+///         "sfx/footstep.wav",
+///         |settings: &mut SampleLoaderSettings| {
+///             settings.mono = true;
+///             settings.resample_quality = ResampleQuality::Low;
+///         },
+///     );
+///
+///     commands.spawn(SamplePlayer::new(step));
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ResampleQuality {
+    /// Fast, lower quality resampling with linear interpolation.
+    ///
+    /// This is worth considering for short sound effects
+    /// that are loaded on demand.
+    Low,
+    /// Slower, high quality resampling.
+    #[default]
+    High,
+}
+
+impl From<ResampleQuality> for symphonium::ResampleQuality {
+    fn from(value: ResampleQuality) -> Self {
+        match value {
+            ResampleQuality::Low => Self::Low,
+            ResampleQuality::High => Self::High,
+        }
+    }
 }
 
 /// Errors produced while loading samples.
@@ -46,6 +375,9 @@ pub enum SampleLoaderError {
     StdIo(std::io::Error),
     /// An error directly from `symphonium`.
     Symphonium(String),
+    /// An error while decoding Opus streams.
+    #[cfg(feature = "opus")]
+    Opus(String),
 }
 
 impl From<std::io::Error> for SampleLoaderError {
@@ -60,6 +392,13 @@ impl From<symphonium::error::LoadError> for SampleLoaderError {
     }
 }
 
+#[cfg(feature = "opus")]
+impl From<audiopus::Error> for SampleLoaderError {
+    fn from(value: audiopus::Error) -> Self {
+        Self::Opus(value.to_string())
+    }
+}
+
 impl std::error::Error for SampleLoaderError {}
 
 impl std::fmt::Display for SampleLoaderError {
@@ -67,6 +406,8 @@ impl std::fmt::Display for SampleLoaderError {
         match self {
             Self::StdIo(stdio) => stdio.fmt(f),
             Self::Symphonium(sy) => f.write_str(sy),
+            #[cfg(feature = "opus")]
+            Self::Opus(opus) => f.write_str(opus),
         }
     }
 }
@@ -76,8 +417,10 @@ impl SampleLoader {
         &[
             #[cfg(feature = "wav")]
             "wav",
-            #[cfg(feature = "ogg")]
+            #[cfg(any(feature = "ogg", feature = "opus"))]
             "ogg",
+            #[cfg(feature = "opus")]
+            "opus",
             #[cfg(feature = "mp3")]
             "mp3",
             #[cfg(feature = "flac")]
@@ -90,20 +433,69 @@ impl SampleLoader {
 
 impl AssetLoader for SampleLoader {
     type Asset = AudioSample;
-    type Settings = ();
+    type Settings = SampleLoaderSettings;
     type Error = SampleLoaderError;
 
     async fn load(
         &self,
         reader: &mut dyn bevy_asset::io::Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut bevy_asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
+        let bytes: Arc<[u8]> = bytes.into();
 
+        // Archive readers may expose paths with arbitrary prefixes,
+        // so only the final extension is used as a hint.
         let mut hint = symphonia::core::probe::Hint::new();
-        hint.with_extension(&load_context.path().to_string_lossy());
+        if let Some(extension) = load_context.path().extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let tags = SampleTags::read(&bytes, &hint);
+        let loop_points = read_loop_points(&bytes, tags.as_ref(), self.sample_rate.get());
+        let markers = read_markers(&bytes, tags.as_ref(), self.sample_rate.get());
+        let with_metadata = |sample: AudioSample| AudioSample {
+            loop_points,
+            markers: markers.clone(),
+            ..sample
+        };
+        let finish = |sample: AudioSample| {
+            with_metadata(if settings.mono {
+                sample.into_mono()
+            } else {
+                sample
+            })
+        };
+
+        if settings.stream && cfg!(not(target_arch = "wasm32")) {
+            match StreamedSample::new(
+                bytes.clone(),
+                self.server.clone(),
+                load_context.asset_path().clone(),
+                &hint,
+                self.sample_rate.get(),
+            ) {
+                Some(sample) => {
+                    return Ok(with_metadata(AudioSample {
+                        streamed: true,
+                        ..AudioSample::new(sample)
+                    }));
+                }
+                None => debug!(
+                    "\"{}\" can't be streamed, so it will be decoded in full",
+                    load_context.path().display()
+                ),
+            }
+        }
+
+        #[cfg(feature = "opus")]
+        if let Some(sample) =
+            super::opus::OpusSample::decode(&bytes, &hint, self.sample_rate.get())?
+        {
+            return Ok(finish(AudioSample::new(sample)));
+        }
 
         let mut loader = symphonium::SymphoniumLoader::new();
         let source = firewheel::load_audio_file_from_source(
@@ -111,15 +503,70 @@ impl AssetLoader for SampleLoader {
             Box::new(std::io::Cursor::new(bytes)),
             Some(hint),
             self.sample_rate.get(),
-            Default::default(),
+            settings.resample_quality.into(),
         )?;
 
-        Ok(AudioSample(ArcGc::new_unsized(|| {
-            Arc::new(source) as Arc<dyn SampleResource>
-        })))
+        Ok(finish(AudioSample::new(source)))
     }
 
     fn extensions(&self) -> &[&str] {
         Self::extensions()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{prelude::*, test::run_until, utils::profiling::ProfilingBackend};
+    use bevy::{
+        asset::{
+            LoadState,
+            io::{
+                AssetSource,
+                memory::{Dir, MemoryAssetReader},
+            },
+        },
+        prelude::*,
+    };
+    use std::path::Path;
+
+    #[test]
+    fn test_custom_reader() {
+        // An in-memory source stands in for an archive.
+        let archive = Dir::default();
+        archive.insert_asset(
+            Path::new("sfx/sine.wav"),
+            include_bytes!("../../assets/sine_440hz_1ms.wav").to_vec(),
+        );
+
+        let mut app = App::new();
+        app.register_asset_source(
+            "pak",
+            AssetSource::build().with_reader(move || {
+                Box::new(MemoryAssetReader {
+                    root: archive.clone(),
+                })
+            }),
+        )
+        .add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            SeedlingPlugin::<ProfilingBackend>::new(),
+        ));
+        app.finish();
+        app.cleanup();
+
+        let handle: Handle<AudioSample> = app
+            .world()
+            .resource::<AssetServer>()
+            .load("pak://sfx/sine.wav");
+
+        run_until(&mut app, move |server: Res<AssetServer>| {
+            match server.load_state(&handle) {
+                LoadState::Loaded => true,
+                LoadState::Failed(e) => panic!("failed to load sample: {e}"),
+                _ => false,
+            }
+        });
+    }
+}
@@ -0,0 +1,195 @@
+use super::{AudioSample, QueuedSample, SamplePlayer};
+use crate::SeedlingSystems;
+use bevy_app::prelude::*;
+use bevy_asset::{Handle, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+
+pub(crate) struct FirstAvailablePlugin;
+
+impl Plugin for FirstAvailablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, FirstAvailable::apply.before(SeedlingSystems::Acquire));
+    }
+}
+
+/// Alternative samples for a [`SamplePlayer`], played if they
+/// finish loading before the player's own sample.
+///
+/// When assets arrive progressively, like during streaming installs
+/// or on the web, it's often better to play a lower-quality variant
+/// right away than to wait for the preferred one.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_theme(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("music/theme.flac")).looping(),
+///         FirstAvailable::new([server.load("music/theme_preview.ogg")]),
+///     ));
+/// }
+///
+/// fn report(chosen: On<VariantChosenEvent>) {
+///     info!("playing variant {} of {}", chosen.index, chosen.entity);
+/// }
+/// ```
+///
+/// While the player is queued, the first loaded sample wins. The player's
+/// own sample is preferred if several are loaded in the same frame,
+/// followed by the alternatives in order. Samples that fail to load
+/// are skipped. If none ever load, the player times out according to
+/// its [`SampleQueueLifetime`][crate::prelude::SampleQueueLifetime].
+///
+/// Once a sample is chosen, a [`VariantChosenEvent`] is triggered
+/// and this component is removed.
+#[derive(Debug, Component, Default, Clone)]
+#[component(immutable)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct FirstAvailable(pub Vec<Handle<AudioSample>>);
+
+impl FirstAvailable {
+    /// Construct a new [`FirstAvailable`] from a list of alternatives.
+    pub fn new(alternatives: impl IntoIterator<Item = Handle<AudioSample>>) -> Self {
+        Self(alternatives.into_iter().collect())
+    }
+
+    fn apply(
+        samples: Query<(Entity, &SamplePlayer, &Self), With<QueuedSample>>,
+        server: Res<AssetServer>,
+        assets: Res<Assets<AudioSample>>,
+        mut commands: Commands,
+    ) {
+        for (entity, player, alternatives) in &samples {
+            let candidates = core::iter::once(&player.sample).chain(&alternatives.0);
+
+            let mut failed = 0;
+            let mut chosen = None;
+            for (index, candidate) in candidates.enumerate() {
+                if assets.contains(candidate.id()) {
+                    chosen = Some((index, candidate));
+                    break;
+                }
+
+                if server.load_state(candidate.id()).is_failed() {
+                    failed += 1;
+                }
+            }
+
+            let Some((index, sample)) = chosen else {
+                if failed == alternatives.0.len() + 1 {
+                    warn!("none of the samples for {entity} could be loaded");
+                    commands.entity(entity).remove::<Self>();
+                }
+                continue;
+            };
+
+            let mut player_commands = commands.entity(entity);
+            if index != 0 {
+                player_commands.insert(SamplePlayer {
+                    sample: sample.clone(),
+                    ..player.clone()
+                });
+            }
+            player_commands.remove::<Self>();
+
+            commands.trigger(VariantChosenEvent {
+                entity,
+                index,
+                sample: sample.clone(),
+            });
+        }
+    }
+}
+
+impl SamplePlayer {
+    /// Construct a [`SamplePlayer`] that plays whichever of
+    /// several samples is loaded first.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// fn play(mut commands: Commands, server: Res<AssetServer>) {
+    ///     commands.spawn(SamplePlayer::first_available([
+    ///         server.load("hit.flac"),
+    ///         server.load("hit.ogg"),
+    ///     ]));
+    /// }
+    /// ```
+    ///
+    /// The first sample is preferred. To configure the player further,
+    /// spawn it with a [`FirstAvailable`] component directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is empty.
+    pub fn first_available(
+        samples: impl IntoIterator<Item = Handle<AudioSample>>,
+    ) -> (Self, FirstAvailable) {
+        let mut samples = samples.into_iter();
+        let first = samples
+            .next()
+            .expect("`SamplePlayer::first_available` requires at least one sample");
+
+        (Self::new(first), FirstAvailable::new(samples))
+    }
+}
+
+/// Triggered when a [`FirstAvailable`] player chooses its sample.
+#[derive(Debug, EntityEvent)]
+pub struct VariantChosenEvent {
+    /// The [`SamplePlayer`] entity.
+    pub entity: Entity,
+    /// The index of the chosen sample.
+    ///
+    /// Index 0 is the player's own sample, and the
+    /// following indices are the alternatives in order.
+    pub index: usize,
+    /// The chosen sample.
+    pub sample: Handle<AudioSample>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run_until},
+    };
+    use bevy::prelude::*;
+
+    #[derive(Resource, Default)]
+    struct Chosen(Option<(usize, Handle<AudioSample>)>);
+
+    #[test]
+    fn test_first_available() {
+        // The sample may be chosen in the very first update,
+        // so the observer is registered alongside the player.
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.init_resource::<Chosen>();
+            commands.add_observer(|chosen: On<VariantChosenEvent>, mut res: ResMut<Chosen>| {
+                res.0 = Some((chosen.index, chosen.sample.clone()));
+            });
+
+            commands
+                .spawn(SamplerPool(DefaultPool))
+                .connect(crate::edge::AudioGraphOutput);
+
+            commands.spawn(SamplePlayer::first_available([
+                server.load("missing.wav"),
+                server.load("sine_440hz_1ms.wav"),
+            ]));
+        });
+
+        run_until(&mut app, |chosen: Res<Chosen>| chosen.0.is_some());
+
+        let (index, sample) = app.world().resource::<Chosen>().0.clone().unwrap();
+        assert_eq!(index, 1);
+
+        let server = app.world().resource::<AssetServer>();
+        assert_eq!(
+            server.get_path(sample.id()).unwrap().to_string(),
+            "sine_440hz_1ms.wav"
+        );
+    }
+}
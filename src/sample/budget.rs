@@ -0,0 +1,172 @@
+use super::{AudioSample, SamplePlayer};
+use crate::SeedlingSystems;
+use bevy_app::prelude::*;
+use bevy_asset::{AssetPath, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::{HashMap, HashSet};
+
+/// A memory budget for decoded samples.
+///
+/// When the samples in [`Assets<AudioSample>`] hold more than
+/// `bytes` of decoded audio, the least recently played samples
+/// are evicted until the total fits the budget again. Samples
+/// referenced by any [`SamplePlayer`] are never evicted.
+///
+/// Evicted samples keep their handles. When a [`SamplePlayer`]
+/// later references one, it's reloaded from its path, so playback
+/// starts as soon as it's decoded again. Samples without a path,
+/// like those built with [`AudioSample::from_frames`], can't be
+/// reloaded and are never evicted.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn plugin(app: &mut App) {
+///     // Open worlds churn through thousands of one-shots.
+///     app.insert_resource(SampleCacheBudget::megabytes(256));
+/// }
+/// ```
+///
+/// Without this resource, samples stay loaded for
+/// as long as their handles are alive.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SampleCacheBudget {
+    /// The maximum decoded size of all samples, in bytes.
+    ///
+    /// See [`AudioSample::memory_size`] for how samples are measured.
+    pub bytes: usize,
+}
+
+impl SampleCacheBudget {
+    /// Create a budget of `megabytes` mebibytes.
+    pub const fn megabytes(megabytes: usize) -> Self {
+        Self {
+            bytes: megabytes * 1024 * 1024,
+        }
+    }
+}
+
+/// Playback recency and evicted sample paths.
+#[derive(Resource, Default)]
+struct SampleCache {
+    frame: u64,
+    last_played: HashMap<AssetId<AudioSample>, u64>,
+    evicted: HashMap<AssetId<AudioSample>, AssetPath<'static>>,
+}
+
+pub(crate) struct SampleBudgetPlugin;
+
+impl Plugin for SampleBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SampleCache>().add_systems(
+            Last,
+            (track_playback, reload_evicted, evict_samples)
+                .chain()
+                .run_if(resource_exists::<SampleCacheBudget>)
+                .before(SeedlingSystems::Acquire),
+        );
+    }
+}
+
+fn track_playback(players: Query<&SamplePlayer>, mut cache: ResMut<SampleCache>) {
+    cache.frame += 1;
+    let frame = cache.frame;
+
+    for player in &players {
+        cache.last_played.insert(player.sample.id(), frame);
+    }
+}
+
+fn reload_evicted(
+    players: Query<&SamplePlayer>,
+    assets: Res<Assets<AudioSample>>,
+    server: Res<AssetServer>,
+    mut cache: ResMut<SampleCache>,
+) {
+    if cache.evicted.is_empty() {
+        return;
+    }
+
+    // Samples may have been reloaded elsewhere in the meantime.
+    cache.evicted.retain(|id, _| !assets.contains(*id));
+
+    for player in &players {
+        if let Some(path) = cache.evicted.remove(&player.sample.id()) {
+            server.reload(path);
+        }
+    }
+}
+
+fn evict_samples(
+    budget: Res<SampleCacheBudget>,
+    players: Query<&SamplePlayer>,
+    mut assets: ResMut<Assets<AudioSample>>,
+    server: Res<AssetServer>,
+    mut cache: ResMut<SampleCache>,
+) {
+    let mut total: usize = assets.iter().map(|(_, sample)| sample.memory_size()).sum();
+    if total <= budget.bytes {
+        return;
+    }
+
+    let active: HashSet<_> = players.iter().map(|p| p.sample.id()).collect();
+
+    let mut candidates: Vec<_> = assets
+        .iter()
+        .filter(|(id, _)| !active.contains(id))
+        .filter_map(|(id, sample)| {
+            let path = server.get_path(id)?.into_owned();
+            let last_played = cache.last_played.get(&id).copied().unwrap_or_default();
+
+            Some((last_played, id, sample.memory_size(), path))
+        })
+        .collect();
+    candidates.sort_unstable_by_key(|(last_played, ..)| *last_played);
+
+    for (_, id, size, path) in candidates {
+        if total <= budget.bytes {
+            break;
+        }
+
+        assets.remove(id);
+        cache.last_played.remove(&id);
+        cache.evicted.insert(id, path);
+        total -= size;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run_until},
+    };
+
+    #[test]
+    fn test_eviction() {
+        let mut app = prepare_app(|| {});
+        app.insert_resource(SampleCacheBudget { bytes: 0 });
+
+        let server = app.world().resource::<AssetServer>().clone();
+        let sample: Handle<AudioSample> = server.load("sine_440hz_1ms.wav");
+
+        // With no budget to spare, the sample is evicted once it loads.
+        let id = sample.id();
+        run_until(&mut app, move |cache: Res<SampleCache>| {
+            cache.evicted.contains_key(&id)
+        });
+        assert!(
+            !app.world()
+                .resource::<Assets<AudioSample>>()
+                .contains(&sample)
+        );
+
+        // Playing it again brings it back.
+        app.world_mut().spawn(SamplePlayer::new(sample.clone()));
+        run_until(&mut app, move |assets: Res<Assets<AudioSample>>| {
+            assets.contains(&sample)
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use super::{AudioSample, QueuedSample, SamplePlayer, SampleRewrites};
+use crate::context::SampleRate;
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use core::{num::NonZeroUsize, ops::Range};
+use firewheel::{nodes::sampler::RepeatMode, sample_resource::SampleResource};
+use std::time::Duration;
+
+pub(crate) struct LoopCrossfadePlugin;
+
+impl Plugin for LoopCrossfadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrossfadedLoops>()
+            .add_systems(Last, LoopCrossfade::apply.in_set(SampleRewrites));
+    }
+}
+
+/// Crossfade a looping sample's loop boundary.
+///
+/// Samples that don't loop cleanly will click when they wrap
+/// around. With [`LoopCrossfade`], the end of the sample is
+/// blended into its beginning over the given duration, hiding
+/// the seam without needing a perfectly authored loop.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use std::time::Duration;
+/// fn play_ambience(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("my_ambience.wav")).looping(),
+///         LoopCrossfade(Duration::from_millis(250)),
+///     ));
+/// }
+/// ```
+///
+/// The crossfaded sample is prepared just before the sample is
+/// assigned to a sampler, replacing [`SamplePlayer::sample`]. Since
+/// the crossfade consumes the end of the sample, each loop is
+/// shorter by the crossfade's duration. The crossfade is limited
+/// to half the sample's length, and it has no effect on samples
+/// that don't loop.
+///
+/// Samples with [`LoopPoints`][super::LoopPoints] loop at those points instead.
+///
+/// Crossfaded samples are shared between players with the same
+/// sample and duration, and they're freed once no player uses them.
+#[derive(Debug, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct LoopCrossfade(pub Duration);
+
+impl LoopCrossfade {
+    pub(super) fn apply(
+        samples: Query<(Entity, &SamplePlayer, &Self), With<QueuedSample>>,
+        mut assets: ResMut<Assets<AudioSample>>,
+        mut loops: ResMut<CrossfadedLoops>,
+        sample_rate: Res<SampleRate>,
+        mut commands: Commands,
+    ) {
+        for (entity, player, crossfade) in &samples {
+            if player.repeat_mode == RepeatMode::PlayOnce {
+                commands.entity(entity).remove::<Self>();
+                continue;
+            }
+
+            let frames =
+                (crossfade.0.as_secs_f64() * sample_rate.get().get() as f64).round() as usize;
+            let key = (player.sample.id(), frames);
+
+            let existing = loops
+                .0
+                .get(&key)
+                .and_then(|id| assets.get_strong_handle(*id));
+
+            let sample = match existing {
+                Some(sample) => sample,
+                None => {
+                    // Wait for the source to finish loading.
+                    let Some(source) = assets.get(&player.sample) else {
+                        continue;
+                    };
+
+                    let crossfaded = CrossfadedLoop::new(&*source.get(), frames);
+                    let crossfaded =
+                        AudioSample::new(crossfaded).with_markers(source.markers().iter().cloned());
+                    let handle = assets.add(crossfaded);
+                    loops.0.insert(key, handle.id());
+
+                    handle
+                }
+            };
+
+            commands
+                .entity(entity)
+                .insert(SamplePlayer {
+                    sample,
+                    ..player.clone()
+                })
+                .remove::<Self>();
+        }
+
+        loops.0.retain(|_, id| assets.contains(*id));
+    }
+}
+
+/// Previously crossfaded samples, keyed by source and crossfade length.
+#[derive(Resource, Default)]
+pub(super) struct CrossfadedLoops(HashMap<(AssetId<AudioSample>, usize), AssetId<AudioSample>>);
+
+/// A sample with its loop boundary crossfaded.
+struct CrossfadedLoop {
+    channels: Vec<Vec<f32>>,
+}
+
+impl CrossfadedLoop {
+    fn new(source: &dyn SampleResource, frames: usize) -> Self {
+        let len = source.len_frames() as usize;
+        let mut channels = vec![vec![0.0; len]; source.num_channels().get()];
+
+        {
+            let mut buffers: Vec<_> = channels.iter_mut().map(Vec::as_mut_slice).collect();
+            source.fill_buffers(&mut buffers, 0..len, 0);
+        }
+
+        let frames = frames.min(len / 2);
+        if frames == 0 {
+            return Self { channels };
+        }
+
+        // The tail leads into the head, so the loop region
+        // begins where the tail's fade out starts.
+        let tail_start = len - frames;
+        for channel in &mut channels {
+            for i in 0..frames {
+                let proportion = (i as f32 + 0.5) / frames as f32;
+                let angle = proportion * core::f32::consts::FRAC_PI_2;
+
+                // equal-power crossfade
+                channel[i] = channel[tail_start + i] * angle.cos() + channel[i] * angle.sin();
+            }
+
+            channel.truncate(tail_start);
+        }
+
+        Self { channels }
+    }
+}
+
+impl SampleResource for CrossfadedLoop {
+    fn num_channels(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.channels.len()).unwrap()
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.channels.first().map(Vec::len).unwrap_or_default() as u64
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        let start_frame = start_frame as usize;
+
+        for (buffer, channel) in buffers.iter_mut().zip(&self.channels) {
+            let available = channel.len().saturating_sub(start_frame);
+            let frames = buffer_range.len().min(available);
+
+            buffer[buffer_range.start..buffer_range.start + frames]
+                .copy_from_slice(&channel[start_frame..start_frame + frames]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crossfade_length() {
+        let source = CrossfadedLoop {
+            channels: vec![vec![1.0; 100], vec![0.5; 100]],
+        };
+
+        let crossfaded = CrossfadedLoop::new(&source, 10);
+        assert_eq!(crossfaded.len_frames(), 90);
+
+        // the crossfade can't exceed half the sample
+        let crossfaded = CrossfadedLoop::new(&source, 80);
+        assert_eq!(crossfaded.len_frames(), 50);
+
+        // equal-power crossfades of identical signals stay near unity
+        let crossfaded = CrossfadedLoop::new(&source, 10);
+        for sample in &crossfaded.channels[0][..10] {
+            assert!((1.0..=1.5).contains(sample));
+        }
+    }
+}
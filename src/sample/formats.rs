@@ -0,0 +1,199 @@
+use super::{AudioSample, SampleLoader};
+use bevy_asset::{AssetPath, prelude::*};
+use bevy_ecs::{prelude::*, system::SystemParam};
+
+/// A broad class of target platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SamplePlatform {
+    /// Browsers, where `wasm32` is the target architecture.
+    Web,
+    /// Android and iOS.
+    Mobile,
+    /// Everything else, including desktops and consoles.
+    Native,
+}
+
+impl SamplePlatform {
+    /// The platform this build targets.
+    pub const fn current() -> Self {
+        if cfg!(target_arch = "wasm32") {
+            Self::Web
+        } else if cfg!(any(target_os = "android", target_os = "ios")) {
+            Self::Mobile
+        } else {
+            Self::Native
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FormatRule {
+    from: String,
+    to: Vec<String>,
+    /// Only substitute when `from` can't be loaded.
+    fallback: bool,
+}
+
+/// Rules for swapping sample formats at load time.
+///
+/// Shipping the same sound in several formats is common: web builds
+/// prefer compact `ogg` files while native builds may prefer `wav`
+/// or `flac`. Rather than choosing paths with `cfg` attributes throughout
+/// game code, declare the alternatives once and load through [`SampleAssets`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, sample::{SampleFormats, SamplePlatform}};
+/// fn plugin(app: &mut App) {
+///     app.insert_resource(
+///         SampleFormats::default()
+///             // If `mp3` support is disabled, look for an `ogg` file instead.
+///             .with_fallback("mp3", ["ogg"])
+///             // On native platforms, prefer lossless variants when available.
+///             .with_substitute_on(SamplePlatform::Native, "ogg", ["flac", "wav"]),
+///     );
+/// }
+///
+/// fn play(mut commands: Commands, samples: SampleAssets) {
+///     // Loads `music/theme.flac` on native platforms with the `flac` feature,
+///     // and `music/theme.ogg` on the web.
+///     commands.spawn(SamplePlayer::new(samples.load("music/theme.ogg")));
+/// }
+/// ```
+///
+/// Rules are applied once, in the order they're declared, and only
+/// substitute formats this build can decode. The alternate files
+/// must exist alongside the original.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SampleFormats {
+    rules: Vec<FormatRule>,
+}
+
+impl SampleFormats {
+    /// Load the first supported extension in `to` when `from` isn't supported.
+    pub fn with_fallback(
+        mut self,
+        from: impl Into<String>,
+        to: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.rules.push(FormatRule {
+            from: from.into(),
+            to: to.into_iter().map(Into::into).collect(),
+            fallback: true,
+        });
+        self
+    }
+
+    /// Load the first supported extension in `to` instead of `from`
+    /// on `platform`, even if `from` is supported.
+    pub fn with_substitute_on(
+        mut self,
+        platform: SamplePlatform,
+        from: impl Into<String>,
+        to: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        if platform == SamplePlatform::current() {
+            self.rules.push(FormatRule {
+                from: from.into(),
+                to: to.into_iter().map(Into::into).collect(),
+                fallback: false,
+            });
+        }
+        self
+    }
+
+    /// Apply these rules to `path`.
+    ///
+    /// Paths that match no rule are returned unchanged.
+    pub fn resolve<'a>(&self, path: impl Into<AssetPath<'a>>) -> AssetPath<'a> {
+        self.resolve_with(path.into(), SampleLoader::extensions())
+    }
+
+    fn resolve_with<'a>(&self, path: AssetPath<'a>, supported: &[&str]) -> AssetPath<'a> {
+        let Some(extension) = path.path().extension().and_then(|e| e.to_str()) else {
+            return path;
+        };
+        let is_supported = |ext: &str| supported.iter().any(|s| s.eq_ignore_ascii_case(ext));
+
+        let replacement = self
+            .rules
+            .iter()
+            .filter(|rule| rule.from.eq_ignore_ascii_case(extension))
+            .filter(|rule| !rule.fallback || !is_supported(extension))
+            .find_map(|rule| rule.to.iter().find(|ext| is_supported(ext)));
+
+        match replacement {
+            Some(replacement) => {
+                let mut resolved = AssetPath::from(path.path().with_extension(replacement))
+                    .with_source(path.source().clone_owned());
+                if let Some(label) = path.label() {
+                    resolved = resolved.with_label(label.to_owned());
+                }
+                resolved
+            }
+            None => path,
+        }
+    }
+}
+
+/// Loads [`AudioSample`]s according to the app's [`SampleFormats`].
+///
+/// This is a thin wrapper around the [`AssetServer`].
+#[derive(SystemParam, Debug)]
+pub struct SampleAssets<'w> {
+    server: Res<'w, AssetServer>,
+    formats: Res<'w, SampleFormats>,
+}
+
+impl SampleAssets<'_> {
+    /// Begin loading a sample, substituting its format if necessary.
+    pub fn load<'a>(&self, path: impl Into<AssetPath<'a>>) -> Handle<AudioSample> {
+        self.server.load(self.formats.resolve(path))
+    }
+
+    /// The format rules used by this loader.
+    pub fn formats(&self) -> &SampleFormats {
+        &self.formats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_rules() {
+        let formats = SampleFormats {
+            rules: vec![
+                FormatRule {
+                    from: "mp3".into(),
+                    to: vec!["opus".into(), "ogg".into()],
+                    fallback: true,
+                },
+                FormatRule {
+                    from: "ogg".into(),
+                    to: vec!["flac".into(), "wav".into()],
+                    fallback: false,
+                },
+            ],
+        };
+
+        let resolve = |path: &'static str, supported: &[&str]| {
+            formats
+                .resolve_with(AssetPath::from(path), supported)
+                .to_string()
+        };
+
+        // unsupported formats fall back to the first supported alternative
+        assert_eq!(resolve("music/a.mp3", &["ogg"]), "music/a.ogg");
+        assert_eq!(resolve("music/a.mp3", &["mp3", "ogg"]), "music/a.mp3");
+
+        // substitutes apply even when the original is supported
+        assert_eq!(resolve("b.ogg", &["ogg", "wav"]), "b.wav");
+        assert_eq!(resolve("b.ogg", &["ogg"]), "b.ogg");
+
+        // sources and unrelated paths are preserved
+        assert_eq!(resolve("pak://c.mp3", &["ogg"]), "pak://c.ogg");
+        assert_eq!(resolve("d.wav", &["ogg"]), "d.wav");
+    }
+}
@@ -0,0 +1,212 @@
+use super::{AudioSample, QueuedSample, SamplePlayer, SampleRewrites};
+use crate::SeedlingSystems;
+use bevy_app::prelude::*;
+use bevy_asset::Handle;
+use bevy_ecs::prelude::*;
+use bevy_math::FloatExt;
+use firewheel::Volume;
+
+pub(crate) struct IntensityPlugin;
+
+impl Plugin for IntensityPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Last,
+            SampleRewrites
+                .after(Intensity::apply)
+                .before(SeedlingSystems::Acquire),
+        )
+        .add_systems(Last, Intensity::apply.before(SeedlingSystems::Acquire));
+    }
+}
+
+/// Scales a sample's playback according to some external intensity,
+/// like the impulse of a physics collision.
+///
+/// The intensity is interpreted through the entity's [`IntensityCurve`]
+/// just before the sample is assigned to a sampler. The curve maps the
+/// intensity to a volume, which is applied on top of
+/// [`SamplePlayer::volume`], and optionally selects one of several sample
+/// variants, such as soft, medium, and hard hits.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn impact(mut commands: Commands, server: Res<AssetServer>) {
+///     let impulse = 0.7;
+///
+///     commands.spawn((
+///         SamplePlayer::new(server.load("impact_soft.wav")),
+///         Intensity(impulse),
+///         IntensityCurve::new(Volume::Decibels(-18.0), Volume::UNITY_GAIN)
+///             .with_variant(0.4, server.load("impact_medium.wav"))
+///             .with_variant(0.8, server.load("impact_hard.wav")),
+///     ));
+/// }
+/// ```
+///
+/// The intensity is expected to fall within `0.0..=1.0`; values outside
+/// this range are clamped. Once applied, this component is removed.
+/// Since [`SamplePlayer`] is re-inserted with the scaled values,
+/// inserting an [`Intensity`] on a sample that's already playing
+/// will restart it.
+#[derive(Debug, Component, Default, Clone, Copy, PartialEq)]
+#[require(IntensityCurve)]
+#[component(immutable)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct Intensity(pub f32);
+
+impl Intensity {
+    pub(super) fn apply(
+        samples: Query<(Entity, &SamplePlayer, &Self, &IntensityCurve), With<QueuedSample>>,
+        mut commands: Commands,
+    ) {
+        for (entity, player, intensity, curve) in &samples {
+            let sample = curve
+                .variant(intensity.0)
+                .cloned()
+                .unwrap_or_else(|| player.sample.clone());
+            let volume =
+                Volume::Decibels(player.volume.decibels() + curve.volume(intensity.0).decibels());
+
+            commands
+                .entity(entity)
+                .insert(SamplePlayer {
+                    sample,
+                    volume,
+                    ..player.clone()
+                })
+                .remove::<Self>();
+        }
+    }
+}
+
+/// Describes how an [`Intensity`] maps to playback.
+///
+/// Intensities are first shaped by [`IntensityCurve::exponent`],
+/// then used to interpolate between the minimum and maximum
+/// volumes in decibels.
+///
+/// By default, an intensity of `0.0` plays at -24 dB and an
+/// intensity of `1.0` plays at unity gain, with no sample variants.
+#[derive(Debug, Component, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct IntensityCurve {
+    /// The volume at an intensity of `0.0`.
+    pub min_volume: Volume,
+
+    /// The volume at an intensity of `1.0`.
+    pub max_volume: Volume,
+
+    /// The exponent applied to the intensity before mapping.
+    ///
+    /// Values greater than `1.0` keep quiet hits quiet for longer,
+    /// while values less than `1.0` reach loud hits more quickly.
+    pub exponent: f32,
+
+    /// Sample variants selected by intensity.
+    ///
+    /// The variant with the highest threshold that the intensity
+    /// meets or exceeds is played. If no variant matches, the
+    /// [`SamplePlayer`]'s own sample is played.
+    pub variants: Vec<IntensityVariant>,
+}
+
+impl Default for IntensityCurve {
+    fn default() -> Self {
+        Self {
+            min_volume: Volume::Decibels(-24.0),
+            max_volume: Volume::UNITY_GAIN,
+            exponent: 1.0,
+            variants: Vec::new(),
+        }
+    }
+}
+
+/// A sample variant selected when an [`Intensity`] reaches `threshold`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct IntensityVariant {
+    /// The minimum intensity at which this variant is selected.
+    pub threshold: f32,
+
+    /// The sample to play.
+    pub sample: Handle<AudioSample>,
+}
+
+impl IntensityCurve {
+    /// Construct a new [`IntensityCurve`] spanning `min_volume` to `max_volume`.
+    pub fn new(min_volume: Volume, max_volume: Volume) -> Self {
+        Self {
+            min_volume,
+            max_volume,
+            ..Default::default()
+        }
+    }
+
+    /// Set the [`IntensityCurve::exponent`].
+    pub fn with_exponent(self, exponent: f32) -> Self {
+        Self { exponent, ..self }
+    }
+
+    /// Add a sample variant selected at or above `threshold`.
+    pub fn with_variant(mut self, threshold: f32, sample: Handle<AudioSample>) -> Self {
+        self.variants.push(IntensityVariant { threshold, sample });
+        self
+    }
+
+    /// Calculate the volume for `intensity`.
+    pub fn volume(&self, intensity: f32) -> Volume {
+        let t = intensity.clamp(0.0, 1.0).powf(self.exponent);
+        let min = self.min_volume.decibels();
+        let max = self.max_volume.decibels();
+
+        // Interpolating from silence would produce NaNs.
+        if min == f32::NEG_INFINITY || max == f32::NEG_INFINITY {
+            return Volume::Linear(self.min_volume.linear().lerp(self.max_volume.linear(), t));
+        }
+
+        Volume::Decibels(min.lerp(max, t))
+    }
+
+    /// Select the sample variant for `intensity`, if any.
+    pub fn variant(&self, intensity: f32) -> Option<&Handle<AudioSample>> {
+        self.variants
+            .iter()
+            .filter(|v| intensity >= v.threshold)
+            .max_by(|a, b| a.threshold.total_cmp(&b.threshold))
+            .map(|v| &v.sample)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intensity_volume() {
+        let curve = IntensityCurve::new(Volume::Decibels(-20.0), Volume::Decibels(0.0));
+
+        assert_eq!(curve.volume(0.0), Volume::Decibels(-20.0));
+        assert_eq!(curve.volume(0.5), Volume::Decibels(-10.0));
+        assert_eq!(curve.volume(2.0), Volume::Decibels(0.0));
+
+        let curve = curve.with_exponent(2.0);
+        assert_eq!(curve.volume(0.5), Volume::Decibels(-15.0));
+    }
+
+    #[test]
+    fn test_intensity_variants() {
+        let soft = Handle::<AudioSample>::default();
+        let hard =
+            Handle::<AudioSample>::Uuid(bevy_asset::uuid::Uuid::from_u128(1), Default::default());
+
+        let curve = IntensityCurve::default()
+            .with_variant(0.8, hard.clone())
+            .with_variant(0.3, soft.clone());
+
+        assert_eq!(curve.variant(0.1), None);
+        assert_eq!(curve.variant(0.5), Some(&soft));
+        assert_eq!(curve.variant(0.9), Some(&hard));
+    }
+}
@@ -0,0 +1,548 @@
+use super::{
+    AudioSample, LoopCrossfade, QueuedSample, SamplePlayer, SampleRewrites, region::playhead_frames,
+};
+use crate::context::SampleRate;
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use core::{
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+};
+use firewheel::{
+    collector::ArcGc,
+    nodes::sampler::{Playhead, RepeatMode},
+    sample_resource::SampleResource,
+};
+use std::sync::Arc;
+use symphonia::core::{
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::{MetadataOptions, Tag},
+    probe::Hint,
+};
+
+pub(crate) struct LoopPointsPlugin;
+
+impl Plugin for LoopPointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PointLoops>().add_systems(
+            Last,
+            apply_loop_points
+                .in_set(SampleRewrites)
+                .before(LoopCrossfade::apply),
+        );
+    }
+}
+
+/// A sample's loop region, in frames at the audio engine's sample rate.
+///
+/// Loop points are read from file metadata when a sample is loaded,
+/// including the WAV `smpl` chunk and the `LOOPSTART` and `LOOPLENGTH`
+/// Vorbis comments. When a sample with loop points is played with
+/// [`SamplePlayer::looping`], it plays from the beginning through `end`,
+/// then repeats `start..end` seamlessly. To choose the region
+/// by hand, use [`LoopRegion`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+///     // The intro plays once before the loop takes over.
+///     commands.spawn(SamplePlayer::new(server.load("music/with_intro.ogg")).looping());
+/// }
+///
+/// fn report(samples: Res<Assets<AudioSample>>, mut events: MessageReader<AssetEvent<AudioSample>>) {
+///     for event in events.read() {
+///         if let AssetEvent::LoadedWithDependencies { id } = event {
+///             if let Some(points) = samples.get(*id).and_then(|s| s.loop_points()) {
+///                 info!("loop: {:?}", points.start..points.end);
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// With a finite [`RepeatMode::RepeatMultiple`], the loop region repeats
+/// the given number of times before the rest of the sample plays out.
+/// Loop points take precedence over [`LoopCrossfade`].
+///
+/// Streamed samples jump back to `start` like a seek, so they may
+/// briefly drop out at the loop boundary unless `start` falls within
+/// the first half second of the sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LoopPoints {
+    /// The first frame of the loop.
+    pub start: u64,
+    /// The frame just past the end of the loop.
+    pub end: u64,
+}
+
+/// Read loop points from an encoded sample's metadata.
+pub(super) fn read_loop_points(
+    bytes: &[u8],
+    tags: Option<&SampleTags>,
+    sample_rate: NonZeroU32,
+) -> Option<LoopPoints> {
+    let (start, end, source_rate) = wav_loop(bytes).or_else(|| tagged_loop(tags?))?;
+    let source_rate = NonZeroU32::new(source_rate)?;
+
+    let scale = |frame: u64| {
+        (frame as f64 * sample_rate.get() as f64 / source_rate.get() as f64).round() as u64
+    };
+    let points = LoopPoints {
+        start: scale(start),
+        end: scale(end),
+    };
+
+    (points.start < points.end).then_some(points)
+}
+
+/// Read the first loop of a WAV file's `smpl` chunk.
+///
+/// Returns the start, exclusive end, and sample rate.
+fn wav_loop(bytes: &[u8]) -> Option<(u64, u64, u32)> {
+    let mut sample_rate = None;
+    let mut points = None;
+
+    for (id, data) in wav_chunks(bytes)? {
+        match id {
+            b"fmt " => sample_rate = read_u32(data, 4),
+            b"smpl" if read_u32(data, 28)? > 0 => {
+                // The first loop follows the 36-byte header. Its end is inclusive.
+                let start = read_u32(data, 36 + 8)?;
+                let end = read_u32(data, 36 + 12)?;
+                points = Some((start as u64, end as u64 + 1));
+            }
+            _ => {}
+        }
+    }
+
+    let (start, end) = points?;
+    Some((start, end, sample_rate?))
+}
+
+/// Iterate over the chunks of a RIFF WAVE file.
+///
+/// Returns `None` if `bytes` isn't a WAV file.
+pub(super) fn wav_chunks(bytes: &[u8]) -> Option<impl Iterator<Item = (&[u8], &[u8])>> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    Some(riff_chunks(&bytes[12..]))
+}
+
+/// Iterate over a sequence of RIFF chunks, yielding their IDs and data.
+pub(super) fn riff_chunks(bytes: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut offset = 0;
+    core::iter::from_fn(move || {
+        let id = bytes.get(offset..offset + 4)?;
+        let len = read_u32(bytes, offset + 4)? as usize;
+        let data = offset + 8;
+
+        // Chunks are padded to an even length.
+        offset = data + len + (len & 1);
+        Some((id, bytes.get(data..(data + len).min(bytes.len()))?))
+    })
+}
+
+/// Read a little-endian `u32` at `offset`.
+pub(super) fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// The metadata tags of an encoded sample, like Vorbis comments.
+pub(super) struct SampleTags {
+    tags: Vec<Tag>,
+    pub(super) sample_rate: u32,
+}
+
+impl SampleTags {
+    /// Probe an encoded sample for its tags.
+    pub(super) fn read(bytes: &Arc<[u8]>, hint: &Hint) -> Option<Self> {
+        let stream = MediaSourceStream::new(
+            Box::new(std::io::Cursor::new(bytes.clone())),
+            Default::default(),
+        );
+        let mut probed = symphonia::default::get_probe()
+            .format(
+                hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .ok()?;
+
+        let sample_rate = probed.format.default_track()?.codec_params.sample_rate?;
+
+        let mut tags = Vec::new();
+        if let Some(revision) = probed.format.metadata().current() {
+            tags.extend(revision.tags().iter().cloned());
+        }
+        if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+            tags.extend(revision.tags().iter().cloned());
+        }
+
+        Some(Self { tags, sample_rate })
+    }
+
+    /// Find a tag's value by its case-insensitive key.
+    pub(super) fn get(&self, key: &str) -> Option<String> {
+        self.tags
+            .iter()
+            .find(|t| t.key.eq_ignore_ascii_case(key))
+            .map(|t| t.value.to_string().trim().to_string())
+    }
+}
+
+/// Read the `LOOPSTART` and `LOOPLENGTH` tags, as used in Vorbis comments.
+///
+/// Returns the start, exclusive end, and sample rate.
+fn tagged_loop(tags: &SampleTags) -> Option<(u64, u64, u32)> {
+    let start: u64 = tags.get("LOOPSTART")?.parse().ok()?;
+    let length: u64 = tags.get("LOOPLENGTH")?.parse().ok()?;
+
+    Some((start, start.checked_add(length)?, tags.sample_rate))
+}
+
+/// An explicit loop region for a looping [`SamplePlayer`].
+///
+/// A single file can contain an intro followed by a looped body.
+/// With [`LoopRegion`], the sample plays from the beginning through
+/// `end`, then repeats `start..end` for as long as it loops.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_music(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("music/theme.ogg")).looping(),
+///         // A four-second intro, followed by a 32-second loop.
+///         LoopRegion::seconds(4.0, 36.0),
+///     ));
+/// }
+/// ```
+///
+/// This takes precedence over [`LoopPoints`] read from the file's metadata,
+/// and like them, over [`LoopCrossfade`]. It has no effect on samples that
+/// don't loop. Once applied, [`SamplePlayer::sample`] is replaced with the
+/// looped sample and this component is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct LoopRegion {
+    /// The start of the loop.
+    pub start: Playhead,
+    /// The end of the loop, exclusive.
+    pub end: Playhead,
+}
+
+impl LoopRegion {
+    /// Create a loop region in seconds.
+    pub fn seconds(start: f64, end: f64) -> Self {
+        Self {
+            start: Playhead::Seconds(start),
+            end: Playhead::Seconds(end),
+        }
+    }
+
+    /// Create a loop region in frames.
+    pub fn frames(start: u64, end: u64) -> Self {
+        Self {
+            start: Playhead::Frames(start),
+            end: Playhead::Frames(end),
+        }
+    }
+
+    fn points(&self, sample_rate: NonZeroU32) -> LoopPoints {
+        LoopPoints {
+            start: playhead_frames(self.start, sample_rate),
+            end: playhead_frames(self.end, sample_rate),
+        }
+    }
+}
+
+pub(super) fn apply_loop_points(
+    samples: Query<(Entity, &SamplePlayer, Option<&LoopRegion>), With<QueuedSample>>,
+    mut assets: ResMut<Assets<AudioSample>>,
+    mut loops: ResMut<PointLoops>,
+    sample_rate: Res<SampleRate>,
+    mut commands: Commands,
+) {
+    for (entity, player, region) in &samples {
+        let repeats = match player.repeat_mode {
+            RepeatMode::PlayOnce => continue,
+            RepeatMode::RepeatEndlessly => None,
+            RepeatMode::RepeatMultiple {
+                num_times_to_repeat,
+            } => Some(num_times_to_repeat as u64),
+        };
+
+        let points = match region {
+            Some(region) => region.points(sample_rate.get()),
+            None => match assets.get(&player.sample).and_then(|s| s.loop_points()) {
+                Some(points) => points,
+                None => continue,
+            },
+        };
+
+        let key = (player.sample.id(), points, repeats);
+        let existing = loops
+            .0
+            .get(&key)
+            .and_then(|id| assets.get_strong_handle(*id));
+
+        let sample = match existing {
+            Some(sample) => sample,
+            None => {
+                // Wait for the source to finish loading.
+                let Some(source) = assets.get(&player.sample) else {
+                    continue;
+                };
+
+                let Some(looped) = LoopedSample::new(source.get(), points, repeats) else {
+                    continue;
+                };
+                let looped = AudioSample::new(looped)
+                    .with_markers(source.markers().iter().cloned())
+                    .with_marker_loop(points, repeats);
+                let handle = assets.add(looped);
+                loops.0.insert(key, handle.id());
+
+                handle
+            }
+        };
+
+        // The looped sample plays its repeats itself. Endless loops keep
+        // their repeat mode so pools continue to treat them as looping.
+        let repeat_mode = match repeats {
+            Some(_) => RepeatMode::PlayOnce,
+            None => player.repeat_mode,
+        };
+
+        commands
+            .entity(entity)
+            .insert(SamplePlayer {
+                sample,
+                repeat_mode,
+                ..player.clone()
+            })
+            .remove::<(LoopRegion, LoopCrossfade)>();
+    }
+
+    loops.0.retain(|_, id| assets.contains(*id));
+}
+
+/// Previously looped samples, keyed by source, loop points, and repeat count.
+#[derive(Resource, Default)]
+pub(super) struct PointLoops(
+    HashMap<(AssetId<AudioSample>, LoopPoints, Option<u64>), AssetId<AudioSample>>,
+);
+
+/// A sample that plays through its loop end and then repeats its loop region.
+struct LoopedSample {
+    source: ArcGc<dyn SampleResource>,
+    start: u64,
+    end: u64,
+    /// The number of repeats, or `None` for endless loops.
+    repeats: Option<u64>,
+}
+
+/// The length reported by endless loops.
+///
+/// This is long enough to never be reached in practice.
+const ENDLESS_FRAMES: u64 = u64::MAX / 4;
+
+impl LoopedSample {
+    /// Returns `None` if the loop region falls outside the source.
+    fn new(
+        source: ArcGc<dyn SampleResource>,
+        points: LoopPoints,
+        repeats: Option<u64>,
+    ) -> Option<Self> {
+        let end = points.end.min(source.len_frames());
+
+        (points.start < end).then(|| Self {
+            source,
+            start: points.start,
+            end,
+            repeats,
+        })
+    }
+
+    /// Map an output frame to a source frame, along with the number
+    /// of frames that can be read from there without a jump.
+    fn map(&self, frame: u64) -> (u64, u64) {
+        let len = self.source.len_frames();
+        if frame < self.end {
+            return (frame, self.end - frame);
+        }
+
+        let body = self.end - self.start;
+        let past = frame - self.end;
+        let loops = past / body;
+
+        match self.repeats {
+            Some(repeats) if loops >= repeats => {
+                let tail = self.end + (past - repeats * body);
+                (tail, len.saturating_sub(tail))
+            }
+            _ => {
+                let position = past % body;
+                (self.start + position, body - position)
+            }
+        }
+    }
+}
+
+impl SampleResource for LoopedSample {
+    fn num_channels(&self) -> NonZeroUsize {
+        self.source.num_channels()
+    }
+
+    fn len_frames(&self) -> u64 {
+        match self.repeats {
+            Some(repeats) => self.source.len_frames() + repeats * (self.end - self.start),
+            None => ENDLESS_FRAMES,
+        }
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        let mut offset = 0;
+        while offset < buffer_range.len() {
+            let (source_frame, contiguous) = self.map(start_frame + offset as u64);
+            let count = (buffer_range.len() - offset).min(contiguous as usize);
+
+            let start = buffer_range.start + offset;
+            if count == 0 {
+                for buffer in buffers.iter_mut() {
+                    buffer[start..buffer_range.end].fill(0.0);
+                }
+                break;
+            }
+
+            self.source
+                .fill_buffers(buffers, start..start + count, source_frame);
+            offset += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Ramp(Vec<f32>);
+
+    impl SampleResource for Ramp {
+        fn num_channels(&self) -> NonZeroUsize {
+            NonZeroUsize::MIN
+        }
+
+        fn len_frames(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn fill_buffers(
+            &self,
+            buffers: &mut [&mut [f32]],
+            buffer_range: Range<usize>,
+            start_frame: u64,
+        ) {
+            let start = start_frame as usize;
+            let frames = buffer_range.len().min(self.0.len().saturating_sub(start));
+            buffers[0][buffer_range.start..buffer_range.start + frames]
+                .copy_from_slice(&self.0[start..start + frames]);
+        }
+    }
+
+    #[test]
+    fn test_wav_loop() {
+        let mut wav = Vec::new();
+        let mut chunk = |id: &[u8], data: &[u32]| {
+            wav.extend_from_slice(id);
+            wav.extend_from_slice(&(data.len() as u32 * 4).to_le_bytes());
+            for word in data {
+                wav.extend_from_slice(&word.to_le_bytes());
+            }
+        };
+
+        chunk(b"RIFF", &[]);
+        // `fmt ` fields, packed into words: format and channels, then the sample rate.
+        chunk(b"fmt ", &[0x0001_0001, 22050, 44100, 0x0010_0002]);
+        // The header, with one loop from 100 through 1099.
+        chunk(
+            b"smpl",
+            &[0, 0, 0, 60, 0, 0, 0, 1, 0, 0, 0, 100, 1099, 0, 0],
+        );
+        wav.splice(8..8, *b"WAVE");
+
+        let points = read_loop_points(&wav, None, NonZeroU32::new(44100).unwrap()).unwrap();
+
+        // Doubling the rate doubles the frames.
+        assert_eq!(
+            points,
+            LoopPoints {
+                start: 200,
+                end: 2200
+            }
+        );
+
+        // A zero sample rate can't be scaled, even for a loop starting at zero.
+        wav[24..28].copy_from_slice(&0u32.to_le_bytes());
+        wav[88..92].copy_from_slice(&0u32.to_le_bytes());
+        assert!(read_loop_points(&wav, None, NonZeroU32::new(44100).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_region_points() {
+        let rate = NonZeroU32::new(48000).unwrap();
+
+        assert_eq!(
+            LoopRegion::seconds(0.5, 2.0).points(rate),
+            LoopPoints {
+                start: 24000,
+                end: 96000
+            }
+        );
+        assert_eq!(
+            LoopRegion::frames(10, 20).points(rate),
+            LoopPoints { start: 10, end: 20 }
+        );
+    }
+
+    #[test]
+    fn test_looped_sample() {
+        let ramp: Vec<f32> = (0..10).map(|i| i as f32).collect();
+        let source = ArcGc::new_unsized(|| Arc::new(Ramp(ramp)) as Arc<dyn SampleResource>);
+        let points = LoopPoints { start: 2, end: 5 };
+
+        let render = |sample: &LoopedSample, frames: usize| {
+            let mut out = vec![-1.0; frames];
+            sample.fill_buffers(&mut [out.as_mut_slice()], 0..frames, 0);
+            out
+        };
+
+        let endless = LoopedSample::new(source.clone(), points, None).unwrap();
+        assert_eq!(
+            render(&endless, 11),
+            [0., 1., 2., 3., 4., 2., 3., 4., 2., 3., 4.]
+        );
+
+        // One repeat, then the tail plays out.
+        let once = LoopedSample::new(source, points, Some(1)).unwrap();
+        assert_eq!(once.len_frames(), 13);
+        assert_eq!(
+            render(&once, 14),
+            [0., 1., 2., 3., 4., 2., 3., 4., 5., 6., 7., 8., 9., 0.]
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use super::{
+    AudioSample, SamplePlayer,
+    loop_points::{SampleTags, read_u32, riff_chunks, wav_chunks},
+};
+use crate::{SeedlingSystems, pool::Sampler};
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use core::num::NonZeroU32;
+use firewheel::nodes::sampler::RepeatMode;
+
+/// A named position in a sample, in frames at the audio engine's sample rate.
+///
+/// Markers are read from file metadata when a sample is loaded,
+/// including WAV `cue ` chunks with their `labl` names and
+/// `CHAPTERxxx` Vorbis comments. They can also be set by hand
+/// with [`AudioSample::with_markers`].
+///
+/// When a playing sample's playhead crosses a marker, a
+/// [`SampleMarkerEvent`] is triggered on its [`SamplePlayer`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn speak(mut commands: Commands, server: Res<AssetServer>) {
+///     commands
+///         .spawn(SamplePlayer::new(server.load("dialogue/greeting.wav")))
+///         .observe(|marker: On<SampleMarkerEvent>| {
+///             // Authored as "mouth_open" and "mouth_closed" cues.
+///             info!("lip sync: {}", marker.marker.label);
+///         });
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SampleMarker {
+    /// The marker's position.
+    pub frame: u64,
+    /// The marker's name, which may be empty.
+    pub label: String,
+}
+
+impl SampleMarker {
+    /// Create a new marker.
+    pub fn new(frame: u64, label: impl Into<String>) -> Self {
+        Self {
+            frame,
+            label: label.into(),
+        }
+    }
+}
+
+/// An event triggered on [`SamplePlayer`] entities when
+/// playback crosses one of their sample's [`SampleMarker`]s.
+///
+/// Markers crossed within the same frame are triggered in order.
+/// Looping samples trigger their markers on every repeat. Seeking
+/// backwards doesn't trigger any markers, while seeking forwards
+/// triggers the markers that were skipped.
+#[derive(Debug, Clone, EntityEvent)]
+pub struct SampleMarkerEvent {
+    /// The sample player's entity.
+    pub entity: Entity,
+    /// The marker that was crossed.
+    pub marker: SampleMarker,
+}
+
+/// Read markers from an encoded sample's metadata.
+pub(super) fn read_markers(
+    bytes: &[u8],
+    tags: Option<&SampleTags>,
+    sample_rate: NonZeroU32,
+) -> Vec<SampleMarker> {
+    let Some((markers, source_rate)) = wav_markers(bytes).or_else(|| tagged_markers(tags?)) else {
+        return Vec::new();
+    };
+
+    let scale =
+        |frame: u64| (frame as f64 * sample_rate.get() as f64 / source_rate as f64).round() as u64;
+
+    let mut markers: Vec<_> = markers
+        .into_iter()
+        .map(|marker| SampleMarker {
+            frame: scale(marker.frame),
+            ..marker
+        })
+        .collect();
+    markers.sort_by_key(|marker| marker.frame);
+
+    markers
+}
+
+/// Read the cue points of a WAV file's `cue ` chunk, named by
+/// the `labl` entries of its associated data list.
+///
+/// Returns the markers and sample rate.
+fn wav_markers(bytes: &[u8]) -> Option<(Vec<SampleMarker>, u32)> {
+    let mut sample_rate = None;
+    let mut cues = Vec::new();
+    let mut labels = HashMap::new();
+
+    for (id, data) in wav_chunks(bytes)? {
+        match id {
+            b"fmt " => sample_rate = read_u32(data, 4),
+            b"cue " => {
+                let count = read_u32(data, 0)? as usize;
+
+                // Each cue point is 24 bytes, ending with its sample offset.
+                for cue in 0..count {
+                    let entry = 4 + cue * 24;
+                    let (Some(id), Some(offset)) =
+                        (read_u32(data, entry), read_u32(data, entry + 20))
+                    else {
+                        break;
+                    };
+
+                    cues.push((id, offset as u64));
+                }
+            }
+            b"LIST" if data.starts_with(b"adtl") => {
+                for (id, label) in riff_chunks(&data[4..]) {
+                    if id != b"labl" {
+                        continue;
+                    }
+
+                    let Some(cue) = read_u32(label, 0) else {
+                        continue;
+                    };
+
+                    let text = &label[4..];
+                    let text = text.split(|b| *b == 0).next().unwrap_or_default();
+                    labels.insert(cue, String::from_utf8_lossy(text).into_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if cues.is_empty() {
+        return None;
+    }
+
+    let markers = cues
+        .into_iter()
+        .map(|(id, frame)| SampleMarker {
+            frame,
+            label: labels.remove(&id).unwrap_or_default(),
+        })
+        .collect();
+
+    Some((markers, sample_rate?))
+}
+
+/// Read `CHAPTERxxx` and `CHAPTERxxxNAME` tags, as used in Vorbis comments.
+///
+/// Chapter times are formatted as `HH:MM:SS.sss`.
+///
+/// Returns the markers and sample rate.
+fn tagged_markers(tags: &SampleTags) -> Option<(Vec<SampleMarker>, u32)> {
+    let mut markers = Vec::new();
+
+    for index in 0..1000 {
+        let key = format!("CHAPTER{index:03}");
+        let Some(time) = tags.get(&key) else {
+            // Chapters may be numbered from zero or one.
+            if index == 0 {
+                continue;
+            }
+            break;
+        };
+
+        let Some(seconds) = parse_timestamp(&time) else {
+            continue;
+        };
+
+        markers.push(SampleMarker {
+            frame: (seconds * tags.sample_rate as f64).round() as u64,
+            label: tags.get(&format!("{key}NAME")).unwrap_or_default(),
+        });
+    }
+
+    (!markers.is_empty()).then_some((markers, tags.sample_rate))
+}
+
+/// Parse an `HH:MM:SS.sss` timestamp into seconds.
+fn parse_timestamp(time: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in time.split(':') {
+        let value: f64 = part.trim().parse().ok()?;
+        seconds = seconds * 60.0 + value;
+    }
+
+    (seconds >= 0.0).then_some(seconds)
+}
+
+pub(crate) struct MarkerPlugin;
+
+impl Plugin for MarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, trigger_markers.after(SeedlingSystems::Pool));
+    }
+}
+
+/// The last position at which a player's markers were checked.
+#[derive(Component)]
+struct MarkerCursor {
+    sampler: Entity,
+    /// The last position, in frames of the sample the markers refer to.
+    frame: u64,
+}
+
+fn trigger_markers(
+    mut players: Query<(Entity, &SamplePlayer, &Sampler, Option<&mut MarkerCursor>)>,
+    assets: Res<Assets<AudioSample>>,
+    mut commands: Commands,
+) {
+    for (entity, player, sampler, cursor) in &mut players {
+        let Some(sample) = assets.get(&player.sample) else {
+            continue;
+        };
+        if sample.markers().is_empty() {
+            continue;
+        }
+
+        let Some(playhead) = sampler.try_playhead_frames() else {
+            continue;
+        };
+        let current = sample.marker_frame(playhead.0.max(0) as u64);
+
+        // A new sampler starts from the beginning.
+        let previous = match cursor {
+            Some(mut cursor) if cursor.sampler == sampler.sampler() => {
+                core::mem::replace(&mut cursor.frame, current)
+            }
+            _ => {
+                commands.entity(entity).insert(MarkerCursor {
+                    sampler: sampler.sampler(),
+                    frame: current,
+                });
+                0
+            }
+        };
+
+        let mut trigger = |range: core::ops::Range<u64>| {
+            for marker in sample.markers() {
+                if range.contains(&marker.frame) {
+                    commands.trigger(SampleMarkerEvent {
+                        entity,
+                        marker: marker.clone(),
+                    });
+                }
+            }
+        };
+
+        if current >= previous {
+            trigger(previous..current);
+            continue;
+        }
+
+        // The playhead jumped backwards, either by looping or seeking.
+        if let Some(points) = sample.marker_loop() {
+            trigger(previous..points.end);
+            trigger(points.start..current);
+        } else if player.repeat_mode != RepeatMode::PlayOnce {
+            trigger(previous..u64::MAX);
+            trigger(0..current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_wav_markers() {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend(chunk(
+            b"fmt ",
+            &words(&[0x0001_0001, 24000, 48000, 0x0010_0002]),
+        ));
+        // Two cue points, listed out of order.
+        wav.extend(chunk(
+            b"cue ",
+            &words(&[2, 7, 0, 0, 0, 0, 500, 3, 0, 0, 0, 0, 100]),
+        ));
+
+        let mut labels = b"adtl".to_vec();
+        labels.extend(chunk(b"labl", &[&words(&[3])[..], b"open\0"].concat()));
+        labels.extend(chunk(b"labl", &[&words(&[7])[..], b"close\0"].concat()));
+        wav.extend(chunk(b"LIST", &labels));
+
+        let markers = read_markers(&wav, None, NonZeroU32::new(48000).unwrap());
+
+        // Doubling the rate doubles the frames.
+        assert_eq!(
+            markers,
+            [
+                SampleMarker::new(200, "open"),
+                SampleMarker::new(1000, "close")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(parse_timestamp("00:00:01.500"), Some(1.5));
+        assert_eq!(parse_timestamp("01:02:03"), Some(3723.0));
+        assert_eq!(parse_timestamp("later"), None);
+    }
+}
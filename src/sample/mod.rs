@@ -16,8 +16,64 @@ use firewheel::{
 use std::time::Duration;
 
 mod assets;
+mod available;
+mod budget;
+mod crossfade;
+mod formats;
+mod intensity;
+mod loop_points;
+mod markers;
+#[cfg(feature = "opus")]
+mod opus;
+mod pcm;
+mod preload;
+mod prewarm;
+mod region;
+#[cfg(feature = "sound_events")]
+mod sound_event;
+mod stream;
+mod tone;
 
-pub use assets::{AudioSample, SampleLoader, SampleLoaderError};
+pub use assets::{
+    AudioSample, ResampleQuality, SampleLoader, SampleLoaderError, SampleLoaderSettings,
+};
+pub use available::{FirstAvailable, VariantChosenEvent};
+pub use budget::SampleCacheBudget;
+pub use crossfade::LoopCrossfade;
+pub use formats::{SampleAssets, SampleFormats, SamplePlatform};
+pub use intensity::{Intensity, IntensityCurve, IntensityVariant};
+pub use loop_points::{LoopPoints, LoopRegion};
+pub use markers::{SampleMarker, SampleMarkerEvent};
+pub use pcm::AddSampleFrames;
+pub use preload::{AudioPreloadSet, PreloadAudioState};
+pub use prewarm::{AudioForState, PrewarmAudio, RegisterStateAudio, StateMusic};
+pub use region::PlaybackRegion;
+#[cfg(feature = "sound_events")]
+pub use sound_event::{
+    RegisterSoundPool, SoundEvent, SoundEventLoader, SoundEventLoaderError, SoundEventPlayer,
+};
+pub use stream::StreamedSample;
+pub use tone::{ToneHighpass, ToneLowpass};
+
+pub(crate) use available::FirstAvailablePlugin;
+pub(crate) use budget::SampleBudgetPlugin;
+pub(crate) use crossfade::LoopCrossfadePlugin;
+pub(crate) use intensity::IntensityPlugin;
+pub(crate) use loop_points::LoopPointsPlugin;
+pub(crate) use markers::MarkerPlugin;
+pub(crate) use pcm::PcmPlugin;
+pub(crate) use preload::PreloadPlugin;
+pub(crate) use region::PlaybackRegionPlugin;
+#[cfg(feature = "sound_events")]
+pub(crate) use sound_event::SoundEventPlugin;
+pub(crate) use tone::TonePlugin;
+
+/// Systems that rewrite a [`SamplePlayer`]'s sample before it's played.
+///
+/// Intensities may swap the sample, so this set runs after
+/// [`Intensity`] is applied and before samplers are acquired.
+#[derive(Debug, SystemSet, PartialEq, Eq, Hash, Clone)]
+pub(crate) struct SampleRewrites;
 
 /// A component that queues sample playback.
 ///
@@ -43,6 +99,24 @@ pub use assets::{AudioSample, SampleLoader, SampleLoaderError};
 /// playback. To update a sample's volume dynamically, consider adding a
 /// [`VolumeNode`][crate::prelude::VolumeNode] as an effect.
 ///
+/// ## Direct routing
+///
+/// A sample player can be connected like any other node. Its connections
+/// are made from the end of its assigned sampler's effects chain, in addition
+/// to the pool's own routing, and they follow the player if it's reassigned.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn record_dialogue(mut commands: Commands, server: Res<AssetServer>) {
+///     let tap = commands.spawn(VolumeNode::default()).id();
+///
+///     commands
+///         .spawn(SamplePlayer::new(server.load("line.wav")))
+///         .connect(tap);
+/// }
+/// ```
+///
 /// ## Lifecycle
 ///
 /// By default, entities with a [`SamplePlayer`] component are despawned when
@@ -68,6 +142,10 @@ pub use assets::{AudioSample, SampleLoader, SampleLoaderError};
 /// }
 /// ```
 ///
+/// Disabling a [`SamplePlayer`] entity with Bevy's
+/// [`Disabled`][bevy_ecs::entity_disabling::Disabled] component
+/// pauses its playback. Once re-enabled, playback resumes where it left off.
+///
 /// ## Applying effects
 ///
 /// Effects can be applied directly to a sample entity with
@@ -136,6 +214,11 @@ pub use assets::{AudioSample, SampleLoader, SampleLoaderError};
 /// - [`PlaybackSettings`]
 /// - [`SamplePriority`]
 /// - [`SampleQueueLifetime`]
+/// - [`MaxPlaybackDuration`]
+/// - [`MaxInstances`]
+/// - [`SampleCooldown`]
+/// - [`Intensity`]
+/// - [`LoopCrossfade`]
 /// - [`SampleEffects`][crate::prelude::SampleEffects]
 ///
 /// Altogether, that would look like:
@@ -242,6 +325,37 @@ impl SamplePlayer {
         }
     }
 
+    /// Play the sample once, then repeat it `times` more times.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// fn play_sound(mut commands: Commands, server: Res<AssetServer>) {
+    ///     // The sample plays three times in total.
+    ///     commands
+    ///         .spawn(SamplePlayer::new(server.load("my_sample.wav")).repeat(2))
+    ///         .observe(|_: On<PlaybackCompletionEvent>| info!("done repeating"));
+    /// }
+    /// ```
+    ///
+    /// A [`PlaybackCompletionEvent`][crate::prelude::PlaybackCompletionEvent]
+    /// is triggered once the final repetition finishes. Zero repetitions
+    /// plays the sample once. Like [`SamplePlayer::looping`], repetition
+    /// can only be configured once at the beginning of playback.
+    pub fn repeat(self, times: u32) -> Self {
+        let repeat_mode = match times {
+            0 => RepeatMode::PlayOnce,
+            num_times_to_repeat => RepeatMode::RepeatMultiple {
+                num_times_to_repeat,
+            },
+        };
+
+        Self {
+            repeat_mode,
+            ..self
+        }
+    }
+
     /// Set the overall sample volume.
     ///
     /// ```
@@ -322,6 +436,110 @@ impl Default for SampleQueueLifetime {
     }
 }
 
+/// The maximum duration a sample may play before it's forcibly stopped.
+///
+/// This is a safety net for samples that should never play indefinitely,
+/// like a looping sound whose owner forgot to stop it. The timer begins once
+/// the sample is assigned a sampler. If the sample is still playing when the
+/// duration elapses, it's stopped, triggering a
+/// [`PlaybackTimeoutEvent`][crate::prelude::PlaybackTimeoutEvent] followed by a
+/// [`PlaybackCompletionEvent`][crate::prelude::PlaybackCompletionEvent].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use std::time::Duration;
+/// fn play_alarm(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("alarm.wav")).looping(),
+///         MaxPlaybackDuration(Duration::from_secs(30)),
+///     ));
+/// }
+/// ```
+///
+/// Unlike [`SampleQueueLifetime`], this is not applied by default.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[component(immutable)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MaxPlaybackDuration(pub Duration);
+
+/// The maximum number of instances of a sample that may play at once.
+///
+/// This keeps sounds like explosions from stacking dozens of times in a
+/// single frame. Instances are counted across all [`SamplePlayer`]s with the
+/// same asset, whether they're playing or waiting for a sampler. When a new
+/// sample would exceed the limit, it's dropped, triggering a
+/// [`PlaybackCompletionEvent`][crate::prelude::PlaybackCompletionEvent]. With
+/// [`StealOldestInstance`], the oldest instance is stopped instead.
+/// A limit of zero drops every new sample, even with [`StealOldestInstance`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn explode(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("explosion.wav")),
+///         MaxInstances(4),
+///     ));
+/// }
+/// ```
+///
+/// [`MaxInstances`] can also be inserted on a [`SamplerPool`][crate::prelude::SamplerPool],
+/// limiting each sample played in it. A limit on the sample player takes precedence.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[component(immutable)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct MaxInstances(pub u32);
+
+/// Suppress repeated plays of the same sample within a window of time.
+///
+/// Sounds like UI clicks can be triggered many times in quick succession,
+/// producing a harsh, machine-gun effect. When a sample with a cooldown
+/// starts, any new [`SamplePlayer`] with the same asset and a cooldown
+/// in the same pool is dropped until the cooldown elapses, triggering a
+/// [`PlaybackCompletionEvent`][crate::prelude::PlaybackCompletionEvent].
+/// Players without a cooldown are never dropped.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use std::time::Duration;
+/// fn click(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("click.wav")),
+///         SampleCooldown(Duration::from_millis(50)),
+///     ));
+/// }
+/// ```
+///
+/// [`SampleCooldown`] can also be inserted on a [`SamplerPool`][crate::prelude::SamplerPool],
+/// applying to each sample played in it. A cooldown on the sample player takes precedence.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[component(immutable)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SampleCooldown(pub Duration);
+
+/// Stop the oldest instance of a sample, rather than dropping the new one,
+/// when its [`MaxInstances`] limit is reached.
+///
+/// Like [`MaxInstances`], this can be inserted on a sample player
+/// or a [`SamplerPool`][crate::prelude::SamplerPool].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn footstep(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("footstep.wav")),
+///         MaxInstances(2),
+///         StealOldestInstance,
+///     ));
+/// }
+/// ```
+#[derive(Debug, Default, Component, Clone, Copy)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct StealOldestInstance;
+
 /// Determines what happens when a sample completes playback.
 ///
 /// This will not trigger for looping samples unless they are stopped.
@@ -716,7 +934,7 @@ impl firewheel::diff::Patch for PlaybackSettings {
 pub struct QueuedSample;
 
 #[cfg(feature = "rand")]
-pub use random::{PitchRngSource, RandomPitch};
+pub use random::{PitchRngSource, RandomInterval, RandomPitch, RandomSample};
 
 #[cfg(feature = "rand")]
 pub(crate) use random::RandomPlugin;
@@ -725,8 +943,9 @@ pub(crate) use random::RandomPlugin;
 mod random {
     use crate::SeedlingSystems;
 
-    use super::PlaybackSettings;
+    use super::{AudioSample, Intensity, PlaybackSettings, SamplePlayer};
     use bevy_app::prelude::*;
+    use bevy_asset::prelude::*;
     use bevy_ecs::prelude::*;
     use rand::{SeedableRng, rngs::SmallRng};
 
@@ -735,12 +954,24 @@ mod random {
     impl Plugin for RandomPlugin {
         fn build(&self, app: &mut App) {
             app.insert_resource(PitchRngSource::new(SmallRng::from_os_rng()))
-                .add_systems(Last, RandomPitch::apply.before(SeedlingSystems::Acquire));
+                .add_systems(
+                    Last,
+                    (
+                        // Intensities scale the chosen sample and may swap it
+                        // for a variant, so we'll choose first.
+                        RandomSample::apply.before(Intensity::apply),
+                        RandomPitch::apply,
+                        RandomInterval::apply,
+                    )
+                        .before(SeedlingSystems::Acquire),
+                );
         }
     }
 
     trait PitchRng {
         fn gen_pitch(&mut self, range: std::ops::Range<f64>) -> f64;
+
+        fn gen_index(&mut self, len: usize) -> usize;
     }
 
     struct RandRng<T>(T);
@@ -749,9 +980,13 @@ mod random {
         fn gen_pitch(&mut self, range: std::ops::Range<f64>) -> f64 {
             self.0.random_range(range)
         }
+
+        fn gen_index(&mut self, len: usize) -> usize {
+            self.0.random_range(0..len)
+        }
     }
 
-    /// Provides the RNG source for the [`RandomPitch`] component.
+    /// Provides the RNG source for the [`RandomPitch`] and [`RandomInterval`] components.
     ///
     /// By default, this uses [`rand::rngs::SmallRng`]. To provide
     /// your own RNG source, simply insert this resource after
@@ -770,6 +1005,38 @@ mod random {
         pub fn new<T: rand::Rng + Send + Sync + 'static>(rng: T) -> Self {
             Self(Box::new(RandRng(rng)))
         }
+
+        /// Pick a value in `range`, or its start if it's empty.
+        #[cfg(feature = "sound_events")]
+        pub(crate) fn in_range(&mut self, range: core::ops::Range<f64>) -> f64 {
+            if range.is_empty() {
+                range.start
+            } else {
+                self.0.gen_pitch(range)
+            }
+        }
+
+        /// Pick an index with a probability proportional to its weight.
+        ///
+        /// Returns `None` if no weight is positive.
+        pub(crate) fn weighted_index(&mut self, weights: &[f32]) -> Option<usize> {
+            let total: f64 = weights.iter().map(|w| w.max(0.0) as f64).sum();
+            if total <= 0.0 {
+                return None;
+            }
+
+            let mut target = self.0.gen_pitch(0.0..total);
+            for (index, weight) in weights.iter().enumerate() {
+                let weight = weight.max(0.0) as f64;
+                if target < weight {
+                    return Some(index);
+                }
+                target -= weight;
+            }
+
+            // Rounding may leave the target just past the last weight.
+            weights.iter().rposition(|w| *w > 0.0)
+        }
     }
 
     /// A component that applies a random pitch to [`PlaybackSettings`] when spawned.
@@ -815,4 +1082,235 @@ mod random {
             }
         }
     }
+
+    /// A component that shifts the pitch of [`PlaybackSettings`] by a random
+    /// musical interval when spawned.
+    ///
+    /// Where [`RandomPitch`] picks any speed in a continuous range, this
+    /// picks from a fixed set of intervals, measured in semitones. This keeps
+    /// variations of melodic sounds, like stingers or creature calls, in tune.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// # fn intervals(mut commands: Commands, server: Res<AssetServer>) {
+    /// // Any semitone within a whole step of the original pitch.
+    /// commands.spawn((
+    ///     SamplePlayer::new(server.load("chirp.wav")),
+    ///     RandomInterval::semitones(-2..=2),
+    /// ));
+    ///
+    /// // The notes of a major pentatonic scale, up to an octave in either direction.
+    /// commands.spawn((
+    ///     SamplePlayer::new(server.load("stinger.wav")),
+    ///     RandomInterval::in_scale([0, 2, 4, 7, 9], -12..=12),
+    /// ));
+    /// # }
+    /// ```
+    ///
+    /// The chosen interval is applied relative to the [`PlaybackSettings::speed`]
+    /// the sample was spawned with. If no intervals are provided, the speed
+    /// is left unchanged.
+    ///
+    /// To control the RNG source, you can provide a custom [`PitchRngSource`] resource.
+    #[derive(Debug, Component, Default, Clone)]
+    #[require(PlaybackSettings)]
+    #[component(immutable)]
+    #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+    pub struct RandomInterval(pub Vec<f64>);
+
+    impl RandomInterval {
+        /// Choose from each semitone in `range`.
+        pub fn semitones(range: core::ops::RangeInclusive<i32>) -> Self {
+            Self(range.map(f64::from).collect())
+        }
+
+        /// Choose from the notes of a scale within a range of semitones.
+        ///
+        /// The scale's degrees are given in semitones above the root,
+        /// and are repeated in every octave. For example, `[0, 2, 4, 5, 7, 9, 11]`
+        /// is a major scale.
+        pub fn in_scale(
+            scale: impl IntoIterator<Item = i32>,
+            range: core::ops::RangeInclusive<i32>,
+        ) -> Self {
+            let degrees: Vec<_> = scale.into_iter().map(|d| d.rem_euclid(12)).collect();
+
+            Self(
+                range
+                    .filter(|semitone| degrees.contains(&semitone.rem_euclid(12)))
+                    .map(f64::from)
+                    .collect(),
+            )
+        }
+
+        fn apply(
+            mut samples: Query<(Entity, &mut PlaybackSettings, &Self)>,
+            mut commands: Commands,
+            mut rng: ResMut<PitchRngSource>,
+        ) {
+            for (entity, mut settings, intervals) in samples.iter_mut() {
+                if !intervals.0.is_empty() {
+                    let semitones = intervals.0[rng.0.gen_index(intervals.0.len())];
+                    settings.speed *= 2f64.powf(semitones / 12.0);
+                }
+                commands.entity(entity).remove::<Self>();
+            }
+        }
+    }
+
+    /// A component that picks one of several weighted samples to play when spawned.
+    ///
+    /// This removes the boilerplate of choosing between variations of
+    /// sounds like footsteps and impacts. Once a variant is chosen, it's
+    /// played with a [`SamplePlayer`] inserted on the same entity.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// # fn footstep(mut commands: Commands, server: Res<AssetServer>) {
+    /// commands.spawn((
+    ///     RandomSample::new([
+    ///         server.load("footstep_1.wav"),
+    ///         server.load("footstep_2.wav"),
+    ///         server.load("footstep_3.wav"),
+    ///     ]),
+    ///     RandomPitch::new(0.05),
+    /// ));
+    ///
+    /// // Crunchy footsteps are rarer.
+    /// commands.spawn(RandomSample(vec![
+    ///     (server.load("footstep_1.wav"), 4.0),
+    ///     (server.load("footstep_crunch.wav"), 1.0),
+    /// ]));
+    /// # }
+    /// ```
+    ///
+    /// If the entity already has a [`SamplePlayer`], only its sample is
+    /// replaced, keeping its other settings. Variants are chosen with
+    /// a probability proportional to their weight. If no weight is
+    /// positive, nothing is played.
+    ///
+    /// The variant is chosen before any [`Intensity`] is applied, so an
+    /// [`IntensityCurve`][crate::prelude::IntensityCurve] variant takes precedence.
+    ///
+    /// To control the RNG source, you can provide a custom [`PitchRngSource`] resource.
+    #[derive(Debug, Component, Default, Clone)]
+    #[component(immutable)]
+    #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+    pub struct RandomSample(pub Vec<(Handle<AudioSample>, f32)>);
+
+    impl RandomSample {
+        /// Choose evenly from `samples`.
+        pub fn new(samples: impl IntoIterator<Item = Handle<AudioSample>>) -> Self {
+            Self(samples.into_iter().map(|sample| (sample, 1.0)).collect())
+        }
+
+        fn apply(
+            samples: Query<(Entity, &Self, Option<&SamplePlayer>)>,
+            mut commands: Commands,
+            mut rng: ResMut<PitchRngSource>,
+        ) {
+            for (entity, variants, player) in samples.iter() {
+                let mut entity = commands.entity(entity);
+                entity.remove::<Self>();
+
+                let weights: Vec<_> = variants.0.iter().map(|(_, weight)| *weight).collect();
+                let Some(index) = rng.weighted_index(&weights) else {
+                    continue;
+                };
+
+                let sample = variants.0[index].0.clone();
+                entity.insert(match player {
+                    Some(player) => SamplePlayer {
+                        sample,
+                        ..player.clone()
+                    },
+                    None => SamplePlayer::new(sample),
+                });
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::test::{prepare_app, run};
+
+        #[test]
+        fn test_interval_scales() {
+            assert_eq!(
+                RandomInterval::semitones(-2..=2).0,
+                [-2.0, -1.0, 0.0, 1.0, 2.0]
+            );
+
+            // A major triad, repeated across octaves.
+            assert_eq!(
+                RandomInterval::in_scale([0, 4, 7], -12..=12).0,
+                [-12.0, -8.0, -5.0, 0.0, 4.0, 7.0, 12.0]
+            );
+        }
+
+        #[test]
+        fn test_weighted_index() {
+            let mut rng = PitchRngSource::new(SmallRng::seed_from_u64(0));
+
+            for _ in 0..16 {
+                assert_eq!(rng.weighted_index(&[0.0, 2.0, -1.0]), Some(1));
+            }
+            assert_eq!(rng.weighted_index(&[0.0, -1.0]), None);
+            assert_eq!(rng.weighted_index(&[]), None);
+        }
+
+        #[test]
+        fn test_random_sample() {
+            let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+                commands.spawn(RandomSample(vec![
+                    (server.load("caw.ogg"), 0.0),
+                    (server.load("sine_440hz_1ms.wav"), 1.0),
+                ]));
+            });
+
+            app.update();
+
+            run(
+                &mut app,
+                |player: Single<&SamplePlayer, Without<RandomSample>>, server: Res<AssetServer>| {
+                    assert_eq!(
+                        server.get_path(&player.sample).unwrap().path().to_str(),
+                        Some("sine_440hz_1ms.wav")
+                    );
+                },
+            );
+        }
+
+        #[test]
+        fn test_random_sample_intensity() {
+            let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+                commands.spawn((
+                    SamplePlayer::new(server.load("caw.ogg")),
+                    RandomSample::new([server.load("sine_440hz_1ms.wav")]),
+                    Intensity(0.0),
+                    crate::prelude::IntensityCurve::new(
+                        firewheel::Volume::Decibels(-20.0),
+                        firewheel::Volume::UNITY_GAIN,
+                    ),
+                ));
+            });
+
+            app.update();
+
+            // the intensity scales the randomly chosen sample
+            run(
+                &mut app,
+                |player: Single<&SamplePlayer, Without<Intensity>>, server: Res<AssetServer>| {
+                    assert_eq!(
+                        server.get_path(&player.sample).unwrap().path().to_str(),
+                        Some("sine_440hz_1ms.wav")
+                    );
+                    assert_eq!(player.volume, firewheel::Volume::Decibels(-20.0));
+                },
+            );
+        }
+    }
 }
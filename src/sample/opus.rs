@@ -0,0 +1,147 @@
+//! Ogg Opus decoding.
+//!
+//! Symphonia can demux Opus streams but can't decode them,
+//! so packets are decoded with `libopus` instead.
+
+use super::{SampleLoaderError, pcm::resample};
+use audiopus::{Channels, MutSignals, SampleRate, coder::Decoder, packet::Packet};
+use firewheel::sample_resource::SampleResource;
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+};
+use symphonia::core::{
+    codecs::CODEC_TYPE_OPUS, formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions,
+    probe::Hint,
+};
+
+/// The largest Opus frame, 120 ms at 48 kHz.
+const MAX_FRAME: usize = 5760;
+
+/// A fully decoded Opus sample.
+pub(super) struct OpusSample {
+    channels: Vec<Vec<f32>>,
+}
+
+impl OpusSample {
+    /// Decode an Ogg Opus stream.
+    ///
+    /// Returns `Ok(None)` if the bytes don't contain an Opus stream.
+    pub(super) fn decode(
+        bytes: &[u8],
+        hint: &Hint,
+        sample_rate: NonZeroU32,
+    ) -> Result<Option<Self>, SampleLoaderError> {
+        if !bytes.starts_with(b"OggS") {
+            return Ok(None);
+        }
+
+        let stream = MediaSourceStream::new(
+            Box::new(std::io::Cursor::new(bytes.to_vec())),
+            Default::default(),
+        );
+        let Ok(probed) = symphonia::default::get_probe().format(
+            hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        ) else {
+            return Ok(None);
+        };
+
+        let mut format = probed.format;
+        let Some(track) = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec == CODEC_TYPE_OPUS)
+        else {
+            return Ok(None);
+        };
+
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+        let channel_count = params.channels.map(|c| c.count()).unwrap_or_default();
+        let channels = match channel_count {
+            1 => Channels::Mono,
+            2 => Channels::Stereo,
+            count => {
+                return Err(SampleLoaderError::Opus(format!(
+                    "unsupported Opus channel count: {count}"
+                )));
+            }
+        };
+
+        // Opus decodes natively at several rates, so we only
+        // need to resample when the engine runs at another rate.
+        let native_rate = SampleRate::try_from(sample_rate.get() as i32).ok();
+        let decode_rate = native_rate.unwrap_or(SampleRate::Hz48000);
+        let decode_scale = decode_rate as u64 as f64 / 48000.0;
+
+        let mut decoder = Decoder::new(decode_rate, channels)?;
+
+        let mut interleaved = Vec::new();
+        let mut packet_buffer = vec![0.0; MAX_FRAME * channel_count];
+        while let Ok(packet) = format.next_packet() {
+            if packet.track_id() != track_id || packet.data.is_empty() {
+                continue;
+            }
+
+            let packet = Packet::try_from(&packet.data[..])?;
+            let output = MutSignals::try_from(&mut packet_buffer)?;
+            let frames = decoder.decode_float(Some(packet), output, false)?;
+            interleaved.extend_from_slice(&packet_buffer[..frames * channel_count]);
+        }
+
+        // The pre-skip and the stream's length are given at 48 kHz.
+        let frames = interleaved.len() / channel_count;
+        let skip = ((params.delay.unwrap_or(0) as f64 * decode_scale) as usize).min(frames);
+        let end = params
+            .n_frames
+            .map(|n| (skip + (n as f64 * decode_scale) as usize).min(frames))
+            .unwrap_or(frames);
+
+        let mut decoded = vec![Vec::with_capacity(end - skip); channel_count];
+        for frame in interleaved[skip * channel_count..end * channel_count].chunks(channel_count) {
+            for (channel, sample) in decoded.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+        }
+
+        if native_rate.is_none() {
+            let ratio = 48000.0 / sample_rate.get() as f64;
+            decoded = decoded
+                .iter()
+                .map(|channel| resample(channel, ratio))
+                .collect();
+        }
+
+        Ok(Some(Self { channels: decoded }))
+    }
+}
+
+impl SampleResource for OpusSample {
+    fn num_channels(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.channels.len()).unwrap()
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.channels.first().map(Vec::len).unwrap_or_default() as u64
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        let start_frame = start_frame as usize;
+
+        for (buffer, channel) in buffers.iter_mut().zip(&self.channels) {
+            let available = channel.len().saturating_sub(start_frame);
+            let frames = buffer_range.len().min(available);
+
+            buffer[buffer_range.start..buffer_range.start + frames]
+                .copy_from_slice(&channel[start_frame..start_frame + frames]);
+        }
+    }
+}
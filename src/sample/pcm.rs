@@ -0,0 +1,225 @@
+//! Samples built from raw PCM frames.
+
+use super::AudioSample;
+use crate::{SeedlingSystems, context::SampleRate};
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use firewheel::sample_resource::SampleResource;
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+};
+
+/// De-interleaved PCM frames held in memory.
+pub(super) struct PcmSample {
+    channels: Vec<Vec<f32>>,
+}
+
+impl PcmSample {
+    /// Split interleaved frames into channels.
+    ///
+    /// A trailing partial frame is dropped.
+    pub(super) fn from_interleaved(frames: &[f32], channels: NonZeroUsize) -> Self {
+        let mut split = vec![Vec::with_capacity(frames.len() / channels); channels.get()];
+        for frame in frames.chunks_exact(channels.get()) {
+            for (channel, sample) in split.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+        }
+
+        Self { channels: split }
+    }
+
+    /// Read a resource into memory, averaging its channels into one.
+    pub(super) fn downmixed(resource: &dyn SampleResource) -> Self {
+        let channels = read(resource);
+        let scale = 1.0 / channels.len() as f32;
+
+        let mut mono = channels[0].clone();
+        for channel in &channels[1..] {
+            for (mixed, sample) in mono.iter_mut().zip(channel) {
+                *mixed += sample;
+            }
+        }
+        for sample in &mut mono {
+            *sample *= scale;
+        }
+
+        Self {
+            channels: vec![mono],
+        }
+    }
+
+    /// Read a resource into memory, resampling it by `ratio`
+    /// source frames per output frame.
+    pub(super) fn resampled(resource: &dyn SampleResource, ratio: f64) -> Self {
+        Self {
+            channels: read(resource)
+                .iter()
+                .map(|channel| resample(channel, ratio))
+                .collect(),
+        }
+    }
+}
+
+/// Read every channel of a resource into memory.
+fn read(resource: &dyn SampleResource) -> Vec<Vec<f32>> {
+    let len = resource.len_frames() as usize;
+    let mut channels = vec![vec![0.0; len]; resource.num_channels().get()];
+
+    let mut buffers: Vec<&mut [f32]> = channels.iter_mut().map(Vec::as_mut_slice).collect();
+    resource.fill_buffers(&mut buffers, 0..len, 0);
+
+    channels
+}
+
+/// Resample with linear interpolation, where `ratio` is
+/// the number of source frames per output frame.
+pub(super) fn resample(source: &[f32], ratio: f64) -> Vec<f32> {
+    let len = (source.len() as f64 / ratio).ceil() as usize;
+
+    (0..len)
+        .map(|frame| {
+            let position = frame as f64 * ratio;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+
+            let a = source.get(index).copied().unwrap_or_default();
+            let b = source.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * fraction
+        })
+        .collect()
+}
+
+impl SampleResource for PcmSample {
+    fn num_channels(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.channels.len()).unwrap()
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.channels.first().map(Vec::len).unwrap_or_default() as u64
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        let start_frame = start_frame as usize;
+
+        for (buffer, channel) in buffers.iter_mut().zip(&self.channels) {
+            let available = channel.len().saturating_sub(start_frame);
+            let frames = buffer_range.len().min(available);
+
+            buffer[buffer_range.start..buffer_range.start + frames]
+                .copy_from_slice(&channel[start_frame..start_frame + frames]);
+        }
+    }
+}
+
+/// Adds samples built from raw PCM frames to [`Assets<AudioSample>`].
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// # use std::num::{NonZeroU32, NonZeroUsize};
+/// fn blip(mut commands: Commands, mut samples: ResMut<Assets<AudioSample>>) {
+///     // A tenth of a second of a 660 Hz sine.
+///     let frames = (0..4800)
+///         .map(|i| (i as f32 * 660.0 / 48000.0 * core::f32::consts::TAU).sin() * 0.25)
+///         .collect();
+///
+///     let rate = NonZeroU32::new(48000).unwrap();
+///
+///     commands.spawn(SamplePlayer::new(samples.add_frames(frames, NonZeroUsize::MIN, rate)));
+/// }
+/// ```
+pub trait AddSampleFrames {
+    /// Add a sample from interleaved frames.
+    ///
+    /// See [`AudioSample::from_frames`] for details.
+    fn add_frames(
+        &mut self,
+        frames: Vec<f32>,
+        channels: NonZeroUsize,
+        sample_rate: NonZeroU32,
+    ) -> Handle<AudioSample>;
+}
+
+impl AddSampleFrames for Assets<AudioSample> {
+    fn add_frames(
+        &mut self,
+        frames: Vec<f32>,
+        channels: NonZeroUsize,
+        sample_rate: NonZeroU32,
+    ) -> Handle<AudioSample> {
+        self.add(AudioSample::from_frames(frames, channels, sample_rate))
+    }
+}
+
+pub(crate) struct PcmPlugin;
+
+impl Plugin for PcmPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, resample_frames.before(SeedlingSystems::Acquire));
+    }
+}
+
+/// Bring samples built at another rate to the engine's.
+fn resample_frames(
+    mut events: MessageReader<AssetEvent<AudioSample>>,
+    mut assets: ResMut<Assets<AudioSample>>,
+    sample_rate: Res<SampleRate>,
+) {
+    let target = sample_rate.get();
+
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+
+        let Some(resampled) = assets.get(*id).and_then(|s| s.resampled(target)) else {
+            continue;
+        };
+
+        if let Some(sample) = assets.get_mut(*id) {
+            *sample = resampled;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let source = [0.0, 1.0, 2.0, 3.0];
+
+        assert_eq!(
+            resample(&source, 0.5),
+            [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.0]
+        );
+        assert_eq!(resample(&source, 2.0), [0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let sample =
+            PcmSample::from_interleaved(&[0.0, 1.0, 2.0, 3.0, 4.0], NonZeroUsize::new(2).unwrap());
+
+        assert_eq!(sample.num_channels().get(), 2);
+        assert_eq!(sample.len_frames(), 2);
+
+        let mut left = [0.0; 2];
+        let mut right = [0.0; 2];
+        sample.fill_buffers(&mut [&mut left, &mut right], 0..2, 0);
+        assert_eq!((left, right), ([0.0, 2.0], [1.0, 3.0]));
+
+        let mono = PcmSample::downmixed(&sample);
+        assert_eq!(mono.num_channels().get(), 1);
+        assert_eq!(mono.channels[0], [0.5, 2.5]);
+    }
+}
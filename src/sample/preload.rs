@@ -0,0 +1,246 @@
+use super::AudioSample;
+use bevy_app::prelude::*;
+use bevy_asset::{LoadState, LoadedFolder, RecursiveDependencyLoadState, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_log::prelude::*;
+use bevy_state::{prelude::*, state::FreelyMutableState};
+
+/// A set of samples that must finish loading before gameplay starts.
+///
+/// Samples that are still loading when a [`SamplePlayer`][super::SamplePlayer]
+/// is spawned begin playback late, skipping ahead to keep their scheduled
+/// timing. Adding them to the preload set ahead of time, typically during
+/// a loading screen, ensures they're fully decoded and ready to play.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn queue_audio(mut preload: ResMut<AudioPreloadSet>, server: Res<AssetServer>) {
+///     preload.add(server.load("sfx/jump.wav"));
+///     preload.add(server.load("music/level_one.ogg"));
+/// }
+///
+/// fn loading_bar(preload: Res<AudioPreloadSet>) {
+///     info!("audio {:.0}% loaded", preload.progress() * 100.0);
+/// }
+/// ```
+///
+/// With [`PreloadAudioState`], a loading state can be left automatically
+/// once every sample is ready.
+///
+/// Samples failing to load count towards progress, so a missing file
+/// never stalls the game. The set holds strong handles, keeping its
+/// samples in memory until it's [cleared][AudioPreloadSet::clear].
+#[derive(Resource, Debug, Default)]
+pub struct AudioPreloadSet {
+    samples: Vec<Handle<AudioSample>>,
+    folders: Vec<Handle<LoadedFolder>>,
+    loaded: usize,
+    failed: usize,
+}
+
+impl AudioPreloadSet {
+    /// Add a sample to the set.
+    pub fn add(&mut self, sample: Handle<AudioSample>) -> &mut Self {
+        self.samples.push(sample);
+        self
+    }
+
+    /// Add every sample in a folder to the set.
+    ///
+    /// The folder's samples are added once its listing has loaded.
+    /// Files other than samples are ignored.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// fn queue_folder(mut preload: ResMut<AudioPreloadSet>, server: Res<AssetServer>) {
+    ///     preload.add_folder(server.load_folder("sfx/ui"));
+    /// }
+    /// ```
+    ///
+    /// Folders can't be loaded on the web.
+    pub fn add_folder(&mut self, folder: Handle<LoadedFolder>) -> &mut Self {
+        self.folders.push(folder);
+        self
+    }
+
+    /// The number of samples in the set.
+    ///
+    /// This grows as folders finish loading.
+    pub fn total(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// The number of samples that are loaded and ready to play.
+    pub fn loaded(&self) -> usize {
+        self.loaded
+    }
+
+    /// The number of samples that failed to load.
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// The fraction of the set that has finished loading, from 0 to 1.
+    ///
+    /// Pending folders hold progress below 1.
+    pub fn progress(&self) -> f32 {
+        let finished = self.loaded + self.failed;
+        let total = self.samples.len() + self.folders.len();
+
+        if total == 0 {
+            1.0
+        } else {
+            finished as f32 / total as f32
+        }
+    }
+
+    /// Returns `true` once every sample has finished loading.
+    pub fn is_ready(&self) -> bool {
+        self.folders.is_empty() && self.loaded + self.failed == self.samples.len()
+    }
+
+    /// Release every handle in the set.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+pub(crate) struct PreloadPlugin;
+
+impl Plugin for PreloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioPreloadSet>()
+            .add_systems(PreUpdate, update_preload);
+    }
+}
+
+fn update_preload(
+    mut set: ResMut<AudioPreloadSet>,
+    server: Res<AssetServer>,
+    samples: Res<Assets<AudioSample>>,
+    folders: Res<Assets<LoadedFolder>>,
+) {
+    if set.is_ready() {
+        return;
+    }
+
+    let set = set.as_mut();
+
+    let mut expanded = Vec::new();
+    set.folders.retain(
+        |folder| match server.recursive_dependency_load_state(folder) {
+            RecursiveDependencyLoadState::Loaded => {
+                if let Some(folder) = folders.get(folder) {
+                    expanded.extend(
+                        folder
+                            .handles
+                            .iter()
+                            .filter_map(|h| h.clone().try_typed::<AudioSample>().ok()),
+                    );
+                }
+                false
+            }
+            RecursiveDependencyLoadState::Failed(e) => {
+                warn!("failed to load audio preload folder: {e}");
+                false
+            }
+            _ => true,
+        },
+    );
+    set.samples.extend(expanded);
+
+    // Samples added directly to the asset storage are never
+    // tracked by the server, so we check the storage first.
+    set.loaded = 0;
+    set.failed = 0;
+    for sample in &set.samples {
+        if samples.contains(sample) {
+            set.loaded += 1;
+        } else if matches!(server.load_state(sample), LoadState::Failed(_)) {
+            set.failed += 1;
+        }
+    }
+}
+
+/// Leave a loading state once the [`AudioPreloadSet`] is ready.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// enum GameState {
+///     #[default]
+///     Loading,
+///     Playing,
+/// }
+///
+/// fn plugin(app: &mut App) {
+///     app.init_state::<GameState>()
+///         .preload_audio_during(GameState::Loading, GameState::Playing)
+///         .add_systems(OnEnter(GameState::Loading), queue_audio);
+/// }
+///
+/// fn queue_audio(mut preload: ResMut<AudioPreloadSet>, server: Res<AssetServer>) {
+///     preload.add(server.load("music/level_one.ogg"));
+/// }
+/// ```
+pub trait PreloadAudioState {
+    /// Transition from `loading` to `next` once every sample in the
+    /// [`AudioPreloadSet`] has finished loading.
+    fn preload_audio_during<S: FreelyMutableState>(&mut self, loading: S, next: S) -> &mut Self;
+}
+
+impl PreloadAudioState for App {
+    fn preload_audio_during<S: FreelyMutableState>(&mut self, loading: S, next: S) -> &mut Self {
+        self.add_systems(
+            Update,
+            (move |set: Res<AudioPreloadSet>, mut state: ResMut<NextState<S>>| {
+                if set.is_ready() {
+                    state.set(next.clone());
+                }
+            })
+            .run_if(in_state(loading)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        prelude::*,
+        test::{prepare_app, run_until},
+    };
+
+    #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+    enum Phase {
+        #[default]
+        Loading,
+        Playing,
+    }
+
+    #[test]
+    fn test_preload() {
+        let mut app = prepare_app(|| {});
+        app.init_state::<Phase>()
+            .preload_audio_during(Phase::Loading, Phase::Playing);
+
+        let sample = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<AudioSample>("sine_440hz_1ms.wav");
+        app.world_mut()
+            .resource_mut::<AudioPreloadSet>()
+            .add(sample);
+
+        run_until(&mut app, |phase: Res<State<Phase>>| {
+            *phase.get() == Phase::Playing
+        });
+
+        let set = app.world().resource::<AudioPreloadSet>();
+        assert_eq!((set.total(), set.loaded(), set.failed()), (1, 1, 0));
+        assert_eq!(set.progress(), 1.0);
+    }
+}
@@ -0,0 +1,333 @@
+use super::{AudioSample, PlaybackSettings, SampleAssets, SamplePlayer};
+use crate::pool::{
+    growth::WarmPool,
+    label::{InternedPoolLabel, PoolLabel, PoolLabelContainer},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AssetPath, prelude::*};
+use bevy_ecs::{prelude::*, system::EntityCommands};
+use bevy_platform::collections::HashMap;
+use bevy_state::prelude::*;
+use firewheel::nodes::sampler::{PlaybackState, SamplerConfig};
+use std::sync::Arc;
+
+/// Declares the audio a state needs.
+///
+/// Registering an [`AudioForState`] with [`RegisterStateAudio`] lets
+/// `bevy_seedling` prepare a state's audio ahead of time. When
+/// [`PrewarmAudio`] is triggered for the state, its samples begin loading,
+/// its pools are grown, and its music is queued in a paused sampler. When
+/// the state is entered, the music starts immediately.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// enum GameState {
+///     #[default]
+///     Exploring,
+///     Boss,
+/// }
+///
+/// fn plugin(app: &mut App) {
+///     app.register_state_audio(
+///         AudioForState::new(GameState::Boss)
+///             .with_sample("boss/roar.wav")
+///             .with_sample("boss/slam.wav")
+///             .with_pool(DefaultPool, 24)
+///             .with_music("boss/theme.ogg", MusicPool),
+///     );
+/// }
+///
+/// // As the player approaches the arena, get everything ready.
+/// fn approach_arena(mut commands: Commands) {
+///     commands.trigger(PrewarmAudio(GameState::Boss));
+/// }
+/// ```
+///
+/// Entering a state that hasn't been prewarmed prepares its
+/// audio on the spot. When the state is exited, its samples are
+/// released and its music is despawned.
+///
+/// Paths are loaded through [`SampleAssets`], so they follow
+/// the app's [`SampleFormats`][super::SampleFormats].
+pub struct AudioForState<S: States> {
+    state: S,
+    samples: Vec<AssetPath<'static>>,
+    pools: Vec<(InternedPoolLabel, usize)>,
+    music: Option<StateMusicSource>,
+}
+
+struct StateMusicSource {
+    path: AssetPath<'static>,
+    insert_pool: Arc<dyn Fn(&mut EntityCommands) + Send + Sync>,
+}
+
+impl<S: States> core::fmt::Debug for AudioForState<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AudioForState")
+            .field("state", &self.state)
+            .field("samples", &self.samples)
+            .field("pools", &self.pools)
+            .field("music", &self.music.as_ref().map(|m| &m.path))
+            .finish()
+    }
+}
+
+impl<S: States> AudioForState<S> {
+    /// Declare the audio for `state`.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            samples: Vec::new(),
+            pools: Vec::new(),
+            music: None,
+        }
+    }
+
+    /// Load a sample when prewarming.
+    ///
+    /// The sample is kept loaded until the state is exited.
+    pub fn with_sample(mut self, path: impl Into<AssetPath<'static>>) -> Self {
+        self.samples.push(path.into());
+        self
+    }
+
+    /// Grow the pool labeled `pool` to at least `samplers` when prewarming.
+    ///
+    /// See [`WarmPool`] for details.
+    pub fn with_pool(mut self, pool: impl PoolLabel, samplers: usize) -> Self {
+        self.pools.push((pool.intern(), samplers));
+        self
+    }
+
+    /// Queue a looping music track in `pool` when prewarming,
+    /// starting it when the state is entered.
+    pub fn with_music<T: PoolLabel + Component + Clone>(
+        mut self,
+        path: impl Into<AssetPath<'static>>,
+        pool: T,
+    ) -> Self {
+        self.music = Some(StateMusicSource {
+            path: path.into(),
+            insert_pool: Arc::new(move |commands: &mut EntityCommands| {
+                commands.insert(pool.clone());
+            }),
+        });
+        self
+    }
+
+    /// The state this audio belongs to.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+}
+
+/// Prepare the audio registered for a state.
+///
+/// This should be triggered globally.
+#[derive(Event, Debug, Clone)]
+pub struct PrewarmAudio<S: States>(pub S);
+
+/// A music track spawned for a state's [`AudioForState`].
+///
+/// This is despawned when the state is exited.
+#[derive(Component, Debug, Clone)]
+pub struct StateMusic<S: States>(pub S);
+
+#[derive(Resource)]
+struct StateAudioRegistry<S: States> {
+    entries: Vec<AudioForState<S>>,
+    loaded: HashMap<S, Vec<Handle<AudioSample>>>,
+}
+
+impl<S: States> Default for StateAudioRegistry<S> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            loaded: HashMap::default(),
+        }
+    }
+}
+
+/// Register [`AudioForState`] declarations.
+pub trait RegisterStateAudio {
+    /// Register the audio for a state.
+    ///
+    /// Registering multiple declarations for the same state is allowed.
+    fn register_state_audio<S: States>(&mut self, audio: AudioForState<S>) -> &mut Self;
+}
+
+impl RegisterStateAudio for App {
+    fn register_state_audio<S: States>(&mut self, audio: AudioForState<S>) -> &mut Self {
+        let world = self.world_mut();
+        if !world.contains_resource::<StateAudioRegistry<S>>() {
+            world.init_resource::<StateAudioRegistry<S>>();
+            self.add_observer(prewarm_state_audio::<S>);
+        }
+
+        let state = audio.state.clone();
+        let mut registry = self.world_mut().resource_mut::<StateAudioRegistry<S>>();
+        let first = !registry.entries.iter().any(|e| e.state == state);
+        registry.entries.push(audio);
+
+        if first {
+            let enter = state.clone();
+            let exit = state.clone();
+
+            self.add_systems(
+                OnEnter(state.clone()),
+                move |music: Query<(&StateMusic<S>, &mut PlaybackSettings)>,
+                      mut commands: Commands| {
+                    enter_state_audio(&enter, music, &mut commands)
+                },
+            )
+            .add_systems(
+                OnExit(state),
+                move |registry: ResMut<StateAudioRegistry<S>>,
+                      music: Query<(Entity, &StateMusic<S>)>,
+                      commands: Commands| {
+                    exit_state_audio(&exit, registry, music, commands)
+                },
+            );
+        }
+
+        self
+    }
+}
+
+fn prewarm_state_audio<S: States>(
+    trigger: On<PrewarmAudio<S>>,
+    mut registry: ResMut<StateAudioRegistry<S>>,
+    current: Option<Res<State<S>>>,
+    pools: Query<(Entity, &PoolLabelContainer), (With<SamplerConfig>, Without<SamplePlayer>)>,
+    music: Query<&StateMusic<S>>,
+    samples: SampleAssets,
+    mut commands: Commands,
+) {
+    let state = &trigger.event().0;
+    let registry = registry.as_mut();
+
+    let already_loaded = registry.loaded.contains_key(state);
+    let has_music = music.iter().any(|m| &m.0 == state);
+    let is_active = current.is_some_and(|c| c.get() == state);
+
+    let mut handles = Vec::new();
+    for entry in registry.entries.iter().filter(|e| &e.state == state) {
+        if !already_loaded {
+            handles.extend(entry.samples.iter().map(|path| samples.load(path.clone())));
+        }
+
+        for (label, samplers) in &entry.pools {
+            for (pool, _) in pools.iter().filter(|(_, l)| l.label == *label) {
+                commands.entity(pool).insert(WarmPool(*samplers));
+            }
+        }
+
+        if let Some(source) = entry.music.as_ref().filter(|_| !has_music) {
+            let playback = if is_active {
+                PlaybackState::Play { playhead: None }
+            } else {
+                PlaybackState::Pause
+            };
+
+            let mut music = commands.spawn((
+                StateMusic(state.clone()),
+                SamplePlayer::new(samples.load(source.path.clone())).looping(),
+                PlaybackSettings::default().with_playback(playback),
+            ));
+            (source.insert_pool)(&mut music);
+        }
+    }
+
+    if !already_loaded {
+        registry.loaded.insert(state.clone(), handles);
+    }
+}
+
+fn enter_state_audio<S: States>(
+    state: &S,
+    mut music: Query<(&StateMusic<S>, &mut PlaybackSettings)>,
+    commands: &mut Commands,
+) {
+    for (_, mut settings) in music.iter_mut().filter(|(m, _)| &m.0 == state) {
+        settings.play();
+    }
+
+    // Anything not yet prewarmed is prepared immediately.
+    commands.trigger(PrewarmAudio(state.clone()));
+}
+
+fn exit_state_audio<S: States>(
+    state: &S,
+    mut registry: ResMut<StateAudioRegistry<S>>,
+    music: Query<(Entity, &StateMusic<S>)>,
+    mut commands: Commands,
+) {
+    registry.loaded.remove(state);
+
+    for (entity, _) in music.iter().filter(|(_, m)| &m.0 == state) {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        pool::Sampler,
+        prelude::*,
+        test::{prepare_app, run, run_until},
+    };
+
+    #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+    enum GameState {
+        #[default]
+        Exploring,
+        Boss,
+    }
+
+    #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct TestPool;
+
+    #[test]
+    fn test_prewarm() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn((SamplerPool(TestPool), PoolSize(1..=8)));
+            commands
+                .spawn((VolumeNode::default(), MainBus))
+                .connect(AudioGraphOutput);
+        });
+
+        let mut registry = StateAudioRegistry::<GameState>::default();
+        registry.entries.push(
+            AudioForState::new(GameState::Boss)
+                .with_sample("sine_440hz_1ms.wav")
+                .with_pool(TestPool, 4)
+                .with_music("caw.ogg", TestPool),
+        );
+        app.insert_resource(registry)
+            .add_observer(prewarm_state_audio::<GameState>);
+
+        // prewarming is idempotent
+        app.world_mut().trigger(PrewarmAudio(GameState::Boss));
+        app.update();
+        app.world_mut().trigger(PrewarmAudio(GameState::Boss));
+
+        run_until(
+            &mut app,
+            |music: Query<&StateMusic<GameState>, With<Sampler>>| music.iter().len() == 1,
+        );
+
+        run(
+            &mut app,
+            |registry: Res<StateAudioRegistry<GameState>>,
+             music: Single<&PlaybackSettings, With<StateMusic<GameState>>>,
+             samplers: Query<&SamplerNode>| {
+                assert_eq!(registry.loaded[&GameState::Boss].len(), 1);
+                assert!(matches!(*music.playback, PlaybackState::Pause));
+                assert_eq!(samplers.iter().len(), 4);
+            },
+        );
+    }
+}
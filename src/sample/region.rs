@@ -0,0 +1,260 @@
+use super::{AudioSample, LoopPoints, QueuedSample, SampleMarker, SamplePlayer, SampleRewrites};
+use crate::context::SampleRate;
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::HashMap;
+use core::{
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+};
+use firewheel::{collector::ArcGc, nodes::sampler::Playhead, sample_resource::SampleResource};
+
+pub(crate) struct PlaybackRegionPlugin;
+
+impl Plugin for PlaybackRegionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SlicedSamples>().add_systems(
+            Last,
+            PlaybackRegion::apply
+                .in_set(SampleRewrites)
+                .before(super::loop_points::apply_loop_points),
+        );
+    }
+}
+
+/// Play only a slice of a [`SamplePlayer`]'s sample.
+///
+/// Many sound effects are often packed into a single file, an audio atlas.
+/// With [`PlaybackRegion`], each effect can be played from the shared
+/// sample without editing or splitting the file.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_footstep(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SamplePlayer::new(server.load("sfx_atlas.wav")),
+///         PlaybackRegion::seconds(1.25, 1.5),
+///     ));
+/// }
+/// ```
+///
+/// The region is treated as a sample of its own, so looping repeats only
+/// the region, and [`LoopRegion`][super::LoopRegion] and playheads are
+/// relative to its start. Once applied, [`SamplePlayer::sample`] is replaced
+/// with the slice and this component is removed.
+///
+/// Slices are shared between players with the same sample and region.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct PlaybackRegion {
+    /// The start of the region.
+    pub start: Playhead,
+    /// The end of the region, exclusive.
+    ///
+    /// If `None`, the region extends to the end of the sample.
+    pub end: Option<Playhead>,
+}
+
+impl PlaybackRegion {
+    /// Create a playback region in seconds.
+    pub fn seconds(start: f64, end: f64) -> Self {
+        Self {
+            start: Playhead::Seconds(start),
+            end: Some(Playhead::Seconds(end)),
+        }
+    }
+
+    /// Create a playback region in frames.
+    pub fn frames(start: u64, end: u64) -> Self {
+        Self {
+            start: Playhead::Frames(start),
+            end: Some(Playhead::Frames(end)),
+        }
+    }
+
+    /// Skip the beginning of the sample, playing through to the end.
+    pub fn starting_at(start: Playhead) -> Self {
+        Self { start, end: None }
+    }
+
+    fn apply(
+        samples: Query<(Entity, &SamplePlayer, &Self), With<QueuedSample>>,
+        mut assets: ResMut<Assets<AudioSample>>,
+        mut slices: ResMut<SlicedSamples>,
+        sample_rate: Res<SampleRate>,
+        mut commands: Commands,
+    ) {
+        for (entity, player, region) in &samples {
+            let rate = sample_rate.get();
+            let start = playhead_frames(region.start, rate);
+            let end = region.end.map(|end| playhead_frames(end, rate));
+            let key = (player.sample.id(), start, end);
+
+            let existing = slices
+                .0
+                .get(&key)
+                .and_then(|id| assets.get_strong_handle(*id));
+
+            let sample = match existing {
+                Some(sample) => sample,
+                None => {
+                    // Wait for the source to finish loading.
+                    let Some(source) = assets.get(&player.sample) else {
+                        continue;
+                    };
+
+                    let slice = SampleSlice::new(source.get(), start, end);
+
+                    // Loop points inside the slice are kept.
+                    let loop_points = source.loop_points().and_then(|points| {
+                        let points = LoopPoints {
+                            start: points.start.checked_sub(slice.start)?,
+                            end: points.end.checked_sub(slice.start)?.min(slice.len),
+                        };
+                        (points.start < points.end).then_some(points)
+                    });
+
+                    // As are markers, relative to the slice's start.
+                    let (start, len) = (slice.start, slice.len);
+                    let markers = source.markers().iter().filter_map(move |marker| {
+                        let frame = marker.frame.checked_sub(start)?;
+                        (frame < len).then(|| SampleMarker {
+                            frame,
+                            ..marker.clone()
+                        })
+                    });
+
+                    let mut sliced = AudioSample::new(slice).with_markers(markers);
+                    if let Some(points) = loop_points {
+                        sliced = sliced.with_loop_points(points);
+                    }
+
+                    let handle = assets.add(sliced);
+                    slices.0.insert(key, handle.id());
+
+                    handle
+                }
+            };
+
+            commands
+                .entity(entity)
+                .insert(SamplePlayer {
+                    sample,
+                    ..player.clone()
+                })
+                .remove::<Self>();
+        }
+
+        slices.0.retain(|_, id| assets.contains(*id));
+    }
+}
+
+/// Convert a playhead to frames at the given sample rate.
+pub(super) fn playhead_frames(playhead: Playhead, sample_rate: NonZeroU32) -> u64 {
+    match playhead {
+        Playhead::Seconds(seconds) => (seconds.max(0.0) * sample_rate.get() as f64).round() as u64,
+        Playhead::Frames(frames) => frames,
+    }
+}
+
+/// Previously sliced samples, keyed by source and region in frames.
+#[derive(Resource, Default)]
+struct SlicedSamples(HashMap<(AssetId<AudioSample>, u64, Option<u64>), AssetId<AudioSample>>);
+
+/// A region of another sample.
+struct SampleSlice {
+    source: ArcGc<dyn SampleResource>,
+    start: u64,
+    len: u64,
+}
+
+impl SampleSlice {
+    fn new(source: ArcGc<dyn SampleResource>, start: u64, end: Option<u64>) -> Self {
+        let source_len = source.len_frames();
+        let start = start.min(source_len);
+        let end = end.unwrap_or(source_len).clamp(start, source_len);
+
+        Self {
+            source,
+            start,
+            len: end - start,
+        }
+    }
+}
+
+impl SampleResource for SampleSlice {
+    fn num_channels(&self) -> NonZeroUsize {
+        self.source.num_channels()
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.len
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        let available = self.len.saturating_sub(start_frame);
+        let frames = buffer_range.len().min(available as usize);
+        let split = buffer_range.start + frames;
+
+        if frames > 0 {
+            self.source
+                .fill_buffers(buffers, buffer_range.start..split, self.start + start_frame);
+        }
+
+        for buffer in buffers.iter_mut() {
+            buffer[split..buffer_range.end].fill(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    struct Ramp(u64);
+
+    impl SampleResource for Ramp {
+        fn num_channels(&self) -> NonZeroUsize {
+            NonZeroUsize::MIN
+        }
+
+        fn len_frames(&self) -> u64 {
+            self.0
+        }
+
+        fn fill_buffers(
+            &self,
+            buffers: &mut [&mut [f32]],
+            buffer_range: Range<usize>,
+            start_frame: u64,
+        ) {
+            for (i, sample) in buffers[0][buffer_range].iter_mut().enumerate() {
+                *sample = (start_frame + i as u64) as f32;
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample_slice() {
+        let source = ArcGc::new_unsized(|| Arc::new(Ramp(10)) as Arc<dyn SampleResource>);
+
+        let slice = SampleSlice::new(source.clone(), 3, Some(6));
+        assert_eq!(slice.len_frames(), 3);
+
+        let mut out = [-1.0; 5];
+        slice.fill_buffers(&mut [out.as_mut_slice()], 0..5, 1);
+        assert_eq!(out, [4., 5., 0., 0., 0.]);
+
+        // The end is clamped to the source.
+        let slice = SampleSlice::new(source, 8, Some(20));
+        assert_eq!(slice.len_frames(), 2);
+    }
+}
@@ -0,0 +1,328 @@
+//! Data-driven sound definitions.
+
+use super::{AudioSample, PitchRngSource, PlaybackSettings, SamplePlayer, SamplePriority};
+use crate::{SeedlingSystems, pool::label::PoolLabel, prelude::Volume};
+use bevy_app::prelude::*;
+use bevy_asset::{AssetLoader, LoadContext, prelude::*};
+use bevy_ecs::{prelude::*, system::EntityCommands};
+use bevy_log::prelude::*;
+use bevy_platform::collections::HashMap;
+use bevy_reflect::TypePath;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A sound's variations and playback settings, authored as data.
+///
+/// Sound events are loaded from `.sound.ron` and `.sound.json` files,
+/// so designers can tweak variations without recompiling. Every field
+/// besides `samples` is optional.
+///
+/// ```ron
+/// (
+///     samples: [
+///         (path: "sfx/footstep_1.wav"),
+///         (path: "sfx/footstep_2.wav"),
+///         // Played twice as often as the others.
+///         (path: "sfx/footstep_3.wav", weight: 2.0),
+///     ],
+///     // A random volume between -6 and 0 decibels.
+///     volume: (-6.0, 0.0),
+///     // A random speed between 0.95 and 1.05.
+///     speed: (0.95, 1.05),
+///     pool: Some("sfx"),
+///     priority: 0,
+///     looping: false,
+/// )
+/// ```
+///
+/// Sound events are played with a [`SoundEventPlayer`].
+/// Pools are referenced by the names given to them with
+/// [`RegisterSoundPool::register_sound_pool`].
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct SoundEvent {
+    /// The sample variations and their weights.
+    pub samples: Vec<(Handle<AudioSample>, f32)>,
+    /// The range of volumes to pick from, in decibels.
+    pub volume: (f32, f32),
+    /// The range of playback speeds to pick from.
+    pub speed: (f64, f64),
+    /// The name of the pool to play in.
+    ///
+    /// Without a pool, the sample plays in the [`DefaultPool`][crate::prelude::DefaultPool].
+    pub pool: Option<String>,
+    /// The [`SamplePriority`] of each play.
+    pub priority: i32,
+    /// Whether the sample loops.
+    pub looping: bool,
+}
+
+/// The serialized form of a [`SoundEvent`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SoundEventDefinition {
+    samples: Vec<SoundVariant>,
+    #[serde(default)]
+    volume: (f32, f32),
+    #[serde(default = "unity_speed")]
+    speed: (f64, f64),
+    #[serde(default)]
+    pool: Option<String>,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    looping: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SoundVariant {
+    path: String,
+    #[serde(default = "unity_weight")]
+    weight: f32,
+}
+
+fn unity_speed() -> (f64, f64) {
+    (1.0, 1.0)
+}
+
+fn unity_weight() -> f32 {
+    1.0
+}
+
+impl SoundEventDefinition {
+    fn into_event(self, load_context: &mut LoadContext<'_>) -> SoundEvent {
+        SoundEvent {
+            samples: self
+                .samples
+                .into_iter()
+                .map(|variant| (load_context.load(variant.path), variant.weight))
+                .collect(),
+            volume: self.volume,
+            speed: self.speed,
+            pool: self.pool,
+            priority: self.priority,
+            looping: self.looping,
+        }
+    }
+}
+
+/// A loader for [`SoundEvent`] definitions.
+#[derive(Debug, Default)]
+pub struct SoundEventLoader;
+
+/// Errors produced while loading sound events.
+#[derive(Debug)]
+pub enum SoundEventLoaderError {
+    /// An I/O error, such as missing files.
+    StdIo(std::io::Error),
+    /// The RON definition is malformed.
+    Ron(ron::error::SpannedError),
+    /// The JSON definition is malformed.
+    Json(serde_json::Error),
+}
+
+impl From<std::io::Error> for SoundEventLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        Self::StdIo(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for SoundEventLoaderError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::Ron(value)
+    }
+}
+
+impl From<serde_json::Error> for SoundEventLoaderError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl std::error::Error for SoundEventLoaderError {}
+
+impl std::fmt::Display for SoundEventLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StdIo(stdio) => stdio.fmt(f),
+            Self::Ron(ron) => ron.fmt(f),
+            Self::Json(json) => json.fmt(f),
+        }
+    }
+}
+
+impl AssetLoader for SoundEventLoader {
+    type Asset = SoundEvent;
+    type Settings = ();
+    type Error = SoundEventLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy_asset::io::Reader,
+        _: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let is_json = load_context
+            .path()
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+
+        let definition: SoundEventDefinition = if is_json {
+            serde_json::from_slice(&bytes)?
+        } else {
+            ron::de::from_bytes(&bytes)?
+        };
+
+        Ok(definition.into_event(load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sound.ron", "sound.json"]
+    }
+}
+
+/// Plays a [`SoundEvent`].
+///
+/// Once the sound event has loaded, a variation is picked and
+/// a [`SamplePlayer`] is inserted on this entity with the event's
+/// volume, speed, pool, and priority. The entity then follows the
+/// [`SamplePlayer`]'s usual lifecycle.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn footstep(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn(SoundEventPlayer(server.load("sfx/footstep.sound.ron")));
+/// }
+/// ```
+#[derive(Debug, Clone, Component)]
+pub struct SoundEventPlayer(pub Handle<SoundEvent>);
+
+/// Pools that [`SoundEvent`]s can refer to by name.
+#[derive(Resource, Default)]
+struct SoundEventPools(HashMap<String, Arc<dyn Fn(&mut EntityCommands) + Send + Sync>>);
+
+/// Name pools for [`SoundEvent`] definitions.
+pub trait RegisterSoundPool {
+    /// Let [`SoundEvent`]s play in the pool labeled `pool` by giving its `name`.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_seedling::prelude::*;
+    /// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    /// struct SfxPool;
+    ///
+    /// fn plugin(app: &mut App) {
+    ///     app.register_sound_pool("sfx", SfxPool);
+    /// }
+    /// ```
+    fn register_sound_pool<T: PoolLabel + Component + Clone>(
+        &mut self,
+        name: impl Into<String>,
+        pool: T,
+    ) -> &mut Self;
+}
+
+impl RegisterSoundPool for App {
+    fn register_sound_pool<T: PoolLabel + Component + Clone>(
+        &mut self,
+        name: impl Into<String>,
+        pool: T,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<SoundEventPools>()
+            .0
+            .insert(
+                name.into(),
+                Arc::new(move |commands: &mut EntityCommands| {
+                    commands.insert(pool.clone());
+                }),
+            );
+        self
+    }
+}
+
+pub(crate) struct SoundEventPlugin;
+
+impl Plugin for SoundEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SoundEvent>()
+            .init_asset_loader::<SoundEventLoader>()
+            .init_resource::<SoundEventPools>()
+            .add_systems(Last, play_sound_events.before(SeedlingSystems::Acquire));
+    }
+}
+
+fn play_sound_events(
+    players: Query<(Entity, &SoundEventPlayer), Without<SamplePlayer>>,
+    events: Res<Assets<SoundEvent>>,
+    pools: Res<SoundEventPools>,
+    mut rng: ResMut<PitchRngSource>,
+    mut commands: Commands,
+) {
+    for (entity, player) in &players {
+        let Some(event) = events.get(&player.0) else {
+            continue;
+        };
+
+        let weights: Vec<_> = event.samples.iter().map(|(_, weight)| *weight).collect();
+        let Some(index) = rng.weighted_index(&weights) else {
+            warn!("sound event has no samples to play");
+            commands.entity(entity).remove::<SoundEventPlayer>();
+            continue;
+        };
+
+        let volume = rng.in_range(event.volume.0 as f64..event.volume.1 as f64);
+        let speed = rng.in_range(event.speed.0..event.speed.1);
+
+        let mut sample = SamplePlayer::new(event.samples[index].0.clone())
+            .with_volume(Volume::Decibels(volume as f32));
+        if event.looping {
+            sample = sample.looping();
+        }
+
+        let mut entity = commands.entity(entity);
+        entity.insert((
+            sample,
+            PlaybackSettings::default().with_speed(speed),
+            SamplePriority(event.priority),
+        ));
+
+        if let Some(name) = &event.pool {
+            match pools.0.get(name) {
+                Some(insert_pool) => insert_pool(&mut entity),
+                None => warn!("sound event refers to unregistered pool \"{name}\""),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_definitions() {
+        let ron = r#"(
+            samples: [(path: "a.wav"), (path: "b.wav", weight: 2.0)],
+            volume: (-6.0, 0.0),
+            pool: Some("sfx"),
+        )"#;
+        let definition: SoundEventDefinition = ron::de::from_str(ron).unwrap();
+
+        assert_eq!(definition.samples.len(), 2);
+        assert_eq!(definition.samples[0].weight, 1.0);
+        assert_eq!(definition.samples[1].weight, 2.0);
+        assert_eq!(definition.volume, (-6.0, 0.0));
+        assert_eq!(definition.speed, (1.0, 1.0));
+        assert_eq!(definition.pool.as_deref(), Some("sfx"));
+
+        let json = r#"{ "samples": [{ "path": "a.wav" }], "priority": 3, "looping": true }"#;
+        let definition: SoundEventDefinition = serde_json::from_str(json).unwrap();
+
+        assert_eq!(definition.samples[0].path, "a.wav");
+        assert_eq!(definition.priority, 3);
+        assert!(definition.looping);
+    }
+}
@@ -0,0 +1,745 @@
+//! Streamed sample decoding.
+
+use bevy_asset::{AssetPath, AssetServer, AsyncReadExt, io::AsyncSeekForwardExt};
+use firewheel::sample_resource::SampleResource;
+use std::{
+    collections::VecDeque,
+    io::{Read, Seek, SeekFrom},
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+    sync::{
+        Arc, OnceLock, Weak,
+        atomic::{AtomicU32, AtomicU64, Ordering, fence},
+        mpsc::{Receiver, Sender},
+    },
+    time::Duration,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{Decoder, DecoderOptions},
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+/// The length of the start of a sample that's decoded up front, in seconds.
+///
+/// Keeping this resident lets streamed samples start and loop immediately.
+const HEAD_SECONDS: f64 = 0.5;
+
+/// How far ahead of the playhead the decoder works, in seconds.
+const LOOKAHEAD_SECONDS: f64 = 2.0;
+
+/// The length of the decoded window around the playhead, in seconds.
+///
+/// This must exceed the lookahead so frames ahead of the
+/// playhead aren't overwritten as the decoder catches up.
+const WINDOW_SECONDS: f64 = 3.0;
+
+/// How often the decoder checks on idle streams.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The number of encoded bytes read from the asset source at once.
+const CHUNK_BYTES: usize = 256 * 1024;
+
+/// How far before a seek target the decoder starts, in source frames.
+///
+/// Some codecs, like Vorbis, produce nothing for the first packet
+/// they decode, so seeking directly to the target would skip it.
+const SEEK_PREROLL: u64 = 8192;
+
+/// A sample decoded in chunks as it plays.
+///
+/// Only the start of the sample and a short window around the
+/// playhead are held in decoded form. The remainder is read from
+/// the sample's asset source and decoded on a background thread
+/// shared by all streamed samples, so long music or ambience
+/// tracks use a small fraction of the memory they would when
+/// decoded in full.
+///
+/// Streamed samples are loaded by enabling
+/// [`SampleLoaderSettings::stream`][super::SampleLoaderSettings::stream].
+///
+/// If the background decoder falls behind, like after seeking,
+/// the sample is briefly silent. For the same reason, streamed samples
+/// don't play well with `crossfade_on_seek`, which reads from two
+/// playheads at once.
+pub struct StreamedSample {
+    len_frames: u64,
+    head: Vec<Vec<f32>>,
+    shared: Arc<StreamShared>,
+}
+
+impl core::fmt::Debug for StreamedSample {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StreamedSample")
+            .field("len_frames", &self.len_frames)
+            .field("channels", &self.head.len())
+            .finish_non_exhaustive()
+    }
+}
+
+struct StreamShared {
+    window: StreamWindow,
+    /// The frame most recently requested by the sampler.
+    requested: AtomicU64,
+}
+
+/// The decoded frames following the head.
+///
+/// This is a ring buffer indexed by absolute frame. The decoder is its only
+/// writer, and readers check that the frames they copied weren't overwritten
+/// or invalidated by a seek, so the audio thread never has to wait on it.
+struct StreamWindow {
+    /// Interleaved samples, stored as `f32` bits.
+    buffer: Box<[AtomicU32]>,
+    channels: usize,
+    capacity: u64,
+    /// Incremented whenever the decoder seeks.
+    generation: AtomicU64,
+    /// The first frame that hasn't been overwritten.
+    start: AtomicU64,
+    /// One past the last decoded frame.
+    end: AtomicU64,
+}
+
+impl StreamWindow {
+    fn new(channels: usize, capacity: u64, start: u64) -> Self {
+        Self {
+            buffer: (0..channels * capacity as usize)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+            channels,
+            capacity,
+            generation: AtomicU64::new(0),
+            start: AtomicU64::new(start),
+            end: AtomicU64::new(start),
+        }
+    }
+
+    fn slot(&self, frame: u64) -> usize {
+        (frame % self.capacity) as usize * self.channels
+    }
+
+    /// Copy frames starting at `frame` into `buffers`, returning how many were available.
+    fn read(&self, buffers: &mut [&mut [f32]], range: Range<usize>, frame: u64) -> usize {
+        let generation = self.generation.load(Ordering::Acquire);
+        let start = self.start.load(Ordering::Acquire);
+        let end = self.end.load(Ordering::Acquire);
+
+        if frame < start || frame >= end {
+            return 0;
+        }
+
+        let count = range.len().min((end - frame) as usize);
+        for i in 0..count {
+            let slot = self.slot(frame + i as u64);
+            for (channel, buffer) in buffers.iter_mut().enumerate() {
+                buffer[range.start + i] =
+                    f32::from_bits(self.buffer[slot + channel].load(Ordering::Relaxed));
+            }
+        }
+
+        // If the decoder seeked or overwrote any of these frames
+        // while we were copying, they can't be trusted.
+        fence(Ordering::Acquire);
+        if self.generation.load(Ordering::Relaxed) != generation
+            || self.start.load(Ordering::Relaxed) > frame
+        {
+            return 0;
+        }
+
+        count
+    }
+
+    /// Append decoded frames, overwriting the oldest if the window is full.
+    fn push(&self, channels: &[VecDeque<f32>]) {
+        let mut start = self.start.load(Ordering::Relaxed);
+        let mut end = self.end.load(Ordering::Relaxed);
+
+        for i in 0..channels[0].len() {
+            if end - start >= self.capacity {
+                start = end + 1 - self.capacity;
+                self.start.store(start, Ordering::Relaxed);
+                fence(Ordering::Release);
+            }
+
+            let slot = self.slot(end);
+            for (channel, samples) in channels.iter().enumerate() {
+                self.buffer[slot + channel].store(samples[i].to_bits(), Ordering::Relaxed);
+            }
+            end += 1;
+        }
+
+        self.end.store(end, Ordering::Release);
+    }
+
+    /// Discard the window, restarting it at `frame`.
+    fn reset(&self, frame: u64) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        self.start.store(frame, Ordering::Relaxed);
+        self.end.store(frame, Ordering::Release);
+    }
+}
+
+impl StreamedSample {
+    /// Begin streaming a sample from its asset source.
+    ///
+    /// `bytes` are only used to decode the head of the sample, and
+    /// they aren't retained. The rest of the sample is read from
+    /// `path` as it plays.
+    ///
+    /// Returns `None` if the sample can't be streamed, such as when
+    /// its format doesn't report its length up front.
+    pub(crate) fn new(
+        bytes: Arc<[u8]>,
+        server: AssetServer,
+        path: AssetPath<'static>,
+        hint: &Hint,
+        sample_rate: NonZeroU32,
+    ) -> Option<Self> {
+        let byte_len = bytes.len() as u64;
+        let (mut decoder, len_frames) =
+            StreamDecoder::new(Box::new(std::io::Cursor::new(bytes)), hint, sample_rate)?;
+
+        let head_frames = (HEAD_SECONDS * sample_rate.get() as f64) as u64;
+        let mut head = vec![VecDeque::new(); decoder.channels];
+        while (head[0].len() as u64) < head_frames && !decoder.finished {
+            decoder.decode(&mut head);
+        }
+
+        let head: Vec<Vec<f32>> = head.into_iter().map(Vec::from).collect();
+        let head_len = head[0].len() as u64;
+        let capacity = (WINDOW_SECONDS * sample_rate.get() as f64) as u64;
+        let shared = Arc::new(StreamShared {
+            window: StreamWindow::new(head.len(), capacity, head_len),
+            requested: AtomicU64::new(0),
+        });
+
+        // Samples that fit within their head never need the worker.
+        if head_len < len_frames {
+            let source = AssetSourceReader {
+                server,
+                path,
+                len: byte_len,
+                position: 0,
+                chunk: Vec::new(),
+                chunk_start: 0,
+            };
+
+            StreamWorker::submit(StreamJob {
+                source: Some(source),
+                decoder: None,
+                hint: hint.clone(),
+                sample_rate,
+                head_len,
+                lookahead: (LOOKAHEAD_SECONDS * sample_rate.get() as f64) as u64,
+                shared: Arc::downgrade(&shared),
+            })?;
+        }
+
+        Some(Self {
+            len_frames,
+            head,
+            shared,
+        })
+    }
+}
+
+impl SampleResource for StreamedSample {
+    fn num_channels(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.head.len()).unwrap()
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.len_frames
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: Range<usize>,
+        start_frame: u64,
+    ) {
+        self.shared.requested.store(start_frame, Ordering::Relaxed);
+
+        let frames = buffer_range.len();
+        let mut filled = 0;
+
+        let head_len = self.head[0].len() as u64;
+        if start_frame < head_len {
+            let start = start_frame as usize;
+            filled = frames.min(self.head[0].len() - start);
+
+            for (buffer, channel) in buffers.iter_mut().zip(&self.head) {
+                buffer[buffer_range.start..buffer_range.start + filled]
+                    .copy_from_slice(&channel[start..start + filled]);
+            }
+        }
+
+        if filled < frames {
+            filled += self.shared.window.read(
+                buffers,
+                buffer_range.start + filled..buffer_range.end,
+                start_frame + filled as u64,
+            );
+        }
+
+        for buffer in buffers.iter_mut() {
+            buffer[buffer_range.start + filled..buffer_range.end].fill(0.0);
+        }
+    }
+}
+
+/// Reads an encoded sample from its asset source on demand.
+///
+/// Asset readers can only seek forward, so each chunk is read
+/// from a freshly opened reader. Chunks are large enough that
+/// this happens only every few seconds of playback.
+struct AssetSourceReader {
+    server: AssetServer,
+    path: AssetPath<'static>,
+    len: u64,
+    position: u64,
+    chunk: Vec<u8>,
+    chunk_start: u64,
+}
+
+impl AssetSourceReader {
+    async fn read_chunk(&self, chunk: &mut Vec<u8>) -> std::io::Result<()> {
+        let source = self
+            .server
+            .get_source(self.path.source())
+            .map_err(std::io::Error::other)?;
+        let mut reader = source
+            .reader()
+            .read(self.path.path())
+            .await
+            .map_err(std::io::Error::other)?;
+
+        reader.seek_forward(self.position).await?;
+        reader.take(CHUNK_BYTES as u64).read_to_end(chunk).await?;
+
+        Ok(())
+    }
+}
+
+impl Read for AssetSourceReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let chunk_end = self.chunk_start + self.chunk.len() as u64;
+        if self.position < self.chunk_start || self.position >= chunk_end {
+            let mut chunk = core::mem::take(&mut self.chunk);
+            chunk.clear();
+            bevy_tasks::block_on(self.read_chunk(&mut chunk))?;
+
+            self.chunk = chunk;
+            self.chunk_start = self.position;
+        }
+
+        let offset = (self.position - self.chunk_start) as usize;
+        let count = buf.len().min(self.chunk.len() - offset);
+        buf[..count].copy_from_slice(&self.chunk[offset..offset + count]);
+        self.position += count as u64;
+
+        Ok(count)
+    }
+}
+
+impl Seek for AssetSourceReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek position")
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+impl MediaSource for AssetSourceReader {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+/// The background thread that decodes every streamed sample.
+struct StreamWorker;
+
+impl StreamWorker {
+    /// Hand a stream to the worker, starting it if necessary.
+    ///
+    /// Returns `None` if the worker couldn't be started.
+    fn submit(job: StreamJob) -> Option<()> {
+        static WORKER: OnceLock<Option<Sender<StreamJob>>> = OnceLock::new();
+
+        let sender = WORKER.get_or_init(|| {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            std::thread::Builder::new()
+                .name("seedling stream".into())
+                .spawn(move || Self::run(receiver))
+                .ok()?;
+
+            Some(sender)
+        });
+
+        sender.as_ref()?.send(job).ok()
+    }
+
+    fn run(receiver: Receiver<StreamJob>) {
+        let mut jobs = Vec::new();
+
+        loop {
+            // With nothing to decode, we simply wait for the next stream.
+            if jobs.is_empty() {
+                match receiver.recv() {
+                    Ok(job) => jobs.push(job),
+                    Err(_) => return,
+                }
+            }
+            jobs.extend(receiver.try_iter());
+
+            let mut busy = false;
+            jobs.retain_mut(|job| match job.shared.upgrade() {
+                Some(shared) => {
+                    busy |= job.step(&shared);
+                    true
+                }
+                // The sample has been dropped.
+                None => false,
+            });
+
+            if !busy {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// A streamed sample's decoding state on the worker.
+struct StreamJob {
+    /// The source, held until the decoder is first needed.
+    source: Option<AssetSourceReader>,
+    decoder: Option<StreamDecoder>,
+    hint: Hint,
+    sample_rate: NonZeroU32,
+    head_len: u64,
+    lookahead: u64,
+    shared: Weak<StreamShared>,
+}
+
+impl StreamJob {
+    /// Keep the window filled ahead of the sampler, returning
+    /// `true` if any decoding was done.
+    fn step(&mut self, shared: &StreamShared) -> bool {
+        let window = &shared.window;
+
+        // The head is always resident, so the window begins after it.
+        let requested = shared.requested.load(Ordering::Relaxed).max(self.head_len);
+        let start = window.start.load(Ordering::Relaxed);
+        let end = window.end.load(Ordering::Relaxed);
+
+        let seeking = requested < start || requested > end + self.lookahead;
+        if !seeking && end >= requested + self.lookahead {
+            return false;
+        }
+
+        let decoder = match &mut self.decoder {
+            Some(decoder) => decoder,
+            None => {
+                let Some(source) = self.source.take() else {
+                    return false;
+                };
+                let Some((decoder, _)) =
+                    StreamDecoder::new(Box::new(source), &self.hint, self.sample_rate)
+                else {
+                    return false;
+                };
+
+                // Decoding through the head, rather than seeking past it,
+                // keeps the window aligned with the head's final frame.
+                let decoder = self.decoder.insert(decoder);
+                let mut head = vec![VecDeque::new(); window.channels];
+                while decoder.next_frame < end && !decoder.finished {
+                    decoder.decode(&mut head);
+                    for channel in &mut head {
+                        channel.clear();
+                    }
+                }
+                decoder
+            }
+        };
+
+        if seeking {
+            decoder.seek(requested);
+            window.reset(requested);
+            return true;
+        }
+
+        if decoder.finished {
+            return false;
+        }
+
+        let mut pending = vec![VecDeque::new(); window.channels];
+        decoder.decode(&mut pending);
+        window.push(&pending);
+
+        true
+    }
+}
+
+/// Decodes and resamples a sample on the streaming thread.
+struct StreamDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    channels: usize,
+    len_frames: u64,
+    /// Source frames per output frame.
+    ratio: f64,
+    /// Decoded source frames awaiting resampling.
+    source: Vec<VecDeque<f32>>,
+    /// The index of the first frame in `source`.
+    source_start: u64,
+    /// The next output frame to produce.
+    next_frame: u64,
+    finished: bool,
+}
+
+impl StreamDecoder {
+    fn new(
+        source: Box<dyn MediaSource>,
+        hint: &Hint,
+        sample_rate: NonZeroU32,
+    ) -> Option<(Self, u64)> {
+        let stream = MediaSourceStream::new(source, Default::default());
+        let probed = symphonia::default::get_probe()
+            .format(
+                hint,
+                stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .ok()?;
+
+        let format = probed.format;
+        let track = format.default_track()?;
+        let track_id = track.id;
+        let params = &track.codec_params;
+
+        let channels = params.channels?.count();
+        let source_rate = params.sample_rate?;
+        let source_frames = params.n_frames?;
+        if channels == 0 {
+            return None;
+        }
+
+        let decoder = symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .ok()?;
+
+        let ratio = source_rate as f64 / sample_rate.get() as f64;
+        let len_frames = (source_frames as f64 / ratio).ceil() as u64;
+
+        Some((
+            Self {
+                track_id,
+                format,
+                decoder,
+                channels,
+                len_frames,
+                ratio,
+                source: vec![VecDeque::new(); channels],
+                source_start: 0,
+                next_frame: 0,
+                finished: false,
+            },
+            len_frames,
+        ))
+    }
+
+    /// Decode the next packet, appending any completed frames to `out`.
+    fn decode(&mut self, out: &mut [VecDeque<f32>]) {
+        if self.finished {
+            return;
+        }
+
+        let packet = match self.format.next_packet() {
+            Ok(packet) => packet,
+            Err(_) => {
+                // Pad the end so the final frames can be interpolated.
+                for channel in &mut self.source {
+                    channel.push_back(0.0);
+                }
+                self.resample(out);
+                self.finished = true;
+                return;
+            }
+        };
+
+        if packet.track_id() != self.track_id {
+            return;
+        }
+
+        // Corrupt packets are skipped rather than ending the stream.
+        let Ok(decoded) = self.decoder.decode(&packet) else {
+            return;
+        };
+
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        let frames = buffer.samples().chunks_exact(spec.channels.count().max(1));
+        // Following a seek, decoding begins before the requested frame,
+        // so we discard anything preceding it.
+        let expected = self.source_start + self.source[0].len() as u64;
+        let skip = expected
+            .saturating_sub(packet.ts())
+            .min(frames.len() as u64);
+
+        for frame in frames.skip(skip as usize) {
+            for (channel, sample) in self.source.iter_mut().zip(frame) {
+                channel.push_back(*sample);
+            }
+        }
+
+        self.resample(out);
+    }
+
+    /// Linearly interpolate the decoded source frames to the output rate.
+    fn resample(&mut self, out: &mut [VecDeque<f32>]) {
+        let source_end = self.source_start + self.source[0].len() as u64;
+
+        while self.next_frame < self.len_frames {
+            let position = self.next_frame as f64 * self.ratio;
+            let index = position as u64;
+
+            if index + 1 >= source_end {
+                break;
+            }
+
+            let local = index.saturating_sub(self.source_start) as usize;
+            let fraction = (position - index as f64) as f32;
+            for (out, source) in out.iter_mut().zip(&self.source) {
+                let (a, b) = (source[local], source[local + 1]);
+                out.push_back(a + (b - a) * fraction);
+            }
+
+            self.next_frame += 1;
+        }
+
+        let consumed = ((self.next_frame as f64 * self.ratio) as u64)
+            .saturating_sub(self.source_start)
+            .min(self.source[0].len() as u64);
+        for channel in &mut self.source {
+            channel.drain(..consumed as usize);
+        }
+        self.source_start += consumed;
+    }
+
+    /// Move the decoder to the output `frame`.
+    fn seek(&mut self, frame: u64) {
+        for channel in &mut self.source {
+            channel.clear();
+        }
+
+        // For the formats we support, timestamps are in source frames.
+        let target = (frame as f64 * self.ratio) as u64;
+        let seeked = self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::TimeStamp {
+                ts: target.saturating_sub(SEEK_PREROLL),
+                track_id: self.track_id,
+            },
+        );
+        self.decoder.reset();
+
+        match seeked {
+            Ok(_) => {
+                self.source_start = target;
+                self.next_frame = frame;
+                self.finished = false;
+            }
+            Err(_) => self.finished = true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_streamed_decode() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        let server = app.world().resource::<AssetServer>().clone();
+
+        let bytes: Arc<[u8]> = include_bytes!("../../assets/crow_ambience.ogg")
+            .as_slice()
+            .into();
+        let mut hint = Hint::new();
+        hint.with_extension("ogg");
+        let sample_rate = NonZeroU32::new(48000).unwrap();
+
+        let full = firewheel::load_audio_file_from_source(
+            &mut symphonium::SymphoniumLoader::new(),
+            Box::new(std::io::Cursor::new(bytes.clone())),
+            Some(hint.clone()),
+            sample_rate,
+            Default::default(),
+        )
+        .unwrap();
+        let streamed = StreamedSample::new(
+            bytes,
+            server,
+            "crow_ambience.ogg".into(),
+            &hint,
+            sample_rate,
+        )
+        .unwrap();
+
+        assert_eq!(streamed.len_frames(), full.len_frames());
+        assert_eq!(streamed.num_channels(), full.num_channels());
+
+        // Starting in the head, before the decoder has a chance to run.
+        let matches = |start: u64| {
+            let mut expected = [vec![0.0; 256], vec![0.0; 256]];
+            let mut actual = expected.clone();
+
+            let [a, b] = &mut expected;
+            full.fill_buffers(&mut [a.as_mut_slice(), b.as_mut_slice()], 0..256, start);
+            let [a, b] = &mut actual;
+            streamed.fill_buffers(&mut [a.as_mut_slice(), b.as_mut_slice()], 0..256, start);
+
+            expected
+                .iter()
+                .flatten()
+                .zip(actual.iter().flatten())
+                .all(|(e, a)| (e - a).abs() < 1e-4)
+        };
+        assert!(matches(0));
+
+        // Later frames, including a seek, arrive once decoded.
+        for start in [48000, 400000, 24000] {
+            let mut tries = 0;
+            while !matches(start) {
+                tries += 1;
+                assert!(tries < 1000, "frame {start} was never streamed");
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    SeedlingSystems,
+    nodes::{hpf::HighPassNode, lpf::LowPassNode},
+    pool::sample_effects::{EffectsQuery, SampleEffects},
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+
+pub(crate) struct TonePlugin;
+
+impl Plugin for TonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            apply_tone
+                .after(SeedlingSystems::Pool)
+                .before(SeedlingSystems::Queue),
+        );
+    }
+}
+
+/// Sets the cutoff of a sample's [`LowPassNode`] effect.
+///
+/// When a sample is assigned to a pool with a [`LowPassNode`] effect,
+/// the effect's frequency is set to this value. This makes per-sound
+/// filtering, like muffling a sound behind a wall, a simple component write.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct WorldPool;
+///
+/// fn spawn_pool(mut commands: Commands) {
+///     commands.spawn((
+///         SamplerPool(WorldPool),
+///         sample_effects![
+///             LowPassNode {
+///                 frequency: 20_000.0
+///             },
+///             HighPassNode { frequency: 0.0 }
+///         ],
+///     ));
+/// }
+///
+/// fn play_behind_wall(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         WorldPool,
+///         SamplePlayer::new(server.load("caw.ogg")),
+///         ToneLowpass(800.0),
+///     ));
+/// }
+/// ```
+///
+/// Changing this component while the sample plays updates the filter.
+/// Samples in pools without a [`LowPassNode`] effect are unaffected.
+#[derive(Debug, Component, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ToneLowpass(pub f32);
+
+/// Sets the cutoff of a sample's [`HighPassNode`] effect.
+///
+/// This behaves like [`ToneLowpass`], applying to the
+/// [`HighPassNode`] effect instead.
+#[derive(Debug, Component, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ToneHighpass(pub f32);
+
+fn apply_tone(
+    samples: Query<
+        (Option<&ToneLowpass>, Option<&ToneHighpass>, &SampleEffects),
+        (
+            Or<(With<ToneLowpass>, With<ToneHighpass>)>,
+            Or<(
+                Changed<ToneLowpass>,
+                Changed<ToneHighpass>,
+                Changed<SampleEffects>,
+            )>,
+        ),
+    >,
+    mut low_pass: Query<&mut LowPassNode>,
+    mut high_pass: Query<&mut HighPassNode>,
+) {
+    for (lowpass, highpass, effects) in &samples {
+        if let Some(tone) = lowpass {
+            if let Ok(mut node) = low_pass.get_effect_mut(effects) {
+                node.frequency = tone.0;
+            }
+        }
+
+        if let Some(tone) = highpass {
+            if let Ok(mut node) = high_pass.get_effect_mut(effects) {
+                node.frequency = tone.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        pool::Sampler,
+        prelude::*,
+        test::{prepare_app, run, run_until},
+    };
+    use bevy_asset::AssetServer;
+
+    #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct TestPool;
+
+    #[test]
+    fn test_tone() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands
+                .spawn((
+                    SamplerPool(TestPool),
+                    sample_effects![LowPassNode::default(), HighPassNode::default()],
+                ))
+                .connect(AudioGraphOutput);
+
+            commands.spawn((
+                TestPool,
+                SamplePlayer::new(server.load("caw.ogg")).looping(),
+                ToneLowpass(500.0),
+            ));
+        });
+
+        run_until(
+            &mut app,
+            |q: Query<(), (With<SamplePlayer>, With<Sampler>)>| q.iter().len() == 1,
+        );
+        app.update();
+
+        let sample = run(
+            &mut app,
+            |sample: Single<(Entity, &SampleEffects), With<SamplePlayer>>,
+             low_pass: Query<&LowPassNode>,
+             high_pass: Query<&HighPassNode>| {
+                let (entity, effects) = sample.into_inner();
+                assert_eq!(low_pass.get_effect(effects).unwrap().frequency, 500.0);
+                assert_eq!(
+                    high_pass.get_effect(effects).unwrap().frequency,
+                    HighPassNode::default().frequency
+                );
+
+                entity
+            },
+        );
+
+        app.world_mut()
+            .entity_mut(sample)
+            .insert(ToneHighpass(300.0));
+        app.update();
+
+        run(
+            &mut app,
+            |effects: Single<&SampleEffects, With<SamplePlayer>>,
+             high_pass: Query<&HighPassNode>| {
+                assert_eq!(high_pass.get_effect(*effects).unwrap().frequency, 300.0);
+            },
+        );
+    }
+}
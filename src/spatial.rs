@@ -30,31 +30,62 @@
 //! Multiple listeners are supported. `bevy_seedling` will
 //! simply select the closest listener for distance
 //! calculations.
+//!
+//! ## Velocity
+//!
+//! Moving emitters and listeners can carry an [`EmitterVelocity`],
+//! which drives effects like the [`Doppler`] shift. Physics integrations
+//! can keep it up to date by implementing [`VelocitySource`] for their
+//! velocity component. With the `avian3d` feature, Avian's `LinearVelocity`
+//! is supported out of the box.
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_math::prelude::*;
 use bevy_transform::prelude::*;
-use firewheel::{nodes::spatial_basic::SpatialBasicNode, vector};
+use firewheel::{Volume, nodes::spatial_basic::SpatialBasicNode, vector};
 
-use crate::{SeedlingSystems, nodes::itd::ItdNode, pool::sample_effects::EffectOf};
+use crate::{
+    SeedlingSystems,
+    nodes::{itd::ItdNode, send::SendNode},
+    pool::sample_effects::EffectOf,
+    sample::SamplePlayer,
+};
 
 pub(crate) struct SpatialPlugin;
 
 impl Plugin for SpatialPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<DefaultSpatialScale>().add_systems(
-            Last,
-            (
-                update_2d_emitters,
-                update_2d_emitters_effects,
-                update_3d_emitters,
-                update_3d_emitters_effects,
-                update_itd_effects,
-                #[cfg(feature = "hrtf")]
-                spatial_hrtf::update_hrtf_effects,
+        app.init_resource::<DefaultSpatialScale>()
+            .add_systems(
+                Last,
+                (
+                    update_2d_emitters,
+                    update_2d_emitters_effects,
+                    update_3d_emitters,
+                    update_3d_emitters_effects,
+                    update_itd_effects,
+                    update_reverb_sends,
+                    update_doppler,
+                    #[cfg(feature = "hrtf")]
+                    spatial_hrtf::update_hrtf_effects,
+                )
+                    .after(SeedlingSystems::Pool)
+                    .before(SeedlingSystems::Queue)
+                    .before(update_listener_local),
             )
-                .after(SeedlingSystems::Pool)
+            .add_systems(
+                Last,
+                update_listener_local
+                    .after(SeedlingSystems::Pool)
+                    .before(SeedlingSystems::Queue),
+            );
+
+        #[cfg(feature = "hrtf")]
+        app.add_systems(
+            Last,
+            spatial_hrtf::update_hrtf_listener_local
+                .after(spatial_hrtf::update_hrtf_effects)
                 .before(SeedlingSystems::Queue),
         );
     }
@@ -149,6 +180,416 @@ pub struct SpatialListener2D;
 #[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 pub struct SpatialListener3D;
 
+/// Keep a sample positioned at the listener.
+///
+/// Ambience and UI sounds are often played in pools with spatial
+/// effects, like the [`SpatialPool`][crate::prelude::SpatialPool].
+/// If these sounds are given a transform, they'll pan as the listener
+/// moves and rotates, which is rarely what you want.
+///
+/// Spatial effects on entities with [`ListenerLocal`], or effects
+/// belonging to a [`SamplePlayer`][crate::prelude::SamplePlayer] with
+/// [`ListenerLocal`], have their offset from the listener zeroed,
+/// regardless of any transforms.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn play_ambience(mut commands: Commands, server: Res<AssetServer>) {
+///     commands.spawn((
+///         SpatialPool,
+///         ListenerLocal,
+///         SamplePlayer::new(server.load("my_ambience.wav")).looping(),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct ListenerLocal;
+
+/// Drive a [`SendNode`]'s send volume from the emitter's distance to the listener.
+///
+/// Distant sounds are heard more through the room than directly, so
+/// sending more of them to a shared reverb gives a convincing sense of
+/// space. Placing a [`SpatialReverbSend`] alongside a [`SendNode`] in a pool's
+/// effects makes each sample's send level follow its own distance.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// #[derive(NodeLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct ReverbBus;
+///
+/// #[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+/// struct WorldPool;
+///
+/// fn spawn_pool(mut commands: Commands) {
+///     commands.spawn((ReverbBus, FreeverbNode::default()));
+///
+///     commands.spawn((
+///         SamplerPool(WorldPool),
+///         sample_effects![
+///             SpatialBasicNode::default(),
+///             (
+///                 SendNode::new(Volume::SILENT, ReverbBus),
+///                 SpatialReverbSend::default(),
+///             ),
+///         ],
+///     ));
+/// }
+/// ```
+///
+/// The send volume is interpolated in decibels from `near` at `near_distance`
+/// to `far` at `far_distance`, following the logarithm of the distance.
+/// The distance is scaled by the effect's [`SpatialScale`] or the [`DefaultSpatialScale`].
+/// Samples with [`ListenerLocal`] are always sent at the `near` volume.
+#[derive(Debug, Clone, Copy, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct SpatialReverbSend {
+    /// The send volume at or within `near_distance`.
+    ///
+    /// By default, this is -24 dB.
+    pub near: Volume,
+    /// The send volume at or beyond `far_distance`.
+    ///
+    /// By default, this is -6 dB.
+    pub far: Volume,
+    /// The distance at which the send begins to increase.
+    ///
+    /// By default, this is 2 units.
+    pub near_distance: f32,
+    /// The distance at which the send reaches its maximum.
+    ///
+    /// By default, this is 50 units.
+    pub far_distance: f32,
+}
+
+impl Default for SpatialReverbSend {
+    fn default() -> Self {
+        Self {
+            near: Volume::Decibels(-24.0),
+            far: Volume::Decibels(-6.0),
+            near_distance: 2.0,
+            far_distance: 50.0,
+        }
+    }
+}
+
+impl SpatialReverbSend {
+    /// Calculate the send volume at `distance`.
+    pub fn volume(&self, distance: f32) -> Volume {
+        let near_distance = self.near_distance.max(f32::EPSILON);
+        let far_distance = self.far_distance.max(near_distance);
+
+        let t = if far_distance == near_distance {
+            if distance >= far_distance { 1.0 } else { 0.0 }
+        } else {
+            ((distance.max(near_distance) / near_distance).ln()
+                / (far_distance / near_distance).ln())
+            .clamp(0.0, 1.0)
+        };
+
+        let near = self.near.decibels();
+        let far = self.far.decibels();
+
+        // Interpolating from silence would produce NaNs.
+        if near == f32::NEG_INFINITY || far == f32::NEG_INFINITY {
+            return Volume::Linear(self.near.linear().lerp(self.far.linear(), t));
+        }
+
+        Volume::Decibels(near.lerp(far, t))
+    }
+}
+
+fn update_reverb_sends(
+    listeners: Query<&GlobalTransform, Or<(With<SpatialListener2D>, With<SpatialListener3D>)>>,
+    mut sends: Query<(
+        &mut SendNode,
+        &SpatialReverbSend,
+        Option<&SpatialScale>,
+        Has<ListenerLocal>,
+        &EffectOf,
+    )>,
+    effect_parents: Query<(&GlobalTransform, Has<ListenerLocal>)>,
+    default_scale: Res<DefaultSpatialScale>,
+) {
+    for (mut send, reverb, scale, has_local, effect_of) in sends.iter_mut() {
+        let Ok((transform, parent_local)) = effect_parents.get(effect_of.0) else {
+            continue;
+        };
+
+        let distance = if has_local || parent_local {
+            0.0
+        } else {
+            let emitter_pos = transform.translation();
+            let closest_listener = find_closest_listener(
+                emitter_pos,
+                listeners.iter().map(GlobalTransform::compute_transform),
+            );
+
+            let Some(listener) = closest_listener else {
+                continue;
+            };
+
+            let scale = scale.map(|s| s.0).unwrap_or(default_scale.0);
+            ((emitter_pos - listener.translation) * scale).length()
+        };
+
+        let volume = reverb.volume(distance);
+        if send.send_volume != volume {
+            send.send_volume = volume;
+        }
+    }
+}
+
+/// The velocity of a spatial emitter or listener, in world units per second.
+///
+/// Velocities are used to calculate motion-dependent effects, like the
+/// [`Doppler`] shift. A [`SamplePlayer`] uses its own velocity or,
+/// if it has none, its parent's. This makes it easy to attach sounds
+/// to moving bodies as children.
+///
+/// You can write to this component directly, or let a [`VelocitySource`]
+/// keep it up to date.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct EmitterVelocity(pub Vec3);
+
+/// A component that provides an entity's velocity.
+///
+/// Physics integrations can implement this trait for their
+/// velocity components and register them with
+/// [`RegisterVelocitySource::register_velocity_source`].
+/// [`EmitterVelocity`] is then inserted and updated on
+/// every entity with the source component.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, spatial::{RegisterVelocitySource, VelocitySource}};
+/// #[derive(Component)]
+/// struct Velocity(Vec3);
+///
+/// impl VelocitySource for Velocity {
+///     fn velocity(&self) -> Vec3 {
+///         self.0
+///     }
+/// }
+///
+/// # fn plugin(app: &mut App) {
+/// app.register_velocity_source::<Velocity>();
+/// # }
+/// ```
+pub trait VelocitySource: Component {
+    /// The entity's linear velocity, in world units per second.
+    fn velocity(&self) -> Vec3;
+}
+
+/// Register [`VelocitySource`] components.
+pub trait RegisterVelocitySource {
+    /// Keep [`EmitterVelocity`] in sync with the source component `T`.
+    fn register_velocity_source<T: VelocitySource>(&mut self) -> &mut Self;
+}
+
+impl RegisterVelocitySource for App {
+    fn register_velocity_source<T: VelocitySource>(&mut self) -> &mut Self {
+        self.add_systems(
+            Last,
+            sync_velocity::<T>
+                .after(SeedlingSystems::Pool)
+                .before(update_doppler),
+        )
+    }
+}
+
+fn sync_velocity<T: VelocitySource>(
+    mut sources: Query<(Entity, &T, Option<&mut EmitterVelocity>), Changed<T>>,
+    mut commands: Commands,
+) {
+    for (entity, source, velocity) in sources.iter_mut() {
+        let new_velocity = source.velocity();
+
+        match velocity {
+            Some(mut velocity) => {
+                if velocity.0 != new_velocity {
+                    velocity.0 = new_velocity;
+                }
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(EmitterVelocity(new_velocity));
+            }
+        }
+    }
+}
+
+/// Shift a [`SamplePlayer`]'s pitch according to its motion relative to the listener.
+///
+/// The shift is calculated from the [`EmitterVelocity`] of the sample
+/// (or its parent) and the closest listener, and it's applied on top of
+/// the [`PlaybackSettings`][crate::prelude::PlaybackSettings] speed.
+/// Samples with [`ListenerLocal`] are never shifted.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::prelude::*;
+/// fn spawn_car(mut commands: Commands, server: Res<AssetServer>) {
+///     commands
+///         .spawn((Transform::default(), EmitterVelocity(Vec3::X * 30.0)))
+///         .with_child((
+///             SpatialPool,
+///             SamplePlayer::new(server.load("engine.ogg")).looping(),
+///             Transform::default(),
+///             Doppler::default(),
+///         ));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+pub struct Doppler {
+    /// The speed of sound, in world units per second.
+    ///
+    /// By default, this is 343, the speed of sound in air
+    /// in meters per second.
+    pub speed_of_sound: f32,
+    /// A scaling factor applied to velocities.
+    ///
+    /// Realistic Doppler shifts can be subtle, so exaggerating
+    /// them with values above one is common.
+    ///
+    /// By default, this is 1.
+    pub factor: f32,
+}
+
+impl Default for Doppler {
+    fn default() -> Self {
+        Self {
+            speed_of_sound: 343.0,
+            factor: 1.0,
+        }
+    }
+}
+
+impl Doppler {
+    /// Calculate the playback speed multiplier for an emitter and listener.
+    ///
+    /// Positions and velocities are in world space.
+    pub fn shift(
+        &self,
+        emitter_position: Vec3,
+        emitter_velocity: Vec3,
+        listener_position: Vec3,
+        listener_velocity: Vec3,
+    ) -> f64 {
+        let Some(direction) = (emitter_position - listener_position).try_normalize() else {
+            return 1.0;
+        };
+
+        let speed_of_sound = self.speed_of_sound.max(f32::EPSILON);
+        // Limiting the velocities keeps the shift finite near the speed of sound.
+        let limit = speed_of_sound * 0.9;
+        let listener = (listener_velocity.dot(direction) * self.factor).clamp(-limit, limit);
+        let emitter = (emitter_velocity.dot(direction) * self.factor).clamp(-limit, limit);
+
+        ((speed_of_sound + listener) / (speed_of_sound + emitter)) as f64
+    }
+}
+
+/// The most recently calculated [`Doppler`] shift.
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct DopplerShift(pub(crate) f64);
+
+fn update_doppler(
+    listeners: Query<
+        (&GlobalTransform, Option<&EmitterVelocity>),
+        Or<(With<SpatialListener2D>, With<SpatialListener3D>)>,
+    >,
+    mut emitters: Query<
+        (
+            Entity,
+            &Doppler,
+            &GlobalTransform,
+            Has<ListenerLocal>,
+            Option<&ChildOf>,
+            Option<&mut DopplerShift>,
+        ),
+        With<SamplePlayer>,
+    >,
+    velocities: Query<&EmitterVelocity>,
+    mut commands: Commands,
+) {
+    for (entity, doppler, transform, has_local, child_of, shift) in emitters.iter_mut() {
+        let emitter_position = transform.translation();
+        let closest_listener = listeners.iter().min_by(|a, b| {
+            let a = a.0.translation().distance_squared(emitter_position);
+            let b = b.0.translation().distance_squared(emitter_position);
+            a.total_cmp(&b)
+        });
+
+        let new_shift = match closest_listener {
+            Some((listener, listener_velocity)) if !has_local => {
+                let emitter_velocity = velocities
+                    .get(entity)
+                    .ok()
+                    .or_else(|| child_of.and_then(|c| velocities.get(c.parent()).ok()))
+                    .map(|v| v.0)
+                    .unwrap_or_default();
+
+                doppler.shift(
+                    emitter_position,
+                    emitter_velocity,
+                    listener.translation(),
+                    listener_velocity.map(|v| v.0).unwrap_or_default(),
+                )
+            }
+            _ => 1.0,
+        };
+
+        match shift {
+            Some(mut shift) => {
+                if shift.0 != new_shift {
+                    shift.0 = new_shift;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(DopplerShift(new_shift));
+            }
+        }
+    }
+}
+
+fn update_listener_local(
+    mut spatial: Query<(&mut SpatialBasicNode, Has<ListenerLocal>, Option<&EffectOf>)>,
+    mut itd: Query<(&mut ItdNode, Has<ListenerLocal>, &EffectOf)>,
+    local: Query<(), With<ListenerLocal>>,
+) {
+    let is_local = |has_local: bool, effect_of: Option<&EffectOf>| {
+        has_local || effect_of.is_some_and(|e| local.contains(e.0))
+    };
+
+    for (mut spatial, has_local, effect_of) in spatial.iter_mut() {
+        if !is_local(has_local, effect_of) {
+            continue;
+        }
+
+        let offset: Vec3 = spatial.offset.into();
+        if offset != Vec3::ZERO {
+            spatial.offset = Vec3::ZERO.into();
+        }
+    }
+
+    for (mut itd, has_local, effect_of) in itd.iter_mut() {
+        if !is_local(has_local, Some(effect_of)) {
+            continue;
+        }
+
+        // A zero direction places the source directly in front of the listener.
+        if itd.direction != Vec3::ZERO {
+            itd.direction = Vec3::ZERO;
+        }
+    }
+}
+
 fn update_2d_emitters(
     listeners: Query<&GlobalTransform, With<SpatialListener2D>>,
     mut emitters: Query<(
@@ -351,6 +792,26 @@ mod spatial_hrtf {
             spatial.offset = local_offset * scale;
         }
     }
+
+    pub(super) fn update_hrtf_listener_local(
+        mut emitters: Query<(&mut HrtfNode, Has<ListenerLocal>, Option<&EffectOf>)>,
+        local: Query<(), With<ListenerLocal>>,
+    ) {
+        for (mut spatial, has_local, effect_of) in emitters.iter_mut() {
+            let is_local = has_local || effect_of.is_some_and(|e| local.contains(e.0));
+
+            if is_local && spatial.offset != Vec3::ZERO {
+                spatial.offset = Vec3::ZERO;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "avian3d")]
+impl VelocitySource for avian3d::prelude::LinearVelocity {
+    fn velocity(&self) -> Vec3 {
+        self.0
+    }
 }
 
 #[cfg(test)]
@@ -362,7 +823,7 @@ mod test {
         node::follower::FollowerOf,
         pool::Sampler,
         prelude::*,
-        test::{prepare_app, run},
+        test::{prepare_app, run, run_until},
     };
 
     #[test]
@@ -410,26 +871,138 @@ mod test {
             ));
         });
 
-        loop {
-            let complete = run(
-                &mut app,
-                move |player: Query<&Sampler>,
-                      effect: Query<&SpatialBasicNode, With<FollowerOf>>| {
-                    if player.iter().len() == 1 {
-                        let effect: Vec3 = effect.single().unwrap().offset.into();
-                        assert_eq!(effect, position);
-                        true
-                    } else {
-                        false
-                    }
-                },
-            );
+        run_until(
+            &mut app,
+            move |player: Query<&Sampler>, effect: Query<&SpatialBasicNode, With<FollowerOf>>| {
+                if player.iter().len() == 1 {
+                    let effect: Vec3 = effect.single().unwrap().offset.into();
+                    assert_eq!(effect, position);
+                    true
+                } else {
+                    false
+                }
+            },
+        );
+    }
 
-            if complete {
-                break;
-            }
+    #[test]
+    fn test_reverb_send_volume() {
+        let send = SpatialReverbSend::default();
+
+        assert_eq!(send.volume(0.0), send.near);
+        assert_eq!(send.volume(send.near_distance), send.near);
+        assert_eq!(send.volume(1000.0), send.far);
+
+        let Volume::Decibels(mid) = send.volume(10.0) else {
+            panic!("expected decibels");
+        };
+        assert!(mid > -24.0 && mid < -6.0);
+    }
+
+    #[test]
+    fn test_reverb_send() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            let reverb = commands.spawn(VolumeNode::default()).id();
+
+            commands.spawn((
+                SamplerPool(TestPool),
+                sample_effects![(
+                    SendNode::new(Volume::SILENT, reverb),
+                    SpatialReverbSend::default()
+                )],
+            ));
+
+            commands.spawn((SpatialListener3D, Transform::default()));
 
-            app.update();
+            commands.spawn((
+                TestPool,
+                Transform::from_translation(Vec3::X * 100.0),
+                SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+            ));
+        });
+
+        run_until(
+            &mut app,
+            |player: Query<&Sampler>, effect: Query<&SendNode, With<FollowerOf>>| {
+                if player.iter().len() == 1 {
+                    let effect = effect.single().unwrap();
+                    assert_eq!(effect.send_volume, SpatialReverbSend::default().far);
+                    true
+                } else {
+                    false
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_listener_local() {
+        let mut app = prepare_app(|mut commands: Commands, server: Res<AssetServer>| {
+            commands.spawn((
+                SamplerPool(TestPool),
+                sample_effects![SpatialBasicNode::default()],
+            ));
+
+            commands.spawn((SpatialListener3D, Transform::default()));
+
+            commands.spawn((
+                TestPool,
+                ListenerLocal,
+                Transform::from_translation(Vec3::splat(3.0)),
+                SamplePlayer::new(server.load("sine_440hz_1ms.wav")).looping(),
+            ));
+        });
+
+        run_until(
+            &mut app,
+            |player: Query<&Sampler>, effect: Query<&SpatialBasicNode, With<FollowerOf>>| {
+                if player.iter().len() == 1 {
+                    let effect: Vec3 = effect.single().unwrap().offset.into();
+                    assert_eq!(effect, Vec3::ZERO);
+                    true
+                } else {
+                    false
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_doppler_shift() {
+        let doppler = Doppler::default();
+
+        // approaching sources are raised in pitch
+        let approaching = doppler.shift(Vec3::X * 10.0, Vec3::NEG_X * 34.3, Vec3::ZERO, Vec3::ZERO);
+        assert!((approaching - 1.0 / 0.9).abs() < 1e-4);
+
+        // receding sources are lowered
+        let receding = doppler.shift(Vec3::X * 10.0, Vec3::X * 34.3, Vec3::ZERO, Vec3::ZERO);
+        assert!((receding - 1.0 / 1.1).abs() < 1e-4);
+
+        // perpendicular motion has no effect
+        let passing = doppler.shift(Vec3::X * 10.0, Vec3::Y * 34.3, Vec3::ZERO, Vec3::ZERO);
+        assert!((passing - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_velocity_source() {
+        #[derive(Component)]
+        struct Velocity(Vec3);
+
+        impl VelocitySource for Velocity {
+            fn velocity(&self) -> Vec3 {
+                self.0
+            }
         }
+
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.spawn(Velocity(Vec3::X));
+        });
+        app.register_velocity_source::<Velocity>();
+        app.update();
+
+        run(&mut app, |q: Single<&EmitterVelocity>| {
+            assert_eq!(q.0, Vec3::X);
+        });
     }
 }
@@ -0,0 +1,332 @@
+//! Emitting MIDI clock from the musical transport.
+//!
+//! [`MidiClockPlugin`] sends MIDI beat clock (24 pulses per quarter note),
+//! along with start, stop, continue, and song position messages, derived
+//! from Firewheel's musical transport. External sequencers, drum machines,
+//! and lighting rigs following the clock will stay aligned with events
+//! scheduled in [`InstantMusical`], even as the tempo changes.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, time::midi_clock::MidiClockPlugin};
+//! App::new().add_plugins((
+//!     DefaultPlugins,
+//!     SeedlingPlugin::default(),
+//!     // Send clock to the first output port whose name contains "IAC".
+//!     MidiClockPlugin::new().with_port("IAC"),
+//! ));
+//! ```
+//!
+//! Pulses are sent from a dedicated thread, extrapolating from the
+//! transport position read at the beginning of each frame. This keeps
+//! the clock steady even when frame times are not.
+//!
+//! This requires the `midi_clock` feature.
+//!
+//! [`InstantMusical`]: firewheel::clock::InstantMusical
+
+use crate::context::AudioContext;
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::TimeSystems;
+use midir::{MidiOutput, MidiOutputConnection};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+const PULSES_PER_BEAT: f64 = 24.0;
+const PULSES_PER_SIXTEENTH: i64 = 6;
+
+const CLOCK: u8 = 0xF8;
+const START: u8 = 0xFA;
+const CONTINUE: u8 = 0xFB;
+const STOP: u8 = 0xFC;
+const SONG_POSITION: u8 = 0xF2;
+
+/// Send MIDI clock derived from the musical transport.
+///
+/// See the [module docs][self] for usage.
+#[derive(Debug, Default, Clone)]
+pub struct MidiClockPlugin {
+    /// A substring of the output port's name.
+    ///
+    /// If `None`, the first available port is used.
+    pub port: Option<String>,
+}
+
+impl MidiClockPlugin {
+    /// Send clock to the first available output port.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send clock to the first output port whose name contains `port`.
+    pub fn with_port(self, port: impl Into<String>) -> Self {
+        Self {
+            port: Some(port.into()),
+        }
+    }
+}
+
+impl Plugin for MidiClockPlugin {
+    fn build(&self, app: &mut App) {
+        match MidiClock::connect(self.port.as_deref()) {
+            Ok(clock) => {
+                app.insert_resource(clock)
+                    .add_systems(First, update_midi_clock.after(TimeSystems));
+            }
+            Err(e) => {
+                bevy_log::error!("failed to open MIDI clock output: {e}");
+            }
+        }
+    }
+}
+
+/// An open MIDI clock output.
+///
+/// The output is closed when this resource is removed.
+#[derive(Resource)]
+pub struct MidiClock {
+    shared: Arc<Mutex<Shared>>,
+    port_name: String,
+}
+
+impl core::fmt::Debug for MidiClock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MidiClock")
+            .field("port_name", &self.port_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MidiClock {
+    fn connect(port: Option<&str>) -> Result<Self, Box<dyn core::error::Error>> {
+        let output = MidiOutput::new("bevy_seedling")?;
+
+        let mut selected = None;
+        for candidate in output.ports() {
+            let name = output.port_name(&candidate)?;
+            if port.is_none_or(|port| name.contains(port)) {
+                selected = Some((candidate, name));
+                break;
+            }
+        }
+
+        let Some((port, port_name)) = selected else {
+            return Err("no matching MIDI output port".into());
+        };
+
+        let connection = output
+            .connect(&port, "bevy_seedling clock")
+            .map_err(|e| e.to_string())?;
+
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("seedling midi clock".into())
+            .spawn(move || run_clock(connection, thread_shared))?;
+
+        Ok(Self { shared, port_name })
+    }
+
+    /// The name of the connected output port.
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+}
+
+impl Drop for MidiClock {
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.shutdown = true;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    anchor: Option<Anchor>,
+    shutdown: bool,
+}
+
+/// A transport position sampled from the ECS.
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    at: Instant,
+    beat: f64,
+    beats_per_second: f64,
+    playing: bool,
+}
+
+fn run_clock(mut connection: MidiOutputConnection, shared: Arc<Mutex<Shared>>) {
+    let mut tracker = PulseTracker::default();
+
+    loop {
+        let anchor = {
+            let shared = shared.lock().unwrap();
+            if shared.shutdown {
+                break;
+            }
+            shared.anchor
+        };
+
+        if let Some(anchor) = anchor {
+            let beat = if anchor.playing {
+                anchor.beat + anchor.at.elapsed().as_secs_f64() * anchor.beats_per_second
+            } else {
+                anchor.beat
+            };
+
+            tracker.advance(beat, anchor.playing, |message| {
+                let _ = connection.send(message);
+            });
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    if tracker.playing {
+        let _ = connection.send(&[STOP]);
+    }
+    connection.close();
+}
+
+/// Converts a transport position into MIDI clock messages.
+#[derive(Debug, Default)]
+struct PulseTracker {
+    playing: bool,
+    next_pulse: i64,
+}
+
+impl PulseTracker {
+    fn advance(&mut self, beat: f64, playing: bool, mut send: impl FnMut(&[u8])) {
+        if playing != self.playing {
+            self.playing = playing;
+
+            if !playing {
+                send(&[STOP]);
+                return;
+            }
+
+            if beat < 1.0 / PULSES_PER_BEAT {
+                self.next_pulse = 0;
+                send(&[START]);
+            } else {
+                self.locate(beat, &mut send);
+                send(&[CONTINUE]);
+            }
+        }
+
+        if !self.playing {
+            return;
+        }
+
+        let pulse = (beat * PULSES_PER_BEAT).floor() as i64;
+
+        // Seeking and looping relocate the receiver rather
+        // than flooding it with pulses.
+        if pulse > self.next_pulse + PULSES_PER_BEAT as i64
+            || pulse < self.next_pulse - PULSES_PER_SIXTEENTH
+        {
+            send(&[STOP]);
+            self.locate(beat, &mut send);
+            send(&[CONTINUE]);
+        }
+
+        while self.next_pulse <= pulse {
+            send(&[CLOCK]);
+            self.next_pulse += 1;
+        }
+    }
+
+    /// Send a song position pointer, which has a resolution of sixteenth notes.
+    fn locate(&mut self, beat: f64, send: &mut impl FnMut(&[u8])) {
+        let position = (beat * 4.0).floor().clamp(0.0, 0x3FFF as f64) as u16;
+        send(&[
+            SONG_POSITION,
+            (position & 0x7F) as u8,
+            ((position >> 7) & 0x7F) as u8,
+        ]);
+        self.next_pulse = position as i64 * PULSES_PER_SIXTEENTH;
+    }
+}
+
+#[derive(Debug, Default)]
+struct TransportEstimate {
+    seconds: f64,
+    beat: f64,
+    beats_per_second: f64,
+}
+
+fn update_midi_clock(
+    clock: Res<MidiClock>,
+    context: Option<ResMut<AudioContext>>,
+    mut estimate: Local<TransportEstimate>,
+) {
+    let Some(mut context) = context else {
+        return;
+    };
+
+    let now = context.now();
+    let seconds = now.seconds.0;
+    let playing = now.transport_is_playing && now.musical.is_some();
+    let beat = now.musical.map(|m| m.0).unwrap_or(estimate.beat);
+
+    // The tempo is measured rather than read from the transport
+    // so tempo automation and speed changes are followed for free.
+    if playing && seconds > estimate.seconds {
+        let measured = (beat - estimate.beat) / (seconds - estimate.seconds);
+        if measured > 0.0 {
+            estimate.beats_per_second = measured;
+        }
+    }
+
+    estimate.seconds = seconds;
+    estimate.beat = beat;
+
+    clock.shared.lock().unwrap().anchor = Some(Anchor {
+        at: Instant::now(),
+        beat,
+        beats_per_second: estimate.beats_per_second,
+        playing,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn advance(tracker: &mut PulseTracker, beat: f64, playing: bool) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        tracker.advance(beat, playing, |m| messages.push(m.to_vec()));
+        messages
+    }
+
+    #[test]
+    fn test_pulses() {
+        let mut tracker = PulseTracker::default();
+
+        assert_eq!(advance(&mut tracker, 0.0, true), [vec![START], vec![CLOCK]]);
+        assert_eq!(advance(&mut tracker, 0.5, true), vec![vec![CLOCK]; 12]);
+        assert!(advance(&mut tracker, 0.5, true).is_empty());
+        assert_eq!(advance(&mut tracker, 0.6, false), [vec![STOP]]);
+
+        // Resuming mid-song locates to the nearest sixteenth.
+        assert_eq!(
+            advance(&mut tracker, 2.0, true),
+            [vec![SONG_POSITION, 8, 0], vec![CONTINUE], vec![CLOCK]]
+        );
+
+        // Loops relocate.
+        assert_eq!(
+            advance(&mut tracker, 0.0, true),
+            [
+                vec![STOP],
+                vec![SONG_POSITION, 0, 0],
+                vec![CONTINUE],
+                vec![CLOCK]
+            ]
+        );
+    }
+}
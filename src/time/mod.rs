@@ -35,6 +35,9 @@ use std::time::Duration;
 
 use crate::context::AudioContext;
 
+#[cfg(feature = "midi_clock")]
+pub mod midi_clock;
+
 pub(crate) struct TimePlugin;
 
 impl Plugin for TimePlugin {
@@ -0,0 +1,551 @@
+//! A randomized soak-testing harness.
+//!
+//! [`ChaosHarness`] drives an app through thousands of updates,
+//! randomly spawning and despawning pools, sample players, and nodes,
+//! and restarting the audio stream. After every update, it checks
+//! that the ECS and audio graph agree with each other.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, utils::chaos::*};
+//! // Register your own nodes here.
+//! let mut app = ChaosHarness::app();
+//!
+//! let report = ChaosHarness::new(ChaosConfig {
+//!     updates: 200,
+//!     ..Default::default()
+//! })
+//! .with_node(|rng| LowPassNode {
+//!     frequency: rng.range(100.0, 10_000.0),
+//! })
+//! .run(&mut app)
+//! .unwrap();
+//!
+//! assert!(report.players_spawned > 0);
+//! ```
+
+use bevy_app::{PluginsState, prelude::*};
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_platform::collections::{HashMap, HashSet};
+use core::num::NonZeroUsize;
+use firewheel::{
+    clock::DurationSeconds, node::NodeID, nodes::volume::VolumeNode,
+    sample_resource::SampleResource,
+};
+use std::sync::Arc;
+
+use crate::{
+    configuration::GraphConfiguration,
+    context::{AudioContext, AudioStreamConfig},
+    edge::{AudioGraphOutput, Connect},
+    node::FirewheelNode,
+    pool::{PlaybackCompletionEvent, PoolSize, Sampler, SamplerOf, SamplerPool},
+    prelude::{PoolLabel, SeedlingPlugin},
+    sample::{AudioSample, SamplePlayer},
+    utils::profiling::{ProfilingBackend, VirtualClock},
+};
+
+/// A small, deterministic random number generator.
+///
+/// This is provided to node factories so that a given
+/// seed always produces the same sequence of operations.
+#[derive(Debug, Clone)]
+pub struct ChaosRng(u64);
+
+impl ChaosRng {
+    /// Create a new generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        // xorshift can't escape zero
+        Self(seed.max(1))
+    }
+
+    /// Generate the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Generate a random `f32` in `[0, 1)`.
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Generate a random `f32` in `[min, max)`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.unit()
+    }
+
+    /// Returns `true` with the given probability.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    /// Pick a random index below `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0);
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+/// Configuration for a [`ChaosHarness`].
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// The random seed.
+    pub seed: u64,
+    /// The number of randomized updates to run.
+    pub updates: usize,
+    /// The number of quiet updates after the randomized updates, allowing
+    /// one-shot samples to complete before the final checks.
+    pub settle_updates: usize,
+    /// The maximum number of pools alive at once.
+    pub max_pools: usize,
+    /// The maximum number of sample players alive at once.
+    pub max_players: usize,
+    /// The maximum number of free-standing nodes alive at once.
+    pub max_nodes: usize,
+    /// The probability of restarting the audio stream on any given update.
+    pub restart_probability: f32,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x5eed,
+            updates: 2000,
+            settle_updates: 120,
+            max_pools: 4,
+            max_players: 48,
+            max_nodes: 16,
+            restart_probability: 0.002,
+        }
+    }
+}
+
+/// A summary of a completed [`ChaosHarness`] run.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosReport {
+    /// The total number of updates run, including settling.
+    pub updates: usize,
+    /// The number of pools spawned.
+    pub pools_spawned: usize,
+    /// The number of sample players spawned.
+    pub players_spawned: usize,
+    /// The number of free-standing nodes spawned.
+    pub nodes_spawned: usize,
+    /// The number of stream restarts.
+    pub restarts: usize,
+    /// The number of [`PlaybackCompletionEvent`]s observed.
+    pub completions: usize,
+}
+
+/// A broken invariant found by a [`ChaosHarness`].
+#[derive(Debug, Clone)]
+pub struct ChaosViolation {
+    /// The update on which the violation was detected.
+    pub update: usize,
+    /// A description of the violation.
+    pub message: String,
+}
+
+impl core::fmt::Display for ChaosViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invariant violated on update {}: {}",
+            self.update, self.message
+        )
+    }
+}
+
+impl core::error::Error for ChaosViolation {}
+
+type NodeFactory = Arc<dyn Fn(&mut EntityWorldMut, &mut ChaosRng) + Send + Sync>;
+
+/// A randomized soak-testing harness.
+///
+/// The harness checks the following invariants after every update:
+///
+/// - Every edge in the audio graph connects two nodes that exist.
+/// - Every [`FirewheelNode`] refers to a node in the audio graph.
+/// - Every sampler assignment points to a live [`SamplePlayer`].
+/// - No sample player receives more than one [`PlaybackCompletionEvent`].
+///
+/// Once the randomized updates are complete, the harness lets playback
+/// settle and checks that no one-shot player is still holding a sampler.
+///
+/// Node types registered with [`RegisterNode`][crate::prelude::RegisterNode]
+/// can be added with [`ChaosHarness::with_node`]. They'll be spawned as
+/// free-standing nodes and as pool effects.
+pub struct ChaosHarness {
+    config: ChaosConfig,
+    nodes: Vec<NodeFactory>,
+}
+
+impl core::fmt::Debug for ChaosHarness {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChaosHarness")
+            .field("config", &self.config)
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct ChaosPool(usize);
+
+#[derive(Component)]
+struct ChaosNode;
+
+#[derive(Component)]
+struct OneShot;
+
+/// Tracks sample player lifecycles across updates.
+#[derive(Resource, Default)]
+struct ChaosLedger {
+    completions: HashMap<Entity, usize>,
+    /// One-shot players that were assigned a sampler and have
+    /// not yet completed or been despawned by the harness.
+    outstanding: HashSet<Entity>,
+}
+
+/// A short, mono tone used for every sample player.
+struct ChaosTone(Vec<f32>);
+
+impl SampleResource for ChaosTone {
+    fn num_channels(&self) -> NonZeroUsize {
+        NonZeroUsize::MIN
+    }
+
+    fn len_frames(&self) -> u64 {
+        self.0.len() as u64
+    }
+
+    fn fill_buffers(
+        &self,
+        buffers: &mut [&mut [f32]],
+        buffer_range: core::ops::Range<usize>,
+        start_frame: u64,
+    ) {
+        let start_frame = start_frame as usize;
+        let available = self.0.len().saturating_sub(start_frame);
+        let frames = buffer_range.len().min(available);
+
+        buffers[0][buffer_range.start..buffer_range.start + frames]
+            .copy_from_slice(&self.0[start_frame..start_frame + frames]);
+    }
+}
+
+impl ChaosHarness {
+    /// Create a new harness.
+    ///
+    /// By default, only [`VolumeNode`]s are spawned.
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            config,
+            nodes: vec![Arc::new(|entity, _| {
+                entity.insert(VolumeNode::default());
+            })],
+        }
+    }
+
+    /// Construct an app suitable for chaos testing.
+    ///
+    /// This uses the [`ProfilingBackend`] driven by a [`VirtualClock`],
+    /// so runs are fast and deterministic. Register any additional
+    /// nodes before calling [`ChaosHarness::run`].
+    pub fn app() -> App {
+        let mut app = App::new();
+
+        app.add_plugins((
+            bevy_app::TaskPoolPlugin::default(),
+            bevy_time::TimePlugin,
+            AssetPlugin::default(),
+            SeedlingPlugin::<ProfilingBackend> {
+                graph_config: GraphConfiguration::Minimal,
+                ..SeedlingPlugin::<ProfilingBackend>::new()
+            },
+            VirtualClock::new(DurationSeconds(1.0 / 60.0)),
+        ));
+
+        app
+    }
+
+    /// Add a node type to spawn.
+    pub fn with_node<T, F>(mut self, factory: F) -> Self
+    where
+        T: Bundle,
+        F: Fn(&mut ChaosRng) -> T + Send + Sync + 'static,
+    {
+        self.nodes.push(Arc::new(move |entity, rng| {
+            entity.insert(factory(rng));
+        }));
+        self
+    }
+
+    /// Run the harness to completion.
+    pub fn run(self, app: &mut App) -> Result<ChaosReport, ChaosViolation> {
+        match app.plugins_state() {
+            PluginsState::Ready => {
+                app.finish();
+                app.cleanup();
+            }
+            PluginsState::Finished => app.cleanup(),
+            _ => {}
+        }
+
+        app.init_resource::<ChaosLedger>()
+            .add_observer(record_completion)
+            .add_observer(record_assignment);
+
+        let mut rng = ChaosRng::new(self.config.seed);
+        let mut report = ChaosReport::default();
+
+        let tone = (0..2400)
+            .map(|i| (i as f32 * core::f32::consts::TAU * 440.0 / 48000.0).sin() * 0.25)
+            .collect();
+        let sample = app
+            .world_mut()
+            .resource_mut::<Assets<AudioSample>>()
+            .add(AudioSample::from_resource(Arc::new(ChaosTone(tone))));
+
+        // let the stream start
+        app.update();
+
+        for update in 0..self.config.updates {
+            self.step(app.world_mut(), &mut rng, &sample, &mut report);
+            app.update();
+            report.updates += 1;
+
+            check_invariants(app.world_mut(), update, false)?;
+        }
+
+        for update in 0..self.config.settle_updates {
+            app.update();
+            report.updates += 1;
+
+            check_invariants(app.world_mut(), self.config.updates + update, false)?;
+        }
+
+        check_invariants(app.world_mut(), report.updates, true)?;
+
+        report.completions = app
+            .world()
+            .resource::<ChaosLedger>()
+            .completions
+            .values()
+            .sum();
+
+        Ok(report)
+    }
+
+    fn step(
+        &self,
+        world: &mut World,
+        rng: &mut ChaosRng,
+        sample: &Handle<AudioSample>,
+        report: &mut ChaosReport,
+    ) {
+        let pools = sorted_entities::<With<SamplerPool<ChaosPool>>>(world);
+        let players = sorted_entities::<With<SamplePlayer>>(world);
+        let nodes = sorted_entities::<With<ChaosNode>>(world);
+
+        match rng.index(7) {
+            0 if pools.len() < self.config.max_pools => {
+                let used: HashSet<usize> = world
+                    .query::<&SamplerPool<ChaosPool>>()
+                    .iter(world)
+                    .map(|pool| pool.0.0)
+                    .collect();
+                let Some(label) = (0..self.config.max_pools).find(|i| !used.contains(i)) else {
+                    return;
+                };
+                let label = ChaosPool(label);
+                let max = 1 + rng.index(8);
+                let pool = world.spawn((SamplerPool(label), PoolSize(1..=max))).id();
+
+                for _ in 0..rng.index(3) {
+                    let factory = &self.nodes[rng.index(self.nodes.len())];
+                    let mut effect = world.spawn(crate::pool::sample_effects::EffectOf(pool));
+                    factory(&mut effect, rng);
+                }
+
+                report.pools_spawned += 1;
+            }
+            1 if !pools.is_empty() => {
+                world.despawn(pools[rng.index(pools.len())]);
+            }
+            2 | 3 if players.len() < self.config.max_players => {
+                let label = ChaosPool(rng.index(self.config.max_pools));
+                let player = SamplePlayer::new(sample.clone());
+
+                if rng.chance(0.25) {
+                    world.spawn((label, player.looping()));
+                } else {
+                    world.spawn((label, player, OneShot));
+                }
+
+                report.players_spawned += 1;
+            }
+            4 if !players.is_empty() => {
+                let player = players[rng.index(players.len())];
+                world.despawn(player);
+                world
+                    .resource_mut::<ChaosLedger>()
+                    .outstanding
+                    .remove(&player);
+            }
+            5 if nodes.len() < self.config.max_nodes => {
+                let factory = &self.nodes[rng.index(self.nodes.len())];
+                let mut entity = world.spawn(ChaosNode);
+                factory(&mut entity, rng);
+                let entity = entity.id();
+
+                world.commands().entity(entity).connect(AudioGraphOutput);
+                world.flush();
+
+                report.nodes_spawned += 1;
+            }
+            6 if !nodes.is_empty() => {
+                world.despawn(nodes[rng.index(nodes.len())]);
+            }
+            _ => {}
+        }
+
+        if rng.chance(self.config.restart_probability) {
+            world
+                .resource_mut::<AudioStreamConfig<ProfilingBackend>>()
+                .set_changed();
+            report.restarts += 1;
+        }
+    }
+}
+
+fn sorted_entities<F: bevy_ecs::query::QueryFilter>(world: &mut World) -> Vec<Entity> {
+    let mut entities: Vec<_> = world.query_filtered::<Entity, F>().iter(world).collect();
+    entities.sort();
+    entities
+}
+
+fn record_completion(trigger: On<PlaybackCompletionEvent>, mut ledger: ResMut<ChaosLedger>) {
+    let player = trigger.event().entity;
+    *ledger.completions.entry(player).or_default() += 1;
+    ledger.outstanding.remove(&player);
+}
+
+fn record_assignment(
+    trigger: On<Insert, Sampler>,
+    one_shots: Query<(), With<OneShot>>,
+    mut ledger: ResMut<ChaosLedger>,
+) {
+    let player = trigger.event_target();
+    if one_shots.contains(player) && !ledger.completions.contains_key(&player) {
+        ledger.outstanding.insert(player);
+    }
+}
+
+fn check_invariants(world: &mut World, update: usize, settled: bool) -> Result<(), ChaosViolation> {
+    let violation = |message: String| ChaosViolation { update, message };
+
+    let node_ids: Vec<(Entity, NodeID)> = world
+        .query::<(Entity, &FirewheelNode)>()
+        .iter(world)
+        .map(|(entity, node)| (entity, node.0))
+        .collect();
+
+    let graph_errors = world.resource_mut::<AudioContext>().with(move |context| {
+        let nodes: HashSet<NodeID> = context.nodes().into_iter().map(|n| n.id).collect();
+        let mut errors = Vec::new();
+
+        for edge in context.edges().into_iter() {
+            if !nodes.contains(&edge.src_node) || !nodes.contains(&edge.dst_node) {
+                errors.push(format!(
+                    "edge {:?} connects missing nodes {:?} -> {:?}",
+                    edge.id, edge.src_node, edge.dst_node
+                ));
+            }
+        }
+
+        for (entity, node) in node_ids {
+            if !nodes.contains(&node) {
+                errors.push(format!("{entity} refers to missing audio node {node:?}"));
+            }
+        }
+
+        errors
+    });
+
+    if let Some(error) = graph_errors.into_iter().next() {
+        return Err(violation(error));
+    }
+
+    let assignments: Vec<(Entity, Entity)> = world
+        .query::<(Entity, &SamplerOf)>()
+        .iter(world)
+        .map(|(sampler, of)| (sampler, of.0))
+        .collect();
+
+    for (sampler, player) in assignments {
+        if world.get::<SamplePlayer>(player).is_none() {
+            return Err(violation(format!(
+                "sampler {sampler} is assigned to {player}, which is not a live sample player"
+            )));
+        }
+    }
+
+    let ledger = world.resource::<ChaosLedger>();
+    if let Some((player, count)) = ledger.completions.iter().find(|(_, c)| **c > 1) {
+        return Err(violation(format!(
+            "{player} received {count} completion events"
+        )));
+    }
+
+    if settled {
+        // Players may lose their sampler without completing if their pool is
+        // despawned, but they should never hold onto one after settling.
+        if let Some(player) = ledger
+            .outstanding
+            .iter()
+            .find(|player| world.get::<Sampler>(**player).is_some())
+        {
+            return Err(violation(format!(
+                "one-shot player {player} still holds a sampler after settling"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_chaos() {
+        let mut app = ChaosHarness::app();
+
+        let report = ChaosHarness::new(ChaosConfig {
+            updates: 400,
+            restart_probability: 0.01,
+            ..Default::default()
+        })
+        .with_node(|rng| LowPassNode {
+            frequency: rng.range(100.0, 10_000.0),
+        })
+        .run(&mut app)
+        .unwrap();
+
+        assert!(report.pools_spawned > 0);
+        assert!(report.players_spawned > 0);
+    }
+}
@@ -73,6 +73,11 @@ impl<T> FixedVec<T> {
         }
     }
 
+    /// Remove the most recently pushed value, if any.
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
     /// Clear the sequence, removing all values.
     pub fn clear(&mut self) {
         self.0.clear();
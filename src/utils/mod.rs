@@ -1,9 +1,13 @@
 //! A collection of audio utilities.
 
+#[cfg(any(feature = "profiling", test))]
+pub mod chaos;
 pub(crate) mod entity_set;
 #[cfg(any(feature = "profiling", test))]
-pub(crate) mod profiling;
+pub mod profiling;
 
 pub mod fixed_vec;
 pub mod perceptual_volume;
 pub mod timeline;
+pub mod trace;
+pub(crate) mod wav;
@@ -1,5 +1,8 @@
 //! Profiling utilities.
 
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::{TimeSystems, TimeUpdateStrategy};
 use firewheel::{
     StreamInfo,
     backend::{AudioBackend, DeviceInfo},
@@ -9,13 +12,176 @@ use firewheel::{
 };
 use std::{
     num::NonZeroU32,
-    sync::mpsc::{self, TryRecvError},
+    sync::{
+        Arc, Condvar, Mutex,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    time::Duration,
 };
 
+use crate::context::AudioStreamConfig;
+
+const SAMPLE_RATE: u32 = 48000;
+const BLOCK_SIZE: usize = 128;
+const CHANNELS: usize = 2;
+
 /// A very simple backend for testing and profiling.
 #[allow(dead_code)]
 pub struct ProfilingBackend {
     processor: mpsc::Sender<FirewheelProcessor<Self>>,
+    is_virtual: bool,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for ProfilingBackend {
+    fn drop(&mut self) {
+        // Disconnecting the channel stops the processing thread. Waiting
+        // for it ensures the processor is dropped before this returns,
+        // so the stream can be restarted immediately.
+        self.processor = mpsc::channel().0;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// [`ProfilingBackend`]'s configuration.
+#[derive(Debug, Default, Clone)]
+pub struct ProfilingConfig {
+    /// Drive the stream with a [`VirtualClock`] rather than in realtime.
+    pub virtual_clock: Option<VirtualClock>,
+}
+
+#[derive(Debug, Default)]
+struct VirtualClockState {
+    /// Frames requested but not yet processed.
+    pending_frames: u64,
+    /// The number of processing threads attached to this clock.
+    attached: usize,
+}
+
+#[derive(Debug, Default)]
+struct VirtualClockInner {
+    state: Mutex<VirtualClockState>,
+    condvar: Condvar,
+}
+
+/// A virtual audio clock for the [`ProfilingBackend`].
+///
+/// Rather than processing audio in realtime, the backend processes
+/// exactly `per_update` worth of audio each time the app updates,
+/// blocking until it's done. This makes scheduled events and sample
+/// lifetimes deterministic, and tests that depend on them fast.
+///
+/// Adding the clock as a plugin configures the stream to use it.
+/// Bevy's [`Time`][bevy_time::Time] is also set to advance by `per_update`,
+/// so timers like [`SampleQueueLifetime`][crate::sample::SampleQueueLifetime]
+/// stay in step with the audio.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_seedling::{prelude::*, utils::profiling::*};
+/// let mut app = App::new();
+/// app.add_plugins((
+///     MinimalPlugins,
+///     AssetPlugin::default(),
+///     SeedlingPlugin::<ProfilingBackend>::new(),
+///     VirtualClock::new(DurationSeconds(1.0 / 60.0)),
+/// ));
+/// ```
+#[derive(Debug, Clone, Resource)]
+pub struct VirtualClock {
+    inner: Arc<VirtualClockInner>,
+    per_update: DurationSeconds,
+}
+
+impl VirtualClock {
+    /// Create a new virtual clock that advances by `per_update` every update.
+    pub fn new(per_update: DurationSeconds) -> Self {
+        Self {
+            inner: Default::default(),
+            per_update,
+        }
+    }
+
+    /// The amount the clock advances every update.
+    pub fn per_update(&self) -> DurationSeconds {
+        self.per_update
+    }
+
+    /// Advance the clock by `duration`, blocking until the audio is processed.
+    ///
+    /// The duration is rounded to the nearest frame. If no stream is
+    /// currently running, this returns immediately.
+    pub fn advance(&self, duration: DurationSeconds) {
+        let frames = (duration.0.max(0.0) * SAMPLE_RATE as f64).round() as u64;
+
+        let mut state = self.inner.state.lock().unwrap();
+        if state.attached == 0 {
+            return;
+        }
+
+        state.pending_frames += frames;
+        self.inner.condvar.notify_all();
+
+        while state.pending_frames > 0 && state.attached > 0 {
+            state = self.inner.condvar.wait(state).unwrap();
+        }
+    }
+
+    fn attach(&self) {
+        self.inner.state.lock().unwrap().attached += 1;
+    }
+
+    fn detach(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.attached -= 1;
+        if state.attached == 0 {
+            state.pending_frames = 0;
+        }
+        self.inner.condvar.notify_all();
+    }
+
+    /// Wait for up to a block of frames to process.
+    fn next_block(&self, timeout: Duration) -> Option<usize> {
+        let state = self.inner.state.lock().unwrap();
+        let (state, _) = self
+            .inner
+            .condvar
+            .wait_timeout_while(state, timeout, |s| s.pending_frames == 0)
+            .unwrap();
+
+        (state.pending_frames > 0).then(|| (state.pending_frames as usize).min(BLOCK_SIZE))
+    }
+
+    fn complete_block(&self, frames: usize) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.pending_frames = state.pending_frames.saturating_sub(frames as u64);
+        self.inner.condvar.notify_all();
+    }
+}
+
+impl Plugin for VirtualClock {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                self.per_update.0,
+            )))
+            .add_systems(First, advance_virtual_clock.before(TimeSystems));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(mut config) = app
+            .world_mut()
+            .get_resource_mut::<AudioStreamConfig<ProfilingBackend>>()
+        {
+            config.0.virtual_clock = Some(self.clone());
+        }
+    }
+}
+
+fn advance_virtual_clock(clock: Res<VirtualClock>) {
+    clock.advance(clock.per_update);
 }
 
 impl core::fmt::Debug for ProfilingBackend {
@@ -39,7 +205,7 @@ impl core::fmt::Display for ProfilingError {
 impl std::error::Error for ProfilingError {}
 
 impl AudioBackend for ProfilingBackend {
-    type Config = ();
+    type Config = ProfilingConfig;
     type Instant = std::time::Instant;
 
     type StartStreamError = ProfilingError;
@@ -61,72 +227,33 @@ impl AudioBackend for ProfilingBackend {
         &self,
         process_timestamp: Self::Instant,
     ) -> Option<std::time::Duration> {
+        // The virtual clock only moves when it's advanced.
+        if self.is_virtual {
+            return Some(Duration::ZERO);
+        }
+
         Some(std::time::Instant::now() - process_timestamp)
     }
 
-    fn start_stream(_: Self::Config) -> Result<(Self, StreamInfo), Self::StartStreamError> {
-        let sample_rate = NonZeroU32::new(48000).unwrap();
+    fn start_stream(config: Self::Config) -> Result<(Self, StreamInfo), Self::StartStreamError> {
+        let sample_rate = NonZeroU32::new(SAMPLE_RATE).unwrap();
         let (sender, receiver) = mpsc::channel();
 
-        const BLOCK_SIZE: usize = 128;
-        const CHANNELS: usize = 2;
-
-        std::thread::spawn(move || {
-            let mut processor = None;
-
-            let mut seconds = DurationSeconds(0.0);
-
-            let block_duration = BLOCK_SIZE as f64 / sample_rate.get() as f64;
-            let input = [0f32; BLOCK_SIZE * CHANNELS];
-            let mut output = [0f32; BLOCK_SIZE * CHANNELS];
-
-            let start = std::time::Instant::now();
-
-            loop {
-                match &mut processor {
-                    None => {
-                        let new_processor: FirewheelProcessor<Self> = receiver.recv().unwrap();
-                        processor = Some(new_processor);
-                    }
-                    Some(processor) => {
-                        let now = std::time::Instant::now();
-
-                        processor.process_interleaved(
-                            &input,
-                            &mut output,
-                            firewheel::backend::BackendProcessInfo {
-                                num_in_channels: CHANNELS,
-                                num_out_channels: CHANNELS,
-                                frames: BLOCK_SIZE,
-                                process_timestamp: now,
-                                duration_since_stream_start: start - now,
-                                input_stream_status: StreamStatus::empty(),
-                                output_stream_status: StreamStatus::empty(),
-                                dropped_frames: 0,
-                            }, // CHANNELS,
-                               // CHANNELS,
-                               // BLOCK_SIZE,
-                               // now,
-                               // start - now,
-                               // StreamStatus::empty(),
-                               // StreamStatus::empty(),
-                               // 0,
-                        );
-                        std::thread::sleep(std::time::Duration::from_secs_f64(block_duration));
-                        seconds.0 += block_duration;
-
-                        match receiver.try_recv() {
-                            Ok(new_processor) => *processor = new_processor,
-                            Err(TryRecvError::Empty) => {}
-                            Err(TryRecvError::Disconnected) => break,
-                        }
-                    }
-                }
+        let is_virtual = config.virtual_clock.is_some();
+        let thread = match config.virtual_clock {
+            Some(clock) => {
+                clock.attach();
+                std::thread::spawn(move || run_virtual(clock, receiver))
             }
-        });
+            None => std::thread::spawn(move || run_realtime(sample_rate, receiver)),
+        };
 
         Ok((
-            Self { processor: sender },
+            Self {
+                processor: sender,
+                is_virtual,
+                thread: Some(thread),
+            },
             StreamInfo {
                 prev_sample_rate: sample_rate,
                 sample_rate,
@@ -150,3 +277,137 @@ impl AudioBackend for ProfilingBackend {
         Ok(())
     }
 }
+
+fn run_realtime(sample_rate: NonZeroU32, receiver: Receiver<FirewheelProcessor<ProfilingBackend>>) {
+    let mut processor = None;
+
+    let block_duration = BLOCK_SIZE as f64 / sample_rate.get() as f64;
+    let input = [0f32; BLOCK_SIZE * CHANNELS];
+    let mut output = [0f32; BLOCK_SIZE * CHANNELS];
+
+    let start = std::time::Instant::now();
+
+    loop {
+        match &mut processor {
+            None => {
+                let Ok(new_processor) = receiver.recv() else {
+                    break;
+                };
+                processor = Some(new_processor);
+            }
+            Some(processor) => {
+                let now = std::time::Instant::now();
+
+                processor.process_interleaved(
+                    &input,
+                    &mut output,
+                    firewheel::backend::BackendProcessInfo {
+                        num_in_channels: CHANNELS,
+                        num_out_channels: CHANNELS,
+                        frames: BLOCK_SIZE,
+                        process_timestamp: now,
+                        duration_since_stream_start: start - now,
+                        input_stream_status: StreamStatus::empty(),
+                        output_stream_status: StreamStatus::empty(),
+                        dropped_frames: 0,
+                    },
+                );
+                std::thread::sleep(std::time::Duration::from_secs_f64(block_duration));
+
+                match receiver.try_recv() {
+                    Ok(new_processor) => *processor = new_processor,
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+        }
+    }
+}
+
+fn run_virtual(clock: VirtualClock, receiver: Receiver<FirewheelProcessor<ProfilingBackend>>) {
+    let mut processor = None;
+
+    let input = [0f32; BLOCK_SIZE * CHANNELS];
+    let mut output = [0f32; BLOCK_SIZE * CHANNELS];
+
+    let start = std::time::Instant::now();
+    let mut frames_processed = 0u64;
+
+    loop {
+        let frames = clock.next_block(Duration::from_millis(10));
+
+        // The processor may arrive while we're waiting for frames.
+        match receiver.try_recv() {
+            Ok(new_processor) => processor = Some(new_processor),
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => break,
+        }
+
+        let Some(frames) = frames else {
+            continue;
+        };
+
+        // Without a processor, there's nothing to advance.
+        if let Some(processor) = &mut processor {
+            processor.process_interleaved(
+                &input[..frames * CHANNELS],
+                &mut output[..frames * CHANNELS],
+                firewheel::backend::BackendProcessInfo {
+                    num_in_channels: CHANNELS,
+                    num_out_channels: CHANNELS,
+                    frames,
+                    process_timestamp: start,
+                    duration_since_stream_start: Duration::from_secs_f64(
+                        frames_processed as f64 / SAMPLE_RATE as f64,
+                    ),
+                    input_stream_status: StreamStatus::empty(),
+                    output_stream_status: StreamStatus::empty(),
+                    dropped_frames: 0,
+                },
+            );
+            frames_processed += frames as u64;
+        }
+
+        clock.complete_block(frames);
+    }
+
+    clock.detach();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_virtual_clock() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            SeedlingPlugin::<ProfilingBackend> {
+                graph_config: crate::configuration::GraphConfiguration::Empty,
+                ..SeedlingPlugin::<ProfilingBackend>::new()
+            },
+            VirtualClock::new(DurationSeconds(0.01)),
+        ));
+        app.finish();
+        app.cleanup();
+
+        // the stream starts during the first update
+        app.update();
+        let start = app.world().resource::<Time<Audio>>().now();
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let now = app.world().resource::<Time<Audio>>().now();
+        assert!(
+            (now.0 - start.0 - 0.1).abs() < 1e-6,
+            "{:?}",
+            now.0 - start.0
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Per-frame audio tracing.
+//!
+//! Timing bugs, like late starts or drifting tweens, are difficult to
+//! catch with logging alone. When an [`AudioTrace`] resource is present,
+//! `bevy_seedling` records a compact summary of every frame into a ring
+//! buffer: the audio clock, the frame's render range, the number of
+//! events queued per node, sampler assignments, and stream lifecycle
+//! events. The buffer can be exported in the [Chrome tracing format]
+//! and inspected in tools like Perfetto or `chrome://tracing`.
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_seedling::{prelude::*, utils::trace::AudioTrace};
+//! fn start_tracing(mut commands: Commands) {
+//!     // Keep the last ten seconds or so at 60 FPS.
+//!     commands.insert_resource(AudioTrace::new(600));
+//! }
+//!
+//! fn dump_trace(trace: Res<AudioTrace>, keys: Res<ButtonInput<KeyCode>>) {
+//!     if keys.just_pressed(KeyCode::F9) {
+//!         std::fs::write("audio_trace.json", trace.to_chrome_json()).unwrap();
+//!     }
+//! }
+//! ```
+//!
+//! Tracing is inactive until the resource is inserted,
+//! and stops when it's removed.
+//!
+//! [Chrome tracing format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+
+use crate::{
+    SeedlingSystems,
+    context::{PreStreamRestartEvent, StreamRestartEvent, StreamStartEvent},
+    node::events::AudioEvents,
+    pool::SamplerOf,
+    time::{Audio, AudioTime},
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_time::{Real, Time};
+use core::{fmt::Write, ops::Range, time::Duration};
+use firewheel::clock::InstantSeconds;
+use std::collections::VecDeque;
+
+/// A stream lifecycle event recorded in an [`AudioTrace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamTraceEvent {
+    /// The stream started at the given sample rate.
+    Started {
+        /// The stream's sample rate.
+        sample_rate: u32,
+    },
+    /// The stream is about to restart.
+    Restarting,
+    /// The stream restarted.
+    Restarted {
+        /// The sample rate before the restart.
+        previous_rate: u32,
+        /// The sample rate after the restart.
+        current_rate: u32,
+    },
+}
+
+/// A single frame recorded in an [`AudioTrace`].
+#[derive(Debug, Clone)]
+pub struct TraceFrame {
+    /// The frame's index since tracing began.
+    pub index: u64,
+    /// The wall-clock time at the end of the frame, since startup.
+    pub wall_time: Duration,
+    /// The audio clock's time for this frame.
+    pub audio_time: InstantSeconds,
+    /// The frame's audio render range.
+    pub render_range: Range<InstantSeconds>,
+    /// The number of immediate events flushed for each node.
+    ///
+    /// Nodes without events are omitted.
+    pub events: Vec<(Entity, usize)>,
+    /// Sampler assignments made during the frame,
+    /// as `(sample player, sampler)` pairs.
+    pub assignments: Vec<(Entity, Entity)>,
+    /// Stream events observed during the frame.
+    pub stream: Vec<StreamTraceEvent>,
+}
+
+/// A ring buffer of recent [`TraceFrame`]s.
+///
+/// See the [module docs][self] for details.
+#[derive(Debug, Resource)]
+pub struct AudioTrace {
+    capacity: usize,
+    frames: VecDeque<TraceFrame>,
+    next_index: u64,
+    /// Data collected over the current frame.
+    assignments: Vec<(Entity, Entity)>,
+    stream: Vec<StreamTraceEvent>,
+    events: Vec<(Entity, usize)>,
+}
+
+impl AudioTrace {
+    /// Create a new trace that retains up to `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            frames: VecDeque::with_capacity(capacity.max(1)),
+            next_index: 0,
+            assignments: Vec::new(),
+            stream: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The recorded frames, from oldest to newest.
+    pub fn frames(&self) -> impl Iterator<Item = &TraceFrame> {
+        self.frames.iter()
+    }
+
+    /// Discard all recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    fn push(&mut self, frame: TraceFrame) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Export the recorded frames in the Chrome tracing JSON format.
+    ///
+    /// Each frame is a complete event spanning its wall-clock duration.
+    /// The audio clock and per-node event counts are emitted as counters,
+    /// and assignments and stream events as instant events.
+    pub fn to_chrome_json(&self) -> String {
+        let mut events = Vec::new();
+        let mut last_wall = None;
+
+        for frame in &self.frames {
+            let end = frame.wall_time.as_secs_f64() * 1e6;
+            let start = last_wall.unwrap_or(end);
+            last_wall = Some(end);
+
+            events.push(format!(
+                r#"{{"name":"frame {}","ph":"X","ts":{start:.1},"dur":{:.1},"pid":1,"tid":1,"args":{{"audio_time":{},"render_start":{},"render_end":{}}}}}"#,
+                frame.index,
+                end - start,
+                frame.audio_time.0,
+                frame.render_range.start.0,
+                frame.render_range.end.0,
+            ));
+
+            events.push(format!(
+                r#"{{"name":"audio clock","ph":"C","ts":{end:.1},"pid":1,"args":{{"seconds":{}}}}}"#,
+                frame.audio_time.0,
+            ));
+
+            if !frame.events.is_empty() {
+                let mut args = String::new();
+                for (i, (entity, count)) in frame.events.iter().enumerate() {
+                    if i > 0 {
+                        args.push(',');
+                    }
+                    let _ = write!(args, r#""{entity}":{count}"#);
+                }
+
+                events.push(format!(
+                    r#"{{"name":"node events","ph":"C","ts":{end:.1},"pid":1,"args":{{{args}}}}}"#,
+                ));
+            }
+
+            for (sample, sampler) in &frame.assignments {
+                events.push(format!(
+                    r#"{{"name":"assign","ph":"i","s":"p","ts":{end:.1},"pid":1,"tid":1,"args":{{"sample":"{sample}","sampler":"{sampler}"}}}}"#,
+                ));
+            }
+
+            for stream in &frame.stream {
+                let args = match stream {
+                    StreamTraceEvent::Started { sample_rate } => {
+                        format!(r#""event":"started","sample_rate":{sample_rate}"#)
+                    }
+                    StreamTraceEvent::Restarting => r#""event":"restarting""#.to_string(),
+                    StreamTraceEvent::Restarted {
+                        previous_rate,
+                        current_rate,
+                    } => format!(
+                        r#""event":"restarted","previous_rate":{previous_rate},"current_rate":{current_rate}"#
+                    ),
+                };
+
+                events.push(format!(
+                    r#"{{"name":"stream","ph":"i","s":"g","ts":{end:.1},"pid":1,"tid":1,"args":{{{args}}}}}"#,
+                ));
+            }
+        }
+
+        format!(r#"{{"traceEvents":[{}]}}"#, events.join(","))
+    }
+}
+
+fn record_events(mut trace: ResMut<AudioTrace>, nodes: Query<(Entity, &AudioEvents)>) {
+    let trace = trace.bypass_change_detection();
+    trace.events.clear();
+    trace.events.extend(
+        nodes
+            .iter()
+            .map(|(entity, events)| (entity, events.queued().len()))
+            .filter(|(_, count)| *count > 0),
+    );
+}
+
+fn record_frame(mut trace: ResMut<AudioTrace>, audio: Res<Time<Audio>>, real: Res<Time<Real>>) {
+    let trace = trace.bypass_change_detection();
+
+    let frame = TraceFrame {
+        index: trace.next_index,
+        wall_time: real.elapsed(),
+        audio_time: audio.now(),
+        render_range: audio.render_range(),
+        events: core::mem::take(&mut trace.events),
+        assignments: core::mem::take(&mut trace.assignments),
+        stream: core::mem::take(&mut trace.stream),
+    };
+
+    trace.next_index += 1;
+    trace.push(frame);
+}
+
+fn trace_assignment(
+    trigger: On<Insert, SamplerOf>,
+    samplers: Query<&SamplerOf>,
+    trace: Option<ResMut<AudioTrace>>,
+) {
+    let (Some(mut trace), Ok(sample)) = (trace, samplers.get(trigger.event_target())) else {
+        return;
+    };
+
+    trace
+        .bypass_change_detection()
+        .assignments
+        .push((sample.0, trigger.event_target()));
+}
+
+fn trace_stream_start(trigger: On<StreamStartEvent>, trace: Option<ResMut<AudioTrace>>) {
+    if let Some(mut trace) = trace {
+        trace.stream.push(StreamTraceEvent::Started {
+            sample_rate: trigger.event().sample_rate.get(),
+        });
+    }
+}
+
+fn trace_pre_restart(_: On<PreStreamRestartEvent>, trace: Option<ResMut<AudioTrace>>) {
+    if let Some(mut trace) = trace {
+        trace.stream.push(StreamTraceEvent::Restarting);
+    }
+}
+
+fn trace_restart(trigger: On<StreamRestartEvent>, trace: Option<ResMut<AudioTrace>>) {
+    if let Some(mut trace) = trace {
+        trace.stream.push(StreamTraceEvent::Restarted {
+            previous_rate: trigger.event().previous_rate.get(),
+            current_rate: trigger.event().current_rate.get(),
+        });
+    }
+}
+
+pub(crate) struct TracePlugin;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Last,
+            (
+                record_events
+                    .after(SeedlingSystems::PreFlush)
+                    .before(SeedlingSystems::Flush),
+                record_frame.after(SeedlingSystems::Flush),
+            )
+                .run_if(resource_exists::<AudioTrace>),
+        )
+        .add_observer(trace_assignment)
+        .add_observer(trace_stream_start)
+        .add_observer(trace_pre_restart)
+        .add_observer(trace_restart);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{prelude::*, test::prepare_app};
+
+    #[test]
+    fn test_trace_ring() {
+        let mut app = prepare_app(|mut commands: Commands| {
+            commands.insert_resource(AudioTrace::new(4));
+            commands.spawn(VolumeNode::default());
+        });
+
+        for _ in 0..6 {
+            app.update();
+        }
+
+        let trace = app.world().resource::<AudioTrace>();
+        let indices: Vec<_> = trace.frames().map(|f| f.index).collect();
+        assert_eq!(indices.len(), 4);
+        assert!(indices.windows(2).all(|w| w[1] == w[0] + 1));
+
+        let json = trace.to_chrome_json();
+        assert!(json.starts_with(r#"{"traceEvents":[{"name":"frame"#));
+        assert!(json.ends_with("]}"));
+        assert_eq!(json.matches(r#""ph":"X""#).count(), 4);
+    }
+}
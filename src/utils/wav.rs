@@ -0,0 +1,45 @@
+//! Minimal 32-bit float WAV encoding.
+
+use std::io::{self, Write};
+
+/// The size of the header written by [`write_header`].
+pub(crate) const HEADER_LEN: u64 = 44;
+
+/// Write a 32-bit float WAV header for `samples` interleaved samples.
+pub(crate) fn write_header(
+    writer: &mut impl Write,
+    sample_rate: u32,
+    channels: u16,
+    samples: usize,
+) -> io::Result<()> {
+    let channels = channels.max(1);
+    let block_align = channels * 4;
+    let byte_rate = sample_rate * block_align as u32;
+    let data_len = (samples * 4) as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    // IEEE float
+    writer.write_all(&3u16.to_le_bytes())?;
+    writer.write_all(&channels.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&32u16.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())
+}
+
+/// Write interleaved samples following a header.
+pub(crate) fn write_samples(writer: &mut impl Write, samples: &[f32]) -> io::Result<()> {
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}